@@ -0,0 +1,27 @@
+import assert from 'node:assert/strict'
+import { createRequire } from 'node:module'
+import test from 'node:test'
+
+const rtk = createRequire(import.meta.url)('../index.js')
+
+test('summarize test output', () => {
+  const output = 'test a ... ok\ntest b ... FAILED\ntest result: FAILED. 1 passed; 1 failed\n'
+  const s = rtk.summarize(output, { kind: 'test' })
+  assert.match(s.text, /test b \.\.\. FAILED/)
+  assert.ok(s.inputTokens > 0)
+})
+
+test('maxTokens caps the result', async () => {
+  const log = Array.from({ length: 2000 }, (_, i) => `INFO request ${i} served`).join('\n')
+  const s = await rtk.summarizeAsync(log, { maxTokens: 50 })
+  assert.ok(s.outputTokens <= 50)
+})
+
+test('unknown kind throws', () => {
+  assert.throws(() => rtk.summarize('{}', { kind: 'xml' }), /Unknown kind/)
+})
+
+test('estimateTokens', () => {
+  assert.equal(rtk.estimateTokens('abcd'), 1)
+  assert.equal(rtk.estimateTokens('one two three', 'words'), 4)
+})
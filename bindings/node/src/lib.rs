@@ -0,0 +1,141 @@
+//! Node.js bindings (napi-rs) for the stable rtk compressor API.
+//!
+//! ```js
+//! const rtk = require('@rtk-ai/rtk-node')
+//!
+//! const s = rtk.summarize(output, { kind: 'test', maxTokens: 500 })
+//! console.log(s.text, s.inputTokens, s.outputTokens, s.savingsPct)
+//! await rtk.summarizeAsync(hugeLog, { kind: 'log' }) // off the event loop
+//! rtk.estimateTokens(text, 'words')
+//! ```
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rtk::api::{self, Kind, Tokenizer};
+
+fn invalid_arg(e: anyhow::Error) -> Error {
+    Error::new(Status::InvalidArg, e.to_string())
+}
+
+/// Compacted output with token estimates.
+#[napi(object)]
+pub struct Summary {
+    pub text: String,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    /// Tokens saved, as a percentage of the input
+    pub savings_pct: f64,
+}
+
+impl From<api::Summary> for Summary {
+    fn from(summary: api::Summary) -> Self {
+        Self {
+            savings_pct: summary.savings_pct(),
+            input_tokens: summary.input_tokens as u32,
+            output_tokens: summary.output_tokens as u32,
+            text: summary.text,
+        }
+    }
+}
+
+#[napi(object)]
+pub struct SummarizeOptions {
+    /// json, test, diff, log, errors or generic (default)
+    pub kind: Option<String>,
+    /// Cut the result at a line boundary to fit this many tokens
+    pub max_tokens: Option<u32>,
+}
+
+/// Parsed options: the compressor and the optional token cap.
+fn parse_options(options: Option<SummarizeOptions>) -> Result<(Kind, Option<usize>)> {
+    let options = options.unwrap_or(SummarizeOptions {
+        kind: None,
+        max_tokens: None,
+    });
+    let kind = match options.kind {
+        Some(kind) => kind.parse().map_err(invalid_arg)?,
+        None => Kind::Generic,
+    };
+    Ok((kind, options.max_tokens.map(|max| max as usize)))
+}
+
+fn run_summarize(text: &str, kind: Kind, max_tokens: Option<usize>) -> Result<api::Summary> {
+    let summary = api::summarize(text, kind).map_err(invalid_arg)?;
+    Ok(match max_tokens {
+        Some(max) => summary.truncate(max),
+        None => summary,
+    })
+}
+
+/// Compress `text` with the compressor for `options.kind`.
+#[napi]
+pub fn summarize(text: String, options: Option<SummarizeOptions>) -> Result<Summary> {
+    let (kind, max_tokens) = parse_options(options)?;
+    run_summarize(&text, kind, max_tokens).map(Summary::from)
+}
+
+pub struct SummarizeTask {
+    text: String,
+    kind: Kind,
+    max_tokens: Option<usize>,
+}
+
+impl Task for SummarizeTask {
+    type Output = api::Summary;
+    type JsValue = Summary;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        run_summarize(&self.text, self.kind, self.max_tokens)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output.into())
+    }
+}
+
+/// [`summarize`] on the libuv thread pool, for outputs large enough to
+/// stall the event loop.
+#[napi(ts_return_type = "Promise<Summary>")]
+pub fn summarize_async(
+    text: String,
+    options: Option<SummarizeOptions>,
+) -> Result<AsyncTask<SummarizeTask>> {
+    let (kind, max_tokens) = parse_options(options)?;
+    Ok(AsyncTask::new(SummarizeTask {
+        text,
+        kind,
+        max_tokens,
+    }))
+}
+
+/// Failures and the summary line of a test run; `command` (e.g.
+/// `"cargo test"`, `"pytest"`) selects the framework patterns.
+#[napi]
+pub fn condense_test_output(command: String, output: String) -> Summary {
+    api::condense_test_output(&command, &output).into()
+}
+
+/// A unified diff reduced to per-file hunks, at most `max_lines` lines.
+#[napi]
+pub fn compress_diff(diff: String, max_lines: u32) -> Summary {
+    api::compress_diff(&diff, max_lines as usize).into()
+}
+
+/// The structure of a JSON document, values replaced by their types.
+#[napi]
+pub fn summarize_json(json: String, max_depth: u32) -> Result<Summary> {
+    api::summarize_json(&json, max_depth as usize)
+        .map(Summary::from)
+        .map_err(invalid_arg)
+}
+
+/// Estimated token count of `text`: `chars` (default, ~4 characters per
+/// token) or `words` (~0.75 words per token, prose).
+#[napi]
+pub fn estimate_tokens(text: String, tokenizer: Option<String>) -> Result<u32> {
+    let tokenizer: Tokenizer = match tokenizer {
+        Some(name) => name.parse().map_err(invalid_arg)?,
+        None => Tokenizer::Chars,
+    };
+    Ok(api::estimate_tokens(&text, tokenizer) as u32)
+}
@@ -0,0 +1,19 @@
+from typing import Literal, Optional, Tuple
+
+Kind = Literal["json", "test", "diff", "log", "errors", "generic"]
+
+KINDS: Tuple[str, ...]
+__version__: str
+
+class Summary:
+    @property
+    def text(self) -> str: ...
+    @property
+    def input_tokens(self) -> int: ...
+    @property
+    def output_tokens(self) -> int: ...
+    @property
+    def savings_pct(self) -> float: ...
+
+def summarize(text: str, kind: Kind = "generic", max_tokens: Optional[int] = None) -> Summary: ...
+def estimate_tokens(text: str, tokenizer: Literal["chars", "words"] = "chars") -> int: ...
@@ -0,0 +1,102 @@
+//! Python bindings (`rtk_py`) for the stable rtk compressor API.
+//!
+//! ```python
+//! import rtk_py
+//!
+//! s = rtk_py.summarize(output, kind="test", max_tokens=500)
+//! print(s.text, s.input_tokens, s.output_tokens, s.savings_pct)
+//! rtk_py.estimate_tokens(text, tokenizer="words")
+//! ```
+//!
+//! Compression runs with the GIL released.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rtk::api::{self, Kind, Tokenizer};
+
+fn value_error(e: anyhow::Error) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+/// Compacted output with token estimates.
+#[pyclass(name = "Summary", module = "rtk_py", frozen)]
+struct Summary(api::Summary);
+
+#[pymethods]
+impl Summary {
+    #[getter]
+    fn text(&self) -> &str {
+        &self.0.text
+    }
+
+    #[getter]
+    fn input_tokens(&self) -> usize {
+        self.0.input_tokens
+    }
+
+    #[getter]
+    fn output_tokens(&self) -> usize {
+        self.0.output_tokens
+    }
+
+    /// Tokens saved, as a percentage of the input.
+    #[getter]
+    fn savings_pct(&self) -> f64 {
+        self.0.savings_pct()
+    }
+
+    fn __str__(&self) -> &str {
+        &self.0.text
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Summary(input_tokens={}, output_tokens={}, savings_pct={:.1})",
+            self.0.input_tokens,
+            self.0.output_tokens,
+            self.0.savings_pct()
+        )
+    }
+}
+
+/// Compress `text` with the compressor for `kind` (json, test, diff, log,
+/// errors, generic), cut to `max_tokens` when given.
+#[pyfunction]
+#[pyo3(signature = (text, kind = "generic", max_tokens = None))]
+fn summarize(
+    py: Python<'_>,
+    text: &str,
+    kind: &str,
+    max_tokens: Option<usize>,
+) -> PyResult<Summary> {
+    let kind: Kind = kind.parse().map_err(value_error)?;
+    let summary = py
+        .allow_threads(|| api::summarize(text, kind))
+        .map_err(value_error)?;
+    Ok(Summary(match max_tokens {
+        Some(max) => summary.truncate(max),
+        None => summary,
+    }))
+}
+
+/// Estimated token count of `text`: `chars` (~4 characters per token, code
+/// and logs) or `words` (~0.75 words per token, prose).
+#[pyfunction]
+#[pyo3(signature = (text, tokenizer = "chars"))]
+fn estimate_tokens(text: &str, tokenizer: &str) -> PyResult<usize> {
+    let tokenizer: Tokenizer = tokenizer.parse().map_err(value_error)?;
+    Ok(api::estimate_tokens(text, tokenizer))
+}
+
+#[pymodule]
+fn rtk_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Summary>()?;
+    m.add_function(wrap_pyfunction!(summarize, m)?)?;
+    m.add_function(wrap_pyfunction!(estimate_tokens, m)?)?;
+    m.add(
+        "KINDS",
+        ("json", "test", "diff", "log", "errors", "generic"),
+    )?;
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    Ok(())
+}
@@ -0,0 +1,26 @@
+import pytest
+
+import rtk_py
+
+
+def test_summarize_test_output():
+    output = "test a ... ok\ntest b ... FAILED\ntest result: FAILED. 1 passed; 1 failed\n"
+    summary = rtk_py.summarize(output, kind="test")
+    assert "test b ... FAILED" in summary.text
+    assert summary.input_tokens > 0
+
+
+def test_summarize_max_tokens():
+    log = "\n".join(f"INFO request {i} served" for i in range(2000))
+    summary = rtk_py.summarize(log, max_tokens=50)
+    assert summary.output_tokens <= 50
+
+
+def test_unknown_kind():
+    with pytest.raises(ValueError):
+        rtk_py.summarize("{}", kind="xml")
+
+
+def test_estimate_tokens():
+    assert rtk_py.estimate_tokens("abcd") == 1
+    assert rtk_py.estimate_tokens("one two three", tokenizer="words") == 4
@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rtk::parser::lossy::{self, Summarizer};
+
+// Test runners share one target: their outputs look alike
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+    for summarizer in [Summarizer::CargoTest, Summarizer::Pytest, Summarizer::GoTest] {
+        let _ = lossy::summarize(summarizer, &input);
+    }
+});
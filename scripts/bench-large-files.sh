@@ -0,0 +1,85 @@
+#!/bin/bash
+# Large file gate: rtk cat/grep on a generated file must stay within a time
+# budget of the plain tool. Budgets are rtk's extra time per 100 MB of input,
+# best of RUNS, with the cache off so every run does the full read.
+#
+#   ./scripts/bench-large-files.sh                 # 200 MB file
+#   SIZE_MB=500 CAT_BUDGET_MS=600 ./scripts/bench-large-files.sh
+set -e
+
+RTK="${RTK:-$(cd "$(dirname ./target/release/rtk)" && pwd)/rtk}"
+SIZE_MB="${SIZE_MB:-200}"
+RUNS="${RUNS:-3}"
+CAT_BUDGET_MS="${CAT_BUDGET_MS:-800}"
+GREP_BUDGET_MS="${GREP_BUDGET_MS:-200}"
+
+WORK="$(mktemp -d)"
+trap 'rm -rf "$WORK"' EXIT
+FILE="$WORK/large.log"
+
+export RTK_DB_PATH="$WORK/history.db"
+export RTK_SPILL_DIR="$WORK/spill"
+export RTK_ELIDED_DIR="$WORK/elided"
+export RTK_CACHE__ENABLED=false
+
+# ~60 byte lines; one in a hundred is an error for grep to find
+awk -v bytes=$((SIZE_MB * 1024 * 1024)) 'BEGIN {
+  split("alpha beta gamma delta buffer index thread value result", w, " ")
+  while (size < bytes) {
+    if (n % 100 == 0) line = sprintf("ERROR request %d failed: %s %s", n, w[n % 9 + 1], w[n % 7 + 1])
+    else line = sprintf("INFO %s %s %s %s handled in %dms", w[n % 9 + 1], w[n % 5 + 1], w[n % 7 + 1], w[n % 3 + 1], n % 997)
+    print line
+    size += length(line) + 1
+    n++
+  }
+}' > "$FILE"
+MB=$(( $(wc -c < "$FILE") / 1024 / 1024 ))
+
+if command -v rg >/dev/null; then
+  GREP="rg -n --no-heading"
+else
+  GREP="grep -rn"
+fi
+
+# Best wall time of RUNS, in milliseconds
+best_ms() {
+  local best=""
+  for _ in $(seq "$RUNS"); do
+    local start end ms
+    start=$(date +%s%N)
+    # Not /dev/null: GNU grep stops at the first match when writing there
+    eval "$1" > "$WORK/out" 2>&1 || true
+    end=$(date +%s%N)
+    ms=$(( (end - start) / 1000000 ))
+    if [ -z "$best" ] || [ "$ms" -lt "$best" ]; then best=$ms; fi
+  done
+  echo "$best"
+}
+
+FAILED=0
+
+# Prints a line per case; fails the gate when rtk's extra time is over budget
+gate() {
+  local name="$1" base_cmd="$2" rtk_cmd="$3" budget_per_100="$4"
+  local base rtk extra budget verdict
+  base=$(best_ms "$base_cmd")
+  rtk=$(best_ms "$rtk_cmd")
+  extra=$(( rtk > base ? rtk - base : 0 ))
+  budget=$(( budget_per_100 * MB / 100 ))
+  verdict="✅"
+  if [ "$extra" -gt "$budget" ]; then
+    verdict="❌"
+    FAILED=1
+  fi
+  printf "%s %-5s │ %-22s %6dms │ rtk %-5s %6dms │ +%dms (budget %dms)\n" \
+    "$verdict" "$name" "${base_cmd%% *}" "$base" "$name" "$rtk" "$extra" "$budget"
+}
+
+echo "RTK large file benchmark (${MB} MB, best of $RUNS)"
+gate "cat" "cat $FILE" "$RTK cat -l none --max-lines 50 $FILE" "$CAT_BUDGET_MS"
+gate "grep" "$GREP ERROR $FILE" "$RTK grep ERROR $FILE" "$GREP_BUDGET_MS"
+
+if [ "$FAILED" -ne 0 ]; then
+  echo "LARGE FILE BENCHMARK FAILED: rtk over its time budget"
+  exit 1
+fi
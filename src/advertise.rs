@@ -0,0 +1,370 @@
+//! `rtk advertise` — a CLAUDE.md / AGENTS.md section listing the rtk
+//! commands that matter for the current project.
+//!
+//! The project's languages and tools are detected from marker files
+//! (`Cargo.toml`, `package.json` dependencies, `pyproject.toml`, `go.mod`,
+//! ...), and only the matching commands are listed, each with one line on
+//! when to use it. The section sits between the same markers as
+//! `rtk init --claude-md`, so `--write` replaces it in place.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const START: &str = "<!-- rtk-instructions project -->";
+const END: &str = "<!-- /rtk-instructions -->";
+
+/// Instruction files updated by `--write`, in order of preference.
+const TARGETS: &[&str] = &["CLAUDE.md", "AGENTS.md"];
+
+/// What the project uses, from its marker files.
+#[derive(Debug, Default, PartialEq)]
+struct Project {
+    git: bool,
+    github: bool,
+    rust: bool,
+    /// Package manager (`pnpm` or `npm`) when there is a `package.json`
+    node: Option<&'static str>,
+    /// `package.json` dependencies rtk has a filter for
+    node_tools: Vec<&'static str>,
+    python: bool,
+    python_tools: Vec<&'static str>,
+    go: bool,
+    golangci: bool,
+    docker: bool,
+    kubernetes: bool,
+}
+
+/// JS tools with a dedicated rtk command, by the dependency that reveals them.
+const NODE_TOOLS: &[(&str, &str)] = &[
+    ("typescript", "tsc"),
+    ("eslint", "lint"),
+    ("@biomejs/biome", "lint"),
+    ("prettier", "prettier"),
+    ("vitest", "vitest"),
+    ("@playwright/test", "playwright"),
+    ("next", "next"),
+    ("prisma", "prisma"),
+];
+
+const PYTHON_TOOLS: &[&str] = &["pytest", "ruff"];
+
+fn detect(root: &Path) -> Project {
+    let exists = |file: &str| root.join(file).exists();
+    let read = |file: &str| fs::read_to_string(root.join(file)).unwrap_or_default();
+
+    let mut project = Project {
+        git: exists(".git"),
+        github: exists(".github"),
+        rust: exists("Cargo.toml"),
+        go: exists("go.mod"),
+        golangci: [".golangci.yml", ".golangci.yaml", ".golangci.toml"]
+            .iter()
+            .any(|file| exists(file)),
+        docker: [
+            "Dockerfile",
+            "docker-compose.yml",
+            "compose.yaml",
+            "compose.yml",
+        ]
+        .iter()
+        .any(|file| exists(file)),
+        kubernetes: ["k8s", "kustomization.yaml", "Chart.yaml", "helm"]
+            .iter()
+            .any(|file| exists(file)),
+        ..Project::default()
+    };
+
+    if exists("package.json") {
+        project.node = Some(if exists("pnpm-lock.yaml") {
+            "pnpm"
+        } else {
+            "npm"
+        });
+        let manifest: serde_json::Value =
+            serde_json::from_str(&read("package.json")).unwrap_or_default();
+        for (dependency, tool) in NODE_TOOLS {
+            let declared = ["dependencies", "devDependencies"]
+                .iter()
+                .any(|key| manifest[key].get(dependency).is_some());
+            if declared && !project.node_tools.contains(tool) {
+                project.node_tools.push(tool);
+            }
+        }
+    }
+
+    let python_files = [
+        "pyproject.toml",
+        "requirements.txt",
+        "setup.py",
+        "setup.cfg",
+    ];
+    if python_files.iter().any(|file| exists(file)) {
+        project.python = true;
+        let manifests: String = python_files.iter().map(|file| read(file)).collect();
+        project.python_tools = PYTHON_TOOLS
+            .iter()
+            .copied()
+            .filter(|tool| manifests.contains(tool))
+            .collect();
+    }
+    project
+}
+
+/// The section for `project`, markers included.
+fn render(project: &Project) -> String {
+    let mut sections: Vec<(String, Vec<(String, &str)>)> = Vec::new();
+    let mut add = |title: &str, lines: Vec<(String, &'static str)>| {
+        if !lines.is_empty() {
+            sections.push((title.to_string(), lines));
+        }
+    };
+    let cmd = |s: &str| s.to_string();
+
+    if project.rust {
+        add(
+            "Rust",
+            vec![
+                (cmd("rtk cargo build"), "errors and warnings only"),
+                (cmd("rtk cargo check"), "errors and warnings only"),
+                (cmd("rtk cargo clippy"), "lints grouped by file"),
+                (cmd("rtk cargo test"), "failures only, plus the summary"),
+            ],
+        );
+    }
+    if let Some(pm) = project.node {
+        let mut lines = vec![(
+            format!("rtk {} install", pm),
+            "install without progress output",
+        )];
+        if pm == "pnpm" {
+            lines.push((cmd("rtk pnpm list"), "compact dependency tree"));
+            lines.push((cmd("rtk pnpm outdated"), "outdated packages only"));
+        } else {
+            lines.push((
+                cmd("rtk npm run <script>"),
+                "any package.json script, compacted",
+            ));
+        }
+        for tool in &project.node_tools {
+            lines.push(match *tool {
+                "tsc" => (cmd("rtk tsc"), "type errors grouped by file and code"),
+                "lint" => (cmd("rtk lint"), "ESLint / Biome violations grouped by rule"),
+                "prettier" => (
+                    cmd("rtk prettier --check ."),
+                    "only the files needing format",
+                ),
+                "vitest" => (cmd("rtk vitest run"), "failures only"),
+                "playwright" => (cmd("rtk playwright test"), "failures only"),
+                "next" => (cmd("rtk next build"), "build errors and route sizes"),
+                _ => (cmd("rtk prisma"), "prisma without the banners"),
+            });
+        }
+        lines.push((cmd("rtk npx <cmd>"), "other package binaries"));
+        add("JavaScript / TypeScript", lines);
+    }
+    if project.python {
+        let mut lines = Vec::new();
+        if project.python_tools.contains(&"pytest") {
+            lines.push((cmd("rtk pytest"), "failures only, plus the summary"));
+        }
+        if project.python_tools.contains(&"ruff") {
+            lines.push((cmd("rtk ruff check"), "violations grouped by rule"));
+        }
+        lines.push((
+            cmd("rtk pip list"),
+            "compact package list (uses uv when present)",
+        ));
+        add("Python", lines);
+    }
+    if project.go {
+        let mut lines = vec![
+            (cmd("rtk go test ./..."), "failures only"),
+            (cmd("rtk go build ./..."), "errors only"),
+            (cmd("rtk go vet ./..."), "findings grouped by file"),
+        ];
+        if project.golangci {
+            lines.push((cmd("rtk golangci-lint run"), "issues grouped by linter"));
+        }
+        add("Go", lines);
+    }
+    let mut infra = Vec::new();
+    if project.docker {
+        infra.push((cmd("rtk docker ps"), "compact container list"));
+        infra.push((cmd("rtk docker logs <container>"), "deduplicated logs"));
+    }
+    if project.kubernetes {
+        infra.push((cmd("rtk kubectl pods"), "compact pod list"));
+        infra.push((cmd("rtk kubectl logs <pod>"), "deduplicated logs"));
+    }
+    add("Containers", infra);
+    if project.git {
+        let mut lines = vec![
+            (cmd("rtk git status"), "compact status"),
+            (cmd("rtk git diff"), "compact diff"),
+            (cmd("rtk git log"), "one line per commit"),
+            (
+                cmd("rtk git commit"),
+                "one-line confirmation, likewise add / push / pull",
+            ),
+        ];
+        if project.github {
+            lines.push((cmd("rtk gh pr view <n>"), "PR without the boilerplate"));
+            lines.push((cmd("rtk gh pr checks"), "failing checks first"));
+        }
+        add("Git", lines);
+    }
+    add(
+        "Files & search",
+        vec![
+            (cmd("rtk read <file>"), "file contents with noise stripped"),
+            (cmd("rtk grep <pattern>"), "matches grouped by file"),
+            (cmd("rtk find <pattern>"), "paths grouped by directory"),
+            (cmd("rtk ls <path>"), "compact tree"),
+        ],
+    );
+    add(
+        "Anything else",
+        vec![
+            (cmd("rtk test <cmd>"), "any test command, failures only"),
+            (
+                cmd("rtk err <cmd>"),
+                "any command, errors and warnings only",
+            ),
+            (
+                cmd("rtk proxy <cmd>"),
+                "run unfiltered when the full output is needed",
+            ),
+        ],
+    );
+
+    let mut out = format!("{}\n## rtk\n\n", START);
+    out.push_str(
+        "Prefix shell commands with `rtk`, also inside `&&` chains: output is compacted \
+         for you and commands without a filter run unchanged.\n",
+    );
+    for (title, lines) in sections {
+        out.push_str(&format!("\n### {}\n", title));
+        for (command, hint) in lines {
+            out.push_str(&format!("- `{}` — {}\n", command, hint));
+        }
+    }
+    out.push_str(END);
+    out.push('\n');
+    out
+}
+
+/// `content` with its rtk section replaced by `section` where it stands, or
+/// appended when there is none.
+fn upsert(content: &str, section: &str) -> String {
+    let start = content.find("<!-- rtk-instructions");
+    let end = start.and_then(|start| content[start..].find(END).map(|end| start + end));
+    match (start, end) {
+        (Some(start), Some(end)) => {
+            let after = content[end + END.len()..].trim_start_matches(['\r', '\n']);
+            let separator = if after.is_empty() { "" } else { "\n" };
+            format!("{}{}{}{}", &content[..start], section, separator, after)
+        }
+        _ if content.trim().is_empty() => section.to_string(),
+        _ => format!("{}\n\n{}", content.trim_end(), section),
+    }
+}
+
+/// The files `--write` updates: the given one, else every existing
+/// CLAUDE.md / AGENTS.md, else a new CLAUDE.md.
+fn targets(root: &Path, file: Option<PathBuf>) -> Vec<PathBuf> {
+    if let Some(file) = file {
+        return vec![file];
+    }
+    let existing: Vec<PathBuf> = TARGETS
+        .iter()
+        .map(|name| root.join(name))
+        .filter(|path| path.is_file())
+        .collect();
+    if existing.is_empty() {
+        vec![root.join(TARGETS[0])]
+    } else {
+        existing
+    }
+}
+
+pub fn run(write: bool, file: Option<PathBuf>, verbose: u8) -> Result<()> {
+    let root = std::env::current_dir().context("Failed to read current directory")?;
+    let project = detect(&root);
+    if verbose > 0 {
+        eprintln!("Detected: {:?}", project);
+    }
+    let section = render(&project);
+    if !write {
+        print!("{}", section);
+        return Ok(());
+    }
+    for path in targets(&root, file) {
+        let existing = fs::read_to_string(&path).unwrap_or_default();
+        let updated = upsert(&existing, &section);
+        let name = path.strip_prefix(&root).unwrap_or(&path).display();
+        if updated == existing {
+            println!("{}: up to date", name);
+            continue;
+        }
+        fs::write(&path, &updated).with_context(|| format!("Failed to write {}", name))?;
+        println!("✅ {}", name);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_node_and_python_tools() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"devDependencies":{"vitest":"^1","typescript":"^5","eslint":"^9"}}"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("pnpm-lock.yaml"), "").unwrap();
+        fs::write(dir.path().join("requirements.txt"), "pytest==8\n").unwrap();
+        let project = detect(dir.path());
+        assert_eq!(project.node, Some("pnpm"));
+        assert_eq!(project.node_tools, vec!["tsc", "lint", "vitest"]);
+        assert_eq!(project.python_tools, vec!["pytest"]);
+        assert!(!project.rust);
+    }
+
+    #[test]
+    fn test_render_lists_only_detected() {
+        let section = render(&Project {
+            rust: true,
+            git: true,
+            ..Project::default()
+        });
+        assert!(section.starts_with(START));
+        assert!(section.ends_with("<!-- /rtk-instructions -->\n"));
+        assert!(section.contains("`rtk cargo test`"));
+        assert!(section.contains("`rtk git status`"));
+        assert!(!section.contains("pytest"));
+        assert!(!section.contains("gh pr"));
+    }
+
+    #[test]
+    fn test_upsert_replaces_in_place() {
+        let section = render(&Project::default());
+        let doc = "# Project\n\nNotes.\n";
+        let once = upsert(doc, &section);
+        assert!(once.starts_with("# Project\n\nNotes.\n\n<!-- rtk-instructions project"));
+        assert_eq!(upsert(&once, &section), once);
+
+        let rust = render(&Project {
+            rust: true,
+            ..Project::default()
+        });
+        let with_footer = format!("{}\n## Footer\n", once);
+        let updated = upsert(&with_footer, &rust);
+        assert_eq!(updated.matches("<!-- rtk-instructions").count(), 1);
+        assert!(updated.contains("rtk cargo test"));
+        assert!(updated.ends_with("<!-- /rtk-instructions -->\n\n## Footer\n"));
+    }
+}
@@ -0,0 +1,156 @@
+//! `alias.<name>` in config: shorthands for commands with their usual flags.
+//!
+//! `alias.pt = "pytest -x -q"` makes `rtk pt tests/` run as
+//! `rtk pytest -x -q tests/`, through the same summarizer (or plugin) as the
+//! expanded command. Tracking records the run as `rtk pt tests/`: the alias
+//! is what was asked for, so `rtk gain` groups by it. An alias may expand to
+//! another alias; builtin commands can't be shadowed.
+
+use crate::config::Config;
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::sync::OnceLock;
+
+/// Expansions followed before giving up on an alias loop.
+const MAX_DEPTH: usize = 16;
+
+/// `rtk <alias> args...` as typed, once an alias was expanded
+static LABEL: OnceLock<String> = OnceLock::new();
+
+/// `args` (the whole command line) with the alias at `args[at]` expanded,
+/// or `None` when `args[at]` names no alias.
+pub fn expand(
+    args: &[OsString],
+    at: usize,
+    is_builtin: impl Fn(&str) -> bool,
+) -> Result<Option<Vec<OsString>>> {
+    let expanded = expand_with(&Config::cached().alias, args, at, is_builtin)?;
+    if expanded.is_some() {
+        let typed: Vec<_> = args[at..].iter().map(|a| a.to_string_lossy()).collect();
+        let _ = LABEL.set(format!("rtk {}", typed.join(" ")));
+        tracing::debug!(?expanded, "alias");
+    }
+    Ok(expanded)
+}
+
+/// The tracking label of an aliased run (`rtk pt tests/`).
+pub fn label() -> Option<&'static str> {
+    LABEL.get().map(String::as_str)
+}
+
+fn expand_with(
+    aliases: &BTreeMap<String, String>,
+    args: &[OsString],
+    at: usize,
+    is_builtin: impl Fn(&str) -> bool,
+) -> Result<Option<Vec<OsString>>> {
+    let mut args = args.to_vec();
+    let mut seen = Vec::new();
+    while let Some(name) = args.get(at).and_then(|a| a.to_str()) {
+        let Some(expansion) = aliases.get(name).filter(|_| !is_builtin(name)) else {
+            break;
+        };
+        if seen.iter().any(|s| s == name) || seen.len() == MAX_DEPTH {
+            bail!("alias loop: {} -> {}", seen.join(" -> "), name);
+        }
+        let words = words(expansion)?;
+        if words.is_empty() {
+            bail!("alias.{} is empty", name);
+        }
+        seen.push(name.to_string());
+        args.splice(at..=at, words.into_iter().map(OsString::from));
+    }
+    Ok((!seen.is_empty()).then_some(args))
+}
+
+/// Split an alias on whitespace, honouring '…', "…" and backslash escapes.
+fn words(text: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"') | None, '\\') => {
+                if let Some(next) = chars.next() {
+                    word.get_or_insert_with(String::new).push(next);
+                }
+            }
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        bail!("unterminated quote in alias: {}", text);
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// `rtk alias`: every alias with its expansion.
+pub fn list(is_builtin: impl Fn(&str) -> bool) -> Result<()> {
+    let aliases = &Config::cached().alias;
+    if aliases.is_empty() {
+        println!("No aliases. Add one with: rtk config set alias.pt \"pytest -x -q\"");
+        return Ok(());
+    }
+    let width = aliases.keys().map(|name| name.len()).max().unwrap_or(0);
+    for (name, expansion) in aliases {
+        let note = if is_builtin(name) {
+            "  (ignored: builtin command)"
+        } else {
+            ""
+        };
+        println!("{:<width$}  rtk {}{}", name, expansion, note, width = width);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<OsString> {
+        line.split(' ').map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_expand_with() {
+        let aliases: BTreeMap<String, String> = [
+            ("pt", "pytest -x -q"),
+            ("t", "pt --lf"),
+            ("git", "status"),
+            ("a", "b"),
+            ("b", "a"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let builtin = |name: &str| name == "git";
+
+        let expanded = expand_with(&aliases, &args("rtk -v t tests/"), 2, builtin).unwrap();
+        assert_eq!(expanded, Some(args("rtk -v pytest -x -q --lf tests/")));
+        assert_eq!(
+            expand_with(&aliases, &args("rtk git log"), 1, builtin).unwrap(),
+            None
+        );
+        assert!(expand_with(&aliases, &args("rtk a"), 1, builtin).is_err());
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(
+            words(r#"  grep -k "slow and flaky" 'a b'\ c x\"y "#).unwrap(),
+            ["grep", "-k", "slow and flaky", "a b c", "x\"y"]
+        );
+        assert_eq!(words("run ''").unwrap(), ["run", ""]);
+        assert!(words("say 'hi").is_err());
+    }
+}
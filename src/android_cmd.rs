@@ -0,0 +1,862 @@
+//! `rtk adb` and `rtk gradle`: Android device logs and builds.
+//!
+//! - `adb logcat [--app <package>]`: dumps the log (`-d`), keeps the app's
+//!   processes only, hides verbose/debug lines, collapses repeats and pulls
+//!   Java crashes, native tombstones and ANRs to the top with folded frames.
+//! - `gradle <tasks>` (via `./gradlew` when present): drops configuration,
+//!   daemon, download and up-to-date task noise; keeps compiler errors,
+//!   warnings deduplicated by file, failing tests and the build result.
+//! - Other adb subcommands run unchanged.
+
+use crate::exit_code;
+use crate::tracking;
+use crate::utils::{strip_ansi, truncate};
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::process::Command;
+
+/// Log lines shown after crashes, most recent last.
+const MAX_LOG_LINES: usize = 60;
+/// App frames shown per crash.
+const MAX_FRAMES: usize = 8;
+/// Warnings listed per file by the gradle filter.
+const MAX_WARNINGS_PER_FILE: usize = 3;
+
+lazy_static! {
+    // threadtime: `01-15 10:00:01.123  1234  1250 E Tag     : message`
+    static ref THREADTIME: Regex = Regex::new(
+        r"^\d\d-\d\d\s+(\d\d:\d\d:\d\d\.\d+)\s+(\d+)\s+\d+\s+([VDIWEFA])\s+(.*?)\s*:\s?(.*)$"
+    )
+    .unwrap();
+    // brief: `E/Tag( 1234): message`
+    static ref BRIEF: Regex = Regex::new(r"^([VDIWEFA])/(.*?)\(\s*(\d+)\):\s?(.*)$").unwrap();
+    static ref START_PROC: Regex =
+        Regex::new(r"Start proc (\d+):([\w.]+)|Process: ([\w.]+), PID: (\d+)").unwrap();
+    static ref TOMBSTONE_PID: Regex = Regex::new(r"pid: (\d+), tid: \d+.*>>> ([\w.:]+) <<<").unwrap();
+    static ref NATIVE_FRAME: Regex =
+        Regex::new(r"#(\d+) pc [0-9a-f]+\s+(\S+)(?:\s+\((.*?)\))?").unwrap();
+    static ref DIGITS: Regex = Regex::new(r"\d+").unwrap();
+    // Kotlin `e: file:///src/A.kt:12:5 msg`, javac `/src/A.java:12: error: msg`
+    static ref KOTLIN_DIAG: Regex =
+        Regex::new(r"^([ew]): (?:file://)?(\S+?):(\d+)(?::\d+)? (.*)$").unwrap();
+    static ref JAVAC_DIAG: Regex =
+        Regex::new(r"^(\S+\.java):(\d+): (error|warning): (.*)$").unwrap();
+    static ref FAILED_TEST: Regex = Regex::new(r"^(\S.*) > (.+) FAILED$").unwrap();
+    static ref AGPBI: Regex = Regex::new(r"^AGPBI: (\{.*\})$").unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    time: String,
+    pid: u32,
+    level: char,
+    tag: String,
+    message: String,
+}
+
+fn parse_logcat(output: &str) -> Vec<Entry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            if let Some(c) = THREADTIME.captures(line) {
+                return Some(Entry {
+                    time: c[1].to_string(),
+                    pid: c[2].parse().ok()?,
+                    level: c[3].chars().next()?,
+                    tag: c[4].trim().to_string(),
+                    message: c[5].to_string(),
+                });
+            }
+            let c = BRIEF.captures(line)?;
+            Some(Entry {
+                time: String::new(),
+                pid: c[3].parse().ok()?,
+                level: c[1].chars().next()?,
+                tag: c[2].trim().to_string(),
+                message: c[4].to_string(),
+            })
+        })
+        .collect()
+}
+
+/// PIDs the package ran under, from ActivityManager start lines and crash
+/// headers (the process may be gone by the time the log is read).
+fn package_pids(entries: &[Entry], package: &str) -> BTreeSet<u32> {
+    let mut pids = BTreeSet::new();
+    for entry in entries {
+        for c in START_PROC.captures_iter(&entry.message) {
+            let (pid, name) = match (c.get(1), c.get(2)) {
+                (Some(pid), Some(name)) => (pid, name),
+                _ => match (c.get(4), c.get(3)) {
+                    (Some(pid), Some(name)) => (pid, name),
+                    _ => continue,
+                },
+            };
+            // `com.example:remote` is the same app
+            if name.as_str().split(':').next() == Some(package) {
+                pids.extend(pid.as_str().parse::<u32>().ok());
+            }
+        }
+        if let Some(c) = TOMBSTONE_PID.captures(&entry.message) {
+            if c[2].split(':').next() == Some(package) {
+                pids.extend(c[1].parse::<u32>().ok());
+            }
+        }
+    }
+    pids
+}
+
+/// Framework and runtime frames folded out of Java stack traces.
+fn is_framework_frame(frame: &str) -> bool {
+    const PREFIXES: &[&str] = &[
+        "android.",
+        "androidx.",
+        "com.android.",
+        "java.",
+        "javax.",
+        "kotlin.",
+        "kotlinx.",
+        "dalvik.",
+        "sun.",
+        "libcore.",
+        "com.google.android.",
+    ];
+    let frame = frame.trim_start_matches("at ");
+    PREFIXES.iter().any(|p| frame.starts_with(p))
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Crash {
+    /// `FATAL EXCEPTION: main`, `signal 11 (SIGSEGV) ...`, `ANR in ...`
+    title: String,
+    /// Exception / abort message / ANR reason lines
+    details: Vec<String>,
+    /// Frames with framework runs folded
+    frames: Vec<String>,
+    count: usize,
+}
+
+/// Collapse runs of framework frames into `... N framework frames`.
+fn fold_java_frames(frames: &[String]) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut folded = 0;
+    let mut shown = 0;
+    for (i, frame) in frames.iter().enumerate() {
+        // The throwing frame is kept even when it is framework code
+        if i > 0 && (is_framework_frame(frame) || shown >= MAX_FRAMES) {
+            folded += 1;
+            continue;
+        }
+        if folded > 0 {
+            out.push(format!("... {} framework frames", folded));
+            folded = 0;
+        }
+        out.push(frame.clone());
+        shown += 1;
+    }
+    if folded > 0 {
+        out.push(format!("... {} framework frames", folded));
+    }
+    out
+}
+
+/// Java crashes (`AndroidRuntime`), native tombstones (`DEBUG`) and ANRs
+/// (`ActivityManager`), identical ones counted once. Returns the crashes and
+/// the indexes of the entries they consumed.
+fn extract_crashes(entries: &[Entry], package: Option<&str>) -> (Vec<Crash>, BTreeSet<usize>) {
+    let mut crashes: Vec<Crash> = Vec::new();
+    let mut used = BTreeSet::new();
+    let mut i = 0;
+    while i < entries.len() {
+        let entry = &entries[i];
+        let block = |tag: &str, pid: u32, from: usize| -> Vec<usize> {
+            (from..entries.len())
+                .take_while(|&j| entries[j].tag == tag && entries[j].pid == pid)
+                .collect()
+        };
+        let crash = if entry.tag == "AndroidRuntime" && entry.message.starts_with("FATAL EXCEPTION")
+        {
+            let lines = block("AndroidRuntime", entry.pid, i);
+            let mut crash = Crash {
+                title: entry.message.trim().to_string(),
+                ..Default::default()
+            };
+            let mut frames = Vec::new();
+            for &j in &lines[1..] {
+                let message = entries[j].message.trim();
+                if message.starts_with("at ") {
+                    frames.push(message.to_string());
+                } else if message.starts_with("Process:") {
+                    continue;
+                } else if message.starts_with("Caused by:") {
+                    crash.frames.extend(fold_java_frames(&frames));
+                    frames.clear();
+                    crash.frames.push(message.to_string());
+                } else if !message.starts_with("...") && crash.details.is_empty() {
+                    crash.details.push(message.to_string());
+                }
+            }
+            crash.frames.extend(fold_java_frames(&frames));
+            Some((crash, lines))
+        } else if entry.tag == "DEBUG" && entry.message.contains("*** *** ***") {
+            let lines = block("DEBUG", entry.pid, i);
+            let mut crash = Crash::default();
+            let mut seen_frames = BTreeSet::new();
+            for &j in &lines {
+                let message = entries[j].message.trim();
+                if message.starts_with("signal ") {
+                    crash.title = message.to_string();
+                } else if message.starts_with("pid: ") || message.starts_with("Abort message") {
+                    crash.details.push(message.to_string());
+                } else if let Some(c) = NATIVE_FRAME.captures(message) {
+                    let library = c[2].rsplit('/').next().unwrap_or(&c[2]);
+                    let frame = match c.get(3) {
+                        Some(symbol) => format!("#{} {} ({})", &c[1], library, symbol.as_str()),
+                        None => format!("#{} {}", &c[1], library),
+                    };
+                    let key = (
+                        library.to_string(),
+                        c.get(3).map(|s| s.as_str().to_string()),
+                    );
+                    if crash.frames.len() < MAX_FRAMES && seen_frames.insert(key) {
+                        crash.frames.push(frame);
+                    }
+                }
+            }
+            if crash.title.is_empty() {
+                crash.title = "native crash".to_string();
+            }
+            Some((crash, lines))
+        } else if entry.tag == "ActivityManager" && entry.message.starts_with("ANR in ") {
+            let lines = block("ActivityManager", entry.pid, i);
+            let mut crash = Crash {
+                title: entry.message.trim().to_string(),
+                ..Default::default()
+            };
+            for &j in &lines[1..] {
+                let message = entries[j].message.trim();
+                if message.starts_with("Reason:") {
+                    crash.details.push(message.to_string());
+                }
+            }
+            Some((crash, lines))
+        } else {
+            None
+        };
+
+        match crash {
+            Some((crash, lines)) => {
+                let relevant = package.is_none_or(|p| {
+                    crash.title.contains(p)
+                        || crash.details.iter().any(|d| d.contains(p))
+                        || crash.frames.iter().any(|f| f.contains(p))
+                        || entry.tag == "AndroidRuntime"
+                });
+                i = lines.last().map_or(i, |last| last + 1).max(i + 1);
+                used.extend(lines);
+                if !relevant {
+                    continue;
+                }
+                match crashes.iter_mut().find(|c| {
+                    c.title == crash.title && c.details == crash.details && c.frames == crash.frames
+                }) {
+                    Some(existing) => existing.count += 1,
+                    None => crashes.push(Crash { count: 1, ..crash }),
+                }
+            }
+            None => i += 1,
+        }
+    }
+    (crashes, used)
+}
+
+fn format_logcat(entries: &[Entry], package: Option<&str>) -> String {
+    let pids = package.map(|p| package_pids(entries, p));
+    // Crash reporters log under their own pid
+    let crash_tags = ["DEBUG", "ActivityManager"];
+    let scoped: Vec<Entry> = entries
+        .iter()
+        .filter(|e| match &pids {
+            Some(pids) => pids.contains(&e.pid) || crash_tags.contains(&e.tag.as_str()),
+            None => true,
+        })
+        .cloned()
+        .collect();
+    let (crashes, used) = extract_crashes(&scoped, package);
+
+    let mut hidden = 0;
+    // (line, repeats) with consecutive repeats collapsed
+    let mut log: Vec<(String, usize)> = Vec::new();
+    let mut last_key = String::new();
+    for (i, entry) in scoped.iter().enumerate() {
+        if used.contains(&i) {
+            continue;
+        }
+        // Activity manager chatter about other apps
+        if package
+            .is_some_and(|p| crash_tags.contains(&entry.tag.as_str()) && !entry.message.contains(p))
+        {
+            continue;
+        }
+        if matches!(entry.level, 'V' | 'D') {
+            hidden += 1;
+            continue;
+        }
+        let key = format!(
+            "{}{}{}",
+            entry.level,
+            entry.tag,
+            DIGITS.replace_all(&entry.message, "N")
+        );
+        if key == last_key {
+            if let Some(last) = log.last_mut() {
+                last.1 += 1;
+            }
+            continue;
+        }
+        last_key = key;
+        log.push((
+            format!(
+                "{} {} {}: {}",
+                entry.time,
+                entry.level,
+                entry.tag,
+                truncate(entry.message.trim(), 160)
+            )
+            .trim_start()
+            .to_string(),
+            1,
+        ));
+    }
+
+    let mut out = Vec::new();
+    let scope = package.map(|p| format!(" {}", p)).unwrap_or_default();
+    let pid_note = match &pids {
+        Some(pids) if pids.is_empty() => " (no process found)".to_string(),
+        Some(pids) => format!(
+            " (pid {})",
+            pids.iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        None => String::new(),
+    };
+    out.push(format!(
+        "📱 logcat{}{}: {} lines, {} shown, {} verbose/debug hidden",
+        scope,
+        pid_note,
+        scoped.len(),
+        log.len(),
+        hidden
+    ));
+
+    for crash in &crashes {
+        let times = if crash.count > 1 {
+            format!(" ×{}", crash.count)
+        } else {
+            String::new()
+        };
+        out.push(format!("💥 {}{}", crash.title, times));
+        for detail in &crash.details {
+            out.push(format!("  {}", truncate(detail, 200)));
+        }
+        for frame in &crash.frames {
+            out.push(format!("    {}", frame));
+        }
+    }
+
+    if !log.is_empty() {
+        if log.len() > MAX_LOG_LINES {
+            out.push(format!("... {} earlier lines", log.len() - MAX_LOG_LINES));
+        }
+        for (line, repeats) in &log[log.len().saturating_sub(MAX_LOG_LINES)..] {
+            if *repeats > 1 {
+                out.push(format!("{} (×{})", line, repeats));
+            } else {
+                out.push(line.clone());
+            }
+        }
+    }
+    out.join("\n")
+}
+
+/// Split rtk's `--app <package>` out of the logcat arguments.
+fn take_app(args: &[String]) -> Result<(Option<String>, Vec<String>)> {
+    let mut app = None;
+    let mut rest = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = arg.strip_prefix("--app=") {
+            app = Some(value.to_string());
+        } else if arg == "--app" {
+            app = Some(
+                iter.next()
+                    .context("--app requires a package name")?
+                    .clone(),
+            );
+        } else {
+            rest.push(arg.clone());
+        }
+    }
+    Ok((app, rest))
+}
+
+fn run_logcat(args: &[String], verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let (app, mut rest) = take_app(args)?;
+    // Dump and exit instead of following forever
+    if !rest.iter().any(|a| a == "-d" || a.starts_with("-t")) {
+        rest.insert(0, "-d".to_string());
+    }
+    // Crash parsing needs a format with tag and pid on every line
+    if !rest.iter().any(|a| a == "-v" || a.starts_with("--format")) {
+        rest.extend(["-v".to_string(), "threadtime".to_string()]);
+    }
+    if verbose > 0 {
+        eprintln!("Running: adb logcat {}", rest.join(" "));
+    }
+
+    let output = Command::new("adb")
+        .arg("logcat")
+        .args(&rest)
+        .output()
+        .context("Failed to run adb. Is the Android SDK platform-tools on PATH?")?;
+    let raw = String::from_utf8_lossy(&output.stdout).to_string();
+    crate::crash::raw_output(&raw);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        eprint!("{}", stderr);
+        exit_code::exit_with(output.status);
+    }
+
+    let filtered = format_logcat(&parse_logcat(&raw), app.as_deref());
+    println!("{}", filtered);
+    timer.with_exit_code(output.status).track(
+        &format!("adb logcat {}", args.join(" ")),
+        &format!("rtk adb logcat {}", args.join(" ")),
+        &raw,
+        &filtered,
+    );
+    Ok(())
+}
+
+fn run_passthrough(args: &[String], verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    if verbose > 0 {
+        eprintln!("adb passthrough: {:?}", args);
+    }
+    let status = Command::new("adb")
+        .args(args)
+        .status()
+        .context("Failed to run adb")?;
+
+    timer.with_exit_code(status).track_passthrough(
+        &format!("adb {}", args.join(" ")),
+        &format!("rtk adb {} (passthrough)", args.join(" ")),
+    );
+    if !status.success() {
+        exit_code::exit_with(status);
+    }
+    Ok(())
+}
+
+pub fn run_adb(args: &[String], verbose: u8) -> Result<()> {
+    match args.first().map(String::as_str) {
+        Some("logcat") => run_logcat(&args[1..], verbose),
+        _ => run_passthrough(args, verbose),
+    }
+}
+
+/// Lines that only narrate configuration, daemons, downloads and tasks.
+fn is_gradle_noise(line: &str) -> bool {
+    const PREFIXES: &[&str] = &[
+        "> Configure project",
+        "> Task ",
+        "> Transform ",
+        "Starting a Gradle Daemon",
+        "Starting Gradle Daemon",
+        "Download ",
+        "Downloading ",
+        "Unzipping ",
+        "Welcome to Gradle",
+        "Deprecated Gradle features were used",
+        "You can use '--warning-mode all'",
+        "For more on this, please refer to",
+        "See https://docs.gradle.org",
+        "Configuration on demand is an incubating feature",
+        "<-------------",
+        "<=",
+        "Reusing configuration cache",
+        "Configuration cache entry",
+        "Calculating task graph",
+        "BUILD SUCCESSFUL",
+        "BUILD FAILED",
+        "Run with --",
+        "Get more help at",
+        "> Run with --",
+        "> Get more help at",
+    ];
+    // AGP configuration-time advice
+    const ANDROID: &[&str] = &[
+        "WARNING: The option setting",
+        "WARNING:The option setting",
+        "WARNING: We recommend using a newer Android Gradle plugin",
+        "WARNING:We recommend using a newer Android Gradle plugin",
+        "The option 'android.",
+        "This Android Gradle plugin",
+        "Checking the license for package",
+        "License for package",
+        "Preparing \"Install",
+        "\"Install ",
+    ];
+    let line = line.trim_start();
+    PREFIXES.iter().chain(ANDROID).any(|p| line.starts_with(p))
+}
+
+#[derive(Debug, Default)]
+struct GradleReport {
+    /// `path:line: message`
+    errors: Vec<String>,
+    /// file -> messages
+    warnings: BTreeMap<String, Vec<String>>,
+    failed_tasks: Vec<String>,
+    failed_tests: Vec<String>,
+    /// `* What went wrong:` block
+    what_went_wrong: Vec<String>,
+    /// `BUILD SUCCESSFUL in 12s`
+    result: Option<String>,
+    /// `42 actionable tasks: 3 executed, 39 up-to-date`
+    tasks: Option<String>,
+}
+
+fn short_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    match path.find("/src/") {
+        Some(i) => {
+            // Keep the module: `app/src/main/...`
+            let start = path[..i].rfind('/').map_or(0, |j| j + 1);
+            path[start..].to_string()
+        }
+        None => path,
+    }
+}
+
+fn add_diagnostic(report: &mut GradleReport, error: bool, file: &str, line: &str, message: &str) {
+    let file = short_path(file);
+    if error {
+        let entry = format!("{}:{}: {}", file, line, message.trim());
+        if !report.errors.contains(&entry) {
+            report.errors.push(entry);
+        }
+    } else {
+        let entry = format!("{}: {}", line, message.trim());
+        let messages = report.warnings.entry(file).or_default();
+        if !messages.contains(&entry) {
+            messages.push(entry);
+        }
+    }
+}
+
+fn parse_gradle(output: &str) -> GradleReport {
+    let mut report = GradleReport::default();
+    let mut in_wrong = false;
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if in_wrong {
+            if trimmed.starts_with("* Try:") || trimmed.starts_with("* Get more help") {
+                in_wrong = false;
+            } else if !trimmed.is_empty() {
+                report.what_went_wrong.push(trimmed.to_string());
+            }
+            continue;
+        }
+        if trimmed == "* What went wrong:" {
+            in_wrong = true;
+        } else if let Some(c) = KOTLIN_DIAG.captures(trimmed) {
+            add_diagnostic(&mut report, &c[1] == "e", &c[2], &c[3], &c[4]);
+        } else if let Some(c) = JAVAC_DIAG.captures(trimmed) {
+            add_diagnostic(&mut report, &c[3] == "error", &c[1], &c[2], &c[4]);
+        } else if let Some(c) = AGPBI.captures(trimmed) {
+            // Resource/manifest problems as JSON from the Android plugin
+            let Ok(json) = serde_json::from_str::<serde_json::Value>(&c[1]) else {
+                continue;
+            };
+            let source = &json["sources"][0];
+            let file = source["file"].as_str().unwrap_or("?");
+            let line = source["position"]["startLine"]
+                .as_u64()
+                .map_or(String::from("?"), |l| (l + 1).to_string());
+            let text = json["text"]
+                .as_str()
+                .unwrap_or("")
+                .lines()
+                .next()
+                .unwrap_or("");
+            add_diagnostic(&mut report, json["kind"] == "error", file, &line, text);
+        } else if let Some(task) = trimmed
+            .strip_prefix("> Task ")
+            .and_then(|t| t.strip_suffix(" FAILED"))
+        {
+            report.failed_tasks.push(task.to_string());
+        } else if let Some(c) = FAILED_TEST.captures(trimmed) {
+            report.failed_tests.push(format!("{} > {}", &c[1], &c[2]));
+        } else if trimmed.starts_with("BUILD SUCCESSFUL") || trimmed.starts_with("BUILD FAILED") {
+            report.result = Some(trimmed.to_string());
+        } else if trimmed.contains("actionable task") {
+            report.tasks = Some(trimmed.to_string());
+        }
+    }
+    report
+}
+
+fn format_gradle(report: &GradleReport) -> String {
+    let mut out = Vec::new();
+    let status = match &report.result {
+        Some(result) if result.starts_with("BUILD SUCCESSFUL") => format!("✓ {}", result),
+        Some(result) => format!("✗ {}", result),
+        None => "gradle: no build result".to_string(),
+    };
+    match &report.tasks {
+        Some(tasks) => out.push(format!("{} ({})", status, tasks)),
+        None => out.push(status),
+    }
+    if !report.failed_tasks.is_empty() {
+        out.push(format!("Failed tasks: {}", report.failed_tasks.join(", ")));
+    }
+    if !report.errors.is_empty() {
+        out.push(format!("Errors ({}):", report.errors.len()));
+        for error in &report.errors {
+            out.push(format!("  {}", truncate(error, 200)));
+        }
+    }
+    if !report.failed_tests.is_empty() {
+        out.push(format!("Failed tests ({}):", report.failed_tests.len()));
+        for test in &report.failed_tests {
+            out.push(format!("  {}", test));
+        }
+    }
+    if !report.what_went_wrong.is_empty() && report.errors.is_empty() {
+        out.push("What went wrong:".to_string());
+        for line in report.what_went_wrong.iter().take(10) {
+            out.push(format!("  {}", truncate(line, 200)));
+        }
+    }
+    if !report.warnings.is_empty() {
+        let total: usize = report.warnings.values().map(Vec::len).sum();
+        out.push(format!(
+            "Warnings ({} in {} files):",
+            total,
+            report.warnings.len()
+        ));
+        for (file, messages) in &report.warnings {
+            out.push(format!("  {} ({})", file, messages.len()));
+            for message in messages.iter().take(MAX_WARNINGS_PER_FILE) {
+                out.push(format!("    {}", truncate(message, 160)));
+            }
+            if messages.len() > MAX_WARNINGS_PER_FILE {
+                out.push(format!(
+                    "    ... +{} more",
+                    messages.len() - MAX_WARNINGS_PER_FILE
+                ));
+            }
+        }
+    }
+    out.join("\n")
+}
+
+pub fn run_gradle(args: &[String], verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let program = if cfg!(windows) && Path::new("gradlew.bat").is_file() {
+        "gradlew.bat"
+    } else if Path::new("gradlew").is_file() {
+        "./gradlew"
+    } else {
+        "gradle"
+    };
+    if verbose > 0 {
+        eprintln!("Running: {} {}", program, args.join(" "));
+    }
+
+    let output = Command::new(program)
+        .args(args)
+        .arg("--console=plain")
+        .output()
+        .with_context(|| format!("Failed to run {}", program))?;
+    let raw = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    crate::crash::raw_output(&raw);
+    let clean = strip_ansi(&raw);
+    let report = parse_gradle(&clean);
+    let mut filtered = format_gradle(&report);
+    if verbose > 1 {
+        let rest: Vec<&str> = clean
+            .lines()
+            .filter(|l| !l.trim().is_empty() && !is_gradle_noise(l))
+            .collect();
+        filtered.push_str(&format!("\n--- unfiltered lines ---\n{}", rest.join("\n")));
+    }
+    println!("{}", filtered);
+
+    timer.with_exit_code(output.status).track(
+        &format!("gradle {}", args.join(" ")),
+        &format!("rtk gradle {}", args.join(" ")),
+        &raw,
+        &filtered,
+    );
+    if !output.status.success() {
+        exit_code::exit_with(output.status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOGCAT: &str = "\
+01-15 10:00:00.100   600   700 I ActivityManager: Start proc 4321:com.example.app/u0a123 for activity
+01-15 10:00:00.200   600   700 I ActivityManager: Start proc 5555:com.other/u0a9 for service
+01-15 10:00:01.000  4321  4321 D Choreographer: Skipped 2 frames!
+01-15 10:00:01.100  4321  4330 I Network: GET /api/items 200
+01-15 10:00:01.200  4321  4330 I Network: GET /api/items 200
+01-15 10:00:01.300  4321  4330 I Network: GET /api/items 304
+01-15 10:00:01.400  5555  5555 E Other: unrelated failure
+01-15 10:00:02.000  4321  4321 E AndroidRuntime: FATAL EXCEPTION: main
+01-15 10:00:02.000  4321  4321 E AndroidRuntime: Process: com.example.app, PID: 4321
+01-15 10:00:02.000  4321  4321 E AndroidRuntime: java.lang.NullPointerException: Attempt to invoke virtual method on a null object reference
+01-15 10:00:02.000  4321  4321 E AndroidRuntime: \tat com.example.app.MainActivity.onCreate(MainActivity.kt:42)
+01-15 10:00:02.000  4321  4321 E AndroidRuntime: \tat android.app.Activity.performCreate(Activity.java:8000)
+01-15 10:00:02.000  4321  4321 E AndroidRuntime: \tat android.app.Instrumentation.callActivityOnCreate(Instrumentation.java:1300)
+01-15 10:00:02.000  4321  4321 E AndroidRuntime: \tat com.android.internal.os.ZygoteInit.main(ZygoteInit.java:900)
+01-15 10:00:02.100   600   700 I ActivityManager: Process com.other has died
+";
+
+    #[test]
+    fn test_parse_logcat_formats() {
+        let entries = parse_logcat("E/MyTag( 1234): boom\n--------- beginning of main\n");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].tag, "MyTag");
+        assert_eq!(entries[0].pid, 1234);
+        assert_eq!(parse_logcat(LOGCAT).len(), 15);
+    }
+
+    #[test]
+    fn test_format_logcat_for_app() {
+        let out = format_logcat(&parse_logcat(LOGCAT), Some("com.example.app"));
+        assert_eq!(
+            out,
+            "📱 logcat com.example.app (pid 4321): 14 lines, 2 shown, 1 verbose/debug hidden
+💥 FATAL EXCEPTION: main
+  java.lang.NullPointerException: Attempt to invoke virtual method on a null object reference
+    at com.example.app.MainActivity.onCreate(MainActivity.kt:42)
+    ... 3 framework frames
+10:00:00.100 I ActivityManager: Start proc 4321:com.example.app/u0a123 for activity
+10:00:01.100 I Network: GET /api/items 200 (×3)"
+        );
+    }
+
+    #[test]
+    fn test_native_crash_and_anr() {
+        let log = "\
+01-15 10:00:00.000  9000  9000 F DEBUG   : *** *** *** *** *** *** *** *** *** *** *** *** *** *** *** ***
+01-15 10:00:00.000  9000  9000 F DEBUG   : pid: 4321, tid: 4321, name: example.app  >>> com.example.app <<<
+01-15 10:00:00.000  9000  9000 F DEBUG   : signal 11 (SIGSEGV), code 1 (SEGV_MAPERR), fault addr 0x0
+01-15 10:00:00.000  9000  9000 F DEBUG   :       #00 pc 0000000000012345  /data/app/lib/arm64/libnative.so (crash_me+20)
+01-15 10:00:00.000  9000  9000 F DEBUG   :       #01 pc 0000000000012400  /data/app/lib/arm64/libnative.so (crash_me+20)
+01-15 10:00:00.000  9000  9000 F DEBUG   :       #02 pc 00000000000a1000  /apex/com.android.runtime/lib64/bionic/libc.so (__start_thread+64)
+01-15 10:00:05.000   600   700 E ActivityManager: ANR in com.example.app (com.example.app/.MainActivity)
+01-15 10:00:05.000   600   700 E ActivityManager: PID: 4321
+01-15 10:00:05.000   600   700 E ActivityManager: Reason: Input dispatching timed out
+";
+        let (crashes, used) = extract_crashes(&parse_logcat(log), Some("com.example.app"));
+        assert_eq!(used.len(), 9);
+        assert_eq!(crashes.len(), 2);
+        assert_eq!(
+            crashes[0].title,
+            "signal 11 (SIGSEGV), code 1 (SEGV_MAPERR), fault addr 0x0"
+        );
+        assert_eq!(
+            crashes[0].frames,
+            vec![
+                "#00 libnative.so (crash_me+20)",
+                "#02 libc.so (__start_thread+64)"
+            ]
+        );
+        assert_eq!(
+            crashes[1].details,
+            vec!["Reason: Input dispatching timed out"]
+        );
+    }
+
+    #[test]
+    fn test_take_app() {
+        let args: Vec<String> = ["--app", "com.x", "-t", "100"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (app, rest) = take_app(&args).unwrap();
+        assert_eq!(app.as_deref(), Some("com.x"));
+        assert_eq!(rest, vec!["-t", "100"]);
+    }
+
+    #[test]
+    fn test_gradle_report() {
+        let output = "\
+Starting a Gradle Daemon (subsequent builds will be faster)
+> Configure project :app
+WARNING: The option setting 'android.experimental.x=true' is experimental.
+> Task :app:preBuild UP-TO-DATE
+> Task :app:compileDebugKotlin
+w: file:///home/u/proj/app/src/main/java/com/x/Util.kt:12:5 Parameter 'ctx' is never used
+w: file:///home/u/proj/app/src/main/java/com/x/Util.kt:12:5 Parameter 'ctx' is never used
+w: file:///home/u/proj/app/src/main/java/com/x/Util.kt:30:9 Variable 'y' is never used
+e: file:///home/u/proj/app/src/main/java/com/x/Main.kt:7:1 Unresolved reference: Foo
+> Task :app:compileDebugKotlin FAILED
+
+FAILURE: Build failed with an exception.
+
+* What went wrong:
+Execution failed for task ':app:compileDebugKotlin'.
+> Compilation error. See log for more details
+
+* Try:
+> Run with --stacktrace option to get the stack trace.
+
+BUILD FAILED in 14s
+12 actionable tasks: 4 executed, 8 up-to-date
+";
+        let out = format_gradle(&parse_gradle(output));
+        assert_eq!(
+            out,
+            "✗ BUILD FAILED in 14s (12 actionable tasks: 4 executed, 8 up-to-date)
+Failed tasks: :app:compileDebugKotlin
+Errors (1):
+  app/src/main/java/com/x/Main.kt:7: Unresolved reference: Foo
+Warnings (2 in 1 files):
+  app/src/main/java/com/x/Util.kt (2)
+    12: Parameter 'ctx' is never used
+    30: Variable 'y' is never used"
+        );
+        assert!(is_gradle_noise("> Configure project :app"));
+        assert!(!is_gradle_noise("e: file:///x.kt:1:1 boom"));
+    }
+
+    #[test]
+    fn test_gradle_failed_tests() {
+        let output = "\
+com.x.ParserTest > parsesEmpty FAILED
+    java.lang.AssertionError at ParserTest.kt:20
+3 tests completed, 1 failed
+BUILD FAILED in 3s
+";
+        let report = parse_gradle(output);
+        assert_eq!(report.failed_tests, vec!["com.x.ParserTest > parsesEmpty"]);
+    }
+}
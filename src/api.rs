@@ -0,0 +1,237 @@
+//! Stable, in-process entry points to rtk's compressors.
+//!
+//! Each function takes output that was already captured and returns a
+//! [`Summary`]: the compacted text plus token estimates for the input and
+//! the result. Nothing here runs commands or writes to the tracking
+//! database.
+//!
+//! ```
+//! use rtk::prelude::*;
+//!
+//! let summary = summarize_json(r#"{"user":{"id":1,"tags":["a","b"]}}"#, 5).unwrap();
+//! assert!(summary.text.contains("user"));
+//! assert!(summary.output_tokens > 0);
+//!
+//! let tests = condense_test_output(
+//!     "cargo test",
+//!     "test a ... ok\ntest b ... FAILED\ntest result: FAILED. 1 passed; 1 failed",
+//! );
+//! assert!(tests.text.contains("test b ... FAILED"));
+//! ```
+
+use crate::{generic_cmd, git, json_cmd, log_cmd, runner, tracking};
+use anyhow::Result;
+
+pub use crate::config::Tokenizer;
+
+/// What a piece of output is, to pick the compressor in [`summarize`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// A JSON document: structure and types, values dropped
+    Json,
+    /// Test runner output: failures and the result line
+    Test,
+    /// A unified diff: per-file hunks, capped
+    Diff,
+    /// Logs: repeated lines deduplicated with counts
+    Log,
+    /// Any output: errors and warnings only
+    Errors,
+    /// Any output: progress bars, repeats and the middle of long output dropped
+    Generic,
+}
+
+impl std::str::FromStr for Kind {
+    type Err = anyhow::Error;
+
+    /// `json`, `test`, `diff`, `log`, `errors` or `generic`.
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "json" => Self::Json,
+            "test" => Self::Test,
+            "diff" => Self::Diff,
+            "log" => Self::Log,
+            "errors" => Self::Errors,
+            "generic" => Self::Generic,
+            _ => anyhow::bail!(
+                "Unknown kind '{}': expected json, test, diff, log, errors or generic",
+                s
+            ),
+        })
+    }
+}
+
+/// Compacted output with token estimates (`Tokenizer::Chars`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Summary {
+    pub text: String,
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+}
+
+impl Summary {
+    fn new(input: &str, text: String) -> Self {
+        Self {
+            input_tokens: estimate_tokens(input, Tokenizer::Chars),
+            output_tokens: estimate_tokens(&text, Tokenizer::Chars),
+            text,
+        }
+    }
+
+    /// The summary cut at a line boundary to fit `max_tokens`, with a note
+    /// of how many lines were dropped.
+    pub fn truncate(self, max_tokens: usize) -> Self {
+        if self.output_tokens <= max_tokens {
+            return self;
+        }
+        let lines: Vec<&str> = self.text.lines().collect();
+        let mut kept = Vec::new();
+        let mut used = 0;
+        for line in &lines {
+            let note = estimate_tokens(
+                &format!("... +{} more lines", lines.len() - kept.len()),
+                Tokenizer::Chars,
+            );
+            let cost = estimate_tokens(line, Tokenizer::Chars).max(1);
+            if used + cost + note > max_tokens {
+                break;
+            }
+            used += cost;
+            kept.push(*line);
+        }
+        let dropped = lines.len() - kept.len();
+        let mut text = kept.join("\n");
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(&format!("... +{} more lines", dropped));
+        Self {
+            output_tokens: estimate_tokens(&text, Tokenizer::Chars),
+            input_tokens: self.input_tokens,
+            text,
+        }
+    }
+
+    /// Tokens saved, as a percentage of the input (0 for empty input).
+    pub fn savings_pct(&self) -> f64 {
+        if self.input_tokens == 0 {
+            return 0.0;
+        }
+        let saved = self.input_tokens.saturating_sub(self.output_tokens);
+        saved as f64 * 100.0 / self.input_tokens as f64
+    }
+}
+
+/// Lines of diff kept by [`summarize`] with `Kind::Diff`.
+pub const DEFAULT_DIFF_LINES: usize = 500;
+/// Nesting depth kept by [`summarize`] with `Kind::Json`.
+pub const DEFAULT_JSON_DEPTH: usize = 5;
+
+/// Estimated token count of `text`.
+pub fn estimate_tokens(text: &str, tokenizer: Tokenizer) -> usize {
+    tracking::estimate_tokens_with(text, tokenizer)
+}
+
+/// The structure of a JSON document, values replaced by their types.
+pub fn summarize_json(json: &str, max_depth: usize) -> Result<Summary> {
+    let text = json_cmd::filter_json_string(json, max_depth)?;
+    Ok(Summary::new(json, text))
+}
+
+/// Failures and the summary line of a test run. `command` (e.g.
+/// `"cargo test"`, `"pytest"`, `"go test ./..."`) selects the
+/// framework-specific patterns.
+pub fn condense_test_output(command: &str, output: &str) -> Summary {
+    Summary::new(output, runner::condense_test_output(command, output))
+}
+
+/// A unified diff reduced to per-file hunks, at most `max_lines` lines.
+pub fn compress_diff(diff: &str, max_lines: usize) -> Summary {
+    Summary::new(diff, git::compact_diff(diff, max_lines))
+}
+
+/// Log lines deduplicated, with counts per distinct error and warning.
+pub fn compress_log(log: &str) -> Summary {
+    Summary::new(log, log_cmd::run_stdin_str(log))
+}
+
+/// Only the error and warning lines of `output`, with their context.
+pub fn filter_errors(output: &str) -> Summary {
+    Summary::new(output, runner::filter_errors(output))
+}
+
+/// Generic compaction: progress bars and repeated lines folded, the middle
+/// of long output dropped.
+pub fn compress(output: &str) -> Summary {
+    Summary::new(output, generic_cmd::compress(output))
+}
+
+/// Compress `text` with the compressor for `kind`, using default limits.
+pub fn summarize(text: &str, kind: Kind) -> Result<Summary> {
+    Ok(match kind {
+        Kind::Json => summarize_json(text, DEFAULT_JSON_DEPTH)?,
+        Kind::Test => condense_test_output(guess_test_command(text), text),
+        Kind::Diff => compress_diff(text, DEFAULT_DIFF_LINES),
+        Kind::Log => compress_log(text),
+        Kind::Errors => filter_errors(text),
+        Kind::Generic => compress(text),
+    })
+}
+
+/// The test command whose patterns match `output`, when it is recognizable.
+fn guess_test_command(output: &str) -> &'static str {
+    if output.contains("test result:") {
+        "cargo test"
+    } else if output.contains("Test Suites:") || output.contains("Tests:") {
+        "jest"
+    } else if output.contains("--- FAIL") || output.contains("--- PASS") {
+        "go test"
+    } else if output.contains("=====") && output.contains(" passed") {
+        "pytest"
+    } else {
+        ""
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_token_estimates() {
+        let diff = "diff --git a/x.rs b/x.rs\n--- a/x.rs\n+++ b/x.rs\n@@ -1 +1 @@\n-a\n+b\n";
+        let summary = compress_diff(diff, 100);
+        assert!(summary.text.contains("x.rs"));
+        assert_eq!(
+            summary.input_tokens,
+            estimate_tokens(diff, Tokenizer::Chars)
+        );
+        assert_eq!(Summary::new("abcdefgh", "abcd".into()).savings_pct(), 50.0);
+        assert_eq!(Summary::new("", String::new()).savings_pct(), 0.0);
+
+        let long = (0..100)
+            .map(|i| format!("line number {}", i))
+            .collect::<Vec<_>>();
+        let summary = Summary::new("", long.join("\n")).truncate(30);
+        assert!(summary.output_tokens <= 30);
+        assert!(summary.text.starts_with("line number 0\n"));
+        assert!(summary.text.ends_with("more lines"));
+        assert_eq!("diff".parse::<Kind>().unwrap(), Kind::Diff);
+        assert!("xml".parse::<Kind>().is_err());
+    }
+
+    #[test]
+    fn test_summarize_dispatch() {
+        let log = "ERROR db down\nERROR db down\nERROR db down\n";
+        let summary = summarize(log, Kind::Log).unwrap();
+        assert!(summary.text.contains("db down"));
+        assert!(summarize("{not json", Kind::Json).is_err());
+        let errors = summarize("info: ok\nerror: boom\n", Kind::Errors).unwrap();
+        assert_eq!(errors.text, "error: boom");
+        let tests = summarize(
+            "test x ... FAILED\ntest result: FAILED. 0 passed",
+            Kind::Test,
+        );
+        assert!(tests.unwrap().text.contains("test result: FAILED"));
+    }
+}
@@ -0,0 +1,503 @@
+//! `rtk bench-self`: rtk's own filters on bundled fixtures.
+//!
+//! Each fixture stands in for output rtk meets every day (a wide diff, a
+//! failing test run, an API dump, a noisy service log) and is generated
+//! deterministically, so every build compresses the same bytes. Fixtures go
+//! through the same filter as the subcommand in process, and the table shows
+//! tokens in and out, savings and median latency. `--save` keeps the run as
+//! a baseline that later runs are compared with; `--check` exits 1 when a
+//! fixture's savings fall below its floor or more than
+//! [`MAX_SAVINGS_DROP`] points under the baseline. Latency is reported but
+//! not gated, being too noisy across machines.
+
+use crate::api::{self, Tokenizer};
+use crate::exit_code;
+use crate::{cargo_cmd, git, json_cmd, log_cmd, pytest_cmd};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Savings may drop this many percentage points under the baseline.
+const MAX_SAVINGS_DROP: f64 = 1.0;
+
+struct Fixture {
+    /// Subcommand whose filter is measured
+    command: &'static str,
+    description: &'static str,
+    /// `--check` fails below this savings percentage
+    min_savings: f64,
+    generate: fn() -> String,
+    filter: fn(&str) -> String,
+}
+
+const FIXTURES: &[Fixture] = &[
+    Fixture {
+        command: "git diff",
+        description: "40 files, one lockfile",
+        min_savings: 95.0,
+        generate: big_diff,
+        filter: |text| git::compact_diff(text, 100),
+    },
+    Fixture {
+        command: "cargo test",
+        description: "600 tests, 3 failing",
+        min_savings: 92.0,
+        generate: failing_cargo_test,
+        filter: cargo_cmd::filter_cargo_test,
+    },
+    Fixture {
+        command: "pytest",
+        description: "400 tests -v, 2 failing",
+        min_savings: 93.0,
+        generate: failing_pytest,
+        filter: pytest_cmd::filter_pytest_output,
+    },
+    Fixture {
+        command: "json",
+        description: "2000 user records",
+        min_savings: 98.0,
+        generate: huge_json,
+        filter: |text| json_cmd::filter_json_string(text, 5).unwrap_or_default(),
+    },
+    Fixture {
+        command: "log",
+        description: "20k service log lines",
+        min_savings: 97.0,
+        generate: service_log,
+        filter: log_cmd::run_stdin_str,
+    },
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Measurement {
+    command: String,
+    input_tokens: usize,
+    output_tokens: usize,
+    savings_pct: f64,
+    latency_us: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Baseline {
+    version: String,
+    results: Vec<Measurement>,
+}
+
+pub fn run(runs: usize, save: bool, check: bool, baseline: Option<PathBuf>) -> Result<()> {
+    let path = baseline.unwrap_or_else(default_baseline_path);
+    let previous = load_baseline(&path);
+    let runs = runs.max(1);
+
+    let results: Vec<Measurement> = FIXTURES.iter().map(|f| measure(f, runs)).collect();
+    println!(
+        "{}",
+        render_table(&results, previous.as_ref().map(|b| &b.results[..]), runs)
+    );
+
+    let regressions = regressions(&results, previous.as_ref().map(|b| &b.results[..]));
+    if let Some(previous) = &previous {
+        println!("\nBaseline: {} (rtk {})", path.display(), previous.version);
+    }
+    for regression in &regressions {
+        println!("❌ {}", regression);
+    }
+
+    if save {
+        save_baseline(&path, &results)?;
+        println!("Saved baseline to {}", path.display());
+    }
+    if check && !regressions.is_empty() {
+        exit_code::set(1);
+    }
+    Ok(())
+}
+
+fn measure(fixture: &Fixture, runs: usize) -> Measurement {
+    let input = (fixture.generate)();
+    let mut output = String::new();
+    let mut times: Vec<Duration> = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let start = Instant::now();
+        output = (fixture.filter)(&input);
+        times.push(start.elapsed());
+    }
+    times.sort();
+
+    let input_tokens = api::estimate_tokens(&input, Tokenizer::Chars);
+    let output_tokens = api::estimate_tokens(&output, Tokenizer::Chars);
+    Measurement {
+        command: fixture.command.to_string(),
+        input_tokens,
+        output_tokens,
+        savings_pct: savings_pct(input_tokens, output_tokens),
+        latency_us: times[times.len() / 2].as_micros() as u64,
+    }
+}
+
+pub(crate) fn savings_pct(input: usize, output: usize) -> f64 {
+    if input == 0 {
+        return 0.0;
+    }
+    input.saturating_sub(output) as f64 * 100.0 / input as f64
+}
+
+fn render_table(results: &[Measurement], baseline: Option<&[Measurement]>, runs: usize) -> String {
+    let mut out = vec![
+        format!("rtk bench-self ({} runs per fixture, median latency)", runs),
+        String::new(),
+        format!(
+            "{:<11} {:<24} {:>8} {:>7} {:>7} {:>9}  {}",
+            "COMMAND", "FIXTURE", "IN", "OUT", "SAVED", "LATENCY", "VS BASELINE"
+        ),
+    ];
+    for (result, fixture) in results.iter().zip(FIXTURES) {
+        let previous = baseline.and_then(|b| b.iter().find(|m| m.command == result.command));
+        let versus = match previous {
+            Some(p) => format!(
+                "{:+.1}pp, {}",
+                result.savings_pct - p.savings_pct,
+                latency_ratio(result.latency_us, p.latency_us)
+            ),
+            None => "-".to_string(),
+        };
+        out.push(format!(
+            "{:<11} {:<24} {:>8} {:>7} {:>6.1}% {:>9}  {}",
+            result.command,
+            fixture.description,
+            crate::utils::format_tokens(result.input_tokens),
+            crate::utils::format_tokens(result.output_tokens),
+            result.savings_pct,
+            format_latency(result.latency_us),
+            versus
+        ));
+    }
+    out.join("\n")
+}
+
+fn format_latency(us: u64) -> String {
+    if us < 1000 {
+        format!("{}µs", us)
+    } else {
+        format!("{:.1}ms", us as f64 / 1000.0)
+    }
+}
+
+/// `1.20x` slower or faster than the baseline.
+fn latency_ratio(now: u64, before: u64) -> String {
+    if before == 0 {
+        return "-".to_string();
+    }
+    format!("{:.2}x time", now as f64 / before as f64)
+}
+
+/// Fixtures whose savings fell below their floor or the baseline.
+fn regressions(results: &[Measurement], baseline: Option<&[Measurement]>) -> Vec<String> {
+    let mut found = Vec::new();
+    for (result, fixture) in results.iter().zip(FIXTURES) {
+        if result.savings_pct < fixture.min_savings {
+            found.push(format!(
+                "{}: saved {:.1}%, below the {:.0}% floor",
+                result.command, result.savings_pct, fixture.min_savings
+            ));
+            continue;
+        }
+        let previous = baseline.and_then(|b| b.iter().find(|m| m.command == result.command));
+        if let Some(p) = previous {
+            if result.savings_pct < p.savings_pct - MAX_SAVINGS_DROP {
+                found.push(format!(
+                    "{}: saved {:.1}%, baseline {:.1}%",
+                    result.command, result.savings_pct, p.savings_pct
+                ));
+            }
+        }
+    }
+    found
+}
+
+fn default_baseline_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rtk")
+        .join("bench-self.json")
+}
+
+fn load_baseline(path: &Path) -> Option<Baseline> {
+    serde_json::from_slice(&std::fs::read(path).ok()?).ok()
+}
+
+fn save_baseline(path: &Path, results: &[Measurement]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let baseline = Baseline {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        results: results.to_vec(),
+    };
+    std::fs::write(path, serde_json::to_string_pretty(&baseline)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+// Fixtures. Plain loops over the index rather than randomness, so the text
+// is identical on every run and every platform.
+
+const WORDS: &[&str] = &[
+    "config", "request", "handler", "buffer", "session", "token", "cache", "index", "parser",
+    "stream", "worker", "record",
+];
+
+fn word(i: usize) -> &'static str {
+    WORDS[i % WORDS.len()]
+}
+
+fn big_diff() -> String {
+    let mut diff = String::new();
+    for f in 0..40 {
+        let path = format!("services/{}/src/{}_{}.rs", word(f), word(f * 7 + 3), f);
+        diff.push_str(&format!(
+            "diff --git a/{0} b/{0}\nindex 3f2a1c{1:02}..9b8e7d{1:02} 100644\n--- a/{0}\n+++ b/{0}\n",
+            path, f
+        ));
+        for h in 0..6 {
+            let at = h * 40 + f;
+            diff.push_str(&format!(
+                "@@ -{0},12 +{0},14 @@ impl {1}{2} {{\n",
+                at,
+                word(h).to_uppercase(),
+                word(f)
+            ));
+            for c in 0..3 {
+                diff.push_str(&format!(
+                    "     let {} = self.{}.get({});\n",
+                    word(c),
+                    word(h + c),
+                    c
+                ));
+            }
+            for c in 0..4 {
+                diff.push_str(&format!(
+                    "-    self.{}.insert({}, old_{});\n",
+                    word(c + h),
+                    c,
+                    word(f)
+                ));
+                diff.push_str(&format!(
+                    "+    self.{}.insert({}, {}::new({}))?;\n",
+                    word(c + h),
+                    c,
+                    word(f + c),
+                    h
+                ));
+            }
+            for c in 0..3 {
+                diff.push_str(&format!("     {}.flush()?;\n", word(c + f)));
+            }
+        }
+    }
+    diff.push_str("diff --git a/Cargo.lock b/Cargo.lock\n--- a/Cargo.lock\n+++ b/Cargo.lock\n");
+    diff.push_str("@@ -1,2000 +1,2000 @@\n");
+    for i in 0..2000 {
+        diff.push_str(&format!("-checksum = \"{:064x}\"\n", i * 7919));
+        diff.push_str(&format!("+checksum = \"{:064x}\"\n", i * 104_729));
+    }
+    diff
+}
+
+fn failing_cargo_test() -> String {
+    let mut log = String::from(
+        "   Compiling serde v1.0.200\n   Compiling app v0.3.0 (/work/app)\n    Finished `test` profile [unoptimized + debuginfo] target(s) in 14.21s\n     Running unittests src/lib.rs (target/debug/deps/app-5d1c2b)\n\nrunning 600 tests\n",
+    );
+    let failing = [113, 287, 451];
+    for i in 0..600 {
+        let status = if failing.contains(&i) { "FAILED" } else { "ok" };
+        log.push_str(&format!(
+            "test {}::tests::test_{}_{} ... {}\n",
+            word(i),
+            word(i / 3),
+            i,
+            status
+        ));
+    }
+    log.push_str("\nfailures:\n\n");
+    for &i in &failing {
+        log.push_str(&format!(
+            "---- {0}::tests::test_{1}_{2} stdout ----\nthread '{0}::tests::test_{1}_{2}' panicked at src/{0}.rs:{3}:9:\nassertion `left == right` failed\n  left: {4}\n right: {5}\nnote: run with `RUST_BACKTRACE=1` environment variable to display a backtrace\n\n",
+            word(i),
+            word(i / 3),
+            i,
+            100 + i,
+            i,
+            i + 1
+        ));
+    }
+    log.push_str("\nfailures:\n");
+    for &i in &failing {
+        log.push_str(&format!(
+            "    {}::tests::test_{}_{}\n",
+            word(i),
+            word(i / 3),
+            i
+        ));
+    }
+    log.push_str(
+        "\ntest result: FAILED. 597 passed; 3 failed; 0 ignored; 0 measured; 0 filtered out; finished in 2.87s\n\nerror: test failed, to rerun pass `--lib`\n",
+    );
+    log
+}
+
+fn failing_pytest() -> String {
+    let mut log = String::from(
+        "============================= test session starts ==============================\nplatform linux -- Python 3.12.3, pytest-8.2.0, pluggy-1.5.0\nrootdir: /work/app\nconfigfile: pyproject.toml\nplugins: cov-5.0.0, xdist-3.6.1\ncollected 400 items\n\n",
+    );
+    for m in 0..20 {
+        for t in 0..20 {
+            let status = if (m, t) == (4, 7) || (m, t) == (13, 2) {
+                "FAILED"
+            } else {
+                "PASSED"
+            };
+            log.push_str(&format!(
+                "tests/test_{0}_{1}.py::test_{0}_{2} {3} [{4:>3}%]\n",
+                word(m),
+                m,
+                t,
+                status,
+                (m * 20 + t + 1) / 4
+            ));
+        }
+    }
+    log.push_str(
+        "\n=================================== FAILURES ===================================\n",
+    );
+    for (m, t) in [(4, 7), (13, 2)] {
+        log.push_str(&format!(
+            "___________________________ test_{0}_{1} ___________________________\n\n    def test_{0}_{1}():\n        result = {0}.load({1})\n>       assert result.status == 200\nE       AssertionError: assert 500 == 200\nE        +  where 500 = <Response [500]>.status\n\ntests/test_{0}_{2}.py:{3}: AssertionError\n",
+            word(m),
+            t,
+            m,
+            40 + t
+        ));
+    }
+    log.push_str(
+        "=========================== short test summary info ============================\n",
+    );
+    for (m, t) in [(4, 7), (13, 2)] {
+        log.push_str(&format!(
+            "FAILED tests/test_{0}_{1}.py::test_{0}_{2} - AssertionError: assert 500 == 200\n",
+            word(m),
+            m,
+            t
+        ));
+    }
+    log.push_str(
+        "======================== 2 failed, 398 passed in 6.42s =========================\n",
+    );
+    log
+}
+
+fn huge_json() -> String {
+    let users: Vec<serde_json::Value> = (0..2000)
+        .map(|i| {
+            serde_json::json!({
+                "id": i,
+                "name": format!("{} {}", word(i), word(i / 12)),
+                "email": format!("{}.{}@example.com", word(i), i),
+                "active": i % 3 != 0,
+                "score": (i * 37 % 1000) as f64 / 10.0,
+                "tags": [word(i), word(i + 5), word(i + 9)],
+                "address": {
+                    "street": format!("{} {} Street", i, word(i + 2)),
+                    "city": word(i + 4),
+                    "zip": format!("{:05}", i * 13 % 100_000),
+                },
+                "last_login": format!("2026-03-{:02}T{:02}:{:02}:00Z", i % 28 + 1, i % 24, i % 60),
+            })
+        })
+        .collect();
+    serde_json::json!({ "page": 1, "total": 2000, "users": users }).to_string()
+}
+
+fn service_log() -> String {
+    let mut log = String::new();
+    for i in 0..20_000 {
+        let time = format!(
+            "2026-03-04T10:{:02}:{:02}.{:03}Z",
+            i / 3600 % 60,
+            i / 60 % 60,
+            i % 1000
+        );
+        let line = match i % 50 {
+            0 => format!(
+                "{} ERROR {} failed: connection reset by peer (attempt {})",
+                time,
+                word(i),
+                i % 5
+            ),
+            1 | 2 => format!(
+                "{} WARN slow {} for user {}: {}ms",
+                time,
+                word(i),
+                i % 97,
+                500 + i % 400
+            ),
+            _ => format!(
+                "{} INFO {} {} handled in {}ms request_id={:08x}",
+                time,
+                word(i),
+                word(i / 7),
+                i % 120,
+                i * 2_654_435_761 % 0xffff_ffff
+            ),
+        };
+        log.push_str(&line);
+        log.push('\n');
+    }
+    log
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixtures_are_deterministic_and_compress() {
+        for fixture in FIXTURES {
+            let input = (fixture.generate)();
+            assert_eq!(input, (fixture.generate)(), "{}", fixture.command);
+            let m = measure(fixture, 1);
+            assert!(
+                m.savings_pct >= fixture.min_savings,
+                "{} saved {:.1}%, floor {}",
+                fixture.command,
+                m.savings_pct,
+                fixture.min_savings
+            );
+        }
+    }
+
+    #[test]
+    fn test_regressions_against_baseline() {
+        let m = |command: &str, savings_pct: f64| Measurement {
+            command: command.to_string(),
+            input_tokens: 1000,
+            output_tokens: 100,
+            savings_pct,
+            latency_us: 50,
+        };
+        let baseline = vec![m("git diff", 97.0), m("json", 99.0)];
+        let results = vec![
+            m("git diff", 96.5),
+            m("cargo test", 10.0),
+            m("pytest", 96.0),
+        ];
+        let found = regressions(&results, Some(&baseline));
+        // git diff within tolerance; cargo test under its floor
+        assert_eq!(found, ["cargo test: saved 10.0%, below the 92% floor"]);
+
+        let results = vec![m("git diff", 95.5)];
+        assert_eq!(
+            regressions(&results, Some(&baseline)),
+            ["git diff: saved 95.5%, baseline 97.0%"]
+        );
+    }
+}
@@ -0,0 +1,579 @@
+//! `rtk bloat` — binary size reports from cargo-bloat, twiggy or bloaty.
+//!
+//! Each run is compared against a baseline kept under
+//! `<data dir>/rtk/bloat/`, keyed by directory, tool and arguments. The first
+//! run (or `--save-baseline`) records it; later runs show what grew.
+
+use crate::cache;
+use crate::exit_code;
+use crate::tracking;
+use crate::utils::truncate;
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Changes smaller than this are noise from codegen shuffling.
+const MIN_DELTA: i64 = 256;
+/// Largest growth/shrink entries listed.
+const MAX_CHANGES: usize = 5;
+
+lazy_static! {
+    static ref RUST_HASH: Regex = Regex::new(r"::h[0-9a-f]{16}$").unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Tool {
+    CargoBloat,
+    Twiggy,
+    Bloaty,
+}
+
+impl Tool {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "cargo-bloat" | "bloat" => Some(Self::CargoBloat),
+            "twiggy" => Some(Self::Twiggy),
+            "bloaty" => Some(Self::Bloaty),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::CargoBloat => "cargo-bloat",
+            Self::Twiggy => "twiggy",
+            Self::Bloaty => "bloaty",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Item {
+    name: String,
+    size: u64,
+    /// Owning crate, when the tool reports one
+    krate: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct SizeReport {
+    /// What the items are: functions, crates, sections, symbols...
+    kind: String,
+    file_size: Option<u64>,
+    /// Size the percentages are relative to (.text for cargo-bloat)
+    total: u64,
+    items: Vec<Item>,
+}
+
+/// Sizes from an earlier run, for deltas.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Baseline {
+    pub saved: String,
+    pub total: u64,
+    pub sizes: HashMap<String, u64>,
+}
+
+pub fn format_size(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+fn format_delta(delta: i64) -> String {
+    let sign = if delta < 0 { '-' } else { '+' };
+    format!("{}{}", sign, format_size(delta.unsigned_abs()))
+}
+
+/// Symbol without the Rust legacy-mangling hash suffix.
+fn clean_symbol(name: &str) -> String {
+    RUST_HASH.replace(name, "").to_string()
+}
+
+#[derive(Deserialize)]
+struct BloatJson {
+    #[serde(rename = "file-size", default)]
+    file_size: u64,
+    #[serde(rename = "text-section-size", default)]
+    text_size: u64,
+    #[serde(default)]
+    functions: Option<Vec<BloatFunction>>,
+    #[serde(default)]
+    crates: Option<Vec<BloatCrate>>,
+}
+
+#[derive(Deserialize)]
+struct BloatFunction {
+    #[serde(rename = "crate")]
+    krate: Option<String>,
+    name: String,
+    size: u64,
+}
+
+#[derive(Deserialize)]
+struct BloatCrate {
+    name: String,
+    size: u64,
+}
+
+/// `cargo bloat --message-format json`, for functions or `--crates`.
+fn parse_cargo_bloat(json: &str) -> Result<SizeReport> {
+    let parsed: BloatJson =
+        serde_json::from_str(json).context("Failed to parse cargo-bloat JSON output")?;
+    let (kind, items) = match (parsed.functions, parsed.crates) {
+        (_, Some(crates)) => (
+            "crates",
+            crates
+                .into_iter()
+                .map(|c| Item {
+                    name: c.name,
+                    size: c.size,
+                    krate: None,
+                })
+                .collect(),
+        ),
+        (functions, None) => (
+            "functions",
+            functions
+                .unwrap_or_default()
+                .into_iter()
+                .map(|f| Item {
+                    name: clean_symbol(&f.name),
+                    size: f.size,
+                    krate: f.krate,
+                })
+                .collect(),
+        ),
+    };
+    Ok(SizeReport {
+        kind: kind.to_string(),
+        file_size: Some(parsed.file_size),
+        total: parsed.text_size,
+        items,
+    })
+}
+
+/// `twiggy top -f json`: items with shallow sizes; the total is implied by
+/// the percentages.
+fn parse_twiggy(json: &str) -> Result<SizeReport> {
+    let items: Vec<serde_json::Value> =
+        serde_json::from_str(json).context("Failed to parse twiggy JSON output")?;
+    let mut total = 0u64;
+    let items: Vec<Item> = items
+        .iter()
+        .filter_map(|item| {
+            let size = item["shallow_size"].as_u64()?;
+            if let Some(pct) = item["shallow_size_percent"].as_f64().filter(|p| *p > 0.0) {
+                total = total.max((size as f64 * 100.0 / pct).round() as u64);
+            }
+            Some(Item {
+                name: item["name"].as_str()?.to_string(),
+                size,
+                krate: None,
+            })
+        })
+        .collect();
+    let total = total.max(items.iter().map(|i| i.size).sum());
+    Ok(SizeReport {
+        kind: "items".to_string(),
+        file_size: None,
+        total,
+        items,
+    })
+}
+
+/// `bloaty --csv`: `<dimension>,vmsize,filesize` rows.
+fn parse_bloaty(csv: &str) -> Result<SizeReport> {
+    let mut lines = csv.lines().filter(|l| !l.trim().is_empty());
+    let header = lines.next().context("Empty bloaty output")?;
+    let columns: Vec<&str> = header.split(',').collect();
+    let file_col = columns
+        .iter()
+        .position(|c| *c == "filesize")
+        .context("bloaty output is not --csv (no filesize column)")?;
+    let kind = columns.first().copied().unwrap_or("items").to_string();
+
+    let mut items = Vec::new();
+    for line in lines {
+        // Symbol names can hold commas; sizes are always the last columns
+        let mut fields: Vec<&str> = line.rsplitn(columns.len(), ',').collect();
+        fields.reverse();
+        let Some(size) = fields.get(file_col).and_then(|s| s.trim().parse().ok()) else {
+            continue;
+        };
+        items.push(Item {
+            name: clean_symbol(fields[0].trim_matches('"')),
+            size,
+            krate: None,
+        });
+    }
+    let total = items.iter().map(|i| i.size).sum();
+    Ok(SizeReport {
+        kind,
+        file_size: Some(total),
+        total,
+        items,
+    })
+}
+
+fn parse(tool: Tool, output: &str) -> Result<SizeReport> {
+    match tool {
+        Tool::CargoBloat => parse_cargo_bloat(output),
+        Tool::Twiggy => parse_twiggy(output),
+        Tool::Bloaty => parse_bloaty(output),
+    }
+}
+
+/// Tool behind a saved report, from its shape.
+fn detect(content: &str) -> Tool {
+    match content.trim_start().chars().next() {
+        Some('{') => Tool::CargoBloat,
+        Some('[') => Tool::Twiggy,
+        _ => Tool::Bloaty,
+    }
+}
+
+fn format_report(
+    tool: Tool,
+    report: &SizeReport,
+    baseline: Option<&Baseline>,
+    top: usize,
+) -> String {
+    let mut out = Vec::new();
+    let mut header = format!("📦 {}:", tool.name());
+    if let Some(file_size) = report.file_size.filter(|s| *s != report.total) {
+        header.push_str(&format!(" file {},", format_size(file_size)));
+    }
+    let label = if tool == Tool::CargoBloat {
+        ".text"
+    } else {
+        "total"
+    };
+    header.push_str(&format!(
+        " {} {} in {} {}",
+        label,
+        format_size(report.total),
+        report.items.len(),
+        report.kind
+    ));
+    if let Some(base) = baseline {
+        let delta = report.total as i64 - base.total as i64;
+        header.push_str(&format!(
+            " ({} vs baseline {})",
+            format_delta(delta),
+            base.saved
+        ));
+    }
+    out.push(header);
+
+    let mut items: Vec<&Item> = report.items.iter().collect();
+    items.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+    let pct = |size: u64| {
+        if report.total == 0 {
+            0.0
+        } else {
+            size as f64 * 100.0 / report.total as f64
+        }
+    };
+    for item in items.iter().take(top) {
+        let mut line = format!(
+            "  {:>10} {:>5.1}%  {}",
+            format_size(item.size),
+            pct(item.size),
+            truncate(&item.name, 90)
+        );
+        if let Some(krate) = item.krate.as_ref().filter(|k| !item.name.starts_with(*k)) {
+            line.push_str(&format!("  [{}]", krate));
+        }
+        if let Some(base) = baseline {
+            match base.sizes.get(&item.name) {
+                Some(old) if (item.size as i64 - *old as i64).abs() >= MIN_DELTA => line.push_str(
+                    &format!("  ({})", format_delta(item.size as i64 - *old as i64)),
+                ),
+                None => line.push_str("  (new)"),
+                _ => {}
+            }
+        }
+        out.push(line);
+    }
+    if items.len() > top {
+        let rest: u64 = items[top..].iter().map(|i| i.size).sum();
+        out.push(format!(
+            "  ... +{} more ({}, {:.1}%)",
+            items.len() - top,
+            format_size(rest),
+            pct(rest)
+        ));
+    }
+
+    // Functions roll up into crates, the unit people act on
+    let mut crates: HashMap<&str, u64> = HashMap::new();
+    for item in &report.items {
+        if let Some(krate) = &item.krate {
+            *crates.entry(krate.as_str()).or_default() += item.size;
+        }
+    }
+    if !crates.is_empty() {
+        let mut crates: Vec<(&str, u64)> = crates.into_iter().collect();
+        crates.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        let list: Vec<String> = crates
+            .iter()
+            .take(8)
+            .map(|(name, size)| format!("{} {:.0}%", name, pct(*size)))
+            .collect();
+        out.push(format!("By crate: {}", list.join(", ")));
+    }
+
+    if let Some(base) = baseline {
+        let current: HashMap<&str, u64> = report
+            .items
+            .iter()
+            .map(|i| (i.name.as_str(), i.size))
+            .collect();
+        let mut changes: Vec<(String, i64)> = current
+            .iter()
+            .map(|(name, size)| {
+                let old = base.sizes.get(*name).copied().unwrap_or(0);
+                (name.to_string(), *size as i64 - old as i64)
+            })
+            .chain(
+                base.sizes
+                    .iter()
+                    .filter(|(name, _)| !current.contains_key(name.as_str()))
+                    .map(|(name, size)| (name.clone(), -(*size as i64))),
+            )
+            .filter(|(_, delta)| delta.abs() >= MIN_DELTA)
+            .collect();
+        changes.sort_by(|a, b| b.1.abs().cmp(&a.1.abs()).then_with(|| a.0.cmp(&b.0)));
+        if !changes.is_empty() {
+            out.push(format!("Biggest changes ({}):", changes.len()));
+            for (name, delta) in changes.iter().take(MAX_CHANGES) {
+                let note = if !base.sizes.contains_key(name) {
+                    " (new)"
+                } else if !current.contains_key(name.as_str()) {
+                    " (gone)"
+                } else {
+                    ""
+                };
+                out.push(format!(
+                    "  {:>12}  {}{}",
+                    format_delta(*delta),
+                    truncate(name, 90),
+                    note
+                ));
+            }
+        }
+    }
+
+    out.join("\n")
+}
+
+/// Baseline file under `<data dir>/rtk/<dir>/`, keyed by the current
+/// directory and `parts`.
+pub fn baseline_path(dir: &str, parts: &[&str]) -> Option<PathBuf> {
+    let cwd = std::env::current_dir().ok()?;
+    let mut key = vec![cwd.to_string_lossy().to_string()];
+    key.extend(parts.iter().map(|p| p.to_string()));
+    let key: Vec<&str> = key.iter().map(String::as_str).collect();
+    Some(
+        dirs::data_local_dir()?
+            .join("rtk")
+            .join(dir)
+            .join(cache::key(&key)),
+    )
+}
+
+pub fn load_baseline(path: &Path) -> Option<Baseline> {
+    serde_json::from_slice(&std::fs::read(path).ok()?).ok()
+}
+
+pub fn save_baseline(path: &Path, total: u64, sizes: HashMap<String, u64>) {
+    let baseline = Baseline {
+        saved: chrono::Local::now().format("%Y-%m-%d %H:%M").to_string(),
+        total,
+        sizes,
+    };
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(json) = serde_json::to_vec(&baseline) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+/// Runs the tool with machine-readable output forced.
+fn run_tool(tool: Tool, args: &[String]) -> Result<(String, String, i32)> {
+    let mut cmd = match tool {
+        Tool::CargoBloat => {
+            let mut cmd = Command::new("cargo");
+            cmd.arg("bloat");
+            if !args.iter().any(|a| a.starts_with("--message-format")) {
+                cmd.args(["--message-format", "json"]);
+            }
+            cmd
+        }
+        Tool::Twiggy => {
+            let mut cmd = Command::new("twiggy");
+            // `rtk bloat twiggy app.wasm` means `twiggy top`
+            if args.first().is_some_and(|a| Path::new(a).is_file()) {
+                cmd.arg("top");
+            }
+            if !args.iter().any(|a| a == "-f" || a.starts_with("--format")) {
+                cmd.args(["-f", "json"]);
+            }
+            cmd
+        }
+        Tool::Bloaty => {
+            let mut cmd = Command::new("bloaty");
+            if !args.iter().any(|a| a == "--csv" || a == "--tsv") {
+                cmd.arg("--csv");
+            }
+            cmd
+        }
+    };
+    cmd.args(args);
+    let output = cmd.output().with_context(|| {
+        format!(
+            "Failed to run {}. Is it installed? Try: cargo install {}",
+            tool.name(),
+            tool.name()
+        )
+    })?;
+    Ok((
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+        exit_code::from_status(output.status),
+    ))
+}
+
+/// `source` is cargo-bloat, twiggy or bloaty to run one, or a file holding
+/// their saved JSON/CSV output.
+pub fn run(source: &str, args: &[String], top: usize, save: bool, verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+
+    let (tool, stdout, stderr, code) = match Tool::parse(source) {
+        Some(tool) => {
+            if verbose > 0 {
+                eprintln!("Running: {} {}", tool.name(), args.join(" "));
+            }
+            let (stdout, stderr, code) = run_tool(tool, args)?;
+            (tool, stdout, stderr, code)
+        }
+        None => {
+            let content = std::fs::read_to_string(source).with_context(|| {
+                format!(
+                    "{} is neither cargo-bloat, twiggy, bloaty nor a readable report file",
+                    source
+                )
+            })?;
+            (detect(&content), content, String::new(), 0)
+        }
+    };
+    let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
+
+    let filtered = match parse(tool, &stdout) {
+        Ok(report) => {
+            let path = baseline_path("bloat", &[tool.name(), &report.kind, &args.join(" ")]);
+            let baseline = path.as_deref().and_then(load_baseline);
+            let mut out = format_report(tool, &report, baseline.as_ref(), top);
+            if let Some(path) = path.filter(|_| save || baseline.is_none()) {
+                let sizes = report
+                    .items
+                    .iter()
+                    .map(|i| (i.name.clone(), i.size))
+                    .collect();
+                save_baseline(&path, report.total, sizes);
+                out.push_str("\n(baseline saved)");
+            }
+            out
+        }
+        // Build errors and usage messages from the tool itself
+        Err(_) if stdout.trim().is_empty() => truncate(stderr.trim(), 2000),
+        Err(e) => format!("{} ({})\n{}", tool.name(), e, truncate(&stdout, 500)),
+    };
+    println!("{}", filtered);
+
+    timer.with_code(code).track(
+        &format!("{} {}", tool.name(), args.join(" ")),
+        &format!("rtk bloat {} {}", source, args.join(" ")),
+        &raw,
+        &filtered,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOAT: &str = r#"{"file-size": 4400000, "text-section-size": 2000000, "functions": [
+        {"crate": "regex_automata", "name": "regex_automata::meta::strategy::new::h0123456789abcdef", "size": 60000},
+        {"crate": "std", "name": "std::backtrace_rs::symbolize::gimli::resolve", "size": 40000},
+        {"crate": "rtk", "name": "rtk::main", "size": 20000},
+        {"crate": "std", "name": "core::fmt::write", "size": 2000}
+    ]}"#;
+
+    #[test]
+    fn test_parse_formats() {
+        let report = parse(detect(BLOAT), BLOAT).unwrap();
+        assert_eq!(report.kind, "functions");
+        assert_eq!(report.items[0].name, "regex_automata::meta::strategy::new");
+
+        let twiggy = r#"[{"name": "code[0]", "shallow_size": 500, "shallow_size_percent": 50.0},
+                         {"name": "data[1]", "shallow_size": 100, "shallow_size_percent": 10.0}]"#;
+        let report = parse(detect(twiggy), twiggy).unwrap();
+        assert_eq!(report.total, 1000);
+
+        let bloaty = "sections,vmsize,filesize\n.text,2000,2048\n\"a,b\",10,16\n";
+        let report = parse(detect(bloaty), bloaty).unwrap();
+        assert_eq!(report.kind, "sections");
+        assert_eq!(report.items[1].name, "a,b");
+        assert_eq!(report.total, 2064);
+    }
+
+    #[test]
+    fn test_report_against_baseline() {
+        let report = parse_cargo_bloat(BLOAT).unwrap();
+        let baseline = Baseline {
+            saved: "2026-10-01 09:00".to_string(),
+            total: 1_950_000,
+            sizes: HashMap::from([
+                ("regex_automata::meta::strategy::new".to_string(), 40000),
+                ("rtk::main".to_string(), 20100),
+                ("core::fmt::write".to_string(), 2000),
+                ("old::gone".to_string(), 5000),
+            ]),
+        };
+        let out = format_report(Tool::CargoBloat, &report, Some(&baseline), 2);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines[0],
+            "📦 cargo-bloat: file 4.2 MiB, .text 1.9 MiB in 4 functions (+48.8 KiB vs baseline 2026-10-01 09:00)"
+        );
+        assert_eq!(
+            lines[1],
+            "    58.6 KiB   3.0%  regex_automata::meta::strategy::new  (+19.5 KiB)"
+        );
+        assert_eq!(
+            lines[2],
+            "    39.1 KiB   2.0%  std::backtrace_rs::symbolize::gimli::resolve  (new)"
+        );
+        assert_eq!(lines[3], "  ... +2 more (21.5 KiB, 1.1%)");
+        assert_eq!(lines[4], "By crate: regex_automata 3%, std 2%, rtk 1%");
+        assert_eq!(lines[5], "Biggest changes (3):");
+        assert_eq!(
+            lines[6],
+            "     +39.1 KiB  std::backtrace_rs::symbolize::gimli::resolve (new)"
+        );
+        assert!(out.ends_with("      -4.9 KiB  old::gone (gone)"));
+        assert!(!out.contains("rtk::main"));
+    }
+}
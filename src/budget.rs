@@ -0,0 +1,375 @@
+//! `rtk budget` — how much of the agent's context window the current
+//! session has used, and which rtk modes to prefer as it fills up.
+//!
+//! Usage comes from the session transcript: the token usage the API
+//! reported for the last assistant turn, or a character estimate when the
+//! transcript has none. Tool outputs are tallied by tool (and by program
+//! for shell calls) to point the advice at what is actually eating the
+//! window. Thresholds live in `[budget]`.
+//!
+//! `--hook` reads the hook payload's `transcript_path` from stdin and prints
+//! a `systemMessage` only once the session is past `budget.warn_pct`.
+
+use crate::config::{BudgetConfig, Config};
+use crate::discover::provider::ClaudeProvider;
+use crate::tracking::estimate_tokens;
+use crate::utils::{format_tokens, pad};
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+enum Level {
+    Ok,
+    Warn,
+    Critical,
+}
+
+/// Context use of one session.
+#[derive(Debug, Default)]
+struct Usage {
+    /// Tokens in context at the last turn
+    context: u64,
+    /// False when estimated from the transcript text
+    reported: bool,
+    /// Estimated tokens of tool output, by tool, with the number of calls
+    consumers: HashMap<String, (usize, usize)>,
+}
+
+/// Tool outputs that a terser rtk mode shrinks, with the advice.
+const ADVICE: &[(&[&str], &str)] = &[
+    (
+        &[
+            "Read",
+            "Bash: cat",
+            "Bash: head",
+            "Bash: tail",
+            "Bash: sed",
+            "Bash: rtk read",
+        ],
+        "read files with `rtk read <file> --level aggressive` (signatures only) or `--max-lines N`",
+    ),
+    (
+        &["Grep", "Bash: grep", "Bash: rg", "Bash: find", "Glob"],
+        "search with `rtk grep` / `rtk find`: matches grouped by file",
+    ),
+    (
+        &["Bash: git"],
+        "use `rtk git diff` / `rtk git log`, or `git diff --stat` first",
+    ),
+    (
+        &[
+            "Bash: cargo",
+            "Bash: npm",
+            "Bash: pnpm",
+            "Bash: pytest",
+            "Bash: go",
+            "Bash: npx",
+            "Bash: make",
+        ],
+        "wrap builds and tests in `rtk test <cmd>` / `rtk err <cmd>`: failures only",
+    ),
+];
+
+pub fn run(transcript: Option<PathBuf>, hook: bool, verbose: u8) -> Result<()> {
+    let config = &Config::cached().budget;
+    if hook {
+        return run_hook(config);
+    }
+    let path = match transcript {
+        Some(path) => path,
+        None => latest_transcript()?,
+    };
+    let usage = usage(&path)?;
+    let level = level(usage.context, config);
+    let pct = percent(usage.context, config);
+
+    println!("Session: {}", path.display());
+    println!(
+        "Context: {} / {} tokens ({:.0}%{}) — {}",
+        format_tokens(usage.context as usize),
+        format_tokens(config.context_window as usize),
+        pct,
+        if usage.reported { "" } else { ", estimated" },
+        match level {
+            Level::Ok => "ok",
+            Level::Warn => "getting full",
+            Level::Critical => "nearly full",
+        }
+    );
+
+    let consumers = top_consumers(&usage, 5);
+    if !consumers.is_empty() {
+        println!("\nLargest tool outputs:");
+        for (tool, tokens, calls) in &consumers {
+            println!(
+                "  {} {:>8} tokens ({} call{})",
+                pad(tool, 16),
+                format_tokens(*tokens),
+                calls,
+                if *calls == 1 { "" } else { "s" }
+            );
+        }
+    }
+
+    let advice = advice(&usage, level);
+    if advice.is_empty() {
+        println!("\nPlenty of room: default rtk modes are fine.");
+    } else {
+        println!("\nAdvice:");
+        for line in advice {
+            println!("  - {}", line);
+        }
+    }
+    if verbose > 0 {
+        eprintln!(
+            "thresholds: warn {}%, critical {}% of {} tokens",
+            config.warn_pct, config.critical_pct, config.context_window
+        );
+    }
+    Ok(())
+}
+
+/// Hook mode: stay silent until the session is past the warn threshold.
+fn run_hook(config: &BudgetConfig) -> Result<()> {
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+    let payload: Value = serde_json::from_str(&input).unwrap_or_default();
+    let Some(path) = payload["transcript_path"].as_str() else {
+        return Ok(());
+    };
+    let Ok(usage) = usage(Path::new(path)) else {
+        return Ok(());
+    };
+    let level = level(usage.context, config);
+    if level == Level::Ok {
+        return Ok(());
+    }
+    let message = format!(
+        "rtk budget: {:.0}% of the context window used. Prefer: {}",
+        percent(usage.context, config),
+        advice(&usage, level).join("; ")
+    );
+    ::std::println!("{}", serde_json::json!({ "systemMessage": message }));
+    Ok(())
+}
+
+/// The most recently written transcript of the current project.
+fn latest_transcript() -> Result<PathBuf> {
+    let cwd = std::env::current_dir().context("Failed to read current directory")?;
+    let dir = ClaudeProvider::projects_dir()?
+        .join(ClaudeProvider::encode_project_path(&cwd.to_string_lossy()));
+    fs::read_dir(&dir)
+        .with_context(|| {
+            format!(
+                "No Claude Code sessions for this project ({})",
+                dir.display()
+            )
+        })?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+        .max_by_key(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+        .with_context(|| format!("No session transcript in {}", dir.display()))
+}
+
+fn usage(path: &Path) -> Result<Usage> {
+    let file =
+        fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut usage = Usage::default();
+    let mut estimated = 0u64;
+    let mut tools: HashMap<String, String> = HashMap::new();
+
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        let Ok(entry) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        let content = entry.pointer("/message/content");
+        estimated += content.map_or(0, |c| estimate_tokens(&c.to_string()) as u64);
+
+        if let Some(reported) = entry.pointer("/message/usage") {
+            let field = |name: &str| reported[name].as_u64().unwrap_or(0);
+            usage.context = field("input_tokens")
+                + field("cache_creation_input_tokens")
+                + field("cache_read_input_tokens")
+                + field("output_tokens");
+            usage.reported = true;
+        }
+        for block in content.and_then(Value::as_array).into_iter().flatten() {
+            match block["type"].as_str() {
+                Some("tool_use") => {
+                    if let Some(id) = block["id"].as_str() {
+                        tools.insert(id.to_string(), tool_label(block));
+                    }
+                }
+                Some("tool_result") => {
+                    let label = block["tool_use_id"]
+                        .as_str()
+                        .and_then(|id| tools.get(id))
+                        .cloned()
+                        .unwrap_or_else(|| "other".to_string());
+                    let text = match &block["content"] {
+                        Value::String(text) => text.clone(),
+                        other => other.to_string(),
+                    };
+                    let entry = usage.consumers.entry(label).or_default();
+                    entry.0 += estimate_tokens(&text);
+                    entry.1 += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+    if !usage.reported {
+        usage.context = estimated;
+    }
+    Ok(usage)
+}
+
+/// `Read`, `Grep`, ... or `Bash: <program>` (`Bash: rtk read` for rtk).
+fn tool_label(block: &Value) -> String {
+    let name = block["name"].as_str().unwrap_or("other");
+    let Some(command) = block.pointer("/input/command").and_then(Value::as_str) else {
+        return name.to_string();
+    };
+    // `cd dir && cargo test` is a cargo call
+    let command = command
+        .split(['&', ';'])
+        .map(str::trim)
+        .find(|segment| !segment.is_empty() && !segment.starts_with("cd "))
+        .unwrap_or(command);
+    let mut words = command
+        .split_whitespace()
+        .skip_while(|word| word.contains('='));
+    match words.next() {
+        Some("rtk") => match words.next() {
+            Some(sub) => format!("{}: rtk {}", name, sub),
+            None => format!("{}: rtk", name),
+        },
+        Some(program) => format!(
+            "{}: {}",
+            name,
+            program.rsplit('/').next().unwrap_or(program)
+        ),
+        None => name.to_string(),
+    }
+}
+
+fn top_consumers(usage: &Usage, n: usize) -> Vec<(String, usize, usize)> {
+    let mut consumers: Vec<(String, usize, usize)> = usage
+        .consumers
+        .iter()
+        .map(|(tool, (tokens, calls))| (tool.clone(), *tokens, *calls))
+        .collect();
+    consumers.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    consumers.truncate(n);
+    consumers
+}
+
+fn percent(context: u64, config: &BudgetConfig) -> f64 {
+    context as f64 * 100.0 / config.context_window.max(1) as f64
+}
+
+fn level(context: u64, config: &BudgetConfig) -> Level {
+    let pct = percent(context, config);
+    if pct >= config.critical_pct as f64 {
+        Level::Critical
+    } else if pct >= config.warn_pct as f64 {
+        Level::Warn
+    } else {
+        Level::Ok
+    }
+}
+
+/// Advice for the largest consumers first, then the session-wide modes.
+fn advice(usage: &Usage, level: Level) -> Vec<String> {
+    if level == Level::Ok {
+        return Vec::new();
+    }
+    let mut lines: Vec<String> = Vec::new();
+    for (tool, _, _) in top_consumers(usage, 3) {
+        if let Some((_, text)) = ADVICE
+            .iter()
+            .find(|(tools, _)| tools.contains(&tool.as_str()))
+        {
+            if !lines.iter().any(|line| line == text) {
+                lines.push(text.to_string());
+            }
+        }
+    }
+    lines.push(
+        "set `RTK_PROFILE=minimal` (or `rtk --profile minimal`) for the tersest output".into(),
+    );
+    if level == Level::Critical {
+        lines.push("compact the session (/compact) or start a fresh one before large reads".into());
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn transcript(lines: &[&str]) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        for line in lines {
+            writeln!(file, "{}", line).unwrap();
+        }
+        file
+    }
+
+    #[test]
+    fn test_usage_from_last_turn_and_consumers() {
+        let big = "x".repeat(4000);
+        let file = transcript(&[
+            r#"{"type":"assistant","message":{"usage":{"input_tokens":10,"cache_read_input_tokens":500},"content":[{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"cd app && RUST_LOG=1 cargo test"}}]}}"#,
+            &format!(
+                r#"{{"type":"user","message":{{"content":[{{"type":"tool_result","tool_use_id":"t1","content":"{}"}}]}}}}"#,
+                big
+            ),
+            r#"{"type":"assistant","message":{"usage":{"input_tokens":5,"cache_creation_input_tokens":1000,"cache_read_input_tokens":90000,"output_tokens":200},"content":[{"type":"tool_use","id":"t2","name":"Read","input":{"file_path":"a.rs"}}]}}"#,
+            r#"{"type":"user","message":{"content":[{"type":"tool_result","tool_use_id":"t2","content":[{"type":"text","text":"fn main() {}"}]}]}}"#,
+        ]);
+        let usage = usage(file.path()).unwrap();
+        assert!(usage.reported);
+        assert_eq!(usage.context, 91_205);
+        assert_eq!(usage.consumers["Bash: cargo"], (1000, 1));
+        assert_eq!(usage.consumers["Read"].1, 1);
+        assert_eq!(top_consumers(&usage, 1)[0].0, "Bash: cargo");
+    }
+
+    #[test]
+    fn test_usage_estimated_without_reports() {
+        let file = transcript(&[
+            r#"{"type":"user","message":{"content":"hello there"}}"#,
+            "not json",
+        ]);
+        let usage = usage(file.path()).unwrap();
+        assert!(!usage.reported);
+        assert!(usage.context > 0);
+    }
+
+    #[test]
+    fn test_levels_and_advice() {
+        let config = BudgetConfig::default();
+        assert_eq!(level(10_000, &config), Level::Ok);
+        assert_eq!(level(100_000, &config), Level::Warn);
+        assert_eq!(level(170_000, &config), Level::Critical);
+
+        let mut usage = Usage::default();
+        usage.consumers.insert("Read".into(), (40_000, 12));
+        usage.consumers.insert("Bash: rtk git".into(), (1_000, 3));
+        assert!(advice(&usage, Level::Ok).is_empty());
+        let warn = advice(&usage, Level::Warn);
+        assert!(warn[0].contains("rtk read"));
+        assert_eq!(warn.len(), 2);
+        assert!(advice(&usage, Level::Critical)
+            .last()
+            .unwrap()
+            .contains("/compact"));
+    }
+}
@@ -0,0 +1,532 @@
+//! `rtk bundle` — JS bundle sizes from webpack `stats.json`, a Vite
+//! manifest, or rollup-plugin-visualizer raw data, with deltas against the
+//! previous run (baselines shared with `rtk bloat`).
+
+use crate::bloat_cmd::{self, format_size, Baseline};
+use crate::tracking;
+use crate::utils::truncate;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Searched in order when no file is given.
+const DEFAULT_FILES: &[&str] = &[
+    "stats.json",
+    "dist/stats.json",
+    "build/stats.json",
+    "dist/.vite/manifest.json",
+    "build/.vite/manifest.json",
+    "dist/manifest.json",
+];
+
+/// Changes smaller than this are not worth a note.
+const MIN_DELTA: i64 = 1024;
+
+#[derive(Debug, Default, PartialEq)]
+struct Bundle {
+    format: &'static str,
+    /// Emitted files and their sizes (source maps excluded)
+    assets: Vec<(String, u64)>,
+    /// Entry name, size of everything it loads up front, file count
+    entries: Vec<(String, u64, usize)>,
+    /// Source modules and their size in the output
+    modules: Vec<(String, u64)>,
+}
+
+/// `react-dom` for `./node_modules/react-dom/cjs/x.js`, `@scope/pkg` for
+/// scoped packages, plus the install path up to and including the package.
+fn package_of(module: &str) -> Option<(String, String)> {
+    let idx = module.rfind("node_modules/")?;
+    let rest = &module[idx + "node_modules/".len()..];
+    let mut parts = rest.split('/');
+    let first = parts.next()?;
+    let name = if first.starts_with('@') {
+        format!("{}/{}", first, parts.next()?)
+    } else {
+        first.to_string()
+    };
+    let install = module[..idx + "node_modules/".len() + name.len()]
+        .trim_start_matches("./")
+        .to_string();
+    Some((name, install))
+}
+
+fn is_map(name: &str) -> bool {
+    name.ends_with(".map")
+}
+
+/// webpack stats, including multi-compiler `children`.
+fn parse_webpack(stats: &Value, bundle: &mut Bundle) {
+    let mut sizes: HashMap<&str, u64> = HashMap::new();
+    for asset in stats["assets"].as_array().into_iter().flatten() {
+        let (Some(name), Some(size)) = (asset["name"].as_str(), asset["size"].as_u64()) else {
+            continue;
+        };
+        if !is_map(name) {
+            sizes.insert(name, size);
+            bundle.assets.push((name.to_string(), size));
+        }
+    }
+
+    if let Some(entrypoints) = stats["entrypoints"].as_object() {
+        for (name, entry) in entrypoints {
+            let mut total = 0;
+            let mut files = 0;
+            for asset in entry["assets"].as_array().into_iter().flatten() {
+                // webpack 5: {name, size}; webpack 4: "file.js"
+                let file = asset["name"].as_str().or_else(|| asset.as_str());
+                let Some(file) = file.filter(|f| !is_map(f)) else {
+                    continue;
+                };
+                total += asset["size"]
+                    .as_u64()
+                    .or_else(|| sizes.get(file).copied())
+                    .unwrap_or(0);
+                files += 1;
+            }
+            bundle.entries.push((name.clone(), total, files));
+        }
+    }
+
+    for module in stats["modules"].as_array().into_iter().flatten() {
+        // Concatenated modules list their parts
+        let parts = module["modules"].as_array().filter(|m| !m.is_empty());
+        for m in parts.map_or_else(|| vec![module], |p| p.iter().collect()) {
+            if let (Some(name), Some(size)) = (m["name"].as_str(), m["size"].as_u64()) {
+                bundle
+                    .modules
+                    .push((name.trim_start_matches("./").to_string(), size));
+            }
+        }
+    }
+
+    for child in stats["children"].as_array().into_iter().flatten() {
+        parse_webpack(child, bundle);
+    }
+}
+
+/// Vite `manifest.json`: chunk graph only, sizes come from the files in
+/// `out_dir`. An entry's size includes its static imports and CSS.
+fn parse_vite_manifest(manifest: &Value, out_dir: &Path) -> Bundle {
+    let mut bundle = Bundle {
+        format: "vite",
+        ..Default::default()
+    };
+    let Some(chunks) = manifest.as_object() else {
+        return bundle;
+    };
+    let size = |file: &str| {
+        std::fs::metadata(out_dir.join(file))
+            .map(|m| m.len())
+            .unwrap_or(0)
+    };
+    let files_of = |chunk: &Value| -> Vec<String> {
+        let mut files: Vec<String> = chunk["file"]
+            .as_str()
+            .map(str::to_string)
+            .into_iter()
+            .collect();
+        for key in ["css", "assets"] {
+            for f in chunk[key].as_array().into_iter().flatten() {
+                files.extend(f.as_str().map(str::to_string));
+            }
+        }
+        files
+    };
+
+    let mut seen = BTreeSet::new();
+    for chunk in chunks.values() {
+        for file in files_of(chunk) {
+            if seen.insert(file.clone()) && !is_map(&file) {
+                let bytes = size(&file);
+                bundle.assets.push((file, bytes));
+            }
+        }
+    }
+
+    for (key, chunk) in chunks {
+        if !chunk["isEntry"].as_bool().unwrap_or(false) {
+            continue;
+        }
+        let mut files = BTreeSet::new();
+        let mut stack = vec![key.as_str()];
+        let mut visited = BTreeSet::new();
+        while let Some(key) = stack.pop() {
+            if !visited.insert(key) {
+                continue;
+            }
+            let chunk = &chunks[key];
+            files.extend(files_of(chunk));
+            for import in chunk["imports"].as_array().into_iter().flatten() {
+                if let Some(import) = import.as_str().filter(|i| chunks.contains_key(*i)) {
+                    stack.push(import);
+                }
+            }
+        }
+        let total = files.iter().map(|f| size(f)).sum();
+        let name = chunk["name"].as_str().unwrap_or(key);
+        bundle.entries.push((name.to_string(), total, files.len()));
+    }
+    bundle
+}
+
+/// rollup-plugin-visualizer `--template raw-data` output.
+fn parse_visualizer(data: &Value) -> Bundle {
+    let mut bundle = Bundle {
+        format: "rollup",
+        ..Default::default()
+    };
+    let parts = &data["nodeParts"];
+    let mut assets: BTreeMap<String, u64> = BTreeMap::new();
+    for meta in data["nodeMetas"]
+        .as_object()
+        .into_iter()
+        .flat_map(|m| m.values())
+    {
+        let Some(id) = meta["id"].as_str() else {
+            continue;
+        };
+        let mut total = 0;
+        for (file, part) in meta["moduleParts"].as_object().into_iter().flatten() {
+            let length = part
+                .as_str()
+                .and_then(|uid| parts[uid]["renderedLength"].as_u64())
+                .unwrap_or(0);
+            *assets.entry(file.clone()).or_default() += length;
+            total += length;
+        }
+        bundle
+            .modules
+            .push((id.trim_start_matches('/').to_string(), total));
+    }
+    bundle.assets = assets.into_iter().collect();
+    bundle
+}
+
+fn parse(json: &str, path: &Path) -> Result<Bundle> {
+    let value: Value = serde_json::from_str(json).context("Failed to parse bundle stats JSON")?;
+    if value.get("nodeMetas").is_some() {
+        return Ok(parse_visualizer(&value));
+    }
+    if ["assets", "modules", "chunks", "children", "entrypoints"]
+        .iter()
+        .any(|k| value.get(*k).is_some())
+    {
+        let mut bundle = Bundle {
+            format: "webpack",
+            ..Default::default()
+        };
+        parse_webpack(&value, &mut bundle);
+        return Ok(bundle);
+    }
+    // dist/.vite/manifest.json → dist; dist/manifest.json (Vite 4) → dist
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let out_dir = if dir.ends_with(".vite") {
+        dir.parent().unwrap_or(dir)
+    } else {
+        dir
+    };
+    Ok(parse_vite_manifest(&value, out_dir))
+}
+
+fn delta_note(current: u64, previous: Option<u64>) -> String {
+    match previous {
+        None => " (new)".to_string(),
+        Some(old) => {
+            let delta = current as i64 - old as i64;
+            if delta.abs() < MIN_DELTA {
+                String::new()
+            } else if delta > 0 {
+                format!(" (+{})", format_size(delta as u64))
+            } else {
+                format!(" (-{})", format_size(delta.unsigned_abs()))
+            }
+        }
+    }
+}
+
+/// Baseline entries: `entry:<name>` and `pkg:<name>`; asset names carry
+/// content hashes so they are not compared.
+fn baseline_sizes(bundle: &Bundle, packages: &[(String, u64)]) -> HashMap<String, u64> {
+    bundle
+        .entries
+        .iter()
+        .map(|(name, size, _)| (format!("entry:{}", name), *size))
+        .chain(packages.iter().map(|(p, s)| (format!("pkg:{}", p), *s)))
+        .collect()
+}
+
+/// Packages by total module size, largest first.
+fn packages(bundle: &Bundle) -> Vec<(String, u64)> {
+    let mut sizes: HashMap<String, u64> = HashMap::new();
+    for (module, size) in &bundle.modules {
+        if let Some((name, _)) = package_of(module) {
+            *sizes.entry(name).or_default() += size;
+        }
+    }
+    let mut sizes: Vec<(String, u64)> = sizes.into_iter().collect();
+    sizes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    sizes
+}
+
+fn format_report(bundle: &Bundle, baseline: Option<&Baseline>, top: usize) -> String {
+    let mut out = Vec::new();
+    let total: u64 = bundle.assets.iter().map(|(_, s)| s).sum();
+    let previous = |key: String| baseline.map(|b| b.sizes.get(&key).copied());
+    let mut header = format!(
+        "📦 {} bundle: {} in {} assets",
+        bundle.format,
+        format_size(total),
+        bundle.assets.len()
+    );
+    if let Some(base) = baseline {
+        let note = delta_note(total, Some(base.total));
+        let note = note.trim();
+        let note = if note.is_empty() { "(±0)" } else { note };
+        header.push_str(&format!(" {} vs baseline {}", note, base.saved));
+    }
+    out.push(header);
+
+    let mut entries: Vec<&(String, u64, usize)> = bundle.entries.iter().collect();
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    if !entries.is_empty() {
+        out.push("Entries:".to_string());
+        let width = entries.iter().map(|e| e.0.len()).max().unwrap_or(0);
+        for (name, size, files) in entries {
+            let note = previous(format!("entry:{}", name))
+                .map(|p| delta_note(*size, p))
+                .unwrap_or_default();
+            out.push(format!(
+                "  {:width$}  {:>10}  ({} files){}",
+                name,
+                format_size(*size),
+                files,
+                note
+            ));
+        }
+    }
+
+    let mut assets: Vec<&(String, u64)> = bundle.assets.iter().collect();
+    assets.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    // Without entrypoints the assets are the only per-output view
+    if bundle.modules.is_empty() || bundle.entries.is_empty() {
+        out.push("Largest assets:".to_string());
+        for (name, size) in assets.iter().take(top) {
+            out.push(format!("  {:>10}  {}", format_size(*size), name));
+        }
+        if assets.len() > top {
+            out.push(format!("  ... +{} more", assets.len() - top));
+        }
+    }
+
+    let mut modules: Vec<&(String, u64)> = bundle.modules.iter().collect();
+    modules.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    if !modules.is_empty() {
+        out.push(format!("Largest modules (of {}):", modules.len()));
+        for (name, size) in modules.iter().take(top) {
+            out.push(format!(
+                "  {:>10}  {}",
+                format_size(*size),
+                truncate(name, 90)
+            ));
+        }
+    }
+
+    let packages = packages(bundle);
+    if !packages.is_empty() {
+        let list: Vec<String> = packages
+            .iter()
+            .take(top)
+            .map(|(name, size)| {
+                let note = previous(format!("pkg:{}", name))
+                    .map(|p| delta_note(*size, p))
+                    .unwrap_or_default();
+                format!("{} {}{}", name, format_size(*size), note)
+            })
+            .collect();
+        let more = if packages.len() > top {
+            format!(", +{} more", packages.len() - top)
+        } else {
+            String::new()
+        };
+        out.push(format!("By package: {}{}", list.join(", "), more));
+    }
+
+    // One package installed at several paths ships several copies
+    let mut installs: BTreeMap<String, BTreeMap<String, u64>> = BTreeMap::new();
+    for (module, size) in &bundle.modules {
+        if let Some((name, install)) = package_of(module) {
+            *installs
+                .entry(name)
+                .or_default()
+                .entry(install)
+                .or_default() += size;
+        }
+    }
+    let duplicates: Vec<(&String, &BTreeMap<String, u64>)> = installs
+        .iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .collect();
+    if !duplicates.is_empty() {
+        out.push(format!("Duplicate packages ({}):", duplicates.len()));
+        for (name, paths) in duplicates {
+            let total: u64 = paths.values().sum();
+            let list: Vec<&str> = paths.keys().map(String::as_str).collect();
+            out.push(format!(
+                "  {} ×{} ({}): {}",
+                name,
+                paths.len(),
+                format_size(total),
+                list.join(", ")
+            ));
+        }
+    }
+
+    out.join("\n")
+}
+
+pub fn run(file: Option<&Path>, top: usize, save: bool, verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let path: PathBuf = match file {
+        Some(file) => file.to_path_buf(),
+        None => DEFAULT_FILES
+            .iter()
+            .map(PathBuf::from)
+            .find(|p| p.is_file())
+            .context(
+                "No stats file found. Pass one, e.g. webpack --json > stats.json, \
+                 or build Vite with build.manifest",
+            )?,
+    };
+    let json = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let bundle = parse(&json, &path)?;
+    if verbose > 0 {
+        eprintln!(
+            "bundle: {} format, {} assets, {} modules from {}",
+            bundle.format,
+            bundle.assets.len(),
+            bundle.modules.len(),
+            path.display()
+        );
+    }
+
+    let canonical = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+    let baseline_path = bloat_cmd::baseline_path("bundle", &[&canonical.to_string_lossy()]);
+    let baseline = baseline_path.as_deref().and_then(bloat_cmd::load_baseline);
+    let mut filtered = format_report(&bundle, baseline.as_ref(), top);
+    if let Some(baseline_path) = baseline_path.filter(|_| save || baseline.is_none()) {
+        let total = bundle.assets.iter().map(|(_, s)| s).sum();
+        bloat_cmd::save_baseline(
+            &baseline_path,
+            total,
+            baseline_sizes(&bundle, &packages(&bundle)),
+        );
+        filtered.push_str("\n(baseline saved)");
+    }
+    println!("{}", filtered);
+
+    timer.track(
+        &format!("cat {}", path.display()),
+        &format!("rtk bundle {}", path.display()),
+        &json,
+        &filtered,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WEBPACK: &str = r#"{
+        "assets": [
+            {"name": "main.3f2a.js", "size": 409600},
+            {"name": "main.3f2a.js.map", "size": 999999},
+            {"name": "vendor.91bc.js", "size": 307200},
+            {"name": "admin.77aa.js", "size": 51200}
+        ],
+        "entrypoints": {
+            "main": {"assets": [{"name": "vendor.91bc.js", "size": 307200}, {"name": "main.3f2a.js", "size": 409600}]},
+            "admin": {"assets": ["vendor.91bc.js", "admin.77aa.js"]}
+        },
+        "modules": [
+            {"name": "./node_modules/react-dom/cjs/react-dom.production.min.js", "size": 131072},
+            {"name": "./node_modules/lodash/lodash.js", "size": 71680},
+            {"name": "./node_modules/chart-lib/node_modules/lodash/lodash.js", "size": 71680},
+            {"name": "./src/index.tsx + 3 modules", "size": 2048, "modules": [
+                {"name": "./src/index.tsx", "size": 1024},
+                {"name": "./node_modules/@tanstack/query-core/build/index.js", "size": 1024}
+            ]}
+        ]
+    }"#;
+
+    #[test]
+    fn test_package_of() {
+        assert_eq!(
+            package_of("./node_modules/@tanstack/query-core/build/index.js"),
+            Some((
+                "@tanstack/query-core".to_string(),
+                "node_modules/@tanstack/query-core".to_string()
+            ))
+        );
+        assert_eq!(
+            package_of("./node_modules/a/node_modules/lodash/x.js").map(|p| p.1),
+            Some("node_modules/a/node_modules/lodash".to_string())
+        );
+        assert_eq!(package_of("./src/index.tsx"), None);
+    }
+
+    #[test]
+    fn test_webpack_report_with_baseline() {
+        let bundle = parse(WEBPACK, Path::new("stats.json")).unwrap();
+        assert_eq!(bundle.assets.len(), 3);
+        assert_eq!(bundle.modules.len(), 5);
+        let baseline = Baseline {
+            saved: "2026-10-01 10:00".to_string(),
+            total: 700_000,
+            sizes: HashMap::from([
+                ("entry:main".to_string(), 600_000),
+                ("entry:admin".to_string(), 358_000),
+                ("pkg:lodash".to_string(), 71680),
+            ]),
+        };
+        let out = format_report(&bundle, Some(&baseline), 3);
+        assert_eq!(
+            out,
+            "📦 webpack bundle: 750.0 KiB in 3 assets (+66.4 KiB) vs baseline 2026-10-01 10:00
+Entries:
+  main    700.0 KiB  (2 files) (+114.1 KiB)
+  admin   350.0 KiB  (2 files)
+Largest modules (of 5):
+   128.0 KiB  node_modules/react-dom/cjs/react-dom.production.min.js
+    70.0 KiB  node_modules/chart-lib/node_modules/lodash/lodash.js
+    70.0 KiB  node_modules/lodash/lodash.js
+By package: lodash 140.0 KiB (+70.0 KiB), react-dom 128.0 KiB (new), @tanstack/query-core 1.0 KiB (new)
+Duplicate packages (1):
+  lodash ×2 (140.0 KiB): node_modules/chart-lib/node_modules/lodash, node_modules/lodash"
+        );
+    }
+
+    #[test]
+    fn test_vite_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("assets")).unwrap();
+        std::fs::write(dir.path().join("assets/index.js"), vec![b'x'; 3000]).unwrap();
+        std::fs::write(dir.path().join("assets/vendor.js"), vec![b'x'; 5000]).unwrap();
+        std::fs::write(dir.path().join("assets/index.css"), vec![b'x'; 1000]).unwrap();
+        std::fs::write(dir.path().join("assets/lazy.js"), vec![b'x'; 2000]).unwrap();
+        let manifest = r#"{
+            "index.html": {"file": "assets/index.js", "name": "index", "isEntry": true,
+                           "imports": ["_vendor.js"], "dynamicImports": ["src/lazy.ts"],
+                           "css": ["assets/index.css"]},
+            "_vendor.js": {"file": "assets/vendor.js"},
+            "src/lazy.ts": {"file": "assets/lazy.js", "isDynamicEntry": true}
+        }"#;
+        let bundle = parse(manifest, &dir.path().join(".vite/manifest.json")).unwrap();
+        assert_eq!(bundle.format, "vite");
+        assert_eq!(bundle.entries, vec![("index".to_string(), 9000, 3)]);
+        assert_eq!(bundle.assets.len(), 4);
+    }
+}
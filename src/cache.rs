@@ -0,0 +1,159 @@
+//! Content-addressed cache of filtered output.
+//!
+//! Keys hash everything the filtered result depends on — the wrapper, its
+//! options and the raw content (file bytes, `git status --porcelain`) — so a
+//! changed input can never hit a stale entry. Entries live as one JSON file
+//! per key under `<data dir>/rtk/cache/` and expire after `cache.ttl_secs`.
+//! A hit is flagged on the tracking record and reported by `rtk gain`.
+
+use crate::config::Config;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+
+static HIT: AtomicBool = AtomicBool::new(false);
+
+/// Cached result: the raw input (for savings tracking) and the filtered output.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub input: String,
+    pub output: String,
+}
+
+/// 64-bit FNV-1a over `parts`, separated so `["ab","c"]` != `["a","bc"]`.
+pub fn key(parts: &[&str]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for byte in part.bytes().chain(std::iter::once(0xff)) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+/// Cached entry for `key`, if enabled and not expired. Marks the run as a hit.
+pub fn get(key: &str) -> Option<Entry> {
+    let dir = cache_dir()?;
+    let entry = read_entry(&dir, key, ttl());
+    tracing::debug!(key, hit = entry.is_some(), "cache lookup");
+    let entry = entry?;
+    HIT.store(true, Ordering::Relaxed);
+    Some(entry)
+}
+
+/// Store `entry` under `key` and prune expired entries. Failures are ignored:
+/// the cache is an optimization, never a reason to fail a command.
+pub fn put(key: &str, entry: &Entry) {
+    if let Some(dir) = cache_dir() {
+        let _ = write_entry(&dir, key, entry);
+        prune(&dir, ttl());
+    }
+}
+
+/// Any other value stored under `key` (see walk.rs); not counted as a hit.
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    read_entry(&cache_dir()?, key, ttl())
+}
+
+/// [`put`] for any serializable value.
+pub fn store<T: Serialize>(key: &str, value: &T) {
+    if let Some(dir) = cache_dir() {
+        let _ = write_entry(&dir, key, value);
+        prune(&dir, ttl());
+    }
+}
+
+/// Whether this run was served from the cache.
+pub fn was_hit() -> bool {
+    HIT.load(Ordering::Relaxed)
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    let config = &Config::cached().cache;
+    if !config.enabled {
+        return None;
+    }
+    if let Ok(dir) = std::env::var("RTK_CACHE_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    Some(dirs::data_local_dir()?.join("rtk").join("cache"))
+}
+
+fn ttl() -> Duration {
+    Duration::from_secs(Config::cached().cache.ttl_secs)
+}
+
+fn read_entry<T: DeserializeOwned>(dir: &Path, key: &str, ttl: Duration) -> Option<T> {
+    let path = dir.join(key);
+    let age = path.metadata().ok()?.modified().ok()?.elapsed().ok()?;
+    if age > ttl {
+        return None;
+    }
+    serde_json::from_slice(&std::fs::read(path).ok()?).ok()
+}
+
+fn write_entry<T: Serialize>(dir: &Path, key: &str, entry: &T) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    // Write then rename so concurrent readers never see a partial entry
+    let tmp = dir.join(format!("{}.{}.tmp", key, std::process::id()));
+    std::fs::write(&tmp, serde_json::to_vec(entry)?)?;
+    std::fs::rename(tmp, dir.join(key))
+}
+
+/// Delete files in `dir` last modified more than `ttl` ago.
+pub(crate) fn prune(dir: &Path, ttl: Duration) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let now = SystemTime::now();
+    for entry in entries.flatten() {
+        let expired = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| now.duration_since(t).ok())
+            .is_some_and(|age| age > ttl);
+        if expired {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_is_stable_and_separated() {
+        assert_eq!(key(&["read", "abc"]), key(&["read", "abc"]));
+        assert_ne!(key(&["ab", "c"]), key(&["a", "bc"]));
+        assert_eq!(key(&[]).len(), 16);
+    }
+
+    #[test]
+    fn test_round_trip_and_expiry() {
+        let dir = tempfile::tempdir().unwrap();
+        let entry = Entry {
+            input: "raw".into(),
+            output: "filtered".into(),
+        };
+        write_entry(dir.path(), "k", &entry).unwrap();
+
+        assert_eq!(
+            read_entry(dir.path(), "k", Duration::from_secs(60)),
+            Some(entry)
+        );
+        assert_eq!(
+            read_entry::<Entry>(dir.path(), "missing", Duration::from_secs(60)),
+            None
+        );
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(read_entry::<Entry>(dir.path(), "k", Duration::ZERO), None);
+        prune(dir.path(), Duration::ZERO);
+        assert!(!dir.path().join("k").exists());
+    }
+}
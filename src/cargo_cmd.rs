@@ -1,5 +1,5 @@
+use crate::exit_code;
 use crate::tracking;
-use crate::utils::truncate;
 use anyhow::{Context, Result};
 use std::collections::HashMap;
 use std::ffi::OsString;
@@ -48,11 +48,12 @@ where
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
     let filtered = filter_fn(&raw);
     println!("{}", filtered);
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("cargo {} {}", subcommand, args.join(" ")),
         &format!("rtk cargo {} {}", subcommand, args.join(" ")),
         &raw,
@@ -60,7 +61,7 @@ where
     );
 
     if !output.status.success() {
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     Ok(())
@@ -270,7 +271,7 @@ fn filter_cargo_install(output: &str) -> String {
 }
 
 /// Filter cargo build/check output - strip "Compiling"/"Checking" lines, keep errors + summary
-fn filter_cargo_build(output: &str) -> String {
+pub(crate) fn filter_cargo_build(output: &str) -> String {
     let mut errors: Vec<String> = Vec::new();
     let mut warnings = 0;
     let mut error_count = 0;
@@ -459,7 +460,7 @@ impl AggregatedTestResult {
 }
 
 /// Filter cargo test output - show failures + summary only
-fn filter_cargo_test(output: &str) -> String {
+pub(crate) fn filter_cargo_test(output: &str) -> String {
     let mut failures: Vec<String> = Vec::new();
     let mut summary_lines: Vec<String> = Vec::new();
     let mut in_failure_section = false;
@@ -489,12 +490,13 @@ fn filter_cargo_test(output: &str) -> String {
         if in_failure_section {
             if line.starts_with("test result:") {
                 in_failure_section = false;
+                push_failure(&mut failures, &mut current_failure);
                 summary_lines.push(line.to_string());
+                continue;
             } else if line.starts_with("    ") || line.starts_with("---- ") {
                 current_failure.push(line.to_string());
             } else if line.trim().is_empty() && !current_failure.is_empty() {
-                failures.push(current_failure.join("\n"));
-                current_failure.clear();
+                push_failure(&mut failures, &mut current_failure);
             } else if !line.trim().is_empty() {
                 current_failure.push(line.to_string());
             }
@@ -506,9 +508,7 @@ fn filter_cargo_test(output: &str) -> String {
         }
     }
 
-    if !current_failure.is_empty() {
-        failures.push(current_failure.join("\n"));
-    }
+    push_failure(&mut failures, &mut current_failure);
 
     let mut result = String::new();
 
@@ -550,7 +550,7 @@ fn filter_cargo_test(output: &str) -> String {
         result.push_str(&format!("FAILURES ({}):\n", failures.len()));
         result.push_str("═══════════════════════════════════════\n");
         for (i, failure) in failures.iter().enumerate().take(10) {
-            result.push_str(&format!("{}. {}\n", i + 1, truncate(failure, 200)));
+            result.push_str(&format!("{}. {}\n", i + 1, truncate_failure(failure)));
         }
         if failures.len() > 10 {
             result.push_str(&format!("\n... +{} more failures\n", failures.len() - 10));
@@ -576,8 +576,33 @@ fn filter_cargo_test(output: &str) -> String {
     result.trim().to_string()
 }
 
+/// Lines of a failure shown: the panic, its message and assertion values.
+const MAX_FAILURE_LINES: usize = 12;
+
+/// Add a failure block, unless it is the closing list of failed test names
+/// (already shown with their panics).
+fn push_failure(failures: &mut Vec<String>, current: &mut Vec<String>) {
+    let names_only = current.iter().all(|l| l.starts_with("    "));
+    if !current.is_empty() && !names_only {
+        failures.push(current.join("\n"));
+    }
+    current.clear();
+}
+
+fn truncate_failure(failure: &str) -> String {
+    let lines: Vec<&str> = failure.lines().collect();
+    if lines.len() <= MAX_FAILURE_LINES {
+        return failure.to_string();
+    }
+    format!(
+        "{}\n  ... +{} lines",
+        lines[..MAX_FAILURE_LINES].join("\n"),
+        lines.len() - MAX_FAILURE_LINES
+    )
+}
+
 /// Filter cargo clippy output - group warnings by lint rule
-fn filter_cargo_clippy(output: &str) -> String {
+pub(crate) fn filter_cargo_clippy(output: &str) -> String {
     let mut by_rule: HashMap<String, Vec<String>> = HashMap::new();
     let mut error_count = 0;
     let mut warning_count = 0;
@@ -685,13 +710,13 @@ pub fn run_passthrough(args: &[OsString], verbose: u8) -> Result<()> {
         .context("Failed to run cargo")?;
 
     let args_str = tracking::args_display(args);
-    timer.track_passthrough(
+    timer.with_exit_code(status).track_passthrough(
         &format!("cargo {}", args_str),
         &format!("rtk cargo {} (passthrough)", args_str),
     );
 
     if !status.success() {
-        std::process::exit(status.code().unwrap_or(1));
+        exit_code::exit_with(status);
     }
     Ok(())
 }
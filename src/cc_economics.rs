@@ -4,7 +4,7 @@
 //! dual-metric economic impact reporting with blended and active cost-per-token.
 
 use anyhow::{Context, Result};
-use chrono::NaiveDate;
+use chrono::{Datelike, NaiveDate};
 use serde::Serialize;
 use std::collections::HashMap;
 
@@ -243,10 +243,10 @@ fn merge_weekly(cc: Option<Vec<CcusagePeriod>>, rtk: Vec<WeekStats>) -> Vec<Peri
         }
     }
 
-    // Merge rtk data (week_start = legacy Saturday "2026-01-18")
-    // Convert Saturday to Monday for alignment
+    // Merge rtk data (week_start = display.week_start, Monday or Sunday)
+    // Align to the Monday starting most of the week
     for entry in rtk {
-        let monday_key = match convert_saturday_to_monday(&entry.week_start) {
+        let monday_key = match align_to_monday(&entry.week_start) {
             Some(m) => m,
             None => {
                 eprintln!("⚠️  Invalid week_start format: {}", entry.week_start);
@@ -299,14 +299,13 @@ fn merge_monthly(cc: Option<Vec<CcusagePeriod>>, rtk: Vec<MonthStats>) -> Vec<Pe
 
 // ── Helpers ──
 
-/// Convert Saturday week_start (legacy rtk) to ISO Monday
-/// Example: "2026-01-18" (Sat) -> "2026-01-20" (Mon)
-fn convert_saturday_to_monday(saturday: &str) -> Option<String> {
-    let sat_date = NaiveDate::parse_from_str(saturday, "%Y-%m-%d").ok()?;
-
-    // rtk uses Saturday as week start, ISO uses Monday
-    // Saturday + 2 days = Monday
-    let monday = sat_date + chrono::TimeDelta::try_days(2)?;
+/// Convert an rtk week_start to the ISO Monday ccusage keys weeks by: the
+/// date itself for ISO weeks, the next day for US (Sunday) weeks
+/// Example: "2026-01-18" (Sun) -> "2026-01-19" (Mon)
+fn align_to_monday(week_start: &str) -> Option<String> {
+    let date = NaiveDate::parse_from_str(week_start, "%Y-%m-%d").ok()?;
+    let days = (7 - date.weekday().num_days_from_monday()) % 7;
+    let monday = date + chrono::TimeDelta::try_days(days as i64)?;
 
     Some(monday.format("%Y-%m-%d").to_string())
 }
@@ -713,7 +712,7 @@ fn export_json(
         export.monthly = Some(periods);
     }
 
-    println!(
+    println_verbatim!(
         "{}",
         serde_json::to_string_pretty(&export)
             .context("Failed to serialize economics data to JSON")?
@@ -729,7 +728,7 @@ fn export_csv(
     all: bool,
 ) -> Result<()> {
     // Header (new columns: input_tokens, output_tokens, cache_create, cache_read, weighted_savings)
-    println!("period,spent,input_tokens,output_tokens,cache_create,cache_read,active_tokens,total_tokens,saved_tokens,weighted_savings,active_savings,blended_savings,rtk_commands");
+    println_verbatim!("period,spent,input_tokens,output_tokens,cache_create,cache_read,active_tokens,total_tokens,saved_tokens,weighted_savings,active_savings,blended_savings,rtk_commands");
 
     if all || daily {
         let cc = ccusage::fetch(Granularity::Daily)
@@ -808,7 +807,7 @@ fn print_csv_row(p: &PeriodEconomics) {
         .unwrap_or_default();
     let cmds = p.rtk_commands.map(|c| c.to_string()).unwrap_or_default();
 
-    println!(
+    println_verbatim!(
         "{},{},{},{},{},{},{},{},{},{},{},{},{}",
         p.label,
         spent,
@@ -831,15 +830,20 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_convert_saturday_to_monday() {
-        // Saturday Jan 18 -> Monday Jan 20
+    fn test_align_to_monday() {
+        // US week: Sunday Jan 18 -> Monday Jan 19
+        assert_eq!(
+            align_to_monday("2026-01-18"),
+            Some("2026-01-19".to_string())
+        );
+        // ISO week already starts on Monday
         assert_eq!(
-            convert_saturday_to_monday("2026-01-18"),
-            Some("2026-01-20".to_string())
+            align_to_monday("2026-01-19"),
+            Some("2026-01-19".to_string())
         );
 
         // Invalid format
-        assert_eq!(convert_saturday_to_monday("invalid"), None);
+        assert_eq!(align_to_monday("invalid"), None);
     }
 
     #[test]
@@ -929,6 +933,9 @@ mod tests {
             savings_pct: 50.0,
             total_time_ms: 0,
             avg_time_ms: 0,
+            p50_time_ms: 0,
+            p95_time_ms: 0,
+            max_time_ms: 0,
         }];
 
         let merged = merge_monthly(Some(cc), rtk);
@@ -969,6 +976,9 @@ mod tests {
             savings_pct: 50.0,
             total_time_ms: 0,
             avg_time_ms: 0,
+            p50_time_ms: 0,
+            p95_time_ms: 0,
+            max_time_ms: 0,
         }];
 
         let merged = merge_monthly(None, rtk);
@@ -989,6 +999,9 @@ mod tests {
                 savings_pct: 40.0,
                 total_time_ms: 0,
                 avg_time_ms: 0,
+                p50_time_ms: 0,
+                p95_time_ms: 0,
+                max_time_ms: 0,
             },
             MonthStats {
                 month: "2026-01".to_string(),
@@ -999,6 +1012,9 @@ mod tests {
                 savings_pct: 60.0,
                 total_time_ms: 0,
                 avg_time_ms: 0,
+                p50_time_ms: 0,
+                p95_time_ms: 0,
+                max_time_ms: 0,
             },
         ];
 
@@ -1,6 +1,28 @@
-use anyhow::Result;
+//! Layered configuration.
+//!
+//! Effective settings are merged, lowest to highest precedence, from:
+//! built-in defaults, `~/.config/rtk/config.toml`, the nearest `.rtk.toml`
+//! (searched from the current directory upwards), `RTK_<SECTION>__<KEY>`
+//! environment variables and `--config KEY=VALUE` flags.
+
+use crate::pipeline::PipelineRule;
+use crate::plugin::PluginConfig;
+use crate::profile::Profile;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use toml::{Table, Value};
+
+/// Per-project override file, searched from the current directory upwards.
+const PROJECT_CONFIG_FILE: &str = ".rtk.toml";
+
+/// `--config KEY=VALUE` pairs from the command line, set once at startup.
+static CLI_OVERRIDES: OnceLock<Vec<(String, Value)>> = OnceLock::new();
+
+/// Effective config, loaded on first use by hot paths (token estimation).
+static CACHED: OnceLock<Config> = OnceLock::new();
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Config {
@@ -10,6 +32,34 @@ pub struct Config {
     pub display: DisplayConfig,
     #[serde(default)]
     pub filters: FilterConfig,
+    #[serde(default)]
+    pub output: OutputConfig,
+    #[serde(default)]
+    pub cache: CacheConfig,
+    #[serde(default)]
+    pub execution: ExecutionConfig,
+    #[serde(default)]
+    pub rewrite: RewriteConfig,
+    #[serde(default)]
+    pub policy: PolicyConfig,
+    #[serde(default)]
+    pub budget: BudgetConfig,
+    #[serde(default)]
+    pub digest: DigestConfig,
+    #[serde(default)]
+    pub team: TeamConfig,
+    /// Command shorthands, e.g. `alias.pt = "pytest -x -q"`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub alias: BTreeMap<String, String>,
+    /// Per-command options, e.g. `[commands.git]`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub commands: BTreeMap<String, Table>,
+    /// Regex filter rules per rtk subcommand, e.g. `[[pipelines.cargo]]`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub pipelines: BTreeMap<String, Vec<PipelineRule>>,
+    /// External summarizers for commands without a builtin, e.g. `[plugins.terraform]`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub plugins: BTreeMap<String, PluginConfig>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,6 +68,22 @@ pub struct TrackingConfig {
     pub history_days: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub database_path: Option<PathBuf>,
+    /// Anonymize paths, URLs, repo names and username before storing commands
+    #[serde(default)]
+    pub redact: bool,
+    /// Commands never recorded (glob patterns, e.g. "rtk curl*", "*secret*")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_commands: Vec<String>,
+    /// Keep a sample of raw vs filtered output per record (`rtk gain inspect`)
+    #[serde(default)]
+    pub samples: bool,
+    /// Characters kept from each side of a sample (0: sizes and hashes only)
+    #[serde(default = "default_sample_chars")]
+    pub sample_chars: usize,
+}
+
+fn default_sample_chars() -> usize {
+    2000
 }
 
 impl Default for TrackingConfig {
@@ -26,6 +92,10 @@ impl Default for TrackingConfig {
             enabled: true,
             history_days: 90,
             database_path: None,
+            redact: false,
+            exclude_commands: Vec::new(),
+            samples: false,
+            sample_chars: default_sample_chars(),
         }
     }
 }
@@ -35,6 +105,17 @@ pub struct DisplayConfig {
     pub colors: bool,
     pub emoji: bool,
     pub max_width: usize,
+    /// Timezone for daily/weekly buckets and times: "local", "UTC",
+    /// "+05:30" or an IANA name like "Europe/Paris"
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// First day of the week in weekly views
+    #[serde(default)]
+    pub week_start: WeekStart,
+}
+
+fn default_timezone() -> String {
+    "local".to_string()
 }
 
 impl Default for DisplayConfig {
@@ -43,6 +124,214 @@ impl Default for DisplayConfig {
             colors: true,
             emoji: true,
             max_width: 120,
+            timezone: default_timezone(),
+            week_start: WeekStart::default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    /// Monday (ISO 8601)
+    #[default]
+    #[serde(alias = "monday")]
+    Iso,
+    /// Sunday (US)
+    #[serde(alias = "sunday")]
+    Us,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OutputConfig {
+    /// Token estimation heuristic used for savings tracking
+    #[serde(default)]
+    pub tokenizer: Tokenizer,
+    /// Output profile: human, agent, minimal (overridden by --profile / RTK_PROFILE)
+    #[serde(default)]
+    pub profile: Profile,
+    /// Open output with elided sections in an interactive pager (terminal only; same as --pager)
+    #[serde(default)]
+    pub pager: bool,
+    /// Tag elided sections with IDs recallable via `rtk show <id>`
+    #[serde(default = "default_elided_ids")]
+    pub elided_ids: bool,
+    /// Piped output above this many tokens spills to a file, leaving a digest (0: off)
+    #[serde(default = "default_max_tokens")]
+    pub max_tokens: usize,
+    /// Output a summarizer can't parse falls back to generic compression instead of an error
+    #[serde(default = "default_lossy")]
+    pub lossy: bool,
+}
+
+fn default_elided_ids() -> bool {
+    true
+}
+
+fn default_max_tokens() -> usize {
+    10_000
+}
+
+fn default_lossy() -> bool {
+    true
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            tokenizer: Tokenizer::default(),
+            profile: Profile::default(),
+            pager: false,
+            elided_ids: default_elided_ids(),
+            max_tokens: default_max_tokens(),
+            lossy: default_lossy(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// Reuse filtered output when the input is unchanged (cat, git status)
+    pub enabled: bool,
+    /// Entries older than this are ignored and pruned
+    pub ttl_secs: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl_secs: 3600,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExecutionConfig {
+    /// Stop wrapped commands after this many seconds (0 = never);
+    /// `[commands.<name>] timeout_secs` overrides it per subcommand
+    pub timeout_secs: u64,
+    /// Wait between SIGTERM and SIGKILL on timeout
+    pub kill_grace_secs: u64,
+    /// Threads for multi-file work like `rtk cat a b c` (0 = one per CPU)
+    pub jobs: usize,
+}
+
+impl Default for ExecutionConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 0,
+            kill_grace_secs: 5,
+            jobs: 0,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RewriteConfig {
+    /// Let the agent hook rewrite commands through rtk
+    pub enabled: bool,
+    /// Programs the hook may rewrite (empty = every supported program)
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Commands never rewritten (glob patterns, e.g. "git push*", "*--force*")
+    #[serde(default)]
+    pub deny: Vec<String>,
+    /// Append every rewrite to `<data dir>/rtk/rewrites.jsonl`
+    pub log: bool,
+}
+
+impl Default for RewriteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            allow: Vec::new(),
+            deny: Vec::new(),
+            log: true,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PolicyConfig {
+    /// Built-in protection: deleting, file-writing, interactive and privileged commands
+    pub builtin: bool,
+    /// Extra commands never rewritten or summarized: globs ("terraform apply*")
+    /// or regexes prefixed with `re:` ("re:^kubectl (delete|apply)")
+    #[serde(default)]
+    pub never: Vec<String>,
+}
+
+impl Default for PolicyConfig {
+    fn default() -> Self {
+        Self {
+            builtin: true,
+            never: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    /// Context window of the agent's model, in tokens
+    pub context_window: u64,
+    /// Session usage (% of the window) past which `rtk budget` advises terser modes
+    pub warn_pct: u8,
+    /// Session usage past which it advises compacting the session
+    pub critical_pct: u8,
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            context_window: 200_000,
+            warn_pct: 50,
+            critical_pct: 80,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct DigestConfig {
+    /// Slack or Discord incoming webhook `rtk gain digest` posts to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<String>,
+    /// File `rtk gain digest` writes the summary to (overwritten each run)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct TeamConfig {
+    /// Aggregation server `rtk gain push` / `rtk gain team` talk to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    /// Bearer token shared by the team (prefer RTK_TEAM__TOKEN over the file)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Name pushed for this machine (default: anonymous hash of user@host)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub member: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Tokenizer {
+    /// ~4 characters per token (code, logs)
+    #[default]
+    Chars,
+    /// ~0.75 words per token (prose)
+    Words,
+}
+
+impl std::str::FromStr for Tokenizer {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "chars" => Ok(Self::Chars),
+            "words" => Ok(Self::Words),
+            _ => anyhow::bail!("Unknown tokenizer '{}': expected chars or words", s),
         }
     }
 }
@@ -51,6 +340,12 @@ impl Default for DisplayConfig {
 pub struct FilterConfig {
     pub ignore_dirs: Vec<String>,
     pub ignore_files: Vec<String>,
+    /// Paths dropped from ls/find/grep output (gitignore syntax, like `.rtkignore`)
+    #[serde(default)]
+    pub exclude_paths: Vec<String>,
+    /// Paths collapsed to counts in ls/find/grep and read aggressively by cat
+    #[serde(default)]
+    pub summarize_paths: Vec<String>,
 }
 
 impl Default for FilterConfig {
@@ -65,21 +360,52 @@ impl Default for FilterConfig {
                 "vendor".into(),
             ],
             ignore_files: vec!["*.lock".into(), "*.min.js".into(), "*.min.css".into()],
+            exclude_paths: Vec::new(),
+            summarize_paths: Vec::new(),
+        }
+    }
+}
+
+/// Where an effective setting came from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layer {
+    Default,
+    Global,
+    Project,
+    Env,
+    Cli,
+}
+
+impl Layer {
+    fn label(self) -> &'static str {
+        match self {
+            Layer::Default => "default",
+            Layer::Global => "global",
+            Layer::Project => "project",
+            Layer::Env => "env",
+            Layer::Cli => "cli",
         }
     }
 }
 
 impl Config {
+    /// Load the effective (layered) configuration.
     pub fn load() -> Result<Self> {
-        let path = get_config_path()?;
+        let (merged, _) = load_layers()?;
+        Ok(Value::Table(merged).try_into()?)
+    }
 
-        if path.exists() {
-            let content = std::fs::read_to_string(&path)?;
-            let config: Config = toml::from_str(&content)?;
-            Ok(config)
-        } else {
-            Ok(Config::default())
-        }
+    /// Effective config cached for the life of the process; defaults on error.
+    pub fn cached() -> &'static Config {
+        CACHED.get_or_init(|| {
+            Config::load().unwrap_or_else(|e| {
+                tracing::warn!(
+                    error = format!("{:#}", e),
+                    "config not loaded, using defaults"
+                );
+                Config::default()
+            })
+        })
     }
 
     pub fn save(&self) -> Result<()> {
@@ -101,25 +427,383 @@ impl Config {
     }
 }
 
-fn get_config_path() -> Result<PathBuf> {
+pub fn get_config_path() -> Result<PathBuf> {
     let config_dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
     Ok(config_dir.join("rtk").join("config.toml"))
 }
 
+/// Nearest `.rtk.toml` in `start` or one of its ancestors.
+fn find_project_config(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Record `--config KEY=VALUE` flags. Must run before the first config load.
+pub fn set_cli_overrides(pairs: &[String]) -> Result<()> {
+    let mut overrides = Vec::new();
+    for pair in pairs {
+        let (key, value) = pair
+            .split_once('=')
+            .with_context(|| format!("Invalid --config '{}': expected KEY=VALUE", pair))?;
+        overrides.push((key.trim().to_string(), parse_value(value.trim())));
+    }
+    let _ = CLI_OVERRIDES.set(overrides);
+    Ok(())
+}
+
+/// Env overrides: `RTK_TRACKING__HISTORY_DAYS=30` → `tracking.history_days = 30`.
+fn env_overrides(vars: impl Iterator<Item = (String, String)>) -> Vec<(String, Value)> {
+    vars.filter_map(|(name, value)| {
+        let rest = name.strip_prefix("RTK_")?;
+        if !rest.contains("__") {
+            return None;
+        }
+        let key = rest.to_lowercase().replace("__", ".");
+        Some((key, parse_value(&value)))
+    })
+    .collect()
+}
+
+/// Parse a TOML literal (`30`, `false`, `["a"]`), falling back to a plain string.
+fn parse_value(raw: &str) -> Value {
+    toml::from_str::<Table>(&format!("v = {}", raw))
+        .ok()
+        .and_then(|mut t| t.remove("v"))
+        .unwrap_or_else(|| Value::String(raw.to_string()))
+}
+
+fn read_table(path: &Path) -> Result<Table> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("Invalid TOML in {}", path.display()))
+}
+
+/// Merge every layer, returning the merged table and the origin of each leaf key.
+fn load_layers() -> Result<(Table, BTreeMap<String, Layer>)> {
+    let mut merged = Table::try_from(Config::default())?;
+    let mut origins = BTreeMap::new();
+    record_origins(&merged, "", Layer::Default, &mut origins);
+
+    let global = get_config_path()?;
+    if global.exists() {
+        tracing::debug!(path = %global.display(), "config layer");
+        apply_layer(
+            &mut merged,
+            read_table(&global)?,
+            Layer::Global,
+            &mut origins,
+        );
+    }
+
+    let project = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| find_project_config(&cwd));
+    if let Some(path) = project {
+        tracing::debug!(path = %path.display(), "config layer");
+        apply_layer(
+            &mut merged,
+            read_table(&path)?,
+            Layer::Project,
+            &mut origins,
+        );
+    }
+
+    for (key, value) in env_overrides(std::env::vars()) {
+        set_dotted(&mut merged, &key, value);
+        origins.insert(key, Layer::Env);
+    }
+
+    for (key, value) in CLI_OVERRIDES.get().into_iter().flatten() {
+        set_dotted(&mut merged, key, value.clone());
+        origins.insert(key.clone(), Layer::Cli);
+    }
+
+    Ok((merged, origins))
+}
+
+fn apply_layer(
+    merged: &mut Table,
+    layer: Table,
+    origin: Layer,
+    origins: &mut BTreeMap<String, Layer>,
+) {
+    record_origins(&layer, "", origin, origins);
+    merge_tables(merged, layer);
+}
+
+/// Deep-merge `overlay` into `base`: nested tables merge, anything else replaces.
+fn merge_tables(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(existing)), Value::Table(incoming)) => {
+                merge_tables(existing, incoming)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn record_origins(table: &Table, prefix: &str, origin: Layer, out: &mut BTreeMap<String, Layer>) {
+    for (key, value) in table {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Value::Table(nested) => record_origins(nested, &path, origin, out),
+            _ => {
+                out.insert(path, origin);
+            }
+        }
+    }
+}
+
+fn get_dotted<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    let mut parts = key.split('.');
+    let mut current = table.get(parts.next()?)?;
+    for part in parts {
+        current = current.as_table()?.get(part)?;
+    }
+    Some(current)
+}
+
+fn set_dotted(table: &mut Table, key: &str, value: Value) {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let last = parts.pop().unwrap_or(key);
+    let mut current = table;
+    for part in parts {
+        let entry = current
+            .entry(part.to_string())
+            .or_insert_with(|| Value::Table(Table::new()));
+        if !entry.is_table() {
+            *entry = Value::Table(Table::new());
+        }
+        current = entry.as_table_mut().expect("just ensured table");
+    }
+    current.insert(last.to_string(), value);
+}
+
+/// Flatten a table into sorted `key = value` leaf pairs.
+fn flatten(table: &Table, prefix: &str, out: &mut Vec<(String, Value)>) {
+    for (key, value) in table {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Value::Table(nested) => flatten(nested, &path, out),
+            _ => out.push((path, value.clone())),
+        }
+    }
+}
+
+/// `rtk config` — show config file locations and the effective settings.
 pub fn show_config() -> Result<()> {
     let path = get_config_path()?;
-    println!("Config: {}", path.display());
+    let status = if path.exists() { "" } else { " (not created)" };
+    println!("Global:  {}{}", path.display(), status);
+    let project = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| find_project_config(&cwd));
+    match project {
+        Some(p) => println!("Project: {}", p.display()),
+        None => println!("Project: (no {} found)", PROJECT_CONFIG_FILE),
+    }
     println!();
 
-    if path.exists() {
-        let config = Config::load()?;
-        println!("{}", toml::to_string_pretty(&config)?);
+    let config = Config::load()?;
+    println_verbatim!("{}", toml::to_string_pretty(&config)?);
+    Ok(())
+}
+
+/// `rtk config get KEY` — print the effective value of a dotted key.
+pub fn get_value(key: &str) -> Result<()> {
+    let (merged, _) = load_layers()?;
+    let value = get_dotted(&merged, key)
+        .with_context(|| format!("Unknown or unset config key: {}", key))?;
+    match value {
+        Value::String(s) => println!("{}", s),
+        other => println!("{}", other),
+    }
+    Ok(())
+}
+
+/// `rtk config set KEY VALUE` — write a key to the global file (or `.rtk.toml`).
+pub fn set_value(key: &str, raw: &str, project: bool) -> Result<()> {
+    let path = if project {
+        let cwd = std::env::current_dir()?;
+        find_project_config(&cwd).unwrap_or_else(|| cwd.join(PROJECT_CONFIG_FILE))
     } else {
-        println!("(default config, file not created)");
-        println!();
-        let config = Config::default();
-        println!("{}", toml::to_string_pretty(&config)?);
+        get_config_path()?
+    };
+
+    let mut table = if path.exists() {
+        read_table(&path)?
+    } else {
+        Table::new()
+    };
+    let value = parse_value(raw);
+    set_dotted(&mut table, key, value.clone());
+    validate_key(key, &value, &table)?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, toml::to_string_pretty(&table)?)?;
+    println!("{} = {}  ({})", key, value, path.display());
+    Ok(())
+}
+
+/// Reject unknown keys and ill-typed values before anything is written.
+fn validate_key(key: &str, value: &Value, table: &Table) -> Result<()> {
+    let mut candidate = Table::try_from(Config::default())?;
+    merge_tables(&mut candidate, table.clone());
+    let config: Config = Value::Table(candidate)
+        .try_into()
+        .with_context(|| format!("Invalid value for {}: {}", key, value))?;
+
+    let roundtrip = Table::try_from(&config)?;
+    let is_empty_array = value.as_array().is_some_and(|a| a.is_empty());
+    if get_dotted(&roundtrip, key).is_none() && !is_empty_array {
+        anyhow::bail!("Unknown config key: {}", key);
     }
+    Ok(())
+}
 
+/// `rtk config list` — every effective leaf setting with the layer it came from.
+pub fn list_values() -> Result<()> {
+    let (merged, origins) = load_layers()?;
+    let mut leaves = Vec::new();
+    flatten(&merged, "", &mut leaves);
+    for (key, value) in leaves {
+        let origin = origins.get(&key).copied().unwrap_or(Layer::Default);
+        println!("{} = {}  # {}", key, value, origin.label());
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(src: &str) -> Table {
+        toml::from_str(src).unwrap()
+    }
+
+    #[test]
+    fn test_merge_tables_is_deep() {
+        let mut base = table(
+            "[tracking]
+enabled = true
+history_days = 90
+",
+        );
+        merge_tables(
+            &mut base,
+            table(
+                "[tracking]
+history_days = 30
+",
+            ),
+        );
+        assert_eq!(
+            get_dotted(&base, "tracking.enabled"),
+            Some(&Value::Boolean(true))
+        );
+        assert_eq!(
+            get_dotted(&base, "tracking.history_days"),
+            Some(&Value::Integer(30))
+        );
+    }
+
+    #[test]
+    fn test_set_and_get_dotted() {
+        let mut t = Table::new();
+        set_dotted(&mut t, "commands.git.max_lines", Value::Integer(50));
+        assert_eq!(
+            get_dotted(&t, "commands.git.max_lines"),
+            Some(&Value::Integer(50))
+        );
+        assert_eq!(get_dotted(&t, "commands.cargo"), None);
+    }
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(parse_value("30"), Value::Integer(30));
+        assert_eq!(parse_value("false"), Value::Boolean(false));
+        assert_eq!(parse_value("words"), Value::String("words".into()));
+        assert_eq!(
+            parse_value("[\"rtk curl*\"]"),
+            Value::Array(vec![Value::String("rtk curl*".into())])
+        );
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let vars = vec![
+            ("RTK_TRACKING__HISTORY_DAYS".to_string(), "30".to_string()),
+            ("RTK_DB_PATH".to_string(), "/tmp/x.db".to_string()),
+            ("HOME".to_string(), "/root".to_string()),
+        ];
+        let overrides = env_overrides(vars.into_iter());
+        assert_eq!(
+            overrides,
+            vec![("tracking.history_days".to_string(), Value::Integer(30))]
+        );
+    }
+
+    #[test]
+    fn test_find_project_config_walks_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(find_project_config(&nested), None);
+
+        std::fs::write(dir.path().join(PROJECT_CONFIG_FILE), "").unwrap();
+        assert_eq!(
+            find_project_config(&nested),
+            Some(dir.path().join(PROJECT_CONFIG_FILE))
+        );
+    }
+
+    #[test]
+    fn test_validate_key() {
+        let mut t = Table::new();
+        set_dotted(&mut t, "output.tokenizer", Value::String("words".into()));
+        assert!(validate_key("output.tokenizer", &Value::String("words".into()), &t).is_ok());
+
+        let mut t = Table::new();
+        set_dotted(&mut t, "output.tokenizer", Value::String("bpe".into()));
+        assert!(validate_key("output.tokenizer", &Value::String("bpe".into()), &t).is_err());
+
+        let mut t = Table::new();
+        set_dotted(&mut t, "tracking.nope", Value::Boolean(true));
+        assert!(validate_key("tracking.nope", &Value::Boolean(true), &t).is_err());
+
+        let mut t = Table::new();
+        set_dotted(&mut t, "commands.git.max_lines", Value::Integer(50));
+        assert!(validate_key("commands.git.max_lines", &Value::Integer(50), &t).is_ok());
+    }
+
+    #[test]
+    fn test_layered_config_deserializes() {
+        let mut merged = Table::try_from(Config::default()).unwrap();
+        merge_tables(
+            &mut merged,
+            table(
+                "[output]
+tokenizer = \"words\"
+",
+            ),
+        );
+        let config: Config = Value::Table(merged).try_into().unwrap();
+        assert_eq!(config.output.tokenizer, Tokenizer::Words);
+        assert!(config.tracking.enabled);
+    }
+}
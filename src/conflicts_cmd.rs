@@ -0,0 +1,399 @@
+//! `rtk conflicts`: merge conflicts as minimal ours/base/theirs hunks.
+//!
+//! Lists unmerged files with their hunk counts, then renders each conflict
+//! with the lines both sides share trimmed and only the enclosing
+//! function/class signature as context.
+
+use crate::tracking;
+use crate::utils::truncate;
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::path::Path;
+use std::process::Command;
+
+lazy_static! {
+    // Lines that open a scope worth naming as context
+    static ref SIGNATURE: Regex = Regex::new(
+        r"^\s*(?:(?:pub(?:\([^)]*\))?|export|default|async|static|public|private|protected|unsafe|override|final|abstract)\s+)*(?:fn|def|class|func|function|impl|struct|enum|trait|interface|type|module|mod|object|const\s+\w+\s*=\s*(?:async\s*)?\(|let\s+\w+\s*=\s*(?:async\s*)?\()\b"
+    )
+    .unwrap();
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Hunk {
+    /// Line of the `<<<<<<<` marker
+    line: usize,
+    ours_label: String,
+    theirs_label: String,
+    ours: Vec<String>,
+    /// Only with `merge.conflictStyle = diff3` or `zdiff3`
+    base: Option<Vec<String>>,
+    theirs: Vec<String>,
+    /// Nearest signature line above the hunk
+    signature: Option<String>,
+}
+
+impl Hunk {
+    /// Lines shared at the start and end of both sides, dropped from display.
+    fn shared(&self) -> (usize, usize) {
+        let prefix = self
+            .ours
+            .iter()
+            .zip(&self.theirs)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let max_suffix = self.ours.len().min(self.theirs.len()) - prefix;
+        let suffix = self
+            .ours
+            .iter()
+            .rev()
+            .zip(self.theirs.iter().rev())
+            .take(max_suffix)
+            .take_while(|(a, b)| a == b)
+            .count();
+        (prefix, suffix)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Section {
+    Context,
+    Ours,
+    Base,
+    Theirs,
+}
+
+/// Conflict hunks in a file's working-tree content.
+fn parse_hunks(content: &str) -> Vec<Hunk> {
+    let mut hunks = Vec::new();
+    let mut section = Section::Context;
+    let mut signature: Option<String> = None;
+    let mut current = Hunk::default();
+    for (i, line) in content.lines().enumerate() {
+        match section {
+            Section::Context => {
+                if let Some(label) = line.strip_prefix("<<<<<<<") {
+                    current = Hunk {
+                        line: i + 1,
+                        ours_label: label.trim().to_string(),
+                        signature: signature.clone(),
+                        ..Default::default()
+                    };
+                    section = Section::Ours;
+                } else if SIGNATURE.is_match(line) {
+                    signature = Some(line.trim().to_string());
+                }
+            }
+            Section::Ours | Section::Base if line.starts_with("=======") => {
+                section = Section::Theirs;
+            }
+            Section::Ours if line.starts_with("|||||||") => {
+                current.base = Some(Vec::new());
+                section = Section::Base;
+            }
+            Section::Ours => current.ours.push(line.to_string()),
+            Section::Base => current
+                .base
+                .get_or_insert_with(Vec::new)
+                .push(line.to_string()),
+            Section::Theirs => {
+                if let Some(label) = line.strip_prefix(">>>>>>>") {
+                    current.theirs_label = label.trim().to_string();
+                    hunks.push(std::mem::take(&mut current));
+                    section = Section::Context;
+                } else {
+                    current.theirs.push(line.to_string());
+                }
+            }
+        }
+    }
+    hunks
+}
+
+/// Kind of conflict for a porcelain `XY` status, `None` if merged.
+fn conflict_kind(xy: &str) -> Option<&'static str> {
+    Some(match xy {
+        "UU" => "both modified",
+        "AA" => "both added",
+        "DD" => "both deleted",
+        "AU" => "added by us",
+        "UA" => "added by them",
+        "DU" => "deleted by us",
+        "UD" => "deleted by them",
+        _ => return None,
+    })
+}
+
+/// Unmerged paths and their conflict kind from `git status --porcelain`.
+fn unmerged(porcelain: &str) -> Vec<(String, &'static str)> {
+    porcelain
+        .lines()
+        .filter_map(|line| {
+            let kind = conflict_kind(line.get(..2)?)?;
+            Some((line.get(3..)?.trim_matches('"').to_string(), kind))
+        })
+        .collect()
+}
+
+/// Smallest leading-whitespace width among the non-blank lines.
+fn common_indent<'a>(lines: impl Iterator<Item = &'a String>) -> usize {
+    lines
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0)
+}
+
+fn push_side(
+    out: &mut Vec<String>,
+    label: &str,
+    lines: &[String],
+    indent: usize,
+    max_lines: usize,
+) {
+    out.push(format!("    {}:", label));
+    if lines.is_empty() {
+        out.push("      (nothing)".to_string());
+    }
+    for line in lines.iter().take(max_lines) {
+        let line = line.get(indent..).unwrap_or(line.trim_start());
+        out.push(format!("      {}", truncate(line.trim_end(), 160)));
+    }
+    if lines.len() > max_lines {
+        out.push(format!("      +{} more lines", lines.len() - max_lines));
+    }
+}
+
+fn format_hunk(hunk: &Hunk, max_lines: usize) -> Vec<String> {
+    let mut out = Vec::new();
+    let context = hunk
+        .signature
+        .as_deref()
+        .map(|s| format!(" in {}", truncate(s.trim_end_matches('{').trim_end(), 100)))
+        .unwrap_or_default();
+    let (prefix, suffix) = hunk.shared();
+    let shared = if prefix + suffix > 0 {
+        format!(" ({} shared lines trimmed)", prefix + suffix)
+    } else {
+        String::new()
+    };
+    out.push(format!("  @{}{}{}", hunk.line, context, shared));
+    let indent = common_indent(
+        hunk.ours
+            .iter()
+            .chain(hunk.base.iter().flatten())
+            .chain(&hunk.theirs),
+    );
+    let side = |label: &str, fallback: &str| {
+        if label.is_empty() {
+            fallback.to_string()
+        } else {
+            format!("{} ({})", fallback, label)
+        }
+    };
+    push_side(
+        &mut out,
+        &side(&hunk.ours_label, "ours"),
+        &hunk.ours[prefix..hunk.ours.len() - suffix],
+        indent,
+        max_lines,
+    );
+    if let Some(base) = &hunk.base {
+        // Trim the base by the same shared lines when it has them too
+        let ours_suffix = &hunk.ours[hunk.ours.len() - suffix..];
+        let base_prefix = base
+            .iter()
+            .zip(&hunk.ours[..prefix])
+            .take_while(|(a, b)| a == b)
+            .count();
+        let base_suffix = base
+            .iter()
+            .rev()
+            .zip(ours_suffix.iter().rev())
+            .take(base.len() - base_prefix)
+            .take_while(|(a, b)| a == b)
+            .count();
+        push_side(
+            &mut out,
+            "base",
+            &base[base_prefix..base.len() - base_suffix],
+            indent,
+            max_lines,
+        );
+    }
+    push_side(
+        &mut out,
+        &side(&hunk.theirs_label, "theirs"),
+        &hunk.theirs[prefix..hunk.theirs.len() - suffix],
+        indent,
+        max_lines,
+    );
+    out
+}
+
+/// Report for `(path, kind, hunks)`; hunks are empty for delete/add conflicts
+/// and binary files.
+fn format_report(files: &[(String, &str, Vec<Hunk>)], max_lines: usize) -> String {
+    if files.is_empty() {
+        return "✓ no conflicts".to_string();
+    }
+    let hunks: usize = files.iter().map(|(_, _, h)| h.len()).sum();
+    let mut out = vec![format!(
+        "⚔️ {} conflicted files, {} hunks",
+        files.len(),
+        hunks
+    )];
+    for (path, kind, hunks) in files {
+        out.push(String::new());
+        match (*kind, hunks.len()) {
+            ("both modified" | "both added", 0) => {
+                out.push(format!("{}: {}, no conflict markers left", path, kind))
+            }
+            ("both modified" | "both added", n) => out.push(format!(
+                "{}: {} hunk{}",
+                path,
+                n,
+                if n == 1 { "" } else { "s" }
+            )),
+            (kind, _) => out.push(format!("{}: {}", path, kind)),
+        }
+        for hunk in hunks {
+            out.extend(format_hunk(hunk, max_lines));
+        }
+    }
+    out.join("\n")
+}
+
+fn git(args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+pub fn run(paths: &[String], max_lines: usize, verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let root = git(&["rev-parse", "--show-toplevel"])?;
+    let root = Path::new(root.trim());
+    // Pathspecs are relative to the cwd; porcelain paths to the root
+    let mut status_args = vec!["-c", "core.quotePath=false", "status", "--porcelain", "--"];
+    status_args.extend(paths.iter().map(String::as_str));
+    let porcelain = git(&status_args)?;
+
+    let mut raw = String::new();
+    let mut files = Vec::new();
+    for (path, kind) in unmerged(&porcelain) {
+        // Unreadable (deleted) and binary files have no markers to show
+        let content = std::fs::read_to_string(root.join(&path)).unwrap_or_default();
+        raw.push_str(&content);
+        files.push((path, kind, parse_hunks(&content)));
+    }
+    if verbose > 0 {
+        eprintln!(
+            "conflicts: {} unmerged paths under {}",
+            files.len(),
+            root.display()
+        );
+    }
+
+    let output = format_report(&files, max_lines);
+    println!("{}", output);
+    timer.track("git diff", "rtk conflicts", &raw, &output);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFLICTED: &str = "\
+use std::time::Duration;
+
+pub fn connect(addr: &str) -> Result<Conn> {
+    let conn = Conn::new(addr);
+<<<<<<< HEAD
+    let timeout = Duration::from_secs(30);
+    conn.set_timeout(timeout);
+||||||| 1a2b3c4
+    let timeout = Duration::from_secs(10);
+    conn.set_timeout(timeout);
+=======
+    let timeout = Duration::from_secs(60);
+    conn.set_timeout(timeout);
+>>>>>>> feature/retries (Add retries)
+    Ok(conn)
+}
+
+<<<<<<< HEAD
+=======
+const RETRIES: u32 = 3;
+>>>>>>> feature/retries (Add retries)
+";
+
+    #[test]
+    fn test_parse_hunks_with_base_and_signature() {
+        let hunks = parse_hunks(CONFLICTED);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].line, 5);
+        assert_eq!(hunks[0].ours_label, "HEAD");
+        assert_eq!(hunks[0].theirs_label, "feature/retries (Add retries)");
+        assert_eq!(hunks[0].base.as_ref().map(Vec::len), Some(2));
+        assert_eq!(
+            hunks[0].signature.as_deref(),
+            Some("pub fn connect(addr: &str) -> Result<Conn> {")
+        );
+        assert_eq!(hunks[0].shared(), (0, 1));
+        assert!(hunks[1].ours.is_empty());
+        assert_eq!(hunks[1].theirs, vec!["const RETRIES: u32 = 3;"]);
+    }
+
+    #[test]
+    fn test_report_trims_shared_lines() {
+        let files = vec![
+            (
+                "src/net.rs".to_string(),
+                "both modified",
+                parse_hunks(CONFLICTED),
+            ),
+            ("src/old.rs".to_string(), "deleted by them", Vec::new()),
+        ];
+        let report = format_report(&files, 10);
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "⚔️ 2 conflicted files, 2 hunks");
+        assert_eq!(lines[2], "src/net.rs: 2 hunks");
+        assert_eq!(
+            lines[3],
+            "  @5 in pub fn connect(addr: &str) -> Result<Conn> (1 shared lines trimmed)"
+        );
+        assert_eq!(lines[4], "    ours (HEAD):");
+        assert_eq!(lines[5], "      let timeout = Duration::from_secs(30);");
+        assert_eq!(lines[6], "    base:");
+        assert_eq!(lines[7], "      let timeout = Duration::from_secs(10);");
+        assert_eq!(lines[8], "    theirs (feature/retries (Add retries)):");
+        assert!(!report.contains("conn.set_timeout(timeout);\n    theirs"));
+        assert!(report.contains("      (nothing)"));
+        assert!(report.ends_with("src/old.rs: deleted by them"));
+    }
+
+    #[test]
+    fn test_unmerged_paths() {
+        let porcelain = "UU src/net.rs\nM  src/ok.rs\nUD src/old.rs\n?? new.txt\nAA \"a b.txt\"\n";
+        assert_eq!(
+            unmerged(porcelain),
+            vec![
+                ("src/net.rs".to_string(), "both modified"),
+                ("src/old.rs".to_string(), "deleted by them"),
+                ("a b.txt".to_string(), "both added"),
+            ]
+        );
+    }
+}
@@ -1,4 +1,6 @@
+use crate::exit_code;
 use crate::tracking;
+use crate::utils::{display_width, take_last_width};
 use anyhow::{Context, Result};
 use std::ffi::OsString;
 use std::process::Command;
@@ -48,7 +50,9 @@ fn docker_ps(_verbose: u8) -> Result<()> {
     if stdout.trim().is_empty() {
         rtk.push_str("🐳 0 containers");
         println!("{}", rtk);
-        timer.track("docker ps", "rtk docker ps", &raw, &rtk);
+        timer
+            .with_exit_code(output.status)
+            .track("docker ps", "rtk docker ps", &raw, &rtk);
         return Ok(());
     }
 
@@ -77,7 +81,9 @@ fn docker_ps(_verbose: u8) -> Result<()> {
     }
 
     print!("{}", rtk);
-    timer.track("docker ps", "rtk docker ps", &raw, &rtk);
+    timer
+        .with_exit_code(output.status)
+        .track("docker ps", "rtk docker ps", &raw, &rtk);
     Ok(())
 }
 
@@ -102,7 +108,9 @@ fn docker_images(_verbose: u8) -> Result<()> {
     if lines.is_empty() {
         rtk.push_str("🐳 0 images");
         println!("{}", rtk);
-        timer.track("docker images", "rtk docker images", &raw, &rtk);
+        timer
+            .with_exit_code(output.status)
+            .track("docker images", "rtk docker images", &raw, &rtk);
         return Ok(());
     }
 
@@ -134,12 +142,7 @@ fn docker_images(_verbose: u8) -> Result<()> {
         if !parts.is_empty() {
             let image = parts[0];
             let size = parts.get(1).unwrap_or(&"");
-            let short = if image.len() > 40 {
-                format!("...{}", &image[image.len() - 37..])
-            } else {
-                image.to_string()
-            };
-            rtk.push_str(&format!("  {} [{}]\n", short, size));
+            rtk.push_str(&format!("  {} [{}]\n", short_image(image), size));
         }
     }
     if lines.len() > 15 {
@@ -147,10 +150,21 @@ fn docker_images(_verbose: u8) -> Result<()> {
     }
 
     print!("{}", rtk);
-    timer.track("docker images", "rtk docker images", &raw, &rtk);
+    timer
+        .with_exit_code(output.status)
+        .track("docker images", "rtk docker images", &raw, &rtk);
     Ok(())
 }
 
+/// `image`, keeping the end (name and tag) when it's over 40 columns.
+fn short_image(image: &str) -> String {
+    if display_width(image) > 40 {
+        format!("...{}", take_last_width(image, 37))
+    } else {
+        image.to_string()
+    }
+}
+
 fn docker_logs(args: &[String], _verbose: u8) -> Result<()> {
     let timer = tracking::TimedExecution::start();
 
@@ -168,11 +182,12 @@ fn docker_logs(args: &[String], _verbose: u8) -> Result<()> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
     let analyzed = crate::log_cmd::run_stdin_str(&raw);
     let rtk = format!("🐳 Logs for {}:\n{}", container, analyzed);
     println!("{}", rtk);
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("docker logs {}", container),
         "rtk docker logs",
         &raw,
@@ -192,6 +207,7 @@ fn kubectl_pods(args: &[String], _verbose: u8) -> Result<()> {
 
     let output = cmd.output().context("Failed to run kubectl get pods")?;
     let raw = String::from_utf8_lossy(&output.stdout).to_string();
+    crate::crash::raw_output(&raw);
     let mut rtk = String::new();
 
     let json: serde_json::Value = match serde_json::from_str(&raw) {
@@ -199,7 +215,12 @@ fn kubectl_pods(args: &[String], _verbose: u8) -> Result<()> {
         Err(_) => {
             rtk.push_str("☸️  No pods found");
             println!("{}", rtk);
-            timer.track("kubectl get pods", "rtk kubectl pods", &raw, &rtk);
+            timer.with_exit_code(output.status).track(
+                "kubectl get pods",
+                "rtk kubectl pods",
+                &raw,
+                &rtk,
+            );
             return Ok(());
         }
     };
@@ -208,7 +229,12 @@ fn kubectl_pods(args: &[String], _verbose: u8) -> Result<()> {
     if items.is_none() || items.unwrap().is_empty() {
         rtk.push_str("☸️  No pods found");
         println!("{}", rtk);
-        timer.track("kubectl get pods", "rtk kubectl pods", &raw, &rtk);
+        timer.with_exit_code(output.status).track(
+            "kubectl get pods",
+            "rtk kubectl pods",
+            &raw,
+            &rtk,
+        );
         return Ok(());
     }
 
@@ -293,6 +319,7 @@ fn kubectl_services(args: &[String], _verbose: u8) -> Result<()> {
 
     let output = cmd.output().context("Failed to run kubectl get services")?;
     let raw = String::from_utf8_lossy(&output.stdout).to_string();
+    crate::crash::raw_output(&raw);
     let mut rtk = String::new();
 
     let json: serde_json::Value = match serde_json::from_str(&raw) {
@@ -300,7 +327,12 @@ fn kubectl_services(args: &[String], _verbose: u8) -> Result<()> {
         Err(_) => {
             rtk.push_str("☸️  No services found");
             println!("{}", rtk);
-            timer.track("kubectl get svc", "rtk kubectl svc", &raw, &rtk);
+            timer.with_exit_code(output.status).track(
+                "kubectl get svc",
+                "rtk kubectl svc",
+                &raw,
+                &rtk,
+            );
             return Ok(());
         }
     };
@@ -309,7 +341,9 @@ fn kubectl_services(args: &[String], _verbose: u8) -> Result<()> {
     if items.is_none() || items.unwrap().is_empty() {
         rtk.push_str("☸️  No services found");
         println!("{}", rtk);
-        timer.track("kubectl get svc", "rtk kubectl svc", &raw, &rtk);
+        timer
+            .with_exit_code(output.status)
+            .track("kubectl get svc", "rtk kubectl svc", &raw, &rtk);
         return Ok(());
     }
 
@@ -352,7 +386,9 @@ fn kubectl_services(args: &[String], _verbose: u8) -> Result<()> {
     }
 
     print!("{}", rtk);
-    timer.track("kubectl get svc", "rtk kubectl svc", &raw, &rtk);
+    timer
+        .with_exit_code(output.status)
+        .track("kubectl get svc", "rtk kubectl svc", &raw, &rtk);
     Ok(())
 }
 
@@ -373,10 +409,11 @@ fn kubectl_logs(args: &[String], _verbose: u8) -> Result<()> {
 
     let output = cmd.output().context("Failed to run kubectl logs")?;
     let raw = String::from_utf8_lossy(&output.stdout).to_string();
+    crate::crash::raw_output(&raw);
     let analyzed = crate::log_cmd::run_stdin_str(&raw);
     let rtk = format!("☸️  Logs for {}:\n{}", pod, analyzed);
     println!("{}", rtk);
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("kubectl logs {}", pod),
         "rtk kubectl logs",
         &raw,
@@ -420,13 +457,13 @@ pub fn run_docker_passthrough(args: &[OsString], verbose: u8) -> Result<()> {
         .context("Failed to run docker")?;
 
     let args_str = tracking::args_display(args);
-    timer.track_passthrough(
+    timer.with_exit_code(status).track_passthrough(
         &format!("docker {}", args_str),
         &format!("rtk docker {} (passthrough)", args_str),
     );
 
     if !status.success() {
-        std::process::exit(status.code().unwrap_or(1));
+        exit_code::exit_with(status);
     }
     Ok(())
 }
@@ -444,13 +481,27 @@ pub fn run_kubectl_passthrough(args: &[OsString], verbose: u8) -> Result<()> {
         .context("Failed to run kubectl")?;
 
     let args_str = tracking::args_display(args);
-    timer.track_passthrough(
+    timer.with_exit_code(status).track_passthrough(
         &format!("kubectl {}", args_str),
         &format!("rtk kubectl {} (passthrough)", args_str),
     );
 
     if !status.success() {
-        std::process::exit(status.code().unwrap_or(1));
+        exit_code::exit_with(status);
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_image_cuts_on_characters() {
+        assert_eq!(short_image("nginx:1.27"), "nginx:1.27");
+        let long = format!("registry.example.com/équipe/{}:latest", "服务".repeat(8));
+        let short = short_image(&long);
+        assert!(short.starts_with("...") && short.ends_with("服务:latest"));
+        assert!(display_width(&short) <= 40);
+    }
+}
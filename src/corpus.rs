@@ -0,0 +1,260 @@
+//! `rtk verify-corpus`: compression quality checks on annotated raw outputs.
+//!
+//! `rtk bench-self` watches how much the summarizers save; this watches what
+//! they keep. Each fixture in `corpus/` is a real-world raw output headed by
+//! the summarizer it goes through, the savings it must reach and the lines
+//! (or parts of lines) the summary must still contain:
+//!
+//! ```text
+//! # command: cargo test
+//! # min-savings: 80
+//! # keep: panicked at src/auth.rs:42:9
+//! # keep: test result: FAILED. 118 passed; 2 failed
+//! ---
+//! <raw output>
+//! ```
+//!
+//! Keep lines are matched with whitespace collapsed and ANSI codes removed,
+//! since summarizers re-indent what they keep. A keep line missing from the
+//! raw output is reported too, so a typo can't pass unnoticed. A unit test
+//! runs the corpus, making it part of `cargo test`.
+
+use crate::api::{self, Tokenizer};
+use crate::exit_code;
+use crate::{
+    bench_self, cargo_cmd, err_extract, git, go_cmd, json_cmd, log_cmd, pytest_cmd, tsc_cmd, utils,
+};
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Summarizers a fixture can name, with the arguments their commands default to.
+fn summarizer(command: &str) -> Option<fn(&str) -> String> {
+    let filter: fn(&str) -> String = match command {
+        "cargo build" => cargo_cmd::filter_cargo_build,
+        "cargo clippy" => cargo_cmd::filter_cargo_clippy,
+        "cargo test" => cargo_cmd::filter_cargo_test,
+        "err" => err_extract::extract,
+        "git diff" => |text| git::compact_diff(text, 500),
+        "go test" => |text| go_cmd::filter_go_test_json(text).unwrap_or_default(),
+        "json" => |text| json_cmd::filter_json_string(text, 5).unwrap_or_default(),
+        "log" => log_cmd::run_stdin_str,
+        "pytest" => pytest_cmd::filter_pytest_output,
+        "tsc" => tsc_cmd::filter_tsc_output,
+        _ => return None,
+    };
+    Some(filter)
+}
+
+#[derive(Debug, PartialEq)]
+struct Fixture {
+    command: String,
+    min_savings: f64,
+    keep: Vec<String>,
+    raw: String,
+}
+
+#[derive(Debug, PartialEq)]
+struct Verdict {
+    savings_pct: f64,
+    /// Keep lines the summary lost
+    missing: Vec<String>,
+    /// Keep lines that aren't in the raw output in the first place
+    stray: Vec<String>,
+}
+
+impl Verdict {
+    fn passed(&self, min_savings: f64) -> bool {
+        self.missing.is_empty() && self.stray.is_empty() && self.savings_pct >= min_savings
+    }
+}
+
+pub fn run(dir: Option<PathBuf>, verbose: u8) -> Result<()> {
+    let dir = dir.unwrap_or_else(|| PathBuf::from("corpus"));
+    let paths = fixture_paths(&dir)?;
+    let mut failed = 0;
+
+    for path in &paths {
+        let name = path
+            .strip_prefix(&dir)
+            .unwrap_or(path)
+            .display()
+            .to_string();
+        let fixture = match parse(&std::fs::read_to_string(path)?) {
+            Ok(fixture) => fixture,
+            Err(e) => {
+                println!("❌ {}: {}", name, e);
+                failed += 1;
+                continue;
+            }
+        };
+        let Some(filter) = summarizer(&fixture.command) else {
+            println!("❌ {}: unknown command '{}'", name, fixture.command);
+            failed += 1;
+            continue;
+        };
+        let output = filter(&fixture.raw);
+        let verdict = verify(&fixture, &output);
+        let passed = verdict.passed(fixture.min_savings);
+        println!(
+            "{} {:<32} {:<13} {:>5.1}% saved (min {:.0}%), kept {}/{}",
+            if passed { "✅" } else { "❌" },
+            name,
+            fixture.command,
+            verdict.savings_pct,
+            fixture.min_savings,
+            fixture.keep.len() - verdict.missing.len(),
+            fixture.keep.len()
+        );
+        for line in &verdict.missing {
+            println!("   missing: {}", line);
+        }
+        for line in &verdict.stray {
+            println!("   not in raw output: {}", line);
+        }
+        if !passed {
+            failed += 1;
+            if verbose > 0 {
+                println!("   --- summary ---\n{}\n   ---", output);
+            }
+        }
+    }
+
+    println!(
+        "\n{} fixtures, {} passed, {} failed",
+        paths.len(),
+        paths.len() - failed,
+        failed
+    );
+    if failed > 0 {
+        exit_code::set(1);
+    }
+    Ok(())
+}
+
+/// `*.txt` files under `dir`, sorted.
+fn fixture_paths(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        bail!(
+            "No corpus at {} (run from the rtk checkout or pass the directory)",
+            dir.display()
+        );
+    }
+    let mut paths: Vec<PathBuf> = walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .map(|e| e.into_path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+fn parse(text: &str) -> Result<Fixture> {
+    let (header, raw) = text
+        .split_once("\n---\n")
+        .context("no '---' line between header and raw output")?;
+    let mut command = None;
+    let mut min_savings = None;
+    let mut keep = Vec::new();
+    for line in header.lines() {
+        let Some((key, value)) = line.strip_prefix('#').and_then(|l| l.split_once(':')) else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "command" => command = Some(value.to_string()),
+            "min-savings" => {
+                min_savings = Some(
+                    value
+                        .trim_end_matches('%')
+                        .parse()
+                        .with_context(|| format!("bad min-savings '{}'", value))?,
+                )
+            }
+            "keep" if !value.is_empty() => keep.push(value.to_string()),
+            _ => {}
+        }
+    }
+    Ok(Fixture {
+        command: command.context("no '# command:' line")?,
+        min_savings: min_savings.context("no '# min-savings:' line")?,
+        keep,
+        raw: raw.to_string(),
+    })
+}
+
+fn verify(fixture: &Fixture, output: &str) -> Verdict {
+    let raw = normalize(&fixture.raw);
+    let summary = normalize(output);
+    let input_tokens = api::estimate_tokens(&fixture.raw, Tokenizer::Chars);
+    let output_tokens = api::estimate_tokens(output, Tokenizer::Chars);
+    let savings_pct = bench_self::savings_pct(input_tokens, output_tokens);
+
+    let mut missing = Vec::new();
+    let mut stray = Vec::new();
+    for line in &fixture.keep {
+        let wanted = normalize(line);
+        if !raw.contains(&wanted) {
+            stray.push(line.clone());
+        } else if !summary.contains(&wanted) {
+            missing.push(line.clone());
+        }
+    }
+    Verdict {
+        savings_pct,
+        missing,
+        stray,
+    }
+}
+
+/// Text without ANSI codes, every run of whitespace a single space.
+fn normalize(text: &str) -> String {
+    utils::strip_ansi(text)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_verify() {
+        let fixture = parse(
+            "# command: log\n# min-savings: 50%\n# keep: ERROR  db down\n# keep: not there\n---\nINFO ok\nERROR db down\n",
+        )
+        .unwrap();
+        assert_eq!(fixture.command, "log");
+        assert_eq!(fixture.min_savings, 50.0);
+        assert_eq!(fixture.raw, "INFO ok\nERROR db down\n");
+
+        let verdict = verify(&fixture, "1 error:\n  ERROR db down");
+        assert!(verdict.missing.is_empty());
+        assert_eq!(verdict.stray, ["not there"]);
+
+        let verdict = verify(&fixture, "1 error");
+        assert_eq!(verdict.missing, ["ERROR  db down"]);
+        assert!(parse("# command: log\nraw").is_err());
+    }
+
+    #[test]
+    fn test_corpus_passes() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("corpus");
+        let paths = fixture_paths(&dir).unwrap();
+        assert!(paths.len() >= 8);
+        for path in paths {
+            let fixture = parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            let filter = summarizer(&fixture.command).unwrap();
+            let output = filter(&fixture.raw);
+            let verdict = verify(&fixture, &output);
+            assert!(
+                verdict.passed(fixture.min_savings),
+                "{}: {:?}\n{}",
+                path.display(),
+                verdict,
+                output
+            );
+        }
+    }
+}
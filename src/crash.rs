@@ -0,0 +1,254 @@
+//! Panic fallback: a crashing filter still leaves the agent the raw output.
+//!
+//! Wrappers hand the raw output they are about to filter to [`raw_output`]
+//! (streamed commands to [`raw_line`]), which keeps a copy up to the output
+//! budget. If anything then panics, the hook installed by [`install`]
+//! prints a banner with the panic message and that copy in place of the
+//! summary, records the run in tracking with exit code
+//! [`INTERNAL_ERROR`](crate::exit_code::INTERNAL_ERROR) and the panic as its
+//! output sample (see `rtk gain --failures`), and exits. The hook runs
+//! before unwinding, while the panicking code still holds its locks, so it
+//! writes to stdout directly rather than through the profile, pager or spill.
+//!
+//! Only the CLI's own thread falls back. Panics on other threads unwind as
+//! usual, and long-running servers (`rtk daemon`, `rtk mcp`) call
+//! [`serving`] and answer a request that panics with an error through
+//! [`catch`], so one bad request doesn't take the server down.
+
+use crate::config::Config;
+use crate::exit_code;
+use std::any::Any;
+use std::io::Write;
+use std::panic::{AssertUnwindSafe, PanicHookInfo};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread::ThreadId;
+
+/// Raw output kept when `output.max_tokens` is 0 (no budget).
+const DEFAULT_KEEP_BYTES: usize = 64 * 1024;
+
+/// rtk's arguments, for the tracking record
+static ARGS: OnceLock<Vec<String>> = OnceLock::new();
+/// The subcommand clap parsed, where the recorded command starts
+static SUBCOMMAND: OnceLock<String> = OnceLock::new();
+static RAW: Mutex<Raw> = Mutex::new(Raw::new());
+/// Set once the hook runs: a panic inside it must not recurse
+static PANICKING: AtomicBool = AtomicBool::new(false);
+/// The thread `install` ran on, the only one that falls back
+static MAIN: OnceLock<ThreadId> = OnceLock::new();
+/// Set by long-running servers: no fallback, not even on the main thread
+static SERVING: AtomicBool = AtomicBool::new(false);
+
+struct Raw {
+    /// Head of the raw output, at most `keep` bytes
+    text: String,
+    /// Size of the whole raw output
+    total: usize,
+    keep: usize,
+}
+
+impl Raw {
+    const fn new() -> Self {
+        Self {
+            text: String::new(),
+            total: 0,
+            keep: 0,
+        }
+    }
+
+    fn push(&mut self, text: &str) {
+        if self.keep == 0 {
+            self.keep = keep_bytes();
+        }
+        self.total += text.len();
+        let room = self.keep.saturating_sub(self.text.len());
+        if room > 0 {
+            self.text.push_str(head(text, room));
+        }
+    }
+}
+
+/// Install the panic hook; first thing in `main`, with rtk's arguments.
+pub fn install(args: Vec<String>) {
+    let _ = ARGS.set(args);
+    let _ = MAIN.set(std::thread::current().id());
+    let default = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let fall_back_here =
+            MAIN.get() == Some(&std::thread::current().id()) && !SERVING.load(Ordering::SeqCst);
+        if !fall_back_here || PANICKING.swap(true, Ordering::SeqCst) {
+            default(info);
+            return;
+        }
+        // Location and backtrace hint on stderr, as without the hook
+        default(info);
+        fall_back(&message(info));
+        // Not exit_code::exit: its flushes take locks the panicking code may hold
+        tracing::debug!(code = exit_code::INTERNAL_ERROR, "exit");
+        std::process::exit(exit_code::INTERNAL_ERROR);
+    }));
+}
+
+/// The subcommand clap parsed (`git` in `rtk --profile agent git status`).
+pub fn set_subcommand(name: &str) {
+    let _ = SUBCOMMAND.set(name.to_string());
+}
+
+/// Called by long-running servers: panics unwind to their [`catch`] instead
+/// of printing the fallback and exiting.
+pub fn serving() {
+    SERVING.store(true, Ordering::SeqCst);
+}
+
+/// Run `f`, with a panic turned into `Err(message)`.
+pub fn catch<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    std::panic::catch_unwind(AssertUnwindSafe(f))
+        .map_err(|payload| payload_text(&*payload).to_string())
+}
+
+/// The raw output a wrapper is about to filter (replaces any earlier one).
+pub fn raw_output(raw: &str) {
+    if let Ok(mut held) = RAW.lock() {
+        *held = Raw::new();
+        held.push(raw);
+    }
+}
+
+/// One more line of a streamed command's raw output.
+pub fn raw_line(line: &str) {
+    if let Ok(mut held) = RAW.lock() {
+        held.push(line);
+        held.push("\n");
+    }
+}
+
+fn keep_bytes() -> usize {
+    // ~4 bytes per token, as in tracking::estimate_tokens
+    match Config::cached().output.max_tokens {
+        0 => DEFAULT_KEEP_BYTES,
+        tokens => tokens * 4,
+    }
+}
+
+fn payload_text(payload: &dyn Any) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("panic")
+}
+
+fn message(info: &PanicHookInfo) -> String {
+    let text = payload_text(info.payload());
+    match info.location() {
+        Some(at) => format!("{} ({}:{})", text, at.file(), at.line()),
+        None => text.to_string(),
+    }
+}
+
+/// Banner and raw output on stdout (where the agent reads), then tracking.
+fn fall_back(message: &str) {
+    tracing::error!(panic = message, "crashed, showing raw output");
+    // Held if the panic came from inside `raw_output` itself
+    let (raw, total) = match RAW.try_lock() {
+        Ok(held) => (held.text.clone(), held.total),
+        Err(_) => (String::new(), 0),
+    };
+    let mut out = std::io::stdout().lock();
+    let _ = writeln!(out, "{}", banner(message, !raw.is_empty()));
+    if !raw.is_empty() {
+        let _ = out.write_all(raw.as_bytes());
+        if !raw.ends_with('\n') {
+            let _ = out.write_all(b"\n");
+        }
+        if total > raw.len() {
+            let _ = writeln!(
+                out,
+                "... ({} more bytes of raw output not shown)",
+                total - raw.len()
+            );
+        }
+    }
+    let _ = out.flush();
+    drop(out);
+
+    let args = ARGS.get().map(Vec::as_slice).unwrap_or_default();
+    let (original_cmd, rtk_cmd) = commands(args, SUBCOMMAND.get().map(String::as_str));
+    crate::tracking::record_crash(&original_cmd, &rtk_cmd, &raw, message);
+}
+
+fn banner(message: &str, has_raw: bool) -> String {
+    if has_raw {
+        format!(
+            "[rtk error] filter crashed: {}. Raw output follows, unfiltered.",
+            message
+        )
+    } else {
+        format!(
+            "[rtk error] crashed: {}. Run the command without rtk for its output.",
+            message
+        )
+    }
+}
+
+/// ("git status", "rtk git status") from rtk's arguments, from `subcommand`
+/// on: global flags and their values are left out. All of them before the
+/// arguments were parsed.
+fn commands(args: &[String], subcommand: Option<&str>) -> (String, String) {
+    let start = subcommand
+        .and_then(|name| args.iter().position(|a| a == name))
+        .unwrap_or(0);
+    let original = args[start..].join(" ");
+    (original.clone(), format!("rtk {}", original))
+}
+
+/// The first `max` bytes of `text`, on a char boundary.
+fn head(text: &str, max: usize) -> &str {
+    if text.len() <= max {
+        return text;
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_keeps_head_within_budget() {
+        let mut raw = Raw::new();
+        raw.keep = 10;
+        raw.push("héllo\n");
+        raw.push("world, and more\n");
+        assert_eq!(raw.text, "héllo\nwor");
+        assert_eq!(raw.total, 23);
+    }
+
+    #[test]
+    fn test_commands_and_banner() {
+        let args: Vec<String> = ["--ascii", "--profile", "agent", "git", "log", "-5"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            commands(&args, Some("git")),
+            ("git log -5".to_string(), "rtk git log -5".to_string())
+        );
+        assert_eq!(commands(&args[3..], None).0, "git log -5");
+        assert!(banner("boom (src/a.rs:1)", true).contains("Raw output follows"));
+        assert!(banner("boom", false).contains("without rtk"));
+    }
+
+    #[test]
+    fn test_catch_returns_panic_message() {
+        assert_eq!(catch(|| 7), Ok(7));
+        assert_eq!(
+            catch(|| -> u8 { panic!("bad request {}", 3) }),
+            Err("bad request 3".to_string())
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::exit_code;
 use crate::json_cmd;
 use crate::tracking;
 use crate::utils::truncate;
@@ -28,16 +29,17 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
             stderr.trim().to_string()
         };
         eprintln!("FAILED: curl {}", msg);
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     let raw = stdout.to_string();
+    crate::crash::raw_output(&raw);
 
     // Auto-detect JSON and pipe through filter
     let filtered = filter_curl_output(&stdout);
     println!("{}", filtered);
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("curl {}", args.join(" ")),
         &format!("rtk curl {}", args.join(" ")),
         &raw,
@@ -0,0 +1,583 @@
+//! `rtk daemon` — long-running local HTTP/JSON API for editors and agents.
+//!
+//! Keeps config, the tracking database connection and a summary cache warm
+//! so integrations skip per-invocation startup. Listens on 127.0.0.1 only:
+//!
+//! - `GET  /health`     status, version, uptime
+//! - `POST /summarize`  `{"path", "level"?, "max_lines"?}` → filtered file
+//! - `POST /run`        `{"command"}` → compressed output; commands the
+//!   safety policy protects are refused, not run
+//! - `GET  /gain`       savings totals and top commands
+//!
+//! `/run` executes shell commands and `/summarize` reads any file the user
+//! can, so every request needs `Authorization: Bearer <token>`. The token is
+//! new on each start and written to [`token_path`], readable by the user
+//! only: other users and processes that can't read the file are refused.
+//! Browsers cannot drive the API either: requests with an `Origin` header, a
+//! non-local `Host`, or a POST body that is not `application/json` are
+//! refused.
+
+use crate::filter::FilterLevel;
+use crate::generic_cmd;
+use crate::policy::Policy;
+use crate::read;
+use crate::stream;
+use crate::tracking::{self, Tracker};
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Request bodies larger than this are refused.
+const MAX_BODY: usize = 1024 * 1024;
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Request {
+    pub(crate) method: String,
+    pub(crate) path: String,
+    /// Header names are lowercased
+    pub(crate) headers: HashMap<String, String>,
+    pub(crate) body: Vec<u8>,
+}
+
+/// Summary cache key: file identity plus filter options.
+#[derive(Hash, PartialEq, Eq)]
+struct SummaryKey {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    len: u64,
+    level: String,
+    max_lines: Option<usize>,
+}
+
+struct State {
+    started: Instant,
+    requests: AtomicU64,
+    tracker: Mutex<Option<Tracker>>,
+    summaries: Mutex<HashMap<SummaryKey, (String, String)>>,
+    policy: Policy,
+    /// Bearer token every request must carry
+    token: String,
+}
+
+/// Where the running daemon's token is: `daemon.token` in rtk's data directory.
+pub fn token_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rtk")
+        .join("daemon.token")
+}
+
+pub fn run(port: u16, verbose: u8) -> Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .with_context(|| format!("Failed to listen on 127.0.0.1:{}", port))?;
+    crate::crash::serving();
+    let token = new_token()?;
+    let token_file = token_path();
+    write_token(&token_file, &token)?;
+    let state = Arc::new(State {
+        started: Instant::now(),
+        requests: AtomicU64::new(0),
+        tracker: Mutex::new(Tracker::new().ok()),
+        summaries: Mutex::new(HashMap::new()),
+        policy: Policy::load(),
+        token,
+    });
+    eprintln!(
+        "rtk daemon listening on http://127.0.0.1:{} (token in {})",
+        port,
+        token_file.display()
+    );
+
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        let state = Arc::clone(&state);
+        std::thread::spawn(move || {
+            if let Err(e) = serve(stream, &state, verbose) {
+                if verbose > 0 {
+                    eprintln!("rtk daemon: {:#}", e);
+                }
+            }
+        });
+    }
+    Ok(())
+}
+
+fn serve(mut stream: TcpStream, state: &State, verbose: u8) -> Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let (status, body) = match read_request(&mut BufReader::new(&stream)) {
+        Ok(request) => {
+            state.requests.fetch_add(1, Ordering::Relaxed);
+            if verbose > 0 {
+                eprintln!("{} {}", request.method, request.path);
+            }
+            crate::crash::catch(|| route(&request, state)).unwrap_or_else(|panic| {
+                (
+                    500,
+                    json!({ "error": format!("internal error: {}", panic) }),
+                )
+            })
+        }
+        Err(e) => (400, json!({ "error": format!("{:#}", e) })),
+    };
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
+
+/// 32 random bytes from the OS, hex-encoded.
+fn new_token() -> Result<String> {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes)
+        .map_err(|e| anyhow::anyhow!("Failed to get random bytes for the daemon token: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Compare without returning at the first difference, so response timing
+/// doesn't tell a caller how much of the token it guessed.
+pub(crate) fn token_matches(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Replace the token file with one only the user can read.
+fn write_token(path: &Path, token: &str) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    // A fresh file, so an existing one's permissions don't carry over
+    let _ = std::fs::remove_file(path);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    file.write_all(token.as_bytes())?;
+    Ok(())
+}
+
+pub(crate) fn read_request(reader: &mut impl BufRead) -> Result<Request> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        anyhow::bail!("malformed request line");
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut headers = HashMap::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let len: usize = headers
+        .get("content-length")
+        .map(|v| v.parse())
+        .transpose()
+        .context("invalid Content-Length")?
+        .unwrap_or(0);
+    anyhow::ensure!(len <= MAX_BODY, "body larger than {} bytes", MAX_BODY);
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body)?;
+    Ok(Request {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+/// Why a request must be refused (anything a browser page could send).
+fn forbidden(request: &Request) -> Option<&'static str> {
+    if request.headers.contains_key("origin") {
+        return Some("cross-origin requests are not allowed");
+    }
+    let host = request
+        .headers
+        .get("host")
+        .map(String::as_str)
+        .unwrap_or("");
+    let hostname = host.rsplit_once(':').map_or(host, |(name, _)| name);
+    if !matches!(hostname, "127.0.0.1" | "localhost" | "[::1]") {
+        return Some("Host must be localhost");
+    }
+    let json_body = request
+        .headers
+        .get("content-type")
+        .is_some_and(|t| t.starts_with("application/json"));
+    if request.method == "POST" && !json_body {
+        return Some("POST bodies must be application/json");
+    }
+    None
+}
+
+fn route(request: &Request, state: &State) -> (u16, Value) {
+    if let Some(reason) = forbidden(request) {
+        return (403, json!({ "error": reason }));
+    }
+    let authorized = request
+        .headers
+        .get("authorization")
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .is_some_and(|t| token_matches(t, &state.token));
+    if !authorized {
+        return (401, json!({ "error": "missing or invalid bearer token" }));
+    }
+    let body = || -> std::result::Result<Value, (u16, Value)> {
+        serde_json::from_slice(&request.body)
+            .map_err(|e| (400, json!({ "error": format!("invalid JSON: {}", e) })))
+    };
+    let result = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/health") => Ok(json!({
+            "status": "ok",
+            "version": env!("CARGO_PKG_VERSION"),
+            "uptime_s": state.started.elapsed().as_secs(),
+            "requests": state.requests.load(Ordering::Relaxed),
+        })),
+        ("GET", "/gain") => gain(state),
+        ("POST", "/summarize") => body().and_then(|b| summarize(&b, state)),
+        ("POST", "/run") => body().and_then(|b| run_command(&b, state)),
+        (_, "/health" | "/gain" | "/summarize" | "/run") => {
+            Err((405, json!({ "error": "method not allowed" })))
+        }
+        _ => Err((404, json!({ "error": "not found" }))),
+    };
+    match result {
+        Ok(value) => (200, value),
+        Err(error) => error,
+    }
+}
+
+fn summarize(body: &Value, state: &State) -> std::result::Result<Value, (u16, Value)> {
+    let bad = |message: String| (400, json!({ "error": message }));
+    let path = body
+        .get("path")
+        .and_then(Value::as_str)
+        .ok_or_else(|| bad("missing 'path'".into()))?;
+    let level_name = body
+        .get("level")
+        .and_then(Value::as_str)
+        .unwrap_or("minimal");
+    let level: FilterLevel = level_name.parse().map_err(bad)?;
+    let max_lines = body
+        .get("max_lines")
+        .and_then(Value::as_u64)
+        .map(|n| n as usize);
+
+    let path = Path::new(path);
+    let metadata = std::fs::metadata(path).map_err(|e| (404, json!({ "error": e.to_string() })))?;
+    let key = SummaryKey {
+        path: path.to_path_buf(),
+        modified: metadata.modified().ok(),
+        len: metadata.len(),
+        level: level_name.to_string(),
+        max_lines,
+    };
+    let timer = Instant::now();
+    let cached = state
+        .summaries
+        .lock()
+        .ok()
+        .and_then(|m| m.get(&key).cloned());
+    let hit = cached.is_some();
+    let (content, output) = match cached {
+        Some(entry) => entry,
+        None => {
+            let entry = read::render(path, level, max_lines, false, 0)
+                .map_err(|e| (422, json!({ "error": format!("{:#}", e) })))?;
+            if let Ok(mut summaries) = state.summaries.lock() {
+                summaries.insert(key, entry.clone());
+            }
+            entry
+        }
+    };
+    let display = path.display().to_string();
+    record(
+        state,
+        &format!("cat {}", display),
+        "rtk cat",
+        &content,
+        &output,
+        timer,
+        Some(0),
+    );
+    Ok(json!({
+        "output": output,
+        "input_tokens": tracking::estimate_tokens(&content),
+        "output_tokens": tracking::estimate_tokens(&output),
+        "cached": hit,
+    }))
+}
+
+fn run_command(body: &Value, state: &State) -> std::result::Result<Value, (u16, Value)> {
+    let command = body
+        .get("command")
+        .and_then(Value::as_str)
+        .filter(|c| !c.trim().is_empty())
+        .ok_or_else(|| (400, json!({ "error": "missing 'command'" })))?;
+    let timer = Instant::now();
+    let fail = |e: anyhow::Error| (500, json!({ "error": format!("{:#}", e) }));
+
+    if let Some(protected) = state.policy.check(command) {
+        return Err((
+            403,
+            json!({
+                "error": format!(
+                    "'{}' is protected by the safety policy ({}), not run",
+                    protected.segment, protected.rule
+                ),
+                "protected": true,
+            }),
+        ));
+    }
+
+    let mut lines = Vec::new();
+    let streamed = stream::run(
+        stream::shell(command).stdin(std::process::Stdio::null()),
+        |line| {
+            lines.push(line.clone());
+            None
+        },
+    )
+    .map_err(fail)?;
+    stream::sort_by_time(&mut lines);
+    let raw = generic_cmd::join_lines(&lines, None);
+    let mut output = generic_cmd::compress_streams(&lines);
+    if let Some(notice) = streamed.timeout_notice() {
+        output = format!("{}, partial output follows\n{}", notice, output);
+    }
+    let exit_code = streamed.exit_code();
+    record(
+        state,
+        command,
+        &format!("rtk x {}", command),
+        &raw,
+        &output,
+        timer,
+        Some(exit_code),
+    );
+    Ok(json!({
+        "output": output,
+        "exit_code": exit_code,
+    }))
+}
+
+fn gain(state: &State) -> std::result::Result<Value, (u16, Value)> {
+    let mut tracker = state
+        .tracker
+        .lock()
+        .map_err(|_| (500, json!({ "error": "tracker unavailable" })))?;
+    if tracker.is_none() {
+        *tracker = Tracker::new().ok();
+    }
+    let tracker = tracker
+        .as_ref()
+        .ok_or_else(|| (503, json!({ "error": "tracking database unavailable" })))?;
+    let _ = tracker.refresh();
+    let summary = tracker
+        .get_summary()
+        .map_err(|e| (500, json!({ "error": format!("{:#}", e) })))?;
+    let top: Vec<Value> = summary
+        .by_command
+        .iter()
+        .map(|(cmd, count, saved, pct, ..)| {
+            json!({ "command": cmd, "count": count, "saved": saved, "savings_pct": pct })
+        })
+        .collect();
+    Ok(json!({
+        "commands": summary.total_commands,
+        "input_tokens": summary.total_input,
+        "output_tokens": summary.total_output,
+        "saved_tokens": summary.total_saved,
+        "savings_pct": summary.avg_savings_pct,
+        "cache_hits": summary.cache_hits,
+        "top": top,
+    }))
+}
+
+/// Record through the warm connection instead of opening the database.
+fn record(
+    state: &State,
+    original_cmd: &str,
+    rtk_cmd: &str,
+    input: &str,
+    output: &str,
+    started: Instant,
+    exit_code: Option<i32>,
+) {
+    if !tracking::should_track(original_cmd, rtk_cmd) {
+        return;
+    }
+    if let Ok(Some(tracker)) = state.tracker.lock().as_deref() {
+        let _ = tracker.record(
+            original_cmd,
+            rtk_cmd,
+            tracking::estimate_tokens(input),
+            tracking::estimate_tokens(output),
+            started.elapsed().as_millis() as u64,
+            exit_code,
+        );
+    }
+}
+
+pub(crate) fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        422 => "Unprocessable Entity",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(raw: &str) -> Request {
+        read_request(&mut raw.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_read_request() {
+        let req = request(
+            "POST /summarize HTTP/1.1\r\nHost: localhost:7171\r\nContent-Type: application/json\r\nContent-Length: 17\r\n\r\n{\"path\":\"a.rs\"}xx",
+        );
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.path, "/summarize");
+        assert_eq!(req.headers["host"], "localhost:7171");
+        assert_eq!(req.body, b"{\"path\":\"a.rs\"}xx");
+        assert!(read_request(&mut "GARBAGE\r\n\r\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_forbidden_browser_requests() {
+        let ok = request(
+            "POST /run HTTP/1.1\r\nHost: 127.0.0.1:7171\r\nContent-Type: application/json\r\n\r\n",
+        );
+        assert_eq!(forbidden(&ok), None);
+        let origin =
+            request("GET /gain HTTP/1.1\r\nHost: localhost\r\nOrigin: http://evil.test\r\n\r\n");
+        assert!(forbidden(&origin).is_some());
+        let rebound = request("GET /gain HTTP/1.1\r\nHost: evil.test:7171\r\n\r\n");
+        assert!(forbidden(&rebound).is_some());
+        let form =
+            request("POST /run HTTP/1.1\r\nHost: localhost\r\nContent-Type: text/plain\r\n\r\n");
+        assert!(forbidden(&form).is_some());
+    }
+
+    fn state() -> State {
+        State {
+            started: Instant::now(),
+            requests: AtomicU64::new(0),
+            tracker: Mutex::new(None),
+            summaries: Mutex::new(HashMap::new()),
+            policy: Policy::default(),
+            token: "s3cret".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_route_requires_token() {
+        let state = state();
+        let health = |auth: &str| {
+            request(&format!(
+                "GET /health HTTP/1.1\r\nHost: localhost\r\n{}\r\n",
+                auth
+            ))
+        };
+        assert_eq!(route(&health(""), &state).0, 401);
+        for wrong in ["nope", "s3cre", "s3cret2", "S3cret"] {
+            let auth = format!("Authorization: Bearer {}\r\n", wrong);
+            assert_eq!(route(&health(&auth), &state).0, 401, "{}", wrong);
+        }
+        assert_eq!(
+            route(&health("Authorization: Bearer s3cret\r\n"), &state).0,
+            200
+        );
+    }
+
+    #[test]
+    fn test_tokens_are_random_hex() {
+        let (a, b) = (new_token().unwrap(), new_token().unwrap());
+        assert_eq!(a.len(), 64);
+        assert!(a.bytes().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(a, b);
+        assert!(token_matches(&a, &a.clone()));
+        assert!(!token_matches(&a, &b));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_token_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rtk/daemon.token");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "old").unwrap();
+        let token = new_token().unwrap();
+        assert_eq!(token.len(), 64);
+        write_token(&path, &token).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), token);
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_run_refuses_protected_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        let victim = dir.path().join("keep.txt");
+        std::fs::write(&victim, "x").unwrap();
+        let body = json!({ "command": format!("rm -f {}", victim.display()) });
+        let (status, error) = run_command(&body, &state()).unwrap_err();
+        assert_eq!(status, 403);
+        assert_eq!(error["protected"], true);
+        assert!(victim.exists());
+    }
+
+    #[test]
+    fn test_summarize_caches_by_file_identity() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.rs");
+        std::fs::write(&file, "// comment\nfn main() {}\n").unwrap();
+        let state = state();
+        let body = json!({ "path": file.display().to_string(), "level": "aggressive" });
+        let first = summarize(&body, &state).unwrap();
+        assert_eq!(first["cached"], false);
+        assert!(first["output"].as_str().unwrap().contains("fn main"));
+        assert_eq!(summarize(&body, &state).unwrap()["cached"], true);
+
+        let missing = json!({ "path": dir.path().join("nope.rs").display().to_string() });
+        assert_eq!(summarize(&missing, &state).unwrap_err().0, 404);
+        let bad_level = json!({ "path": file.display().to_string(), "level": "max" });
+        assert_eq!(summarize(&bad_level, &state).unwrap_err().0, 400);
+    }
+}
@@ -0,0 +1,110 @@
+//! `--debug-log`: rtk's own account of a run, written to a file.
+//!
+//! Inside an agent session rtk's stdout belongs to the agent, so when a
+//! wrapper misbehaves there is nowhere to see why. Dispatch, spawned
+//! commands, filters, tracking, config loading, the cache and hook rewrites
+//! emit `tracing` events; with a debug log enabled they are appended to a
+//! file, one line each with its fields and the `rtk` span (command and pid):
+//!
+//! ```text
+//! 2026-03-04T10:15:02.114Z DEBUG rtk{command="git" pid=4242}: rtk::stream: spawn command="git" "status"
+//! ```
+//!
+//! `RTK_DEBUG_LOG=<file>` (or `1` for the default file) does the same where
+//! the command line can't be changed, e.g. hook rewrites. `RTK_LOG` takes
+//! `tracing` filter directives (`trace`, `rtk::tracking=trace`) and defaults
+//! to `debug`. Without a debug log no subscriber is installed, and every
+//! event is skipped after one atomic load.
+
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing_subscriber::EnvFilter;
+
+/// A log past this size is moved to `<file>.1` when the next run starts.
+const MAX_BYTES: u64 = 10 << 20;
+
+/// Start logging to the `--debug-log` file (`Some("")` for the default), or
+/// to `RTK_DEBUG_LOG`'s. Returns the file logged to.
+pub fn init(flag: Option<&Path>) -> Option<PathBuf> {
+    let path = log_path(flag, std::env::var("RTK_DEBUG_LOG").ok().as_deref())?;
+    let file = match open(&path) {
+        Ok(file) => file,
+        Err(e) => {
+            // stderr, not stdout: the agent parses stdout
+            eprintln!("rtk: can't open debug log {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    let filter = EnvFilter::try_from_env("RTK_LOG").unwrap_or_else(|_| EnvFilter::new("debug"));
+    tracing_subscriber::fmt()
+        .with_writer(Mutex::new(file))
+        .with_env_filter(filter)
+        .with_ansi(false)
+        .try_init()
+        .ok()?;
+    Some(path)
+}
+
+/// The default log: `debug.log` in rtk's data directory.
+pub fn default_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rtk")
+        .join("debug.log")
+}
+
+fn log_path(flag: Option<&Path>, env: Option<&str>) -> Option<PathBuf> {
+    let path = match flag {
+        Some(path) => path.to_path_buf(),
+        None => match env? {
+            "" | "0" => return None,
+            "1" => PathBuf::new(),
+            path => PathBuf::from(path),
+        },
+    };
+    Some(if path.as_os_str().is_empty() {
+        default_path()
+    } else {
+        path
+    })
+}
+
+fn open(path: &Path) -> std::io::Result<File> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    if std::fs::metadata(path).is_ok_and(|m| m.len() > MAX_BYTES) {
+        let mut rotated = path.as_os_str().to_owned();
+        rotated.push(".1");
+        let _ = std::fs::rename(path, rotated);
+    }
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_path() {
+        let custom = Path::new("/tmp/rtk.log");
+        assert_eq!(log_path(Some(custom), Some("x")), Some(custom.into()));
+        assert_eq!(log_path(Some(Path::new("")), None), Some(default_path()));
+        assert_eq!(log_path(None, Some("1")), Some(default_path()));
+        assert_eq!(log_path(None, Some("/a/b.log")), Some("/a/b.log".into()));
+        assert_eq!(log_path(None, Some("0")), None);
+        assert_eq!(log_path(None, None), None);
+    }
+
+    #[test]
+    fn test_open_rotates_large_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs/debug.log");
+        drop(open(&path).unwrap());
+        std::fs::write(&path, vec![b'x'; MAX_BYTES as usize + 1]).unwrap();
+        drop(open(&path).unwrap());
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+        assert!(dir.path().join("logs/debug.log.1").exists());
+    }
+}
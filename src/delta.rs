@@ -0,0 +1,329 @@
+//! `rtk cat --delta <file>` — show only what changed since rtk last served the file.
+//!
+//! The content of every file read with `--delta` is kept as a snapshot under
+//! `<data dir>/rtk/snapshots/`. The next `--delta` read prints a unified diff
+//! against that snapshot (or a one-line "unchanged" note) instead of the whole
+//! file. First reads and rewrites too large to diff usefully fall back to a
+//! normal filtered read.
+
+use crate::cache;
+use crate::filter::FilterLevel;
+use crate::read;
+use crate::tracking;
+use crate::utils::line_endings;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Unchanged lines shown around each change.
+const CONTEXT: usize = 3;
+/// Beyond this many inserted/deleted lines a diff is no cheaper than the file.
+const MAX_EDITS: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Edit {
+    Keep,
+    Delete,
+    Insert,
+}
+
+pub fn run(
+    file: &Path,
+    level: FilterLevel,
+    max_lines: Option<usize>,
+    line_numbers: bool,
+    verbose: u8,
+) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let content = fs::read_to_string(file)
+        .with_context(|| format!("Failed to read file: {}", file.display()))?;
+    let snapshot = snapshot_path(file);
+    let previous = snapshot.as_ref().and_then(|p| fs::read_to_string(p).ok());
+    if let Some(path) = &snapshot {
+        save_snapshot(path, &content);
+    }
+
+    let delta = previous
+        .as_deref()
+        .and_then(|prev| render_delta(&file.display().to_string(), prev, &content));
+    let Some(delta) = delta else {
+        if verbose > 0 {
+            eprintln!("No usable snapshot for {}, reading in full", file.display());
+        }
+        return read::run(file, level, max_lines, line_numbers, verbose);
+    };
+
+    println!("{}", delta);
+    timer.track(
+        &format!("cat {}", file.display()),
+        "rtk cat --delta",
+        &content,
+        &delta,
+    );
+    Ok(())
+}
+
+/// Snapshot location for `file`, keyed by its canonical path.
+fn snapshot_path(file: &Path) -> Option<PathBuf> {
+    let canonical = fs::canonicalize(file).ok()?;
+    let dir = dirs::data_local_dir()?.join("rtk").join("snapshots");
+    Some(dir.join(cache::key(&[&canonical.to_string_lossy()])))
+}
+
+fn save_snapshot(path: &Path, content: &str) {
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let _ = fs::write(path, content);
+}
+
+/// Unchanged note or unified diff; `None` when the rewrite is too large.
+fn render_delta(name: &str, old: &str, new: &str) -> Option<String> {
+    if old == new {
+        return Some(format!(
+            "{}: unchanged since last read ({} lines)",
+            name,
+            new.lines().count()
+        ));
+    }
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let edits = edit_script(&a, &b)?;
+    let added = edits.iter().filter(|e| **e == Edit::Insert).count();
+    let removed = edits.iter().filter(|e| **e == Edit::Delete).count();
+    // Lines are compared without their CR; report a CRLF/LF switch once
+    let (before, after) = (line_endings(old), line_endings(new));
+    let endings = if before != after {
+        format!(", line endings {} → {}", before, after)
+    } else {
+        String::new()
+    };
+
+    let mut out = vec![format!(
+        "{}: changed since last read (+{} -{}{})",
+        name, added, removed, endings
+    )];
+    out.extend(unified_hunks(&a, &b, &edits));
+    Some(out.join("\n"))
+}
+
+/// Myers shortest edit script over lines, after trimming the common prefix
+/// and suffix. `None` when more than `MAX_EDITS` edits are needed.
+fn edit_script(a: &[&str], b: &[&str]) -> Option<Vec<Edit>> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let mid = myers(&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix])?;
+
+    let mut edits = vec![Edit::Keep; prefix];
+    edits.extend(mid);
+    edits.resize(edits.len() + suffix, Edit::Keep);
+    Some(edits)
+}
+
+fn myers(a: &[&str], b: &[&str]) -> Option<Vec<Edit>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (n + m) as usize;
+    let offset = max as isize + 1;
+    let idx = |k: isize| (offset + k) as usize;
+    let mut v = vec![0isize; 2 * max + 3];
+    // trace[d] holds v[-d..=d] as it was before step d
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    for d in 0..=max.min(MAX_EDITS) as isize {
+        trace.push(v[idx(-d)..=idx(d)].to_vec());
+        let mut k = -d;
+        while k <= d {
+            let mut x = if k == -d || (k != d && v[idx(k - 1)] < v[idx(k + 1)]) {
+                v[idx(k + 1)]
+            } else {
+                v[idx(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx(k)] = x;
+            if x >= n && y >= m {
+                return Some(backtrack(&trace, n, m));
+            }
+            k += 2;
+        }
+    }
+    None
+}
+
+fn backtrack(trace: &[Vec<isize>], n: isize, m: isize) -> Vec<Edit> {
+    let (mut x, mut y) = (n, m);
+    let mut edits = Vec::new();
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        if d == 0 {
+            edits.resize(edits.len() + x as usize, Edit::Keep);
+            break;
+        }
+        let at = |k: isize| v[(k + d) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Keep);
+            x -= 1;
+            y -= 1;
+        }
+        edits.push(if x == prev_x {
+            Edit::Insert
+        } else {
+            Edit::Delete
+        });
+        x = prev_x;
+        y = prev_y;
+    }
+    edits.reverse();
+    edits
+}
+
+/// Format edits as `@@ -a,n +b,m @@` hunks with `CONTEXT` lines around changes.
+fn unified_hunks(a: &[&str], b: &[&str], edits: &[Edit]) -> Vec<String> {
+    // Position in a and b before each edit
+    let mut positions = Vec::with_capacity(edits.len() + 1);
+    let (mut i, mut j) = (0, 0);
+    for edit in edits {
+        positions.push((i, j));
+        match edit {
+            Edit::Keep => {
+                i += 1;
+                j += 1;
+            }
+            Edit::Delete => i += 1,
+            Edit::Insert => j += 1,
+        }
+    }
+    positions.push((i, j));
+
+    let changes: Vec<usize> = (0..edits.len())
+        .filter(|&e| edits[e] != Edit::Keep)
+        .collect();
+    let mut groups: Vec<(usize, usize)> = Vec::new();
+    for &c in &changes {
+        match groups.last_mut() {
+            Some((_, end)) if c - *end <= 2 * CONTEXT => *end = c,
+            _ => groups.push((c, c)),
+        }
+    }
+
+    let mut out = Vec::new();
+    for (first, last) in groups {
+        let start = first.saturating_sub(CONTEXT);
+        let end = (last + CONTEXT + 1).min(edits.len());
+        let (a0, b0) = positions[start];
+        let (a1, b1) = positions[end];
+        out.push(format!(
+            "@@ -{},{} +{},{} @@",
+            a0 + 1,
+            a1 - a0,
+            b0 + 1,
+            b1 - b0
+        ));
+        for e in start..end {
+            let (i, j) = positions[e];
+            out.push(match edits[e] {
+                Edit::Keep => format!(" {}", a[i]),
+                Edit::Delete => format!("-{}", a[i]),
+                Edit::Insert => format!("+{}", b[j]),
+            });
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(a: &[&str], b: &[&str], edits: &[Edit]) -> Vec<String> {
+        let (mut i, mut j) = (0, 0);
+        let mut out = Vec::new();
+        for edit in edits {
+            match edit {
+                Edit::Keep => {
+                    out.push(a[i].to_string());
+                    i += 1;
+                    j += 1;
+                }
+                Edit::Delete => i += 1,
+                Edit::Insert => {
+                    out.push(b[j].to_string());
+                    j += 1;
+                }
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_edit_script_reconstructs_target() {
+        let a = ["a", "b", "c", "d", "e", "f"];
+        let b = ["a", "x", "c", "d", "f", "g"];
+        let edits = edit_script(&a, &b).unwrap();
+        assert_eq!(apply(&a, &b, &edits), b);
+        let changed = edits.iter().filter(|e| **e != Edit::Keep).count();
+        assert_eq!(changed, 4); // -b +x -e +g
+    }
+
+    #[test]
+    fn test_edit_script_edge_cases() {
+        assert_eq!(edit_script(&[], &["a"]).unwrap(), vec![Edit::Insert]);
+        assert_eq!(edit_script(&["a"], &[]).unwrap(), vec![Edit::Delete]);
+        assert!(edit_script(&[], &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_render_delta_hunks() {
+        let old: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+        let new = old.replace("line 10\n", "line ten\n");
+        let delta = render_delta("f.rs", &old, &new).unwrap();
+        let expected = "f.rs: changed since last read (+1 -1)\n\
+                        @@ -7,7 +7,7 @@\n line 7\n line 8\n line 9\n-line 10\n+line ten\n line 11\n line 12\n line 13";
+        assert_eq!(delta, expected);
+    }
+
+    #[test]
+    fn test_render_delta_unchanged() {
+        assert_eq!(
+            render_delta("f.rs", "a\nb\n", "a\nb\n").unwrap(),
+            "f.rs: unchanged since last read (2 lines)"
+        );
+    }
+
+    #[test]
+    fn test_render_delta_line_endings_only() {
+        assert_eq!(
+            render_delta("f.rs", "a\nb\n", "a\r\nb\r\n").unwrap(),
+            "f.rs: changed since last read (+0 -0, line endings LF → CRLF)"
+        );
+    }
+
+    #[test]
+    fn test_distant_changes_split_hunks() {
+        let old: Vec<String> = (0..40).map(|i| i.to_string()).collect();
+        let mut new = old.clone();
+        new[2] = "x".into();
+        new[30] = "y".into();
+        let a: Vec<&str> = old.iter().map(String::as_str).collect();
+        let b: Vec<&str> = new.iter().map(String::as_str).collect();
+        let hunks = unified_hunks(&a, &b, &edit_script(&a, &b).unwrap());
+        assert_eq!(hunks.iter().filter(|l| l.starts_with("@@")).count(), 2);
+        assert_eq!(hunks[0], "@@ -1,6 +1,6 @@");
+    }
+}
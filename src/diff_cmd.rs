@@ -1,5 +1,5 @@
 use crate::tracking;
-use crate::utils::truncate;
+use crate::utils::{line_endings, truncate};
 use anyhow::Result;
 use std::fs;
 use std::path::Path;
@@ -15,14 +15,26 @@ pub fn run(file1: &Path, file2: &Path, verbose: u8) -> Result<()> {
     let content1 = fs::read_to_string(file1)?;
     let content2 = fs::read_to_string(file2)?;
     let raw = format!("{}\n---\n{}", content1, content2);
+    crate::crash::raw_output(&raw);
 
     let lines1: Vec<&str> = content1.lines().collect();
     let lines2: Vec<&str> = content2.lines().collect();
     let diff = compute_diff(&lines1, &lines2);
     let mut rtk = String::new();
 
-    if diff.added == 0 && diff.removed == 0 {
-        rtk.push_str("✅ Files are identical");
+    // Lines are compared without their CR, so CRLF/LF-only changes are reported once
+    let endings = (line_endings(&content1), line_endings(&content2));
+    let endings_note = if endings.0 != endings.1 {
+        Some(format!("line endings {} → {}", endings.0, endings.1))
+    } else {
+        None
+    };
+
+    if diff.added == 0 && diff.removed == 0 && diff.modified == 0 {
+        match &endings_note {
+            Some(note) => rtk.push_str(&format!("✅ Files are identical except {}", note)),
+            None => rtk.push_str("✅ Files are identical"),
+        }
         println!("{}", rtk);
         timer.track(
             &format!("diff {} {}", file1.display(), file2.display()),
@@ -38,6 +50,9 @@ pub fn run(file1: &Path, file2: &Path, verbose: u8) -> Result<()> {
         "   +{} added, -{} removed, ~{} modified\n\n",
         diff.added, diff.removed, diff.modified
     ));
+    if let Some(note) = &endings_note {
+        rtk.push_str(&format!("   {}\n", note));
+    }
 
     for change in diff.changes.iter().take(50) {
         match change {
@@ -55,7 +70,7 @@ pub fn run(file1: &Path, file2: &Path, verbose: u8) -> Result<()> {
         rtk.push_str(&format!("... +{} more changes", diff.changes.len() - 50));
     }
 
-    print!("{}", rtk);
+    crate::profile::println_quoting(rtk.strip_suffix('\n').unwrap_or(&rtk), is_change_line);
     timer.track(
         &format!("diff {} {}", file1.display(), file2.display()),
         "rtk diff",
@@ -75,13 +90,18 @@ pub fn run_stdin(_verbose: u8) -> Result<()> {
 
     // Parse unified diff format
     let condensed = condense_unified_diff(&input);
-    println!("{}", condensed);
+    crate::profile::println_quoting(&condensed, crate::git::is_hunk_line);
 
     timer.track("diff (stdin)", "rtk diff (stdin)", &input, &condensed);
 
     Ok(())
 }
 
+/// `+`/`-`/`~` lines quote the files; the rest is rtk's summary.
+fn is_change_line(line: &str) -> bool {
+    line.starts_with(['+', '-', '~'])
+}
+
 #[derive(Debug)]
 enum DiffChange {
     Added(usize, String),
@@ -316,6 +336,14 @@ mod tests {
         assert_eq!(result.removed, 1);
     }
 
+    #[test]
+    fn test_compute_diff_ignores_crlf() {
+        let crlf: Vec<&str> = "a\r\nb\r\n".lines().collect();
+        let lf: Vec<&str> = "a\nb\n".lines().collect();
+        let result = compute_diff(&crlf, &lf);
+        assert!(result.changes.is_empty());
+    }
+
     #[test]
     fn test_compute_diff_empty_inputs() {
         let result = compute_diff(&[], &[]);
@@ -208,7 +208,7 @@ pub fn run(
     };
 
     match format {
-        "json" => println!("{}", report::format_json(&report)),
+        "json" => println_verbatim!("{}", report::format_json(&report)),
         _ => print!("{}", report::format_text(&report, limit, verbose > 0)),
     }
 
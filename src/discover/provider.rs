@@ -35,7 +35,7 @@ pub struct ClaudeProvider;
 
 impl ClaudeProvider {
     /// Get the base directory for Claude Code projects.
-    fn projects_dir() -> Result<PathBuf> {
+    pub fn projects_dir() -> Result<PathBuf> {
         let home = dirs::home_dir().context("could not determine home directory")?;
         let dir = home.join(".claude").join("projects");
         if !dir.exists() {
@@ -47,20 +47,19 @@ impl ClaudeProvider {
         Ok(dir)
     }
 
-    /// Encode a filesystem path to Claude Code's directory name format.
-    /// `/Users/foo/bar` → `-Users-foo-bar`
-    pub fn encode_project_path(path: &str) -> String {
-        path.replace('/', "-")
-    }
-}
-
-impl SessionProvider for ClaudeProvider {
-    fn discover_sessions(
-        &self,
+    /// Find session files under an explicit projects directory
+    /// (e.g. a copied `~/.claude/projects` passed to `rtk gain reconcile --transcripts`).
+    pub fn discover_sessions_in(
+        projects_dir: &Path,
         project_filter: Option<&str>,
         since_days: Option<u64>,
     ) -> Result<Vec<PathBuf>> {
-        let projects_dir = Self::projects_dir()?;
+        if !projects_dir.is_dir() {
+            anyhow::bail!(
+                "transcripts directory not found: {}",
+                projects_dir.display()
+            );
+        }
         let cutoff = since_days.map(|days| {
             SystemTime::now()
                 .checked_sub(Duration::from_secs(days * 86400))
@@ -70,7 +69,7 @@ impl SessionProvider for ClaudeProvider {
         let mut sessions = Vec::new();
 
         // List project directories
-        let entries = fs::read_dir(&projects_dir)
+        let entries = fs::read_dir(projects_dir)
             .with_context(|| format!("failed to read {}", projects_dir.display()))?;
 
         for entry in entries.flatten() {
@@ -116,6 +115,22 @@ impl SessionProvider for ClaudeProvider {
         Ok(sessions)
     }
 
+    /// Encode a filesystem path to Claude Code's directory name format.
+    /// `/Users/foo/bar` → `-Users-foo-bar`
+    pub fn encode_project_path(path: &str) -> String {
+        path.replace('/', "-")
+    }
+}
+
+impl SessionProvider for ClaudeProvider {
+    fn discover_sessions(
+        &self,
+        project_filter: Option<&str>,
+        since_days: Option<u64>,
+    ) -> Result<Vec<PathBuf>> {
+        Self::discover_sessions_in(&Self::projects_dir()?, project_filter, since_days)
+    }
+
     fn extract_commands(&self, path: &Path) -> Result<Vec<ExtractedCommand>> {
         let file =
             fs::File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
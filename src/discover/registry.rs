@@ -280,9 +280,14 @@ const IGNORED_PREFIXES: &[&str] = &[
     "while ",
     "if ",
     "case ",
+    "$env:",
+    "Set-Location ",
+    "Write-Host ",
 ];
 
-const IGNORED_EXACT: &[&str] = &["cd", "echo", "true", "false", "wait", "pwd", "bash", "sh"];
+const IGNORED_EXACT: &[&str] = &[
+    "cd", "echo", "true", "false", "wait", "pwd", "bash", "sh", "cls",
+];
 
 lazy_static! {
     static ref REGEX_SET: RegexSet = RegexSet::new(PATTERNS).expect("invalid regex patterns");
@@ -296,7 +301,8 @@ lazy_static! {
 
 /// Classify a single (already-split) command.
 pub fn classify_command(cmd: &str) -> Classification {
-    let trimmed = cmd.trim();
+    let normalized = normalize_windows(cmd.trim());
+    let trimmed = normalized.trim();
     if trimmed.is_empty() {
         return Classification::Ignored;
     }
@@ -372,6 +378,107 @@ pub fn classify_command(cmd: &str) -> Classification {
     }
 }
 
+/// Shell a Windows command line was wrapped in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WinShell {
+    None,
+    Cmd,
+    PowerShell,
+}
+
+/// Rewrite Windows command lines into the POSIX form the patterns expect:
+/// `cmd /c` and `powershell -Command` wrappers are unwrapped,
+/// `C:\...\git.exe` becomes `git`, and cmd/PowerShell builtins map to
+/// their Unix equivalents (`type` → `cat`, `Get-ChildItem` → `ls`, ...).
+fn normalize_windows(cmd: &str) -> String {
+    let (shell, inner) = unwrap_windows_shell(cmd);
+    let (program, rest) = split_program(inner);
+    let name = program_name(program);
+    let name = windows_alias(&name, shell).map_or(name, str::to_string);
+    if rest.is_empty() {
+        name
+    } else {
+        format!("{} {}", name, rest)
+    }
+}
+
+fn unwrap_windows_shell(cmd: &str) -> (WinShell, &str) {
+    let (program, mut rest) = split_program(cmd);
+    match program_name(program).to_ascii_lowercase().as_str() {
+        "cmd" => {
+            let (flag, inner) = split_program(rest);
+            if flag.eq_ignore_ascii_case("/c") || flag.eq_ignore_ascii_case("/k") {
+                return (WinShell::Cmd, unquote(inner));
+            }
+        }
+        "powershell" | "pwsh" => loop {
+            let (flag, after) = split_program(rest);
+            if !flag.starts_with('-') {
+                return (WinShell::PowerShell, unquote(rest));
+            }
+            if flag.eq_ignore_ascii_case("-c") || flag.eq_ignore_ascii_case("-command") {
+                return (WinShell::PowerShell, unquote(after));
+            }
+            rest = if flag.eq_ignore_ascii_case("-executionpolicy") {
+                split_program(after).1
+            } else {
+                after
+            };
+        },
+        _ => {}
+    }
+    (WinShell::None, cmd)
+}
+
+/// First word (or quoted program path) and the rest of the command.
+fn split_program(cmd: &str) -> (&str, &str) {
+    let cmd = cmd.trim_start();
+    if let Some(quoted) = cmd.strip_prefix('"') {
+        if let Some(end) = quoted.find('"') {
+            return (&quoted[..end], quoted[end + 1..].trim_start());
+        }
+    }
+    match cmd.find(char::is_whitespace) {
+        Some(i) => (&cmd[..i], cmd[i..].trim_start()),
+        None => (cmd, ""),
+    }
+}
+
+/// `C:\Program Files\Git\cmd\git.exe` → `git`. Unix programs are kept as is.
+fn program_name(program: &str) -> String {
+    let lower = program.to_ascii_lowercase();
+    let executable = [".exe", ".cmd", ".bat"]
+        .iter()
+        .any(|ext| lower.ends_with(ext));
+    if !executable && !program.contains('\\') {
+        return program.to_string();
+    }
+    let base = program.rsplit(['\\', '/']).next().unwrap_or(program);
+    match base.rfind('.') {
+        Some(dot) if executable => base[..dot].to_string(),
+        _ => base.to_string(),
+    }
+}
+
+fn windows_alias(name: &str, shell: WinShell) -> Option<&'static str> {
+    match (name.to_ascii_lowercase().as_str(), shell) {
+        ("get-content", _) | ("type", WinShell::Cmd) | ("gc", WinShell::PowerShell) => Some("cat"),
+        ("get-childitem", _) | ("dir", _) | ("gci", WinShell::PowerShell) => Some("ls"),
+        ("select-string", _) | ("findstr", _) | ("sls", WinShell::PowerShell) => Some("grep"),
+        _ => None,
+    }
+}
+
+fn unquote(s: &str) -> &str {
+    let s = s.trim();
+    for quote in ['"', '\''] {
+        if let Some(inner) = s.strip_prefix(quote).and_then(|r| r.strip_suffix(quote)) {
+            return inner;
+        }
+    }
+    s
+}
+
 /// Extract the base command (first word, or first two if it looks like a subcommand pattern).
 fn extract_base_command(cmd: &str) -> &str {
     let parts: Vec<&str> = cmd.splitn(3, char::is_whitespace).collect();
@@ -401,7 +508,7 @@ fn extract_base_command(cmd: &str) -> &str {
     }
 }
 
-/// Split a command chain on `&&`, `||`, `;` outside quotes.
+/// Split a command chain on `&&`, `||`, `;` and cmd's ` & ` outside quotes.
 /// For pipes `|`, only keep the first command.
 /// Lines with `<<` (heredoc) or `$((` are returned whole.
 pub fn split_command_chain(cmd: &str) -> Vec<&str> {
@@ -462,6 +569,20 @@ pub fn split_command_chain(cmd: &str) -> Vec<&str> {
                 i += 2;
                 start = i;
             }
+            // cmd.exe chains with a lone `&`; `2>&1` and `&>` are redirections
+            b'&' if !in_single
+                && !in_double
+                && i > 0
+                && bytes[i - 1].is_ascii_whitespace()
+                && bytes.get(i + 1).is_none_or(|b| b.is_ascii_whitespace()) =>
+            {
+                let segment = trimmed[start..i].trim();
+                if !segment.is_empty() {
+                    results.push(segment);
+                }
+                i += 1;
+                start = i;
+            }
             b';' if !in_single && !in_double => {
                 let segment = trimmed[start..i].trim();
                 if !segment.is_empty() {
@@ -727,6 +848,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_split_cmd_ampersand() {
+        assert_eq!(
+            split_command_chain("cd repo & git status 2>&1"),
+            vec!["cd repo", "git status 2>&1"]
+        );
+    }
+
+    #[test]
+    fn test_classify_windows_wrappers() {
+        let git = classify_command("git status");
+        assert_eq!(classify_command(r#"cmd /c "git status""#), git);
+        assert_eq!(
+            classify_command("powershell -NoProfile -Command git status"),
+            git
+        );
+        assert_eq!(
+            classify_command(r#""C:\Program Files\Git\cmd\git.exe" status"#),
+            git
+        );
+        assert_eq!(
+            classify_command("cargo.exe test"),
+            classify_command("cargo test")
+        );
+    }
+
+    #[test]
+    fn test_classify_windows_builtins() {
+        let cat = classify_command("cat src\\main.rs");
+        assert_eq!(classify_command(r"cmd /c type src\main.rs"), cat);
+        assert_eq!(classify_command(r"Get-Content src\main.rs"), cat);
+        assert_eq!(classify_command("dir"), classify_command("ls"));
+        assert_eq!(
+            classify_command("$env:RUST_LOG=debug"),
+            Classification::Ignored
+        );
+        // Bash `type` is still a builtin lookup, not a file read
+        assert_eq!(classify_command("type git"), Classification::Ignored);
+    }
+
     #[test]
     fn test_split_heredoc_no_split() {
         let cmd = "cat <<'EOF'\nhello && world\nEOF";
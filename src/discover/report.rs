@@ -1,3 +1,4 @@
+use crate::utils::{display_width, pad, take_width};
 use serde::Serialize;
 
 /// RTK support status for a command.
@@ -104,8 +105,8 @@ pub fn format_text(report: &DiscoverReport, limit: usize, verbose: bool) -> Stri
 
         for entry in report.supported.iter().take(limit) {
             out.push_str(&format!(
-                "{:<24} {:>5}    {:<18} {:<13} ~{}\n",
-                truncate_str(&entry.command, 23),
+                "{} {:>5}    {:<18} {:<13} ~{}\n",
+                pad(&truncate_str(&entry.command, 23), 24),
                 entry.count,
                 entry.rtk_equivalent,
                 entry.rtk_status.as_str(),
@@ -134,8 +135,8 @@ pub fn format_text(report: &DiscoverReport, limit: usize, verbose: bool) -> Stri
 
         for entry in report.unsupported.iter().take(limit) {
             out.push_str(&format!(
-                "{:<24} {:>5}    {}\n",
-                truncate_str(&entry.base_command, 23),
+                "{} {:>5}    {}\n",
+                pad(&truncate_str(&entry.base_command, 23), 24),
                 entry.count,
                 truncate_str(&entry.example, 40),
             ));
@@ -171,15 +172,9 @@ fn format_tokens(tokens: usize) -> String {
 }
 
 fn truncate_str(s: &str, max: usize) -> String {
-    if s.len() <= max {
+    if display_width(s) <= max {
         s.to_string()
     } else {
-        // UTF-8 safe truncation: collect chars up to max-2, then add ".."
-        let truncated: String = s
-            .char_indices()
-            .take_while(|(i, _)| *i < max.saturating_sub(2))
-            .map(|(_, c)| c)
-            .collect();
-        format!("{}..", truncated)
+        format!("{}..", take_width(s, max.saturating_sub(2)))
     }
 }
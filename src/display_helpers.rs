@@ -51,6 +51,12 @@ pub trait PeriodStats {
     /// Average execution time per command in milliseconds
     fn avg_time_ms(&self) -> u64;
 
+    /// 95th percentile execution time in milliseconds
+    fn p95_time_ms(&self) -> u64;
+
+    /// Slowest execution in milliseconds
+    fn max_time_ms(&self) -> u64;
+
     /// Period column width for alignment
     fn period_width() -> usize;
 
@@ -77,7 +83,7 @@ pub fn print_period_table<T: PeriodStats>(data: &[T]) {
     );
     println!("{}", separator);
     println!(
-        "{:<width$} {:>7} {:>10} {:>10} {:>10} {:>7} {:>8}",
+        "{:<width$} {:>7} {:>10} {:>10} {:>10} {:>7} {:>8} {:>8} {:>8}",
         match T::label() {
             "Weekly" => "Week",
             "Monthly" => "Month",
@@ -89,13 +95,15 @@ pub fn print_period_table<T: PeriodStats>(data: &[T]) {
         "Saved",
         "Save%",
         "Time",
+        "P95",
+        "Max",
         width = period_width
     );
     println!("{}", "─".repeat(T::separator_width()));
 
     for period in data {
         println!(
-            "{:<width$} {:>7} {:>10} {:>10} {:>10} {:>6.1}% {:>8}",
+            "{:<width$} {:>7} {:>10} {:>10} {:>10} {:>6.1}% {:>8} {:>8} {:>8}",
             period.period(),
             period.commands(),
             format_tokens(period.input_tokens()),
@@ -103,6 +111,8 @@ pub fn print_period_table<T: PeriodStats>(data: &[T]) {
             format_tokens(period.saved_tokens()),
             period.savings_pct(),
             format_duration(period.avg_time_ms()),
+            format_duration(period.p95_time_ms()),
+            format_duration(period.max_time_ms()),
             width = period_width
         );
     }
@@ -113,6 +123,8 @@ pub fn print_period_table<T: PeriodStats>(data: &[T]) {
     let total_output: usize = data.iter().map(|d| d.output_tokens()).sum();
     let total_saved: usize = data.iter().map(|d| d.saved_tokens()).sum();
     let total_time: u64 = data.iter().map(|d| d.total_time_ms()).sum();
+    // Percentiles don't aggregate across periods; only the max is exact
+    let max_time: u64 = data.iter().map(|d| d.max_time_ms()).max().unwrap_or(0);
     let avg_pct = if total_input > 0 {
         (total_saved as f64 / total_input as f64) * 100.0
     } else {
@@ -126,7 +138,7 @@ pub fn print_period_table<T: PeriodStats>(data: &[T]) {
 
     println!("{}", "─".repeat(T::separator_width()));
     println!(
-        "{:<width$} {:>7} {:>10} {:>10} {:>10} {:>6.1}% {:>8}",
+        "{:<width$} {:>7} {:>10} {:>10} {:>10} {:>6.1}% {:>8} {:>8} {:>8}",
         "TOTAL",
         total_cmds,
         format_tokens(total_input),
@@ -134,11 +146,82 @@ pub fn print_period_table<T: PeriodStats>(data: &[T]) {
         format_tokens(total_saved),
         avg_pct,
         format_duration(avg_time),
+        "-",
+        format_duration(max_time),
         width = period_width
     );
     println!();
 }
 
+/// Render period statistics as a GitHub-flavored Markdown table (with TOTAL row)
+pub fn format_period_markdown<T: PeriodStats>(data: &[T]) -> String {
+    let mut out = format!("### {} Breakdown\n\n", T::label());
+
+    if data.is_empty() {
+        out.push_str(&format!(
+            "No {} data available.\n",
+            T::label().to_lowercase()
+        ));
+        return out;
+    }
+
+    let period_header = match T::label() {
+        "Weekly" => "Week",
+        "Monthly" => "Month",
+        _ => "Date",
+    };
+    out.push_str(&format!(
+        "| {} | Cmds | Input | Output | Saved | Save% | Time | P95 | Max |\n",
+        period_header
+    ));
+    out.push_str("|---|---:|---:|---:|---:|---:|---:|---:|---:|\n");
+
+    for period in data {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {:.1}% | {} | {} | {} |\n",
+            period.period(),
+            period.commands(),
+            format_tokens(period.input_tokens()),
+            format_tokens(period.output_tokens()),
+            format_tokens(period.saved_tokens()),
+            period.savings_pct(),
+            format_duration(period.avg_time_ms()),
+            format_duration(period.p95_time_ms()),
+            format_duration(period.max_time_ms())
+        ));
+    }
+
+    let total_cmds: usize = data.iter().map(|d| d.commands()).sum();
+    let total_input: usize = data.iter().map(|d| d.input_tokens()).sum();
+    let total_output: usize = data.iter().map(|d| d.output_tokens()).sum();
+    let total_saved: usize = data.iter().map(|d| d.saved_tokens()).sum();
+    let total_time: u64 = data.iter().map(|d| d.total_time_ms()).sum();
+    // Percentiles don't aggregate across periods; only the max is exact
+    let max_time: u64 = data.iter().map(|d| d.max_time_ms()).max().unwrap_or(0);
+    let avg_pct = if total_input > 0 {
+        (total_saved as f64 / total_input as f64) * 100.0
+    } else {
+        0.0
+    };
+    let avg_time = if total_cmds > 0 {
+        total_time / total_cmds as u64
+    } else {
+        0
+    };
+
+    out.push_str(&format!(
+        "| **TOTAL** | **{}** | **{}** | **{}** | **{}** | **{:.1}%** | **{}** | - | **{}** |\n",
+        total_cmds,
+        format_tokens(total_input),
+        format_tokens(total_output),
+        format_tokens(total_saved),
+        avg_pct,
+        format_duration(avg_time),
+        format_duration(max_time)
+    ));
+    out
+}
+
 // ── Trait Implementations ──
 
 impl PeriodStats for DayStats {
@@ -182,12 +265,20 @@ impl PeriodStats for DayStats {
         self.avg_time_ms
     }
 
+    fn p95_time_ms(&self) -> u64 {
+        self.p95_time_ms
+    }
+
+    fn max_time_ms(&self) -> u64 {
+        self.max_time_ms
+    }
+
     fn period_width() -> usize {
         12
     }
 
     fn separator_width() -> usize {
-        74
+        92
     }
 }
 
@@ -242,12 +333,20 @@ impl PeriodStats for WeekStats {
         self.avg_time_ms
     }
 
+    fn p95_time_ms(&self) -> u64 {
+        self.p95_time_ms
+    }
+
+    fn max_time_ms(&self) -> u64 {
+        self.max_time_ms
+    }
+
     fn period_width() -> usize {
         22
     }
 
     fn separator_width() -> usize {
-        82
+        100
     }
 }
 
@@ -292,12 +391,20 @@ impl PeriodStats for MonthStats {
         self.avg_time_ms
     }
 
+    fn p95_time_ms(&self) -> u64 {
+        self.p95_time_ms
+    }
+
+    fn max_time_ms(&self) -> u64 {
+        self.max_time_ms
+    }
+
     fn period_width() -> usize {
         10
     }
 
     fn separator_width() -> usize {
-        74
+        92
     }
 }
 
@@ -316,6 +423,9 @@ mod tests {
             savings_pct: 20.0,
             total_time_ms: 1500,
             avg_time_ms: 150,
+            p50_time_ms: 130,
+            p95_time_ms: 600,
+            max_time_ms: 1200,
         };
 
         assert_eq!(day.period(), "2026-01-20");
@@ -338,6 +448,9 @@ mod tests {
             savings_pct: 40.0,
             total_time_ms: 5000,
             avg_time_ms: 100,
+            p50_time_ms: 80,
+            p95_time_ms: 400,
+            max_time_ms: 800,
         };
 
         assert_eq!(week.period(), "01-20 → 01-26");
@@ -357,10 +470,15 @@ mod tests {
             savings_pct: 50.0,
             total_time_ms: 20000,
             avg_time_ms: 100,
+            p50_time_ms: 80,
+            p95_time_ms: 400,
+            max_time_ms: 800,
         };
 
         assert_eq!(month.period(), "2026-01");
         assert_eq!(month.avg_time_ms(), 100);
+        assert_eq!(month.p95_time_ms(), 400);
+        assert_eq!(month.max_time_ms(), 800);
         assert_eq!(MonthStats::icon(), "📆");
         assert_eq!(MonthStats::label(), "Monthly");
     }
@@ -384,6 +502,9 @@ mod tests {
                 savings_pct: 20.0,
                 total_time_ms: 1500,
                 avg_time_ms: 150,
+                p50_time_ms: 130,
+                p95_time_ms: 600,
+                max_time_ms: 1200,
             },
             DayStats {
                 date: "2026-01-21".to_string(),
@@ -394,9 +515,46 @@ mod tests {
                 savings_pct: 30.0,
                 total_time_ms: 2250,
                 avg_time_ms: 150,
+                p50_time_ms: 130,
+                p95_time_ms: 600,
+                max_time_ms: 1200,
             },
         ];
         print_period_table(&data);
         // Should print table with 2 rows + total
     }
+
+    #[test]
+    fn test_format_period_markdown_with_data() {
+        let data = vec![MonthStats {
+            month: "2026-01".to_string(),
+            commands: 200,
+            input_tokens: 20000,
+            output_tokens: 10000,
+            saved_tokens: 5000,
+            savings_pct: 25.0,
+            total_time_ms: 20000,
+            avg_time_ms: 100,
+            p50_time_ms: 80,
+            p95_time_ms: 400,
+            max_time_ms: 800,
+        }];
+        let md = format_period_markdown(&data);
+        assert!(md.starts_with("### Monthly Breakdown"));
+        assert!(md.contains("| Month | Cmds |"));
+        assert!(md.contains("|---|---:|"));
+        assert!(
+            md.contains("| 2026-01 | 200 | 20.0K | 10.0K | 5.0K | 25.0% | 100ms | 400ms | 800ms |")
+        );
+        assert!(md.contains("| **TOTAL** | **200** |"));
+        assert!(md.contains("| - | **800ms** |"));
+    }
+
+    #[test]
+    fn test_format_period_markdown_empty() {
+        let data: Vec<WeekStats> = vec![];
+        let md = format_period_markdown(&data);
+        assert!(md.contains("No weekly data available."));
+        assert!(!md.contains("|---"));
+    }
 }
@@ -0,0 +1,445 @@
+//! `rtk doc <file.pdf|file.docx>` — read documents agents otherwise can't.
+//!
+//! Text is extracted page by page (PDF via pdf-extract, `.docx` from
+//! `word/document.xml` with Word's page breaks) and turned into markdown:
+//! Word heading styles become `#` headings; in PDFs, short standalone lines
+//! that look like titles (numbered, ALL CAPS or Title Case) do, and running
+//! headers/footers repeated on most pages are dropped.
+//!
+//! By default the result goes through the prose summarizer (see
+//! summarize_cmd.rs) with each heading labelled by its page; `--page 3` or
+//! `--page 3-5` prints pages in full, `--section` one section, and `--full`
+//! the whole text.
+
+use crate::markdown;
+use crate::summarize_cmd;
+use crate::tracking::{self, estimate_tokens};
+use crate::utils::format_tokens;
+use anyhow::{bail, Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+lazy_static! {
+    static ref NUMBERED: Regex = Regex::new(r"^((?:\d+\.)*\d+)\.?\s+\p{Lu}").unwrap();
+    static ref DOCX_BLOCK: Regex =
+        Regex::new(r"(?s)<w:tr\b.*?</w:tr>|<w:p\b[^>]*/>|<w:p\b.*?</w:p>").unwrap();
+    static ref DOCX_CELL: Regex = Regex::new(r"(?s)<w:tc\b.*?</w:tc>").unwrap();
+    static ref DOCX_RUN: Regex = Regex::new(
+        r#"<w:t(?:\s[^>]*)?>([^<]*)</w:t>|<w:tab/>|<w:br w:type="page"/>|<w:lastRenderedPageBreak/>|<w:br/>"#
+    )
+    .unwrap();
+    static ref DOCX_STYLE: Regex = Regex::new(r#"<w:pStyle w:val="([^"]+)"/>"#).unwrap();
+    static ref HEADING_STYLE: Regex = Regex::new(r"(?i)^(?:heading|berschrift|titre)\s*(\d)$").unwrap();
+}
+
+/// Which part of the document to print.
+pub enum Mode {
+    Summary { max_tokens: usize },
+    Pages(String),
+    Section(String),
+    Full,
+}
+
+pub fn run(file: &Path, mode: Mode, verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let pages = extract(file)?;
+    if verbose > 0 {
+        eprintln!("{}: {} pages extracted", file.display(), pages.len());
+    }
+    let text = pages.join("\n\n");
+    let name = file.display().to_string();
+
+    let output = match mode {
+        Mode::Summary { max_tokens } => summary(&name, &pages, max_tokens),
+        Mode::Pages(range) => {
+            let (first, last) = parse_range(&range, pages.len())?;
+            (first..=last)
+                .map(|n| {
+                    format!(
+                        "--- page {}/{} ---\n{}",
+                        n,
+                        pages.len(),
+                        pages[n - 1].trim()
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        }
+        Mode::Section(query) => markdown::extract_section(&text, &query)?,
+        Mode::Full => text.clone(),
+    };
+    println!("{}", output.trim_end());
+    timer.track(
+        &format!("cat {}", name),
+        &format!("rtk doc {}", name),
+        &text,
+        &output,
+    );
+    Ok(())
+}
+
+fn summary(name: &str, pages: &[String], max_tokens: usize) -> String {
+    let text = pages.join("\n\n");
+    // 1-based line where each page starts in `text`
+    let mut starts = Vec::with_capacity(pages.len());
+    let mut line = 1;
+    for page in pages {
+        starts.push(line);
+        line += page.lines().count() + 1;
+    }
+    let locate = |line: usize| format!("p{}", starts.partition_point(|&s| s <= line).max(1));
+
+    format!(
+        "{}: {} pages, {} words, ~{} tokens\n{}\n(rtk doc {} --page N for a full page, --section \"<heading>\")",
+        name,
+        pages.len(),
+        text.split_whitespace().count(),
+        format_tokens(estimate_tokens(&text)),
+        summarize_cmd::summarize_text(&text, max_tokens, &locate).trim_end(),
+        name
+    )
+}
+
+/// `3` or `3-5`, 1-based and inclusive, checked against `count`.
+fn parse_range(range: &str, count: usize) -> Result<(usize, usize)> {
+    let (first, last) = match range.split_once('-') {
+        Some((a, b)) => (a.trim().parse::<usize>(), b.trim().parse::<usize>()),
+        None => (range.trim().parse(), range.trim().parse()),
+    };
+    let (Ok(first), Ok(last)) = (first, last) else {
+        bail!("Invalid page range '{}': expected N or A-B", range);
+    };
+    if first == 0 || first > last || last > count {
+        bail!(
+            "Page range {} out of bounds: document has {} pages",
+            range,
+            count
+        );
+    }
+    Ok((first, last))
+}
+
+/// Markdown text of each page.
+fn extract(file: &Path) -> Result<Vec<String>> {
+    let ext = file
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    let bytes =
+        std::fs::read(file).with_context(|| format!("Failed to read file: {}", file.display()))?;
+    let pages = match ext.as_str() {
+        "pdf" => pdf_pages(&bytes)?,
+        "docx" => docx_pages(&bytes)?,
+        _ => bail!(
+            "Unsupported document type '{}': expected .pdf or .docx",
+            file.display()
+        ),
+    };
+    if pages.iter().all(|p| p.trim().is_empty()) {
+        bail!(
+            "No text found in {} (scanned images need OCR)",
+            file.display()
+        );
+    }
+    Ok(pages)
+}
+
+fn pdf_pages(bytes: &[u8]) -> Result<Vec<String>> {
+    // pdf-extract panics on some malformed files instead of returning errors.
+    // Off the main thread the panic unwinds to `join` (see crash.rs).
+    let extracted = std::thread::scope(|scope| {
+        scope
+            .spawn(|| pdf_extract::extract_text_from_mem_by_pages(bytes))
+            .join()
+    })
+    .map_err(|_| anyhow::anyhow!("Failed to parse PDF (malformed or unsupported)"))?
+    .context("Failed to extract PDF text")?;
+    Ok(pdf_to_markdown(&extracted))
+}
+
+/// Tidy extracted PDF pages: drop running headers/footers and page numbers,
+/// squeeze blank lines, and mark likely headings.
+fn pdf_to_markdown(pages: &[String]) -> Vec<String> {
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    for page in pages {
+        let mut lines: Vec<&str> = page
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect();
+        lines.dedup();
+        for line in lines {
+            *seen.entry(line).or_default() += 1;
+        }
+    }
+    let running =
+        |line: &str| pages.len() >= 3 && seen.get(line).copied().unwrap_or(0) * 2 > pages.len();
+
+    pages
+        .iter()
+        .map(|page| {
+            let lines: Vec<&str> = page
+                .lines()
+                .map(str::trim_end)
+                .filter(|l| !running(l.trim()))
+                .filter(|l| !l.trim().chars().all(|c| c.is_ascii_digit()) || l.trim().is_empty())
+                .collect();
+            let mut out: Vec<String> = Vec::new();
+            for (i, line) in lines.iter().enumerate() {
+                let blank_before = i == 0 || lines[i - 1].trim().is_empty();
+                let blank_after = lines.get(i + 1).is_none_or(|l| l.trim().is_empty());
+                if line.trim().is_empty() {
+                    if out.last().is_some_and(|l| !l.is_empty()) {
+                        out.push(String::new());
+                    }
+                } else if blank_before && blank_after {
+                    match heading_level(line.trim()) {
+                        Some(level) => out.push(format!("{} {}", "#".repeat(level), line.trim())),
+                        None => out.push(line.to_string()),
+                    }
+                } else {
+                    out.push(line.to_string());
+                }
+            }
+            out.join("\n").trim().to_string()
+        })
+        .collect()
+}
+
+/// Heading level of a standalone PDF line, if it looks like a title.
+fn heading_level(line: &str) -> Option<usize> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    if words.is_empty() || words.len() > 10 || line.len() > 80 {
+        return None;
+    }
+    if line.ends_with(['.', ',', ';', ':'])
+        || !line.starts_with(|c: char| c.is_uppercase() || c.is_ascii_digit())
+    {
+        return None;
+    }
+    if let Some(caps) = NUMBERED.captures(line) {
+        return Some(caps[1].split('.').count().min(6));
+    }
+    let letters: Vec<char> = line.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.len() >= 3 && letters.iter().all(|c| c.is_uppercase()) {
+        return Some(1);
+    }
+    let capitalized = words
+        .iter()
+        .filter(|w| w.len() > 3)
+        .all(|w| w.starts_with(char::is_uppercase));
+    (words.len() >= 2 && capitalized).then_some(2)
+}
+
+fn docx_pages(bytes: &[u8]) -> Result<Vec<String>> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
+        .context("Not a valid .docx (zip) file")?;
+    let mut xml = String::new();
+    archive
+        .by_name("word/document.xml")
+        .context("Not a Word document: word/document.xml missing")?
+        .read_to_string(&mut xml)
+        .context("Failed to read word/document.xml")?;
+    Ok(docx_to_markdown(&xml))
+}
+
+/// Paragraphs (headings as `#`, list items as `-`) and table rows as
+/// `a | b | c`, split into pages at Word's page breaks.
+fn docx_to_markdown(xml: &str) -> Vec<String> {
+    let mut pages = vec![Vec::new()];
+    for block in DOCX_BLOCK.find_iter(xml).map(|m| m.as_str()) {
+        if block.starts_with("<w:tr") {
+            let cells: Vec<String> = DOCX_CELL
+                .find_iter(block)
+                .map(|cell| {
+                    docx_text(cell.as_str(), &mut 0)
+                        .replace('\n', " ")
+                        .trim()
+                        .to_string()
+                })
+                .collect();
+            pages.last_mut().unwrap().push(cells.join(" | "));
+            continue;
+        }
+        let mut breaks = 0;
+        let text = docx_text(block, &mut breaks);
+        for _ in 0..breaks {
+            pages.push(Vec::new());
+        }
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let style = DOCX_STYLE.captures(block).map(|c| c[1].to_string());
+        let level = style.as_deref().and_then(|s| {
+            if s.eq_ignore_ascii_case("title") {
+                return Some(1);
+            }
+            HEADING_STYLE
+                .captures(s)
+                .and_then(|c| c[1].parse::<usize>().ok())
+        });
+        let line = match level {
+            Some(level) => format!("{} {}", "#".repeat(level.clamp(1, 6)), text),
+            None if block.contains("<w:numPr>") => format!("- {}", text),
+            None => text.to_string(),
+        };
+        let page = pages.last_mut().unwrap();
+        page.push(line);
+        page.push(String::new());
+    }
+    pages
+        .into_iter()
+        .map(|lines| lines.join("\n").trim().to_string())
+        .filter(|page| !page.is_empty())
+        .collect()
+}
+
+/// Text of the runs in `xml`, counting page breaks into `breaks`.
+fn docx_text(xml: &str, breaks: &mut usize) -> String {
+    let mut text = String::new();
+    for caps in DOCX_RUN.captures_iter(xml) {
+        match caps.get(1) {
+            Some(t) => text.push_str(&unescape(t.as_str())),
+            None if caps[0].starts_with("<w:tab") => text.push('\t'),
+            None if &caps[0] == "<w:br/>" => text.push('\n'),
+            None => *breaks += 1,
+        }
+    }
+    text
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_docx_headings_lists_tables_and_pages() {
+        let xml = r#"<w:document><w:body>
+<w:p><w:pPr><w:pStyle w:val="Title"/></w:pPr><w:r><w:t>Design</w:t></w:r></w:p>
+<w:p><w:r><w:t xml:space="preserve">Caches are </w:t></w:r><w:r><w:t>fast &amp; small.</w:t></w:r></w:p>
+<w:p><w:pPr><w:numPr><w:ilvl w:val="0"/></w:numPr></w:pPr><w:r><w:t>First point</w:t></w:r></w:p>
+<w:p/>
+<w:tbl><w:tr><w:tc><w:p><w:r><w:t>Key</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>Value</w:t></w:r></w:p></w:tc></w:tr></w:tbl>
+<w:p><w:r><w:br w:type="page"/></w:r><w:pPr><w:pStyle w:val="Heading2"/></w:pPr><w:r><w:t>Risks</w:t></w:r></w:p>
+</w:body></w:document>"#;
+        assert_eq!(
+            docx_to_markdown(xml),
+            vec![
+                "# Design\n\nCaches are fast & small.\n\n- First point\n\nKey | Value",
+                "## Risks"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pdf_headings_and_running_headers() {
+        let page = |n: usize, body: &str| format!("ACME Corp Confidential\n\n{}\n\n{}\n", body, n);
+        let pages = vec![
+            page(
+                1,
+                "1. Introduction\n\nThis report covers caching. It is short.",
+            ),
+            page(
+                2,
+                "2.1 Cache Sizing Rules\n\nSizes were measured,\nacross many hosts.",
+            ),
+            page(3, "APPENDIX\n\nRaw numbers follow."),
+        ];
+        let md = pdf_to_markdown(&pages);
+        assert_eq!(
+            md[0],
+            "# 1. Introduction\n\nThis report covers caching. It is short."
+        );
+        assert_eq!(
+            md[1],
+            "## 2.1 Cache Sizing Rules\n\nSizes were measured,\nacross many hosts."
+        );
+        assert_eq!(md[2], "# APPENDIX\n\nRaw numbers follow.");
+    }
+
+    #[test]
+    fn test_heading_level_rejects_sentences() {
+        assert_eq!(heading_level("Results And Discussion"), Some(2));
+        assert_eq!(heading_level("This is a sentence."), None);
+        assert_eq!(heading_level("lowercase start here"), None);
+        assert_eq!(heading_level("3.2.1 Error Budget"), Some(3));
+    }
+
+    #[test]
+    fn test_summary_labels_pages() {
+        let pages = vec![
+            "# Overview\n\nThe system stores data.".to_string(),
+            "# Limits\n\nLimits apply per tenant.".to_string(),
+        ];
+        let out = summary("spec.pdf", &pages, 200);
+        assert!(out.starts_with("spec.pdf: 2 pages, 12 words"));
+        assert!(out.contains("Overview (p1)\n  The system stores data."));
+        assert!(out.contains("Limits (p2)"));
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("3", 5).unwrap(), (3, 3));
+        assert_eq!(parse_range("2-4", 5).unwrap(), (2, 4));
+        assert!(parse_range("4-9", 5).is_err());
+        assert!(parse_range("0", 5).is_err());
+        assert!(parse_range("x", 5).is_err());
+    }
+
+    /// A PDF with a valid cross-reference table around `objects` (1-based).
+    fn pdf(objects: &[&str]) -> Vec<u8> {
+        let mut out = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, body) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend(format!("{} 0 obj\n{}\nendobj\n", i + 1, body).bytes());
+        }
+        let xref = out.len();
+        out.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).bytes());
+        for offset in offsets {
+            out.extend(format!("{:010} 00000 n \n", offset).bytes());
+        }
+        out.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                xref
+            )
+            .bytes(),
+        );
+        out
+    }
+
+    #[test]
+    fn test_malformed_pdf_is_an_error() {
+        let content = "BT /F1 12 Tf (Hello) Tj ET";
+        let stream = format!(
+            "<< /Length {} >>\nstream\n{}\nendstream",
+            content.len(),
+            content
+        );
+        // No /MediaBox: pdf-extract panics on it
+        let bytes = pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R >>",
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 2 0 R /Contents 4 0 R >>",
+            &stream,
+        ]);
+        let err = pdf_pages(&bytes).unwrap_err();
+        assert!(format!("{:#}", err).contains("Failed to parse PDF"));
+        assert!(pdf_pages(b"%PDF-1.4\ngarbage").is_err());
+    }
+}
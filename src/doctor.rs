@@ -0,0 +1,422 @@
+//! `rtk doctor` — diagnose the local rtk setup and print fixes.
+//!
+//! Checks the rtk binary on PATH, hook installation and registration,
+//! config validity, tracking database health, the active tokenizer, and
+//! wrapped tools shadowed by other copies on PATH.
+//! Exits 1 when any check fails.
+
+use crate::config::{Config, Tokenizer};
+use crate::exit_code;
+use crate::init;
+use crate::tracking;
+use crate::utils::{which, which_all};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Tools rtk wraps; a different copy earlier on PATH changes what rtk runs.
+const WRAPPED_TOOLS: &[&str] = &[
+    "git",
+    "gh",
+    "cargo",
+    "npm",
+    "npx",
+    "pnpm",
+    "docker",
+    "kubectl",
+    "rg",
+    "grep",
+    "find",
+    "ls",
+    "tree",
+    "curl",
+    "wget",
+    "tsc",
+    "eslint",
+    "prettier",
+    "vitest",
+    "playwright",
+    "prisma",
+    "next",
+    "pytest",
+    "ruff",
+    "pip",
+    "go",
+    "golangci-lint",
+    "semgrep",
+    "trivy",
+    "grype",
+    "systemctl",
+    "lighthouse",
+    "adb",
+    "gradle",
+    "xcodebuild",
+    "swift",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug)]
+struct Check {
+    name: &'static str,
+    status: Status,
+    detail: String,
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Fail,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+pub fn run(verbose: u8) -> anyhow::Result<()> {
+    let mut checks = vec![check_binary()];
+    checks.extend(check_hook());
+    checks.push(check_config());
+    checks.push(check_database());
+    checks.push(check_tokenizer());
+    checks.extend(check_shadowing(verbose));
+
+    println!("🩺 rtk doctor\n");
+    for check in &checks {
+        let icon = match check.status {
+            Status::Ok => "✅",
+            Status::Warn => "⚠️ ",
+            Status::Fail => "❌",
+        };
+        println!("{} {}: {}", icon, check.name, check.detail);
+        if let Some(fix) = &check.fix {
+            println!("   Fix: {}", fix);
+        }
+    }
+
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    let warned = checks.iter().filter(|c| c.status == Status::Warn).count();
+    println!();
+    if failed == 0 && warned == 0 {
+        println!("All {} checks passed", checks.len());
+    } else {
+        println!("{} failed, {} warnings", failed, warned);
+    }
+    if failed > 0 {
+        exit_code::set(1);
+    }
+    Ok(())
+}
+
+/// The `rtk` the hook will call must be this tool, not another crate named rtk.
+fn check_binary() -> Check {
+    let version = env!("CARGO_PKG_VERSION");
+    let Some(on_path) = which("rtk") else {
+        return Check::warn(
+            "Binary",
+            format!("rtk {} is not on PATH, so the hook cannot call it", version),
+            "add the directory containing rtk to PATH (e.g. ~/.cargo/bin)",
+        );
+    };
+    let current = std::env::current_exe().ok();
+    if current.as_deref().and_then(canonical) == canonical(&on_path) {
+        return Check::ok("Binary", format!("rtk {} ({})", version, on_path.display()));
+    }
+    let reported = Command::new(&on_path)
+        .arg("--version")
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default();
+    binary_check(&on_path, &reported, version)
+}
+
+fn binary_check(on_path: &Path, reported: &str, version: &str) -> Check {
+    match reported.strip_prefix("rtk ") {
+        Some(v) if v == version => {
+            Check::ok("Binary", format!("rtk {} ({})", v, on_path.display()))
+        }
+        Some(v) => Check::warn(
+            "Binary",
+            format!(
+                "{} is rtk {}, this binary is {}",
+                on_path.display(),
+                v,
+                version
+            ),
+            "reinstall so a single rtk version is on PATH",
+        ),
+        None => Check::fail(
+            "Binary",
+            format!(
+                "{} is not Rust Token Killer (reports '{}')",
+                on_path.display(),
+                reported
+            ),
+            "cargo uninstall rtk && cargo install --git https://github.com/rtk-ai/rtk",
+        ),
+    }
+}
+
+fn check_hook() -> Vec<Check> {
+    let Ok(claude_dir) = init::resolve_claude_dir() else {
+        return vec![Check::warn(
+            "Hook",
+            "cannot determine home directory",
+            "set HOME",
+        )];
+    };
+    let hook_path = claude_dir.join("hooks").join("rtk-rewrite.sh");
+    let content = fs::read_to_string(&hook_path).ok();
+    let mut checks = vec![hook_check(
+        &hook_path,
+        content.as_deref(),
+        is_executable(&hook_path),
+    )];
+    if content.is_none() {
+        return checks;
+    }
+
+    let settings_path = claude_dir.join("settings.json");
+    let settings = fs::read_to_string(&settings_path)
+        .ok()
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok());
+    checks.push(match settings {
+        Some(root) if init::hook_already_present(&root, &hook_path.display().to_string()) => {
+            Check::ok("Settings", "hook registered in settings.json")
+        }
+        Some(_) => Check::fail(
+            "Settings",
+            "settings.json does not register the hook",
+            "rtk init -g --auto-patch",
+        ),
+        None => Check::fail(
+            "Settings",
+            format!("{} is missing or invalid JSON", settings_path.display()),
+            "rtk init -g --auto-patch",
+        ),
+    });
+    checks
+}
+
+fn hook_check(path: &Path, content: Option<&str>, executable: bool) -> Check {
+    match content {
+        None => Check::warn(
+            "Hook",
+            "not installed, commands are not rewritten automatically",
+            "rtk init -g",
+        ),
+        Some(_) if !executable => Check::fail(
+            "Hook",
+            format!("{} is not executable", path.display()),
+            format!("chmod +x {}", path.display()),
+        ),
+        Some(content) if content != init::REWRITE_HOOK => Check::warn(
+            "Hook",
+            format!("{} differs from this version's hook", path.display()),
+            "rtk init -g",
+        ),
+        Some(_) => Check::ok("Hook", format!("{} (up to date)", path.display())),
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.exists()
+}
+
+fn check_config() -> Check {
+    match Config::load() {
+        Ok(_) => Check::ok("Config", "all layers parse and match the schema"),
+        Err(e) => Check::fail(
+            "Config",
+            format!("{:#}", e),
+            "fix or remove the offending key (`rtk config` shows where each value is set)",
+        ),
+    }
+}
+
+fn check_database() -> Check {
+    match tracking::db_health() {
+        Ok(health) => match health.integrity.as_deref() {
+            None => Check::ok(
+                "Database",
+                format!("{} (not created yet)", health.path.display()),
+            ),
+            Some("ok") if health.schema < tracking::latest_schema_version() => Check::warn(
+                "Database",
+                format!(
+                    "{} at schema v{}, v{} available",
+                    health.path.display(),
+                    health.schema,
+                    tracking::latest_schema_version()
+                ),
+                "rtk gain migrate",
+            ),
+            Some("ok") => Check::ok(
+                "Database",
+                format!(
+                    "{} ({} records, integrity ok)",
+                    health.path.display(),
+                    health.records
+                ),
+            ),
+            Some(problem) => Check::fail(
+                "Database",
+                format!(
+                    "{} failed integrity check: {}",
+                    health.path.display(),
+                    problem
+                ),
+                format!(
+                    "move {} aside; rtk recreates it on the next command",
+                    health.path.display()
+                ),
+            ),
+        },
+        Err(e) => Check::fail(
+            "Database",
+            format!("cannot open: {:#}", e),
+            "check RTK_DB_PATH / tracking.database_path and file permissions",
+        ),
+    }
+}
+
+fn check_tokenizer() -> Check {
+    let detail = match Config::cached().output.tokenizer {
+        Tokenizer::Chars => "chars (built-in, ~4 characters per token)",
+        Tokenizer::Words => "words (built-in, ~0.75 words per token)",
+    };
+    Check::ok("Tokenizer", detail)
+}
+
+fn check_shadowing(verbose: u8) -> Vec<Check> {
+    let rtk = std::env::current_exe().ok().as_deref().and_then(canonical);
+    let mut checks = Vec::new();
+    for tool in WRAPPED_TOOLS {
+        let found: Vec<PathBuf> = which_all(tool);
+        if found.is_empty() {
+            continue;
+        }
+        let targets: Vec<Option<PathBuf>> = found.iter().map(|p| canonical(p)).collect();
+        if let Some(check) = shadow_check(tool, &found, &targets, rtk.as_deref()) {
+            checks.push(check);
+        } else if verbose > 0 {
+            eprintln!("{}: {}", tool, found[0].display());
+        }
+    }
+    if checks.is_empty() {
+        checks.push(Check::ok("PATH", "no wrapped tool is shadowed"));
+    }
+    checks
+}
+
+/// Problem with how `tool` resolves: a shim that loops back into rtk, or an
+/// earlier copy hiding a different binary later on PATH.
+fn shadow_check(
+    tool: &str,
+    found: &[PathBuf],
+    targets: &[Option<PathBuf>],
+    rtk: Option<&Path>,
+) -> Option<Check> {
+    if rtk.is_some() && targets[0].as_deref() == rtk {
+        return Some(Check::fail(
+            "PATH",
+            format!("{} resolves to rtk itself", found[0].display()),
+            format!("remove the {} shim; rtk would call itself in a loop", tool),
+        ));
+    }
+    let other = (1..found.len()).find(|&i| targets[i] != targets[0])?;
+    Some(Check::warn(
+        "PATH",
+        format!("{} shadows {}", found[0].display(), found[other].display()),
+        format!("make sure the first {} on PATH is the one you expect", tool),
+    ))
+}
+
+fn canonical(path: &Path) -> Option<PathBuf> {
+    fs::canonicalize(path).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_check_detects_other_rtk() {
+        let path = Path::new("/usr/bin/rtk");
+        assert_eq!(binary_check(path, "rtk 1.0.0", "1.0.0").status, Status::Ok);
+        assert_eq!(
+            binary_check(path, "rtk 0.9.0", "1.0.0").status,
+            Status::Warn
+        );
+        assert_eq!(
+            binary_check(path, "typekit 0.1", "1.0.0").status,
+            Status::Fail
+        );
+    }
+
+    #[test]
+    fn test_hook_check() {
+        let path = Path::new("/home/u/.claude/hooks/rtk-rewrite.sh");
+        assert_eq!(hook_check(path, None, false).status, Status::Warn);
+        assert_eq!(
+            hook_check(path, Some(init::REWRITE_HOOK), false).status,
+            Status::Fail
+        );
+        assert_eq!(
+            hook_check(path, Some("#!/bin/sh\n"), true).status,
+            Status::Warn
+        );
+        assert_eq!(
+            hook_check(path, Some(init::REWRITE_HOOK), true).status,
+            Status::Ok
+        );
+    }
+
+    #[test]
+    fn test_shadow_check() {
+        let found = [PathBuf::from("/a/git"), PathBuf::from("/b/git")];
+        let same = [Some(PathBuf::from("/b/git")), Some(PathBuf::from("/b/git"))];
+        assert!(shadow_check("git", &found, &same, None).is_none());
+
+        let different = [Some(PathBuf::from("/a/git")), Some(PathBuf::from("/b/git"))];
+        let check = shadow_check("git", &found, &different, None).unwrap();
+        assert_eq!(check.status, Status::Warn);
+        assert_eq!(check.detail, "/a/git shadows /b/git");
+
+        let rtk = PathBuf::from("/a/git");
+        let check = shadow_check("git", &found, &different, Some(&rtk)).unwrap();
+        assert_eq!(check.status, Status::Fail);
+    }
+}
@@ -0,0 +1,211 @@
+//! Recallable elided sections: `[elided:<id>]` tags and `rtk show <id>`.
+//!
+//! When a wrapper tracks its run, every block of input missing from the
+//! output (see explain.rs) is stored under `<data dir>/rtk/elided/<id>` and
+//! listed after the output with its ID. The ID hashes the hidden text, so the
+//! same block gets the same ID across runs. `rtk show <id>` prints exactly
+//! that block, so an agent never has to rerun the whole command with `--raw`.
+//! Entries expire after a day; inputs of 16 MiB or more are not recorded.
+//!
+//! Only output that quotes its input has hidden blocks: when most output
+//! lines are rewritten (`rtk ls`, `rtk git log`), nothing is recorded. Runs
+//! that aren't tracked (`RTK_NO_TRACK`, `tracking.exclude_commands`) and
+//! sensitive wrappers (`rtk secrets`, `rtk env`) store nothing either.
+
+use crate::cache;
+use crate::config::Config;
+use crate::explain;
+use crate::mapped;
+use crate::utils::{strip_ansi, truncate};
+use anyhow::{bail, Result};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const ID_LEN: usize = 6;
+const TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Sections listed after the output; the rest are still stored.
+const MAX_LISTED: usize = 5;
+
+/// Called from `track`: store the elided sections and list their IDs.
+/// Returns the printed listing, if any.
+pub fn record(input: &str, output: &str) -> Option<String> {
+    // A huge input would be stored whole to recall a part of it
+    if !Config::cached().output.elided_ids || input.len() >= mapped::LARGE {
+        return None;
+    }
+    let dir = store_dir()?;
+    let listing = store_sections(&dir, input, output);
+    if listing.is_empty() {
+        return None;
+    }
+    cache::prune(&dir, TTL);
+    // Entries end with the section's first line, as the input had it
+    crate::spill::unbudgeted(|| {
+        crate::profile::println_quoting(&listing, |l| l.starts_with("[elided:"))
+    });
+    Some(listing)
+}
+
+/// `rtk show <id>`: print a stored section verbatim.
+pub fn show(id: &str) -> Result<()> {
+    let id = parse_id(id);
+    let Some(dir) = store_dir() else {
+        bail!("No data directory to read elided sections from");
+    };
+    match read_section(&dir, id)? {
+        Some(text) => {
+            let _ = std::io::stdout().lock().write_all(text.as_bytes());
+            Ok(())
+        }
+        None => bail!("No elided section '{}' (expired or never recorded)", id),
+    }
+}
+
+fn store_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("RTK_ELIDED_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    Some(dirs::data_local_dir()?.join("rtk").join("elided"))
+}
+
+/// Accepts `af3c01`, `[elided:af3c01]` or `elided:af3c01`.
+fn parse_id(arg: &str) -> &str {
+    let arg = arg.trim().trim_start_matches('[').trim_end_matches(']');
+    arg.strip_prefix("elided:").unwrap_or(arg)
+}
+
+fn section_id(text: &str) -> String {
+    cache::key(&["elided", text])[..ID_LEN].to_string()
+}
+
+/// Whether most non-blank lines of `output` are lines of `input`, i.e. the
+/// wrapper dropped input rather than rewriting it.
+fn quotes_input(lines: &[&str], output: &str) -> bool {
+    let input: HashSet<&str> = lines.iter().map(|l| l.trim()).collect();
+    let output = strip_ansi(output);
+    let (quoted, total) = output
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .fold((0, 0), |(q, t), l| {
+            (q + usize::from(input.contains(l)), t + 1)
+        });
+    quoted * 2 > total
+}
+
+/// Write every elided section of `input` to `dir`; returns the listing to print.
+fn store_sections(dir: &Path, input: &str, output: &str) -> String {
+    let clean = strip_ansi(input);
+    let lines: Vec<&str> = clean.lines().collect();
+    if !quotes_input(&lines, output) {
+        return String::new();
+    }
+    let sections = explain::elided_sections(input, output);
+    if sections.is_empty() || std::fs::create_dir_all(dir).is_err() {
+        return String::new();
+    }
+
+    let mut listed = Vec::new();
+    for (start, len, first) in &sections {
+        let mut text = lines[start - 1..start - 1 + len].join("\n");
+        text.push('\n');
+        let id = section_id(&text);
+        if std::fs::write(dir.join(&id), &text).is_err() {
+            continue;
+        }
+        listed.push(format!(
+            "[elided:{}] lines {}-{}: {}",
+            id,
+            start,
+            start + len - 1,
+            truncate(first, 60)
+        ));
+    }
+    if listed.len() > MAX_LISTED {
+        let more = listed.len() - MAX_LISTED;
+        listed.truncate(MAX_LISTED);
+        listed.push(format!("... +{} more elided sections", more));
+    }
+    if listed.is_empty() {
+        return String::new();
+    }
+    format!("rtk show <id> for hidden lines:\n{}", listed.join("\n"))
+}
+
+/// Stored text for `id`; a unique prefix of at least 4 characters also matches.
+fn read_section(dir: &Path, id: &str) -> Result<Option<String>> {
+    if id.len() < 4 || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!("Invalid elided section id '{}'", id);
+    }
+    if let Ok(text) = std::fs::read_to_string(dir.join(id)) {
+        return Ok(Some(text));
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok(None);
+    };
+    let matches: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(id))
+        })
+        .collect();
+    match matches.as_slice() {
+        [] => Ok(None),
+        [path] => Ok(std::fs::read_to_string(path).ok()),
+        _ => bail!("Elided section id '{}' is ambiguous", id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "Compiling a\nCompiling b\nCompiling c\nCompiling d\nerror: boom\n";
+
+    #[test]
+    fn test_store_and_recall() {
+        let dir = tempfile::tempdir().unwrap();
+        let listing = store_sections(dir.path(), INPUT, "error: boom\n");
+        let id = section_id("Compiling a\nCompiling b\nCompiling c\nCompiling d\n");
+        assert_eq!(
+            listing,
+            format!(
+                "rtk show <id> for hidden lines:\n[elided:{}] lines 1-4: Compiling a",
+                id
+            )
+        );
+        assert_eq!(
+            read_section(dir.path(), parse_id(&format!("[elided:{}]", id))).unwrap(),
+            Some("Compiling a\nCompiling b\nCompiling c\nCompiling d\n".to_string())
+        );
+        assert_eq!(
+            read_section(dir.path(), &id[..4]).unwrap().map(|t| t.len()),
+            Some(48)
+        );
+        assert_eq!(read_section(dir.path(), "ffff00").unwrap(), None);
+        assert!(read_section(dir.path(), "zz").is_err());
+    }
+
+    #[test]
+    fn test_nothing_elided_lists_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(store_sections(dir.path(), INPUT, INPUT), "");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_rewritten_output_lists_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = "1a2b3c fix the parser for nested groups\n\
+                   4d5e6f add a test for nested groups\n\
+                   7a8b9c bump the version to 0.4.2\n";
+        let short = "1a2b3c fix the parser...\n4d5e6f add a test...\n7a8b9c bump the...\n";
+        assert_eq!(store_sections(dir.path(), log, short), "");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,228 @@
+//! Decoding of input that is not clean UTF-8.
+//!
+//! Files and command output arrive as bytes: Windows tools write UTF-16,
+//! old logs are latin-1, and concatenated logs mix both. [`decode`] sniffs
+//! the encoding (BOM, then the NUL pattern of UTF-16, then UTF-8 validity)
+//! and returns UTF-8; bytes that are not valid UTF-8 are read as
+//! windows-1252, a superset of latin-1, so nothing turns into `\u{fffd}`.
+//! Readers print a one-line [`notice`] on stderr when they had to convert.
+
+use crate::mapped;
+use anyhow::Result;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// How many leading bytes the UTF-16 sniffing looks at.
+const SNIFF_LEN: usize = 4096;
+
+static NOTIFIED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    /// UTF-8 with a byte order mark (stripped)
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    Windows1252,
+    /// UTF-8 with windows-1252 bytes in between
+    Mixed,
+}
+
+impl Encoding {
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf8Bom => "UTF-8 with BOM",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Windows1252 => "windows-1252",
+            Encoding::Mixed => "mixed UTF-8/windows-1252",
+        }
+    }
+
+    /// Whether decoding changed anything worth telling.
+    pub fn converted(self) -> bool {
+        !matches!(self, Encoding::Utf8 | Encoding::Utf8Bom)
+    }
+}
+
+/// `bytes` as UTF-8 text, with the encoding it was read as.
+pub fn decode(bytes: &[u8]) -> (String, Encoding) {
+    if let Some(rest) = bytes.strip_prefix(b"\xef\xbb\xbf") {
+        let (text, encoding) = decode_utf8(rest);
+        let encoding = match encoding {
+            Encoding::Utf8 => Encoding::Utf8Bom,
+            other => other,
+        };
+        return (text, encoding);
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xff\xfe") {
+        return (decode_utf16(rest, true), Encoding::Utf16Le);
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xfe\xff") {
+        return (decode_utf16(rest, false), Encoding::Utf16Be);
+    }
+    match sniff_utf16(bytes) {
+        Some(Encoding::Utf16Le) => (decode_utf16(bytes, true), Encoding::Utf16Le),
+        Some(_) => (decode_utf16(bytes, false), Encoding::Utf16Be),
+        None => decode_utf8(bytes),
+    }
+}
+
+/// [`decode`] for one line of a stream split at `\n`: a UTF-16LE stream
+/// leaves the newline's NUL byte at the start of the next line.
+pub fn decode_line(bytes: &[u8]) -> (String, Encoding) {
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        if !text.contains('\0') {
+            return (text.to_string(), Encoding::Utf8);
+        }
+    }
+    match bytes {
+        [0, rest @ ..] if sniff_utf16(rest) == Some(Encoding::Utf16Le) => decode(rest),
+        _ => decode(bytes),
+    }
+}
+
+/// Read `path` as UTF-8 whatever its encoding, with a [`notice`] if it had
+/// to be converted. Large files are decoded straight from a mapping.
+pub fn read_file(path: &Path) -> Result<String> {
+    let bytes = mapped::read(path)?;
+    let (text, encoding) = decode(&bytes);
+    notice(&path.display().to_string(), encoding);
+    Ok(text)
+}
+
+/// Tell (once per run, on stderr) that `source` was converted to UTF-8.
+pub fn notice(source: &str, encoding: Encoding) {
+    if encoding.converted() && !NOTIFIED.swap(true, Ordering::Relaxed) {
+        eprintln!("rtk: {} decoded from {} to UTF-8", source, encoding.name());
+    }
+}
+
+/// UTF-16 without a BOM: mostly ASCII text has a NUL in every other byte.
+fn sniff_utf16(bytes: &[u8]) -> Option<Encoding> {
+    let sample = &bytes[..bytes.len().min(SNIFF_LEN)];
+    let pairs = sample.len() / 2;
+    if pairs < 2 {
+        return None;
+    }
+    let even_nuls = sample.iter().step_by(2).filter(|&&b| b == 0).count();
+    let odd_nuls = sample
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .filter(|&&b| b == 0)
+        .count();
+    if odd_nuls * 10 >= pairs * 4 && even_nuls * 20 <= pairs {
+        Some(Encoding::Utf16Le)
+    } else if even_nuls * 10 >= pairs * 4 && odd_nuls * 20 <= pairs {
+        Some(Encoding::Utf16Be)
+    } else {
+        None
+    }
+}
+
+fn decode_utf16(bytes: &[u8], little_endian: bool) -> String {
+    let units = bytes.chunks(2).map(|pair| match (pair, little_endian) {
+        ([lo, hi], true) | ([hi, lo], false) => u16::from_le_bytes([*lo, *hi]),
+        // Odd trailing byte, e.g. a line cut after the newline's first byte
+        ([b], _) => u16::from(*b),
+        _ => 0,
+    });
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+/// Valid UTF-8 runs as is, invalid bytes as windows-1252.
+fn decode_utf8(bytes: &[u8]) -> (String, Encoding) {
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return (text.to_string(), Encoding::Utf8);
+    }
+    let mut out = String::with_capacity(bytes.len() + bytes.len() / 4);
+    let mut multibyte = false;
+    for chunk in bytes.utf8_chunks() {
+        let valid = chunk.valid();
+        multibyte |= !valid.is_ascii();
+        out.push_str(valid);
+        out.extend(chunk.invalid().iter().map(|&b| windows_1252(b)));
+    }
+    let encoding = if multibyte {
+        Encoding::Mixed
+    } else {
+        Encoding::Windows1252
+    };
+    (out, encoding)
+}
+
+fn windows_1252(byte: u8) -> char {
+    const HIGH: [char; 32] = [
+        '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž',
+        '\u{8f}', '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}',
+        'ž', 'Ÿ',
+    ];
+    match byte {
+        0x80..=0x9f => HIGH[(byte - 0x80) as usize],
+        _ => char::from(byte),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(|u| u.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn test_decode_detects_encodings() {
+        assert_eq!(
+            decode("héllo".as_bytes()),
+            ("héllo".to_string(), Encoding::Utf8)
+        );
+        assert_eq!(
+            decode(b"\xef\xbb\xbfhi"),
+            ("hi".to_string(), Encoding::Utf8Bom)
+        );
+        assert_eq!(
+            decode(b"caf\xe9 \x93ok\x94"),
+            ("café “ok”".to_string(), Encoding::Windows1252)
+        );
+
+        let mut bom = vec![0xff, 0xfe];
+        bom.extend(utf16le("Fehler: Datei größer\r\n"));
+        assert_eq!(
+            decode(&bom),
+            ("Fehler: Datei größer\r\n".to_string(), Encoding::Utf16Le)
+        );
+        let be: Vec<u8> = "error: x"
+            .encode_utf16()
+            .flat_map(|u| u.to_be_bytes())
+            .collect();
+        assert_eq!(decode(&be), ("error: x".to_string(), Encoding::Utf16Be));
+        assert_eq!(
+            decode(&utf16le("no bom here")),
+            ("no bom here".to_string(), Encoding::Utf16Le)
+        );
+    }
+
+    #[test]
+    fn test_decode_mixed_keeps_valid_utf8() {
+        let mut bytes = "naïve ".as_bytes().to_vec();
+        bytes.extend(b"na\xefve");
+        assert_eq!(decode(&bytes), ("naïve naïve".to_string(), Encoding::Mixed));
+    }
+
+    #[test]
+    fn test_decode_line_realigns_utf16_stream() {
+        let stream = utf16le("first\r\nsecond\r\n");
+        let mut lines = stream.split_inclusive(|&b| b == b'\n');
+        let first = decode_line(lines.next().unwrap()).0;
+        let second = decode_line(lines.next().unwrap()).0;
+        assert_eq!(first.trim_end_matches(['\r', '\n']), "first");
+        assert_eq!(second.trim_end_matches(['\r', '\n']), "second");
+        assert_eq!(decode_line(b"plain"), ("plain".to_string(), Encoding::Utf8));
+    }
+}
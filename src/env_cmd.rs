@@ -1,4 +1,5 @@
 use crate::tracking;
+use crate::utils::take_width;
 use anyhow::Result;
 use std::collections::HashSet;
 use std::env;
@@ -37,8 +38,12 @@ pub fn run(filter: Option<&str>, show_all: bool, verbose: u8) -> Result<()> {
 
         let display_value = if is_sensitive && !show_all {
             mask_value(value)
-        } else if value.len() > 100 {
-            format!("{}... ({} chars)", &value[..50], value.len())
+        } else if value.chars().count() > 100 {
+            format!(
+                "{}... ({} chars)",
+                take_width(value, 50),
+                value.chars().count()
+            )
         } else {
             value.clone()
         };
@@ -122,7 +127,7 @@ pub fn run(filter: Option<&str>, show_all: bool, verbose: u8) -> Result<()> {
 
     let raw: String = vars.iter().map(|(k, v)| format!("{}={}\n", k, v)).collect();
     let rtk = format!("{} vars -> {} shown", total, shown);
-    timer.track("env", "rtk env", &raw, &rtk);
+    timer.sensitive().track("env", "rtk env", &raw, &rtk);
     Ok(())
 }
 
@@ -143,10 +148,13 @@ fn get_sensitive_patterns() -> HashSet<&'static str> {
 }
 
 fn mask_value(value: &str) -> String {
-    if value.len() <= 4 {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 4 {
         "****".to_string()
     } else {
-        format!("{}****{}", &value[..2], &value[value.len() - 2..])
+        let head: String = chars[..2].iter().collect();
+        let tail: String = chars[chars.len() - 2..].iter().collect();
+        format!("{}****{}", head, tail)
     }
 }
 
@@ -0,0 +1,1023 @@
+//! Error extraction engine behind `rtk err`.
+//!
+//! Language detectors recognise multi-line error blocks — Python tracebacks,
+//! JavaScript and Java stack traces, Go panics — and compress them to the
+//! frames worth reading. Lines no detector claims go through the generic
+//! filter, which keeps compiler diagnostics (rustc, gcc/clang, tsc, go vet)
+//! and other error/warning lines with their indented continuation.
+//!
+//! Adding a language means implementing [`Detector`] and listing it in
+//! [`detectors`].
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// Stack frames kept per trace (or per `Caused by:` section).
+const KEEP_FRAMES: usize = 3;
+/// Lines kept per block; a block going on longer (a runaway trace in a huge
+/// log) ends with a count of the rest, so memory stays bounded.
+const MAX_BLOCK_LINES: usize = 200;
+/// Blocks the prioritizer holds back for the end of the output; later ones
+/// are only counted.
+const MAX_HELD_BLOCKS: usize = 500;
+
+/// A multi-line error format.
+pub trait Detector {
+    /// Does `line` open a block of this kind?
+    fn starts(&self, line: &str) -> bool;
+    /// Does `line` belong to the open `block`? The first line that doesn't
+    /// closes the block and is examined again on its own.
+    fn continues(&self, line: &str, block: &[String]) -> bool;
+    /// The lines shown for a finished block.
+    fn render(&self, block: Vec<String>) -> Vec<String> {
+        block
+    }
+}
+
+/// Built-in detectors, tried in order.
+pub fn detectors() -> Vec<Box<dyn Detector>> {
+    vec![
+        Box::new(PythonTraceback),
+        Box::new(JavaException),
+        Box::new(GoPanic),
+        Box::new(JsError),
+    ]
+}
+
+lazy_static! {
+    static ref PY_EXCEPTION: Regex =
+        Regex::new(r"^[A-Za-z_][\w.]*(Error|Exception|Exit|Interrupt|Warning|Iteration)\b(: .*)?$")
+            .unwrap();
+    static ref PY_FRAME: Regex = Regex::new(r#"^\s+File ".*", line \d+"#).unwrap();
+    static ref JAVA_HEADER: Regex = Regex::new(
+        r#"^(Exception in thread "[^"]*" )?([a-z_][\w$]*\.)+[A-Z][\w$]*(Exception|Error|Throwable)(: .*)?$"#
+    )
+    .unwrap();
+    static ref JAVA_FRAME: Regex = Regex::new(r"^\s+at [\w$.<>/]+\(.*\)$").unwrap();
+    static ref JAVA_MORE: Regex = Regex::new(r"^\s+\.\.\. \d+ more$").unwrap();
+    static ref JS_HEADER: Regex =
+        Regex::new(r"^(Uncaught )?([A-Z]\w*)?Error( \[[\w-]+\])?: ").unwrap();
+    static ref JS_FRAME: Regex = Regex::new(r"^\s+at .+").unwrap();
+    static ref GO_FUNC: Regex = Regex::new(r"^[\w./*()\[\]-]+\(.*\)$").unwrap();
+    /// rustc source excerpt: `12 |     let x = …`
+    static ref SOURCE_LINE: Regex = Regex::new(r"^\d+\s+\|").unwrap();
+}
+
+/// `Traceback (most recent call last):` … `ValueError: bad input`.
+/// Python prints the innermost frame last, so the last frames are kept.
+struct PythonTraceback;
+
+impl Detector for PythonTraceback {
+    fn starts(&self, line: &str) -> bool {
+        line.starts_with("Traceback (most recent call last):")
+    }
+
+    fn continues(&self, line: &str, block: &[String]) -> bool {
+        let last = block.last().map(String::as_str).unwrap_or("");
+        let in_frames = last.starts_with(' ') || last.starts_with('\t') || self.starts(last);
+        line.starts_with(' ')
+            || line.starts_with('\t')
+            // The exception line right after the frames
+            || (in_frames && PY_EXCEPTION.is_match(line))
+            // Chained exceptions
+            || (line.trim().is_empty() && (PY_EXCEPTION.is_match(last) || is_chain(last)))
+            || is_chain(line)
+            || (self.starts(line) && last.trim().is_empty())
+    }
+
+    fn render(&self, block: Vec<String>) -> Vec<String> {
+        let mut out = Vec::new();
+        // One traceback per chained exception
+        let mut section: Vec<String> = Vec::new();
+        for line in block {
+            if self.starts(&line) && !section.is_empty() {
+                out.extend(render_python_section(std::mem::take(&mut section)));
+            }
+            section.push(line);
+        }
+        out.extend(render_python_section(section));
+        out
+    }
+}
+
+fn is_chain(line: &str) -> bool {
+    line.starts_with("During handling of the above exception")
+        || line.starts_with("The above exception was the direct cause")
+}
+
+fn render_python_section(section: Vec<String>) -> Vec<String> {
+    let frames: Vec<usize> = section
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| PY_FRAME.is_match(l))
+        .map(|(i, _)| i)
+        .collect();
+    if frames.len() <= KEEP_FRAMES {
+        return section;
+    }
+    let first_kept = frames[frames.len() - KEEP_FRAMES];
+    let mut out = vec![section[0].clone()];
+    out.push(format!("  … {} earlier frames", frames.len() - KEEP_FRAMES));
+    out.extend(section[first_kept..].iter().cloned());
+    out
+}
+
+/// `java.lang.IllegalStateException: msg` + `\tat …` frames and
+/// `Caused by:` sections; the first frames of each section are kept.
+struct JavaException;
+
+impl Detector for JavaException {
+    fn starts(&self, line: &str) -> bool {
+        JAVA_HEADER.is_match(line)
+    }
+
+    fn continues(&self, line: &str, _block: &[String]) -> bool {
+        JAVA_FRAME.is_match(line)
+            || JAVA_MORE.is_match(line)
+            || line.starts_with("Caused by: ")
+            || line.trim_start().starts_with("Suppressed: ")
+    }
+
+    fn render(&self, block: Vec<String>) -> Vec<String> {
+        keep_leading_frames(block, |l| JAVA_FRAME.is_match(l) || JAVA_MORE.is_match(l))
+    }
+}
+
+/// `TypeError: x is undefined` + `    at fn (file:line:col)` frames; frames
+/// from node_modules and node internals are dropped before keeping the first.
+struct JsError;
+
+impl Detector for JsError {
+    fn starts(&self, line: &str) -> bool {
+        JS_HEADER.is_match(line)
+    }
+
+    fn continues(&self, line: &str, _block: &[String]) -> bool {
+        JS_FRAME.is_match(line)
+    }
+
+    fn render(&self, block: Vec<String>) -> Vec<String> {
+        let (own, vendored): (Vec<String>, Vec<String>) = block
+            .into_iter()
+            .partition(|l| !(l.contains("node_modules/") || l.contains("node:internal")));
+        let mut out = keep_leading_frames(own, |l| JS_FRAME.is_match(l));
+        if !vendored.is_empty() {
+            out.push(format!(
+                "    … {} node_modules/internal frames",
+                vendored.len()
+            ));
+        }
+        out
+    }
+}
+
+/// Header lines kept; runs of frame lines cut to [`KEEP_FRAMES`].
+fn keep_leading_frames(block: Vec<String>, is_frame: impl Fn(&str) -> bool) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut run = 0;
+    let mut dropped = 0;
+    for line in block {
+        if is_frame(&line) {
+            run += 1;
+            if run > KEEP_FRAMES {
+                dropped += 1;
+                continue;
+            }
+        } else {
+            if dropped > 0 {
+                out.push(format!("    … {} more frames", dropped));
+                dropped = 0;
+            }
+            run = 0;
+        }
+        out.push(line);
+    }
+    if dropped > 0 {
+        out.push(format!("    … {} more frames", dropped));
+    }
+    out
+}
+
+/// `panic: msg` / `fatal error: msg` + goroutine dumps; runtime frames are
+/// dropped and the first frames of the panicking goroutine kept.
+struct GoPanic;
+
+impl Detector for GoPanic {
+    fn starts(&self, line: &str) -> bool {
+        line.starts_with("panic: ") || line.starts_with("fatal error: ")
+    }
+
+    fn continues(&self, line: &str, _block: &[String]) -> bool {
+        line.trim().is_empty()
+            || line.starts_with('\t')
+            || line.starts_with("goroutine ")
+            || line.starts_with("[signal ")
+            || line.starts_with("created by ")
+            || line.starts_with("panic: ")
+            || GO_FUNC.is_match(line)
+    }
+
+    fn render(&self, block: Vec<String>) -> Vec<String> {
+        let mut out = Vec::new();
+        let mut frames = 0;
+        let mut dropped = 0;
+        let mut goroutines = 0;
+        let mut lines = block.into_iter().peekable();
+        while let Some(line) = lines.next() {
+            if line.starts_with("goroutine ") {
+                goroutines += 1;
+            }
+            if goroutines > 1 {
+                continue;
+            }
+            if GO_FUNC.is_match(&line) || line.starts_with("created by ") {
+                // A frame is the function line plus its `\tfile:line` line
+                let file = lines.next_if(|l| l.starts_with('\t'));
+                if line.starts_with("runtime.") || frames >= KEEP_FRAMES {
+                    dropped += 1;
+                    continue;
+                }
+                frames += 1;
+                out.push(line);
+                out.extend(file);
+            } else if !line.trim().is_empty() {
+                out.push(line);
+            }
+        }
+        if dropped > 0 {
+            out.push(format!("\t… {} more frames", dropped));
+        }
+        if goroutines > 1 {
+            out.push(format!("… {} other goroutines", goroutines - 1));
+        }
+        out
+    }
+}
+
+/// Compiler diagnostics and other error/warning lines plus their indented
+/// continuation; a block ends at two blank lines or an unindented line.
+#[derive(Default)]
+struct Generic {
+    block: Vec<String>,
+    blank_count: usize,
+    /// Lines past `MAX_BLOCK_LINES`
+    omitted: usize,
+}
+
+impl Generic {
+    /// The block finished by `line`, if any.
+    fn feed(&mut self, line: &str) -> Option<Vec<String>> {
+        lazy_static! {
+            static ref ERROR_PATTERNS: Vec<Regex> = vec![
+                // Generic errors
+                Regex::new(r"(?i)^.*error[\s:\[].*$").unwrap(),
+                Regex::new(r"(?i)^.*\berr\b.*$").unwrap(),
+                Regex::new(r"(?i)^.*warning[\s:\[].*$").unwrap(),
+                Regex::new(r"(?i)^.*\bwarn\b.*$").unwrap(),
+                Regex::new(r"(?i)^.*failed.*$").unwrap(),
+                Regex::new(r"(?i)^.*failure.*$").unwrap(),
+                Regex::new(r"(?i)^.*exception.*$").unwrap(),
+                Regex::new(r"(?i)^.*panic.*$").unwrap(),
+                // Rust specific
+                Regex::new(r"^error\[E\d+\]:.*$").unwrap(),
+                Regex::new(r"^\s*--> .*:\d+:\d+$").unwrap(),
+                // Python
+                Regex::new(r"^Traceback.*$").unwrap(),
+                Regex::new(r#"^\s*File ".*", line \d+.*$"#).unwrap(),
+                // JavaScript/TypeScript
+                Regex::new(r"^\s*at .*:\d+:\d+.*$").unwrap(),
+                // Go
+                Regex::new(r"^.*\.go:\d+:.*$").unwrap(),
+            ];
+        }
+
+        if ERROR_PATTERNS.iter().any(|p| p.is_match(line)) {
+            // A matching indented line (` --> src/a.rs:1:2`) continues the block
+            let continues = !self.block.is_empty() && is_continuation(line);
+            let finished = if continues { None } else { self.flush() };
+            self.push(line);
+            self.blank_count = 0;
+            return finished;
+        }
+        if self.block.is_empty() {
+            return None;
+        }
+        if line.trim().is_empty() {
+            self.blank_count += 1;
+            if self.blank_count >= 2 {
+                return self.flush();
+            }
+            self.push(line);
+            None
+        } else if is_continuation(line) || self.introduced(line) {
+            self.blank_count = 0;
+            self.push(line);
+            None
+        } else {
+            self.flush()
+        }
+    }
+
+    /// `error during build:` announces its detail on the next line, indented
+    /// or not.
+    fn introduced(&self, line: &str) -> bool {
+        self.blank_count == 0
+            && !line.trim().is_empty()
+            && self.block.last().is_some_and(|l| l.trim_end().ends_with(':'))
+    }
+
+    fn push(&mut self, line: &str) {
+        if self.block.len() < MAX_BLOCK_LINES {
+            self.block.push(line.to_string());
+        } else {
+            self.omitted += 1;
+        }
+    }
+
+    fn flush(&mut self) -> Option<Vec<String>> {
+        self.blank_count = 0;
+        if self.omitted > 0 {
+            self.block
+                .push(omitted_lines(std::mem::take(&mut self.omitted)));
+        }
+        (!self.block.is_empty()).then(|| std::mem::take(&mut self.block))
+    }
+}
+
+fn omitted_lines(n: usize) -> String {
+    format!("  … {} more line{}", n, if n == 1 { "" } else { "s" })
+}
+
+fn is_continuation(line: &str) -> bool {
+    line.starts_with(' ') || line.starts_with('\t') || SOURCE_LINE.is_match(line)
+}
+
+/// Streaming extractor: feed lines as they arrive, print what comes back.
+/// Output is produced a block at a time, once the block has ended.
+pub struct ErrorExtractor {
+    detectors: Vec<Box<dyn Detector>>,
+    /// Index of the detector whose block is open, and its lines so far
+    open: Option<(usize, Vec<String>)>,
+    /// Lines of the open block past `MAX_BLOCK_LINES`
+    open_omitted: usize,
+    generic: Generic,
+    prioritizer: Option<Prioritizer>,
+}
+
+impl Default for ErrorExtractor {
+    fn default() -> Self {
+        Self::new(detectors())
+    }
+}
+
+impl ErrorExtractor {
+    pub fn new(detectors: Vec<Box<dyn Detector>>) -> Self {
+        Self {
+            detectors,
+            open: None,
+            open_omitted: 0,
+            generic: Generic::default(),
+            prioritizer: None,
+        }
+    }
+
+    /// Reorder and filter blocks by severity (see [`Prioritizer`]).
+    pub fn prioritized(min: Severity) -> Self {
+        Self {
+            prioritizer: Some(Prioritizer::new(min)),
+            ..Self::default()
+        }
+    }
+
+    /// Lines to show after `line` arrives.
+    pub fn feed(&mut self, line: &str) -> Vec<String> {
+        let blocks = self.feed_blocks(line);
+        self.order(blocks)
+    }
+
+    /// Lines still pending at end of input.
+    pub fn finish(&mut self) -> Vec<String> {
+        let blocks = self.finish_blocks();
+        let mut out = self.order(blocks);
+        if let Some(prioritizer) = &mut self.prioritizer {
+            out.extend(prioritizer.finish());
+        }
+        out
+    }
+
+    fn order(&mut self, blocks: Vec<Vec<String>>) -> Vec<String> {
+        match &mut self.prioritizer {
+            Some(prioritizer) => blocks
+                .into_iter()
+                .flat_map(|b| prioritizer.push(b))
+                .collect(),
+            None => blocks.into_iter().flatten().collect(),
+        }
+    }
+
+    /// Blocks completed by `line`, in output order.
+    fn feed_blocks(&mut self, line: &str) -> Vec<Vec<String>> {
+        let mut out = Vec::new();
+        if let Some((idx, mut block)) = self.open.take() {
+            if self.detectors[idx].continues(line, &block) {
+                if block.len() < MAX_BLOCK_LINES {
+                    block.push(line.to_string());
+                } else {
+                    self.open_omitted += 1;
+                }
+                self.open = Some((idx, block));
+                return out;
+            }
+            out.push(self.render(idx, block));
+        }
+        if let Some(idx) = self.detectors.iter().position(|d| d.starts(line)) {
+            out.extend(self.generic.flush());
+            self.open = Some((idx, vec![line.to_string()]));
+            return out;
+        }
+        out.extend(self.generic.feed(line));
+        out
+    }
+
+    /// Blocks still open at end of input.
+    fn finish_blocks(&mut self) -> Vec<Vec<String>> {
+        let mut out: Vec<Vec<String>> = self.generic.flush().into_iter().collect();
+        if let Some((idx, block)) = self.open.take() {
+            out.push(self.render(idx, block));
+        }
+        out
+    }
+
+    fn render(&mut self, idx: usize, block: Vec<String>) -> Vec<String> {
+        let mut lines = self.detectors[idx].render(block);
+        // Trailing blank lines were only kept in case the block went on
+        while lines.last().is_some_and(|l| l.trim().is_empty()) {
+            lines.pop();
+        }
+        if self.open_omitted > 0 {
+            lines.push(omitted_lines(std::mem::take(&mut self.open_omitted)));
+        }
+        lines
+    }
+}
+
+/// Errors and warnings of a captured output.
+pub fn extract(output: &str) -> String {
+    let mut extractor = ErrorExtractor::default();
+    let mut lines: Vec<String> = output.lines().flat_map(|l| extractor.feed(l)).collect();
+    lines.extend(extractor.finish());
+    lines.join("\n")
+}
+
+/// Severity of an extracted block, lowest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Note,
+    Warning,
+    Error,
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "note" | "info" | "help" => Ok(Severity::Note),
+            "warning" | "warn" => Ok(Severity::Warning),
+            "error" => Ok(Severity::Error),
+            _ => Err(format!("Unknown severity: {} (error, warning, note)", s)),
+        }
+    }
+}
+
+lazy_static! {
+    static ref WARNING: Regex = Regex::new(r"(?i)\bwarn(ing)?\b").unwrap();
+    static ref NOTE: Regex = Regex::new(r"(?i)^\s*(note|help|info)\b").unwrap();
+    static ref ERROR_WORD: Regex =
+        Regex::new(r"(?i)\b(error|fatal|panic|exception|traceback|failed|failure)\b").unwrap();
+    /// Messages that only repeat that earlier errors happened
+    static ref CASCADE: Regex = Regex::new(
+        r"(?i)previous errors?\b|^error: aborting due to|^compilation terminated\.|^make: \*\*\* .*Error \d+"
+    )
+    .unwrap();
+    /// `path/file.ext:12`, `path/file.ext(12,5)`
+    static ref FILE_REF: Regex =
+        Regex::new(r"([\w./\\-]+\.[A-Za-z]\w*)(?::\d+|\(\d+,\d+\))").unwrap();
+}
+
+/// Severity of a block, from its first line.
+pub fn severity(block: &[String]) -> Severity {
+    let first = block.first().map(String::as_str).unwrap_or("");
+    if ERROR_WORD.is_match(first) {
+        Severity::Error
+    } else if WARNING.is_match(first) {
+        Severity::Warning
+    } else if NOTE.is_match(first) {
+        Severity::Note
+    } else {
+        Severity::Error
+    }
+}
+
+/// A compiler error that makes many later errors meaningless, and the shape
+/// of those later errors. Both match the first line of a block.
+struct CascadeRule {
+    root: Regex,
+    downstream: Regex,
+}
+
+lazy_static! {
+    static ref CASCADE_RULES: Vec<CascadeRule> = vec![
+        // rustc: an unresolved import turns every use of the name into E0412/E0425/E0433
+        CascadeRule {
+            root: Regex::new(r"^error\[E04(32|33)\]").unwrap(),
+            downstream: Regex::new(r"^error\[E0(282|283|412|422|425|433)\]").unwrap(),
+        },
+        // tsc: a missing module makes its names unknown or implicitly `any`
+        CascadeRule {
+            root: Regex::new(r"error TS(2307|2792):").unwrap(),
+            downstream: Regex::new(r"error TS(2304|2305|2339|2552|2694|7006|7016|7031):")
+                .unwrap(),
+        },
+        // go: a package that fails to load leaves its identifiers undefined
+        CascadeRule {
+            root: Regex::new(
+                r"could not import|cannot find package|no required module provides package|is not in (GOROOT|std)",
+            )
+            .unwrap(),
+            downstream: Regex::new(r": undefined: |: too many errors$").unwrap(),
+        },
+        // javac: a missing package makes its classes unresolvable symbols
+        CascadeRule {
+            root: Regex::new(r"error: package \S+ does not exist").unwrap(),
+            downstream: Regex::new(r"error: cannot find symbol").unwrap(),
+        },
+        // mypy: an unresolvable module leaves its names undefined
+        CascadeRule {
+            root: Regex::new(r"error: (Cannot find implementation or library stub|Library stubs not installed) for module").unwrap(),
+            downstream: Regex::new(r#"error: (Name ".*" is not defined|Module ".*" has no attribute)"#)
+                .unwrap(),
+        },
+        // gcc/clang: a missing header leaves its types and functions undeclared
+        CascadeRule {
+            root: Regex::new(r"(fatal )?error: .*: No such file or directory|error: '.*' file not found").unwrap(),
+            downstream: Regex::new(
+                r"error: (unknown type name|implicit declaration of function|use of undeclared identifier|'.*' (was not declared in this scope|undeclared))",
+            )
+            .unwrap(),
+        },
+    ];
+}
+
+/// First source file a block points at.
+fn block_file(block: &[String]) -> Option<String> {
+    block
+        .iter()
+        .find_map(|l| FILE_REF.captures(l).map(|c| c[1].to_string()))
+}
+
+/// Orders extracted blocks so the root cause comes first: the first error
+/// of each file is shown as soon as it ends, repeat errors of a file follow
+/// at the end, then warnings, then notes. Messages that only report earlier
+/// errors are dropped.
+///
+/// Once a root error such as an unresolved import has been seen, the errors
+/// it typically causes are folded into a per-file count (see [`CascadeRule`]).
+struct Prioritizer {
+    min: Severity,
+    seen_files: HashSet<String>,
+    /// Indexes into [`CASCADE_RULES`] whose root error has been seen
+    roots: HashSet<usize>,
+    /// Downstream errors per file, in first-seen order
+    folded: Vec<(String, usize)>,
+    later_errors: Vec<Vec<String>>,
+    warnings: Vec<Vec<String>>,
+    notes: Vec<Vec<String>>,
+    hidden: usize,
+    cascades: usize,
+    /// Blocks past `MAX_HELD_BLOCKS`
+    overflow: usize,
+}
+
+impl Prioritizer {
+    fn new(min: Severity) -> Self {
+        Self {
+            min,
+            seen_files: HashSet::new(),
+            roots: HashSet::new(),
+            folded: Vec::new(),
+            later_errors: Vec::new(),
+            warnings: Vec::new(),
+            notes: Vec::new(),
+            hidden: 0,
+            cascades: 0,
+            overflow: 0,
+        }
+    }
+
+    /// Lines to show now for `block` (empty when held back or dropped).
+    fn push(&mut self, block: Vec<String>) -> Vec<String> {
+        if block.first().is_some_and(|l| CASCADE.is_match(l)) {
+            self.cascades += 1;
+            return Vec::new();
+        }
+        let severity = severity(&block);
+        if severity < self.min {
+            self.hidden += 1;
+            return Vec::new();
+        }
+        match severity {
+            Severity::Error => {
+                let first = block.first().map(String::as_str).unwrap_or("");
+                if self
+                    .roots
+                    .iter()
+                    .any(|&i| CASCADE_RULES[i].downstream.is_match(first))
+                {
+                    self.fold(block_file(&block));
+                    return Vec::new();
+                }
+                if let Some(i) = CASCADE_RULES.iter().position(|r| r.root.is_match(first)) {
+                    // The root cause is shown in full, whatever file it is in
+                    self.roots.insert(i);
+                    if let Some(file) = block_file(&block) {
+                        self.seen_files.insert(file);
+                    }
+                    return block;
+                }
+                let first_in_file = block_file(&block).is_none_or(|f| self.seen_files.insert(f));
+                if first_in_file {
+                    block
+                } else {
+                    self.hold(Severity::Error, block)
+                }
+            }
+            severity => self.hold(severity, block),
+        }
+    }
+
+    /// Keep `block` for the end of the output.
+    fn hold(&mut self, severity: Severity, block: Vec<String>) -> Vec<String> {
+        let held = self.later_errors.len() + self.warnings.len() + self.notes.len();
+        if held >= MAX_HELD_BLOCKS {
+            self.overflow += 1;
+            return Vec::new();
+        }
+        match severity {
+            Severity::Error => self.later_errors.push(block),
+            Severity::Warning => self.warnings.push(block),
+            Severity::Note => self.notes.push(block),
+        }
+        Vec::new()
+    }
+
+    fn fold(&mut self, file: Option<String>) {
+        let file = file.unwrap_or_else(|| "?".to_string());
+        match self.folded.iter_mut().find(|(f, _)| *f == file) {
+            Some((_, n)) => *n += 1,
+            None => self.folded.push((file, 1)),
+        }
+    }
+
+    /// Held-back blocks, in priority order, plus a note on what was dropped.
+    fn finish(&mut self) -> Vec<String> {
+        let mut out: Vec<String> = std::mem::take(&mut self.later_errors)
+            .into_iter()
+            .flatten()
+            .collect();
+        if !self.folded.is_empty() {
+            out.push(format_folded(&self.folded));
+        }
+        out.extend(
+            std::mem::take(&mut self.warnings)
+                .into_iter()
+                .chain(std::mem::take(&mut self.notes))
+                .flatten(),
+        );
+        let mut dropped = Vec::new();
+        if self.cascades > 0 {
+            dropped.push(format!("{} 'previous error' summaries", self.cascades));
+        }
+        if self.overflow > 0 {
+            dropped.push(format!(
+                "{} more after the first {} held back",
+                self.overflow, MAX_HELD_BLOCKS
+            ));
+        }
+        if self.hidden > 0 {
+            dropped.push(format!(
+                "{} below {}",
+                self.hidden,
+                match self.min {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                    Severity::Note => "note",
+                }
+            ));
+        }
+        if !dropped.is_empty() {
+            out.push(format!("(hidden: {})", dropped.join(", ")));
+        }
+        out
+    }
+}
+
+/// "… 12 downstream errors folded: src/a.rs (7), src/b.rs (5)"
+fn format_folded(folded: &[(String, usize)]) -> String {
+    const MAX_FILES: usize = 5;
+    let total: usize = folded.iter().map(|(_, n)| n).sum();
+    let mut files: Vec<String> = folded
+        .iter()
+        .take(MAX_FILES)
+        .map(|(f, n)| format!("{} ({})", f, n))
+        .collect();
+    if folded.len() > MAX_FILES {
+        files.push(format!("+{} more files", folded.len() - MAX_FILES));
+    }
+    format!(
+        "… {} downstream error{} folded (likely caused by the root error above): {}",
+        total,
+        if total == 1 { "" } else { "s" },
+        files.join(", ")
+    )
+}
+
+/// Errors and warnings of a captured output, prioritized (see [`Prioritizer`]).
+pub fn extract_prioritized(output: &str, min: Severity) -> String {
+    let mut extractor = ErrorExtractor::prioritized(min);
+    let mut lines: Vec<String> = output.lines().flat_map(|l| extractor.feed(l)).collect();
+    lines.extend(extractor.finish());
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generic_block_continuation() {
+        let output = "error: boom\n  detail\n\n  more\n\n\n  orphan\nok";
+        assert_eq!(extract(output), "error: boom\n  detail\n\n  more\n");
+    }
+
+    #[test]
+    fn test_generic_block_announced_detail() {
+        let output = "error during build:\n[vite:vue] a.vue (4:2): bad prop\nbuilt 12 modules";
+        assert_eq!(
+            extract(output),
+            "error during build:\n[vite:vue] a.vue (4:2): bad prop"
+        );
+    }
+
+    #[test]
+    fn test_python_traceback_keeps_last_frames() {
+        let mut input = vec!["collecting...".to_string()];
+        input.push("Traceback (most recent call last):".to_string());
+        for i in 0..5 {
+            input.push(format!(
+                "  File \"app/m{}.py\", line {}, in f{}",
+                i,
+                i + 1,
+                i
+            ));
+            input.push(format!("    f{}()", i + 1));
+        }
+        input.push("ValueError: bad input".to_string());
+        input.push("done".to_string());
+        assert_eq!(
+            extract(&input.join("\n")),
+            "Traceback (most recent call last):\n\
+             \x20 … 2 earlier frames\n\
+             \x20 File \"app/m2.py\", line 3, in f2\n\
+             \x20   f3()\n\
+             \x20 File \"app/m3.py\", line 4, in f3\n\
+             \x20   f4()\n\
+             \x20 File \"app/m4.py\", line 5, in f4\n\
+             \x20   f5()\n\
+             ValueError: bad input"
+        );
+    }
+
+    #[test]
+    fn test_python_chained_traceback() {
+        let input = "Traceback (most recent call last):\n  File \"a.py\", line 1, in <module>\nKeyError: 'x'\n\nDuring handling of the above exception, another exception occurred:\n\nTraceback (most recent call last):\n  File \"a.py\", line 3, in <module>\nRuntimeError: wrapped\nnext";
+        let out = extract(input);
+        assert!(out.starts_with("Traceback (most recent call last):\n  File \"a.py\", line 1"));
+        assert!(out.contains("KeyError: 'x'"));
+        assert!(out.ends_with("RuntimeError: wrapped"));
+    }
+
+    #[test]
+    fn test_java_exception_with_cause() {
+        let mut input =
+            vec!["Exception in thread \"main\" java.lang.IllegalStateException: boom".to_string()];
+        for i in 0..6 {
+            input.push(format!("\tat com.acme.App.step{}(App.java:{})", i, i + 10));
+        }
+        input.push("Caused by: java.io.IOException: disk".to_string());
+        input.push("\tat com.acme.Io.read(Io.java:3)".to_string());
+        input.push("\t... 6 more".to_string());
+        assert_eq!(
+            extract(&input.join("\n")),
+            "Exception in thread \"main\" java.lang.IllegalStateException: boom\n\
+             \tat com.acme.App.step0(App.java:10)\n\
+             \tat com.acme.App.step1(App.java:11)\n\
+             \tat com.acme.App.step2(App.java:12)\n\
+             \x20   … 3 more frames\n\
+             Caused by: java.io.IOException: disk\n\
+             \tat com.acme.Io.read(Io.java:3)\n\
+             \t... 6 more"
+        );
+    }
+
+    #[test]
+    fn test_js_error_drops_vendored_frames() {
+        let input = "TypeError: Cannot read properties of undefined (reading 'id')\n    at getUser (/app/src/user.js:12:18)\n    at Layer.handle (/app/node_modules/express/lib/router/layer.js:95:5)\n    at next (node:internal/process/task_queues:140:7)\n    at main (/app/src/index.js:4:3)\nServer stopped";
+        assert_eq!(
+            extract(input),
+            "TypeError: Cannot read properties of undefined (reading 'id')\n    at getUser (/app/src/user.js:12:18)\n    at main (/app/src/index.js:4:3)\n    … 2 node_modules/internal frames"
+        );
+    }
+
+    #[test]
+    fn test_go_panic_keeps_user_frames() {
+        let input = "panic: runtime error: index out of range [3] with length 3\n\ngoroutine 1 [running]:\nruntime.gopanic(0x1)\n\t/usr/lib/go/src/runtime/panic.go:770 +0x132\nmain.lookup(...)\n\t/app/main.go:12\nmain.main()\n\t/app/main.go:7 +0x1d\n\ngoroutine 6 [chan receive]:\nmain.worker()\n\t/app/main.go:20 +0x10\nexit status 2";
+        assert_eq!(
+            extract(input),
+            "panic: runtime error: index out of range [3] with length 3\n\
+             goroutine 1 [running]:\n\
+             main.lookup(...)\n\
+             \t/app/main.go:12\n\
+             main.main()\n\
+             \t/app/main.go:7 +0x1d\n\
+             \t… 1 more frames\n\
+             … 1 other goroutines"
+        );
+    }
+
+    #[test]
+    fn test_compiler_diagnostics_pass_through() {
+        let input = "   Compiling app v0.1.0\nerror[E0308]: mismatched types\n --> src/main.rs:4:13\n  |\n4 |     let x: u8 = \"a\";\n  |            --   ^^^ expected `u8`\nsrc/a.ts(3,5): error TS2322: Type 'string' is not assignable to type 'number'.\nmain.c:3:5: warning: unused variable 'y' [-Wunused-variable]";
+        let out = extract(input);
+        assert!(!out.contains("Compiling"));
+        assert!(out.contains("error[E0308]: mismatched types\n --> src/main.rs:4:13\n  |\n4 |"));
+        assert!(out.contains("error TS2322"));
+        assert!(out.contains("main.c:3:5: warning"));
+    }
+
+    #[test]
+    fn test_severity() {
+        let b = |l: &str| vec![l.to_string()];
+        assert_eq!(
+            severity(&b("error[E0425]: cannot find value")),
+            Severity::Error
+        );
+        assert_eq!(severity(&b("warning: unused import")), Severity::Warning);
+        assert_eq!(
+            severity(&b("main.c:3:5: warning: unused")),
+            Severity::Warning
+        );
+        assert_eq!(severity(&b("note: see also")), Severity::Note);
+        assert_eq!(
+            severity(&b("Traceback (most recent call last):")),
+            Severity::Error
+        );
+        assert_eq!("warn".parse::<Severity>(), Ok(Severity::Warning));
+        assert!("loud".parse::<Severity>().is_err());
+    }
+
+    #[test]
+    fn test_prioritized_errors_before_warnings() {
+        let input = "warning: unused import `foo`\n --> src/a.rs:1:5\n\
+                     error[E0425]: cannot find value `x`\n --> src/a.rs:10:5\n\
+                     error[E0425]: cannot find value `y`\n --> src/a.rs:12:5\n\
+                     error[E0308]: mismatched types\n --> src/b.rs:3:9\n\
+                     error: aborting due to 3 previous errors";
+        assert_eq!(
+            extract_prioritized(input, Severity::Note),
+            "error[E0425]: cannot find value `x`\n --> src/a.rs:10:5\n\
+             error[E0308]: mismatched types\n --> src/b.rs:3:9\n\
+             error[E0425]: cannot find value `y`\n --> src/a.rs:12:5\n\
+             warning: unused import `foo`\n --> src/a.rs:1:5\n\
+             (hidden: 1 'previous error' summaries)"
+        );
+    }
+
+    #[test]
+    fn test_prioritized_only_errors() {
+        let input = "warning: unused variable\n --> src/a.rs:1:5\nsrc/a.ts(3,5): error TS2322: bad";
+        assert_eq!(
+            extract_prioritized(input, Severity::Error),
+            "src/a.ts(3,5): error TS2322: bad\n(hidden: 1 below error)"
+        );
+    }
+
+    #[test]
+    fn test_cascade_rustc_unresolved_import() {
+        let input = "error[E0432]: unresolved import `crate::model::User`\n\
+                     \x20--> src/api.rs:3:5\n\
+                     error[E0412]: cannot find type `User` in this scope\n\
+                     \x20--> src/api.rs:10:18\n\
+                     error[E0425]: cannot find function `load_user` in this scope\n\
+                     \x20--> src/handlers.rs:4:9\n\
+                     error[E0308]: mismatched types\n\
+                     \x20--> src/main.rs:8:5";
+        assert_eq!(
+            extract_prioritized(input, Severity::Note),
+            "error[E0432]: unresolved import `crate::model::User`\n\
+             \x20--> src/api.rs:3:5\n\
+             error[E0308]: mismatched types\n\
+             \x20--> src/main.rs:8:5\n\
+             … 2 downstream errors folded (likely caused by the root error above): \
+             src/api.rs (1), src/handlers.rs (1)"
+        );
+    }
+
+    #[test]
+    fn test_cascade_tsc_missing_module() {
+        let input = "src/app.ts(1,22): error TS2307: Cannot find module './store' or its corresponding type declarations.\n\
+                     src/app.ts(5,3): error TS2304: Cannot find name 'Store'.\n\
+                     src/app.ts(9,14): error TS7006: Parameter 's' implicitly has an 'any' type.\n\
+                     src/view.ts(2,1): error TS2304: Cannot find name 'Store'.";
+        let out = extract_prioritized(input, Severity::Note);
+        assert!(out.starts_with("src/app.ts(1,22): error TS2307"));
+        assert!(out.ends_with("3 downstream errors folded (likely caused by the root error above): src/app.ts (2), src/view.ts (1)"));
+    }
+
+    #[test]
+    fn test_cascade_go_undefined() {
+        let input = "main.go:4:2: could not import example.com/missing/db (no required module provides package)\n\
+                     main.go:10:9: undefined: db\n\
+                     store.go:7:2: undefined: db\n\
+                     store.go:12:5: too many errors";
+        let out = extract_prioritized(input, Severity::Note);
+        assert_eq!(out.lines().count(), 2);
+        assert!(out.ends_with("3 downstream errors folded (likely caused by the root error above): main.go (1), store.go (2)"));
+    }
+
+    #[test]
+    fn test_cascade_javac_missing_package() {
+        let input = "App.java:3: error: package org.acme.db does not exist\n\
+                     import org.acme.db.Store;\n\
+                     App.java:8: error: cannot find symbol\n\
+                     \x20   symbol:   class Store\n\
+                     \x20   location: class App\n\
+                     App.java:9: error: cannot find symbol\n\
+                     \x20   symbol:   class Store\n\
+                     2 errors";
+        let out = extract_prioritized(input, Severity::Note);
+        assert!(out.starts_with("App.java:3: error: package org.acme.db does not exist"));
+        assert!(!out.contains("cannot find symbol"));
+        assert!(out.contains(
+            "2 downstream errors folded (likely caused by the root error above): App.java (2)"
+        ));
+    }
+
+    #[test]
+    fn test_cascade_mypy_missing_stub() {
+        let input = "app.py:1: error: Cannot find implementation or library stub for module named \"yaml\"\n\
+                     app.py:7: error: Name \"Loader\" is not defined\n\
+                     app.py:9: error: Incompatible return value type";
+        let out = extract_prioritized(input, Severity::Note);
+        assert!(out.contains("Incompatible return value type"));
+        assert!(!out.contains("Loader"));
+        assert!(out.ends_with(
+            "1 downstream error folded (likely caused by the root error above): app.py (1)"
+        ));
+    }
+
+    #[test]
+    fn test_cascade_gcc_missing_header() {
+        let input = "main.c:1:10: fatal error: 'sqlite3.h' file not found\n\
+                     main.c:5:3: error: unknown type name 'sqlite3'\n\
+                     main.c:9:5: error: implicit declaration of function 'sqlite3_open'";
+        let out = extract_prioritized(input, Severity::Note);
+        assert!(out.starts_with("main.c:1:10: fatal error"));
+        assert!(out.ends_with(
+            "2 downstream errors folded (likely caused by the root error above): main.c (2)"
+        ));
+    }
+
+    #[test]
+    fn test_no_folding_without_root() {
+        let input = "error[E0425]: cannot find value `x` in this scope\n --> src/a.rs:1:5";
+        assert_eq!(extract_prioritized(input, Severity::Note), input);
+    }
+
+    #[test]
+    fn test_held_blocks_and_block_lines_are_bounded() {
+        let warnings: String = (0..MAX_HELD_BLOCKS + 30)
+            .map(|i| format!("warning: unused variable v{}\n", i))
+            .collect();
+        let out = extract_prioritized(&warnings, Severity::Note);
+        assert_eq!(out.matches("unused variable").count(), MAX_HELD_BLOCKS);
+        assert!(out.ends_with("(hidden: 30 more after the first 500 held back)"));
+
+        let trace = format!("error: boom\n{}", "    at frame\n".repeat(MAX_BLOCK_LINES + 9));
+        let out = extract(&trace);
+        assert_eq!(out.lines().count(), MAX_BLOCK_LINES + 1);
+        assert!(out.ends_with("  … 10 more lines"));
+    }
+}
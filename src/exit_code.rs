@@ -0,0 +1,121 @@
+//! Exit code policy: rtk exits with the wrapped command's exit code.
+//!
+//! - Wrappers report the command's code through
+//!   `TimedExecution::with_exit_code`; `main` exits with it once output is
+//!   flushed. Wrappers that must stop early call [`exit_with`].
+//! - A command killed by a signal maps to `128 + signal`, as in shells.
+//! - Codes 124-127 are reserved for rtk's own outcomes (GNU `timeout` and
+//!   POSIX shell conventions), so scripts can tell them apart.
+
+use std::process::ExitStatus;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// The wrapped command hit its timeout (see stream.rs).
+pub const TIMEOUT: i32 = 124;
+/// rtk itself failed: bad arguments or config, I/O errors.
+pub const INTERNAL_ERROR: i32 = 125;
+/// The wrapped command exists but could not be executed.
+pub const NOT_EXECUTABLE: i32 = 126;
+/// The wrapped command was not found.
+pub const NOT_FOUND: i32 = 127;
+
+static CODE: AtomicI32 = AtomicI32::new(0);
+
+/// Remember the wrapped command's exit code for `main` to exit with.
+pub fn set(code: i32) {
+    CODE.store(code, Ordering::Relaxed);
+}
+
+/// Remember a failed wrapped command's status (for wrappers that don't track it).
+pub fn record(status: ExitStatus) {
+    set(from_status(status));
+}
+
+/// Exit code for a successful rtk run: the last code reported by a wrapper.
+pub fn pending() -> i32 {
+    CODE.load(Ordering::Relaxed)
+}
+
+/// The code a shell would report for `status`.
+pub fn from_status(status: ExitStatus) -> i32 {
+    if let Some(code) = status.code() {
+        return code;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    1
+}
+
+/// Exit now with the wrapped command's code, flushing held `--raw` output.
+pub fn exit_with(status: ExitStatus) -> ! {
+    exit(from_status(status))
+}
+
+/// Exit now with `code`, flushing held `--raw`, pager or spilled output.
+pub fn exit(code: i32) -> ! {
+    tracing::debug!(code, "exit");
+    crate::profile::flush_raw();
+    crate::pager::flush();
+    crate::spill::flush();
+    crate::timings::report();
+    std::process::exit(code)
+}
+
+/// Exit code for an error returned to `main`.
+///
+/// 126/127 only when spawning the wrapped command failed (wrappers add a
+/// "Failed to run/execute" context); a missing input file is an rtk error.
+pub fn for_error(err: &anyhow::Error) -> i32 {
+    let spawning = err.chain().any(|cause| {
+        let msg = cause.to_string();
+        msg.starts_with("Failed to run") || msg.starts_with("Failed to execute")
+    });
+    if !spawning {
+        return INTERNAL_ERROR;
+    }
+    let io_kind = err
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<std::io::Error>())
+        .map(|e| e.kind());
+    match io_kind {
+        Some(std::io::ErrorKind::NotFound) => NOT_FOUND,
+        Some(std::io::ErrorKind::PermissionDenied) => NOT_EXECUTABLE,
+        _ => INTERNAL_ERROR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_from_status() {
+        use std::os::unix::process::ExitStatusExt;
+        assert_eq!(from_status(ExitStatus::from_raw(0)), 0);
+        assert_eq!(from_status(ExitStatus::from_raw(3 << 8)), 3);
+        // Killed by SIGKILL (9)
+        assert_eq!(from_status(ExitStatus::from_raw(9)), 137);
+    }
+
+    #[test]
+    fn test_for_error() {
+        let spawn = std::process::Command::new("rtk-definitely-missing-binary")
+            .output()
+            .context("Failed to run rtk-definitely-missing-binary")
+            .unwrap_err();
+        assert_eq!(for_error(&spawn), NOT_FOUND);
+        assert_eq!(for_error(&anyhow::anyhow!("bad config")), INTERNAL_ERROR);
+
+        let read = std::fs::read_to_string("/nonexistent/rtk-file")
+            .context("Failed to read file: /nonexistent/rtk-file")
+            .unwrap_err();
+        assert_eq!(for_error(&read), INTERNAL_ERROR);
+    }
+}
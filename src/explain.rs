@@ -0,0 +1,215 @@
+//! `--explain`: append a compression report after a command's output.
+//!
+//! Wrappers are opaque filters, so the report is reconstructed from what they
+//! hand to `track`: every input line missing from the output is classified
+//! (blank, progress, duplicate, other), contiguous dropped blocks are listed
+//! as elided sections, and user pipeline rules report their own hit counts.
+
+use crate::generic_cmd::is_progress_line;
+use crate::pipeline;
+use crate::style;
+use crate::tracking::estimate_tokens;
+use crate::utils::{format_tokens, strip_ansi, truncate};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Dropped runs shorter than this are not reported as sections.
+const MIN_SECTION: usize = 3;
+const MAX_SECTIONS: usize = 5;
+/// Substring matching against the output is skipped above this many input lines.
+const SUBSTRING_LIMIT: usize = 5000;
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Called from `track`: print the report when `--explain` is on.
+pub fn report_if_enabled(input: &str, output: &str) {
+    if ENABLED.load(Ordering::Relaxed) {
+        // Bypass profile/pipeline/--raw so the report is always shown in full
+        let report = build_report(input, output, &pipeline::stats());
+        ::std::println!("{}", style::apply(&report));
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Dropped {
+    blank: usize,
+    progress: usize,
+    duplicate: usize,
+    other: usize,
+}
+
+impl Dropped {
+    fn total(&self) -> usize {
+        self.blank + self.progress + self.duplicate + self.other
+    }
+}
+
+/// Elided block: 1-based first line, length, first line text.
+pub(crate) type Section = (usize, usize, String);
+
+/// Blocks of `input` that did not make it into `output` (see pager.rs).
+pub(crate) fn elided_sections(input: &str, output: &str) -> Vec<Section> {
+    analyze(input, output).1
+}
+
+fn analyze(input: &str, output: &str) -> (Dropped, Vec<Section>) {
+    let output = strip_ansi(output);
+    let output_lines: HashSet<&str> = output.lines().map(str::trim).collect();
+    let input_clean = strip_ansi(input);
+    let input_lines: Vec<&str> = input_clean.lines().collect();
+    let substring = input_lines.len() <= SUBSTRING_LIMIT;
+
+    let mut dropped = Dropped::default();
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut sections: Vec<Section> = Vec::new();
+    let mut run_start: Option<usize> = None;
+
+    for (i, line) in input_lines.iter().enumerate() {
+        let trimmed = line.trim();
+        let first_time = seen.insert(trimmed);
+        let kept = !trimmed.is_empty()
+            && (output_lines.contains(trimmed) || (substring && output.contains(trimmed)));
+
+        if kept {
+            close_run(&mut run_start, i, &input_lines, &mut sections);
+            continue;
+        }
+        if trimmed.is_empty() {
+            dropped.blank += 1;
+        } else if is_progress_line(line) {
+            dropped.progress += 1;
+        } else if !first_time {
+            dropped.duplicate += 1;
+        } else {
+            dropped.other += 1;
+        }
+        if !trimmed.is_empty() && run_start.is_none() {
+            run_start = Some(i);
+        }
+    }
+    close_run(
+        &mut run_start,
+        input_lines.len(),
+        &input_lines,
+        &mut sections,
+    );
+    (dropped, sections)
+}
+
+fn close_run(
+    run_start: &mut Option<usize>,
+    end: usize,
+    lines: &[&str],
+    sections: &mut Vec<Section>,
+) {
+    if let Some(start) = run_start.take() {
+        if end - start >= MIN_SECTION {
+            sections.push((start + 1, end - start, lines[start].trim().to_string()));
+        }
+    }
+}
+
+fn build_report(input: &str, output: &str, rules: &[(String, usize)]) -> String {
+    let (dropped, sections) = analyze(input, output);
+    let in_tokens = estimate_tokens(input);
+    let out_tokens = estimate_tokens(output);
+    let saved = in_tokens.saturating_sub(out_tokens);
+    let pct = if in_tokens > 0 {
+        saved as f64 / in_tokens as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    let mut out = vec![
+        "--- rtk explain ---".to_string(),
+        format!(
+            "lines: {} -> {}   tokens: {} -> {} (saved {}, {:.1}%)",
+            input.lines().count(),
+            output.lines().count(),
+            format_tokens(in_tokens),
+            format_tokens(out_tokens),
+            format_tokens(saved),
+            pct
+        ),
+        format!(
+            "dropped {} lines: {} blank, {} progress, {} duplicate, {} filtered/summarized",
+            dropped.total(),
+            dropped.blank,
+            dropped.progress,
+            dropped.duplicate,
+            dropped.other
+        ),
+    ];
+
+    if !sections.is_empty() {
+        out.push(format!("elided sections ({}):", sections.len()));
+        for (start, len, first) in sections.iter().take(MAX_SECTIONS) {
+            out.push(format!(
+                "  lines {}-{} ({}): {}",
+                start,
+                start + len - 1,
+                len,
+                truncate(first, 60)
+            ));
+        }
+        if sections.len() > MAX_SECTIONS {
+            out.push(format!("  ... +{} more", sections.len() - MAX_SECTIONS));
+        }
+    }
+
+    for (rule, hits) in rules {
+        out.push(format!("pipeline {}: {} lines", rule, hits));
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_classifies_dropped_lines() {
+        let input = "Compiling a\nCompiling b\nCompiling c\n\n[=========>   ] 50%\nwarning: x\nwarning: x\nerror: boom\n";
+        let output = "error: boom\nwarning: x";
+        let (dropped, sections) = analyze(input, output);
+        assert_eq!(
+            dropped,
+            Dropped {
+                blank: 1,
+                progress: 1,
+                duplicate: 0,
+                other: 3,
+            }
+        );
+        assert_eq!(sections, vec![(1, 5, "Compiling a".to_string())]);
+    }
+
+    #[test]
+    fn test_duplicates_counted() {
+        let (dropped, _) = analyze("same\nsame\nsame\nkept", "kept");
+        assert_eq!(dropped.other, 1);
+        assert_eq!(dropped.duplicate, 2);
+    }
+
+    #[test]
+    fn test_reformatted_lines_count_as_kept() {
+        // Output embeds the input line inside a longer formatted line
+        let (dropped, sections) = analyze("src/main.rs", "📄 src/main.rs (3)");
+        assert_eq!(dropped.total(), 0);
+        assert!(sections.is_empty());
+    }
+
+    #[test]
+    fn test_report_lists_rules() {
+        let report = build_report("a\nb\nc\nd\n", "a", &[("drop /^DEBUG/".to_string(), 7)]);
+        assert!(report.starts_with("--- rtk explain ---"));
+        assert!(report.contains("lines: 4 -> 1"));
+        assert!(report.contains("dropped 3 lines"));
+        assert!(report.contains("lines 2-4 (3): b"));
+        assert!(report.contains("pipeline drop /^DEBUG/: 7 lines"));
+    }
+}
@@ -0,0 +1,507 @@
+//! `rtk fetch <url>` — a URL as compact text.
+//!
+//! The page is fetched with `curl` and filtered by content type: HTML goes
+//! through the readability extraction of `rtk html`, JSON through the schema
+//! summary of `rtk json`, text is passed as is. Responses are cached under
+//! `<data dir>/rtk/fetch/` keyed by URL, with their `ETag`/`Last-Modified`:
+//! within `FRESH` an entry is served without touching the network, after
+//! that it is revalidated with a conditional request, so an unchanged page
+//! costs a 304. `robots.txt` is honoured unless `--ignore-robots`.
+
+use crate::cache;
+use crate::html_cmd;
+use crate::json_cmd;
+use crate::tracking;
+use crate::utils::format_tokens;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Cached responses newer than this are served without revalidating.
+const FRESH: Duration = Duration::from_secs(60 * 60);
+/// Cached responses (and robots.txt files) are deleted after this.
+const TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const ROBOTS_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const TIMEOUT_SECS: &str = "30";
+/// Plain-text bodies longer than this are cut.
+const MAX_TEXT_LINES: usize = 500;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Cached {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    content_type: String,
+    raw_len: usize,
+    /// Raw text body (empty for binary content), for `--raw` and tracking
+    raw: String,
+    output: String,
+    fetched_at: u64,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Response {
+    status: u16,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    content_type: String,
+    /// URL after redirects
+    url: String,
+    body: Vec<u8>,
+}
+
+pub fn run(url: &str, refresh: bool, ignore_robots: bool, verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        bail!("Not an http(s) URL: {}", url);
+    }
+    let dir = fetch_dir();
+    if let Some(dir) = &dir {
+        let _ = std::fs::create_dir_all(dir);
+        cache::prune(dir, TTL);
+    }
+    let path = dir
+        .as_ref()
+        .map(|d| d.join(format!("{}.json", cache::key(&[url]))));
+    let cached = path.as_deref().filter(|_| !refresh).and_then(read_cached);
+
+    let (entry, note) = match cached {
+        Some(entry) if age(&entry) < FRESH => {
+            let note = format!("cached {} ago", format_age(age(&entry)));
+            (entry, note)
+        }
+        cached => {
+            if !ignore_robots {
+                check_robots(url, dir.as_deref(), verbose)?;
+            }
+            let response = request(url, cached.as_ref(), verbose)?;
+            match cached {
+                Some(mut entry) if response.status == 304 => {
+                    entry.fetched_at = now();
+                    (entry, "unchanged since last fetch (304)".to_string())
+                }
+                _ => {
+                    if response.status >= 400 {
+                        bail!("HTTP {} for {}", response.status, response.url);
+                    }
+                    let (output, text) = filter_body(&response);
+                    let entry = Cached {
+                        output,
+                        raw: if text {
+                            String::from_utf8_lossy(&response.body).into_owned()
+                        } else {
+                            String::new()
+                        },
+                        url: response.url,
+                        etag: response.etag,
+                        last_modified: response.last_modified,
+                        content_type: response.content_type,
+                        raw_len: response.body.len(),
+                        fetched_at: now(),
+                    };
+                    (entry, format!("HTTP {}", response.status))
+                }
+            }
+        }
+    };
+    if let Some(path) = &path {
+        if let Ok(json) = serde_json::to_vec(&entry) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+
+    let header = format!(
+        "{} ({}, {} bytes, {})",
+        entry.url,
+        if entry.content_type.is_empty() {
+            "unknown type"
+        } else {
+            &entry.content_type
+        },
+        entry.raw_len,
+        note
+    );
+    let output = format!("{}\n\n{}", header, entry.output);
+    println!("{}", output);
+    if verbose > 0 {
+        eprintln!(
+            "~{} tokens after filtering",
+            format_tokens(tracking::estimate_tokens(&entry.output))
+        );
+    }
+    timer.track(
+        &format!("curl -sL {}", url),
+        &format!("rtk fetch {}", url),
+        &entry.raw,
+        &output,
+    );
+    Ok(())
+}
+
+fn fetch_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("RTK_FETCH_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    Some(dirs::data_local_dir()?.join("rtk").join("fetch"))
+}
+
+fn read_cached(path: &Path) -> Option<Cached> {
+    serde_json::from_slice(&std::fs::read(path).ok()?).ok()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn age(entry: &Cached) -> Duration {
+    Duration::from_secs(now().saturating_sub(entry.fetched_at))
+}
+
+fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        _ => format!("{}h", secs / 3600),
+    }
+}
+
+/// GET `url` with curl, conditional on the cached validators.
+fn request(url: &str, cached: Option<&Cached>, verbose: u8) -> Result<Response> {
+    let tmp = tempfile::tempdir().context("Failed to create temp dir")?;
+    let headers = tmp.path().join("headers");
+    let body = tmp.path().join("body");
+    let mut cmd = Command::new("curl");
+    cmd.args(["-sSL", "--compressed", "--max-time", TIMEOUT_SECS])
+        .args(["-A", concat!("rtk/", env!("CARGO_PKG_VERSION"))])
+        .arg("-D")
+        .arg(&headers)
+        .arg("-o")
+        .arg(&body)
+        .args(["-w", "%{url_effective}"]);
+    if let Some(cached) = cached {
+        if let Some(etag) = &cached.etag {
+            cmd.arg("-H").arg(format!("If-None-Match: {}", etag));
+        }
+        if let Some(modified) = &cached.last_modified {
+            cmd.arg("-H")
+                .arg(format!("If-Modified-Since: {}", modified));
+        }
+    }
+    cmd.arg(url);
+    if verbose > 0 {
+        eprintln!("Fetching: {}", url);
+    }
+    let output = cmd.output().context("Failed to run curl")?;
+    if !output.status.success() {
+        bail!(
+            "curl failed for {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let headers = std::fs::read_to_string(&headers).unwrap_or_default();
+    let mut response = parse_headers(&headers);
+    response.url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if response.url.is_empty() {
+        response.url = url.to_string();
+    }
+    response.body = std::fs::read(&body).unwrap_or_default();
+    Ok(response)
+}
+
+/// Status and validators from the last header block (curl writes one per
+/// redirect).
+fn parse_headers(headers: &str) -> Response {
+    let block = headers
+        .split("\r\n\r\n")
+        .flat_map(|b| b.split("\n\n"))
+        .filter(|b| b.trim_start().starts_with("HTTP/"))
+        .last()
+        .unwrap_or("");
+    let mut response = Response::default();
+    for (i, line) in block.trim().lines().enumerate() {
+        if i == 0 {
+            response.status = line
+                .split_whitespace()
+                .nth(1)
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0);
+            continue;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "etag" => response.etag = Some(value),
+            "last-modified" => response.last_modified = Some(value),
+            "content-type" => response.content_type = value,
+            _ => {}
+        }
+    }
+    response
+}
+
+/// Compact text for the body, by content type (sniffed when absent), and
+/// whether the body is text.
+fn filter_body(response: &Response) -> (String, bool) {
+    let body = String::from_utf8_lossy(&response.body);
+    let mime = response
+        .content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    let trimmed = body.trim_start();
+    let lower_start = trimmed
+        .get(..trimmed.len().min(100))
+        .unwrap_or("")
+        .to_ascii_lowercase();
+
+    if mime.contains("json") || (mime.is_empty() && trimmed.starts_with(['{', '['])) {
+        if let Ok(schema) = json_cmd::filter_json_string(body.trim(), 5) {
+            return (schema, true);
+        }
+    }
+    if mime.contains("html")
+        || lower_start.starts_with("<!doctype html")
+        || lower_start.starts_with("<html")
+    {
+        return (
+            html_cmd::extract(&body, Some(&response.url)).render(true),
+            true,
+        );
+    }
+    if !mime.is_empty() && !mime.starts_with("text/") && !mime.contains("xml") {
+        let note = format!("(binary {}, {} bytes not shown)", mime, response.body.len());
+        return (note, false);
+    }
+    let lines: Vec<&str> = body.lines().collect();
+    if lines.len() > MAX_TEXT_LINES {
+        let text = format!(
+            "{}\n... +{} more lines",
+            lines[..MAX_TEXT_LINES].join("\n"),
+            lines.len() - MAX_TEXT_LINES
+        );
+        return (text, true);
+    }
+    (body.trim_end().to_string(), true)
+}
+
+/// Fail if the site's robots.txt disallows `url` for rtk.
+fn check_robots(url: &str, dir: Option<&Path>, verbose: u8) -> Result<()> {
+    let Some((origin, path)) = split_origin(url) else {
+        return Ok(());
+    };
+    let cache_path = dir.map(|d| d.join(format!("{}.robots", cache::key(&["robots", origin]))));
+    let fresh = cache_path.as_deref().and_then(|p| {
+        let age = p.metadata().ok()?.modified().ok()?.elapsed().ok()?;
+        (age < ROBOTS_TTL).then(|| std::fs::read_to_string(p).ok())?
+    });
+    let robots = match fresh {
+        Some(robots) => robots,
+        None => {
+            let robots_url = format!("{}/robots.txt", origin);
+            // No robots.txt allows everything; an unreachable site is
+            // reported by the page request itself
+            let Ok(response) = request(&robots_url, None, verbose) else {
+                return Ok(());
+            };
+            let robots = if response.status == 200 {
+                String::from_utf8_lossy(&response.body).into_owned()
+            } else {
+                String::new()
+            };
+            if let Some(p) = &cache_path {
+                let _ = std::fs::write(p, &robots);
+            }
+            robots
+        }
+    };
+    if !robots_allows(&robots, "rtk", path) {
+        bail!(
+            "{}/robots.txt disallows {} (--ignore-robots to fetch anyway)",
+            origin,
+            path
+        );
+    }
+    Ok(())
+}
+
+/// `("https://host:port", "/path?query")`
+fn split_origin(url: &str) -> Option<(&str, &str)> {
+    let scheme_end = url.find("://")? + 3;
+    let path_start = url[scheme_end..]
+        .find(['/', '?', '#'])
+        .map_or(url.len(), |i| scheme_end + i);
+    let path = url[path_start..].split('#').next().unwrap_or("");
+    Some((&url[..path_start], if path.is_empty() { "/" } else { path }))
+}
+
+/// One `User-agent` group of robots.txt.
+#[derive(Default)]
+struct RobotsGroup {
+    agents: Vec<String>,
+    /// (allow, path pattern)
+    rules: Vec<(bool, String)>,
+}
+
+/// Whether `path` may be fetched by `agent`: the group naming the agent
+/// wins over `*`; within it the longest matching rule wins, Allow on ties.
+fn robots_allows(robots: &str, agent: &str, path: &str) -> bool {
+    let agent = agent.to_ascii_lowercase();
+    let mut groups: Vec<RobotsGroup> = Vec::new();
+    let mut in_agents = false;
+    for line in robots.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let Some((field, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match field.trim().to_ascii_lowercase().as_str() {
+            "user-agent" => {
+                if !in_agents {
+                    groups.push(RobotsGroup::default());
+                }
+                in_agents = true;
+                if let Some(group) = groups.last_mut() {
+                    group.agents.push(value.to_ascii_lowercase());
+                }
+            }
+            field @ ("allow" | "disallow") => {
+                in_agents = false;
+                if let Some(group) = groups.last_mut() {
+                    // An empty Disallow allows everything
+                    if !value.is_empty() {
+                        group.rules.push((field == "allow", value));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    let named = groups
+        .iter()
+        .filter(|group| {
+            group
+                .agents
+                .iter()
+                .any(|a| a != "*" && agent.contains(a.as_str()))
+        })
+        .collect::<Vec<_>>();
+    let chosen = if named.is_empty() {
+        groups
+            .iter()
+            .filter(|group| group.agents.iter().any(|a| a == "*"))
+            .collect()
+    } else {
+        named
+    };
+    chosen
+        .iter()
+        .flat_map(|group| &group.rules)
+        .filter(|(_, pattern)| rule_matches(pattern, path))
+        .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+        .is_none_or(|(allow, _)| *allow)
+}
+
+/// robots.txt path pattern: prefix match, `*` wildcard, `$` end anchor.
+fn rule_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        let last = i + 1 == parts.len();
+        if last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_headers_takes_last_block() {
+        let headers = "HTTP/1.1 301 Moved Permanently\r\nLocation: /b\r\n\r\n\
+                       HTTP/2 200\r\nETag: \"abc\"\r\ncontent-type: text/html; charset=utf-8\r\n\
+                       Last-Modified: Tue, 01 Oct 2024 10:00:00 GMT\r\n\r\n";
+        let response = parse_headers(headers);
+        assert_eq!(response.status, 200);
+        assert_eq!(response.etag.as_deref(), Some("\"abc\""));
+        assert_eq!(response.content_type, "text/html; charset=utf-8");
+        assert_eq!(
+            response.last_modified.as_deref(),
+            Some("Tue, 01 Oct 2024 10:00:00 GMT")
+        );
+    }
+
+    #[test]
+    fn test_filter_body_by_content_type() {
+        let mut response = Response {
+            content_type: "application/json".to_string(),
+            url: "https://a.dev/docs/".to_string(),
+            body: br#"{"name": "x", "tags": ["a"]}"#.to_vec(),
+            ..Response::default()
+        };
+        let (json, _) = filter_body(&response);
+        assert!(json.contains("name"));
+        assert!(!json.contains("\"x\""));
+
+        response.content_type = "text/html".to_string();
+        response.body = b"<p>See <a href=\"intro\">intro</a>.</p>".to_vec();
+        assert_eq!(
+            filter_body(&response).0,
+            "See intro [1].\n\nLinks (1):\n[1] intro: https://a.dev/docs/intro"
+        );
+
+        response.content_type = "image/png".to_string();
+        assert_eq!(
+            filter_body(&response),
+            ("(binary image/png, 37 bytes not shown)".to_string(), false)
+        );
+    }
+
+    #[test]
+    fn test_robots_rules() {
+        let robots =
+            "User-agent: *\nDisallow: /private\nAllow: /private/docs\nDisallow: /*.pdf$\n\n\
+                      User-agent: BadBot\nDisallow: /\n";
+        assert!(robots_allows(robots, "rtk", "/docs/intro"));
+        assert!(!robots_allows(robots, "rtk", "/private/keys"));
+        assert!(robots_allows(robots, "rtk", "/private/docs/a"));
+        assert!(!robots_allows(robots, "rtk", "/files/spec.pdf"));
+        assert!(robots_allows(robots, "rtk", "/files/spec.pdf?x=1"));
+        assert!(!robots_allows(robots, "badbot", "/docs"));
+        assert!(robots_allows("", "rtk", "/anything"));
+        assert!(robots_allows("User-agent: *\nDisallow:\n", "rtk", "/x"));
+    }
+
+    #[test]
+    fn test_split_origin() {
+        assert_eq!(
+            split_origin("https://a.dev:8080/x/y?q=1#frag"),
+            Some(("https://a.dev:8080", "/x/y?q=1"))
+        );
+        assert_eq!(split_origin("https://a.dev"), Some(("https://a.dev", "/")));
+    }
+}
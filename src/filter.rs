@@ -1,3 +1,4 @@
+use crate::mapped;
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::str::FromStr;
@@ -315,7 +316,7 @@ pub fn get_filter(level: FilterLevel) -> Box<dyn FilterStrategy> {
 }
 
 pub fn smart_truncate(content: &str, max_lines: usize, _lang: &Language) -> String {
-    let lines: Vec<&str> = content.lines().collect();
+    let lines: Vec<&str> = mapped::lines(content).collect();
     if lines.len() <= max_lines {
         return content.to_string();
     }
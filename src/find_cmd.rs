@@ -1,8 +1,17 @@
+use crate::exit_code;
+use crate::path_rules::{PathAction, PathRules};
 use crate::tracking;
-use anyhow::Result;
-use ignore::WalkBuilder;
-use std::collections::HashMap;
+use crate::utils::{display_width, slash_path, take_last_width};
+use crate::walk::{self, Options};
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+/// Names listed per directory before the rest collapse to `+N`
+pub const DEFAULT_PER_DIR: usize = 10;
 
 /// Match a filename against a glob pattern (supports `*` and `?`).
 fn glob_match(pattern: &str, name: &str) -> bool {
@@ -23,41 +32,214 @@ fn glob_match_inner(pat: &[u8], name: &[u8]) -> bool {
     }
 }
 
+/// Directory part of a relative path, "." for top-level entries.
+fn parent_dir(path: &str) -> String {
+    let dir = Path::new(path)
+        .parent()
+        .map(|d| d.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if dir.is_empty() {
+        ".".to_string()
+    } else {
+        dir
+    }
+}
+
+/// How result names are matched: `rtk find` takes a glob, `rtk fd` a regex.
+enum NameMatcher {
+    Glob(String),
+    Regex(Regex),
+}
+
+impl NameMatcher {
+    fn is_match(&self, name: &str) -> bool {
+        match self {
+            NameMatcher::Glob(pattern) => glob_match(pattern, name),
+            NameMatcher::Regex(re) => re.is_match(name),
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            NameMatcher::Glob(pattern) => pattern,
+            NameMatcher::Regex(re) => re.as_str(),
+        }
+    }
+}
+
+/// Listing options shared by `rtk find` and `rtk fd`.
+struct FindOptions {
+    max_results: usize,
+    per_dir: usize,
+    want_dirs: bool,
+    extensions: Vec<String>,
+    changed_within: Option<Duration>,
+}
+
+/// Parse an age like `30m`, `2h`, `3d` or `1w` (bare numbers are seconds).
+pub fn parse_age(text: &str) -> Result<Duration> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (num, unit) = text.split_at(split);
+    let num: u64 = num
+        .parse()
+        .with_context(|| format!("Invalid age '{}' (expected e.g. 30m, 2h, 3d)", text))?;
+    let secs = match unit {
+        "" | "s" => 1,
+        "m" | "min" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        "w" => 604_800,
+        _ => bail!("Invalid age '{}' (expected e.g. 30m, 2h, 3d)", text),
+    };
+    Ok(Duration::from_secs(num * secs))
+}
+
 pub fn run(
     pattern: &str,
     path: &str,
     max_results: usize,
+    per_dir: usize,
     file_type: &str,
+    changed_within: Option<&str>,
     verbose: u8,
 ) -> Result<()> {
-    let timer = tracking::TimedExecution::start();
-
     // Treat "." as match-all
     let effective_pattern = if pattern == "." { "*" } else { pattern };
+    let opts = FindOptions {
+        max_results,
+        per_dir,
+        want_dirs: file_type == "d",
+        extensions: Vec::new(),
+        changed_within: changed_within.map(parse_age).transpose()?,
+    };
+    find(
+        &NameMatcher::Glob(effective_pattern.to_string()),
+        path,
+        &opts,
+        "rtk find",
+        verbose,
+    )
+}
+
+/// `rtk fd [PATTERN] [PATH] [-e EXT] [-t f|d] [--changed-within AGE]`:
+/// fd's common flags on top of the same grouped listing. Anything else runs
+/// fd unfiltered.
+pub fn run_fd(args: &[String], verbose: u8) -> Result<()> {
+    let mut positional: Vec<&str> = Vec::new();
+    let mut opts = FindOptions {
+        max_results: 50,
+        per_dir: DEFAULT_PER_DIR,
+        want_dirs: false,
+        extensions: Vec::new(),
+        changed_within: None,
+    };
+    let mut glob = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if arg.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = || inline.clone().or_else(|| iter.next().cloned());
+        match flag {
+            "-e" | "--extension" => {
+                if let Some(ext) = value() {
+                    opts.extensions
+                        .push(ext.trim_start_matches('.').to_string());
+                }
+            }
+            "-t" | "--type" => {
+                opts.want_dirs = matches!(value().as_deref(), Some("d" | "directory"));
+            }
+            "--changed-within" => {
+                opts.changed_within = value().as_deref().map(parse_age).transpose()?
+            }
+            "--max-results" => {
+                opts.max_results = value().and_then(|v| v.parse().ok()).unwrap_or(50);
+            }
+            "-g" | "--glob" => glob = true,
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                return run_fd_passthrough(args, verbose);
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let pattern = positional.first().copied().unwrap_or("");
+    let path = positional.get(1).copied().unwrap_or(".");
+    let matcher = if glob {
+        NameMatcher::Glob(pattern.to_string())
+    } else if pattern.is_empty() {
+        NameMatcher::Glob("*".to_string())
+    } else {
+        // fd's smart case: case-insensitive unless the pattern has uppercase
+        let smart = if pattern.chars().any(|c| c.is_uppercase()) {
+            pattern.to_string()
+        } else {
+            format!("(?i){}", pattern)
+        };
+        NameMatcher::Regex(Regex::new(&smart).context("Invalid fd pattern")?)
+    };
+    find(&matcher, path, &opts, "rtk fd", verbose)
+}
 
+fn run_fd_passthrough(args: &[String], verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
     if verbose > 0 {
-        eprintln!("find: {} in {}", effective_pattern, path);
+        eprintln!("fd passthrough: {:?}", args);
     }
+    let status = Command::new("fd")
+        .args(args)
+        .status()
+        .context("Failed to run fd")?;
+    timer.with_exit_code(status).track_passthrough(
+        &format!("fd {}", args.join(" ")),
+        &format!("rtk fd {} (passthrough)", args.join(" ")),
+    );
+    if !status.success() {
+        exit_code::exit_with(status);
+    }
+    Ok(())
+}
+
+/// A match: path relative to the search root and its modification time.
+struct Found {
+    path: String,
+    modified: Option<SystemTime>,
+}
+
+fn find(
+    matcher: &NameMatcher,
+    path: &str,
+    opts: &FindOptions,
+    rtk_cmd: &str,
+    verbose: u8,
+) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let effective_pattern = matcher.as_str();
 
-    let want_dirs = file_type == "d";
+    if verbose > 0 {
+        eprintln!("find: {} in {}", effective_pattern, path);
+    }
 
-    let walker = WalkBuilder::new(path)
-        .hidden(true) // skip hidden files/dirs
-        .git_ignore(true) // respect .gitignore
-        .git_global(true)
-        .git_exclude(true)
-        .build();
+    let want_dirs = opts.want_dirs;
+    let cutoff = opts
+        .changed_within
+        .and_then(|age| SystemTime::now().checked_sub(age));
 
-    let mut files: Vec<String> = Vec::new();
+    let rules = PathRules::load();
+    // Hidden files skipped, .gitignore and .rtkignore respected
+    let entries = walk::walk(Path::new(path), Options::default());
 
-    for entry in walker {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => continue,
-        };
+    let mut files: Vec<Found> = Vec::new();
+    // Matches under summarize_paths, counted per directory instead of listed
+    let mut summarized: BTreeMap<String, usize> = BTreeMap::new();
 
-        let ft = entry.file_type();
-        let is_dir = ft.as_ref().map_or(false, |t| t.is_dir());
+    for entry in &entries {
+        let is_dir = entry.is_dir();
 
         // Filter by type
         if want_dirs && !is_dir {
@@ -67,7 +249,7 @@ pub fn run(
             continue;
         }
 
-        let entry_path = entry.path();
+        let entry_path = entry.path.as_path();
 
         // Get filename for glob matching
         let name = match entry_path.file_name() {
@@ -75,133 +257,189 @@ pub fn run(
             None => continue,
         };
 
-        if !glob_match(effective_pattern, &name) {
+        if !matcher.is_match(&name) {
+            continue;
+        }
+        if !opts.extensions.is_empty()
+            && !entry_path
+                .extension()
+                .is_some_and(|ext| opts.extensions.iter().any(|e| *ext == **e))
+        {
+            continue;
+        }
+
+        // Store path relative to search root, with `/` separators on every platform
+        let display_path = slash_path(
+            &entry_path
+                .strip_prefix(path)
+                .unwrap_or(entry_path)
+                .to_string_lossy(),
+        );
+
+        if display_path.is_empty() {
             continue;
         }
 
-        // Store path relative to search root
-        let display_path = entry_path
-            .strip_prefix(path)
-            .unwrap_or(entry_path)
-            .to_string_lossy()
-            .to_string();
+        let modified = entry.metadata().ok().and_then(|m| m.modified().ok());
+        if let Some(cutoff) = cutoff {
+            if modified.is_none_or(|m| m < cutoff) {
+                continue;
+            }
+        }
 
-        if !display_path.is_empty() {
-            files.push(display_path);
+        match rules.action(entry_path, is_dir) {
+            PathAction::Keep => files.push(Found {
+                path: display_path,
+                modified,
+            }),
+            PathAction::Exclude => {}
+            PathAction::Summarize => {
+                *summarized.entry(parent_dir(&display_path)).or_default() += 1;
+            }
         }
     }
 
-    files.sort();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
 
-    let raw_output = files.join("\n");
+    let raw_output = files
+        .iter()
+        .map(|f| f.path.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let original_cmd = format!("find {} -name '{}'", path, effective_pattern);
 
-    if files.is_empty() {
+    if files.is_empty() && summarized.is_empty() {
         let msg = format!("0 for '{}'", effective_pattern);
         println!("{}", msg);
-        timer.track(
-            &format!("find {} -name '{}'", path, effective_pattern),
-            "rtk find",
-            &raw_output,
-            &msg,
-        );
+        timer.track(&original_cmd, rtk_cmd, &raw_output, &msg);
         return Ok(());
     }
 
-    // Group by directory
-    let mut by_dir: HashMap<String, Vec<String>> = HashMap::new();
-
-    for file in &files {
-        let p = Path::new(file);
-        let dir = p
-            .parent()
-            .map(|d| d.to_string_lossy().to_string())
-            .unwrap_or_else(|| ".".to_string());
-        let dir = if dir.is_empty() { ".".to_string() } else { dir };
-        let filename = p
-            .file_name()
-            .map(|f| f.to_string_lossy().to_string())
-            .unwrap_or_default();
-        by_dir.entry(dir).or_default().push(filename);
-    }
-
-    let mut dirs: Vec<_> = by_dir.keys().cloned().collect();
-    dirs.sort();
-    let dirs_count = dirs.len();
     let total_files = files.len();
-
-    println!("📁 {}F {}D:", total_files, dirs_count);
-    println!();
-
-    // Display with proper --max limiting (count individual files)
-    let mut shown = 0;
-    for dir in &dirs {
-        if shown >= max_results {
-            break;
-        }
-
-        let files_in_dir = &by_dir[dir];
-        let dir_display = if dir.len() > 50 {
-            format!("...{}", &dir[dir.len() - 47..])
-        } else {
-            dir.clone()
-        };
-
-        let remaining_budget = max_results - shown;
-        if files_in_dir.len() <= remaining_budget {
-            println!("{}/ {}", dir_display, files_in_dir.join(" "));
-            shown += files_in_dir.len();
-        } else {
-            // Partial display: show only what fits in budget
-            let partial: Vec<_> = files_in_dir
-                .iter()
-                .take(remaining_budget)
-                .cloned()
-                .collect();
-            println!("{}/ {}", dir_display, partial.join(" "));
-            shown += partial.len();
-            break;
+    let grouped = group_by_dir(files, cutoff.is_some());
+    let dirs_count = grouped.len();
+    let listing = format_groups(&grouped, opts.max_results, opts.per_dir);
+
+    let mut out = vec![
+        format!("📁 {}F {}D:", total_files, dirs_count),
+        String::new(),
+    ];
+    out.extend(listing);
+
+    if !summarized.is_empty() {
+        out.push(String::new());
+        for (dir, count) in &summarized {
+            out.push(format!("{}/ ({} summarized)", dir, count));
         }
     }
 
-    if shown < total_files {
-        println!("+{} more", total_files - shown);
-    }
-
     // Extension summary
     let mut by_ext: HashMap<String, usize> = HashMap::new();
-    for file in &files {
-        let ext = Path::new(file)
-            .extension()
-            .map(|e| e.to_string_lossy().to_string())
-            .unwrap_or_else(|| "none".to_string());
-        *by_ext.entry(ext).or_default() += 1;
+    for (_, names) in &grouped {
+        for name in names {
+            let ext = Path::new(name)
+                .extension()
+                .map(|e| e.to_string_lossy().to_string())
+                .unwrap_or_else(|| "none".to_string());
+            *by_ext.entry(ext).or_default() += 1;
+        }
     }
 
-    let mut ext_line = String::new();
     if by_ext.len() > 1 {
-        println!();
+        out.push(String::new());
         let mut exts: Vec<_> = by_ext.iter().collect();
-        exts.sort_by(|a, b| b.1.cmp(a.1));
+        exts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
         let ext_str: Vec<String> = exts
             .iter()
             .take(5)
             .map(|(e, c)| format!(".{}({})", e, c))
             .collect();
-        ext_line = format!("ext: {}", ext_str.join(" "));
-        println!("{}", ext_line);
+        out.push(format!("ext: {}", ext_str.join(" ")));
     }
 
-    let rtk_output = format!("{}F {}D + {}", total_files, dirs_count, ext_line);
-    timer.track(
-        &format!("find {} -name '{}'", path, effective_pattern),
-        "rtk find",
-        &raw_output,
-        &rtk_output,
-    );
+    let rtk_output = out.join("\n");
+    println!("{}", rtk_output);
+    timer.track(&original_cmd, rtk_cmd, &raw_output, &rtk_output);
 
     Ok(())
 }
 
+/// Group matches by directory. Alphabetical by default; with `by_recency`,
+/// newest files first and directories ordered by their newest file.
+fn group_by_dir(files: Vec<Found>, by_recency: bool) -> Vec<(String, Vec<String>)> {
+    let mut by_dir: BTreeMap<String, Vec<Found>> = BTreeMap::new();
+    for file in files {
+        by_dir.entry(parent_dir(&file.path)).or_default().push(file);
+    }
+    let mut groups: Vec<(String, Vec<Found>)> = by_dir.into_iter().collect();
+    if by_recency {
+        for (_, files) in &mut groups {
+            files.sort_by_key(|f| std::cmp::Reverse(f.modified));
+        }
+        groups.sort_by_key(|(_, files)| std::cmp::Reverse(files[0].modified));
+    }
+    groups
+        .into_iter()
+        .map(|(dir, files)| {
+            let names = files
+                .iter()
+                .map(|f| {
+                    Path::new(&f.path)
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default()
+                })
+                .collect();
+            (dir, names)
+        })
+        .collect()
+}
+
+/// One line per directory: `dir/ (count) first second … +N`, at most
+/// `per_dir` names per directory and `max_results` names overall.
+fn format_groups(
+    groups: &[(String, Vec<String>)],
+    max_results: usize,
+    per_dir: usize,
+) -> Vec<String> {
+    let total: usize = groups.iter().map(|(_, names)| names.len()).sum();
+    let mut lines = Vec::new();
+    let mut shown = 0;
+    let mut listed = 0;
+    for (dir, names) in groups {
+        if shown >= max_results {
+            break;
+        }
+        let dir_display = if display_width(dir) > 50 {
+            format!("...{}", take_last_width(dir, 47))
+        } else {
+            dir.clone()
+        };
+        let take = names.len().min(per_dir.max(1)).min(max_results - shown);
+        let mut line = format!("{}/", dir_display);
+        if names.len() > 1 {
+            line.push_str(&format!(" ({})", names.len()));
+        }
+        line.push(' ');
+        line.push_str(&names[..take].join(" "));
+        if take < names.len() {
+            line.push_str(&format!(" +{}", names.len() - take));
+        }
+        lines.push(line);
+        shown += take;
+        listed += names.len();
+    }
+
+    if listed < total {
+        lines.push(format!(
+            "+{} more in {} dirs",
+            total - listed,
+            groups.len() - lines.len()
+        ));
+    }
+    lines
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -251,39 +489,123 @@ mod tests {
         assert_eq!(effective, "*");
     }
 
+    #[test]
+    fn parent_dir_of_top_level_is_dot() {
+        assert_eq!(parent_dir("main.rs"), ".");
+        assert_eq!(parent_dir("src/main.rs"), "src");
+        assert_eq!(parent_dir("a/b/c.rs"), "a/b");
+    }
+
+    #[test]
+    fn parse_age_units() {
+        assert_eq!(parse_age("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_age("30m").unwrap(), Duration::from_secs(1800));
+        assert_eq!(parse_age("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse_age("1w").unwrap(), Duration::from_secs(604_800));
+        assert!(parse_age("2 days").is_err());
+        assert!(parse_age("h").is_err());
+    }
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn format_groups_caps_per_dir() {
+        let groups = vec![
+            ("src".to_string(), names(&["a.rs", "b.rs", "c.rs", "d.rs"])),
+            ("tests".to_string(), names(&["t.rs"])),
+        ];
+        assert_eq!(
+            format_groups(&groups, 50, 2),
+            vec!["src/ (4) a.rs b.rs +2", "tests/ t.rs"]
+        );
+    }
+
+    #[test]
+    fn format_groups_caps_total() {
+        let groups = vec![
+            ("a".to_string(), names(&["1", "2", "3"])),
+            ("b".to_string(), names(&["4", "5"])),
+            ("c".to_string(), names(&["6"])),
+        ];
+        assert_eq!(
+            format_groups(&groups, 2, 10),
+            vec!["a/ (3) 1 2 +1", "+3 more in 2 dirs"]
+        );
+    }
+
+    #[test]
+    fn format_groups_shortens_wide_dirs() {
+        let dir = format!("docs/{}/résumé", "翻訳".repeat(20));
+        let lines = format_groups(&[(dir, names(&["a.md"]))], 10, 10);
+        let shown = lines[0].strip_suffix("/ a.md").unwrap();
+        assert!(shown.starts_with("...") && shown.ends_with("翻訳/résumé"));
+        assert!(display_width(shown) <= 50);
+    }
+
+    #[test]
+    fn group_by_dir_recency_orders_newest_first() {
+        let at = |secs| Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+        let files = vec![
+            Found {
+                path: "a/old.rs".into(),
+                modified: at(10),
+            },
+            Found {
+                path: "a/new.rs".into(),
+                modified: at(30),
+            },
+            Found {
+                path: "b/newest.rs".into(),
+                modified: at(50),
+            },
+        ];
+        let groups = group_by_dir(files, true);
+        assert_eq!(groups[0], ("b".to_string(), names(&["newest.rs"])));
+        assert_eq!(groups[1], ("a".to_string(), names(&["new.rs", "old.rs"])));
+    }
+
+    #[test]
+    fn fd_flags_run() {
+        let args = names(&["-e", "rs", "--changed-within", "1w", "main", "src"]);
+        assert!(run_fd(&args, 0).is_ok());
+        assert!(run_fd(&names(&["--changed-within", "soon"]), 0).is_err());
+    }
+
     // --- integration: run on this repo ---
 
     #[test]
     fn find_rs_files_in_src() {
         // Should find .rs files without error
-        let result = run("*.rs", "src", 100, "f", 0);
+        let result = run("*.rs", "src", 100, 10, "f", None, 0);
         assert!(result.is_ok());
     }
 
     #[test]
     fn find_dot_pattern_works() {
         // "." pattern should not error (was broken before)
-        let result = run(".", "src", 10, "f", 0);
+        let result = run(".", "src", 10, 10, "f", None, 0);
         assert!(result.is_ok());
     }
 
     #[test]
     fn find_no_matches() {
-        let result = run("*.xyz_nonexistent", "src", 50, "f", 0);
+        let result = run("*.xyz_nonexistent", "src", 50, 10, "f", None, 0);
         assert!(result.is_ok());
     }
 
     #[test]
     fn find_respects_max() {
         // With max=2, should not error
-        let result = run("*.rs", "src", 2, "f", 0);
+        let result = run("*.rs", "src", 2, 10, "f", None, 0);
         assert!(result.is_ok());
     }
 
     #[test]
     fn find_gitignored_excluded() {
         // target/ is in .gitignore — files inside should not appear
-        let result = run("*", ".", 1000, "f", 0);
+        let result = run("*", ".", 1000, 10, "f", None, 0);
         assert!(result.is_ok());
         // We can't easily capture stdout in unit tests, but at least
         // verify it runs without error. The smoke tests verify content.
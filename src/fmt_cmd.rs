@@ -0,0 +1,396 @@
+//! `rtk fmt-check <formatter> --check ...`: formatter check modes print the
+//! full diff of every misformatted file. The agent only needs to know which
+//! files are off and that the formatter fixes them, so this lists files with
+//! changed-line counts and shows a single representative hunk.
+
+use crate::tracking;
+use crate::utils::{package_manager_exec, strip_ansi};
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::path::Path;
+use std::process::Command;
+
+/// Files listed before collapsing to a count.
+const MAX_FILES: usize = 20;
+/// Lines of the representative hunk.
+const MAX_HUNK_LINES: usize = 12;
+
+lazy_static! {
+    /// rustfmt: `Diff in /abs/src/main.rs at line 12:` or `Diff in /abs/src/main.rs:12:`
+    static ref RUSTFMT_HEADER: Regex =
+        Regex::new(r"^Diff in (.+?)(?: at line (\d+)|:(\d+)):?$").unwrap();
+    static ref HUNK_HEADER: Regex = Regex::new(r"^@@ -\d+(?:,\d+)? \+(\d+)").unwrap();
+    /// Files named without a diff: prettier, black
+    static ref LISTED: Regex =
+        Regex::new(r"^(?:\[warn\] |would reformat )(\S.*)$").unwrap();
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct FileDiff {
+    path: String,
+    added: usize,
+    removed: usize,
+    /// Whether the output contained a diff for this file
+    has_diff: bool,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Report {
+    files: Vec<FileDiff>,
+    /// `path:line` and lines of the first hunk
+    hunk: Option<(String, Vec<String>)>,
+}
+
+impl Report {
+    fn file(&mut self, path: &str) -> usize {
+        match self.files.iter().position(|f| f.path == path) {
+            Some(i) => i,
+            None => {
+                self.files.push(FileDiff {
+                    path: path.to_string(),
+                    ..FileDiff::default()
+                });
+                self.files.len() - 1
+            }
+        }
+    }
+}
+
+pub fn run(args: &[String], verbose: u8) -> Result<()> {
+    let Some((program, rest)) = args.split_first() else {
+        anyhow::bail!(
+            "Usage: rtk fmt-check <cargo fmt|rustfmt|prettier|black|ruff format|gofmt> ..."
+        );
+    };
+    let timer = tracking::TimedExecution::start();
+    let run_args = diff_args(program, rest);
+
+    let mut cmd = if program == "prettier" {
+        package_manager_exec("prettier")
+    } else {
+        Command::new(program)
+    };
+    cmd.args(&run_args);
+
+    if verbose > 0 {
+        eprintln!("Running: {} {}", program, run_args.join(" "));
+    }
+
+    let output = cmd
+        .output()
+        .with_context(|| format!("Failed to run {}", program))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
+
+    let report = parse(&strip_ansi(&raw), &cwd_prefix());
+    let filtered = if report.files.is_empty() && !output.status.success() {
+        // Not a formatting complaint (bad flag, syntax error): show it
+        let mut lines: Vec<&str> = raw.lines().filter(|l| !l.trim().is_empty()).collect();
+        lines.truncate(20);
+        format!(
+            "❌ {} failed (exit code: {:?})\n{}",
+            program,
+            output.status.code(),
+            lines.join("\n")
+        )
+    } else {
+        format_report(&report, &fix_command(program, rest))
+    };
+    println!("{}", filtered);
+
+    let original = args.join(" ");
+    timer.with_exit_code(output.status).track(
+        &original,
+        &format!("rtk fmt-check {}", original),
+        &raw,
+        &filtered,
+    );
+    Ok(())
+}
+
+/// Arguments that make the formatter print diffs where it can.
+fn diff_args(program: &str, args: &[String]) -> Vec<String> {
+    let has = |flag: &str| args.iter().any(|a| a == flag);
+    let mut out = args.to_vec();
+    match program {
+        "black" if !has("--diff") => out.push("--diff".to_string()),
+        // `ruff format --diff` implies the check
+        "ruff" if !has("--diff") => {
+            out.retain(|a| a != "--check");
+            out.push("--diff".to_string());
+        }
+        "gofmt" => {
+            out.retain(|a| a != "-l");
+            if !out.iter().any(|a| a == "-d") {
+                out.insert(0, "-d".to_string());
+            }
+        }
+        _ => {}
+    }
+    out
+}
+
+/// The command that applies the formatting.
+fn fix_command(program: &str, args: &[String]) -> String {
+    let mut out = vec![program.to_string()];
+    out.extend(
+        args.iter()
+            .filter(|a| {
+                !matches!(
+                    a.as_str(),
+                    "--check" | "-c" | "--diff" | "-d" | "-l" | "--list-different"
+                )
+            })
+            .cloned(),
+    );
+    if out.last().is_some_and(|a| a == "--") {
+        out.pop();
+    }
+    match program {
+        "prettier" => out.insert(1, "--write".to_string()),
+        "gofmt" => out.insert(1, "-w".to_string()),
+        _ => {}
+    }
+    out.join(" ")
+}
+
+fn cwd_prefix() -> String {
+    std::env::current_dir()
+        .map(|d| format!("{}/", d.display()))
+        .unwrap_or_default()
+}
+
+fn parse(output: &str, cwd: &str) -> Report {
+    let mut report = Report::default();
+    let mut current: Option<usize> = None;
+    // Collecting the first hunk of the output
+    let mut in_first_hunk = false;
+
+    let relative = |p: &str| p.strip_prefix(cwd).unwrap_or(p).to_string();
+
+    for line in output.lines() {
+        let new_hunk = if let Some(caps) = RUSTFMT_HEADER.captures(line) {
+            let idx = report.file(&relative(&caps[1]));
+            current = Some(idx);
+            caps.get(2).or(caps.get(3)).map(|m| m.as_str().to_string())
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            let path = path.split('\t').next().unwrap_or(path).trim();
+            let path = path.strip_prefix("b/").unwrap_or(path);
+            current = Some(report.file(&relative(path)));
+            continue;
+        } else if line.starts_with("--- ") || line.starts_with("diff ") {
+            continue;
+        } else {
+            HUNK_HEADER.captures(line).map(|caps| caps[1].to_string())
+        };
+        if let Some(start) = new_hunk {
+            in_first_hunk = report.hunk.is_none();
+            if let (true, Some(idx)) = (in_first_hunk, current) {
+                let location = format!("{}:{}", report.files[idx].path, start);
+                report.hunk = Some((location, Vec::new()));
+            }
+            continue;
+        }
+
+        if let Some(idx) = current {
+            let counted = match line.chars().next() {
+                Some('+') => {
+                    report.files[idx].added += 1;
+                    true
+                }
+                Some('-') => {
+                    report.files[idx].removed += 1;
+                    true
+                }
+                Some(' ') | Some('\\') => true,
+                _ => false,
+            };
+            if counted {
+                report.files[idx].has_diff = true;
+                if in_first_hunk {
+                    if let Some((_, lines)) = &mut report.hunk {
+                        lines.push(line.to_string());
+                    }
+                }
+                continue;
+            }
+            current = None;
+            in_first_hunk = false;
+        }
+
+        if let Some(caps) = LISTED.captures(line) {
+            let path = caps[1].trim();
+            if !path.contains(' ') {
+                report.file(&relative(path));
+            }
+        } else {
+            // `prettier --list-different`, `gofmt -l`: bare paths
+            let path = line.trim();
+            if !path.is_empty() && !path.contains(' ') && Path::new(path).is_file() {
+                report.file(&relative(path));
+            }
+        }
+    }
+    report
+}
+
+fn format_report(report: &Report, fix: &str) -> String {
+    if report.files.is_empty() {
+        return "✓ All files formatted".to_string();
+    }
+    let n = report.files.len();
+    let mut out = vec![format!(
+        "✗ {} file{} need{} formatting — run: {}",
+        n,
+        if n == 1 { "" } else { "s" },
+        if n == 1 { "s" } else { "" },
+        fix
+    )];
+    for file in report.files.iter().take(MAX_FILES) {
+        if file.has_diff {
+            out.push(format!(
+                "  {}  +{} -{}",
+                file.path, file.added, file.removed
+            ));
+        } else {
+            out.push(format!("  {}", file.path));
+        }
+    }
+    if n > MAX_FILES {
+        out.push(format!("  … +{} more files", n - MAX_FILES));
+    }
+    if let Some((location, lines)) = &report.hunk {
+        out.push(format!("e.g. {}:", location));
+        for line in lines.iter().take(MAX_HUNK_LINES) {
+            out.push(format!("  {}", line));
+        }
+        if lines.len() > MAX_HUNK_LINES {
+            out.push(format!("  … {} more lines", lines.len() - MAX_HUNK_LINES));
+        }
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_rustfmt() {
+        let output = "Diff in /repo/src/main.rs at line 3:\n \
+                      fn main() {\n\
+                      -    foo(a,b);\n\
+                      +    foo(a, b);\n \
+                      }\n\
+                      Diff in /repo/src/main.rs:20:\n\
+                      -use b;\n\
+                      -use a;\n\
+                      +use a;\n\
+                      +use b;\n\
+                      Diff in /repo/src/lib.rs:1:\n\
+                      -pub mod x ;\n\
+                      +pub mod x;\n";
+        let report = parse(output, "/repo/");
+        let summary: Vec<_> = report
+            .files
+            .iter()
+            .map(|f| (f.path.as_str(), f.added, f.removed))
+            .collect();
+        assert_eq!(summary, vec![("src/main.rs", 3, 3), ("src/lib.rs", 1, 1)]);
+        let (location, lines) = report.hunk.unwrap();
+        assert_eq!(location, "src/main.rs:3");
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn test_parse_black_diff() {
+        let output = "--- app.py\t2024-05-01 10:00:00.000000+00:00\n\
+                      +++ app.py\t2024-05-01 10:00:01.000000+00:00\n\
+                      @@ -1,3 +1,3 @@\n\
+                      -x = {'a':1}\n\
+                      +x = {\"a\": 1}\n\
+                      \x20y = 2\n\
+                      would reformat app.py\n\
+                      would reformat lib/util.py\n\
+                      \n\
+                      Oh no! 💥 💔 💥\n\
+                      2 files would be reformatted, 7 files would be left unchanged.\n";
+        let report = parse(output, "/repo/");
+        assert_eq!(report.files.len(), 2);
+        assert_eq!(report.files[0].added, 1);
+        assert!(report.files[0].has_diff);
+        assert_eq!(report.files[1].path, "lib/util.py");
+        assert!(!report.files[1].has_diff);
+        assert_eq!(report.hunk.unwrap().0, "app.py:1");
+    }
+
+    #[test]
+    fn test_parse_prettier() {
+        let output = "Checking formatting...\n\
+                      [warn] src/app.ts\n\
+                      [warn] src/view.tsx\n\
+                      [warn] Code style issues found in 2 files. Run Prettier with --write to fix.\n";
+        let report = parse(output, "/repo/");
+        let paths: Vec<_> = report.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/app.ts", "src/view.tsx"]);
+        assert!(report.hunk.is_none());
+    }
+
+    #[test]
+    fn test_format_report() {
+        let report = parse(
+            "Diff in /repo/src/a.rs:7:\n-let x=1;\n+let x = 1;\n",
+            "/repo/",
+        );
+        assert_eq!(
+            format_report(&report, "cargo fmt"),
+            "✗ 1 file needs formatting — run: cargo fmt\n  \
+             src/a.rs  +1 -1\n\
+             e.g. src/a.rs:7:\n  \
+             -let x=1;\n  \
+             +let x = 1;"
+        );
+        assert_eq!(
+            format_report(&Report::default(), "cargo fmt"),
+            "✓ All files formatted"
+        );
+    }
+
+    #[test]
+    fn test_fix_command() {
+        assert_eq!(
+            fix_command("cargo", &args("fmt --all -- --check")),
+            "cargo fmt --all"
+        );
+        assert_eq!(
+            fix_command("prettier", &args("--check src")),
+            "prettier --write src"
+        );
+        assert_eq!(fix_command("black", &args("--check .")), "black .");
+        assert_eq!(fix_command("gofmt", &args("-l .")), "gofmt -w .");
+    }
+
+    #[test]
+    fn test_diff_args() {
+        assert_eq!(
+            diff_args("black", &args("--check .")),
+            args("--check . --diff")
+        );
+        assert_eq!(
+            diff_args("ruff", &args("format --check")),
+            args("format --diff")
+        );
+        assert_eq!(diff_args("gofmt", &args("-l .")), args("-d ."));
+        assert_eq!(
+            diff_args("cargo", &args("fmt --check")),
+            args("fmt --check")
+        );
+    }
+}
@@ -1,27 +1,85 @@
-use crate::display_helpers::{format_duration, print_period_table};
-use crate::tracking::{DayStats, MonthStats, Tracker, WeekStats};
-use crate::utils::format_tokens;
+use crate::display_helpers::{format_duration, format_period_markdown, print_period_table};
+use crate::local_time;
+use crate::redact::redact_command;
+use crate::tracking::{
+    self, Breakdown, DayStats, FailureStats, GroupStats, MonthStats, SampledRecord,
+    TimePercentiles, Tracker, WeekStats,
+};
+use crate::utils::{format_tokens, pad, truncate};
 use anyhow::{Context, Result};
 use serde::Serialize;
+use std::io::Write;
+
+/// `rtk gain` flags (without a subcommand).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GainOptions<'a> {
+    /// ASCII graph of daily savings
+    pub graph: bool,
+    /// Recent command history
+    pub history: bool,
+    /// Monthly quota savings estimate
+    pub quota: bool,
+    /// Subscription tier for the quota estimate: pro, 5x, 20x
+    pub tier: &'a str,
+    pub daily: bool,
+    pub weekly: bool,
+    pub monthly: bool,
+    /// Daily, weekly and monthly breakdowns
+    pub all: bool,
+    /// text, json, csv or markdown
+    pub format: &'a str,
+    /// Live dashboard instead of a report
+    pub watch: bool,
+    /// Failure rates instead of savings
+    pub failures: bool,
+    pub by_agent: bool,
+    pub by_model: bool,
+    /// Anonymize paths, URLs, repo names and username
+    pub redact: bool,
+}
+
+pub fn run(options: GainOptions, _verbose: u8) -> Result<()> {
+    let GainOptions {
+        graph,
+        history,
+        quota,
+        tier,
+        daily,
+        weekly,
+        monthly,
+        all,
+        format,
+        watch,
+        failures,
+        by_agent,
+        by_model,
+        redact,
+    } = options;
+    if watch {
+        return crate::gain_watch::run();
+    }
 
-pub fn run(
-    graph: bool,
-    history: bool,
-    quota: bool,
-    tier: &str,
-    daily: bool,
-    weekly: bool,
-    monthly: bool,
-    all: bool,
-    format: &str,
-    _verbose: u8,
-) -> Result<()> {
     let tracker = Tracker::new().context("Failed to initialize tracking database")?;
 
+    if failures {
+        return print_failures(&tracker, format, redact);
+    }
+    if by_agent || by_model {
+        let mut breakdowns = Vec::new();
+        if by_agent {
+            breakdowns.push(Breakdown::Agent);
+        }
+        if by_model {
+            breakdowns.push(Breakdown::Model);
+        }
+        return print_breakdowns(&tracker, &breakdowns, format);
+    }
+
     // Handle export formats
     match format {
         "json" => return export_json(&tracker, daily, weekly, monthly, all),
         "csv" => return export_csv(&tracker, daily, weekly, monthly, all),
+        "markdown" | "md" => return export_markdown(&tracker, daily, weekly, monthly, all, redact),
         _ => {} // Continue with text format
     }
 
@@ -54,29 +112,30 @@ pub fn run(
             format_duration(summary.total_time_ms),
             format_duration(summary.avg_time_ms)
         );
+        if summary.cache_hits > 0 {
+            println!("Cache hits:        {}", summary.cache_hits);
+        }
         println!();
 
         if !summary.by_command.is_empty() {
-            let by_command = normalize_by_command(summary.by_command);
+            let by_command = display_by_command(summary.by_command, redact);
             println!("By Command:");
             println!("────────────────────────────────────────");
             println!(
-                "{:<20} {:>6} {:>10} {:>8} {:>8}",
-                "Command", "Count", "Saved", "Avg%", "Time"
+                "{:<20} {:>6} {:>10} {:>8} {:>8} {:>8} {:>8} {:>8}",
+                "Command", "Count", "Saved", "Avg%", "Time", "P50", "P95", "Max"
             );
-            for (cmd, count, saved, pct, avg_time) in &by_command {
-                let cmd_short = if cmd.len() > 18 {
-                    format!("{}...", &cmd[..15])
-                } else {
-                    cmd.clone()
-                };
+            for (cmd, count, saved, pct, avg_time, timing) in &by_command {
                 println!(
-                    "{:<20} {:>6} {:>10} {:>7.1}% {:>8}",
-                    cmd_short,
+                    "{} {:>6} {:>10} {:>7.1}% {:>8} {:>8} {:>8} {:>8}",
+                    pad(&truncate(cmd, 18), 20),
                     count,
                     format_tokens(*saved),
                     pct,
-                    format_duration(*avg_time)
+                    format_duration(*avg_time),
+                    format_duration(timing.p50_ms),
+                    format_duration(timing.p95_ms),
+                    format_duration(timing.max_ms)
                 );
             }
             println!();
@@ -95,17 +154,12 @@ pub fn run(
                 println!("Recent Commands:");
                 println!("────────────────────────────────────────");
                 for rec in recent {
-                    let time = rec.timestamp.format("%m-%d %H:%M");
-                    let cmd_name = normalize_cmd_name(&rec.rtk_cmd);
-                    let cmd_short = if cmd_name.len() > 25 {
-                        format!("{}...", &cmd_name[..22])
-                    } else {
-                        cmd_name
-                    };
+                    let time = local_time::format(rec.timestamp, "%m-%d %H:%M");
+                    let cmd_name = display_cmd_name(&rec.rtk_cmd, redact);
                     println!(
-                        "{} {:<25} -{:.0}% ({})",
+                        "{} {} -{:.0}% ({})",
                         time,
-                        cmd_short,
+                        pad(&truncate(&cmd_name, 25), 25),
                         rec.savings_pct,
                         format_tokens(rec.saved_tokens)
                     );
@@ -159,6 +213,395 @@ pub fn run(
     Ok(())
 }
 
+/// Number of commands listed in the "most frequently failing" table.
+const TOP_FAILURES: usize = 10;
+
+/// Failure rate per command (`rtk gain --failures`).
+fn print_failures(tracker: &Tracker, format: &str, redact: bool) -> Result<()> {
+    let stats = display_failures(
+        tracker
+            .get_failure_stats()
+            .context("Failed to load failure statistics from database")?,
+        redact,
+    );
+
+    if format == "json" {
+        println_verbatim!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    if stats.is_empty() {
+        println!("No exit codes recorded yet.");
+        return Ok(());
+    }
+
+    let runs: usize = stats.iter().map(|f| f.runs).sum();
+    let failed: usize = stats.iter().map(|f| f.failures).sum();
+
+    println!("📊 RTK Command Failures");
+    println!("════════════════════════════════════════");
+    println!();
+    println!(
+        "Overall failure rate: {:.1}% ({} of {} runs)",
+        tracking::failure_pct(failed, runs),
+        failed,
+        runs
+    );
+    println!();
+
+    let failing: Vec<&FailureStats> = stats
+        .iter()
+        .filter(|f| f.failures > 0)
+        .take(TOP_FAILURES)
+        .collect();
+    if failing.is_empty() {
+        println!("No failing commands.");
+        return Ok(());
+    }
+
+    println!("Most Frequent Failures:");
+    println!("────────────────────────────────────────");
+    println!(
+        "{:<24} {:>6} {:>8} {:>7}",
+        "Command", "Runs", "Failed", "Rate"
+    );
+    for f in failing {
+        println!(
+            "{} {:>6} {:>8} {:>6.1}%",
+            pad(&truncate(&f.command, 22), 24),
+            f.runs,
+            f.failures,
+            f.failure_pct
+        );
+    }
+    Ok(())
+}
+
+/// Savings per agent and/or model (`rtk gain --by-agent`, `--by-model`).
+fn print_breakdowns(tracker: &Tracker, breakdowns: &[Breakdown], format: &str) -> Result<()> {
+    let mut tables = Vec::new();
+    for &by in breakdowns {
+        let stats = tracker
+            .get_breakdown(by)
+            .context("Failed to load savings breakdown from database")?;
+        tables.push((by, stats));
+    }
+
+    if format == "json" {
+        let mut json = serde_json::Map::new();
+        for (by, stats) in &tables {
+            let key = match by {
+                Breakdown::Agent => "by_agent",
+                Breakdown::Model => "by_model",
+            };
+            json.insert(key.to_string(), serde_json::to_value(stats)?);
+        }
+        println_verbatim!("{}", serde_json::to_string_pretty(&json)?);
+        return Ok(());
+    }
+
+    for (i, (by, stats)) in tables.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        print!("{}", format_breakdown(*by, stats));
+    }
+    Ok(())
+}
+
+fn format_breakdown(by: Breakdown, stats: &[GroupStats]) -> String {
+    // Agents are recorded since schema v5; models only when the agent exposes one
+    let (label, unknown) = match by {
+        Breakdown::Agent => ("Agent", "(unrecorded)"),
+        Breakdown::Model => ("Model", "(unknown)"),
+    };
+    let mut out = String::new();
+    out.push_str(&format!("📊 RTK Savings by {}\n", label));
+    out.push_str("════════════════════════════════════════\n");
+    if stats.is_empty() {
+        out.push_str("No tracking data yet.\n");
+        return out;
+    }
+    out.push_str(&format!(
+        "{:<24} {:>6} {:>10} {:>7}\n",
+        label, "Count", "Saved", "Avg%"
+    ));
+    for g in stats {
+        let name = g.name.as_deref().unwrap_or(unknown);
+        out.push_str(&format!(
+            "{} {:>6} {:>10} {:>6.1}%\n",
+            pad(&truncate(name, 22), 24),
+            g.commands,
+            format_tokens(g.saved_tokens),
+            g.savings_pct
+        ));
+    }
+    out
+}
+
+/// Normalize (and optionally redact) failure rows, merging names that collapse
+/// together, ordered by failure count.
+fn display_failures(stats: Vec<FailureStats>, redact: bool) -> Vec<FailureStats> {
+    let mut merged: Vec<FailureStats> = Vec::new();
+    for f in stats {
+        let name = display_cmd_name(&f.command, redact);
+        match merged.iter_mut().find(|m| m.command == name) {
+            Some(m) => {
+                m.runs += f.runs;
+                m.failures += f.failures;
+            }
+            None => merged.push(FailureStats { command: name, ..f }),
+        }
+    }
+    for m in &mut merged {
+        m.failure_pct = tracking::failure_pct(m.failures, m.runs);
+    }
+    merged.sort_by(|a, b| b.failures.cmp(&a.failures).then(b.runs.cmp(&a.runs)));
+    merged
+}
+
+/// Rows fetched per page when streaming raw records.
+const EXPORT_PAGE_SIZE: usize = 1000;
+
+/// Stream raw tracking records (`rtk gain export --records`).
+///
+/// Pages through the database by id so memory stays flat on large DBs.
+/// `after` is an exclusive id cursor; `limit` caps the number of rows written.
+/// When the limit is hit, the cursor for the next page is printed on stderr.
+pub fn run_export(
+    records: bool,
+    format: &str,
+    after: i64,
+    limit: Option<usize>,
+    redact: bool,
+    verbose: u8,
+) -> Result<()> {
+    if !records {
+        anyhow::bail!("Nothing to export: pass --records to stream raw tracking rows");
+    }
+    if format != "jsonl" {
+        anyhow::bail!("Unsupported export format '{}' (supported: jsonl)", format);
+    }
+
+    let tracker = Tracker::new().context("Failed to initialize tracking database")?;
+    let stdout = std::io::stdout();
+    let mut out = std::io::BufWriter::new(stdout.lock());
+
+    let mut cursor = after;
+    let mut written = 0usize;
+    loop {
+        let remaining = limit.map(|l| l - written).unwrap_or(usize::MAX);
+        if remaining == 0 {
+            break;
+        }
+        let page = tracker.get_records_after(cursor, EXPORT_PAGE_SIZE.min(remaining))?;
+        let Some(last) = page.last() else { break };
+        cursor = last.id;
+
+        for record in page.iter() {
+            let line = if redact {
+                let mut record = record.clone();
+                record.original_cmd = redact_command(&record.original_cmd);
+                record.rtk_cmd = redact_command(&record.rtk_cmd);
+                serde_json::to_string(&record)?
+            } else {
+                serde_json::to_string(record)?
+            };
+            if let Err(e) = writeln!(out, "{}", line) {
+                // Downstream closed the pipe (e.g. `| head`): stop quietly
+                if e.kind() == std::io::ErrorKind::BrokenPipe {
+                    return Ok(());
+                }
+                return Err(e.into());
+            }
+        }
+        written += page.len();
+    }
+
+    if let Err(e) = out.flush() {
+        if e.kind() != std::io::ErrorKind::BrokenPipe {
+            return Err(e.into());
+        }
+    }
+
+    if limit.is_some_and(|l| written >= l) && tracker.latest_id()? > cursor {
+        eprintln!("next cursor: --after {}", cursor);
+    } else if verbose > 0 {
+        eprintln!("exported {} records", written);
+    }
+
+    Ok(())
+}
+
+/// Report or apply tracking database schema migrations (`rtk gain migrate`).
+///
+/// With `check`, nothing is modified and the process exits 1 when migrations
+/// are pending (useful in CI or before downgrading rtk).
+pub fn run_migrate(check: bool, verbose: u8) -> Result<()> {
+    let status = tracking::migration_status().context("Failed to read tracking schema")?;
+
+    if status.current > status.latest {
+        anyhow::bail!(
+            "Tracking DB schema v{} is newer than this rtk supports (v{}). Upgrade rtk.",
+            status.current,
+            status.latest
+        );
+    }
+
+    if check {
+        println!("schema: v{} (latest v{})", status.current, status.latest);
+        if status.pending.is_empty() {
+            println!("ok up to date");
+            return Ok(());
+        }
+        println!("{} pending:", status.pending.len());
+        for m in &status.pending {
+            println!("  v{} {}", m.version, m.description);
+        }
+        std::process::exit(1);
+    }
+
+    let applied = tracking::migrate().context("Failed to migrate tracking database")?;
+    if applied.is_empty() {
+        println!("ok schema v{} up to date", status.latest);
+    } else {
+        if verbose > 0 {
+            for m in &status.pending {
+                eprintln!("applied v{} {}", m.version, m.description);
+            }
+        }
+        println!(
+            "ok migrated v{} → v{} ({} applied)",
+            status.current,
+            status.latest,
+            applied.len()
+        );
+    }
+    Ok(())
+}
+
+/// `rtk gain compact`: import spooled records now instead of on the next read.
+pub fn run_compact() -> Result<()> {
+    let imported = tracking::compact().context("Failed to import tracking spool")?;
+    match imported {
+        0 => println!("ok spool empty"),
+        n => println!(
+            "ok imported {} spooled record{}",
+            n,
+            if n == 1 { "" } else { "s" }
+        ),
+    }
+    Ok(())
+}
+
+/// Sampled records listed by `rtk gain inspect` without an id.
+const RECENT_SAMPLES: usize = 20;
+
+/// Show the raw vs filtered output sample of a record (`rtk gain inspect`).
+///
+/// Without an id, lists the most recent sampled records. Samples are only
+/// stored with `tracking.samples = true`.
+pub fn run_inspect(id: Option<i64>, format: &str, redact: bool) -> Result<()> {
+    let tracker = Tracker::new().context("Failed to initialize tracking database")?;
+
+    let Some(id) = id else {
+        let recent = tracker.get_recent_samples(RECENT_SAMPLES)?;
+        if format == "json" {
+            println_verbatim!("{}", serde_json::to_string_pretty(&recent)?);
+            return Ok(());
+        }
+        if recent.is_empty() {
+            println!("No samples recorded yet.");
+            println!("Enable them with: rtk config set tracking.samples true");
+            return Ok(());
+        }
+        for sampled in &recent {
+            let rec = &sampled.record;
+            println!(
+                "#{:<6} {} {:<28} {} → {} bytes (-{:.0}%)",
+                rec.id,
+                rec.timestamp
+                    .get(5..16)
+                    .unwrap_or(&rec.timestamp)
+                    .replace('T', " "),
+                display_cmd_name(&rec.rtk_cmd, redact),
+                sampled.sample.raw_bytes,
+                sampled.sample.output_bytes,
+                rec.savings_pct
+            );
+        }
+        return Ok(());
+    };
+
+    let mut sampled = tracker.get_sample(id)?.with_context(|| {
+        format!(
+            "No sample for record #{} (tracked without tracking.samples?)",
+            id
+        )
+    })?;
+    if redact {
+        sampled.record.original_cmd = redact_command(&sampled.record.original_cmd);
+        sampled.record.rtk_cmd = redact_command(&sampled.record.rtk_cmd);
+    }
+    if format == "json" {
+        println_verbatim!("{}", serde_json::to_string_pretty(&sampled)?);
+    } else {
+        let (header, heads) = format_sample(&sampled);
+        println!("{}", header);
+        for (title, head) in heads {
+            println!();
+            println!("{}", title);
+            println_verbatim!("{}", head);
+        }
+    }
+    Ok(())
+}
+
+/// The record's details, and a title and text for each stored head (tool
+/// output, to be printed verbatim).
+fn format_sample(sampled: &SampledRecord) -> (String, Vec<(String, &str)>) {
+    let rec = &sampled.record;
+    let sample = &sampled.sample;
+    let mut out = vec![
+        format!("#{} {}", rec.id, rec.rtk_cmd),
+        format!("  original: {}", rec.original_cmd),
+        format!("  time:     {}", rec.timestamp),
+        format!(
+            "  tokens:   {} → {} ({:.1}% saved)",
+            format_tokens(rec.input_tokens),
+            format_tokens(rec.output_tokens),
+            rec.savings_pct
+        ),
+        format!(
+            "  raw:      {} bytes (hash {})",
+            sample.raw_bytes, sample.raw_hash
+        ),
+        format!(
+            "  output:   {} bytes (hash {})",
+            sample.output_bytes, sample.output_hash
+        ),
+    ];
+    if let Some(code) = rec.exit_code {
+        out.insert(3, format!("  exit:     {}", code));
+    }
+    let mut heads = Vec::new();
+    for (label, head, bytes) in [
+        ("raw", &sample.raw_head, sample.raw_bytes),
+        ("output", &sample.output_head, sample.output_bytes),
+    ] {
+        if head.is_empty() {
+            continue;
+        }
+        let title = if head.len() < bytes {
+            format!("── {} (first {} of {} bytes) ──", label, head.len(), bytes)
+        } else {
+            format!("── {} ──", label)
+        };
+        heads.push((title, head.trim_end()));
+    }
+    (out.join("\n"), heads)
+}
+
 fn print_ascii_graph(data: &[(String, usize)]) {
     if data.is_empty() {
         return;
@@ -227,6 +670,7 @@ struct ExportSummary {
     avg_savings_pct: f64,
     total_time_ms: u64,
     avg_time_ms: u64,
+    cache_hits: usize,
 }
 
 fn export_json(
@@ -249,6 +693,7 @@ fn export_json(
             avg_savings_pct: summary.avg_savings_pct,
             total_time_ms: summary.total_time_ms,
             avg_time_ms: summary.avg_time_ms,
+            cache_hits: summary.cache_hits,
         },
         daily: if all || daily {
             Some(tracker.get_all_days()?)
@@ -268,7 +713,7 @@ fn export_json(
     };
 
     let json = serde_json::to_string_pretty(&export)?;
-    println!("{}", json);
+    println_verbatim!("{}", json);
 
     Ok(())
 }
@@ -278,7 +723,7 @@ fn export_json(
 /// Historical entries may use internal names that don't match what users type.
 /// The hook now preserves original command names (cat, rg, eslint), so gain
 /// output should reflect those names.
-fn normalize_cmd_name(cmd: &str) -> String {
+pub(crate) fn normalize_cmd_name(cmd: &str) -> String {
     // Exact prefix replacements for renamed commands
     if cmd == "rtk run-err" {
         return "rtk err".to_string();
@@ -293,48 +738,179 @@ fn normalize_cmd_name(cmd: &str) -> String {
     cmd.to_string()
 }
 
+/// Canonical command name for display, anonymized when `redact` is set.
+pub(crate) fn display_cmd_name(cmd: &str, redact: bool) -> String {
+    let name = normalize_cmd_name(cmd);
+    if redact {
+        redact_command(&name)
+    } else {
+        name
+    }
+}
+
+/// Normalize by_command entries for display, merging rows that collapse to
+/// the same name once redacted.
+fn display_by_command(
+    entries: Vec<(String, usize, usize, f64, u64, TimePercentiles)>,
+    redact: bool,
+) -> Vec<(String, usize, usize, f64, u64, TimePercentiles)> {
+    let entries = if redact {
+        entries
+            .into_iter()
+            .map(|(cmd, count, saved, pct, time, timing)| {
+                (redact_command(&cmd), count, saved, pct, time, timing)
+            })
+            .collect()
+    } else {
+        entries
+    };
+    normalize_by_command(entries)
+}
+
 /// Re-aggregate by_command entries after normalization.
 ///
 /// Multiple stored names may map to the same canonical name (e.g. "rtk read"
 /// and "rtk cat" both map to "rtk cat"). This merges their stats using
-/// weighted averages.
-fn normalize_by_command(
-    entries: Vec<(String, usize, usize, f64, u64)>,
-) -> Vec<(String, usize, usize, f64, u64)> {
+/// weighted averages. Merged p50/p95 are count-weighted approximations;
+/// max stays exact.
+pub(crate) fn normalize_by_command(
+    entries: Vec<(String, usize, usize, f64, u64, TimePercentiles)>,
+) -> Vec<(String, usize, usize, f64, u64, TimePercentiles)> {
     use std::collections::HashMap;
 
     // Preserve insertion order via a separate vec of keys
     let mut order: Vec<String> = Vec::new();
-    // Accumulate: (total_count, total_saved, weighted_pct_sum, weighted_time_sum)
-    let mut merged: HashMap<String, (usize, usize, f64, f64)> = HashMap::new();
+    // Accumulate: (total_count, total_saved, weighted_pct_sum, weighted_time_sum,
+    //              weighted_p50_sum, weighted_p95_sum, max)
+    type Acc = (usize, usize, f64, f64, f64, f64, u64);
+    let mut merged: HashMap<String, Acc> = HashMap::new();
 
-    for (cmd, count, saved, pct, avg_time) in entries {
+    for (cmd, count, saved, pct, avg_time, timing) in entries {
         let canonical = normalize_cmd_name(&cmd);
         let entry = merged.entry(canonical.clone()).or_insert_with(|| {
             order.push(canonical);
-            (0, 0, 0.0, 0.0)
+            (0, 0, 0.0, 0.0, 0.0, 0.0, 0)
         });
         entry.0 += count;
         entry.1 += saved;
         entry.2 += pct * count as f64;
         entry.3 += avg_time as f64 * count as f64;
+        entry.4 += timing.p50_ms as f64 * count as f64;
+        entry.5 += timing.p95_ms as f64 * count as f64;
+        entry.6 = entry.6.max(timing.max_ms);
     }
 
     order
         .into_iter()
         .map(|name| {
-            let (count, saved, wpct, wtime) = merged.remove(&name).unwrap();
-            let avg_pct = if count > 0 { wpct / count as f64 } else { 0.0 };
-            let avg_time = if count > 0 {
-                (wtime / count as f64) as u64
-            } else {
-                0
+            let (count, saved, wpct, wtime, wp50, wp95, max_ms) = merged.remove(&name).unwrap();
+            let weighted = |sum: f64| {
+                if count > 0 {
+                    sum / count as f64
+                } else {
+                    0.0
+                }
             };
-            (name, count, saved, avg_pct, avg_time)
+            let timing = TimePercentiles {
+                p50_ms: weighted(wp50) as u64,
+                p95_ms: weighted(wp95) as u64,
+                max_ms,
+            };
+            (
+                name,
+                count,
+                saved,
+                weighted(wpct),
+                weighted(wtime) as u64,
+                timing,
+            )
         })
         .collect()
 }
 
+fn export_markdown(
+    tracker: &Tracker,
+    daily: bool,
+    weekly: bool,
+    monthly: bool,
+    all: bool,
+    redact: bool,
+) -> Result<()> {
+    let summary = tracker
+        .get_summary()
+        .context("Failed to load token savings summary from database")?;
+
+    println_verbatim!("## RTK Token Savings\n");
+    println_verbatim!("| Metric | Value |");
+    println_verbatim!("|---|---:|");
+    println_verbatim!("| Total commands | {} |", summary.total_commands);
+    println_verbatim!("| Input tokens | {} |", format_tokens(summary.total_input));
+    println_verbatim!(
+        "| Output tokens | {} |",
+        format_tokens(summary.total_output)
+    );
+    println_verbatim!(
+        "| Tokens saved | {} ({:.1}%) |",
+        format_tokens(summary.total_saved),
+        summary.avg_savings_pct
+    );
+    println_verbatim!(
+        "| Total exec time | {} (avg {}) |",
+        format_duration(summary.total_time_ms),
+        format_duration(summary.avg_time_ms)
+    );
+    if summary.cache_hits > 0 {
+        println_verbatim!("| Cache hits | {} |", summary.cache_hits);
+    }
+    println_verbatim!();
+
+    if !summary.by_command.is_empty() {
+        println_verbatim!(
+            "{}",
+            format_by_command_markdown(&display_by_command(summary.by_command, redact))
+        );
+    }
+
+    if all || daily {
+        println_verbatim!("{}", format_period_markdown(&tracker.get_all_days()?));
+    }
+
+    if all || weekly {
+        println_verbatim!("{}", format_period_markdown(&tracker.get_by_week()?));
+    }
+
+    if all || monthly {
+        println_verbatim!("{}", format_period_markdown(&tracker.get_by_month()?));
+    }
+
+    Ok(())
+}
+
+/// Render the per-command breakdown as a Markdown table.
+///
+/// Pipes in command strings are escaped so they don't break table columns.
+fn format_by_command_markdown(
+    entries: &[(String, usize, usize, f64, u64, TimePercentiles)],
+) -> String {
+    let mut out = String::from("### By Command\n\n");
+    out.push_str("| Command | Count | Saved | Avg% | Time | P50 | P95 | Max |\n");
+    out.push_str("|---|---:|---:|---:|---:|---:|---:|---:|\n");
+    for (cmd, count, saved, pct, avg_time, timing) in entries {
+        out.push_str(&format!(
+            "| `{}` | {} | {} | {:.1}% | {} | {} | {} | {} |\n",
+            cmd.replace('|', "\\|"),
+            count,
+            format_tokens(*saved),
+            pct,
+            format_duration(*avg_time),
+            format_duration(timing.p50_ms),
+            format_duration(timing.p95_ms),
+            format_duration(timing.max_ms)
+        ));
+    }
+    out
+}
+
 fn export_csv(
     tracker: &Tracker,
     daily: bool,
@@ -344,11 +920,11 @@ fn export_csv(
 ) -> Result<()> {
     if all || daily {
         let days = tracker.get_all_days()?;
-        println!("# Daily Data");
-        println!("date,commands,input_tokens,output_tokens,saved_tokens,savings_pct,total_time_ms,avg_time_ms");
+        println_verbatim!("# Daily Data");
+        println_verbatim!("date,commands,input_tokens,output_tokens,saved_tokens,savings_pct,total_time_ms,avg_time_ms,p50_time_ms,p95_time_ms,max_time_ms");
         for day in days {
-            println!(
-                "{},{},{},{},{},{:.2},{},{}",
+            println_verbatim!(
+                "{},{},{},{},{},{:.2},{},{},{},{},{}",
                 day.date,
                 day.commands,
                 day.input_tokens,
@@ -356,21 +932,24 @@ fn export_csv(
                 day.saved_tokens,
                 day.savings_pct,
                 day.total_time_ms,
-                day.avg_time_ms
+                day.avg_time_ms,
+                day.p50_time_ms,
+                day.p95_time_ms,
+                day.max_time_ms
             );
         }
-        println!();
+        println_verbatim!();
     }
 
     if all || weekly {
         let weeks = tracker.get_by_week()?;
-        println!("# Weekly Data");
-        println!(
-            "week_start,week_end,commands,input_tokens,output_tokens,saved_tokens,savings_pct,total_time_ms,avg_time_ms"
+        println_verbatim!("# Weekly Data");
+        println_verbatim!(
+            "week_start,week_end,commands,input_tokens,output_tokens,saved_tokens,savings_pct,total_time_ms,avg_time_ms,p50_time_ms,p95_time_ms,max_time_ms"
         );
         for week in weeks {
-            println!(
-                "{},{},{},{},{},{},{:.2},{},{}",
+            println_verbatim!(
+                "{},{},{},{},{},{},{:.2},{},{},{},{},{}",
                 week.week_start,
                 week.week_end,
                 week.commands,
@@ -379,19 +958,22 @@ fn export_csv(
                 week.saved_tokens,
                 week.savings_pct,
                 week.total_time_ms,
-                week.avg_time_ms
+                week.avg_time_ms,
+                week.p50_time_ms,
+                week.p95_time_ms,
+                week.max_time_ms
             );
         }
-        println!();
+        println_verbatim!();
     }
 
     if all || monthly {
         let months = tracker.get_by_month()?;
-        println!("# Monthly Data");
-        println!("month,commands,input_tokens,output_tokens,saved_tokens,savings_pct,total_time_ms,avg_time_ms");
+        println_verbatim!("# Monthly Data");
+        println_verbatim!("month,commands,input_tokens,output_tokens,saved_tokens,savings_pct,total_time_ms,avg_time_ms,p50_time_ms,p95_time_ms,max_time_ms");
         for month in months {
-            println!(
-                "{},{},{},{},{},{:.2},{},{}",
+            println_verbatim!(
+                "{},{},{},{},{},{:.2},{},{},{},{},{}",
                 month.month,
                 month.commands,
                 month.input_tokens,
@@ -399,7 +981,10 @@ fn export_csv(
                 month.saved_tokens,
                 month.savings_pct,
                 month.total_time_ms,
-                month.avg_time_ms
+                month.avg_time_ms,
+                month.p50_time_ms,
+                month.p95_time_ms,
+                month.max_time_ms
             );
         }
     }
@@ -411,6 +996,88 @@ fn export_csv(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_format_breakdown() {
+        let stats = vec![
+            GroupStats {
+                name: Some("claude-code".to_string()),
+                commands: 120,
+                input_tokens: 50_000,
+                output_tokens: 10_000,
+                saved_tokens: 40_000,
+                savings_pct: 80.0,
+            },
+            GroupStats {
+                name: None,
+                commands: 3,
+                input_tokens: 0,
+                output_tokens: 0,
+                saved_tokens: 0,
+                savings_pct: 0.0,
+            },
+        ];
+        let text = format_breakdown(Breakdown::Agent, &stats);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "📊 RTK Savings by Agent");
+        assert!(lines[3].starts_with("claude-code"));
+        assert!(lines[3].ends_with("80.0%"));
+        assert!(lines[4].starts_with("(unrecorded)"));
+
+        // Wide names are cut and padded by columns, not bytes or chars
+        let wide = GroupStats {
+            name: Some("エージェント-アシスタント".to_string()),
+            commands: 3,
+            input_tokens: 0,
+            output_tokens: 0,
+            saved_tokens: 0,
+            savings_pct: 0.0,
+        };
+        let text = format_breakdown(Breakdown::Agent, &[wide]);
+        let row = text.lines().nth(3).unwrap();
+        assert!(row.starts_with("エージェント-アシス... "));
+        let name_col = &row[..row.len() - "      3          0    0.0%".len()];
+        assert_eq!(crate::utils::display_width(name_col), 24);
+        assert!(format_breakdown(Breakdown::Model, &[]).ends_with("No tracking data yet.\n"));
+    }
+
+    #[test]
+    fn test_format_sample() {
+        let sampled = SampledRecord {
+            record: tracking::RawRecord {
+                id: 7,
+                timestamp: "2026-10-16T09:30:00+00:00".to_string(),
+                original_cmd: "git log".to_string(),
+                rtk_cmd: "rtk git log".to_string(),
+                input_tokens: 1200,
+                output_tokens: 100,
+                saved_tokens: 1100,
+                savings_pct: 91.7,
+                exec_time_ms: 12,
+                exit_code: Some(0),
+                invoker: None,
+                model: None,
+            },
+            sample: tracking::OutputSample::new(
+                "commit 1a2b3c\nAuthor: A\n\n    fix\n",
+                "1a2b3c fix\n",
+                13,
+            ),
+        };
+        let (header, heads) = format_sample(&sampled);
+        assert!(header.starts_with("#7 rtk git log\n  original: git log\n"));
+        assert!(header.contains("  exit:     0\n"));
+        assert_eq!(
+            heads,
+            [
+                (
+                    "── raw (first 13 of 33 bytes) ──".to_string(),
+                    "commit 1a2b3c"
+                ),
+                ("── output ──".to_string(), "1a2b3c fix"),
+            ]
+        );
+    }
+
     #[test]
     fn test_normalize_cmd_run_err() {
         assert_eq!(normalize_cmd_name("rtk run-err"), "rtk err");
@@ -445,8 +1112,30 @@ mod tests {
     #[test]
     fn test_normalize_by_command_merges_duplicates() {
         let entries = vec![
-            ("rtk run-err".to_string(), 10, 500, 80.0, 100),
-            ("rtk err".to_string(), 5, 300, 75.0, 50),
+            (
+                "rtk run-err".to_string(),
+                10,
+                500,
+                80.0,
+                100,
+                TimePercentiles {
+                    p50_ms: 90,
+                    p95_ms: 300,
+                    max_ms: 900,
+                },
+            ),
+            (
+                "rtk err".to_string(),
+                5,
+                300,
+                75.0,
+                50,
+                TimePercentiles {
+                    p50_ms: 30,
+                    p95_ms: 150,
+                    max_ms: 2000,
+                },
+            ),
         ];
         let result = normalize_by_command(entries);
         // Should merge into single "rtk err" entry
@@ -458,14 +1147,132 @@ mod tests {
         assert!((result[0].3 - 78.33).abs() < 0.1);
         // weighted avg time: (100*10 + 50*5) / 15 = 1250/15 ≈ 83
         assert_eq!(result[0].4, 83);
+        // weighted p50: (90*10 + 30*5) / 15 = 70; max stays exact
+        assert_eq!(result[0].5.p50_ms, 70);
+        assert_eq!(result[0].5.p95_ms, 250);
+        assert_eq!(result[0].5.max_ms, 2000);
+    }
+
+    #[test]
+    fn test_display_by_command_redacts_and_merges() {
+        let entries = vec![
+            (
+                "rtk read /home/alice/a.rs".to_string(),
+                2,
+                100,
+                50.0,
+                10,
+                TimePercentiles::default(),
+            ),
+            (
+                "rtk read /home/alice/a.rs".to_string(),
+                1,
+                50,
+                50.0,
+                10,
+                TimePercentiles::default(),
+            ),
+            (
+                "rtk ls".to_string(),
+                1,
+                10,
+                10.0,
+                5,
+                TimePercentiles::default(),
+            ),
+        ];
+        let result = display_by_command(entries, true);
+        assert_eq!(result.len(), 2);
+        assert!(result[0].0.starts_with("rtk cat <path:"));
+        assert_eq!(result[0].1, 3);
+        assert_eq!(result[1].0, "rtk ls");
+    }
+
+    #[test]
+    fn test_format_by_command_markdown() {
+        let entries = vec![
+            (
+                "rtk git status".to_string(),
+                20,
+                1000,
+                70.0,
+                50,
+                TimePercentiles::default(),
+            ),
+            (
+                "rtk grep a|b".to_string(),
+                2,
+                40,
+                50.0,
+                1500,
+                TimePercentiles::default(),
+            ),
+        ];
+        let md = format_by_command_markdown(&entries);
+        assert!(md.contains("| Command | Count | Saved | Avg% | Time |"));
+        assert!(md.contains("| `rtk git status` | 20 | 1.0K | 70.0% | 50ms |"));
+        // Pipes must be escaped to keep the table intact
+        assert!(md.contains("`rtk grep a\\|b`"));
+    }
+
+    #[test]
+    fn test_display_failures_merges_and_sorts() {
+        let stats = vec![
+            FailureStats {
+                command: "rtk git status".to_string(),
+                runs: 10,
+                failures: 1,
+                failure_pct: 10.0,
+            },
+            FailureStats {
+                command: "rtk read".to_string(),
+                runs: 2,
+                failures: 1,
+                failure_pct: 50.0,
+            },
+            FailureStats {
+                command: "rtk cat".to_string(),
+                runs: 2,
+                failures: 1,
+                failure_pct: 50.0,
+            },
+        ];
+        let result = display_failures(stats, false);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].command, "rtk cat");
+        assert_eq!(result[0].runs, 4);
+        assert_eq!(result[0].failures, 2);
+        assert_eq!(result[0].failure_pct, 50.0);
+        assert_eq!(result[1].command, "rtk git status");
     }
 
     #[test]
     fn test_normalize_by_command_preserves_order() {
         let entries = vec![
-            ("rtk git status".to_string(), 20, 1000, 70.0, 50),
-            ("rtk run-err".to_string(), 10, 500, 80.0, 100),
-            ("rtk ls".to_string(), 5, 200, 60.0, 30),
+            (
+                "rtk git status".to_string(),
+                20,
+                1000,
+                70.0,
+                50,
+                TimePercentiles::default(),
+            ),
+            (
+                "rtk run-err".to_string(),
+                10,
+                500,
+                80.0,
+                100,
+                TimePercentiles::default(),
+            ),
+            (
+                "rtk ls".to_string(),
+                5,
+                200,
+                60.0,
+                30,
+                TimePercentiles::default(),
+            ),
         ];
         let result = normalize_by_command(entries);
         assert_eq!(result.len(), 3);
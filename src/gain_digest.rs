@@ -0,0 +1,275 @@
+//! Periodic savings digest (`rtk gain digest`).
+//!
+//! Renders a short summary of the last week (or day) of tracked savings and
+//! delivers it to a Slack/Discord incoming webhook or a file, so it can run
+//! from cron and reach people who never open the CLI. Webhooks are posted
+//! with `curl`, like the other network-facing commands.
+
+use crate::config::Config;
+use crate::gain::display_cmd_name;
+use crate::local_time;
+use crate::tracking::{GroupStats, Tracker, WindowTotals};
+use crate::utils::{format_tokens, pad};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use serde_json::json;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Commands listed in the digest.
+const TOP_COMMANDS: usize = 5;
+
+#[derive(Debug, Serialize)]
+struct Digest {
+    /// "weekly" or "daily"
+    period: &'static str,
+    /// First and last day covered (YYYY-MM-DD, UTC)
+    since: String,
+    until: String,
+    totals: WindowTotals,
+    /// Same-length period just before `since`
+    previous: WindowTotals,
+    top_commands: Vec<GroupStats>,
+}
+
+/// Build the digest, print it and deliver it to the webhook and/or file
+/// (flags override `[digest]` in the config).
+pub fn run(
+    daily: bool,
+    webhook: Option<String>,
+    output: Option<PathBuf>,
+    format: &str,
+    redact: bool,
+    verbose: u8,
+) -> Result<()> {
+    let config = Config::cached();
+    // `--webhook ""` skips a configured webhook
+    let webhook = webhook
+        .or_else(|| config.digest.webhook.clone())
+        .filter(|url| !url.is_empty());
+    let output = output.or_else(|| config.digest.output.clone());
+
+    let (period, days) = if daily { ("daily", 1) } else { ("weekly", 7) };
+    let until = Utc::now();
+    let since = until - Duration::days(days);
+
+    let tracker = Tracker::new().context("Failed to initialize tracking database")?;
+    let totals = tracker
+        .get_totals_since(since)
+        .context("Failed to load tracked totals from database")?;
+    // Two periods minus the current one (saturating: a command may be
+    // recorded between the two queries)
+    let both = tracker.get_totals_since(since - Duration::days(days))?;
+    let previous = WindowTotals {
+        commands: both.commands.saturating_sub(totals.commands),
+        input_tokens: both.input_tokens.saturating_sub(totals.input_tokens),
+        output_tokens: both.output_tokens.saturating_sub(totals.output_tokens),
+        saved_tokens: both.saved_tokens.saturating_sub(totals.saved_tokens),
+    };
+    let mut top_commands = tracker.get_top_commands_since(since, TOP_COMMANDS)?;
+    for cmd in &mut top_commands {
+        cmd.name = cmd.name.as_deref().map(|n| display_cmd_name(n, redact));
+    }
+
+    let digest = Digest {
+        period,
+        since: day(since),
+        until: day(until),
+        totals,
+        previous,
+        top_commands,
+    };
+    let rendered = if format == "json" {
+        let json = serde_json::to_string_pretty(&digest)?;
+        println_verbatim!("{}", json);
+        json
+    } else {
+        let text = render(&digest);
+        println!("{}", text);
+        text
+    };
+
+    if let Some(path) = &output {
+        std::fs::write(path, format!("{}\n", rendered))
+            .with_context(|| format!("Failed to write digest to {}", path.display()))?;
+        if verbose > 0 {
+            eprintln!("digest written to {}", path.display());
+        }
+    }
+    if let Some(url) = &webhook {
+        post_webhook(url, &render(&digest))?;
+        if verbose > 0 {
+            eprintln!("digest posted to webhook");
+        }
+    }
+    Ok(())
+}
+
+fn render(d: &Digest) -> String {
+    let title = if d.period == "daily" {
+        "RTK daily digest"
+    } else {
+        "RTK weekly digest"
+    };
+    let mut out = vec![format!("{} ({} → {})", title, d.since, d.until)];
+
+    if d.totals.commands == 0 {
+        out.push("No commands tracked in this period.".to_string());
+        return out.join("\n");
+    }
+
+    out.push(format!(
+        "Tokens saved: {} ({:.1}%) across {} commands",
+        format_tokens(d.totals.saved_tokens),
+        pct(d.totals.saved_tokens, d.totals.input_tokens),
+        d.totals.commands
+    ));
+    if d.previous.saved_tokens > 0 {
+        let change = (d.totals.saved_tokens as f64 / d.previous.saved_tokens as f64 - 1.0) * 100.0;
+        out.push(format!(
+            "vs previous {}: {:+.0}% ({} saved)",
+            if d.period == "daily" { "day" } else { "week" },
+            change,
+            format_tokens(d.previous.saved_tokens)
+        ));
+    }
+
+    if !d.top_commands.is_empty() {
+        out.push("Top commands:".to_string());
+        for cmd in &d.top_commands {
+            out.push(format!(
+                "  {} {:>5}x {:>8} saved ({:.0}%)",
+                pad(cmd.name.as_deref().unwrap_or("?"), 22),
+                cmd.commands,
+                format_tokens(cmd.saved_tokens),
+                cmd.savings_pct
+            ));
+        }
+    }
+    out.join("\n")
+}
+
+fn day(time: DateTime<Utc>) -> String {
+    local_time::date(time)
+}
+
+fn pct(part: usize, whole: usize) -> f64 {
+    if whole > 0 {
+        part as f64 / whole as f64 * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Slack reads `text`, Discord reads `content`.
+fn webhook_payload(url: &str, text: &str) -> serde_json::Value {
+    let is_discord =
+        url.contains("discord.com/api/webhooks") || url.contains("discordapp.com/api/webhooks");
+    // Monospace keeps the command table aligned in both
+    let body = format!("```\n{}\n```", text);
+    if is_discord {
+        json!({ "content": body })
+    } else {
+        json!({ "text": body })
+    }
+}
+
+fn post_webhook(url: &str, text: &str) -> Result<()> {
+    let payload = webhook_payload(url, text).to_string();
+    let mut child = Command::new("curl")
+        .args([
+            "-sS",
+            "--fail",
+            "--max-time",
+            "15",
+            "-X",
+            "POST",
+            "-H",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+            url,
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .context("Failed to run curl (needed to post the digest webhook)")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(payload.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        anyhow::bail!(
+            "Webhook post failed (curl exit {})",
+            status.code().unwrap_or(-1)
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn totals(commands: usize, input: usize, saved: usize) -> WindowTotals {
+        WindowTotals {
+            commands,
+            input_tokens: input,
+            output_tokens: input - saved,
+            saved_tokens: saved,
+        }
+    }
+
+    fn digest(current: WindowTotals, previous: WindowTotals) -> Digest {
+        let until = Utc.with_ymd_and_hms(2026, 10, 16, 8, 0, 0).unwrap();
+        Digest {
+            period: "weekly",
+            since: day(until - Duration::days(7)),
+            until: day(until),
+            totals: current,
+            previous,
+            top_commands: vec![GroupStats {
+                name: Some("rtk cargo test".to_string()),
+                commands: 42,
+                input_tokens: 120_000,
+                output_tokens: 12_000,
+                saved_tokens: 108_000,
+                savings_pct: 90.0,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_render_weekly() {
+        let text = render(&digest(
+            totals(120, 200_000, 150_000),
+            totals(80, 150_000, 100_000),
+        ));
+        assert_eq!(
+            text,
+            "RTK weekly digest (2026-10-09 → 2026-10-16)
+Tokens saved: 150.0K (75.0%) across 120 commands
+vs previous week: +50% (100.0K saved)
+Top commands:
+  rtk cargo test            42x   108.0K saved (90%)"
+        );
+    }
+
+    #[test]
+    fn test_render_empty_period() {
+        let text = render(&digest(totals(0, 0, 0), totals(0, 0, 0)));
+        assert!(text.ends_with("No commands tracked in this period."));
+    }
+
+    #[test]
+    fn test_webhook_payload() {
+        let slack = webhook_payload("https://hooks.slack.com/services/T/B/X", "hi");
+        assert_eq!(slack["text"], "```\nhi\n```");
+        let discord = webhook_payload("https://discord.com/api/webhooks/1/abc", "hi");
+        assert_eq!(discord["content"], "```\nhi\n```");
+        assert!(discord.get("text").is_none());
+    }
+}
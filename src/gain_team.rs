@@ -0,0 +1,663 @@
+//! Team aggregation: `rtk gain push`, `rtk gain team` and `rtk gain serve`.
+//!
+//! Each member pushes anonymized per-day totals (commands and token counts
+//! only, never command strings) to a self-hosted endpoint; `rtk gain team`
+//! pulls every member's totals back and merges them. `rtk gain serve` is
+//! that endpoint: a token-protected HTTP/JSON server storing aggregates in
+//! a single JSON file.
+//!
+//! - `POST /v1/aggregates`  `{"member", "days": [...]}` → upsert by date
+//! - `GET  /v1/aggregates`  `{"members": [{"member", "days": [...]}]}`
+//!
+//! Both require `Authorization: Bearer <token>`. Put the server behind a
+//! TLS-terminating proxy when it is reachable beyond localhost.
+
+use crate::config::Config;
+use crate::daemon::{read_request, reason, token_matches, Request};
+use crate::local_time;
+use crate::tracking::Tracker;
+use crate::utils::{format_tokens, pad};
+use anyhow::{Context, Result};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io::{BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+/// One member's totals for one day.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DayAggregate {
+    /// ISO date (YYYY-MM-DD)
+    pub date: String,
+    pub commands: usize,
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+    pub saved_tokens: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct MemberAggregates {
+    member: String,
+    days: Vec<DayAggregate>,
+}
+
+/// Server storage: member → date → totals.
+type Store = BTreeMap<String, BTreeMap<String, DayAggregate>>;
+
+/// Shared by the server's connection threads.
+struct Server {
+    store: Mutex<Store>,
+    token: String,
+    data: PathBuf,
+}
+
+/// Team server URL and token, from flags or `[team]`.
+struct Endpoint {
+    server: String,
+    token: String,
+}
+
+fn endpoint(server: Option<String>, token: Option<String>) -> Result<Endpoint> {
+    let team = &Config::cached().team;
+    let server = server
+        .or_else(|| team.server.clone())
+        .context("No team server: pass --server or set team.server")?;
+    let token = token
+        .or_else(|| team.token.clone())
+        .context("No team token: pass --token or set team.token (RTK_TEAM__TOKEN)")?;
+    Ok(Endpoint {
+        server: server.trim_end_matches('/').to_string(),
+        token,
+    })
+}
+
+/// Name pushed for this machine: `team.member`, else a stable hash of
+/// user@host so teammates stay distinguishable without being identified.
+fn member_name() -> String {
+    if let Some(member) = Config::cached().team.member.clone() {
+        return member;
+    }
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+    let host = std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .unwrap_or_default();
+    format!(
+        "member-{}",
+        crate::redact::short_hash(&format!("{}@{}", user, host.trim()))
+    )
+}
+
+/// Upload the last `days` days of per-day totals (`rtk gain push`).
+pub fn run_push(
+    server: Option<String>,
+    token: Option<String>,
+    days: u32,
+    verbose: u8,
+) -> Result<()> {
+    let endpoint = endpoint(server, token)?;
+    let tracker = Tracker::new().context("Failed to initialize tracking database")?;
+    let since = local_time::date(Utc::now() - Duration::days(days as i64));
+    let payload = MemberAggregates {
+        member: member_name(),
+        days: tracker
+            .get_all_days()
+            .context("Failed to load daily statistics from database")?
+            .into_iter()
+            .filter(|d| d.date >= since)
+            .map(|d| DayAggregate {
+                date: d.date,
+                commands: d.commands,
+                input_tokens: d.input_tokens,
+                output_tokens: d.output_tokens,
+                saved_tokens: d.saved_tokens,
+            })
+            .collect(),
+    };
+    if verbose > 0 {
+        eprintln!(
+            "pushing {} days as {} to {}",
+            payload.days.len(),
+            payload.member,
+            endpoint.server
+        );
+    }
+
+    let body = serde_json::to_string(&payload)?;
+    curl_json(&endpoint, "POST", Some(&body))?;
+    let saved: usize = payload.days.iter().map(|d| d.saved_tokens).sum();
+    println!(
+        "ok pushed {} days ({} tokens saved) as {}",
+        payload.days.len(),
+        format_tokens(saved),
+        payload.member
+    );
+    Ok(())
+}
+
+/// Merged view of every member's totals (`rtk gain team`).
+#[derive(Debug, Serialize, PartialEq)]
+struct TeamReport {
+    since_days: u32,
+    members: Vec<MemberTotals>,
+    total: MemberTotals,
+}
+
+#[derive(Debug, Default, Serialize, PartialEq)]
+struct MemberTotals {
+    member: String,
+    active_days: usize,
+    commands: usize,
+    input_tokens: usize,
+    output_tokens: usize,
+    saved_tokens: usize,
+    savings_pct: f64,
+}
+
+impl MemberTotals {
+    fn add(&mut self, day: &DayAggregate) {
+        self.active_days += 1;
+        self.commands += day.commands;
+        self.input_tokens += day.input_tokens;
+        self.output_tokens += day.output_tokens;
+        self.saved_tokens += day.saved_tokens;
+    }
+
+    fn finish(mut self) -> Self {
+        self.savings_pct = if self.input_tokens > 0 {
+            self.saved_tokens as f64 / self.input_tokens as f64 * 100.0
+        } else {
+            0.0
+        };
+        self
+    }
+}
+
+fn merge(members: &[MemberAggregates], since: &str, since_days: u32) -> TeamReport {
+    let mut total = MemberTotals {
+        member: "team".to_string(),
+        ..Default::default()
+    };
+    let mut team_days = std::collections::BTreeSet::new();
+    let mut rows: Vec<MemberTotals> = members
+        .iter()
+        .map(|m| {
+            let mut totals = MemberTotals {
+                member: m.member.clone(),
+                ..Default::default()
+            };
+            for day in m.days.iter().filter(|d| d.date.as_str() >= since) {
+                totals.add(day);
+                total.add(day);
+                team_days.insert(day.date.clone());
+            }
+            totals.finish()
+        })
+        .filter(|m| m.active_days > 0)
+        .collect();
+    rows.sort_by_key(|m| std::cmp::Reverse(m.saved_tokens));
+    total.active_days = team_days.len();
+
+    TeamReport {
+        since_days,
+        members: rows,
+        total: total.finish(),
+    }
+}
+
+/// Pull every member's totals and print the merged view (`rtk gain team`).
+pub fn run_team(
+    server: Option<String>,
+    token: Option<String>,
+    days: u32,
+    format: &str,
+    verbose: u8,
+) -> Result<()> {
+    let endpoint = endpoint(server, token)?;
+    let response = curl_json(&endpoint, "GET", None)?;
+    let mut value: Value =
+        serde_json::from_str(&response).context("Team server returned invalid JSON")?;
+    let members: Vec<MemberAggregates> = serde_json::from_value(value["members"].take())
+        .context("Team server returned an unexpected response")?;
+    if verbose > 0 {
+        eprintln!("{} members from {}", members.len(), endpoint.server);
+    }
+
+    let since = local_time::date(Utc::now() - Duration::days(days as i64));
+    let report = merge(&members, &since, days);
+    if format == "json" {
+        println_verbatim!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", format_team(&report));
+    }
+    Ok(())
+}
+
+fn format_team(r: &TeamReport) -> String {
+    let mut out = String::new();
+    out.push_str(&format!(
+        "📊 RTK Team Savings (last {} days)\n",
+        r.since_days
+    ));
+    out.push_str("════════════════════════════════════════\n");
+    if r.members.is_empty() {
+        out.push_str("No aggregates pushed yet (rtk gain push).\n");
+        return out;
+    }
+    out.push_str(&format!("Members:           {}\n", r.members.len()));
+    out.push_str(&format!("Commands:          {}\n", r.total.commands));
+    out.push_str(&format!(
+        "Tokens saved:      {} ({:.1}%)\n",
+        format_tokens(r.total.saved_tokens),
+        r.total.savings_pct
+    ));
+    out.push('\n');
+    out.push_str("By Member:\n");
+    out.push_str("────────────────────────────────────────\n");
+    out.push_str(&format!(
+        "{:<20} {:>5} {:>9} {:>10} {:>7}\n",
+        "Member", "Days", "Commands", "Saved", "Avg%"
+    ));
+    for m in &r.members {
+        out.push_str(&format!(
+            "{} {:>5} {:>9} {:>10} {:>6.1}%\n",
+            pad(&m.member, 20),
+            m.active_days,
+            m.commands,
+            format_tokens(m.saved_tokens),
+            m.savings_pct
+        ));
+    }
+    out
+}
+
+/// Call the team endpoint with curl. The token goes through a temp file
+/// header so it never shows up in the process list.
+fn curl_json(endpoint: &Endpoint, method: &str, body: Option<&str>) -> Result<String> {
+    let mut header = tempfile::NamedTempFile::new()?;
+    writeln!(header, "Authorization: Bearer {}", endpoint.token)?;
+    let url = format!("{}/v1/aggregates", endpoint.server);
+
+    let mut cmd = Command::new("curl");
+    cmd.args(["-sS", "--fail-with-body", "--max-time", "30", "-X", method])
+        .arg("-H")
+        .arg(format!("@{}", header.path().display()))
+        .arg(&url)
+        .stdout(Stdio::piped());
+    if body.is_some() {
+        cmd.args([
+            "-H",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+        ])
+        .stdin(Stdio::piped());
+    }
+    let mut child = cmd
+        .spawn()
+        .context("Failed to run curl (needed to reach the team server)")?;
+    if let (Some(body), Some(mut stdin)) = (body, child.stdin.take()) {
+        stdin.write_all(body.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    let text = String::from_utf8_lossy(&output.stdout).into_owned();
+    if !output.status.success() {
+        anyhow::bail!(
+            "{} {} failed (curl exit {}){}",
+            method,
+            url,
+            output.status.code().unwrap_or(-1),
+            if text.is_empty() {
+                String::new()
+            } else {
+                format!(": {}", text.trim())
+            }
+        );
+    }
+    Ok(text)
+}
+
+/// Serve the aggregation endpoint (`rtk gain serve`).
+pub fn run_serve(
+    bind: &str,
+    port: u16,
+    token: Option<String>,
+    data: Option<PathBuf>,
+    verbose: u8,
+) -> Result<()> {
+    let token = token
+        .or_else(|| Config::cached().team.token.clone())
+        .filter(|t| !t.is_empty())
+        .context("The team server needs a token: pass --token or set team.token")?;
+    let data = match data {
+        Some(path) => path,
+        None => dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("rtk")
+            .join("team.json"),
+    };
+    let store = load_store(&data)?;
+
+    let listener = TcpListener::bind((bind, port))
+        .with_context(|| format!("Failed to listen on {}:{}", bind, port))?;
+    crate::crash::serving();
+    eprintln!(
+        "rtk team server listening on http://{}:{} ({} members in {})",
+        bind,
+        port,
+        store.len(),
+        data.display()
+    );
+    let server = Arc::new(Server {
+        store: Mutex::new(store),
+        token,
+        data,
+    });
+
+    // A thread per connection, so a slow client doesn't hold up the rest;
+    // the store is only locked while a request is routed
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        let server = Arc::clone(&server);
+        std::thread::spawn(move || {
+            if let Err(e) = serve(stream, &server, verbose) {
+                if verbose > 0 {
+                    eprintln!("rtk team server: {:#}", e);
+                }
+            }
+        });
+    }
+    Ok(())
+}
+
+fn load_store(path: &Path) -> Result<Store> {
+    if !path.exists() {
+        return Ok(Store::new());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Invalid team data in {}", path.display()))
+}
+
+fn save_store(path: &Path, store: &Store) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Write-then-rename so a crash never leaves a truncated file
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string(store)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn serve(mut stream: TcpStream, server: &Server, verbose: u8) -> Result<()> {
+    stream.set_read_timeout(Some(std::time::Duration::from_secs(10)))?;
+    let (status, body) = match read_request(&mut BufReader::new(&stream)) {
+        Ok(request) => {
+            if verbose > 0 {
+                eprintln!("{} {}", request.method, request.path);
+            }
+            match server.store.lock() {
+                Ok(mut store) => crate::crash::catch(|| {
+                    route(&request, &mut store, &server.token, |store| {
+                        save_store(&server.data, store)
+                    })
+                })
+                .unwrap_or_else(|panic| {
+                    (
+                        500,
+                        json!({ "error": format!("internal error: {}", panic) }),
+                    )
+                }),
+                Err(_) => (500, json!({ "error": "team data unavailable" })),
+            }
+        }
+        Err(e) => (400, json!({ "error": format!("{:#}", e) })),
+    };
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
+
+/// `save` persists the store after a push; if it fails the push is undone,
+/// so the served data always matches the file.
+fn route(
+    request: &Request,
+    store: &mut Store,
+    token: &str,
+    save: impl FnOnce(&Store) -> Result<()>,
+) -> (u16, Value) {
+    let authorized = request
+        .headers
+        .get("authorization")
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .is_some_and(|t| token_matches(t, token));
+    if !authorized {
+        return (401, json!({ "error": "missing or invalid bearer token" }));
+    }
+
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/v1/aggregates") => {
+            let members: Vec<MemberAggregates> = store
+                .iter()
+                .map(|(member, days)| MemberAggregates {
+                    member: member.clone(),
+                    days: days.values().cloned().collect(),
+                })
+                .collect();
+            (200, json!({ "members": members }))
+        }
+        ("POST", "/v1/aggregates") => {
+            match serde_json::from_slice::<MemberAggregates>(&request.body) {
+                Ok(push) if !push.member.trim().is_empty() => {
+                    let days = store.entry(push.member.clone()).or_default();
+                    let count = push.days.len();
+                    let replaced: Vec<(String, Option<DayAggregate>)> = push
+                        .days
+                        .into_iter()
+                        .map(|day| (day.date.clone(), days.insert(day.date.clone(), day)))
+                        .collect();
+                    match save(store) {
+                        Ok(()) => (200, json!({ "ok": true, "days": count })),
+                        Err(e) => {
+                            undo_push(store, &push.member, replaced);
+                            (500, json!({ "error": format!("{:#}", e) }))
+                        }
+                    }
+                }
+                Ok(_) => (400, json!({ "error": "missing 'member'" })),
+                Err(e) => (400, json!({ "error": format!("invalid JSON: {}", e) })),
+            }
+        }
+        (_, "/v1/aggregates") => (405, json!({ "error": "method not allowed" })),
+        _ => (404, json!({ "error": "not found" })),
+    }
+}
+
+/// Put back the days a push replaced, latest first.
+fn undo_push(store: &mut Store, member: &str, replaced: Vec<(String, Option<DayAggregate>)>) {
+    let Some(days) = store.get_mut(member) else {
+        return;
+    };
+    for (date, previous) in replaced.into_iter().rev() {
+        match previous {
+            Some(day) => days.insert(date, day),
+            None => days.remove(&date),
+        };
+    }
+    if days.is_empty() {
+        store.remove(member);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn day(date: &str, commands: usize, input: usize, saved: usize) -> DayAggregate {
+        DayAggregate {
+            date: date.to_string(),
+            commands,
+            input_tokens: input,
+            output_tokens: input - saved,
+            saved_tokens: saved,
+        }
+    }
+
+    fn request(method: &str, token: &str, body: &str) -> Request {
+        let mut headers = HashMap::new();
+        headers.insert("authorization".to_string(), format!("Bearer {}", token));
+        Request {
+            method: method.to_string(),
+            path: "/v1/aggregates".to_string(),
+            headers,
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    fn saved(_: &Store) -> Result<()> {
+        Ok(())
+    }
+
+    #[test]
+    fn test_route_requires_token() {
+        let mut store = Store::new();
+        let (status, _) = route(&request("GET", "wrong", ""), &mut store, "s3cret", saved);
+        assert_eq!(status, 401);
+    }
+
+    #[test]
+    fn test_route_push_upserts_by_date() {
+        let mut store = Store::new();
+        let push = |days: Vec<DayAggregate>| {
+            serde_json::to_string(&MemberAggregates {
+                member: "alice".to_string(),
+                days,
+            })
+            .unwrap()
+        };
+        let first = push(vec![
+            day("2026-10-14", 5, 100, 80),
+            day("2026-10-15", 2, 10, 5),
+        ]);
+        let (status, _) = route(
+            &request("POST", "s3cret", &first),
+            &mut store,
+            "s3cret",
+            saved,
+        );
+        assert_eq!(status, 200);
+        // Re-pushing a day replaces it instead of double counting
+        let second = push(vec![day("2026-10-15", 4, 40, 30)]);
+        route(
+            &request("POST", "s3cret", &second),
+            &mut store,
+            "s3cret",
+            saved,
+        );
+
+        let (status, body) = route(&request("GET", "s3cret", ""), &mut store, "s3cret", saved);
+        assert_eq!(status, 200);
+        let members: Vec<MemberAggregates> =
+            serde_json::from_value(body["members"].clone()).unwrap();
+        assert_eq!(members.len(), 1);
+        assert_eq!(
+            members[0].days,
+            vec![day("2026-10-14", 5, 100, 80), day("2026-10-15", 4, 40, 30)]
+        );
+    }
+
+    #[test]
+    fn test_failed_save_undoes_the_push() {
+        let mut store = Store::new();
+        let push = |days| {
+            serde_json::to_string(&MemberAggregates {
+                member: "alice".to_string(),
+                days,
+            })
+            .unwrap()
+        };
+        let first = push(vec![day("2026-10-14", 5, 100, 80)]);
+        route(
+            &request("POST", "s3cret", &first),
+            &mut store,
+            "s3cret",
+            saved,
+        );
+        let before = store.clone();
+
+        let second = push(vec![
+            day("2026-10-14", 7, 100, 90),
+            day("2026-10-15", 2, 10, 5),
+            day("2026-10-15", 3, 10, 5),
+        ]);
+        let (status, _) = route(
+            &request("POST", "s3cret", &second),
+            &mut store,
+            "s3cret",
+            |_| anyhow::bail!("disk full"),
+        );
+        assert_eq!(status, 500);
+        assert_eq!(store, before);
+
+        // A new member's failed first push leaves no trace
+        let mut store = Store::new();
+        route(
+            &request("POST", "s3cret", &first),
+            &mut store,
+            "s3cret",
+            |_| anyhow::bail!("disk full"),
+        );
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_merge_members() {
+        let members = vec![
+            MemberAggregates {
+                member: "alice".to_string(),
+                days: vec![
+                    day("2026-09-01", 9, 900, 900),
+                    day("2026-10-15", 10, 1000, 800),
+                ],
+            },
+            MemberAggregates {
+                member: "member-3fa2b1c9".to_string(),
+                days: vec![
+                    day("2026-10-15", 5, 1000, 900),
+                    day("2026-10-16", 1, 200, 100),
+                ],
+            },
+            MemberAggregates {
+                member: "idle".to_string(),
+                days: vec![day("2026-08-01", 1, 10, 5)],
+            },
+        ];
+        let report = merge(&members, "2026-10-01", 15);
+        let names: Vec<&str> = report.members.iter().map(|m| m.member.as_str()).collect();
+        assert_eq!(names, vec!["member-3fa2b1c9", "alice"]);
+        assert_eq!(report.total.commands, 16);
+        assert_eq!(report.total.saved_tokens, 1800);
+        assert_eq!(report.total.active_days, 2);
+        assert_eq!(report.total.savings_pct, 1800.0 / 2200.0 * 100.0);
+
+        let text = format_team(&report);
+        assert!(text.contains("Members:           2\n"));
+        assert!(text.contains("Tokens saved:      1.8K (81.8%)\n"));
+    }
+}
@@ -0,0 +1,381 @@
+//! Live terminal dashboard for `rtk gain --watch`.
+//!
+//! Polls the tracking database for new rows and redraws a ratatui view with
+//! a savings sparkline, a per-command table and a stream of recent commands.
+//!
+//! Keys: `q`/`Esc` quit, `d` daily, `w` weekly, `s` cycle sort column,
+//! `r` reverse sort order.
+
+use crate::display_helpers::format_duration;
+use crate::gain::{normalize_by_command, normalize_cmd_name};
+use crate::local_time;
+use crate::tracking::{CommandRecord, TimePercentiles, Tracker};
+use crate::utils::format_tokens;
+use anyhow::{Context, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Borders, Cell, List, ListItem, Paragraph, Row, Sparkline, Table};
+use ratatui::{DefaultTerminal, Frame};
+use std::io::IsTerminal;
+use std::time::Duration;
+
+/// How often the database is probed for new rows.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Number of recent commands shown in the stream panel.
+const RECENT_LIMIT: usize = 20;
+
+type CommandRow = (String, usize, usize, f64, u64, TimePercentiles);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum View {
+    Daily,
+    Weekly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SortColumn {
+    Count,
+    Saved,
+    Pct,
+    Time,
+}
+
+impl SortColumn {
+    fn next(self) -> Self {
+        match self {
+            SortColumn::Count => SortColumn::Saved,
+            SortColumn::Saved => SortColumn::Pct,
+            SortColumn::Pct => SortColumn::Time,
+            SortColumn::Time => SortColumn::Count,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SortColumn::Count => "Count",
+            SortColumn::Saved => "Saved",
+            SortColumn::Pct => "Avg%",
+            SortColumn::Time => "Time",
+        }
+    }
+}
+
+struct Dashboard {
+    view: View,
+    sort: SortColumn,
+    descending: bool,
+    total_commands: usize,
+    total_saved: usize,
+    avg_savings_pct: f64,
+    by_command: Vec<CommandRow>,
+    trend: Vec<u64>,
+    trend_label: String,
+    recent: Vec<CommandRecord>,
+    last_id: i64,
+}
+
+impl Dashboard {
+    fn new() -> Self {
+        Self {
+            view: View::Daily,
+            sort: SortColumn::Saved,
+            descending: true,
+            total_commands: 0,
+            total_saved: 0,
+            avg_savings_pct: 0.0,
+            by_command: Vec::new(),
+            trend: Vec::new(),
+            trend_label: String::new(),
+            recent: Vec::new(),
+            last_id: -1,
+        }
+    }
+
+    fn reload(&mut self, tracker: &Tracker) -> Result<()> {
+        let summary = tracker
+            .get_summary()
+            .context("Failed to load token savings summary from database")?;
+
+        self.total_commands = summary.total_commands;
+        self.total_saved = summary.total_saved;
+        self.avg_savings_pct = summary.avg_savings_pct;
+        self.by_command = normalize_by_command(summary.by_command);
+        sort_commands(&mut self.by_command, self.sort, self.descending);
+
+        match self.view {
+            View::Daily => {
+                self.trend = summary.by_day.iter().map(|(_, v)| *v as u64).collect();
+                self.trend_label = "Daily savings (last 30 days)".to_string();
+            }
+            View::Weekly => {
+                let weeks = tracker.get_by_week()?;
+                self.trend = weeks.iter().map(|w| w.saved_tokens as u64).collect();
+                self.trend_label = format!("Weekly savings ({} weeks)", weeks.len());
+            }
+        }
+
+        self.recent = tracker.get_recent(RECENT_LIMIT)?;
+        self.last_id = tracker.latest_id()?;
+        Ok(())
+    }
+
+    /// Apply a key press. Returns `true` when the dashboard should exit.
+    fn handle_key(&mut self, code: KeyCode, tracker: &Tracker) -> Result<bool> {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(true),
+            KeyCode::Char('d') => {
+                self.view = View::Daily;
+                self.reload(tracker)?;
+            }
+            KeyCode::Char('w') => {
+                self.view = View::Weekly;
+                self.reload(tracker)?;
+            }
+            KeyCode::Char('s') => {
+                self.sort = self.sort.next();
+                sort_commands(&mut self.by_command, self.sort, self.descending);
+            }
+            KeyCode::Char('r') => {
+                self.descending = !self.descending;
+                sort_commands(&mut self.by_command, self.sort, self.descending);
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+}
+
+/// Run the live dashboard until the user quits.
+pub fn run() -> Result<()> {
+    if !std::io::stdout().is_terminal() {
+        anyhow::bail!("rtk gain --watch requires an interactive terminal");
+    }
+
+    let tracker = Tracker::new().context("Failed to initialize tracking database")?;
+    let mut dashboard = Dashboard::new();
+    dashboard.reload(&tracker)?;
+
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &tracker, &mut dashboard);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    tracker: &Tracker,
+    dashboard: &mut Dashboard,
+) -> Result<()> {
+    loop {
+        terminal.draw(|frame| draw(frame, dashboard))?;
+
+        if event::poll(POLL_INTERVAL)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && dashboard.handle_key(key.code, tracker)? {
+                    return Ok(());
+                }
+            }
+        } else {
+            // Runs from other processes wait in the spool until imported
+            let _ = tracker.refresh();
+            if tracker.latest_id()? != dashboard.last_id {
+                dashboard.reload(tracker)?;
+            }
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, dashboard: &Dashboard) {
+    let [header, trend, body, footer] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(7),
+        Constraint::Min(8),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [table_area, recent_area] =
+        Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(body);
+
+    let totals = format!(
+        "Commands: {}   Saved: {} ({:.1}%)",
+        dashboard.total_commands,
+        format_tokens(dashboard.total_saved),
+        dashboard.avg_savings_pct
+    );
+    frame.render_widget(
+        Paragraph::new(totals).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" RTK Token Savings (live) "),
+        ),
+        header,
+    );
+
+    frame.render_widget(
+        Sparkline::default()
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" {} ", dashboard.trend_label)),
+            )
+            .data(&dashboard.trend),
+        trend,
+    );
+
+    let header_row = Row::new(
+        ["Command", "Count", "Saved", "Avg%", "Time", "P95"].map(|h| {
+            let style = if h == dashboard.sort.label() {
+                Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+            } else {
+                Style::default().add_modifier(Modifier::BOLD)
+            };
+            Cell::from(h).style(style)
+        }),
+    );
+    let rows = dashboard
+        .by_command
+        .iter()
+        .map(|(cmd, count, saved, pct, time, timing)| {
+            Row::new([
+                cmd.clone(),
+                count.to_string(),
+                format_tokens(*saved),
+                format!("{:.1}%", pct),
+                format_duration(*time),
+                format_duration(timing.p95_ms),
+            ])
+        });
+    let order = if dashboard.descending { "desc" } else { "asc" };
+    frame.render_widget(
+        Table::new(
+            rows,
+            [
+                Constraint::Min(20),
+                Constraint::Length(7),
+                Constraint::Length(9),
+                Constraint::Length(7),
+                Constraint::Length(8),
+                Constraint::Length(8),
+            ],
+        )
+        .header(header_row)
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            " By Command (sort: {} {}) ",
+            dashboard.sort.label(),
+            order
+        ))),
+        table_area,
+    );
+
+    let items: Vec<ListItem> = dashboard
+        .recent
+        .iter()
+        .map(|rec| {
+            ListItem::new(format!(
+                "{} {} -{:.0}% ({})",
+                local_time::format(rec.timestamp, "%H:%M:%S"),
+                normalize_cmd_name(&rec.rtk_cmd),
+                rec.savings_pct,
+                format_tokens(rec.saved_tokens)
+            ))
+        })
+        .collect();
+    frame.render_widget(
+        List::new(items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Recent Commands "),
+        ),
+        recent_area,
+    );
+
+    frame.render_widget(
+        Paragraph::new("q quit  d daily  w weekly  s sort column  r reverse"),
+        footer,
+    );
+}
+
+/// Sort per-command rows in place by the selected column.
+fn sort_commands(rows: &mut [CommandRow], column: SortColumn, descending: bool) {
+    rows.sort_by(|a, b| {
+        let ord = match column {
+            SortColumn::Count => a.1.cmp(&b.1),
+            SortColumn::Saved => a.2.cmp(&b.2),
+            SortColumn::Pct => a.3.total_cmp(&b.3),
+            SortColumn::Time => a.4.cmp(&b.4),
+        };
+        if descending {
+            ord.reverse()
+        } else {
+            ord
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_rows() -> Vec<CommandRow> {
+        vec![
+            (
+                "rtk ls".to_string(),
+                5,
+                200,
+                60.0,
+                30,
+                TimePercentiles::default(),
+            ),
+            (
+                "rtk git status".to_string(),
+                20,
+                1000,
+                70.0,
+                50,
+                TimePercentiles::default(),
+            ),
+            (
+                "rtk err".to_string(),
+                10,
+                500,
+                80.0,
+                100,
+                TimePercentiles::default(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_sort_commands_by_saved_desc() {
+        let mut rows = sample_rows();
+        sort_commands(&mut rows, SortColumn::Saved, true);
+        assert_eq!(rows[0].0, "rtk git status");
+        assert_eq!(rows[2].0, "rtk ls");
+    }
+
+    #[test]
+    fn test_sort_commands_by_pct_asc() {
+        let mut rows = sample_rows();
+        sort_commands(&mut rows, SortColumn::Pct, false);
+        assert_eq!(rows[0].0, "rtk ls");
+        assert_eq!(rows[2].0, "rtk err");
+    }
+
+    #[test]
+    fn test_sort_commands_by_time_desc() {
+        let mut rows = sample_rows();
+        sort_commands(&mut rows, SortColumn::Time, true);
+        assert_eq!(rows[0].0, "rtk err");
+    }
+
+    #[test]
+    fn test_sort_column_cycles() {
+        let mut col = SortColumn::Count;
+        for _ in 0..4 {
+            col = col.next();
+        }
+        assert_eq!(col, SortColumn::Count);
+    }
+}
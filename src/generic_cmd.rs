@@ -0,0 +1,287 @@
+//! `rtk x <cmd>` — run any command with generic, tool-agnostic compression.
+//!
+//! For tools without a dedicated wrapper: strips ANSI codes, resolves `\r`
+//! overwrites and drops progress bars, folds runs of repeated (or
+//! number-only-different) lines, and truncates long output keeping more of
+//! the tail, where errors and summaries usually are. When a command writes
+//! to both streams, stderr is compressed on its own under a `--- stderr ---`
+//! label.
+
+use crate::policy::{self, Policy};
+use crate::stream::{self, Line, Source};
+use crate::tracking;
+use crate::utils::strip_ansi;
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::process::Command;
+
+/// Output longer than this is truncated.
+const MAX_LINES: usize = 120;
+/// Lines kept from the start when truncating; the rest of the budget goes to the tail.
+const HEAD_LINES: usize = 20;
+/// Error/warning lines rescued from the truncated middle.
+const MAX_RESCUED: usize = 20;
+
+lazy_static! {
+    static ref PERCENT: Regex = Regex::new(r"\b\d{1,3}(\.\d+)?%").unwrap();
+    static ref DIGITS: Regex = Regex::new(r"\d+").unwrap();
+    pub(crate) static ref IMPORTANT: Regex =
+        Regex::new(r"(?i)\b(error|fail(ed|ure)?|fatal|panic|warn(ing)?|exception)\b").unwrap();
+}
+
+pub fn run(command: &[String], verbose: u8) -> Result<()> {
+    let (program, args) = command
+        .split_first()
+        .context("x requires a command to execute\nUsage: rtk x <command> [args...]")?;
+
+    if let Some(protected) = Policy::load().check(&command.join(" ")) {
+        return policy::run_unfiltered(
+            Command::new(program).args(args),
+            &command.join(" "),
+            "rtk x",
+            &protected,
+            verbose,
+        );
+    }
+
+    let timer = tracking::TimedExecution::start();
+
+    if verbose > 0 {
+        eprintln!("Running: {} {}", program, args.join(" "));
+    }
+
+    let mut lines = Vec::new();
+    let streamed = stream::run(Command::new(program).args(args), |line| {
+        lines.push(line.clone());
+        None
+    })
+    .with_context(|| format!("Failed to run {}", program))?;
+
+    stream::sort_by_time(&mut lines);
+    let raw = join_lines(&lines, None);
+    let mut compressed = compress_streams(&lines);
+    if let Some(notice) = streamed.timeout_notice() {
+        compressed = format!("{}, partial output follows\n{}", notice, compressed);
+    }
+
+    println!("{}", compressed);
+
+    let cmd_line = format!("{} {}", program, args.join(" "));
+    timer.with_code(streamed.exit_code()).track(
+        cmd_line.trim(),
+        &format!("rtk x {}", cmd_line.trim()),
+        &raw,
+        &compressed,
+    );
+    Ok(())
+}
+
+/// Compress stdout and stderr separately, labelling stderr when both have
+/// content so diagnostics are not lost among regular output.
+pub fn compress_streams(lines: &[Line]) -> String {
+    let stdout = join_lines(lines, Some(Source::Stdout));
+    let stderr = join_lines(lines, Some(Source::Stderr));
+    if stdout.trim().is_empty() || stderr.trim().is_empty() {
+        return compress(&join_lines(lines, None));
+    }
+    format!(
+        "{}\n--- stderr ---\n{}",
+        compress(&stdout),
+        compress(&stderr)
+    )
+}
+
+pub fn join_lines(lines: &[Line], source: Option<Source>) -> String {
+    lines
+        .iter()
+        .filter(|line| source.is_none_or(|s| line.source == s))
+        .map(|line| line.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Apply every generic heuristic in order.
+pub fn compress(raw: &str) -> String {
+    let clean = strip_ansi(raw);
+    let lines: Vec<&str> = clean
+        .lines()
+        .map(resolve_overwrites)
+        .filter(|line| !is_progress_line(line))
+        .collect();
+    let folded = fold_repeats(&lines);
+    truncate_tail_weighted(folded).join("\n")
+}
+
+/// Keep what a terminal would show after `\r` overwrites.
+fn resolve_overwrites(line: &str) -> &str {
+    let line = line.trim_end_matches('\r');
+    match line.rfind('\r') {
+        Some(pos) => &line[pos + 1..],
+        None => line,
+    }
+}
+
+/// Progress bars (`[=====>    ] 45%`, `45% |████  |`) and braille spinners.
+pub fn is_progress_line(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    if trimmed
+        .chars()
+        .next()
+        .is_some_and(|c| ('\u{2800}'..='\u{28FF}').contains(&c))
+    {
+        return true;
+    }
+    let bar_chars = line
+        .chars()
+        .filter(|c| matches!(c, '=' | '#' | '>' | '█' | '░' | '▓' | '▒' | '▌' | '━'))
+        .count();
+    PERCENT.is_match(line) && bar_chars >= 8
+}
+
+/// Fold runs of lines equal after masking numbers.
+///
+/// Identical runs become `line (×N)`; runs differing only in numbers keep
+/// the first and last line around a `... N similar` marker.
+fn fold_repeats(lines: &[&str]) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let key = DIGITS.replace_all(lines[i], "#");
+        let mut j = i + 1;
+        while j < lines.len() && DIGITS.replace_all(lines[j], "#") == key {
+            j += 1;
+        }
+        let run = &lines[i..j];
+        if run.len() == 1 || run[0].trim().is_empty() {
+            // Blank runs collapse to a single blank line
+            out.push(run[0].to_string());
+        } else if run.iter().all(|l| *l == run[0]) {
+            out.push(format!("{} (×{})", run[0], run.len()));
+        } else if run.len() == 2 {
+            out.extend(run.iter().map(|l| l.to_string()));
+        } else {
+            out.push(run[0].to_string());
+            out.push(format!("  ... {} similar", run.len() - 2));
+            out.push(run[run.len() - 1].to_string());
+        }
+        i = j;
+    }
+    out
+}
+
+/// Keep the first `HEAD_LINES` and the tail up to `MAX_LINES`, rescuing
+/// error-like lines from the dropped middle.
+fn truncate_tail_weighted(lines: Vec<String>) -> Vec<String> {
+    if lines.len() <= MAX_LINES {
+        return lines;
+    }
+    let tail_len = MAX_LINES - HEAD_LINES;
+    let middle = &lines[HEAD_LINES..lines.len() - tail_len];
+    let rescued: Vec<&String> = middle
+        .iter()
+        .filter(|l| IMPORTANT.is_match(l))
+        .take(MAX_RESCUED)
+        .collect();
+
+    let mut out: Vec<String> = lines[..HEAD_LINES].to_vec();
+    out.push(format!(
+        "... {} lines omitted ({} error/warning lines kept) ...",
+        middle.len() - rescued.len(),
+        rescued.len()
+    ));
+    out.extend(rescued.into_iter().cloned());
+    out.extend(lines[lines.len() - tail_len..].iter().cloned());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn line(source: Source, text: &str, ms: u64) -> Line {
+        Line {
+            source,
+            text: text.to_string(),
+            at: Duration::from_millis(ms),
+        }
+    }
+
+    #[test]
+    fn test_compress_streams_labels_stderr() {
+        let lines = [
+            line(Source::Stdout, "building", 0),
+            line(Source::Stderr, "warning: unused", 1),
+            line(Source::Stdout, "done", 2),
+        ];
+        assert_eq!(
+            compress_streams(&lines),
+            "building\ndone\n--- stderr ---\nwarning: unused"
+        );
+        assert_eq!(compress_streams(&lines[..1]), "building");
+        assert_eq!(compress_streams(&lines[1..2]), "warning: unused");
+    }
+
+    #[test]
+    fn test_resolve_overwrites() {
+        assert_eq!(resolve_overwrites("10%\r50%\rdone"), "done");
+        assert_eq!(resolve_overwrites("plain\r"), "plain");
+    }
+
+    #[test]
+    fn test_progress_lines() {
+        assert!(is_progress_line("[=========>          ] 45%"));
+        assert!(is_progress_line(" 45%|████████▌         | 45/100"));
+        assert!(is_progress_line("⠋ Resolving dependencies"));
+        assert!(!is_progress_line("Coverage: 85% of lines"));
+        assert!(!is_progress_line("========"));
+    }
+
+    #[test]
+    fn test_fold_identical_and_similar_runs() {
+        let lines = [
+            "retrying",
+            "retrying",
+            "retrying",
+            "Downloaded 1 of 50",
+            "Downloaded 2 of 50",
+            "Downloaded 3 of 50",
+            "Downloaded 50 of 50",
+            "",
+            "",
+            "done",
+        ];
+        assert_eq!(
+            fold_repeats(&lines),
+            vec![
+                "retrying (×3)",
+                "Downloaded 1 of 50",
+                "  ... 2 similar",
+                "Downloaded 50 of 50",
+                "",
+                "done",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_truncate_keeps_head_tail_and_errors() {
+        let mut lines: Vec<String> = (0..300).map(|i| format!("line {}", i)).collect();
+        lines[150] = "error: something broke".to_string();
+        let out = truncate_tail_weighted(lines);
+
+        assert_eq!(out[0], "line 0");
+        assert_eq!(out[HEAD_LINES - 1], "line 19");
+        assert!(out[HEAD_LINES].starts_with("... 179 lines omitted (1 error/warning"));
+        assert!(out.contains(&"error: something broke".to_string()));
+        assert_eq!(out.last().unwrap(), "line 299");
+        assert!(out.len() <= MAX_LINES + 2);
+    }
+
+    #[test]
+    fn test_short_output_untouched() {
+        let raw = "\x1b[32mok\x1b[0m\nall good";
+        assert_eq!(compress(raw), "ok\nall good");
+    }
+}
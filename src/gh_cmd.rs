@@ -3,6 +3,7 @@
 //! Provides token-optimized alternatives to verbose `gh` commands.
 //! Focuses on extracting essential information from JSON outputs.
 
+use crate::exit_code;
 use crate::git;
 use crate::json_cmd;
 use crate::tracking;
@@ -63,12 +64,15 @@ fn list_prs(args: &[String], _verbose: u8, ultra_compact: bool) -> Result<()> {
 
     let output = cmd.output().context("Failed to run gh pr list")?;
     let raw = String::from_utf8_lossy(&output.stdout).to_string();
+    crate::crash::raw_output(&raw);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        timer.track("gh pr list", "rtk gh pr list", &stderr, &stderr);
+        timer
+            .with_exit_code(output.status)
+            .track("gh pr list", "rtk gh pr list", &stderr, &stderr);
         eprintln!("{}", stderr.trim());
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     let json: Value =
@@ -125,7 +129,9 @@ fn list_prs(args: &[String], _verbose: u8, ultra_compact: bool) -> Result<()> {
         }
     }
 
-    timer.track("gh pr list", "rtk gh pr list", &raw, &filtered);
+    timer
+        .with_exit_code(output.status)
+        .track("gh pr list", "rtk gh pr list", &raw, &filtered);
     Ok(())
 }
 
@@ -149,17 +155,18 @@ fn view_pr(args: &[String], _verbose: u8, ultra_compact: bool) -> Result<()> {
 
     let output = cmd.output().context("Failed to run gh pr view")?;
     let raw = String::from_utf8_lossy(&output.stdout).to_string();
+    crate::crash::raw_output(&raw);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        timer.track(
+        timer.with_exit_code(output.status).track(
             &format!("gh pr view {}", pr_number),
             &format!("rtk gh pr view {}", pr_number),
             &stderr,
             &stderr,
         );
         eprintln!("{}", stderr.trim());
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     let json: Value =
@@ -316,17 +323,18 @@ fn pr_checks(args: &[String], _verbose: u8, _ultra_compact: bool) -> Result<()>
 
     let output = cmd.output().context("Failed to run gh pr checks")?;
     let raw = String::from_utf8_lossy(&output.stdout).to_string();
+    crate::crash::raw_output(&raw);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        timer.track(
+        timer.with_exit_code(output.status).track(
             &format!("gh pr checks {}", pr_number),
             &format!("rtk gh pr checks {}", pr_number),
             &stderr,
             &stderr,
         );
         eprintln!("{}", stderr.trim());
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -379,7 +387,7 @@ fn pr_checks(args: &[String], _verbose: u8, _ultra_compact: bool) -> Result<()>
         }
     }
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("gh pr checks {}", pr_number),
         &format!("rtk gh pr checks {}", pr_number),
         &raw,
@@ -401,12 +409,18 @@ fn pr_status(_verbose: u8, _ultra_compact: bool) -> Result<()> {
 
     let output = cmd.output().context("Failed to run gh pr status")?;
     let raw = String::from_utf8_lossy(&output.stdout).to_string();
+    crate::crash::raw_output(&raw);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        timer.track("gh pr status", "rtk gh pr status", &stderr, &stderr);
+        timer.with_exit_code(output.status).track(
+            "gh pr status",
+            "rtk gh pr status",
+            &stderr,
+            &stderr,
+        );
         eprintln!("{}", stderr.trim());
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     let json: Value =
@@ -428,7 +442,9 @@ fn pr_status(_verbose: u8, _ultra_compact: bool) -> Result<()> {
         }
     }
 
-    timer.track("gh pr status", "rtk gh pr status", &raw, &filtered);
+    timer
+        .with_exit_code(output.status)
+        .track("gh pr status", "rtk gh pr status", &raw, &filtered);
     Ok(())
 }
 
@@ -456,12 +472,18 @@ fn list_issues(args: &[String], _verbose: u8, ultra_compact: bool) -> Result<()>
 
     let output = cmd.output().context("Failed to run gh issue list")?;
     let raw = String::from_utf8_lossy(&output.stdout).to_string();
+    crate::crash::raw_output(&raw);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        timer.track("gh issue list", "rtk gh issue list", &stderr, &stderr);
+        timer.with_exit_code(output.status).track(
+            "gh issue list",
+            "rtk gh issue list",
+            &stderr,
+            &stderr,
+        );
         eprintln!("{}", stderr.trim());
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     let json: Value =
@@ -507,7 +529,12 @@ fn list_issues(args: &[String], _verbose: u8, ultra_compact: bool) -> Result<()>
         }
     }
 
-    timer.track("gh issue list", "rtk gh issue list", &raw, &filtered);
+    timer.with_exit_code(output.status).track(
+        "gh issue list",
+        "rtk gh issue list",
+        &raw,
+        &filtered,
+    );
     Ok(())
 }
 
@@ -531,17 +558,18 @@ fn view_issue(args: &[String], _verbose: u8) -> Result<()> {
 
     let output = cmd.output().context("Failed to run gh issue view")?;
     let raw = String::from_utf8_lossy(&output.stdout).to_string();
+    crate::crash::raw_output(&raw);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        timer.track(
+        timer.with_exit_code(output.status).track(
             &format!("gh issue view {}", issue_number),
             &format!("rtk gh issue view {}", issue_number),
             &stderr,
             &stderr,
         );
         eprintln!("{}", stderr.trim());
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     let json: Value =
@@ -588,7 +616,7 @@ fn view_issue(args: &[String], _verbose: u8) -> Result<()> {
         }
     }
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("gh issue view {}", issue_number),
         &format!("rtk gh issue view {}", issue_number),
         &raw,
@@ -627,12 +655,18 @@ fn list_runs(args: &[String], _verbose: u8, ultra_compact: bool) -> Result<()> {
 
     let output = cmd.output().context("Failed to run gh run list")?;
     let raw = String::from_utf8_lossy(&output.stdout).to_string();
+    crate::crash::raw_output(&raw);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        timer.track("gh run list", "rtk gh run list", &stderr, &stderr);
+        timer.with_exit_code(output.status).track(
+            "gh run list",
+            "rtk gh run list",
+            &stderr,
+            &stderr,
+        );
         eprintln!("{}", stderr.trim());
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     let json: Value =
@@ -688,7 +722,9 @@ fn list_runs(args: &[String], _verbose: u8, ultra_compact: bool) -> Result<()> {
         }
     }
 
-    timer.track("gh run list", "rtk gh run list", &raw, &filtered);
+    timer
+        .with_exit_code(output.status)
+        .track("gh run list", "rtk gh run list", &raw, &filtered);
     Ok(())
 }
 
@@ -706,17 +742,18 @@ fn view_run(args: &[String], _verbose: u8) -> Result<()> {
 
     let output = cmd.output().context("Failed to run gh run view")?;
     let raw = String::from_utf8_lossy(&output.stdout).to_string();
+    crate::crash::raw_output(&raw);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        timer.track(
+        timer.with_exit_code(output.status).track(
             &format!("gh run view {}", run_id),
             &format!("rtk gh run view {}", run_id),
             &stderr,
             &stderr,
         );
         eprintln!("{}", stderr.trim());
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     // Parse output and show only failures
@@ -751,7 +788,7 @@ fn view_run(args: &[String], _verbose: u8) -> Result<()> {
         }
     }
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("gh run view {}", run_id),
         &format!("rtk gh run view {}", run_id),
         &raw,
@@ -788,12 +825,18 @@ fn run_repo(args: &[String], _verbose: u8, _ultra_compact: bool) -> Result<()> {
 
     let output = cmd.output().context("Failed to run gh repo view")?;
     let raw = String::from_utf8_lossy(&output.stdout).to_string();
+    crate::crash::raw_output(&raw);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        timer.track("gh repo view", "rtk gh repo view", &stderr, &stderr);
+        timer.with_exit_code(output.status).track(
+            "gh repo view",
+            "rtk gh repo view",
+            &stderr,
+            &stderr,
+        );
         eprintln!("{}", stderr.trim());
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     let json: Value =
@@ -837,7 +880,9 @@ fn run_repo(args: &[String], _verbose: u8, _ultra_compact: bool) -> Result<()> {
     filtered.push_str(&line);
     print!("{}", line);
 
-    timer.track("gh repo view", "rtk gh repo view", &raw, &filtered);
+    timer
+        .with_exit_code(output.status)
+        .track("gh repo view", "rtk gh repo view", &raw, &filtered);
     Ok(())
 }
 
@@ -855,9 +900,14 @@ fn pr_create(args: &[String], _verbose: u8) -> Result<()> {
     let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
     if !output.status.success() {
-        timer.track("gh pr create", "rtk gh pr create", &stderr, &stderr);
+        timer.with_exit_code(output.status).track(
+            "gh pr create",
+            "rtk gh pr create",
+            &stderr,
+            &stderr,
+        );
         eprintln!("{}", stderr.trim());
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     // gh pr create outputs the URL on success
@@ -875,7 +925,12 @@ fn pr_create(args: &[String], _verbose: u8) -> Result<()> {
     let filtered = ok_confirmation("created", &detail);
     println!("{}", filtered);
 
-    timer.track("gh pr create", "rtk gh pr create", &stdout, &filtered);
+    timer.with_exit_code(output.status).track(
+        "gh pr create",
+        "rtk gh pr create",
+        &stdout,
+        &filtered,
+    );
     Ok(())
 }
 
@@ -893,9 +948,14 @@ fn pr_merge(args: &[String], _verbose: u8) -> Result<()> {
     let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
     if !output.status.success() {
-        timer.track("gh pr merge", "rtk gh pr merge", &stderr, &stderr);
+        timer.with_exit_code(output.status).track(
+            "gh pr merge",
+            "rtk gh pr merge",
+            &stderr,
+            &stderr,
+        );
         eprintln!("{}", stderr.trim());
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     // Extract PR number from args (first non-flag arg)
@@ -921,7 +981,9 @@ fn pr_merge(args: &[String], _verbose: u8) -> Result<()> {
         detail.clone()
     };
 
-    timer.track("gh pr merge", "rtk gh pr merge", &raw, &filtered);
+    timer
+        .with_exit_code(output.status)
+        .track("gh pr merge", "rtk gh pr merge", &raw, &filtered);
     Ok(())
 }
 
@@ -936,12 +998,15 @@ fn pr_diff(args: &[String], _verbose: u8) -> Result<()> {
 
     let output = cmd.output().context("Failed to run gh pr diff")?;
     let raw = String::from_utf8_lossy(&output.stdout).to_string();
+    crate::crash::raw_output(&raw);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        timer.track("gh pr diff", "rtk gh pr diff", &stderr, &stderr);
+        timer
+            .with_exit_code(output.status)
+            .track("gh pr diff", "rtk gh pr diff", &stderr, &stderr);
         eprintln!("{}", stderr.trim());
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     let filtered = if raw.trim().is_empty() {
@@ -950,11 +1015,13 @@ fn pr_diff(args: &[String], _verbose: u8) -> Result<()> {
         msg.to_string()
     } else {
         let compacted = git::compact_diff(&raw, 100);
-        println!("{}", compacted);
+        crate::profile::println_quoting(&compacted, git::is_hunk_line);
         compacted
     };
 
-    timer.track("gh pr diff", "rtk gh pr diff", &raw, &filtered);
+    timer
+        .with_exit_code(output.status)
+        .track("gh pr diff", "rtk gh pr diff", &raw, &filtered);
     Ok(())
 }
 
@@ -975,14 +1042,14 @@ fn pr_action(action: &str, args: &[String], _verbose: u8) -> Result<()> {
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        timer.track(
+        timer.with_exit_code(output.status).track(
             &format!("gh pr {}", action),
             &format!("rtk gh pr {}", action),
             &stderr,
             &stderr,
         );
         eprintln!("{}", stderr.trim());
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     // Extract PR number from args
@@ -1002,7 +1069,7 @@ fn pr_action(action: &str, args: &[String], _verbose: u8) -> Result<()> {
         pr_num.clone()
     };
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("gh pr {}", action),
         &format!("rtk gh pr {}", action),
         &raw,
@@ -1022,12 +1089,15 @@ fn run_api(args: &[String], _verbose: u8) -> Result<()> {
 
     let output = cmd.output().context("Failed to run gh api")?;
     let raw = String::from_utf8_lossy(&output.stdout).to_string();
+    crate::crash::raw_output(&raw);
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        timer.track("gh api", "rtk gh api", &stderr, &stderr);
+        timer
+            .with_exit_code(output.status)
+            .track("gh api", "rtk gh api", &stderr, &stderr);
         eprintln!("{}", stderr.trim());
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     // Try to parse as JSON and filter
@@ -1051,7 +1121,9 @@ fn run_api(args: &[String], _verbose: u8) -> Result<()> {
         }
     };
 
-    timer.track("gh api", "rtk gh api", &raw, &filtered);
+    timer
+        .with_exit_code(output.status)
+        .track("gh api", "rtk gh api", &raw, &filtered);
     Ok(())
 }
 
@@ -1069,13 +1141,13 @@ fn run_passthrough(cmd: &str, subcommand: &str, args: &[String]) -> Result<()> {
         .context(format!("Failed to run {} {}", cmd, subcommand))?;
 
     let args_str = tracking::args_display(&args.iter().map(|s| s.into()).collect::<Vec<_>>());
-    timer.track_passthrough(
+    timer.with_exit_code(status).track_passthrough(
         &format!("{} {} {}", cmd, subcommand, args_str),
         &format!("rtk {} {} {} (passthrough)", cmd, subcommand, args_str),
     );
 
     if !status.success() {
-        std::process::exit(status.code().unwrap_or(1));
+        exit_code::exit_with(status);
     }
 
     Ok(())
@@ -1096,12 +1168,12 @@ mod tests {
 
     #[test]
     fn test_truncate_multibyte_utf8() {
-        // Emoji: 🚀 = 4 bytes, 1 char
-        assert_eq!(truncate("🚀🎉🔥abc", 6), "🚀🎉🔥abc"); // 6 chars, fits
-        assert_eq!(truncate("🚀🎉🔥abcdef", 8), "🚀🎉🔥ab..."); // 10 chars > 8
+        // Emoji: 🚀 = 4 bytes, 1 char, 2 columns
+        assert_eq!(truncate("🚀🎉🔥abc", 9), "🚀🎉🔥abc"); // 9 columns, fits
+        assert_eq!(truncate("🚀🎉🔥abcdef", 10), "🚀🎉🔥a..."); // 12 columns > 10
                                                                 // Edge case: all multibyte
-        assert_eq!(truncate("🚀🎉🔥🌟🎯", 5), "🚀🎉🔥🌟🎯"); // exact fit
-        assert_eq!(truncate("🚀🎉🔥🌟🎯x", 5), "🚀🎉..."); // 6 chars > 5
+        assert_eq!(truncate("🚀🎉🔥🌟🎯", 10), "🚀🎉🔥🌟🎯"); // exact fit
+        assert_eq!(truncate("🚀🎉🔥🌟🎯x", 10), "🚀🎉🔥..."); // 11 columns > 10
     }
 
     #[test]
@@ -1,7 +1,16 @@
+use crate::cache;
+use crate::exit_code;
+use crate::profile;
+use crate::git_diff::DiffCompactor;
+use crate::stream::{self, RawInput};
 use crate::tracking;
+use crate::utils::truncate;
 use anyhow::{Context, Result};
 use std::ffi::OsString;
-use std::process::Command;
+use std::io::BufReader;
+use std::process::{Command, Stdio};
+
+pub use crate::git_diff::{compact_diff, is_hunk_line};
 
 #[derive(Debug, Clone)]
 pub enum GitCommand {
@@ -17,6 +26,8 @@ pub enum GitCommand {
     Fetch,
     Stash { subcommand: Option<String> },
     Worktree,
+    State,
+    Overview,
 }
 
 pub fn run(cmd: GitCommand, args: &[String], max_lines: Option<usize>, verbose: u8) -> Result<()> {
@@ -33,6 +44,8 @@ pub fn run(cmd: GitCommand, args: &[String], max_lines: Option<usize>, verbose:
         GitCommand::Fetch => run_fetch(args, verbose),
         GitCommand::Stash { subcommand } => run_stash(subcommand.as_deref(), args, verbose),
         GitCommand::Worktree => run_worktree(args, verbose),
+        GitCommand::State => run_state(verbose),
+        GitCommand::Overview => run_overview(verbose),
     }
 }
 
@@ -60,13 +73,13 @@ fn run_diff(args: &[String], max_lines: Option<usize>, verbose: u8) -> Result<()
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
             eprintln!("{}", stderr);
-            std::process::exit(output.status.code().unwrap_or(1));
+            exit_code::exit_with(output.status);
         }
 
         let stdout = String::from_utf8_lossy(&output.stdout);
-        println!("{}", stdout.trim());
+        println_verbatim!("{}", stdout.trim());
 
-        timer.track(
+        timer.with_exit_code(output.status).track(
             &format!("git diff {}", args.join(" ")),
             &format!("rtk git diff {} (passthrough)", args.join(" ")),
             &stdout,
@@ -94,35 +107,68 @@ fn run_diff(args: &[String], max_lines: Option<usize>, verbose: u8) -> Result<()
     // Print stat summary first
     println!("{}", stat_stdout.trim());
 
-    // Now get actual diff but compact it
+    // Now compact the actual diff as git writes it: the stat gives one
+    // line per file plus a summary, which sizes each file's share
+    let mut raw = RawInput::default();
+    for line in stat_stdout.lines() {
+        raw.push_line(line);
+    }
+    let files = stat_stdout.lines().count().saturating_sub(1);
     let mut diff_cmd = Command::new("git");
     diff_cmd.arg("diff");
     for arg in args {
         diff_cmd.arg(arg);
     }
-
-    let diff_output = diff_cmd.output().context("Failed to run git diff")?;
-    let diff_stdout = String::from_utf8_lossy(&diff_output.stdout);
+    let (compacted, any) = stream_compact(
+        diff_cmd,
+        max_lines.unwrap_or(100),
+        files,
+        &mut raw,
+    )
+    .context("Failed to run git diff")?;
 
     let mut final_output = stat_stdout.to_string();
-    if !diff_stdout.is_empty() {
+    if any {
         println!("\n--- Changes ---");
-        let compacted = compact_diff(&diff_stdout, max_lines.unwrap_or(100));
-        println!("{}", compacted);
+        profile::println_quoting(&compacted, is_hunk_line);
         final_output.push_str("\n--- Changes ---\n");
         final_output.push_str(&compacted);
     }
 
-    timer.track(
+    raw.track(
+        timer.with_exit_code(output.status),
         &format!("git diff {}", args.join(" ")),
         &format!("rtk git diff {}", args.join(" ")),
-        &format!("{}\n{}", stat_stdout, diff_stdout),
         &final_output,
     );
 
     Ok(())
 }
 
+/// Run `cmd` and compact the diff it writes line by line, without holding
+/// it; every line also goes to `raw`. Returns the compacted text and
+/// whether the diff had any lines.
+fn stream_compact(
+    mut cmd: Command,
+    max_lines: usize,
+    files: usize,
+    raw: &mut RawInput,
+) -> Result<(String, bool)> {
+    let mut child = cmd.stdout(Stdio::piped()).spawn()?;
+    let stdout = child.stdout.take().context("no stdout")?;
+    let mut compactor = DiffCompactor::new(max_lines, files);
+    let mut any = false;
+    let read = stream::each_line(BufReader::new(stdout), "git output", |line| {
+        any = true;
+        raw.push_line(&line);
+        compactor.push(&line);
+        true
+    });
+    child.wait()?;
+    read?;
+    Ok((compactor.finish(), any))
+}
+
 fn run_show(args: &[String], max_lines: Option<usize>, verbose: u8) -> Result<()> {
     let timer = tracking::TimedExecution::start();
 
@@ -145,12 +191,12 @@ fn run_show(args: &[String], max_lines: Option<usize>, verbose: u8) -> Result<()
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
             eprintln!("{}", stderr);
-            std::process::exit(output.status.code().unwrap_or(1));
+            exit_code::exit_with(output.status);
         }
         let stdout = String::from_utf8_lossy(&output.stdout);
-        println!("{}", stdout.trim());
+        println_verbatim!("{}", stdout.trim());
 
-        timer.track(
+        timer.with_exit_code(output.status).track(
             &format!("git show {}", args.join(" ")),
             &format!("rtk git show {} (passthrough)", args.join(" ")),
             &stdout,
@@ -160,17 +206,6 @@ fn run_show(args: &[String], max_lines: Option<usize>, verbose: u8) -> Result<()
         return Ok(());
     }
 
-    // Get raw output for tracking
-    let mut raw_cmd = Command::new("git");
-    raw_cmd.arg("show");
-    for arg in args {
-        raw_cmd.arg(arg);
-    }
-    let raw_output = raw_cmd
-        .output()
-        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
-        .unwrap_or_default();
-
     // Step 1: one-line commit summary
     let mut summary_cmd = Command::new("git");
     summary_cmd.args(["show", "--no-patch", "--pretty=format:%h %s (%ar) <%an>"]);
@@ -181,10 +216,15 @@ fn run_show(args: &[String], max_lines: Option<usize>, verbose: u8) -> Result<()
     if !summary_output.status.success() {
         let stderr = String::from_utf8_lossy(&summary_output.stderr);
         eprintln!("{}", stderr);
-        std::process::exit(summary_output.status.code().unwrap_or(1));
+        exit_code::exit_with(summary_output.status);
     }
     let summary = String::from_utf8_lossy(&summary_output.stdout);
     println!("{}", summary.trim());
+    // What `git show` would print: the commit, then its diff
+    let mut raw = RawInput::default();
+    for line in summary.lines() {
+        raw.push_line(line);
+    }
 
     // Step 2: --stat summary
     let mut stat_cmd = Command::new("git");
@@ -199,102 +239,35 @@ fn run_show(args: &[String], max_lines: Option<usize>, verbose: u8) -> Result<()
         println!("{}", stat_text);
     }
 
-    // Step 3: compacted diff
+    // Step 3: compacted diff, streamed
     let mut diff_cmd = Command::new("git");
     diff_cmd.args(["show", "--pretty=format:"]);
     for arg in args {
         diff_cmd.arg(arg);
     }
-    let diff_output = diff_cmd.output().context("Failed to run git show (diff)")?;
-    let diff_stdout = String::from_utf8_lossy(&diff_output.stdout);
-    let diff_text = diff_stdout.trim();
+    let files = stat_text.lines().count().saturating_sub(1);
+    let (compacted, _) = stream_compact(diff_cmd, max_lines.unwrap_or(100), files, &mut raw)
+        .context("Failed to run git show (diff)")?;
 
     let mut final_output = summary.to_string();
-    if !diff_text.is_empty() {
+    if !compacted.trim().is_empty() {
         if verbose > 0 {
             println!("\n--- Changes ---");
         }
-        let compacted = compact_diff(diff_text, max_lines.unwrap_or(100));
-        println!("{}", compacted);
+        profile::println_quoting(&compacted, is_hunk_line);
         final_output.push_str(&format!("\n{}", compacted));
     }
 
-    timer.track(
+    raw.track(
+        timer.with_exit_code(summary_output.status),
         &format!("git show {}", args.join(" ")),
         &format!("rtk git show {}", args.join(" ")),
-        &raw_output,
         &final_output,
     );
 
     Ok(())
 }
 
-pub(crate) fn compact_diff(diff: &str, max_lines: usize) -> String {
-    let mut result = Vec::new();
-    let mut current_file = String::new();
-    let mut added = 0;
-    let mut removed = 0;
-    let mut in_hunk = false;
-    let mut hunk_lines = 0;
-    let max_hunk_lines = 10;
-
-    for line in diff.lines() {
-        if line.starts_with("diff --git") {
-            // New file
-            if !current_file.is_empty() && (added > 0 || removed > 0) {
-                result.push(format!("  +{} -{}", added, removed));
-            }
-            current_file = line.split(" b/").nth(1).unwrap_or("unknown").to_string();
-            result.push(format!("\n📄 {}", current_file));
-            added = 0;
-            removed = 0;
-            in_hunk = false;
-        } else if line.starts_with("@@") {
-            // New hunk
-            in_hunk = true;
-            hunk_lines = 0;
-            let hunk_info = line.split("@@").nth(1).unwrap_or("").trim();
-            result.push(format!("  @@ {} @@", hunk_info));
-        } else if in_hunk {
-            if line.starts_with('+') && !line.starts_with("+++") {
-                added += 1;
-                if hunk_lines < max_hunk_lines {
-                    result.push(format!("  {}", line));
-                    hunk_lines += 1;
-                }
-            } else if line.starts_with('-') && !line.starts_with("---") {
-                removed += 1;
-                if hunk_lines < max_hunk_lines {
-                    result.push(format!("  {}", line));
-                    hunk_lines += 1;
-                }
-            } else if hunk_lines < max_hunk_lines && !line.starts_with("\\") {
-                // Context line
-                if hunk_lines > 0 {
-                    result.push(format!("  {}", line));
-                    hunk_lines += 1;
-                }
-            }
-
-            if hunk_lines == max_hunk_lines {
-                result.push("  ... (truncated)".to_string());
-                hunk_lines += 1;
-            }
-        }
-
-        if result.len() >= max_lines {
-            result.push("\n... (more changes truncated)".to_string());
-            break;
-        }
-    }
-
-    if !current_file.is_empty() && (added > 0 || removed > 0) {
-        result.push(format!("  +{} -{}", added, removed));
-    }
-
-    result.join("\n")
-}
-
 fn run_log(args: &[String], _max_lines: Option<usize>, verbose: u8) -> Result<()> {
     let timer = tracking::TimedExecution::start();
 
@@ -348,7 +321,7 @@ fn run_log(args: &[String], _max_lines: Option<usize>, verbose: u8) -> Result<()
         let stderr = String::from_utf8_lossy(&output.stderr);
         eprintln!("{}", stderr);
         // Propagate git's exit code
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -361,7 +334,7 @@ fn run_log(args: &[String], _max_lines: Option<usize>, verbose: u8) -> Result<()
     let filtered = filter_log_output(&stdout, limit);
     println!("{}", filtered);
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("git log {}", args.join(" ")),
         &format!("rtk git log {}", args.join(" ")),
         &stdout,
@@ -377,13 +350,7 @@ fn filter_log_output(output: &str, limit: usize) -> String {
     let capped: Vec<String> = lines
         .iter()
         .take(limit)
-        .map(|line| {
-            if line.len() > 80 {
-                format!("{}...", &line[..77])
-            } else {
-                line.to_string()
-            }
-        })
+        .map(|line| truncate(line, 80))
         .collect();
 
     capped.join("\n").trim().to_string()
@@ -544,7 +511,7 @@ fn run_status(args: &[String], verbose: u8) -> Result<()> {
         let filtered = filter_status_with_args(&stdout);
         print!("{}", filtered);
 
-        timer.track(
+        timer.with_exit_code(output.status).track(
             &format!("git status {}", args.join(" ")),
             &format!("rtk git status {}", args.join(" ")),
             &stdout,
@@ -555,25 +522,40 @@ fn run_status(args: &[String], verbose: u8) -> Result<()> {
     }
 
     // Default RTK compact mode (no args provided)
-    // Get raw git status for tracking
-    let raw_output = Command::new("git")
-        .args(["status"])
-        .output()
-        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
-        .unwrap_or_default();
-
     let output = Command::new("git")
         .args(["status", "--porcelain", "-b"])
         .output()
         .context("Failed to run git status")?;
 
     let stdout = String::from_utf8_lossy(&output.stdout);
-    let formatted = format_status_output(&stdout);
 
-    println!("{}", formatted);
+    // Unchanged porcelain status: reuse the formatted output and skip the
+    // second `git status` run that only feeds savings tracking
+    let cache_key = cache::key(&["git status", &stdout]);
+    let entry = cache::get(&cache_key).unwrap_or_else(|| {
+        let entry = cache::Entry {
+            input: Command::new("git")
+                .args(["status"])
+                .output()
+                .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+                .unwrap_or_default(),
+            output: format_status_output(&stdout),
+        };
+        if output.status.success() {
+            cache::put(&cache_key, &entry);
+        }
+        entry
+    });
+
+    println!("{}", entry.output);
 
     // Track for statistics
-    timer.track("git status", "rtk git status", &raw_output, &formatted);
+    timer.with_exit_code(output.status).track(
+        "git status",
+        "rtk git status",
+        &entry.input,
+        &entry.output,
+    );
 
     Ok(())
 }
@@ -627,7 +609,7 @@ fn run_add(args: &[String], verbose: u8) -> Result<()> {
 
         println!("{}", compact);
 
-        timer.track(
+        timer.with_exit_code(output.status).track(
             &format!("git add {}", args.join(" ")),
             &format!("rtk git add {}", args.join(" ")),
             &raw_output,
@@ -644,7 +626,7 @@ fn run_add(args: &[String], verbose: u8) -> Result<()> {
             eprintln!("{}", stdout);
         }
         // Propagate git's exit code
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     Ok(())
@@ -685,7 +667,7 @@ fn run_commit(message: &str, verbose: u8) -> Result<()> {
 
         println!("{}", compact);
 
-        timer.track(
+        timer.with_exit_code(output.status).track(
             &format!("git commit -m \"{}\"", message),
             "rtk git commit",
             &raw_output,
@@ -694,7 +676,7 @@ fn run_commit(message: &str, verbose: u8) -> Result<()> {
     } else {
         if stderr.contains("nothing to commit") || stdout.contains("nothing to commit") {
             println!("ok (nothing to commit)");
-            timer.track(
+            timer.with_exit_code(output.status).track(
                 &format!("git commit -m \"{}\"", message),
                 "rtk git commit",
                 &raw_output,
@@ -708,6 +690,7 @@ fn run_commit(message: &str, verbose: u8) -> Result<()> {
             if !stdout.trim().is_empty() {
                 eprintln!("{}", stdout);
             }
+            exit_code::record(output.status);
         }
     }
 
@@ -732,6 +715,7 @@ fn run_push(args: &[String], verbose: u8) -> Result<()> {
     let stderr = String::from_utf8_lossy(&output.stderr);
     let stdout = String::from_utf8_lossy(&output.stdout);
     let raw = format!("{}{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
     if output.status.success() {
         let compact = if stderr.contains("Everything up-to-date") {
@@ -756,7 +740,7 @@ fn run_push(args: &[String], verbose: u8) -> Result<()> {
 
         println!("{}", compact);
 
-        timer.track(
+        timer.with_exit_code(output.status).track(
             &format!("git push {}", args.join(" ")),
             &format!("rtk git push {}", args.join(" ")),
             &raw,
@@ -770,6 +754,7 @@ fn run_push(args: &[String], verbose: u8) -> Result<()> {
         if !stdout.trim().is_empty() {
             eprintln!("{}", stdout);
         }
+        exit_code::record(output.status);
     }
 
     Ok(())
@@ -841,7 +826,7 @@ fn run_pull(args: &[String], verbose: u8) -> Result<()> {
 
         println!("{}", compact);
 
-        timer.track(
+        timer.with_exit_code(output.status).track(
             &format!("git pull {}", args.join(" ")),
             &format!("rtk git pull {}", args.join(" ")),
             &raw_output,
@@ -855,6 +840,7 @@ fn run_pull(args: &[String], verbose: u8) -> Result<()> {
         if !stdout.trim().is_empty() {
             eprintln!("{}", stdout);
         }
+        exit_code::record(output.status);
     }
 
     Ok(())
@@ -890,7 +876,7 @@ fn run_branch(args: &[String], verbose: u8) -> Result<()> {
             &combined
         };
 
-        timer.track(
+        timer.with_exit_code(output.status).track(
             &format!("git branch {}", args.join(" ")),
             &format!("rtk git branch {}", args.join(" ")),
             &combined,
@@ -900,6 +886,7 @@ fn run_branch(args: &[String], verbose: u8) -> Result<()> {
         if output.status.success() {
             println!("ok ✓");
         } else {
+            exit_code::record(output.status);
             eprintln!("FAILED: git branch");
             if !stderr.trim().is_empty() {
                 eprintln!("{}", stderr);
@@ -920,11 +907,12 @@ fn run_branch(args: &[String], verbose: u8) -> Result<()> {
     let output = cmd.output().context("Failed to run git branch")?;
     let stdout = String::from_utf8_lossy(&output.stdout);
     let raw = stdout.to_string();
+    crate::crash::raw_output(&raw);
 
     let filtered = filter_branch_output(&stdout);
     println!("{}", filtered);
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("git branch {}", args.join(" ")),
         &format!("rtk git branch {}", args.join(" ")),
         &raw,
@@ -1005,8 +993,10 @@ fn run_fetch(args: &[String], verbose: u8) -> Result<()> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
     if !output.status.success() {
+        exit_code::record(output.status);
         eprintln!("FAILED: git fetch");
         if !stderr.trim().is_empty() {
             eprintln!("{}", stderr);
@@ -1027,7 +1017,9 @@ fn run_fetch(args: &[String], verbose: u8) -> Result<()> {
     };
 
     println!("{}", msg);
-    timer.track("git fetch", "rtk git fetch", &raw, &msg);
+    timer
+        .with_exit_code(output.status)
+        .track("git fetch", "rtk git fetch", &raw, &msg);
 
     Ok(())
 }
@@ -1047,17 +1039,28 @@ fn run_stash(subcommand: Option<&str>, args: &[String], verbose: u8) -> Result<(
                 .context("Failed to run git stash list")?;
             let stdout = String::from_utf8_lossy(&output.stdout);
             let raw = stdout.to_string();
+            crate::crash::raw_output(&raw);
 
             if stdout.trim().is_empty() {
                 let msg = "No stashes";
                 println!("{}", msg);
-                timer.track("git stash list", "rtk git stash list", &raw, msg);
+                timer.with_exit_code(output.status).track(
+                    "git stash list",
+                    "rtk git stash list",
+                    &raw,
+                    msg,
+                );
                 return Ok(());
             }
 
             let filtered = filter_stash_list(&stdout);
             println!("{}", filtered);
-            timer.track("git stash list", "rtk git stash list", &raw, &filtered);
+            timer.with_exit_code(output.status).track(
+                "git stash list",
+                "rtk git stash list",
+                &raw,
+                &filtered,
+            );
         }
         Some("show") => {
             let mut cmd = Command::new("git");
@@ -1068,6 +1071,7 @@ fn run_stash(subcommand: Option<&str>, args: &[String], verbose: u8) -> Result<(
             let output = cmd.output().context("Failed to run git stash show")?;
             let stdout = String::from_utf8_lossy(&output.stdout);
             let raw = stdout.to_string();
+            crate::crash::raw_output(&raw);
 
             let filtered = if stdout.trim().is_empty() {
                 let msg = "Empty stash";
@@ -1075,11 +1079,16 @@ fn run_stash(subcommand: Option<&str>, args: &[String], verbose: u8) -> Result<(
                 msg.to_string()
             } else {
                 let compacted = compact_diff(&stdout, 100);
-                println!("{}", compacted);
+                profile::println_quoting(&compacted, is_hunk_line);
                 compacted
             };
 
-            timer.track("git stash show", "rtk git stash show", &raw, &filtered);
+            timer.with_exit_code(output.status).track(
+                "git stash show",
+                "rtk git stash show",
+                &raw,
+                &filtered,
+            );
         }
         Some("pop") | Some("apply") | Some("drop") | Some("push") => {
             let sub = subcommand.unwrap();
@@ -1105,7 +1114,7 @@ fn run_stash(subcommand: Option<&str>, args: &[String], verbose: u8) -> Result<(
                 combined.clone()
             };
 
-            timer.track(
+            timer.with_exit_code(output.status).track(
                 &format!("git stash {}", sub),
                 &format!("rtk git stash {}", sub),
                 &combined,
@@ -1142,7 +1151,12 @@ fn run_stash(subcommand: Option<&str>, args: &[String], verbose: u8) -> Result<(
                 combined.clone()
             };
 
-            timer.track("git stash", "rtk git stash", &combined, &msg);
+            timer.with_exit_code(output.status).track(
+                "git stash",
+                "rtk git stash",
+                &combined,
+                &msg,
+            );
         }
     }
 
@@ -1199,7 +1213,7 @@ fn run_worktree(args: &[String], verbose: u8) -> Result<()> {
             &combined
         };
 
-        timer.track(
+        timer.with_exit_code(output.status).track(
             &format!("git worktree {}", args.join(" ")),
             &format!("rtk git worktree {}", args.join(" ")),
             &combined,
@@ -1209,6 +1223,7 @@ fn run_worktree(args: &[String], verbose: u8) -> Result<()> {
         if output.status.success() {
             println!("ok ✓");
         } else {
+            exit_code::record(output.status);
             eprintln!("FAILED: git worktree {}", args.join(" "));
             if !stderr.trim().is_empty() {
                 eprintln!("{}", stderr);
@@ -1225,10 +1240,16 @@ fn run_worktree(args: &[String], verbose: u8) -> Result<()> {
 
     let stdout = String::from_utf8_lossy(&output.stdout);
     let raw = stdout.to_string();
+    crate::crash::raw_output(&raw);
 
     let filtered = filter_worktree_list(&stdout);
     println!("{}", filtered);
-    timer.track("git worktree list", "rtk git worktree", &raw, &filtered);
+    timer.with_exit_code(output.status).track(
+        "git worktree list",
+        "rtk git worktree",
+        &raw,
+        &filtered,
+    );
 
     Ok(())
 }
@@ -1260,6 +1281,504 @@ fn filter_worktree_list(output: &str) -> String {
     result.join("\n")
 }
 
+/// An operation git has paused mid-way.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operation {
+    Rebase,
+    /// `git am`, or `git rebase --apply`
+    Am,
+    Merge,
+    CherryPick,
+    Revert,
+    Bisect,
+}
+
+impl Operation {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Rebase => "rebase",
+            Self::Am => "am",
+            Self::Merge => "merge",
+            Self::CherryPick => "cherry-pick",
+            Self::Revert => "revert",
+            Self::Bisect => "bisect",
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct OperationState {
+    operation: Option<Operation>,
+    /// Current step and total, for rebases and multi-commit picks
+    step: Option<(usize, usize)>,
+    /// Branch being rebased
+    branch: Option<String>,
+    /// Commit the rebase replays onto
+    onto: Option<String>,
+    /// Commit that stopped the operation (or the bisect candidate)
+    current: Option<String>,
+    /// Subject of `current`, or of the patch/merge message
+    subject: Option<String>,
+    /// Bisect: good and bad marks so far
+    bisect_marks: (usize, usize),
+    conflicts: Vec<String>,
+}
+
+fn read_trimmed(path: &std::path::Path) -> Option<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Operation in progress from the state files in `git_dir`.
+fn detect_operation(git_dir: &std::path::Path) -> OperationState {
+    let file = |name: &str| read_trimmed(&git_dir.join(name));
+    let number = |name: &str| file(name).and_then(|n| n.parse::<usize>().ok());
+    let branch = |name: &str| file(name).map(|b| b.trim_start_matches("refs/heads/").to_string());
+
+    if git_dir.join("rebase-merge").is_dir() {
+        return OperationState {
+            operation: Some(Operation::Rebase),
+            step: number("rebase-merge/msgnum").zip(number("rebase-merge/end")),
+            branch: branch("rebase-merge/head-name"),
+            onto: file("rebase-merge/onto"),
+            current: file("rebase-merge/stopped-sha"),
+            ..Default::default()
+        };
+    }
+    if git_dir.join("rebase-apply").is_dir() {
+        let am = git_dir.join("rebase-apply/applying").exists();
+        let subject = file("rebase-apply/final-commit")
+            .or_else(|| file("rebase-apply/msg"))
+            .and_then(|m| m.lines().next().map(str::to_string));
+        return OperationState {
+            operation: Some(if am { Operation::Am } else { Operation::Rebase }),
+            step: number("rebase-apply/next").zip(number("rebase-apply/last")),
+            branch: branch("rebase-apply/head-name"),
+            onto: file("rebase-apply/onto"),
+            current: file("rebase-apply/original-commit"),
+            subject,
+            ..Default::default()
+        };
+    }
+    // Multi-commit cherry-pick/revert: done and todo lists in sequencer/
+    let sequencer_step = || {
+        let count = |name: &str| {
+            file(name).map_or(0, |s| {
+                s.lines()
+                    .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+                    .count()
+            })
+        };
+        let (done, todo) = (count("sequencer/done"), count("sequencer/todo"));
+        (done + todo > 1).then_some((done.max(1), done + todo))
+    };
+    for (name, operation) in [
+        ("CHERRY_PICK_HEAD", Operation::CherryPick),
+        ("REVERT_HEAD", Operation::Revert),
+    ] {
+        if let Some(sha) = file(name) {
+            return OperationState {
+                operation: Some(operation),
+                step: sequencer_step(),
+                current: Some(sha),
+                ..Default::default()
+            };
+        }
+    }
+    if let Some(sha) = file("MERGE_HEAD") {
+        return OperationState {
+            operation: Some(Operation::Merge),
+            current: sha.lines().next().map(str::to_string),
+            subject: file("MERGE_MSG").and_then(|m| m.lines().next().map(str::to_string)),
+            ..Default::default()
+        };
+    }
+    if let Some(log) = file("BISECT_LOG") {
+        let marks = |verb: &str| {
+            log.lines()
+                .filter(|l| l.starts_with(&format!("git bisect {}", verb)))
+                .count()
+        };
+        return OperationState {
+            operation: Some(Operation::Bisect),
+            bisect_marks: (marks("good"), marks("bad")),
+            ..Default::default()
+        };
+    }
+    OperationState::default()
+}
+
+fn short_sha(sha: &str) -> &str {
+    sha.get(..7).unwrap_or(sha)
+}
+
+/// Exact next commands for `operation`, given unresolved conflicts.
+fn next_commands(operation: Operation, conflicted: bool) -> Vec<(&'static str, String)> {
+    let resolve = if conflicted {
+        "git add <files> && "
+    } else {
+        ""
+    };
+    let sequence = |cmd: &str| {
+        vec![
+            (
+                "continue",
+                format!("{}GIT_EDITOR=true git {} --continue", resolve, cmd),
+            ),
+            ("skip", format!("git {} --skip", cmd)),
+            ("abort", format!("git {} --abort", cmd)),
+        ]
+    };
+    match operation {
+        Operation::Rebase => sequence("rebase"),
+        Operation::Am => sequence("am"),
+        Operation::CherryPick => sequence("cherry-pick"),
+        Operation::Revert => sequence("revert"),
+        Operation::Merge => vec![
+            ("continue", format!("{}git commit --no-edit", resolve)),
+            ("abort", "git merge --abort".to_string()),
+        ],
+        Operation::Bisect => vec![
+            (
+                "mark",
+                "git bisect good | git bisect bad | git bisect skip".to_string(),
+            ),
+            ("automate", "git bisect run <test command>".to_string()),
+            ("finish", "git bisect reset".to_string()),
+        ],
+    }
+}
+
+fn format_state(state: &OperationState, head: &str) -> String {
+    let Some(operation) = state.operation else {
+        return format!(
+            "✓ no rebase, merge, cherry-pick, revert or bisect in progress (on {})",
+            head
+        );
+    };
+
+    let mut title = format!("🔀 {} in progress", operation.name());
+    if let Some(branch) = &state.branch {
+        title.push_str(&format!(": {}", branch));
+        if let Some(onto) = &state.onto {
+            title.push_str(&format!(" onto {}", onto));
+        }
+    }
+    if let Some((step, total)) = state.step {
+        title.push_str(&format!(", step {}/{}", step, total));
+    }
+    let mut out = vec![title];
+
+    if operation == Operation::Bisect {
+        let (good, bad) = state.bisect_marks;
+        out.push(format!(
+            "   {} good, {} bad marked; testing {}",
+            good,
+            bad,
+            state.current.as_deref().map(short_sha).unwrap_or(head)
+        ));
+        if let Some(subject) = &state.subject {
+            out.push(format!("   \"{}\"", subject));
+        }
+    } else {
+        let verb = match operation {
+            Operation::Merge => "merging",
+            _ => "stopped at",
+        };
+        match (&state.current, &state.subject) {
+            (Some(sha), Some(subject)) => {
+                out.push(format!("   {} {} \"{}\"", verb, short_sha(sha), subject))
+            }
+            (Some(sha), None) => out.push(format!("   {} {}", verb, short_sha(sha))),
+            (None, Some(subject)) => out.push(format!("   {} \"{}\"", verb, subject)),
+            (None, None) => {}
+        }
+    }
+
+    if !state.conflicts.is_empty() {
+        let mut files: Vec<&str> = state.conflicts.iter().take(5).map(String::as_str).collect();
+        if state.conflicts.len() > 5 {
+            files.push("...");
+        }
+        out.push(format!(
+            "   {} conflicted: {} (rtk conflicts for hunks)",
+            state.conflicts.len(),
+            files.join(", ")
+        ));
+    }
+
+    let commands = next_commands(operation, !state.conflicts.is_empty());
+    let width = commands.iter().map(|(l, _)| l.len()).max().unwrap_or(0) + 1;
+    for (label, command) in commands {
+        out.push(format!("{:<width$} {}", format!("{}:", label), command));
+    }
+    out.join("\n")
+}
+
+/// First line of `git <args>` output, if it succeeds.
+fn git_line(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+}
+
+fn run_state(verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+
+    let git_dir = git_line(&["rev-parse", "--absolute-git-dir"])
+        .context("Not a git repository (git rev-parse --absolute-git-dir failed)")?;
+    if verbose > 0 {
+        eprintln!("git dir: {}", git_dir);
+    }
+    let mut state = detect_operation(std::path::Path::new(&git_dir));
+
+    let head = git_line(&["rev-parse", "--abbrev-ref", "HEAD"])
+        .filter(|h| h != "HEAD")
+        .or_else(|| {
+            git_line(&["rev-parse", "--short", "HEAD"]).map(|sha| format!("detached {}", sha))
+        })
+        .unwrap_or_else(|| "no commits".to_string());
+
+    if state.operation.is_some() {
+        if let Some(onto) = state.onto.take() {
+            // `main (a1b2c3d)` rather than a bare sha
+            let name = git_line(&["name-rev", "--name-only", "--exclude=refs/tags/*", &onto])
+                .filter(|n| n != "undefined");
+            state.onto = Some(match name {
+                Some(name) => format!("{} ({})", name, short_sha(&onto)),
+                None => short_sha(&onto).to_string(),
+            });
+        }
+        if state.operation == Some(Operation::Bisect) {
+            state.current = git_line(&["rev-parse", "HEAD"]);
+        }
+        if state.subject.is_none() {
+            if let Some(sha) = &state.current {
+                state.subject = git_line(&["log", "-1", "--format=%s", sha]);
+            }
+        }
+        state.conflicts = Command::new("git")
+            .args(["diff", "--name-only", "--diff-filter=U"])
+            .output()
+            .map(|o| {
+                String::from_utf8_lossy(&o.stdout)
+                    .lines()
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+    }
+
+    let filtered = format_state(&state, &head);
+    println!("{}", filtered);
+
+    // What it replaces: git status plus reading the rebase todo/log
+    let raw = Command::new("git")
+        .arg("status")
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+        .unwrap_or_default();
+    timer.track("git status", "rtk git state", &raw, &filtered);
+    Ok(())
+}
+
+/// Branch line, pending changes, stashes, worktrees and recently active
+/// branches, from `git status --porcelain=v2 --branch`, `git stash list`,
+/// `git worktree list` and `git for-each-ref` output.
+fn format_overview(
+    status: &str,
+    stashes: &str,
+    worktrees: &str,
+    branches: &str,
+    operation: Option<Operation>,
+) -> String {
+    let mut head = "(unknown)";
+    let mut oid = "";
+    let mut upstream: Option<&str> = None;
+    let mut ab = (0i64, 0i64);
+    let (mut staged, mut modified, mut untracked, mut conflicted) = (0, 0, 0, 0);
+
+    for line in status.lines() {
+        if let Some(v) = line.strip_prefix("# branch.head ") {
+            head = v;
+        } else if let Some(v) = line.strip_prefix("# branch.oid ") {
+            oid = v;
+        } else if let Some(v) = line.strip_prefix("# branch.upstream ") {
+            upstream = Some(v);
+        } else if let Some(v) = line.strip_prefix("# branch.ab ") {
+            let mut nums = v.split_whitespace().filter_map(|n| n.parse::<i64>().ok());
+            ab = (nums.next().unwrap_or(0), nums.next().unwrap_or(0).abs());
+        } else if line.starts_with("1 ") || line.starts_with("2 ") {
+            let xy = line.as_bytes();
+            if xy.len() > 3 {
+                if xy[2] != b'.' {
+                    staged += 1;
+                }
+                if xy[3] != b'.' {
+                    modified += 1;
+                }
+            }
+        } else if line.starts_with("u ") {
+            conflicted += 1;
+        } else if line.starts_with("? ") {
+            untracked += 1;
+        }
+    }
+
+    let mut out = Vec::new();
+    let mut branch_line = if head == "(detached)" {
+        format!("* detached @ {}", short_sha(oid))
+    } else if oid == "(initial)" {
+        format!("* {} (no commits)", head)
+    } else {
+        format!("* {} @ {}", head, short_sha(oid))
+    };
+    match upstream {
+        Some(up) => {
+            branch_line.push_str(&format!(" → {}", up));
+            if ab == (0, 0) {
+                branch_line.push_str(" (up to date)");
+            } else {
+                if ab.0 > 0 {
+                    branch_line.push_str(&format!(" ↑{}", ab.0));
+                }
+                if ab.1 > 0 {
+                    branch_line.push_str(&format!(" ↓{}", ab.1));
+                }
+            }
+        }
+        None if head != "(detached)" => branch_line.push_str(" (no upstream)"),
+        None => {}
+    }
+    out.push(branch_line);
+
+    let changes: Vec<String> = [
+        (conflicted, "conflicted"),
+        (staged, "staged"),
+        (modified, "modified"),
+        (untracked, "untracked"),
+    ]
+    .iter()
+    .filter(|(n, _)| *n > 0)
+    .map(|(n, label)| format!("{} {}", n, label))
+    .collect();
+    if changes.is_empty() {
+        out.push("  clean".to_string());
+    } else {
+        out.push(format!("  {}", changes.join(", ")));
+    }
+    if let Some(op) = operation {
+        out.push(format!("  ⚠️ {} in progress → rtk git state", op.name()));
+    }
+
+    let stash_lines: Vec<&str> = stashes.lines().filter(|l| !l.trim().is_empty()).collect();
+    if !stash_lines.is_empty() {
+        out.push(format!("stashes ({}):", stash_lines.len()));
+        for line in stash_lines.iter().take(3) {
+            // `stash@{0}<TAB>2 days ago<TAB>WIP on main: ...`
+            let parts: Vec<&str> = line.splitn(3, '\t').collect();
+            match parts.as_slice() {
+                [id, age, subject] => out.push(format!("  {} ({}) {}", id, age, subject)),
+                _ => out.push(format!("  {}", line)),
+            }
+        }
+        if stash_lines.len() > 3 {
+            out.push(format!("  ... +{} more", stash_lines.len() - 3));
+        }
+    }
+
+    // The main worktree is always listed; only mention linked ones
+    let worktree_list = filter_worktree_list(worktrees);
+    let worktree_lines: Vec<&str> = worktree_list.lines().collect();
+    if worktree_lines.len() > 1 {
+        out.push(format!("worktrees ({}):", worktree_lines.len()));
+        for line in worktree_lines.iter().take(5) {
+            out.push(format!("  {}", line));
+        }
+        if worktree_lines.len() > 5 {
+            out.push(format!("  ... +{} more", worktree_lines.len() - 5));
+        }
+    }
+
+    let recent: Vec<Vec<&str>> = branches
+        .lines()
+        .map(|l| l.split('\t').collect::<Vec<_>>())
+        .filter(|parts| parts.len() >= 2 && parts[0] != head)
+        .take(5)
+        .collect();
+    if !recent.is_empty() {
+        out.push("recent branches:".to_string());
+        let width = recent.iter().map(|p| p[0].len()).max().unwrap_or(0);
+        for parts in &recent {
+            let track = parts.get(2).map(|t| t.trim()).unwrap_or("");
+            let line = format!("  {:width$}  {} {}", parts[0], parts[1], track);
+            out.push(line.trim_end().to_string());
+        }
+    }
+
+    out.join("\n")
+}
+
+/// Stdout of `git <args>`, empty if git fails.
+fn git_stdout(args: &[&str]) -> String {
+    Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+        .unwrap_or_default()
+}
+
+fn run_overview(verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+
+    let git_dir = git_line(&["rev-parse", "--absolute-git-dir"])
+        .context("Not a git repository (git rev-parse --absolute-git-dir failed)")?;
+    if verbose > 0 {
+        eprintln!("git dir: {}", git_dir);
+    }
+    let operation = detect_operation(std::path::Path::new(&git_dir)).operation;
+
+    let status = git_stdout(&["status", "--porcelain=v2", "--branch"]);
+    let stashes = git_stdout(&["stash", "list", "--format=%gd%x09%cr%x09%s"]);
+    let worktrees = git_stdout(&["worktree", "list"]);
+    let branches = git_stdout(&[
+        "for-each-ref",
+        "--sort=-committerdate",
+        "--count=6",
+        "--format=%(refname:short)%09%(committerdate:relative)%09%(upstream:track)",
+        "refs/heads",
+    ]);
+
+    let filtered = format_overview(&status, &stashes, &worktrees, &branches, operation);
+    println!("{}", filtered);
+
+    // What it replaces: the commands usually run one by one at session start
+    let raw = [
+        git_stdout(&["status"]),
+        git_stdout(&["stash", "list"]),
+        worktrees,
+        git_stdout(&["branch", "-vv"]),
+    ]
+    .join("\n");
+    timer.track(
+        "git status && git stash list && git worktree list && git branch -vv",
+        "rtk git overview",
+        &raw,
+        &filtered,
+    );
+    Ok(())
+}
+
 /// Runs an unsupported git subcommand by passing it through directly
 pub fn run_passthrough(args: &[OsString], verbose: u8) -> Result<()> {
     let timer = tracking::TimedExecution::start();
@@ -1273,13 +1792,13 @@ pub fn run_passthrough(args: &[OsString], verbose: u8) -> Result<()> {
         .context("Failed to run git")?;
 
     let args_str = tracking::args_display(args);
-    timer.track_passthrough(
+    timer.with_exit_code(status).track_passthrough(
         &format!("git {}", args_str),
         &format!("rtk git {} (passthrough)", args_str),
     );
 
     if !status.success() {
-        std::process::exit(status.code().unwrap_or(1));
+        exit_code::exit_with(status);
     }
     Ok(())
 }
@@ -1432,7 +1951,7 @@ M  file7.rs
 
     #[test]
     fn test_filter_log_output_truncate_long() {
-        let long_line = "abc1234 ".to_string() + &"x".repeat(100) + " (2 days ago) <author>";
+        let long_line = format!("abc1234 {} (2 days ago) <author>", "x".repeat(100));
         let result = filter_log_output(&long_line, 10);
         assert!(result.len() < long_line.len());
         assert!(result.contains("..."));
@@ -1477,4 +1996,104 @@ no changes added to commit (use "git add" and/or "git commit -a")
         let result = filter_status_with_args(output);
         assert!(result.contains("nothing to commit"));
     }
+
+    #[test]
+    fn test_detect_rebase_and_cherry_pick() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(detect_operation(dir.path()).operation, None);
+
+        let rebase = dir.path().join("rebase-merge");
+        std::fs::create_dir(&rebase).unwrap();
+        for (name, value) in [
+            ("msgnum", "3\n"),
+            ("end", "7\n"),
+            ("head-name", "refs/heads/feature\n"),
+            ("onto", "cbfaa55d0e\n"),
+            ("stopped-sha", "9f8e7d6c5b\n"),
+        ] {
+            std::fs::write(rebase.join(name), value).unwrap();
+        }
+        let state = detect_operation(dir.path());
+        assert_eq!(state.operation, Some(Operation::Rebase));
+        assert_eq!(state.step, Some((3, 7)));
+        assert_eq!(state.branch.as_deref(), Some("feature"));
+        assert_eq!(state.current.as_deref(), Some("9f8e7d6c5b"));
+
+        std::fs::remove_dir_all(&rebase).unwrap();
+        std::fs::write(dir.path().join("CHERRY_PICK_HEAD"), "abc1234def\n").unwrap();
+        std::fs::create_dir(dir.path().join("sequencer")).unwrap();
+        std::fs::write(
+            dir.path().join("sequencer/done"),
+            "pick a1 One\npick b2 Two\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("sequencer/todo"), "pick c3 Three\n").unwrap();
+        let state = detect_operation(dir.path());
+        assert_eq!(state.operation, Some(Operation::CherryPick));
+        assert_eq!(state.step, Some((2, 3)));
+    }
+
+    #[test]
+    fn test_format_state() {
+        let state = OperationState {
+            operation: Some(Operation::Rebase),
+            step: Some((3, 7)),
+            branch: Some("feature".into()),
+            onto: Some("main (cbfaa55)".into()),
+            current: Some("9f8e7d6c5b".into()),
+            subject: Some("Add retries".into()),
+            conflicts: vec!["src/net.rs".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            format_state(&state, "HEAD"),
+            "🔀 rebase in progress: feature onto main (cbfaa55), step 3/7\n   \
+             stopped at 9f8e7d6 \"Add retries\"\n   \
+             1 conflicted: src/net.rs (rtk conflicts for hunks)\n\
+             continue: git add <files> && GIT_EDITOR=true git rebase --continue\n\
+             skip:     git rebase --skip\n\
+             abort:    git rebase --abort"
+        );
+        assert_eq!(
+            format_state(&OperationState::default(), "main"),
+            "✓ no rebase, merge, cherry-pick, revert or bisect in progress (on main)"
+        );
+    }
+
+    #[test]
+    fn test_format_overview() {
+        let status = "# branch.oid 0e15a5e1234567890\n# branch.head feat\n# branch.upstream origin/feat\n# branch.ab +2 -1\n1 M. N... 100644 100644 100644 a b src/a.rs\n1 .M N... 100644 100644 100644 a b src/b.rs\n1 MM N... 100644 100644 100644 a b src/c.rs\nu UU N... 1 2 3 4 a b c src/d.rs\n? notes.txt\n";
+        let stashes = "stash@{0}\t2 days ago\tWIP on feat: 0e15a5e Bump x\n";
+        let worktrees = "/repo  0e15a5e [feat]\n/repo-main  cbfaa55 [main]\n";
+        let branches =
+            "feat\t1 hour ago\t[ahead 2, behind 1]\nmain\t3 days ago\t\nold\t2 weeks ago\t[gone]\n";
+        let out = format_overview(
+            status,
+            stashes,
+            worktrees,
+            branches,
+            Some(Operation::Rebase),
+        );
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "* feat @ 0e15a5e → origin/feat ↑2 ↓1");
+        assert_eq!(
+            lines[1],
+            "  1 conflicted, 2 staged, 2 modified, 1 untracked"
+        );
+        assert_eq!(lines[2], "  ⚠️ rebase in progress → rtk git state");
+        assert_eq!(lines[3], "stashes (1):");
+        assert_eq!(
+            lines[4],
+            "  stash@{0} (2 days ago) WIP on feat: 0e15a5e Bump x"
+        );
+        assert_eq!(lines[5], "worktrees (2):");
+        assert_eq!(lines[8], "recent branches:");
+        assert_eq!(lines[9], "  main  3 days ago");
+        assert_eq!(lines[10], "  old   2 weeks ago [gone]");
+
+        // Single worktree and no stashes are left out
+        let clean = "# branch.oid (initial)\n# branch.head main\n";
+        let out = format_overview(clean, "", "/repo  0000000 [main]\n", "", None);
+        assert_eq!(out, "* main (no commits) (no upstream)\n  clean");
+    }
 }
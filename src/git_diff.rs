@@ -0,0 +1,294 @@
+//! Streaming compaction of unified diffs.
+//!
+//! A monorepo `git diff` can run to hundreds of megabytes, so the diff is fed
+//! to a [`DiffCompactor`] one line at a time as git writes it; only the
+//! compacted output is kept. Each file gets a share of the remaining line
+//! budget when its header arrives, so one huge file can't crowd out the
+//! rest. Once a file's share is spent its remaining hunks are only counted,
+//! and once the whole budget is spent every later line is.
+
+/// Changed lines shown per hunk before it is cut.
+const MAX_HUNK_LINES: usize = 10;
+/// Smallest share of the budget a file gets, however many follow it.
+const MIN_FILE_LINES: usize = 20;
+
+/// Compact `diff` to at most about `max_lines` lines.
+pub fn compact_diff(diff: &str, max_lines: usize) -> String {
+    let files = diff
+        .lines()
+        .filter(|line| line.starts_with("diff --git"))
+        .count();
+    let mut compactor = DiffCompactor::new(max_lines, files);
+    for line in diff.lines() {
+        compactor.push(line);
+    }
+    compactor.finish()
+}
+
+/// Whether a line of compacted output quotes the diff (a hunk header,
+/// change or context line) rather than being rtk's own, for
+/// [`crate::profile::println_quoting`].
+pub fn is_hunk_line(line: &str) -> bool {
+    line.strip_prefix("  ")
+        .is_some_and(|rest| rest.starts_with(['+', '-', ' ', '@']))
+}
+
+/// Incremental [`compact_diff`]: [`push`](Self::push) every line, then
+/// [`finish`](Self::finish).
+pub struct DiffCompactor {
+    max_lines: usize,
+    /// Files the diff is expected to touch (e.g. from `--stat`); 0 if unknown
+    files_expected: usize,
+    files_seen: usize,
+    out: Vec<String>,
+    file: Option<FileDiff>,
+    /// Set once `max_lines` is reached: what was left out after that
+    overflow: Option<Overflow>,
+}
+
+#[derive(Default)]
+struct FileDiff {
+    /// From a `diff --git` header (bare `diff -u` output has none)
+    named: bool,
+    added: usize,
+    removed: usize,
+    /// Output lines this file may use, and has used
+    budget: usize,
+    used: usize,
+    in_hunk: bool,
+    /// Whether the current hunk is being shown
+    showing: bool,
+    hunk_lines: usize,
+    hidden_hunks: usize,
+}
+
+#[derive(Default)]
+struct Overflow {
+    files: usize,
+    added: usize,
+    removed: usize,
+}
+
+impl DiffCompactor {
+    pub fn new(max_lines: usize, files_expected: usize) -> Self {
+        Self {
+            max_lines,
+            files_expected,
+            files_seen: 0,
+            out: Vec::new(),
+            file: None,
+            overflow: None,
+        }
+    }
+
+    pub fn push(&mut self, line: &str) {
+        if line.starts_with("diff --git") {
+            self.close_file();
+            self.files_seen += 1;
+            if let Some(overflow) = self.overflow.as_mut() {
+                overflow.files += 1;
+            } else {
+                let name = line.split(" b/").nth(1).unwrap_or("unknown");
+                self.emit(format!("\n📄 {}", name));
+            }
+            self.file = Some(FileDiff {
+                named: true,
+                budget: self.file_budget(),
+                ..FileDiff::default()
+            });
+            return;
+        }
+        if line.starts_with("@@") {
+            let budget = self.file_budget();
+            let file = self.file.get_or_insert_with(|| FileDiff {
+                budget,
+                ..FileDiff::default()
+            });
+            file.in_hunk = true;
+            file.hunk_lines = 0;
+            file.showing = self.overflow.is_none() && file.used < file.budget;
+            if file.showing {
+                let hunk_info = line.split("@@").nth(1).unwrap_or("").trim();
+                self.emit_in_file(format!("  @@ {} @@", hunk_info));
+            } else if self.overflow.is_none() {
+                file.hidden_hunks += 1;
+            }
+            return;
+        }
+
+        let Some(file) = self.file.as_mut().filter(|f| f.in_hunk) else {
+            return;
+        };
+        let added = line.starts_with('+') && !line.starts_with("+++");
+        let removed = line.starts_with('-') && !line.starts_with("---");
+        if let Some(overflow) = self.overflow.as_mut() {
+            overflow.added += usize::from(added);
+            overflow.removed += usize::from(removed);
+            return;
+        }
+        file.added += usize::from(added);
+        file.removed += usize::from(removed);
+        if !file.showing || file.hunk_lines >= MAX_HUNK_LINES {
+            return;
+        }
+        // Context is shown between changes, not before the first one
+        let show = if added || removed {
+            true
+        } else {
+            file.hunk_lines > 0 && !line.starts_with('\\')
+        };
+        if show {
+            file.hunk_lines += 1;
+            self.emit_in_file(format!("  {}", line));
+        }
+        if let Some(file) = self.file.as_mut() {
+            if file.showing && (file.hunk_lines == MAX_HUNK_LINES || file.used >= file.budget) {
+                file.showing = false;
+                file.hunk_lines = MAX_HUNK_LINES;
+                self.emit("  ... (truncated)".to_string());
+            }
+        }
+    }
+
+    pub fn finish(mut self) -> String {
+        self.close_file();
+        if let Some(overflow) = self.overflow {
+            let mut note = "\n... (more changes truncated".to_string();
+            if overflow.added + overflow.removed > 0 {
+                note.push_str(&format!(": +{} -{}", overflow.added, overflow.removed));
+                if overflow.files > 0 {
+                    note.push_str(&format!(" in {} more files", overflow.files));
+                }
+            }
+            note.push(')');
+            self.out.push(note);
+        }
+        self.out.join("\n")
+    }
+
+    /// Share of the remaining budget for the next file.
+    fn file_budget(&self) -> usize {
+        let remaining = self.max_lines.saturating_sub(self.out.len());
+        let files_left = self
+            .files_expected
+            .saturating_sub(self.files_seen.saturating_sub(1))
+            .max(1);
+        (remaining / files_left).max(MIN_FILE_LINES)
+    }
+
+    fn close_file(&mut self) {
+        let Some(file) = self.file.take() else {
+            return;
+        };
+        if self.overflow.is_some() || !file.named {
+            return;
+        }
+        if file.hidden_hunks > 0 {
+            self.emit(format!("  ... +{} more hunks", file.hidden_hunks));
+        }
+        if file.added > 0 || file.removed > 0 {
+            self.emit(format!("  +{} -{}", file.added, file.removed));
+        }
+    }
+
+    fn emit_in_file(&mut self, line: String) {
+        if let Some(file) = self.file.as_mut() {
+            file.used += 1;
+        }
+        self.emit(line);
+    }
+
+    fn emit(&mut self, line: String) {
+        if self.overflow.is_some() {
+            return;
+        }
+        self.out.push(line);
+        if self.out.len() >= self.max_lines {
+            self.overflow = Some(Overflow::default());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_diff(name: &str, hunks: usize, changes: usize) -> String {
+        let mut diff = format!("diff --git a/{0} b/{0}\n--- a/{0}\n+++ b/{0}\n", name);
+        for h in 0..hunks {
+            diff.push_str(&format!("@@ -{0},3 +{0},4 @@ fn f{0}()\n", h * 100));
+            diff.push_str(" context\n");
+            for c in 0..changes {
+                diff.push_str(&format!("+added {}\n-removed {}\n", c, c));
+            }
+        }
+        diff
+    }
+
+    #[test]
+    fn test_small_diff_shown_whole() {
+        let diff = file_diff("foo.rs", 1, 2);
+        assert_eq!(
+            compact_diff(&diff, 100),
+            "\n📄 foo.rs\n  @@ -0,3 +0,4 @@\n  +added 0\n  -removed 0\n  +added 1\n  -removed 1\n  +2 -2"
+        );
+    }
+
+    #[test]
+    fn test_large_file_leaves_room_for_others() {
+        let diff = format!(
+            "{}{}",
+            file_diff("big.rs", 50, 5),
+            file_diff("small.rs", 1, 1)
+        );
+        let out = compact_diff(&diff, 60);
+        assert!(out.contains("📄 small.rs"), "{}", out);
+        assert!(out.contains("  +1 -1"));
+        assert!(out.contains("more hunks"));
+        // Totals still count the hunks that were not shown
+        assert!(out.contains("  +250 -250"), "{}", out);
+        assert!(out.lines().count() <= 70);
+    }
+
+    #[test]
+    fn test_overflow_counts_what_was_left_out() {
+        let diff: String = (0..20)
+            .map(|i| file_diff(&format!("f{}.rs", i), 1, 3))
+            .collect();
+        let out = compact_diff(&diff, 30);
+        let last = out.lines().last().unwrap();
+        assert!(
+            last.starts_with("... (more changes truncated: +") && last.ends_with("more files)"),
+            "{}",
+            last
+        );
+    }
+
+    #[test]
+    fn test_hunk_lines_are_told_from_rtk_lines() {
+        let diff = "diff --git a/a.rs b/a.rs\n@@ -1,3 +1,4 @@\n-x  =  1;\n+x\t= 1; // ✅\n \n+y\n";
+        let out = compact_diff(diff, 100);
+        let quoted: Vec<_> = out.lines().filter(|l| is_hunk_line(l)).collect();
+        assert_eq!(
+            quoted,
+            // The file's totals read as a change line; printing them as
+            // written is harmless
+            [
+                "  @@ -1,3 +1,4 @@",
+                "  -x  =  1;",
+                "  +x\t= 1; // ✅",
+                "   ",
+                "  +y",
+                "  +2 -1"
+            ]
+        );
+        assert!(!is_hunk_line("📄 a.rs"));
+        assert!(!is_hunk_line("  ... (truncated)"));
+    }
+
+    #[test]
+    fn test_bare_unified_diff() {
+        let diff = "--- a.txt\n+++ b.txt\n@@ -1 +1 @@\n-old\n+new\n";
+        assert_eq!(compact_diff(diff, 100), "  @@ -1 +1 @@\n  -old\n  +new");
+    }
+}
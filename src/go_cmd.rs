@@ -1,6 +1,8 @@
+use crate::exit_code;
+use crate::parser::lossy;
 use crate::tracking;
 use crate::utils::truncate;
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::ffi::OsString;
@@ -57,8 +59,9 @@ pub fn run_test(args: &[String], verbose: u8) -> Result<()> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
-    let filtered = filter_go_test_json(&stdout);
+    let filtered = lossy::degrade("go test", &stdout, filter_go_test_json(&stdout))?;
 
     println!("{}", filtered);
 
@@ -67,7 +70,7 @@ pub fn run_test(args: &[String], verbose: u8) -> Result<()> {
         eprintln!("{}", stderr.trim());
     }
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("go test {}", args.join(" ")),
         &format!("rtk go test {}", args.join(" ")),
         &raw,
@@ -76,7 +79,7 @@ pub fn run_test(args: &[String], verbose: u8) -> Result<()> {
 
     // Preserve exit code for CI/CD
     if !output.status.success() {
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     Ok(())
@@ -103,6 +106,7 @@ pub fn run_build(args: &[String], verbose: u8) -> Result<()> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
     let filtered = filter_go_build(&raw);
 
@@ -110,7 +114,7 @@ pub fn run_build(args: &[String], verbose: u8) -> Result<()> {
         println!("{}", filtered);
     }
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("go build {}", args.join(" ")),
         &format!("rtk go build {}", args.join(" ")),
         &raw,
@@ -119,7 +123,7 @@ pub fn run_build(args: &[String], verbose: u8) -> Result<()> {
 
     // Preserve exit code for CI/CD
     if !output.status.success() {
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     Ok(())
@@ -146,6 +150,7 @@ pub fn run_vet(args: &[String], verbose: u8) -> Result<()> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
     let filtered = filter_go_vet(&raw);
 
@@ -153,7 +158,7 @@ pub fn run_vet(args: &[String], verbose: u8) -> Result<()> {
         println!("{}", filtered);
     }
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("go vet {}", args.join(" ")),
         &format!("rtk go vet {}", args.join(" ")),
         &raw,
@@ -162,7 +167,7 @@ pub fn run_vet(args: &[String], verbose: u8) -> Result<()> {
 
     // Preserve exit code for CI/CD
     if !output.status.success() {
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     Ok(())
@@ -194,11 +199,12 @@ pub fn run_other(args: &[OsString], verbose: u8) -> Result<()> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
     print!("{}", stdout);
     eprint!("{}", stderr);
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("go {}", subcommand),
         &format!("rtk go {}", subcommand),
         &raw,
@@ -207,16 +213,17 @@ pub fn run_other(args: &[OsString], verbose: u8) -> Result<()> {
 
     // Preserve exit code
     if !output.status.success() {
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     Ok(())
 }
 
-/// Parse go test -json output (NDJSON format)
-fn filter_go_test_json(output: &str) -> String {
+/// Parse go test -json output (NDJSON format); an error when no line is an event
+pub(crate) fn filter_go_test_json(output: &str) -> Result<String> {
     let mut packages: HashMap<String, PackageResult> = HashMap::new();
     let mut current_test_output: HashMap<(String, String), Vec<String>> = HashMap::new(); // (package, test) -> outputs
+    let mut events = 0;
 
     for line in output.lines() {
         let trimmed = line.trim();
@@ -228,6 +235,7 @@ fn filter_go_test_json(output: &str) -> String {
             Ok(e) => e,
             Err(_) => continue, // Skip non-JSON lines
         };
+        events += 1;
 
         let package = event.package.unwrap_or_else(|| "unknown".to_string());
         let pkg_result = packages.entry(package.clone()).or_default();
@@ -267,6 +275,10 @@ fn filter_go_test_json(output: &str) -> String {
         }
     }
 
+    if events == 0 && !output.trim().is_empty() {
+        bail!("no go test -json events in output");
+    }
+
     // Build summary
     let total_packages = packages.len();
     let total_pass: usize = packages.values().map(|p| p.pass).sum();
@@ -274,14 +286,14 @@ fn filter_go_test_json(output: &str) -> String {
     let total_skip: usize = packages.values().map(|p| p.skip).sum();
 
     if total_fail == 0 && total_pass == 0 {
-        return "Go test: No tests found".to_string();
+        return Ok("Go test: No tests found".to_string());
     }
 
     if total_fail == 0 {
-        return format!(
+        return Ok(format!(
             "✓ Go test: {} passed in {} packages",
             total_pass, total_packages
-        );
+        ));
     }
 
     let mut result = String::new();
@@ -322,6 +334,8 @@ fn filter_go_test_json(output: &str) -> String {
                         && (lower.contains("error")
                             || lower.contains("expected")
                             || lower.contains("got")
+                            || lower.contains("want")
+                            || line.contains("_test.go:")
                             || lower.contains("panic")
                             || line.trim().starts_with("at "))
                 })
@@ -334,7 +348,7 @@ fn filter_go_test_json(output: &str) -> String {
         }
     }
 
-    result.trim().to_string()
+    Ok(result.trim().to_string())
 }
 
 /// Filter go build output - show only errors
@@ -433,7 +447,7 @@ mod tests {
 {"Time":"2024-01-01T10:00:02Z","Action":"pass","Package":"example.com/foo","Test":"TestBar","Elapsed":0.5}
 {"Time":"2024-01-01T10:00:02Z","Action":"pass","Package":"example.com/foo","Elapsed":0.5}"#;
 
-        let result = filter_go_test_json(output);
+        let result = filter_go_test_json(output).unwrap();
         assert!(result.contains("✓ Go test"));
         assert!(result.contains("1 passed"));
         assert!(result.contains("1 packages"));
@@ -447,12 +461,18 @@ mod tests {
 {"Time":"2024-01-01T10:00:03Z","Action":"fail","Package":"example.com/foo","Test":"TestFail","Elapsed":0.5}
 {"Time":"2024-01-01T10:00:03Z","Action":"fail","Package":"example.com/foo","Elapsed":0.5}"#;
 
-        let result = filter_go_test_json(output);
+        let result = filter_go_test_json(output).unwrap();
         assert!(result.contains("1 failed"));
         assert!(result.contains("TestFail"));
         assert!(result.contains("expected 5, got 3"));
     }
 
+    #[test]
+    fn test_filter_go_test_without_events() {
+        assert!(filter_go_test_json("FAIL\texample.com/foo [setup failed]\n").is_err());
+        assert_eq!(filter_go_test_json("").unwrap(), "Go test: No tests found");
+    }
+
     #[test]
     fn test_filter_go_build_success() {
         let output = "";
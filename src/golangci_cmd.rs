@@ -62,6 +62,7 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
     let filtered = filter_golangci_json(&stdout);
 
@@ -72,7 +73,7 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
         eprintln!("{}", stderr.trim());
     }
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("golangci-lint {}", args.join(" ")),
         &format!("rtk golangci-lint {}", args.join(" ")),
         &raw,
@@ -1,9 +1,18 @@
+use crate::encoding;
+use crate::mapped;
+use crate::parallel;
+use crate::path_rules::{PathAction, PathRules};
 use crate::tracking;
+use crate::utils::slash_path;
 use anyhow::{Context, Result};
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::process::Command;
 
+/// Match lines per parallel work item.
+const CLEAN_CHUNK: usize = 512;
+
 pub fn run(
     pattern: &str,
     path: &str,
@@ -36,47 +45,58 @@ pub fn run(
         .or_else(|_| Command::new("grep").args(["-rn", pattern, path]).output())
         .context("grep/rg failed")?;
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-
-    let raw_output = stdout.to_string();
+    // Matches in latin-1 or mixed-encoding files come through as raw bytes
+    let (stdout, detected) = encoding::decode(&output.stdout);
+    encoding::notice("grep output", detected);
 
     if stdout.trim().is_empty() {
         let msg = format!("🔍 0 for '{}'", pattern);
         println!("{}", msg);
-        timer.track(
+        timer.with_exit_code(output.status).track(
             &format!("grep -rn '{}' {}", pattern, path),
             "rtk grep",
-            &raw_output,
+            &stdout,
             &msg,
         );
         return Ok(());
     }
 
-    let mut by_file: HashMap<String, Vec<(usize, String)>> = HashMap::new();
+    let rules = PathRules::load();
+    let context = context_only
+        .then(|| Regex::new(&format!("(?i).{{0,20}}{}.*", regex::escape(pattern))).ok())
+        .flatten();
+    let mut by_file: HashMap<String, Vec<(usize, &str)>> = HashMap::new();
+    // Files under summarize_paths: match count only, no lines
+    let mut summarized: HashSet<String> = HashSet::new();
     let mut total = 0;
 
-    for line in stdout.lines() {
-        let parts: Vec<&str> = line.splitn(3, ':').collect();
-
-        let (file, line_num, content) = if parts.len() == 3 {
-            let ln = parts[1].parse().unwrap_or(0);
-            (parts[0].to_string(), ln, parts[2])
-        } else if parts.len() == 2 {
-            let ln = parts[0].parse().unwrap_or(0);
-            (path.to_string(), ln, parts[1])
-        } else {
-            continue;
-        };
-
+    // Path rules dominate on large result sets; only shown lines are cleaned
+    let lines: Vec<&str> = mapped::lines(&stdout).collect();
+    let chunks: Vec<&[&str]> = lines.chunks(CLEAN_CHUNK).collect();
+    let parsed = parallel::map(&chunks, |chunk| {
+        chunk
+            .iter()
+            .filter_map(|line| {
+                let (file, line_num, content) = parse_match_line(line, path)?;
+                let action = rules.action(Path::new(&file), false);
+                (action != PathAction::Exclude).then_some((file, line_num, content, action))
+            })
+            .collect::<Vec<_>>()
+    });
+
+    for (file, line_num, content, action) in parsed.into_iter().flatten() {
+        if action == PathAction::Summarize {
+            summarized.insert(file.clone());
+        }
         total += 1;
-        let cleaned = clean_line(content, max_line_len, context_only, pattern);
-        by_file.entry(file).or_default().push((line_num, cleaned));
+        by_file.entry(file).or_default().push((line_num, content));
     }
 
     let mut rtk_output = String::new();
     rtk_output.push_str(&format!("🔍 {} in {}F:\n\n", total, by_file.len()));
 
     let mut shown = 0;
+    let mut collapsed = 0;
     let mut files: Vec<_> = by_file.iter().collect();
     files.sort_by_key(|(f, _)| *f);
 
@@ -86,10 +106,20 @@ pub fn run(
         }
 
         let file_display = compact_path(file);
+        if summarized.contains(file.as_str()) {
+            rtk_output.push_str(&format!(
+                "📄 {} ({}, summarized)\n\n",
+                file_display,
+                matches.len()
+            ));
+            collapsed += matches.len();
+            continue;
+        }
         rtk_output.push_str(&format!("📄 {} ({}):\n", file_display, matches.len()));
 
         for (line_num, content) in matches.iter().take(10) {
-            rtk_output.push_str(&format!("  {:>4}: {}\n", line_num, content));
+            let cleaned = clean_line(content, max_line_len, context.as_ref(), pattern);
+            rtk_output.push_str(&format!("  {:>4}: {}\n", line_num, cleaned));
             shown += 1;
             if shown >= max_results {
                 break;
@@ -102,32 +132,40 @@ pub fn run(
         rtk_output.push('\n');
     }
 
-    if total > shown {
-        rtk_output.push_str(&format!("... +{}\n", total - shown));
+    if total > shown + collapsed {
+        rtk_output.push_str(&format!("... +{}\n", total - shown - collapsed));
     }
 
-    print!("{}", rtk_output);
-    timer.track(
+    crate::profile::println_quoting(
+        rtk_output.strip_suffix('\n').unwrap_or(&rtk_output),
+        is_match_line,
+    );
+    timer.with_exit_code(output.status).track(
         &format!("grep -rn '{}' {}", pattern, path),
         "rtk grep",
-        &raw_output,
+        &stdout,
         &rtk_output,
     );
 
     Ok(())
 }
 
-fn clean_line(line: &str, max_len: usize, context_only: bool, pattern: &str) -> String {
+/// Trim a match line to `max_len` around `pattern`; with a `context`
+/// regex (`--context-only`), just the text it matches when that fits.
+/// `  <n>: <line>` quotes a match; headers and counts are rtk's.
+fn is_match_line(line: &str) -> bool {
+    line.trim_start()
+        .split_once(": ")
+        .is_some_and(|(n, _)| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn clean_line(line: &str, max_len: usize, context: Option<&Regex>, pattern: &str) -> String {
     let trimmed = line.trim();
 
-    if context_only {
-        if let Ok(re) = Regex::new(&format!("(?i).{{0,20}}{}.*", regex::escape(pattern))) {
-            if let Some(m) = re.find(trimmed) {
-                let matched = m.as_str();
-                if matched.len() <= max_len {
-                    return matched.to_string();
-                }
-            }
+    if let Some(m) = context.and_then(|re| re.find(trimmed)) {
+        let matched = m.as_str();
+        if matched.len() <= max_len {
+            return matched.to_string();
         }
     }
 
@@ -160,6 +198,28 @@ fn clean_line(line: &str, max_len: usize, context_only: bool, pattern: &str) ->
     }
 }
 
+/// Split a `file:line:content` match. The file may start with a Windows
+/// drive (`C:\src\main.rs:12:...`); single-file searches omit it.
+fn parse_match_line<'a>(line: &'a str, path: &str) -> Option<(String, usize, &'a str)> {
+    let bytes = line.as_bytes();
+    let drive = bytes.len() > 2
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && matches!(bytes[2], b'\\' | b'/');
+    let skip = if drive { 2 } else { 0 };
+    let parts: Vec<&str> = line[skip..].splitn(3, ':').collect();
+
+    match parts.as_slice() {
+        [file, ln, content] => Some((
+            slash_path(&format!("{}{}", &line[..skip], file)),
+            ln.parse().unwrap_or(0),
+            content,
+        )),
+        [ln, content] if !drive => Some((slash_path(path), ln.parse().unwrap_or(0), content)),
+        _ => None,
+    }
+}
+
 fn compact_path(path: &str) -> String {
     if path.len() <= 50 {
         return path.to_string();
@@ -182,14 +242,40 @@ fn compact_path(path: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_match_line_windows_paths() {
+        assert_eq!(
+            parse_match_line(r"C:\repo\src\main.rs:12:fn main() {", "."),
+            Some(("C:/repo/src/main.rs".to_string(), 12, "fn main() {"))
+        );
+        assert_eq!(
+            parse_match_line(r"src\lib.rs:3:use std::io;", "."),
+            Some(("src/lib.rs".to_string(), 3, "use std::io;"))
+        );
+        assert_eq!(
+            parse_match_line("7:let x = 1;", "src/main.rs"),
+            Some(("src/main.rs".to_string(), 7, "let x = 1;"))
+        );
+        assert_eq!(parse_match_line("no match here", "."), None);
+    }
+
     #[test]
     fn test_clean_line() {
         let line = "            const result = someFunction();";
-        let cleaned = clean_line(line, 50, false, "result");
+        let cleaned = clean_line(line, 50, None, "result");
         assert!(!cleaned.starts_with(' '));
         assert!(cleaned.len() <= 50);
     }
 
+    #[test]
+    fn test_match_lines_are_quoted() {
+        assert!(is_match_line("    12: let x  =  1; // ✅ ok"));
+        assert!(is_match_line("  1234: "));
+        assert!(!is_match_line("📄 src/main.rs (3):"));
+        assert!(!is_match_line("🔍 3 in 1F:"));
+        assert!(!is_match_line("  +5"));
+    }
+
     #[test]
     fn test_compact_path() {
         let path = "/Users/patrick/dev/project/src/components/Button.tsx";
@@ -0,0 +1,652 @@
+//! `rtk html [file|-]` — readable text from an HTML page.
+//!
+//! Readability-style extraction without a DOM: the page is tokenized,
+//! scripts, styles and boilerplate (`nav`, `header`, `footer`, `aside`,
+//! forms, and elements whose class/id says menu, sidebar, cookie, share...)
+//! are skipped, and when the page has an `<article>` or `<main>` only the
+//! one with the most text is kept. What remains is rendered as markdown-ish
+//! text (headings, list items, code blocks, table rows) with numbered link
+//! references, followed by the list of links.
+
+use crate::tracking;
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::io::Read;
+use std::path::Path;
+
+/// Links listed after the text.
+const MAX_LINKS: usize = 50;
+
+const VOID: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+/// Elements whose content is never text.
+const SKIP: &[&str] = &[
+    "script", "style", "noscript", "svg", "template", "iframe", "canvas", "select", "button",
+];
+/// Page chrome around the content.
+const BOILERPLATE: &[&str] = &["nav", "header", "footer", "aside", "form", "dialog"];
+const BLOCK: &[&str] = &[
+    "p",
+    "div",
+    "section",
+    "article",
+    "main",
+    "ul",
+    "ol",
+    "dl",
+    "dt",
+    "dd",
+    "table",
+    "tr",
+    "blockquote",
+    "figure",
+    "figcaption",
+    "details",
+    "summary",
+    "hr",
+    "br",
+];
+
+lazy_static! {
+    static ref TAG: Regex =
+        Regex::new(r#"(?s)<!--.*?-->|<!\[CDATA\[.*?\]\]>|<[!?][^>]*>|<(/?)([a-zA-Z][\w:-]*)((?:[^>"']|"[^"]*"|'[^']*')*)>"#)
+            .unwrap();
+    static ref ATTR: Regex =
+        Regex::new(r#"([\w:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#).unwrap();
+    static ref CHROME: Regex = Regex::new(
+        r"(?i)(^|[\s_-])(nav|navbar|menu|sidebar|footer|header|breadcrumbs?|cookie|consent|banner|share|social|related|comments?|advert|ads?|promo|newsletter|subscribe|popup|modal|skip-link|toc)($|[\s_-])"
+    )
+    .unwrap();
+    static ref ENTITY: Regex = Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap();
+    static ref SPACES: Regex = Regex::new(r"[ \t\r\n\u{a0}]+").unwrap();
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Open {
+        name: String,
+        attrs: &'a str,
+        self_closing: bool,
+    },
+    Close(String),
+    Text(&'a str),
+}
+
+/// Extracted page: title, markdown-ish text and links in reference order.
+#[derive(Debug, Default, PartialEq)]
+pub struct Readable {
+    pub title: Option<String>,
+    pub text: String,
+    pub links: Vec<(String, String)>,
+}
+
+impl Readable {
+    pub fn render(&self, with_links: bool) -> String {
+        let mut out = String::new();
+        if let Some(title) = &self.title {
+            if !self.text.starts_with(&format!("# {}", title)) {
+                out.push_str(&format!("# {}\n\n", title));
+            }
+        }
+        out.push_str(&self.text);
+        if with_links && !self.links.is_empty() {
+            out.push_str(&format!("\n\nLinks ({}):", self.links.len()));
+            for (i, (text, href)) in self.links.iter().enumerate().take(MAX_LINKS) {
+                out.push_str(&format!("\n[{}] {}: {}", i + 1, text, href));
+            }
+            if self.links.len() > MAX_LINKS {
+                out.push_str(&format!(
+                    "\n... +{} more links",
+                    self.links.len() - MAX_LINKS
+                ));
+            }
+        }
+        out
+    }
+}
+
+pub fn run(file: &Path, base: Option<&str>, links: bool, verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let (name, html) = if file == Path::new("-") {
+        let mut buf = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut buf)
+            .context("Failed to read from stdin")?;
+        ("-".to_string(), String::from_utf8_lossy(&buf).into_owned())
+    } else {
+        let buf = std::fs::read(file)
+            .with_context(|| format!("Failed to read file: {}", file.display()))?;
+        (
+            file.display().to_string(),
+            String::from_utf8_lossy(&buf).into_owned(),
+        )
+    };
+    let readable = extract(&html, base);
+    if verbose > 0 {
+        eprintln!(
+            "{} bytes of HTML -> {} bytes of text, {} links",
+            html.len(),
+            readable.text.len(),
+            readable.links.len()
+        );
+    }
+    let output = readable.render(links);
+    println!("{}", output);
+    timer.track(
+        &format!("cat {}", name),
+        &format!("rtk html {}", name),
+        &html,
+        &output,
+    );
+    Ok(())
+}
+
+fn tokenize(html: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    let mut raw_until: Option<String> = None;
+    for caps in TAG.captures_iter(html) {
+        let whole = caps.get(0).unwrap();
+        let name = caps.get(2).map(|m| m.as_str().to_ascii_lowercase());
+        let closing = caps.get(1).is_some_and(|m| !m.is_empty());
+        // Inside <script>/<style>, only the matching close tag counts
+        if let Some(raw) = &raw_until {
+            if !(closing && name.as_deref() == Some(raw.as_str())) {
+                continue;
+            }
+            raw_until = None;
+        }
+        if whole.start() > pos {
+            tokens.push(Token::Text(&html[pos..whole.start()]));
+        }
+        pos = whole.end();
+        let Some(name) = name else {
+            continue; // comment, doctype, CDATA
+        };
+        if closing {
+            tokens.push(Token::Close(name));
+            continue;
+        }
+        let attrs = caps.get(3).map_or("", |m| m.as_str());
+        if matches!(name.as_str(), "script" | "style") {
+            raw_until = Some(name.clone());
+        }
+        tokens.push(Token::Open {
+            self_closing: attrs.trim_end().ends_with('/') || VOID.contains(&name.as_str()),
+            name,
+            attrs,
+        });
+    }
+    if pos < html.len() && raw_until.is_none() {
+        tokens.push(Token::Text(&html[pos..]));
+    }
+    tokens
+}
+
+fn attr(attrs: &str, name: &str) -> Option<String> {
+    ATTR.captures_iter(attrs)
+        .find(|c| c[1].eq_ignore_ascii_case(name))
+        .map(|c| {
+            let value = c
+                .get(2)
+                .or(c.get(3))
+                .or(c.get(4))
+                .map_or("", |m| m.as_str());
+            decode_entities(value)
+        })
+}
+
+fn is_chrome(name: &str, attrs: &str) -> bool {
+    if BOILERPLATE.contains(&name) || SKIP.contains(&name) {
+        return true;
+    }
+    if attr(attrs, "hidden").is_some() || attr(attrs, "aria-hidden").as_deref() == Some("true") {
+        return true;
+    }
+    if matches!(
+        attr(attrs, "role").as_deref(),
+        Some("navigation" | "banner" | "contentinfo" | "complementary" | "search")
+    ) {
+        return true;
+    }
+    // The content container itself may carry words like "post-header"
+    if matches!(name, "article" | "main" | "body" | "html") {
+        return false;
+    }
+    let class_id = format!(
+        "{} {}",
+        attr(attrs, "class").unwrap_or_default(),
+        attr(attrs, "id").unwrap_or_default()
+    );
+    CHROME.is_match(&class_id)
+}
+
+/// Index range of the tokens inside the element opened at `start`.
+fn element_end(tokens: &[Token], start: usize) -> usize {
+    let Token::Open { name, .. } = &tokens[start] else {
+        return start;
+    };
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(start) {
+        match token {
+            Token::Open {
+                name: n,
+                self_closing: false,
+                ..
+            } if n == name => depth += 1,
+            Token::Close(n) if n == name => {
+                depth -= 1;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => {}
+        }
+    }
+    tokens.len()
+}
+
+/// The `<article>`/`<main>` with the most text, else the whole page.
+fn content_range(tokens: &[Token]) -> (usize, usize) {
+    let mut best: Option<(usize, usize, usize)> = None;
+    for (i, token) in tokens.iter().enumerate() {
+        let Token::Open { name, attrs, .. } = token else {
+            continue;
+        };
+        let main =
+            name == "article" || name == "main" || attr(attrs, "role").as_deref() == Some("main");
+        if !main {
+            continue;
+        }
+        let end = element_end(tokens, i);
+        let text: usize = tokens[i..end]
+            .iter()
+            .map(|t| match t {
+                Token::Text(t) => t.trim().len(),
+                _ => 0,
+            })
+            .sum();
+        if best.is_none_or(|(_, _, best)| text > best) {
+            best = Some((i, end, text));
+        }
+    }
+    match best {
+        Some((start, end, text)) if text > 0 => (start, end),
+        _ => (0, tokens.len()),
+    }
+}
+
+/// Readable text of `html`; relative links resolve against `base`.
+pub fn extract(html: &str, base: Option<&str>) -> Readable {
+    let tokens = tokenize(html);
+    let title = tokens
+        .iter()
+        .position(|t| matches!(t, Token::Open { name, .. } if name == "title"))
+        .and_then(|i| match tokens.get(i + 1) {
+            Some(Token::Text(t)) => Some(clean(t)),
+            _ => None,
+        })
+        .filter(|t| !t.is_empty());
+    let (start, end) = content_range(&tokens);
+
+    let mut renderer = Renderer::new(base);
+    let mut i = start;
+    while i < end {
+        match &tokens[i] {
+            Token::Open {
+                name,
+                attrs,
+                self_closing,
+            } => {
+                if name == "head" || (!self_closing && is_chrome(name, attrs)) {
+                    i = element_end(&tokens, i) + 1;
+                    continue;
+                }
+                renderer.open(name, attrs);
+            }
+            Token::Close(name) => renderer.close(name),
+            Token::Text(text) => renderer.text(text),
+        }
+        i += 1;
+    }
+    Readable {
+        title,
+        text: renderer.finish(),
+        links: renderer.links,
+    }
+}
+
+struct Renderer<'a> {
+    base: Option<&'a str>,
+    lines: Vec<String>,
+    current: String,
+    pre: usize,
+    list_depth: usize,
+    /// Open `<a>`: its href and where its text starts in `current`
+    link: Option<(String, usize)>,
+    links: Vec<(String, String)>,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(base: Option<&'a str>) -> Self {
+        Self {
+            base,
+            lines: Vec::new(),
+            current: String::new(),
+            pre: 0,
+            list_depth: 0,
+            link: None,
+            links: Vec::new(),
+        }
+    }
+
+    fn break_line(&mut self) {
+        let line = self.current.trim_end().to_string();
+        self.current.clear();
+        if !line.trim().is_empty() {
+            self.lines.push(line);
+        }
+    }
+
+    fn paragraph(&mut self) {
+        self.break_line();
+        if self.lines.last().is_some_and(|l| !l.is_empty()) {
+            self.lines.push(String::new());
+        }
+    }
+
+    fn open(&mut self, name: &str, attrs: &str) {
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.paragraph();
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                self.current.push_str(&format!("{} ", "#".repeat(level)));
+            }
+            "pre" => {
+                self.paragraph();
+                self.lines.push("```".to_string());
+                self.pre += 1;
+            }
+            "li" => {
+                self.break_line();
+                self.current.push_str(&format!(
+                    "{}- ",
+                    "  ".repeat(self.list_depth.saturating_sub(1))
+                ));
+            }
+            "ul" | "ol" => {
+                self.break_line();
+                self.list_depth += 1;
+            }
+            "td" | "th" if !self.current.trim().is_empty() => self.current.push_str(" | "),
+            "code" if self.pre == 0 => self.current.push('`'),
+            "img" => {
+                if let Some(alt) = attr(attrs, "alt").filter(|a| !a.trim().is_empty()) {
+                    self.current.push_str(&format!("[image: {}]", alt.trim()));
+                }
+            }
+            "a" => {
+                if let Some(href) = attr(attrs, "href") {
+                    self.link = Some((href, self.current.len()));
+                }
+            }
+            "p" | "blockquote" | "table" | "figure" | "hr" => self.paragraph(),
+            "br" if self.pre > 0 => self.current.push('\n'),
+            _ if BLOCK.contains(&name) => self.break_line(),
+            _ => {}
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "p" | "blockquote" | "table" => {
+                self.paragraph()
+            }
+            "pre" if self.pre > 0 => {
+                let code = std::mem::take(&mut self.current);
+                let code = code.trim_matches('\n');
+                self.lines
+                    .extend(code.lines().map(|l| l.trim_end().to_string()));
+                self.lines.push("```".to_string());
+                self.lines.push(String::new());
+                self.pre -= 1;
+            }
+            "ul" | "ol" => {
+                self.break_line();
+                self.list_depth = self.list_depth.saturating_sub(1);
+                if self.list_depth == 0 {
+                    self.paragraph();
+                }
+            }
+            "code" if self.pre == 0 => self.current.push('`'),
+            "a" => self.close_link(),
+            _ if BLOCK.contains(&name) || name == "li" => self.break_line(),
+            _ => {}
+        }
+    }
+
+    fn close_link(&mut self) {
+        let Some((href, start)) = self.link.take() else {
+            return;
+        };
+        let text = clean(self.current.get(start..).unwrap_or(""));
+        if text.is_empty() || href.starts_with('#') || href.starts_with("javascript:") {
+            return;
+        }
+        let href = resolve(self.base, &href);
+        let index = match self.links.iter().position(|(_, h)| *h == href) {
+            Some(i) => i,
+            None => {
+                self.links.push((text, href));
+                self.links.len() - 1
+            }
+        };
+        self.current.push_str(&format!(" [{}]", index + 1));
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.pre > 0 {
+            self.current.push_str(&decode_entities(text));
+            return;
+        }
+        let text = SPACES.replace_all(text, " ");
+        if text == " " && (self.current.is_empty() || self.current.ends_with(' ')) {
+            return;
+        }
+        let text = decode_entities(&text);
+        if self.current.trim().is_empty() || self.current.ends_with(' ') {
+            self.current.push_str(text.trim_start());
+        } else {
+            self.current.push_str(&text);
+        }
+    }
+
+    fn finish(&mut self) -> String {
+        self.break_line();
+        while self.lines.last().is_some_and(|l| l.is_empty()) {
+            self.lines.pop();
+        }
+        let mut out: Vec<&str> = Vec::new();
+        for line in &self.lines {
+            // Headings and bullets left empty by skipped content
+            let marker = line.trim().trim_start_matches(['#', '-']);
+            if !line.is_empty() && marker.trim().is_empty() {
+                continue;
+            }
+            if line.is_empty() && out.last().is_some_and(|l| l.is_empty()) {
+                continue;
+            }
+            out.push(line);
+        }
+        out.join("\n").trim().to_string()
+    }
+}
+
+/// `href` made absolute against `base` (scheme-relative, root-relative and
+/// path-relative forms).
+fn resolve(base: Option<&str>, href: &str) -> String {
+    let Some(base) = base else {
+        return href.to_string();
+    };
+    if href.contains("://") || href.starts_with("mailto:") {
+        return href.to_string();
+    }
+    let Some((scheme, rest)) = base.split_once("://") else {
+        return href.to_string();
+    };
+    if let Some(host_path) = href.strip_prefix("//") {
+        return format!("{}://{}", scheme, host_path);
+    }
+    let host = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    if href.starts_with('/') {
+        return format!("{}://{}{}", scheme, host, href);
+    }
+    let path = rest[host.len()..].split(['?', '#']).next().unwrap_or("");
+    let dir = path.rfind('/').map_or("/", |i| &path[..=i]);
+    format!(
+        "{}://{}{}{}",
+        scheme,
+        host,
+        dir,
+        href.trim_start_matches("./")
+    )
+}
+
+fn clean(text: &str) -> String {
+    decode_entities(SPACES.replace_all(text, " ").trim())
+}
+
+pub(crate) fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    ENTITY
+        .replace_all(text, |caps: &regex::Captures| {
+            let entity = &caps[1];
+            let decoded = if let Some(hex) = entity.strip_prefix("#x").or(entity.strip_prefix("#X"))
+            {
+                u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+            } else if let Some(dec) = entity.strip_prefix('#') {
+                dec.parse().ok().and_then(char::from_u32)
+            } else {
+                match entity {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some(' '),
+                    "mdash" => Some('—'),
+                    "ndash" => Some('–'),
+                    "hellip" => Some('…'),
+                    "lsquo" => Some('‘'),
+                    "rsquo" => Some('’'),
+                    "ldquo" => Some('“'),
+                    "rdquo" => Some('”'),
+                    "copy" => Some('©'),
+                    "reg" => Some('®'),
+                    "trade" => Some('™'),
+                    "middot" => Some('·'),
+                    "times" => Some('×'),
+                    "rarr" => Some('→'),
+                    "larr" => Some('←'),
+                    _ => None,
+                }
+            };
+            decoded.map_or_else(|| caps[0].to_string(), String::from)
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<!DOCTYPE html>
+<html><head><title>Install &amp; Setup — Widget</title>
+<style>body { color: red }</style>
+<script>var x = "<p>not text</p>";</script></head>
+<body>
+<nav class="navbar"><a href="/">Home</a> <a href="/docs">Docs</a></nav>
+<div class="cookie-banner">We use cookies</div>
+<main>
+  <h1>Install</h1>
+  <p>Run the   installer from the <a href="releases/latest">latest release</a>.
+  See the <a href="https://example.com/faq">FAQ</a> &mdash; or
+  the <a href="releases/latest">release page</a>.</p>
+  <ul><li>Linux</li><li>macOS <ul><li>Intel</li></ul></li></ul>
+  <pre><code>curl -sSf https://x.sh | sh
+widget --version</code></pre>
+  <table><tr><th>OS</th><th>Size</th></tr><tr><td>Linux</td><td>4 MB</td></tr></table>
+  <aside>Related posts</aside>
+</main>
+<footer>© 2024 Widget</footer>
+</body></html>"#;
+
+    #[test]
+    fn test_extract_main_content() {
+        let readable = extract(PAGE, Some("https://widget.dev/docs/install.html"));
+        assert_eq!(readable.title.as_deref(), Some("Install & Setup — Widget"));
+        assert_eq!(
+            readable.text,
+            "# Install\n\n\
+             Run the installer from the latest release [1]. See the FAQ [2] — or the release page [1].\n\n\
+             - Linux\n\
+             - macOS\n  \
+             - Intel\n\n\
+             ```\n\
+             curl -sSf https://x.sh | sh\n\
+             widget --version\n\
+             ```\n\n\
+             OS | Size\n\
+             Linux | 4 MB"
+        );
+        assert_eq!(
+            readable.links,
+            vec![
+                (
+                    "latest release".to_string(),
+                    "https://widget.dev/docs/releases/latest".to_string()
+                ),
+                ("FAQ".to_string(), "https://example.com/faq".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_with_links() {
+        let readable = extract(PAGE, None);
+        let out = readable.render(true);
+        assert!(out.starts_with("# Install & Setup — Widget\n\n# Install\n"));
+        assert!(out.ends_with(
+            "Links (2):\n[1] latest release: releases/latest\n[2] FAQ: https://example.com/faq"
+        ));
+        assert!(!readable.render(false).contains("Links"));
+        assert!(!out.contains("cookies"));
+        assert!(!out.contains("Related posts"));
+        assert!(!out.contains("not text"));
+    }
+
+    #[test]
+    fn test_without_main_uses_body_minus_chrome() {
+        let html =
+            "<body><div id=\"sidebar\">Menu</div><div><p>Hello <b>world</b>.</p></div></body>";
+        assert_eq!(extract(html, None).text, "Hello world.");
+    }
+
+    #[test]
+    fn test_resolve_and_entities() {
+        let base = Some("https://a.dev/x/y/page?q=1");
+        assert_eq!(resolve(base, "/root"), "https://a.dev/root");
+        assert_eq!(resolve(base, "./z"), "https://a.dev/x/y/z");
+        assert_eq!(resolve(base, "//cdn.dev/f"), "https://cdn.dev/f");
+        assert_eq!(resolve(None, "rel"), "rel");
+        assert_eq!(
+            decode_entities("a&#39;b&#x41;&unknown;&lt;"),
+            "a'bA&unknown;<"
+        );
+    }
+}
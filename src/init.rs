@@ -5,7 +5,7 @@ use std::path::{Path, PathBuf};
 use tempfile::NamedTempFile;
 
 // Embedded hook script (guards before set -euo pipefail)
-const REWRITE_HOOK: &str = include_str!("../hooks/rtk-rewrite.sh");
+pub const REWRITE_HOOK: &str = include_str!("../hooks/rtk-rewrite.sh");
 
 // Embedded slim RTK awareness instructions
 const RTK_SLIM: &str = include_str!("../hooks/rtk-awareness.md");
@@ -28,7 +28,7 @@ pub enum PatchResult {
 }
 
 // Legacy full instructions for backward compatibility (--claude-md mode)
-const RTK_INSTRUCTIONS: &str = r##"<!-- rtk-instructions v2 -->
+pub const RTK_INSTRUCTIONS: &str = r##"<!-- rtk-instructions v2 -->
 # RTK (Rust Token Killer) - Token-Optimized Commands
 
 ## Golden Rule
@@ -286,17 +286,28 @@ fn atomic_write(path: &Path, content: &str) -> Result<()> {
 }
 
 /// Prompt user for consent to patch settings.json
-/// Prints to stderr (stdout may be piped), reads from stdin
 /// Default is No (capital N)
 fn prompt_user_consent(settings_path: &Path) -> Result<bool> {
+    prompt_yes_no(
+        &format!("\nPatch existing {}?", settings_path.display()),
+        false,
+    )
+}
+
+/// Ask a yes/no question. Prints to stderr (stdout may be piped), reads
+/// from stdin; an empty answer or a non-terminal stdin gives `default`.
+fn prompt_yes_no(question: &str, default: bool) -> Result<bool> {
     use std::io::{self, BufRead, IsTerminal};
 
-    eprintln!("\nPatch existing {}? [y/N] ", settings_path.display());
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+    eprintln!("{} {} ", question, hint);
 
-    // If stdin is not a terminal (piped), default to No
     if !io::stdin().is_terminal() {
-        eprintln!("(non-interactive mode, defaulting to N)");
-        return Ok(false);
+        eprintln!(
+            "(non-interactive mode, defaulting to {})",
+            if default { "Y" } else { "N" }
+        );
+        return Ok(default);
     }
 
     let stdin = io::stdin();
@@ -306,8 +317,14 @@ fn prompt_user_consent(settings_path: &Path) -> Result<bool> {
         .read_line(&mut line)
         .context("Failed to read user input")?;
 
-    let response = line.trim().to_lowercase();
-    Ok(response == "y" || response == "yes")
+    Ok(parse_answer(&line, default))
+}
+
+fn parse_answer(line: &str, default: bool) -> bool {
+    match line.trim().to_lowercase().as_str() {
+        "" => default,
+        answer => answer == "y" || answer == "yes",
+    }
 }
 
 /// Print manual instructions for settings.json patching
@@ -616,7 +633,7 @@ fn insert_hook_entry(root: &mut serde_json::Value, hook_command: &str) {
 
 /// Check if RTK hook is already present in settings.json
 /// Matches on rtk-rewrite.sh substring to handle different path formats
-fn hook_already_present(root: &serde_json::Value, hook_command: &str) -> bool {
+pub fn hook_already_present(root: &serde_json::Value, hook_command: &str) -> bool {
     let pre_tool_use_array = match root
         .get("hooks")
         .and_then(|h| h.get("PreToolUse"))
@@ -840,7 +857,7 @@ fn patch_claude_md(path: &Path, verbose: u8) -> Result<bool> {
 }
 
 /// Remove old RTK block from CLAUDE.md (migration helper)
-fn remove_rtk_block(content: &str) -> (String, bool) {
+pub fn remove_rtk_block(content: &str) -> (String, bool) {
     if let (Some(start), Some(end)) = (
         content.find("<!-- rtk-instructions"),
         content.find("<!-- /rtk-instructions -->"),
@@ -877,8 +894,120 @@ fn remove_rtk_block(content: &str) -> (String, bool) {
     }
 }
 
+/// Coding agents `rtk init --interactive` looks for, with the home-relative
+/// directory and binary that reveal each one.
+const AGENTS: &[(&str, &str, &str)] = &[
+    ("Claude Code", ".claude", "claude"),
+    ("Cursor", ".cursor", "cursor"),
+    ("Aider", ".aider", "aider"),
+    ("Codex CLI", ".codex", "codex"),
+    ("Gemini CLI", ".gemini", "gemini"),
+];
+
+/// Agents found on this machine, with what gave each away.
+fn detect_agents(home: &Path, on_path: &dyn Fn(&str) -> bool) -> Vec<(&'static str, String)> {
+    AGENTS
+        .iter()
+        .filter_map(|(name, dir, binary)| {
+            let dir_path = home.join(dir);
+            if dir_path.is_dir() {
+                Some((*name, dir_path.display().to_string()))
+            } else if on_path(binary) {
+                Some((*name, format!("`{}` on PATH", binary)))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// `rtk init --interactive`: detect agents, install the Claude Code hook,
+/// create the default config, then verify the hook rewrites a test command.
+pub fn run_interactive(verbose: u8) -> Result<()> {
+    let home = dirs::home_dir().context("Cannot determine home directory. Is $HOME set?")?;
+    let agents = detect_agents(&home, &|binary| crate::utils::which(binary).is_some());
+
+    println!("rtk setup\n");
+    if agents.is_empty() {
+        println!("No coding agent detected (looked for ~/.claude, ~/.cursor, ~/.aider, ~/.codex, ~/.gemini)");
+    }
+    for (name, evidence) in &agents {
+        println!("  ✅ {} ({})", name, evidence);
+    }
+    println!();
+
+    let has_claude = agents.iter().any(|(name, _)| *name == "Claude Code");
+    let mut hook_installed = false;
+    if prompt_yes_no(
+        "Install the Claude Code hook (rewrites Bash commands through rtk)?",
+        has_claude,
+    )? {
+        run_default_mode(true, PatchMode::Auto, verbose)?;
+        hook_installed = cfg!(unix);
+    }
+    for (name, _) in agents.iter().filter(|(name, _)| *name != "Claude Code") {
+        let binary = AGENTS
+            .iter()
+            .find(|(agent, _, _)| agent == name)
+            .map_or("", |(_, _, binary)| *binary);
+        println!(
+            "  {}: run `rtk integrate {}` in each project to set it up",
+            name, binary
+        );
+    }
+
+    let config_path = crate::config::get_config_path()?;
+    if config_path.exists() {
+        println!("Config: {} (kept)", config_path.display());
+    } else if prompt_yes_no(
+        &format!("Create default config at {}?", config_path.display()),
+        true,
+    )? {
+        let path = crate::config::Config::create_default()?;
+        println!("Config: created {}", path.display());
+    }
+
+    if hook_installed {
+        let (_, hook_path) = prepare_hook_paths()?;
+        match verify_hook(&hook_path) {
+            Ok(rewritten) => println!("\n✅ Verified: `git status` → `{}`", rewritten),
+            Err(reason) => {
+                println!("\n⚠️  Hook check failed: {}", reason);
+                println!("    Run `rtk doctor` for details");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Feed the hook a `git status` tool call, as Claude Code would.
+fn verify_hook(hook_path: &Path) -> std::result::Result<String, String> {
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(hook_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("cannot run {}: {}", hook_path.display(), e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(br#"{"tool_input":{"command":"git status"}}"#);
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    rewritten_command(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| "no rewrite produced (is rtk on PATH?)".to_string())
+}
+
+/// The command a hook response rewrote the tool call to.
+fn rewritten_command(hook_stdout: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(hook_stdout.trim()).ok()?;
+    json.pointer("/hookSpecificOutput/updatedInput/command")?
+        .as_str()
+        .map(str::to_string)
+}
+
 /// Resolve ~/.claude directory with proper home expansion
-fn resolve_claude_dir() -> Result<PathBuf> {
+pub fn resolve_claude_dir() -> Result<PathBuf> {
     dirs::home_dir()
         .map(|h| h.join(".claude"))
         .context("Cannot determine home directory. Is $HOME set?")
@@ -904,8 +1033,7 @@ pub fn show_config() -> Result<()> {
             let is_executable = perms.mode() & 0o111 != 0;
 
             let hook_content = fs::read_to_string(&hook_path)?;
-            let has_guards =
-                hook_content.contains("command -v rtk") && hook_content.contains("command -v jq");
+            let has_guards = hook_content.contains("command -v rtk");
 
             if is_executable && has_guards {
                 println!("✅ Hook: {} (executable, with guards)", hook_path.display());
@@ -1002,6 +1130,50 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_detect_agents() {
+        let home = TempDir::new().unwrap();
+        fs::create_dir(home.path().join(".claude")).unwrap();
+        let agents = detect_agents(home.path(), &|binary| binary == "aider");
+        let names: Vec<&str> = agents.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, vec!["Claude Code", "Aider"]);
+        assert_eq!(agents[1].1, "`aider` on PATH");
+    }
+
+    #[test]
+    fn test_parse_answer() {
+        assert!(parse_answer("\n", true));
+        assert!(!parse_answer("", false));
+        assert!(parse_answer("Yes\n", false));
+        assert!(!parse_answer("n", true));
+    }
+
+    #[test]
+    fn test_rewritten_command() {
+        let out = r#"{"hookSpecificOutput":{"hookEventName":"PreToolUse","updatedInput":{"command":"rtk git status"}}}"#;
+        assert_eq!(rewritten_command(out).as_deref(), Some("rtk git status"));
+        assert_eq!(rewritten_command(""), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_verify_installed_hook() {
+        // Needs an rtk on PATH that knows `rtk rewrite`
+        let rewrite_supported = std::process::Command::new("rtk")
+            .args(["rewrite", "git", "status"])
+            .output()
+            .is_ok_and(|o| o.status.success());
+        if !rewrite_supported {
+            return;
+        }
+        use std::os::unix::fs::PermissionsExt;
+        let dir = TempDir::new().unwrap();
+        let hook = dir.path().join("rtk-rewrite.sh");
+        fs::write(&hook, REWRITE_HOOK).unwrap();
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(verify_hook(&hook).unwrap(), "rtk git status");
+    }
+
     #[test]
     fn test_init_mentions_all_top_level_commands() {
         for cmd in [
@@ -1039,7 +1211,7 @@ mod tests {
     #[test]
     fn test_hook_has_guards() {
         assert!(REWRITE_HOOK.contains("command -v rtk"));
-        assert!(REWRITE_HOOK.contains("command -v jq"));
+        assert!(REWRITE_HOOK.contains("rtk rewrite --hook"));
         // Guards must be BEFORE set -euo pipefail
         let guard_pos = REWRITE_HOOK.find("command -v rtk").unwrap();
         let set_pos = REWRITE_HOOK.find("set -euo pipefail").unwrap();
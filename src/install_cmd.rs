@@ -0,0 +1,410 @@
+//! Dependency installs (npm/yarn/pnpm/pip/uv/poetry): progress bars,
+//! deprecation notices and audit summaries are dropped. What's left is the
+//! added/removed/changed package counts, peer-dependency conflicts and
+//! failed build scripts.
+
+use crate::tracking;
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::ffi::OsString;
+use std::process::Command;
+
+/// Problem lines kept per category.
+const MAX_PROBLEMS: usize = 8;
+
+lazy_static! {
+    // npm: "added 120 packages, removed 3 packages, changed 5 packages, and audited 500 packages in 6s"
+    static ref NPM_COUNT: Regex =
+        Regex::new(r"\b(added|removed|changed) (\d+) packages?\b").unwrap();
+    static ref NPM_UP_TO_DATE: Regex = Regex::new(r"^up to date\b").unwrap();
+    // pnpm: "Packages: +12 -3"
+    static ref PNPM_PACKAGES: Regex = Regex::new(r"^Packages: (?:\+(\d+))?\s*(?:-(\d+))?").unwrap();
+    // yarn classic: "success Saved 3 new dependencies."
+    static ref YARN_SAVED: Regex = Regex::new(r"^success Saved (\d+) new dependenc").unwrap();
+    // yarn berry: "YN0085: │ + lodash@npm:4.17.21, react@npm:18.2.0, and 10 more."
+    static ref YARN_BERRY: Regex = Regex::new(r"YN0085: │ ([+-]) (.*)$").unwrap();
+    static ref AND_MORE: Regex = Regex::new(r"and (\d+) more").unwrap();
+    // poetry: "Package operations: 3 installs, 1 update, 0 removals"
+    static ref POETRY_OPS: Regex =
+        Regex::new(r"Package operations: (\d+) installs?, (\d+) updates?, (\d+) removals?").unwrap();
+    // pip: "Successfully installed a-1.0 b-2.0" / "Successfully uninstalled a-0.9"
+    static ref PIP_DONE: Regex =
+        Regex::new(r"^Successfully (installed|uninstalled) (.+)$").unwrap();
+    // uv: " + requests==2.31.0" / " - requests==2.30.0"
+    static ref UV_CHANGE: Regex = Regex::new(r"^ ([+-]) ([A-Za-z0-9_.\-\[\]]+)==").unwrap();
+    static ref PIP_VERSION: Regex = Regex::new(r"-\d[^-]*$").unwrap();
+    static ref DURATION: Regex = Regex::new(r"\bin (\d+(?:\.\d+)?m?s)\b").unwrap();
+
+    static ref PEER: Regex = Regex::new(
+        r"(?i)ERESOLVE|unmet peer|peer dep|\bpeer \S+@|(ERR!|error) (While resolving|Found):|Could not resolve dependency|Conflicting peer|YN0060|ERR_PNPM_PEER|requires .+, but you have|conflicting dependencies|version solving failed"
+    )
+    .unwrap();
+    static ref BUILD_FAIL: Regex = Regex::new(
+        r"(?i)Failed (to build|building wheel for)|couldn't be built successfully|YN0009|ELIFECYCLE|(install|postinstall|preinstall) script.*failed|subprocess-exited-with-error|gyp ERR!|ChefBuildError|Command failed"
+    )
+    .unwrap();
+    static ref ERROR_LINE: Regex =
+        Regex::new(r"(?i)^(npm ERR!|npm error|error\b|ERROR:|ERR_PNPM|\s*ERR_|✕|.*YN\d{4}: .*error)").unwrap();
+    /// Error lines that carry no information on their own
+    static ref ERROR_NOISE: Regex = Regex::new(
+        r"(?i)A complete log of this run|npm (ERR!|error)\s*$|^error\s*$|debug-\d|For more information|Run .* for details|Visit https://|This error originates from a subprocess|note: This"
+    )
+    .unwrap();
+}
+
+/// Subcommands that install or remove dependencies, per package manager.
+pub fn is_install(program: &str, subcommand: Option<&str>) -> bool {
+    let Some(sub) = subcommand else {
+        // A bare `yarn` installs
+        return program == "yarn";
+    };
+    match program {
+        "npm" => matches!(
+            sub,
+            "install" | "i" | "ci" | "add" | "uninstall" | "remove" | "rm" | "un" | "update" | "up"
+        ),
+        "pnpm" => matches!(
+            sub,
+            "install" | "i" | "add" | "remove" | "rm" | "uninstall" | "update" | "up"
+        ),
+        "yarn" => matches!(sub, "install" | "add" | "remove" | "upgrade" | "up"),
+        "pip" | "pip3" => matches!(sub, "install" | "uninstall"),
+        "poetry" => matches!(sub, "install" | "add" | "remove" | "update"),
+        _ => false,
+    }
+}
+
+/// Run `program args` (e.g. `npm install`) and print the condensed result.
+pub fn run(program: &str, args: &[String], verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let label = format!("{} {}", program, args.join(" "));
+    if verbose > 0 {
+        eprintln!("Running: {}", label);
+    }
+
+    let output = Command::new(program)
+        .args(args)
+        // npm and yarn skip spinners and colors when they see these
+        .env("CI", "true")
+        .env("NO_COLOR", "1")
+        .output()
+        .with_context(|| format!("Failed to run {}", label))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
+
+    let summary = InstallSummary::parse(&crate::utils::strip_ansi(&raw));
+    let filtered = summary.format(label.trim(), output.status.success());
+    println!("{}", filtered);
+
+    timer.with_exit_code(output.status).track(
+        label.trim(),
+        &format!("rtk {}", label.trim()),
+        &raw,
+        &filtered,
+    );
+    Ok(())
+}
+
+/// `rtk yarn`/`rtk poetry`: installs are condensed, anything else passes through.
+pub fn run_tool(program: &str, args: &[String], verbose: u8) -> Result<()> {
+    if is_install(program, args.first().map(String::as_str)) {
+        return run(program, args, verbose);
+    }
+    let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+    run_passthrough(program, &args, verbose)
+}
+
+/// Run any other subcommand of `program` unfiltered.
+pub fn run_passthrough(program: &str, args: &[OsString], verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    if verbose > 0 {
+        eprintln!("{} passthrough: {:?}", program, args);
+    }
+    let status = Command::new(program)
+        .args(args)
+        .status()
+        .with_context(|| format!("Failed to run {}", program))?;
+
+    let args_str = tracking::args_display(args);
+    timer.with_exit_code(status).track_passthrough(
+        &format!("{} {}", program, args_str),
+        &format!("rtk {} {} (passthrough)", program, args_str),
+    );
+    Ok(())
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct InstallSummary {
+    added: usize,
+    removed: usize,
+    changed: usize,
+    up_to_date: bool,
+    duration: Option<String>,
+    peer: Vec<String>,
+    build: Vec<String>,
+    errors: Vec<String>,
+}
+
+impl InstallSummary {
+    fn parse(output: &str) -> Self {
+        let mut s = InstallSummary::default();
+        // pip/uv report upgrades as an uninstall plus an install
+        let mut installed = BTreeSet::new();
+        let mut uninstalled = BTreeSet::new();
+
+        for line in output.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            if s.duration.is_none()
+                && (NPM_COUNT.is_match(trimmed) || NPM_UP_TO_DATE.is_match(trimmed))
+            {
+                s.duration = DURATION.captures(trimmed).map(|c| c[1].to_string());
+            }
+            if NPM_UP_TO_DATE.is_match(trimmed) {
+                s.up_to_date = true;
+            }
+            for caps in NPM_COUNT.captures_iter(trimmed) {
+                let n: usize = caps[2].parse().unwrap_or(0);
+                match &caps[1] {
+                    "added" => s.added += n,
+                    "removed" => s.removed += n,
+                    _ => s.changed += n,
+                }
+            }
+            if let Some(caps) = PNPM_PACKAGES.captures(trimmed) {
+                s.added += caps.get(1).map_or(0, |m| m.as_str().parse().unwrap_or(0));
+                s.removed += caps.get(2).map_or(0, |m| m.as_str().parse().unwrap_or(0));
+            }
+            if trimmed == "Already up to date" || trimmed.starts_with("Lockfile is up to date") {
+                s.up_to_date = true;
+            }
+            if let Some(caps) = YARN_SAVED.captures(trimmed) {
+                s.added += caps[1].parse::<usize>().unwrap_or(0);
+            }
+            if let Some(caps) = YARN_BERRY.captures(trimmed) {
+                let list = AND_MORE.replace(&caps[2], "");
+                let more: usize = AND_MORE
+                    .captures(&caps[2])
+                    .map_or(0, |c| c[1].parse().unwrap_or(0));
+                let n = list.split(',').filter(|p| p.contains('@')).count() + more;
+                if &caps[1] == "+" {
+                    s.added += n;
+                } else {
+                    s.removed += n;
+                }
+            }
+            if let Some(caps) = POETRY_OPS.captures(trimmed) {
+                s.added += caps[1].parse::<usize>().unwrap_or(0);
+                s.changed += caps[2].parse::<usize>().unwrap_or(0);
+                s.removed += caps[3].parse::<usize>().unwrap_or(0);
+            }
+            if trimmed.starts_with("Requirement already satisfied")
+                || trimmed == "No dependencies to install or update"
+                || trimmed.starts_with("Audited ")
+            {
+                s.up_to_date = true;
+            }
+            if let Some(caps) = PIP_DONE.captures(trimmed) {
+                let names = caps[2]
+                    .split_whitespace()
+                    .map(|p| PIP_VERSION.replace(p, "").to_lowercase());
+                if &caps[1] == "installed" {
+                    installed.extend(names);
+                } else {
+                    uninstalled.extend(names);
+                }
+            }
+            if let Some(caps) = UV_CHANGE.captures(line) {
+                let name = caps[2].to_lowercase();
+                if &caps[1] == "+" {
+                    installed.insert(name);
+                } else {
+                    uninstalled.insert(name);
+                }
+            }
+
+            if PEER.is_match(trimmed) {
+                push_unique(&mut s.peer, trimmed);
+            } else if BUILD_FAIL.is_match(trimmed) {
+                push_unique(&mut s.build, trimmed);
+            } else if ERROR_LINE.is_match(line) && !ERROR_NOISE.is_match(trimmed) {
+                push_unique(&mut s.errors, trimmed);
+            }
+        }
+
+        s.changed += installed.intersection(&uninstalled).count();
+        s.added += installed.difference(&uninstalled).count();
+        s.removed += uninstalled.difference(&installed).count();
+        s
+    }
+
+    fn format(&self, label: &str, success: bool) -> String {
+        let mut counts = Vec::new();
+        if self.added > 0 {
+            counts.push(format!("+{} added", self.added));
+        }
+        if self.removed > 0 {
+            counts.push(format!("-{} removed", self.removed));
+        }
+        if self.changed > 0 {
+            counts.push(format!("~{} changed", self.changed));
+        }
+        let counts = if !counts.is_empty() {
+            counts.join(", ")
+        } else if self.up_to_date || success {
+            "up to date".to_string()
+        } else {
+            "nothing installed".to_string()
+        };
+        let duration = self
+            .duration
+            .as_ref()
+            .map(|d| format!(" ({})", d))
+            .unwrap_or_default();
+        let mark = if success { "✓" } else { "✗" };
+        let mut out = vec![format!("{} {}: {}{}", mark, label, counts, duration)];
+
+        section(&mut out, "peer dependency conflicts", &self.peer);
+        section(&mut out, "build scripts failed", &self.build);
+        // Plain errors only matter when nothing more specific explains a failure
+        if !success && self.peer.is_empty() && self.build.is_empty() {
+            section(&mut out, "errors", &self.errors);
+        }
+        out.join("\n")
+    }
+}
+
+fn push_unique(list: &mut Vec<String>, line: &str) {
+    if !list.iter().any(|l| l == line) {
+        list.push(line.to_string());
+    }
+}
+
+fn section(out: &mut Vec<String>, title: &str, lines: &[String]) {
+    if lines.is_empty() {
+        return;
+    }
+    out.push(format!("{} ({}):", title, lines.len()));
+    for line in lines.iter().take(MAX_PROBLEMS) {
+        out.push(format!("  {}", line));
+    }
+    if lines.len() > MAX_PROBLEMS {
+        out.push(format!("  … +{} more", lines.len() - MAX_PROBLEMS));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_npm_install_success() {
+        let output = "npm WARN deprecated inflight@1.0.6: This module is not supported\n\
+                      npm WARN deprecated glob@7.2.3: Glob versions prior to v9 are no longer supported\n\
+                      \n\
+                      added 120 packages, removed 3 packages, changed 5 packages, and audited 500 packages in 6s\n\
+                      \n\
+                      42 packages are looking for funding\n  run `npm fund` for details\n\
+                      \n\
+                      3 moderate severity vulnerabilities\n";
+        let summary = InstallSummary::parse(output);
+        assert_eq!(
+            summary.format("npm install", true),
+            "✓ npm install: +120 added, -3 removed, ~5 changed (6s)"
+        );
+    }
+
+    #[test]
+    fn test_npm_peer_conflict() {
+        let output = "npm error code ERESOLVE\n\
+                      npm error ERESOLVE unable to resolve dependency tree\n\
+                      npm error\n\
+                      npm error While resolving: app@1.0.0\n\
+                      npm error Found: react@18.2.0\n\
+                      npm error Could not resolve dependency:\n\
+                      npm error peer react@\"^17.0.0\" from old-widget@2.1.0\n\
+                      npm error A complete log of this run can be found in: /root/.npm/_logs/x.log\n";
+        let out = InstallSummary::parse(output).format("npm install", false);
+        assert!(out.starts_with("✗ npm install: nothing installed"));
+        assert!(out.contains("peer dependency conflicts (6):"));
+        assert!(out.contains("peer react@\"^17.0.0\" from old-widget@2.1.0"));
+        assert!(!out.contains("complete log"));
+    }
+
+    #[test]
+    fn test_pnpm_build_failure() {
+        let output = "Packages: +12 -1\n\
+                      ++++++++++++-\n\
+                      Progress: resolved 200, reused 190, downloaded 10, added 12, done\n\
+                      node_modules/.pnpm/sharp@0.32.0/node_modules/sharp: Running install script, failed in 2.1s\n\
+                       ELIFECYCLE  Command failed with exit code 1.\n";
+        let summary = InstallSummary::parse(output);
+        assert_eq!((summary.added, summary.removed), (12, 1));
+        assert_eq!(summary.build.len(), 2);
+    }
+
+    #[test]
+    fn test_pip_upgrade_counts() {
+        let output = "Collecting requests==2.31.0\n\
+                      Downloading requests-2.31.0-py3-none-any.whl (62 kB)\n\
+                      \u{20}    ━━━━━━━━━━━━━━━━━━━━ 62.6/62.6 kB 3.1 MB/s eta 0:00:00\n\
+                      Installing collected packages: idna, requests\n\
+                      \u{20} Attempting uninstall: requests\n\
+                      \u{20}   Found existing installation: requests 2.30.0\n\
+                      \u{20}   Uninstalling requests-2.30.0:\n\
+                      \u{20}     Successfully uninstalled requests-2.30.0\n\
+                      Successfully installed idna-3.6 requests-2.31.0\n";
+        let summary = InstallSummary::parse(output);
+        assert_eq!((summary.added, summary.removed, summary.changed), (1, 0, 1));
+    }
+
+    #[test]
+    fn test_pip_build_and_conflict() {
+        let output = "error: subprocess-exited-with-error\n\
+                      \u{20} × Building wheel for lxml (pyproject.toml) did not run successfully.\n\
+                      \u{20} note: This error originates from a subprocess, and is likely not a problem with pip.\n\
+                      \u{20} ERROR: Failed building wheel for lxml\n\
+                      ERROR: pip's dependency resolver does not currently take into account all the packages that are installed.\n\
+                      ERROR: boto3 1.28.0 requires botocore<1.32.0, but you have botocore 1.33.0 which is incompatible.\n";
+        let summary = InstallSummary::parse(output);
+        assert_eq!(summary.build.len(), 2);
+        assert_eq!(summary.peer.len(), 1);
+    }
+
+    #[test]
+    fn test_uv_and_poetry_counts() {
+        let uv = "Resolved 10 packages in 120ms\nInstalled 2 packages in 8ms\n + httpx==0.27.0\n + sniffio==1.3.1\n - sniffio==1.3.0\n";
+        let summary = InstallSummary::parse(uv);
+        assert_eq!((summary.added, summary.changed), (1, 1));
+
+        let poetry = "Updating dependencies\nResolving dependencies...\n\n\
+                      Package operations: 3 installs, 1 update, 0 removals\n\n\
+                      \u{20} - Installing certifi (2024.2.2)\n";
+        let summary = InstallSummary::parse(poetry);
+        assert_eq!((summary.added, summary.changed, summary.removed), (3, 1, 0));
+    }
+
+    #[test]
+    fn test_yarn_counts() {
+        let classic = "[1/4] Resolving packages...\nsuccess Saved lockfile.\nsuccess Saved 3 new dependencies.\nDone in 2.5s.\n";
+        assert_eq!(InstallSummary::parse(classic).added, 3);
+        let berry = "➤ YN0085: │ + lodash@npm:4.17.21, react@npm:18.2.0, and 10 more.\n➤ YN0085: │ - left-pad@npm:1.3.0\n";
+        let summary = InstallSummary::parse(berry);
+        assert_eq!((summary.added, summary.removed), (12, 1));
+    }
+
+    #[test]
+    fn test_is_install() {
+        assert!(is_install("npm", Some("ci")));
+        assert!(is_install("yarn", None));
+        assert!(is_install("poetry", Some("add")));
+        assert!(!is_install("npm", Some("test")));
+        assert!(!is_install("pip", Some("list")));
+    }
+}
@@ -0,0 +1,563 @@
+//! `rtk integrate <agent>` — set up rtk for agents other than Claude Code.
+//!
+//! These agents have no command-rewriting hook, so rtk is wired in through
+//! each agent's own configuration in the current project:
+//! - Aider: the rtk instructions as a read-only context file, and
+//!   `test-cmd` / `lint-cmd` (run after each edit) wrapped in `rtk test` /
+//!   `rtk err` with the `agent` profile.
+//! - Cursor: an always-applied rule with the rtk instructions, and
+//!   `RTK_PROFILE=agent` in the integrated terminal the agent runs in.
+//! - Codex CLI: the rtk instructions in `AGENTS.md`, and `RTK_AGENT` /
+//!   `RTK_PROFILE` in `shell_environment_policy.set` of the user's
+//!   `$CODEX_HOME/config.toml` (default `~/.codex`).
+//! - Gemini CLI: the rtk instructions in `GEMINI.md`, and `RTK_AGENT` /
+//!   `RTK_PROFILE` in the `.env` file Gemini loads for the project.
+//!
+//! `RTK_AGENT` attributes the commands to the agent in the tracking database.
+//! `--show` prints the changes without writing, `--uninstall` reverts them.
+
+use crate::init::{remove_rtk_block, RTK_INSTRUCTIONS};
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const AIDER_CONF: &str = ".aider.conf.yml";
+/// Matches Aider's default `.aider*` gitignore entry
+const AIDER_RULES: &str = ".aider.rtk.md";
+const CURSOR_RULE: &str = ".cursor/rules/rtk.mdc";
+const CURSOR_SETTINGS: &str = ".vscode/settings.json";
+const CODEX_RULES: &str = "AGENTS.md";
+const GEMINI_RULES: &str = "GEMINI.md";
+const GEMINI_ENV: &str = ".gemini/.env";
+
+/// Markers around what rtk adds to shared `#`-commented files
+const BLOCK_START: &str = "# >>> rtk >>>";
+const BLOCK_END: &str = "# <<< rtk <<<";
+const CODEX_BLOCK: &str =
+    "[shell_environment_policy.set]\nRTK_AGENT = \"codex\"\nRTK_PROFILE = \"agent\"";
+const GEMINI_BLOCK: &str = "RTK_AGENT=gemini-cli\nRTK_PROFILE=agent";
+
+const TEST_PREFIX: &str = "rtk --profile agent test ";
+const LINT_PREFIX: &str = "rtk --profile agent err ";
+const TERMINAL_ENV_KEYS: &[&str] = &[
+    "terminal.integrated.env.linux",
+    "terminal.integrated.env.osx",
+    "terminal.integrated.env.windows",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Agent {
+    Aider,
+    Cursor,
+    Codex,
+    Gemini,
+}
+
+/// A file to write (`Some`) or remove (`None`).
+#[derive(Debug, PartialEq)]
+struct Change {
+    path: PathBuf,
+    content: Option<String>,
+}
+
+pub fn run(agent: Agent, uninstall: bool, show: bool, verbose: u8) -> Result<()> {
+    let root = std::env::current_dir().context("Failed to read current directory")?;
+    let changes = match agent {
+        Agent::Aider => plan_aider(&root, uninstall)?,
+        Agent::Cursor => plan_cursor(&root, uninstall)?,
+        Agent::Codex => plan_codex(&root, &codex_home()?, uninstall)?,
+        Agent::Gemini => plan_gemini(&root, uninstall)?,
+    };
+    let changes: Vec<Change> = changes
+        .into_iter()
+        .filter(|change| fs::read_to_string(&change.path).ok() != change.content)
+        .collect();
+
+    if changes.is_empty() {
+        println!(
+            "Nothing to change: rtk integration for {:?} is up to date",
+            agent
+        );
+        return Ok(());
+    }
+    for change in &changes {
+        let path = change.path.strip_prefix(&root).unwrap_or(&change.path);
+        match (&change.content, show) {
+            (Some(content), true) => {
+                println!("--- {} ---", path.display());
+                println!("{}", content.trim_end());
+            }
+            (None, true) => println!("--- {} (removed) ---", path.display()),
+            (Some(content), false) => {
+                if let Some(parent) = change.path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&change.path, content)
+                    .with_context(|| format!("Failed to write {}", change.path.display()))?;
+                println!("✅ {}", path.display());
+            }
+            (None, false) => {
+                fs::remove_file(&change.path)
+                    .with_context(|| format!("Failed to remove {}", change.path.display()))?;
+                println!("🗑️  {}", path.display());
+            }
+        }
+        if verbose > 0 && !show {
+            eprintln!("{}", change.path.display());
+        }
+    }
+    if !show && !uninstall {
+        match agent {
+            Agent::Aider => println!("\nRestart aider to load the new settings."),
+            Agent::Cursor => println!("\nOpen a new Cursor terminal so RTK_PROFILE applies."),
+            Agent::Codex => println!("\nRestart codex to load the new settings."),
+            Agent::Gemini => println!("\nRestart gemini to load the new settings."),
+        }
+    }
+    Ok(())
+}
+
+fn plan_aider(root: &Path, uninstall: bool) -> Result<Vec<Change>> {
+    let conf_path = root.join(AIDER_CONF);
+    let conf = fs::read_to_string(&conf_path).unwrap_or_default();
+    let rules = (!uninstall).then(|| RTK_INSTRUCTIONS.to_string());
+    let mut changes = vec![Change {
+        path: root.join(AIDER_RULES),
+        content: rules,
+    }];
+    if uninstall {
+        if !conf.is_empty() {
+            changes.push(Change {
+                path: conf_path,
+                content: Some(aider_conf(&conf, false)),
+            });
+        }
+    } else {
+        changes.push(Change {
+            path: conf_path,
+            content: Some(aider_conf(&conf, true)),
+        });
+    }
+    Ok(changes)
+}
+
+/// `.aider.conf.yml` with the rtk context file and wrapped test/lint commands
+/// added (or removed). Line-based so comments and layout survive.
+fn aider_conf(conf: &str, install: bool) -> String {
+    let mut lines: Vec<String> = conf.lines().map(str::to_string).collect();
+    for line in lines.iter_mut() {
+        if let Some(value) = line.strip_prefix("test-cmd:") {
+            *line = format!("test-cmd:{}", wrap_value(value, TEST_PREFIX, install));
+        } else if let Some(value) = line.strip_prefix("lint-cmd:") {
+            *line = format!("lint-cmd:{}", wrap_value(value, LINT_PREFIX, install));
+        }
+    }
+    set_read_entry(&mut lines, install);
+    let mut out = lines.join("\n");
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+/// Add or strip `prefix` inside a YAML scalar, keeping quotes and Aider's
+/// `lang: cmd` lint form.
+fn wrap_value(value: &str, prefix: &str, install: bool) -> String {
+    let trimmed = value.trim();
+    let quote = trimmed
+        .chars()
+        .next()
+        .filter(|c| *c == '"' || *c == '\'')
+        .filter(|q| trimmed.len() > 1 && trimmed.ends_with(*q));
+    let inner = match quote {
+        Some(_) => &trimmed[1..trimmed.len() - 1],
+        None => trimmed,
+    };
+    if inner.is_empty() {
+        return value.to_string();
+    }
+    let (lang, cmd) = match inner.split_once(": ") {
+        Some((lang, cmd)) if !lang.contains(' ') => (format!("{}: ", lang), cmd),
+        _ => (String::new(), inner),
+    };
+    let cmd = match (install, cmd.strip_prefix(prefix)) {
+        (true, None) if !cmd.starts_with("rtk ") => format!("{}{}", prefix, cmd),
+        (false, Some(original)) => original.to_string(),
+        _ => cmd.to_string(),
+    };
+    let q = quote.map(String::from).unwrap_or_default();
+    format!(" {}{}{}{}", q, lang, cmd, q)
+}
+
+/// Ensure (or remove) `AIDER_RULES` in the top-level `read:` key, in any of
+/// its scalar, inline-list or block-list forms.
+fn set_read_entry(lines: &mut Vec<String>, install: bool) {
+    let Some(idx) = lines.iter().position(|l| l.starts_with("read:")) else {
+        if install {
+            lines.push(format!("read: {}", AIDER_RULES));
+        }
+        return;
+    };
+    let value = lines[idx]["read:".len()..].trim().to_string();
+
+    if value.is_empty() {
+        // Block list: following indented `- item` lines
+        let items_end = (idx + 1..lines.len())
+            .find(|&i| !lines[i].trim_start().starts_with("- "))
+            .unwrap_or(lines.len());
+        let existing = (idx + 1..items_end).find(|&i| {
+            lines[i].trim_start()[2..]
+                .trim()
+                .trim_matches(|c| c == '"' || c == '\'')
+                == AIDER_RULES
+        });
+        match (install, existing) {
+            (true, None) => {
+                let indent = lines
+                    .get(idx + 1)
+                    .filter(|_| items_end > idx + 1)
+                    .map(|l| l.len() - l.trim_start().len())
+                    .unwrap_or(2);
+                lines.insert(
+                    items_end,
+                    format!("{}- {}", " ".repeat(indent), AIDER_RULES),
+                );
+            }
+            (false, Some(i)) => {
+                lines.remove(i);
+                if items_end - 1 == idx + 1 {
+                    lines.remove(idx);
+                }
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    let mut items: Vec<String> = match value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+        Some(list) => list
+            .split(',')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect(),
+        None => vec![value.clone()],
+    };
+    let present = items
+        .iter()
+        .any(|item| item.trim_matches(|c| c == '"' || c == '\'') == AIDER_RULES);
+    if install && !present {
+        items.push(AIDER_RULES.to_string());
+    } else if !install {
+        items.retain(|item| item.trim_matches(|c| c == '"' || c == '\'') != AIDER_RULES);
+    }
+    match items.as_slice() {
+        [] => {
+            lines.remove(idx);
+        }
+        [single] if !value.starts_with('[') => lines[idx] = format!("read: {}", single),
+        _ => lines[idx] = format!("read: [{}]", items.join(", ")),
+    }
+}
+
+fn plan_cursor(root: &Path, uninstall: bool) -> Result<Vec<Change>> {
+    let rule = (!uninstall).then(|| {
+        format!(
+            "---\ndescription: Prefix shell commands with rtk to cut output tokens\nalwaysApply: true\n---\n\n{}",
+            RTK_INSTRUCTIONS
+        )
+    });
+    let mut changes = vec![Change {
+        path: root.join(CURSOR_RULE),
+        content: rule,
+    }];
+
+    let settings_path = root.join(CURSOR_SETTINGS);
+    let settings = match fs::read_to_string(&settings_path) {
+        Ok(text) => Some(serde_json::from_str::<Value>(&text).with_context(|| {
+            format!(
+                "{} is not plain JSON (comments?); add \"RTK_PROFILE\": \"agent\" to {} by hand",
+                settings_path.display(),
+                TERMINAL_ENV_KEYS.join(" / ")
+            )
+        })?),
+        Err(_) => None,
+    };
+    if settings.is_some() || !uninstall {
+        let updated = cursor_settings(
+            settings.unwrap_or_else(|| Value::Object(Map::new())),
+            !uninstall,
+        );
+        // Nothing left but what rtk added: remove the file
+        let content = match updated.as_object() {
+            Some(map) if map.is_empty() && uninstall => None,
+            _ => Some(serde_json::to_string_pretty(&updated)? + "\n"),
+        };
+        changes.push(Change {
+            path: settings_path,
+            content,
+        });
+    }
+    Ok(changes)
+}
+
+/// Workspace settings with `RTK_PROFILE=agent` set (or removed) in the
+/// integrated terminal environment of every platform.
+fn cursor_settings(mut settings: Value, install: bool) -> Value {
+    let Some(root) = settings.as_object_mut() else {
+        return settings;
+    };
+    for key in TERMINAL_ENV_KEYS {
+        if install {
+            let env = root
+                .entry(key.to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Some(env) = env.as_object_mut() {
+                env.insert("RTK_PROFILE".into(), "agent".into());
+            }
+        } else if let Some(env) = root.get_mut(*key).and_then(Value::as_object_mut) {
+            env.remove("RTK_PROFILE");
+            if env.is_empty() {
+                root.remove(*key);
+            }
+        }
+    }
+    settings
+}
+
+fn codex_home() -> Result<PathBuf> {
+    match std::env::var_os("CODEX_HOME") {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => Ok(dirs::home_dir()
+            .context("Cannot determine home directory. Is $HOME set?")?
+            .join(".codex")),
+    }
+}
+
+fn plan_codex(root: &Path, codex_home: &Path, uninstall: bool) -> Result<Vec<Change>> {
+    let config_path = codex_home.join("config.toml");
+    let config = fs::read_to_string(&config_path).unwrap_or_default();
+    let without = marked_block(&config, None);
+    if !uninstall {
+        let table: toml::Table = toml::from_str(&without)
+            .with_context(|| format!("Failed to parse {}", config_path.display()))?;
+        let set = table
+            .get("shell_environment_policy")
+            .and_then(|policy| policy.get("set"));
+        if set.is_some() {
+            anyhow::bail!(
+                "{} already sets shell_environment_policy.set; add these keys to it by hand:\n{}",
+                config_path.display(),
+                CODEX_BLOCK.lines().skip(1).collect::<Vec<_>>().join("\n")
+            );
+        }
+    }
+    let mut changes = vec![instructions_change(&root.join(CODEX_RULES), uninstall)];
+    if !config.is_empty() || !uninstall {
+        let updated = marked_block(&config, (!uninstall).then_some(CODEX_BLOCK));
+        changes.push(Change {
+            path: config_path,
+            content: Some(updated),
+        });
+    }
+    Ok(changes)
+}
+
+fn plan_gemini(root: &Path, uninstall: bool) -> Result<Vec<Change>> {
+    // Gemini loads only the first `.env` it finds, `.gemini/.env` before
+    // `.env`: extend the project's own `.env` rather than shadow it
+    let env_path = [GEMINI_ENV, ".env"]
+        .iter()
+        .map(|file| root.join(file))
+        .find(|path| path.is_file())
+        .unwrap_or_else(|| root.join(GEMINI_ENV));
+    let env = fs::read_to_string(&env_path).unwrap_or_default();
+
+    let mut changes = vec![instructions_change(&root.join(GEMINI_RULES), uninstall)];
+    if !env.is_empty() || !uninstall {
+        let updated = marked_block(&env, (!uninstall).then_some(GEMINI_BLOCK));
+        // Nothing left but what rtk added: remove the file
+        let content = (!updated.is_empty()).then_some(updated);
+        changes.push(Change {
+            path: env_path,
+            content,
+        });
+    }
+    Ok(changes)
+}
+
+/// The rtk instructions block added to (or removed from) a project
+/// instructions file; the file is removed when nothing else is left.
+fn instructions_change(path: &Path, uninstall: bool) -> Change {
+    let existing = fs::read_to_string(path).unwrap_or_default();
+    let (rest, _) = remove_rtk_block(&existing);
+    let rest = rest.trim();
+    let content = match (uninstall, rest.is_empty()) {
+        (true, true) => None,
+        (true, false) => Some(format!("{}\n", rest)),
+        (false, true) => Some(RTK_INSTRUCTIONS.to_string()),
+        (false, false) => Some(format!("{}\n\n{}", rest, RTK_INSTRUCTIONS)),
+    };
+    Change {
+        path: path.to_path_buf(),
+        content,
+    }
+}
+
+/// `content` with the rtk marker block replaced by `block`, or removed when
+/// `None`.
+fn marked_block(content: &str, block: Option<&str>) -> String {
+    let mut lines: Vec<&str> = content.lines().collect();
+    let start = lines.iter().position(|line| line.trim() == BLOCK_START);
+    let end = start.and_then(|start| {
+        lines[start..]
+            .iter()
+            .position(|line| line.trim() == BLOCK_END)
+            .map(|offset| start + offset)
+    });
+    if let (Some(start), Some(end)) = (start, end) {
+        lines.drain(start..=end);
+        // Drop the blank line rtk put before the block
+        if start > 0 && start == lines.len() && lines[start - 1].trim().is_empty() {
+            lines.remove(start - 1);
+        }
+    }
+    let mut out = lines.join("\n");
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    if let Some(block) = block {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("{}\n{}\n{}\n", BLOCK_START, block, BLOCK_END));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_aider_conf_install_and_uninstall() {
+        let conf = "# aider settings\nmodel: sonnet\ntest-cmd: cargo test\nlint-cmd: \"python: flake8 --select=E9\"\n";
+        let installed = aider_conf(conf, true);
+        assert_eq!(
+            installed,
+            "# aider settings\nmodel: sonnet\ntest-cmd: rtk --profile agent test cargo test\nlint-cmd: \"python: rtk --profile agent err flake8 --select=E9\"\nread: .aider.rtk.md\n"
+        );
+        assert_eq!(aider_conf(&installed, true), installed);
+        assert_eq!(aider_conf(&installed, false), conf);
+    }
+
+    #[test]
+    fn test_aider_read_forms() {
+        let scalar = aider_conf("read: CONVENTIONS.md\n", true);
+        assert_eq!(scalar, "read: [CONVENTIONS.md, .aider.rtk.md]\n");
+        assert_eq!(aider_conf(&scalar, false), "read: [CONVENTIONS.md]\n");
+
+        let block = aider_conf("read:\n  - CONVENTIONS.md\nmodel: x\n", true);
+        assert_eq!(
+            block,
+            "read:\n  - CONVENTIONS.md\n  - .aider.rtk.md\nmodel: x\n"
+        );
+        assert_eq!(
+            aider_conf(&block, false),
+            "read:\n  - CONVENTIONS.md\nmodel: x\n"
+        );
+
+        assert_eq!(aider_conf("read: .aider.rtk.md\n", false), "");
+    }
+
+    #[test]
+    fn test_cursor_settings_merge() {
+        let existing =
+            json!({ "editor.tabSize": 2, "terminal.integrated.env.linux": { "FOO": "1" } });
+        let installed = cursor_settings(existing.clone(), true);
+        assert_eq!(installed["terminal.integrated.env.linux"]["FOO"], "1");
+        assert_eq!(
+            installed["terminal.integrated.env.osx"]["RTK_PROFILE"],
+            "agent"
+        );
+        assert_eq!(cursor_settings(installed, false), existing);
+    }
+
+    #[test]
+    fn test_plan_cursor_writes_rule() {
+        let dir = tempfile::tempdir().unwrap();
+        let changes = plan_cursor(dir.path(), false).unwrap();
+        let rule = changes[0].content.as_deref().unwrap();
+        assert!(rule.starts_with("---\ndescription:"));
+        assert!(rule.contains("alwaysApply: true"));
+        assert!(rule.contains("rtk git status"));
+
+        fs::create_dir_all(dir.path().join(".vscode")).unwrap();
+        fs::write(dir.path().join(CURSOR_SETTINGS), "{ // comment\n}").unwrap();
+        assert!(plan_cursor(dir.path(), false).is_err());
+    }
+
+    #[test]
+    fn test_marked_block_upsert_and_remove() {
+        let env = "API_KEY=x\n";
+        let installed = marked_block(env, Some(GEMINI_BLOCK));
+        assert_eq!(
+            installed,
+            "API_KEY=x\n\n# >>> rtk >>>\nRTK_AGENT=gemini-cli\nRTK_PROFILE=agent\n# <<< rtk <<<\n"
+        );
+        assert_eq!(marked_block(&installed, Some(GEMINI_BLOCK)), installed);
+        assert_eq!(marked_block(&installed, None), env);
+        assert_eq!(marked_block(&marked_block("", Some("X=1")), None), "");
+    }
+
+    #[test]
+    fn test_plan_codex() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().join("codex");
+        fs::create_dir_all(&home).unwrap();
+        fs::write(dir.path().join(CODEX_RULES), "# Project\n").unwrap();
+        fs::write(home.join("config.toml"), "model = \"o4\"\n").unwrap();
+
+        let changes = plan_codex(dir.path(), &home, false).unwrap();
+        let agents = changes[0].content.as_deref().unwrap();
+        assert!(agents.starts_with("# Project\n\n<!-- rtk-instructions"));
+        let config: toml::Table = toml::from_str(changes[1].content.as_deref().unwrap()).unwrap();
+        assert_eq!(
+            config["shell_environment_policy"]["set"]["RTK_AGENT"].as_str(),
+            Some("codex")
+        );
+
+        for change in &changes {
+            fs::write(&change.path, change.content.as_deref().unwrap()).unwrap();
+        }
+        let removed = plan_codex(dir.path(), &home, true).unwrap();
+        assert_eq!(removed[0].content.as_deref(), Some("# Project\n"));
+        assert_eq!(removed[1].content.as_deref(), Some("model = \"o4\"\n"));
+
+        fs::write(
+            home.join("config.toml"),
+            "[shell_environment_policy.set]\nFOO = \"1\"\n",
+        )
+        .unwrap();
+        assert!(plan_codex(dir.path(), &home, false).is_err());
+    }
+
+    #[test]
+    fn test_plan_gemini_env_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let changes = plan_gemini(dir.path(), false).unwrap();
+        assert_eq!(changes[1].path, dir.path().join(GEMINI_ENV));
+
+        // An existing project .env is extended, not shadowed
+        fs::write(dir.path().join(".env"), "GEMINI_API_KEY=x\n").unwrap();
+        let changes = plan_gemini(dir.path(), false).unwrap();
+        assert_eq!(changes[1].path, dir.path().join(".env"));
+        assert!(changes[1]
+            .content
+            .as_deref()
+            .unwrap()
+            .starts_with("GEMINI_API_KEY=x\n"));
+        assert_eq!(changes[0].content.as_deref(), Some(RTK_INSTRUCTIONS));
+    }
+}
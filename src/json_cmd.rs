@@ -1,3 +1,4 @@
+use crate::parser::lossy;
 use crate::tracking;
 use anyhow::{Context, Result};
 use serde_json::Value;
@@ -16,7 +17,7 @@ pub fn run(file: &Path, max_depth: usize, verbose: u8) -> Result<()> {
     let content = fs::read_to_string(file)
         .with_context(|| format!("Failed to read file: {}", file.display()))?;
 
-    let schema = filter_json_string(&content, max_depth)?;
+    let schema = lossy::degrade("json", &content, filter_json_string(&content, max_depth))?;
     println!("{}", schema);
     timer.track(
         &format!("cat {}", file.display()),
@@ -41,7 +42,7 @@ pub fn run_stdin(max_depth: usize, verbose: u8) -> Result<()> {
         .read_to_string(&mut content)
         .context("Failed to read from stdin")?;
 
-    let schema = filter_json_string(&content, max_depth)?;
+    let schema = lossy::degrade("json", &content, filter_json_string(&content, max_depth))?;
     println!("{}", schema);
     timer.track("cat - (stdin)", "rtk json -", &content, &schema);
     Ok(())
@@ -100,7 +100,7 @@ pub fn run(
                     "base_command": r.base_command,
                 })).collect::<Vec<_>>(),
             });
-            println!("{}", serde_json::to_string_pretty(&json)?);
+            println_verbatim!("{}", serde_json::to_string_pretty(&json)?);
         }
         _ => {
             // Text output
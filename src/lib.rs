@@ -0,0 +1,286 @@
+//! rtk (Rust Token Killer) as a library: the compressors behind the `rtk`
+//! CLI, callable in-process by other Rust tools.
+//!
+//! The stable surface is [`api`], re-exported by [`prelude`], plus the
+//! savings database in [`tracking`] and the helpers in [`utils`]. The other
+//! modules implement the CLI and change without notice.
+//!
+//! ```
+//! use rtk::prelude::*;
+//!
+//! let diff = "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-x\n+y\n";
+//! let summary = compress_diff(diff, 50);
+//! println!("{} ({:.0}% saved)", summary.text, summary.savings_pct());
+//! ```
+
+// Crate-wide `print!`/`println!` go through the active output profile
+// (see profile.rs); textual scoping makes these shadow the std macros.
+macro_rules! print {
+    ($($arg:tt)*) => {
+        $crate::profile::print(&::std::format!($($arg)*))
+    };
+}
+
+macro_rules! println {
+    () => {
+        $crate::profile::print_line("")
+    };
+    ($($arg:tt)*) => {
+        $crate::profile::print_line(&::std::format!($($arg)*))
+    };
+}
+
+// Structured exports (JSON, CSV, Markdown) and file content: written exactly
+// as formatted, whatever the profile. Only rtk's own decoration is rendered.
+macro_rules! print_verbatim {
+    ($($arg:tt)*) => {
+        $crate::profile::print_verbatim(&::std::format!($($arg)*))
+    };
+}
+
+macro_rules! println_verbatim {
+    () => {
+        $crate::profile::print_verbatim("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::profile::print_verbatim(&::std::format!("{}\n", ::std::format_args!($($arg)*)))
+    };
+}
+
+#[doc(hidden)]
+pub mod advertise;
+#[doc(hidden)]
+pub mod alias;
+#[doc(hidden)]
+pub mod android_cmd;
+pub mod api;
+#[doc(hidden)]
+pub mod bench_self;
+#[doc(hidden)]
+pub mod bloat_cmd;
+#[doc(hidden)]
+pub mod budget;
+#[doc(hidden)]
+pub mod bundle_cmd;
+#[doc(hidden)]
+pub mod cache;
+#[doc(hidden)]
+pub mod cargo_cmd;
+#[doc(hidden)]
+pub mod cc_economics;
+#[doc(hidden)]
+pub mod ccusage;
+#[doc(hidden)]
+pub mod config;
+#[doc(hidden)]
+pub mod conflicts_cmd;
+#[doc(hidden)]
+pub mod container;
+#[doc(hidden)]
+pub mod corpus;
+#[doc(hidden)]
+pub mod crash;
+#[doc(hidden)]
+pub mod curl_cmd;
+#[doc(hidden)]
+pub mod daemon;
+#[doc(hidden)]
+pub mod debug_log;
+#[doc(hidden)]
+pub mod delta;
+#[doc(hidden)]
+pub mod deps;
+#[doc(hidden)]
+pub mod diff_cmd;
+#[doc(hidden)]
+pub mod discover;
+#[doc(hidden)]
+pub mod display_helpers;
+#[doc(hidden)]
+pub mod doc_cmd;
+#[doc(hidden)]
+pub mod doctor;
+#[doc(hidden)]
+pub mod elided;
+#[doc(hidden)]
+pub mod encoding;
+#[doc(hidden)]
+pub mod env_cmd;
+#[doc(hidden)]
+pub mod err_extract;
+#[doc(hidden)]
+pub mod exit_code;
+#[doc(hidden)]
+pub mod explain;
+#[doc(hidden)]
+pub mod fetch_cmd;
+#[doc(hidden)]
+pub mod filter;
+#[doc(hidden)]
+pub mod find_cmd;
+#[doc(hidden)]
+pub mod fmt_cmd;
+#[doc(hidden)]
+pub mod gain;
+#[doc(hidden)]
+pub mod gain_digest;
+#[doc(hidden)]
+pub mod gain_reconcile;
+#[doc(hidden)]
+pub mod gain_team;
+#[doc(hidden)]
+pub mod gain_watch;
+#[doc(hidden)]
+pub mod generic_cmd;
+#[doc(hidden)]
+pub mod gh_cmd;
+#[doc(hidden)]
+pub mod git;
+#[doc(hidden)]
+pub mod git_diff;
+#[doc(hidden)]
+pub mod go_cmd;
+#[doc(hidden)]
+pub mod golangci_cmd;
+#[doc(hidden)]
+pub mod grep_cmd;
+#[doc(hidden)]
+pub mod html_cmd;
+#[doc(hidden)]
+pub mod init;
+#[doc(hidden)]
+pub mod install_cmd;
+#[doc(hidden)]
+pub mod integrate;
+#[doc(hidden)]
+pub mod json_cmd;
+#[doc(hidden)]
+pub mod learn;
+#[doc(hidden)]
+pub mod lighthouse_cmd;
+#[doc(hidden)]
+pub mod lint_cmd;
+#[doc(hidden)]
+pub mod local_llm;
+#[doc(hidden)]
+pub mod local_time;
+#[doc(hidden)]
+pub mod log_cmd;
+#[doc(hidden)]
+pub mod ls;
+#[doc(hidden)]
+pub mod mapped;
+#[doc(hidden)]
+pub mod markdown;
+#[doc(hidden)]
+pub mod mcp;
+#[doc(hidden)]
+pub mod next_cmd;
+#[doc(hidden)]
+pub mod nm_cmd;
+#[doc(hidden)]
+pub mod npm_cmd;
+#[doc(hidden)]
+pub mod owners_cmd;
+#[doc(hidden)]
+pub mod pager;
+#[doc(hidden)]
+pub mod parallel;
+#[doc(hidden)]
+pub mod parser;
+#[doc(hidden)]
+pub mod path_rules;
+#[doc(hidden)]
+pub mod pip_cmd;
+#[doc(hidden)]
+pub mod pipeline;
+#[doc(hidden)]
+pub mod plan;
+#[doc(hidden)]
+pub mod playwright_cmd;
+#[doc(hidden)]
+pub mod plugin;
+#[doc(hidden)]
+pub mod pnpm_cmd;
+#[doc(hidden)]
+pub mod policy;
+#[doc(hidden)]
+pub mod ports_cmd;
+#[doc(hidden)]
+pub mod pr_cmd;
+#[doc(hidden)]
+pub mod prettier_cmd;
+#[doc(hidden)]
+pub mod prisma_cmd;
+#[doc(hidden)]
+pub mod profile;
+#[doc(hidden)]
+pub mod pytest_cmd;
+#[doc(hidden)]
+pub mod read;
+#[doc(hidden)]
+pub mod redact;
+#[doc(hidden)]
+pub mod relnotes_cmd;
+#[doc(hidden)]
+pub mod rewrite;
+#[doc(hidden)]
+pub mod ruff_cmd;
+#[doc(hidden)]
+pub mod runner;
+#[doc(hidden)]
+pub mod sarif;
+#[doc(hidden)]
+pub mod script;
+#[doc(hidden)]
+pub mod secrets_cmd;
+#[doc(hidden)]
+pub mod semgrep_cmd;
+#[doc(hidden)]
+pub mod sourcemap_cmd;
+#[doc(hidden)]
+pub mod spill;
+#[doc(hidden)]
+pub mod stream;
+#[doc(hidden)]
+pub mod style;
+#[doc(hidden)]
+pub mod summarize_cmd;
+#[doc(hidden)]
+pub mod summary;
+#[doc(hidden)]
+pub mod syspkg_cmd;
+#[doc(hidden)]
+pub mod systemctl_cmd;
+#[doc(hidden)]
+pub mod test_history;
+#[doc(hidden)]
+pub mod timings;
+#[doc(hidden)]
+pub mod todo_cmd;
+pub mod tracking;
+#[doc(hidden)]
+pub mod tree;
+#[doc(hidden)]
+pub mod tsc_cmd;
+pub mod utils;
+#[doc(hidden)]
+pub mod vitest_cmd;
+#[doc(hidden)]
+pub mod vuln_cmd;
+#[doc(hidden)]
+pub mod walk;
+#[doc(hidden)]
+pub mod watch;
+#[doc(hidden)]
+pub mod wget_cmd;
+#[doc(hidden)]
+pub mod xcode_cmd;
+
+/// `use rtk::prelude::*;` for the stable API.
+pub mod prelude {
+    pub use crate::api::{
+        compress, compress_diff, compress_log, condense_test_output, estimate_tokens,
+        filter_errors, summarize, summarize_json, Kind, Summary, Tokenizer,
+    };
+}
@@ -0,0 +1,358 @@
+use crate::exit_code;
+use crate::tracking;
+use crate::utils::truncate;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::path::Path;
+use std::process::Command;
+
+/// Lab metrics shown on one line: audit id, short label.
+const METRICS: &[(&str, &str)] = &[
+    ("first-contentful-paint", "FCP"),
+    ("largest-contentful-paint", "LCP"),
+    ("total-blocking-time", "TBT"),
+    ("cumulative-layout-shift", "CLS"),
+    ("speed-index", "SI"),
+    ("interactive", "TTI"),
+];
+
+/// Audits scoring at or above this pass.
+const PASS: f64 = 0.9;
+const MAX_OPPORTUNITIES: usize = 8;
+/// Failing audits named per category before the rest are counted.
+const MAX_FAILING: usize = 8;
+/// Resource URLs listed under each opportunity.
+const MAX_ITEMS: usize = 2;
+
+fn score_icon(score: f64) -> &'static str {
+    if score >= PASS {
+        "✓"
+    } else if score >= 0.5 {
+        "~"
+    } else {
+        "✗"
+    }
+}
+
+fn format_bytes(bytes: f64) -> String {
+    if bytes >= 1024.0 * 1024.0 {
+        format!("{:.1} MiB", bytes / (1024.0 * 1024.0))
+    } else {
+        format!("{:.0} KiB", bytes / 1024.0)
+    }
+}
+
+/// Estimated savings: `overallSavingsMs/Bytes` (Lighthouse ≤11) or the
+/// largest `metricSavings` entry (12+).
+fn savings(audit: &Value) -> (f64, f64) {
+    let details = &audit["details"];
+    let ms = details["overallSavingsMs"].as_f64().unwrap_or_else(|| {
+        audit["metricSavings"]
+            .as_object()
+            .map(|m| m.values().filter_map(Value::as_f64).fold(0.0, f64::max))
+            .unwrap_or(0.0)
+    });
+    let bytes = details["overallSavingsBytes"].as_f64().unwrap_or(0.0);
+    (ms, bytes)
+}
+
+/// Path part of a resource URL, the bit that identifies the file.
+fn short_url(url: &str) -> String {
+    let path = url
+        .split("://")
+        .nth(1)
+        .and_then(|rest| rest.find('/').map(|i| &rest[i..]))
+        .unwrap_or(url);
+    truncate(path.split('?').next().unwrap_or(path), 70)
+}
+
+fn format_report(report: &Value) -> String {
+    let mut out = Vec::new();
+    let audits = &report["audits"];
+
+    let url = report["finalDisplayedUrl"]
+        .as_str()
+        .or_else(|| report["finalUrl"].as_str())
+        .or_else(|| report["requestedUrl"].as_str())
+        .unwrap_or("?");
+    let form = report["configSettings"]["formFactor"]
+        .as_str()
+        .or_else(|| report["configSettings"]["emulatedFormFactor"].as_str())
+        .unwrap_or("?");
+    out.push(format!(
+        "🚦 Lighthouse {} ({}, v{})",
+        url,
+        form,
+        report["lighthouseVersion"].as_str().unwrap_or("?")
+    ));
+    if let Some(error) = report["runtimeError"]["message"].as_str() {
+        out.push(format!("  ⚠️ {}", error));
+    }
+
+    let categories = report["categories"].as_object();
+    if let Some(categories) = categories {
+        let scores: Vec<String> = categories
+            .values()
+            .map(|c| {
+                let score = c["score"]
+                    .as_f64()
+                    .map_or("?".to_string(), |s| format!("{:.0}", s * 100.0));
+                format!("{} {}", c["title"].as_str().unwrap_or("?"), score)
+            })
+            .collect();
+        out.push(format!("  {}", scores.join(" | ")));
+    }
+
+    let metrics: Vec<String> = METRICS
+        .iter()
+        .filter_map(|(id, label)| {
+            let audit = &audits[*id];
+            let value = audit["displayValue"].as_str()?;
+            let icon = audit["score"].as_f64().map_or("", score_icon);
+            Some(format!(
+                "{} {} {}",
+                label,
+                value.replace('\u{a0}', " "),
+                icon
+            ))
+        })
+        .collect();
+    if !metrics.is_empty() {
+        out.push(format!("Metrics: {}", metrics.join(" | ")));
+    }
+
+    let is_metric = |id: &str| METRICS.iter().any(|(m, _)| *m == id);
+    let mut opportunities: Vec<(&str, &Value, f64, f64)> = audits
+        .as_object()
+        .map(|audits| {
+            audits
+                .iter()
+                .filter(|(id, a)| !is_metric(id) && a["score"].as_f64().is_some_and(|s| s < PASS))
+                .map(|(id, a)| {
+                    let (ms, bytes) = savings(a);
+                    (id.as_str(), a, ms, bytes)
+                })
+                .filter(|(_, _, ms, bytes)| *ms >= 50.0 || *bytes >= 10240.0)
+                .collect()
+        })
+        .unwrap_or_default();
+    opportunities.sort_by(|a, b| {
+        b.2.partial_cmp(&a.2)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| b.3.partial_cmp(&a.3).unwrap_or(std::cmp::Ordering::Equal))
+            .then_with(|| a.0.cmp(b.0))
+    });
+    if !opportunities.is_empty() {
+        out.push(String::new());
+        out.push("Opportunities (est. savings):".to_string());
+        for (_, audit, ms, bytes) in opportunities.iter().take(MAX_OPPORTUNITIES) {
+            let mut saved = Vec::new();
+            if *ms > 0.0 {
+                saved.push(format!("{:.0} ms", ms));
+            }
+            if *bytes > 0.0 {
+                saved.push(format_bytes(*bytes));
+            }
+            out.push(format!(
+                "  {} — {}",
+                audit["title"].as_str().unwrap_or("?"),
+                saved.join(", ")
+            ));
+            let items = audit["details"]["items"].as_array();
+            for item in items.into_iter().flatten().take(MAX_ITEMS) {
+                let Some(url) = item["url"].as_str() else {
+                    continue;
+                };
+                let wasted = item["wastedBytes"]
+                    .as_f64()
+                    .map(|b| format!(" ({})", format_bytes(b)))
+                    .unwrap_or_default();
+                out.push(format!("    {}{}", short_url(url), wasted));
+            }
+        }
+        if opportunities.len() > MAX_OPPORTUNITIES {
+            out.push(format!(
+                "  ... +{} more",
+                opportunities.len() - MAX_OPPORTUNITIES
+            ));
+        }
+    }
+
+    // Remaining failures, by the category that references them
+    let listed: Vec<&str> = opportunities.iter().map(|(id, ..)| *id).collect();
+    let mut failing = Vec::new();
+    for category in categories.into_iter().flat_map(|c| c.values()) {
+        let mut names = Vec::new();
+        for audit_ref in category["auditRefs"].as_array().into_iter().flatten() {
+            let Some(id) = audit_ref["id"].as_str() else {
+                continue;
+            };
+            let audit = &audits[id];
+            let failed = audit["score"].as_f64().is_some_and(|s| s < PASS)
+                && matches!(
+                    audit["scoreDisplayMode"].as_str(),
+                    Some("binary" | "numeric" | "metricSavings")
+                );
+            if !failed || is_metric(id) || listed.contains(&id) {
+                continue;
+            }
+            let count = audit["details"]["items"]
+                .as_array()
+                .map_or(0, |items| items.len());
+            names.push(if count > 0 {
+                format!("{} ({})", id, count)
+            } else {
+                id.to_string()
+            });
+        }
+        if names.is_empty() {
+            continue;
+        }
+        let total = names.len();
+        names.truncate(MAX_FAILING);
+        let mut line = format!(
+            "  {}: {}",
+            category["title"].as_str().unwrap_or("?"),
+            names.join(", ")
+        );
+        if total > MAX_FAILING {
+            line.push_str(&format!(", +{} more", total - MAX_FAILING));
+        }
+        failing.push(line);
+    }
+    if !failing.is_empty() {
+        out.push(String::new());
+        out.push("Failing audits:".to_string());
+        out.extend(failing);
+    }
+
+    out.join("\n")
+}
+
+pub fn run(args: &[String], verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+
+    // A saved report is summarized as-is; anything else is a URL to audit
+    let (json, code, original) = match args.first().filter(|a| Path::new(a).is_file()) {
+        Some(file) => (
+            std::fs::read_to_string(file).with_context(|| format!("Failed to read {}", file))?,
+            0,
+            format!("cat {}", file),
+        ),
+        None => {
+            let mut cmd = Command::new("lighthouse");
+            cmd.args(args);
+            if !args.iter().any(|a| a.starts_with("--output")) {
+                cmd.args(["--output=json", "--output-path=stdout"]);
+            }
+            if !args.iter().any(|a| a == "--quiet") {
+                cmd.arg("--quiet");
+            }
+            if !args.iter().any(|a| a.starts_with("--chrome-flags")) {
+                cmd.arg("--chrome-flags=--headless");
+            }
+            if verbose > 0 {
+                eprintln!("Running: lighthouse {} (JSON output)", args.join(" "));
+            }
+            let output = cmd
+                .output()
+                .context("Failed to run lighthouse. Is it installed? Try: npm i -g lighthouse")?;
+            if output.stdout.is_empty() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let filtered = truncate(stderr.trim(), 2000);
+                println!("{}", filtered);
+                timer.with_exit_code(output.status).track(
+                    &format!("lighthouse {}", args.join(" ")),
+                    &format!("rtk lighthouse {}", args.join(" ")),
+                    &stderr,
+                    &filtered,
+                );
+                return Ok(());
+            }
+            (
+                String::from_utf8_lossy(&output.stdout).to_string(),
+                exit_code::from_status(output.status),
+                format!("lighthouse {}", args.join(" ")),
+            )
+        }
+    };
+
+    let filtered = match serde_json::from_str::<Value>(&json) {
+        Ok(report) => format_report(&report),
+        Err(e) => format!(
+            "lighthouse (JSON parse failed: {})\n{}",
+            e,
+            truncate(&json, 500)
+        ),
+    };
+    println!("{}", filtered);
+
+    timer.with_code(code).track(
+        &original,
+        &format!("rtk lighthouse {}", args.join(" ")),
+        &json,
+        &filtered,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_format_report() {
+        let report = json!({
+            "lighthouseVersion": "12.1.0",
+            "finalDisplayedUrl": "https://shop.example/",
+            "configSettings": {"formFactor": "mobile"},
+            "categories": {
+                "performance": {"title": "Performance", "score": 0.54, "auditRefs": [
+                    {"id": "largest-contentful-paint"}, {"id": "unused-javascript"},
+                    {"id": "render-blocking-resources"}, {"id": "uses-long-cache-ttl"}]},
+                "accessibility": {"title": "Accessibility", "score": 0.92, "auditRefs": [
+                    {"id": "color-contrast"}, {"id": "image-alt"}]}
+            },
+            "audits": {
+                "first-contentful-paint": {"score": 0.95, "displayValue": "1.2\u{a0}s"},
+                "largest-contentful-paint": {"score": 0.2, "displayValue": "6.3\u{a0}s",
+                    "scoreDisplayMode": "numeric"},
+                "cumulative-layout-shift": {"score": 0.7, "displayValue": "0.12"},
+                "unused-javascript": {"title": "Reduce unused JavaScript", "score": 0.3,
+                    "scoreDisplayMode": "metricSavings",
+                    "details": {"type": "opportunity", "overallSavingsMs": 1250,
+                        "overallSavingsBytes": 430080, "items": [
+                        {"url": "https://shop.example/static/js/vendor.js?v=3", "wastedBytes": 317440},
+                        {"url": "https://cdn.example/analytics.js", "wastedBytes": 51200},
+                        {"url": "https://shop.example/static/js/app.js", "wastedBytes": 1024}]}},
+                "render-blocking-resources": {"title": "Eliminate render-blocking resources",
+                    "score": 0.5, "scoreDisplayMode": "metricSavings",
+                    "metricSavings": {"FCP": 300, "LCP": 450}, "details": {"items": []}},
+                "uses-long-cache-ttl": {"title": "Serve static assets with an efficient cache policy",
+                    "score": 0.4, "scoreDisplayMode": "numeric",
+                    "details": {"items": [{"url": "a"}, {"url": "b"}]}},
+                "color-contrast": {"score": 0, "scoreDisplayMode": "binary",
+                    "details": {"items": [{}, {}, {}]}},
+                "image-alt": {"score": 1, "scoreDisplayMode": "binary"}
+            }
+        });
+        let out = format_report(&report);
+        assert_eq!(
+            out,
+            "🚦 Lighthouse https://shop.example/ (mobile, v12.1.0)
+  Performance 54 | Accessibility 92
+Metrics: FCP 1.2 s ✓ | LCP 6.3 s ✗ | CLS 0.12 ~
+
+Opportunities (est. savings):
+  Reduce unused JavaScript — 1250 ms, 420 KiB
+    /static/js/vendor.js (310 KiB)
+    /analytics.js (50 KiB)
+  Eliminate render-blocking resources — 450 ms
+
+Failing audits:
+  Performance: uses-long-cache-ttl (2)
+  Accessibility: color-contrast (3)"
+        );
+    }
+}
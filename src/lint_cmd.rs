@@ -76,6 +76,7 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
     // ESLint returns exit code 1 when lint errors found (expected behavior)
     let filtered = if linter == "eslint" {
@@ -86,7 +87,7 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
 
     println!("{}", filtered);
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("{} {}", linter, args.join(" ")),
         &format!("rtk {} {}", linter, args.join(" ")),
         &raw,
@@ -0,0 +1,203 @@
+//! Calendar time in the configured timezone.
+//!
+//! Tracking stores UTC timestamps; daily and weekly views bucket them by the
+//! calendar of `display.timezone` (the system zone by default) so an evening
+//! session stays on one day, and weeks start on `display.week_start`. The
+//! bucketing runs inside SQLite through the [`register`]ed `rtk_date()` and
+//! `rtk_week_start()` functions, so every query groups the same way.
+
+use crate::config::{Config, WeekStart};
+use anyhow::{bail, Result};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use rusqlite::functions::FunctionFlags;
+use rusqlite::Connection;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Zone {
+    /// The system timezone (`TZ` or /etc/localtime)
+    Local,
+    Fixed(FixedOffset),
+    Named(Tz),
+}
+
+impl std::str::FromStr for Zone {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        match s.to_ascii_lowercase().as_str() {
+            "" | "local" => return Ok(Zone::Local),
+            "utc" | "z" | "gmt" => return Ok(Zone::Fixed(FixedOffset::east_opt(0).unwrap())),
+            _ => {}
+        }
+        if let Some(offset) = parse_offset(s) {
+            return Ok(Zone::Fixed(offset));
+        }
+        match s.parse::<Tz>() {
+            Ok(tz) => Ok(Zone::Named(tz)),
+            Err(_) => bail!(
+                "Unknown timezone '{}': expected local, UTC, an offset like +05:30 or an IANA name like Europe/Paris",
+                s
+            ),
+        }
+    }
+}
+
+impl Zone {
+    /// Wall-clock time of `time` in this zone.
+    pub fn naive(&self, time: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            Zone::Local => time.with_timezone(&Local).naive_local(),
+            Zone::Fixed(offset) => time.with_timezone(offset).naive_local(),
+            Zone::Named(tz) => time.with_timezone(tz).naive_local(),
+        }
+    }
+
+    pub fn date(&self, time: DateTime<Utc>) -> NaiveDate {
+        self.naive(time).date()
+    }
+}
+
+/// `+05:30`, `-0800`, `+02`
+fn parse_offset(s: &str) -> Option<FixedOffset> {
+    let sign = match s.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits: String = s[1..].chars().filter(|c| *c != ':').collect();
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match digits.len() {
+        1 | 2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (digits[..2].parse().ok()?, digits[2..].parse::<i32>().ok()?),
+        _ => return None,
+    };
+    if minutes >= 60 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// The configured zone; an invalid setting warns once and falls back to local.
+pub fn zone() -> Zone {
+    static ZONE: OnceLock<Zone> = OnceLock::new();
+    *ZONE.get_or_init(|| {
+        let setting = &Config::cached().display.timezone;
+        setting.parse().unwrap_or_else(|e| {
+            eprintln!("rtk: warning: {}", e);
+            Zone::Local
+        })
+    })
+}
+
+pub fn week_start_day() -> WeekStart {
+    Config::cached().display.week_start
+}
+
+/// `YYYY-MM-DD` of `time` in the configured zone.
+pub fn date(time: DateTime<Utc>) -> String {
+    zone().date(time).format("%Y-%m-%d").to_string()
+}
+
+/// `time` formatted (strftime) in the configured zone.
+pub fn format(time: DateTime<Utc>, fmt: &str) -> String {
+    zone().naive(time).format(fmt).to_string()
+}
+
+/// First day of the week containing `date`.
+pub fn week_start(date: NaiveDate, start: WeekStart) -> NaiveDate {
+    let offset = match start {
+        WeekStart::Iso => date.weekday().num_days_from_monday(),
+        WeekStart::Us => date.weekday().num_days_from_sunday(),
+    };
+    date - Duration::days(offset as i64)
+}
+
+/// Add `rtk_date(ts)` and `rtk_week_start(ts)` to `conn`: the local date and
+/// the local week start (`YYYY-MM-DD`) of an RFC 3339 timestamp.
+pub fn register(conn: &Connection) -> rusqlite::Result<()> {
+    register_with(conn, zone(), week_start_day())
+}
+
+fn register_with(conn: &Connection, zone: Zone, start: WeekStart) -> rusqlite::Result<()> {
+    let flags = FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC;
+    conn.create_scalar_function("rtk_date", 1, flags, move |ctx| {
+        let date = parse(&ctx.get::<String>(0)?).map(|t| zone.date(t));
+        Ok(date.map(|d| d.format("%Y-%m-%d").to_string()))
+    })?;
+    conn.create_scalar_function("rtk_week_start", 1, flags, move |ctx| {
+        let date = parse(&ctx.get::<String>(0)?).map(|t| week_start(zone.date(t), start));
+        Ok(date.map(|d| d.format("%Y-%m-%d").to_string()))
+    })
+}
+
+fn parse(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_zones() {
+        assert_eq!("local".parse::<Zone>().unwrap(), Zone::Local);
+        assert_eq!(
+            "UTC".parse::<Zone>().unwrap(),
+            Zone::Fixed(FixedOffset::east_opt(0).unwrap())
+        );
+        assert_eq!(
+            "+05:30".parse::<Zone>().unwrap(),
+            Zone::Fixed(FixedOffset::east_opt(5 * 3600 + 1800).unwrap())
+        );
+        assert_eq!(
+            "-08".parse::<Zone>().unwrap(),
+            Zone::Fixed(FixedOffset::west_opt(8 * 3600).unwrap())
+        );
+        assert_eq!(
+            "America/New_York".parse::<Zone>().unwrap(),
+            Zone::Named(chrono_tz::America::New_York)
+        );
+        assert!("Mars/Olympus".parse::<Zone>().is_err());
+        assert!("+25:99".parse::<Zone>().is_err());
+    }
+
+    #[test]
+    fn test_evening_stays_on_local_day() {
+        // 23:30 in New York is already the next day in UTC
+        let time = parse("2026-03-03T04:30:00+00:00").unwrap();
+        let zone: Zone = "America/New_York".parse().unwrap();
+        assert_eq!(zone.date(time).to_string(), "2026-03-02");
+        let utc: Zone = "UTC".parse().unwrap();
+        assert_eq!(utc.date(time).to_string(), "2026-03-03");
+    }
+
+    #[test]
+    fn test_week_start() {
+        let sunday = NaiveDate::from_ymd_opt(2026, 3, 8).unwrap();
+        assert_eq!(week_start(sunday, WeekStart::Iso).to_string(), "2026-03-02");
+        assert_eq!(week_start(sunday, WeekStart::Us).to_string(), "2026-03-08");
+    }
+
+    #[test]
+    fn test_sql_functions() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_with(&conn, "-05:00".parse().unwrap(), WeekStart::Us).unwrap();
+        let (date, week): (String, String) = conn
+            .query_row(
+                "SELECT rtk_date('2026-03-08T02:00:00+00:00'), rtk_week_start('2026-03-08T02:00:00+00:00')",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        // Saturday evening locally
+        assert_eq!(date, "2026-03-07");
+        assert_eq!(week, "2026-03-01");
+    }
+}
@@ -1,11 +1,45 @@
+//! `rtk log`: errors and warnings of a log, deduplicated and counted.
+//!
+//! Logs are read a chunk at a time and summarized in constant memory, so a
+//! multi-gigabyte file costs no more RAM than a small one: at most
+//! `MAX_DISTINCT` messages are counted per level (rare ones are evicted when
+//! the table fills up, so the top counts stay close), and the lines that are
+//! neither errors nor warnings are represented by a fixed-size reservoir
+//! sample.
+
+use crate::stream::{self, RawInput};
 use crate::tracking;
-use anyhow::Result;
+use crate::utils::truncate;
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::HashMap;
-use std::fs;
-use std::io::{self, BufRead};
+use std::fs::File;
+use std::io::{self, BufReader};
 use std::path::Path;
 
+/// Distinct messages counted per level before rare ones are evicted.
+const MAX_DISTINCT: usize = 1000;
+/// Other lines sampled to show what the rest of the log looks like.
+const SAMPLE_LINES: usize = 5;
+/// Width messages are kept and shown at.
+const LINE_WIDTH: usize = 100;
+/// Errors get more room: the end of the line is often the cause.
+const ERROR_WIDTH: usize = 200;
+/// Read buffer size.
+const CHUNK_BYTES: usize = 256 * 1024;
+
+lazy_static! {
+    static ref TIMESTAMP: Regex =
+        Regex::new(r"^\d{4}[-/]\d{2}[-/]\d{2}[T ]\d{2}:\d{2}:\d{2}[.,]?\d*\s*").unwrap();
+    static ref UUID: Regex =
+        Regex::new(r"[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}")
+            .unwrap();
+    static ref HEX: Regex = Regex::new(r"0x[0-9a-fA-F]+").unwrap();
+    static ref NUM: Regex = Regex::new(r"\b\d{4,}\b").unwrap();
+    static ref PATH: Regex = Regex::new(r"/[\w./\-]+").unwrap();
+}
+
 /// Filter and deduplicate log output
 pub fn run_file(file: &Path, verbose: u8) -> Result<()> {
     let timer = tracking::TimedExecution::start();
@@ -14,13 +48,13 @@ pub fn run_file(file: &Path, verbose: u8) -> Result<()> {
         eprintln!("Analyzing log: {}", file.display());
     }
 
-    let content = fs::read_to_string(file)?;
-    let result = analyze_logs(&content);
+    let reader = File::open(file).with_context(|| format!("Failed to read {}", file.display()))?;
+    let (input, result) = summarize(BufReader::with_capacity(CHUNK_BYTES, reader), "log file")?;
     println!("{}", result);
-    timer.track(
+    input.track(
+        timer,
         &format!("cat {}", file.display()),
         "rtk log",
-        &content,
         &result,
     );
     Ok(())
@@ -30,17 +64,12 @@ pub fn run_file(file: &Path, verbose: u8) -> Result<()> {
 pub fn run_stdin(_verbose: u8) -> Result<()> {
     let timer = tracking::TimedExecution::start();
 
-    let mut content = String::new();
     let stdin = io::stdin();
-    for line in stdin.lock().lines() {
-        content.push_str(&line?);
-        content.push('\n');
-    }
-
-    let result = analyze_logs(&content);
+    let reader = BufReader::with_capacity(CHUNK_BYTES, stdin.lock());
+    let (input, result) = summarize(reader, "stdin")?;
     println!("{}", result);
 
-    timer.track("log (stdin)", "rtk log (stdin)", &content, &result);
+    input.track(timer, "log (stdin)", "rtk log (stdin)", &result);
 
     Ok(())
 }
@@ -50,166 +79,249 @@ pub fn run_stdin_str(content: &str) -> String {
     analyze_logs(content)
 }
 
-fn analyze_logs(content: &str) -> String {
-    let mut result = Vec::new();
-    let mut error_counts: HashMap<String, usize> = HashMap::new();
-    let mut warn_counts: HashMap<String, usize> = HashMap::new();
-    let mut info_counts: HashMap<String, usize> = HashMap::new();
-    let mut unique_errors: Vec<String> = Vec::new();
-    let mut unique_warnings: Vec<String> = Vec::new();
-
-    // Patterns to normalize log messages
-    let timestamp_re =
-        Regex::new(r"^\d{4}[-/]\d{2}[-/]\d{2}[T ]\d{2}:\d{2}:\d{2}[.,]?\d*\s*").unwrap();
-    let uuid_re =
-        Regex::new(r"[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}")
-            .unwrap();
-    let hex_re = Regex::new(r"0x[0-9a-fA-F]+").unwrap();
-    let num_re = Regex::new(r"\b\d{4,}\b").unwrap();
-    let path_re = Regex::new(r"/[\w./\-]+").unwrap();
+fn summarize(reader: impl io::BufRead, source: &str) -> Result<(RawInput, String)> {
+    let mut input = RawInput::default();
+    let mut summary = LogSummary::default();
+    stream::each_line(reader, source, |line| {
+        input.push_line(&line);
+        summary.feed(&line);
+        true
+    })
+    .with_context(|| format!("Failed to read {}", source))?;
+    Ok((input, summary.finish()))
+}
 
+fn analyze_logs(content: &str) -> String {
+    let mut summary = LogSummary::default();
     for line in content.lines() {
-        let line_lower = line.to_lowercase();
+        summary.feed(line);
+    }
+    summary.finish()
+}
+
+/// Occurrences of each normalized message, with the first line seen for it.
+#[derive(Default)]
+struct MessageCounts {
+    counts: HashMap<String, Message>,
+    total: usize,
+    /// Some messages were evicted: counts are lower bounds
+    evicted: bool,
+    next_order: usize,
+}
+
+struct Message {
+    count: usize,
+    first: String,
+    order: usize,
+}
+
+impl MessageCounts {
+    fn add(&mut self, normalized: String, line: &str, width: usize) {
+        self.total += 1;
+        if let Some(message) = self.counts.get_mut(&normalized) {
+            message.count += 1;
+            return;
+        }
+        if self.counts.len() >= 2 * MAX_DISTINCT {
+            self.evict();
+        }
+        self.counts.insert(
+            normalized,
+            Message {
+                count: 1,
+                first: truncate(line, width),
+                order: self.next_order,
+            },
+        );
+        self.next_order += 1;
+    }
 
-        // Normalize for deduplication
-        let normalized =
-            normalize_log_line(line, &timestamp_re, &uuid_re, &hex_re, &num_re, &path_re);
+    /// Keep the `MAX_DISTINCT` most frequent messages. Amortized over the
+    /// `MAX_DISTINCT` inserts between evictions, so each line stays cheap.
+    fn evict(&mut self) {
+        let mut ranked: Vec<(usize, usize)> =
+            self.counts.values().map(|m| (m.count, m.order)).collect();
+        ranked.sort_unstable_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        let (min_count, min_order) = ranked[MAX_DISTINCT - 1];
+        self.counts.retain(|_, m| {
+            (m.count, std::cmp::Reverse(m.order)) >= (min_count, std::cmp::Reverse(min_order))
+        });
+        self.evicted = true;
+    }
+
+    /// "12" or, once messages were evicted, "1000+"
+    fn distinct(&self) -> String {
+        if self.evicted {
+            format!("{}+", MAX_DISTINCT)
+        } else {
+            self.counts.len().to_string()
+        }
+    }
+
+    /// Most frequent first, then in order of appearance.
+    fn ranked(&self) -> Vec<&Message> {
+        let mut list: Vec<&Message> = self.counts.values().collect();
+        list.sort_by(|a, b| b.count.cmp(&a.count).then(a.order.cmp(&b.order)));
+        list
+    }
+}
+
+/// Fixed-size uniform sample of a stream of lines (reservoir sampling),
+/// seeded so the same log always gives the same sample.
+struct Reservoir {
+    /// (line number, line)
+    kept: Vec<(usize, String)>,
+    seen: usize,
+    rng: u64,
+}
+
+impl Default for Reservoir {
+    fn default() -> Self {
+        Self {
+            kept: Vec::with_capacity(SAMPLE_LINES),
+            seen: 0,
+            rng: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+}
+
+impl Reservoir {
+    fn offer(&mut self, line_number: usize, line: &str) {
+        self.seen += 1;
+        if self.kept.len() < SAMPLE_LINES {
+            self.kept.push((line_number, truncate(line, LINE_WIDTH)));
+            return;
+        }
+        let slot = (self.next_random() % self.seen as u64) as usize;
+        if slot < SAMPLE_LINES {
+            self.kept[slot] = (line_number, truncate(line, LINE_WIDTH));
+        }
+    }
+
+    /// splitmix64
+    fn next_random(&mut self) -> u64 {
+        self.rng = self.rng.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Sampled lines in log order.
+    fn lines(&self) -> Vec<&(usize, String)> {
+        let mut lines: Vec<_> = self.kept.iter().collect();
+        lines.sort_by_key(|(n, _)| *n);
+        lines
+    }
+}
+
+/// Streaming log summary: feed lines, then [`finish`](Self::finish).
+#[derive(Default)]
+struct LogSummary {
+    errors: MessageCounts,
+    warnings: MessageCounts,
+    info: usize,
+    lines: usize,
+    sample: Reservoir,
+}
+
+impl LogSummary {
+    fn feed(&mut self, line: &str) {
+        self.lines += 1;
+        let line_lower = line.to_lowercase();
 
         // Categorize
         if line_lower.contains("error")
             || line_lower.contains("fatal")
             || line_lower.contains("panic")
         {
-            let count = error_counts.entry(normalized.clone()).or_insert(0);
-            if *count == 0 {
-                unique_errors.push(line.to_string());
-            }
-            *count += 1;
+            self.errors.add(normalize_log_line(line), line, ERROR_WIDTH);
         } else if line_lower.contains("warn") {
-            let count = warn_counts.entry(normalized.clone()).or_insert(0);
-            if *count == 0 {
-                unique_warnings.push(line.to_string());
+            self.warnings
+                .add(normalize_log_line(line), line, LINE_WIDTH);
+        } else {
+            if line_lower.contains("info") {
+                self.info += 1;
+            }
+            if !line.trim().is_empty() {
+                self.sample.offer(self.lines, line);
             }
-            *count += 1;
-        } else if line_lower.contains("info") {
-            *info_counts.entry(normalized).or_insert(0) += 1;
         }
     }
 
-    // Summary
-    let total_errors: usize = error_counts.values().sum();
-    let total_warnings: usize = warn_counts.values().sum();
-    let total_info: usize = info_counts.values().sum();
-
-    result.push(format!("📊 Log Summary"));
-    result.push(format!(
-        "   ❌ {} errors ({} unique)",
-        total_errors,
-        error_counts.len()
-    ));
-    result.push(format!(
-        "   ⚠️  {} warnings ({} unique)",
-        total_warnings,
-        warn_counts.len()
-    ));
-    result.push(format!("   ℹ️  {} info messages", total_info));
-    result.push(String::new());
-
-    // Errors with counts
-    if !unique_errors.is_empty() {
-        result.push("❌ ERRORS:".to_string());
-
-        // Sort by count
-        let mut error_list: Vec<_> = error_counts.iter().collect();
-        error_list.sort_by(|a, b| b.1.cmp(a.1));
-
-        for (normalized, count) in error_list.iter().take(10) {
-            // Find original message
-            let original = unique_errors
-                .iter()
-                .find(|e| {
-                    &normalize_log_line(e, &timestamp_re, &uuid_re, &hex_re, &num_re, &path_re)
-                        == *normalized
-                })
-                .map(|s| s.as_str())
-                .unwrap_or(normalized);
-
-            let truncated = if original.len() > 100 {
-                format!("{}...", &original[..97])
-            } else {
-                original.to_string()
-            };
-
-            if **count > 1 {
-                result.push(format!("   [×{}] {}", count, truncated));
-            } else {
-                result.push(format!("   {}", truncated));
-            }
-        }
+    fn finish(&self) -> String {
+        let mut result = Vec::new();
+
+        result.push("📊 Log Summary".to_string());
+        result.push(format!(
+            "   ❌ {} errors ({} unique)",
+            self.errors.total,
+            self.errors.distinct()
+        ));
+        result.push(format!(
+            "   ⚠️  {} warnings ({} unique)",
+            self.warnings.total,
+            self.warnings.distinct()
+        ));
+        result.push(format!("   ℹ️  {} info messages", self.info));
+        result.push(String::new());
 
-        if error_list.len() > 10 {
-            result.push(format!(
-                "   ... +{} more unique errors",
-                error_list.len() - 10
-            ));
+        // Errors with counts
+        if self.errors.total > 0 {
+            result.push("❌ ERRORS:".to_string());
+            push_ranked(&mut result, &self.errors, 10, "errors");
+            result.push(String::new());
         }
-        result.push(String::new());
-    }
 
-    // Warnings with counts
-    if !unique_warnings.is_empty() {
-        result.push("⚠️  WARNINGS:".to_string());
-
-        let mut warn_list: Vec<_> = warn_counts.iter().collect();
-        warn_list.sort_by(|a, b| b.1.cmp(a.1));
-
-        for (normalized, count) in warn_list.iter().take(5) {
-            let original = unique_warnings
-                .iter()
-                .find(|w| {
-                    &normalize_log_line(w, &timestamp_re, &uuid_re, &hex_re, &num_re, &path_re)
-                        == *normalized
-                })
-                .map(|s| s.as_str())
-                .unwrap_or(normalized);
-
-            let truncated = if original.len() > 100 {
-                format!("{}...", &original[..97])
-            } else {
-                original.to_string()
-            };
-
-            if **count > 1 {
-                result.push(format!("   [×{}] {}", count, truncated));
-            } else {
-                result.push(format!("   {}", truncated));
-            }
+        // Warnings with counts
+        if self.warnings.total > 0 {
+            result.push("⚠️  WARNINGS:".to_string());
+            push_ranked(&mut result, &self.warnings, 5, "warnings");
         }
 
-        if warn_list.len() > 5 {
+        if self.sample.seen > 0 {
+            if self.warnings.total > 0 {
+                result.push(String::new());
+            }
             result.push(format!(
-                "   ... +{} more unique warnings",
-                warn_list.len() - 5
+                "📝 SAMPLE ({} of {} other lines):",
+                self.sample.kept.len(),
+                self.sample.seen
             ));
+            for (n, line) in self.sample.lines() {
+                result.push(format!("   {:>6}: {}", n, line));
+            }
         }
+
+        result.join("\n")
     }
+}
 
-    result.join("\n")
+fn push_ranked(result: &mut Vec<String>, counts: &MessageCounts, top: usize, kind: &str) {
+    let ranked = counts.ranked();
+    for message in ranked.iter().take(top) {
+        if message.count > 1 {
+            result.push(format!("   [×{}] {}", message.count, message.first));
+        } else {
+            result.push(format!("   {}", message.first));
+        }
+    }
+    if ranked.len() > top {
+        let more = if counts.evicted {
+            "many".to_string()
+        } else {
+            (ranked.len() - top).to_string()
+        };
+        result.push(format!("   ... +{} more unique {}", more, kind));
+    }
 }
 
-fn normalize_log_line(
-    line: &str,
-    timestamp_re: &Regex,
-    uuid_re: &Regex,
-    hex_re: &Regex,
-    num_re: &Regex,
-    path_re: &Regex,
-) -> String {
-    let mut normalized = timestamp_re.replace_all(line, "").to_string();
-    normalized = uuid_re.replace_all(&normalized, "<UUID>").to_string();
-    normalized = hex_re.replace_all(&normalized, "<HEX>").to_string();
-    normalized = num_re.replace_all(&normalized, "<NUM>").to_string();
-    normalized = path_re.replace_all(&normalized, "<PATH>").to_string();
+fn normalize_log_line(line: &str) -> String {
+    let mut normalized = TIMESTAMP.replace_all(line, "").to_string();
+    normalized = UUID.replace_all(&normalized, "<UUID>").to_string();
+    normalized = HEX.replace_all(&normalized, "<HEX>").to_string();
+    normalized = NUM.replace_all(&normalized, "<NUM>").to_string();
+    normalized = PATH.replace_all(&normalized, "<PATH>").to_string();
     normalized.trim().to_string()
 }
 
@@ -229,5 +341,44 @@ mod tests {
         let result = analyze_logs(logs);
         assert!(result.contains("×3"));
         assert!(result.contains("ERRORS"));
+        assert!(result.contains("SAMPLE (1 of 1 other lines)"));
+    }
+
+    #[test]
+    fn test_bounded_counts_keep_frequent_messages() {
+        let mut summary = LogSummary::default();
+        for i in 0..20_000 {
+            // Letters, so normalization doesn't merge them
+            let id: String = format!("{:o}", i)
+                .bytes()
+                .map(|d| (d + 49) as char)
+                .collect();
+            summary.feed(&format!("ERROR unique failure {}", id));
+            if i % 10 == 0 {
+                summary.feed("ERROR: disk full");
+            }
+            summary.feed(&format!("request {} served", i));
+        }
+        assert!(summary.errors.counts.len() <= 2 * MAX_DISTINCT);
+        assert_eq!(summary.errors.total, 22_000);
+        assert_eq!(summary.sample.kept.len(), SAMPLE_LINES);
+        assert_eq!(summary.sample.seen, 20_000);
+
+        let result = summary.finish();
+        assert!(result.contains("(1000+ unique)"), "{}", result);
+        assert!(result.contains("[×2000] ERROR: disk full"), "{}", result);
+        assert!(
+            result.contains("SAMPLE (5 of 20000 other lines)"),
+            "{}",
+            result
+        );
+    }
+
+    #[test]
+    fn test_summarize_streams_reader() {
+        let log = "INFO start\nERROR boom\nERROR boom\n";
+        let (_, result) = summarize(log.as_bytes(), "test").unwrap();
+        assert!(result.contains("[×2] ERROR boom"));
+        assert_eq!(result, analyze_logs(log));
     }
 }
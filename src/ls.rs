@@ -1,5 +1,11 @@
+use crate::exit_code;
+use crate::path_rules::{PathAction, PathRules};
 use crate::tracking;
+use crate::utils::which;
+use crate::walk::{self, Options};
 use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
 use std::process::Command;
 
 /// Noise directories commonly excluded from LLM context
@@ -30,7 +36,63 @@ const NOISE_DIRS: &[&str] = &[
     ".eggs",
 ];
 
+/// Default token budget for `rtk ls --tree`
+const DEFAULT_TREE_BUDGET: usize = 800;
+
+/// Deepest level `--tree` will expand
+const MAX_TREE_DEPTH: usize = 8;
+
+/// Directories with more entries than this are always rolled up in `--tree`
+const MAX_DIR_ENTRIES: usize = 40;
+
+/// File names worth pointing out in `--tree`: (name, annotation)
+const KEY_FILES: &[(&str, &str)] = &[
+    ("main.rs", "entry"),
+    ("lib.rs", "entry"),
+    ("main.go", "entry"),
+    ("main.py", "entry"),
+    ("__main__.py", "entry"),
+    ("app.py", "entry"),
+    ("manage.py", "entry"),
+    ("index.js", "entry"),
+    ("index.ts", "entry"),
+    ("index.tsx", "entry"),
+    ("main.js", "entry"),
+    ("main.ts", "entry"),
+    ("server.js", "entry"),
+    ("server.ts", "entry"),
+    ("Main.java", "entry"),
+    ("Program.cs", "entry"),
+    ("main.c", "entry"),
+    ("main.cpp", "entry"),
+    ("main.swift", "entry"),
+    ("Cargo.toml", "config"),
+    ("package.json", "config"),
+    ("pyproject.toml", "config"),
+    ("setup.py", "config"),
+    ("requirements.txt", "config"),
+    ("go.mod", "config"),
+    ("tsconfig.json", "config"),
+    ("pom.xml", "config"),
+    ("build.gradle", "config"),
+    ("build.gradle.kts", "config"),
+    ("Gemfile", "config"),
+    ("composer.json", "config"),
+    ("Package.swift", "config"),
+    ("CMakeLists.txt", "config"),
+    ("Makefile", "config"),
+    ("justfile", "config"),
+    ("Dockerfile", "config"),
+    ("docker-compose.yml", "config"),
+    ("compose.yaml", "config"),
+    (".rtk.toml", "config"),
+    ("README.md", "docs"),
+];
+
 pub fn run(args: &[String], verbose: u8) -> Result<()> {
+    if let Some((budget, rest)) = take_tree(args)? {
+        return run_tree(&rest, budget, verbose);
+    }
     let timer = tracking::TimedExecution::start();
 
     // Separate flags from paths
@@ -80,16 +142,29 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
         }
     }
 
-    let output = cmd.output().context("Failed to run ls")?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        eprint!("{}", stderr);
-        std::process::exit(output.status.code().unwrap_or(1));
-    }
-
-    let raw = String::from_utf8_lossy(&output.stdout).to_string();
-    let filtered = compact_ls(&raw, show_all);
+    let raw = if which("ls").is_some() {
+        let output = cmd.output().context("Failed to run ls")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            eprint!("{}", stderr);
+            exit_code::exit_with(output.status);
+        }
+        String::from_utf8_lossy(&output.stdout).to_string()
+    } else {
+        // Plain Windows has no `ls`: list natively in the same format
+        native_listing(&paths)?
+    };
+    // Path rules only apply to a single listed directory (no per-dir headers)
+    let rules = PathRules::load();
+    let base = Path::new(paths.first().copied().unwrap_or("."));
+    let classify = |name: &str, is_dir: bool| {
+        if paths.len() > 1 {
+            PathAction::Keep
+        } else {
+            rules.action(&base.join(name), is_dir)
+        }
+    };
+    let filtered = compact_ls(&raw, show_all, &classify);
 
     if verbose > 0 {
         eprintln!(
@@ -120,6 +195,300 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
     Ok(())
 }
 
+/// Split the rtk-only `--tree` / `--budget N` flags out of the ls args.
+/// `None` when `--tree` wasn't given.
+fn take_tree(args: &[String]) -> Result<Option<(usize, Vec<String>)>> {
+    let mut tree = false;
+    let mut budget = DEFAULT_TREE_BUDGET;
+    let mut rest = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = if arg == "--tree" {
+            tree = true;
+            continue;
+        } else if arg == "--budget" {
+            iter.next().map(String::as_str)
+        } else if let Some(v) = arg.strip_prefix("--budget=") {
+            Some(v)
+        } else {
+            rest.push(arg.clone());
+            continue;
+        };
+        budget = value
+            .and_then(|v| v.parse().ok())
+            .filter(|b| *b > 0)
+            .context("--budget expects a positive number of tokens")?;
+    }
+    Ok(tree.then_some((budget, rest)))
+}
+
+/// A directory (or file) in the `--tree` listing, with rolled-up totals.
+#[derive(Debug, Default)]
+struct TreeNode {
+    name: String,
+    is_dir: bool,
+    /// File size, or total size of everything below a directory
+    bytes: u64,
+    /// Files below a directory (recursive)
+    files: usize,
+    /// `.rtkignore`/config summarize rule: always shown rolled up
+    summarized: bool,
+    children: Vec<TreeNode>,
+}
+
+impl TreeNode {
+    fn dir(name: &str) -> Self {
+        TreeNode {
+            name: name.to_string(),
+            is_dir: true,
+            ..Default::default()
+        }
+    }
+
+    /// Insert a path (relative components) below this node.
+    fn insert(&mut self, parts: &[&str], is_dir: bool, bytes: u64, summarized: bool) {
+        let Some((first, rest)) = parts.split_first() else {
+            return;
+        };
+        let idx = match self.children.iter().position(|c| c.name == *first) {
+            Some(idx) => idx,
+            None => {
+                let mut node = TreeNode::dir(first);
+                node.is_dir = is_dir || !rest.is_empty();
+                self.children.push(node);
+                self.children.len() - 1
+            }
+        };
+        let child = &mut self.children[idx];
+        if rest.is_empty() {
+            child.summarized |= summarized;
+            if !is_dir {
+                child.bytes = bytes;
+                child.files = 1;
+            }
+        } else {
+            child.insert(rest, is_dir, bytes, summarized);
+        }
+    }
+
+    /// Fill in directory totals and sort (dirs first, then by name).
+    fn finish(&mut self) {
+        if !self.is_dir {
+            return;
+        }
+        for child in &mut self.children {
+            child.finish();
+        }
+        self.bytes = self.children.iter().map(|c| c.bytes).sum();
+        self.files = self.children.iter().map(|c| c.files).sum();
+        self.children
+            .sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    }
+
+    fn height(&self) -> usize {
+        self.children
+            .iter()
+            .filter(|c| c.is_dir && !c.children.is_empty())
+            .map(|c| c.height() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn count_dirs(&self) -> usize {
+        self.children
+            .iter()
+            .filter(|c| c.is_dir)
+            .map(|c| 1 + c.count_dirs())
+            .sum()
+    }
+}
+
+/// Walk `root` honoring `.gitignore`, `.rtkignore`, noise dirs and path rules.
+fn build_tree(root: &Path, show_all: bool) -> TreeNode {
+    let rules = PathRules::load();
+    let mut tree = TreeNode::dir(".");
+    let opts = Options {
+        hidden: show_all,
+        prune: if show_all { &[] } else { NOISE_DIRS },
+    };
+    for entry in walk::walk(root, opts) {
+        let Ok(rel) = entry.path.strip_prefix(root) else {
+            continue;
+        };
+        if rel.as_os_str().is_empty() {
+            continue;
+        }
+        let is_dir = entry.is_dir();
+        let summarized = match rules.action(&entry.path, is_dir) {
+            PathAction::Keep => false,
+            PathAction::Exclude => continue,
+            PathAction::Summarize => true,
+        };
+        let bytes = if is_dir {
+            0
+        } else {
+            entry.metadata().map(|m| m.len()).unwrap_or(0)
+        };
+        let parts: Vec<String> = rel
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        let parts: Vec<&str> = parts.iter().map(String::as_str).collect();
+        tree.insert(&parts, is_dir, bytes, summarized);
+    }
+    tree.finish();
+    tree
+}
+
+fn key_file(name: &str) -> Option<&'static str> {
+    KEY_FILES
+        .iter()
+        .find(|(key, _)| *key == name)
+        .map(|(_, note)| *note)
+}
+
+/// Render `tree` expanded to `depth` levels; deeper, large or summarized
+/// directories collapse to `name/ (N files, SIZE)`.
+fn render_tree(tree: &TreeNode, depth: usize) -> String {
+    fn walk(node: &TreeNode, level: usize, depth: usize, out: &mut String) {
+        let indent = "  ".repeat(level);
+        for child in &node.children {
+            if !child.is_dir {
+                out.push_str(&format!(
+                    "{}{}  {}",
+                    indent,
+                    child.name,
+                    human_size(child.bytes)
+                ));
+                if let Some(note) = key_file(&child.name) {
+                    out.push_str(&format!("  [{}]", note));
+                }
+                out.push('\n');
+            } else if child.children.is_empty() {
+                out.push_str(&format!("{}{}/\n", indent, child.name));
+            } else if level + 1 >= depth
+                || child.summarized
+                || child.children.len() > MAX_DIR_ENTRIES
+            {
+                out.push_str(&format!(
+                    "{}{}/ ({} files, {})\n",
+                    indent,
+                    child.name,
+                    child.files,
+                    human_size(child.bytes)
+                ));
+            } else {
+                out.push_str(&format!("{}{}/\n", indent, child.name));
+                walk(child, level + 1, depth, out);
+            }
+        }
+    }
+    let mut out = String::new();
+    walk(tree, 0, depth, &mut out);
+    out
+}
+
+/// Deepest rendering of `tree` that fits in `budget` tokens (at least depth 1).
+fn fit_tree(tree: &TreeNode, budget: usize) -> (String, usize) {
+    let max_depth = (tree.height() + 1).min(MAX_TREE_DEPTH);
+    let mut best = (render_tree(tree, 1), 1);
+    for depth in 2..=max_depth {
+        let rendered = render_tree(tree, depth);
+        if tracking::estimate_tokens(&rendered) > budget {
+            break;
+        }
+        best = (rendered, depth);
+    }
+    best
+}
+
+fn run_tree(args: &[String], budget: usize, verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let show_all = args
+        .iter()
+        .any(|a| (a.starts_with('-') && !a.starts_with("--") && a.contains('a')) || a == "--all");
+    let target = args
+        .iter()
+        .find(|a| !a.starts_with('-'))
+        .map(String::as_str)
+        .unwrap_or(".");
+    let root = Path::new(target);
+    if !root.is_dir() {
+        anyhow::bail!("{}: not a directory", target);
+    }
+
+    let tree = build_tree(root, show_all);
+    if tree.children.is_empty() {
+        println!("(empty)");
+        return Ok(());
+    }
+    let height = tree.height() + 1;
+    let (body, depth) = fit_tree(&tree, budget);
+    let filtered = format!(
+        "{}\n📊 {} files, {} dirs, {} (depth {} of {}, budget {} tokens)\n",
+        body,
+        tree.files,
+        tree.count_dirs(),
+        human_size(tree.bytes),
+        depth,
+        height,
+        budget
+    );
+    // What a full recursive listing would have cost
+    let raw = render_tree(&tree, usize::MAX);
+
+    if verbose > 0 {
+        eprintln!(
+            "Tokens: {} → {} (depth {})",
+            tracking::estimate_tokens(&raw),
+            tracking::estimate_tokens(&filtered),
+            depth
+        );
+    }
+    print!("{}", filtered);
+    timer.track(
+        &format!("ls -R {}", target),
+        "rtk ls --tree",
+        &raw,
+        &filtered,
+    );
+    Ok(())
+}
+
+/// `ls -la`-shaped lines built from `std::fs`, for systems without `ls`.
+fn native_listing(paths: &[&str]) -> Result<String> {
+    let targets = if paths.is_empty() {
+        vec!["."]
+    } else {
+        paths.to_vec()
+    };
+    let mut out = String::new();
+    for target in targets {
+        let path = Path::new(target);
+        let meta = fs::metadata(path).with_context(|| format!("Cannot access {}", target))?;
+        if !meta.is_dir() {
+            out.push_str(&listing_line(target, &meta));
+            continue;
+        }
+        if paths.len() > 1 {
+            out.push_str(&format!("{}:\n", target));
+        }
+        let mut entries: Vec<_> = fs::read_dir(path)?.filter_map(|e| e.ok()).collect();
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            if let Ok(meta) = entry.metadata() {
+                out.push_str(&listing_line(&entry.file_name().to_string_lossy(), &meta));
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn listing_line(name: &str, meta: &fs::Metadata) -> String {
+    let kind = if meta.is_dir() { 'd' } else { '-' };
+    format!("{}rw-r--r-- 1 - - {} - - - {}\n", kind, meta.len(), name)
+}
+
 /// Format bytes into human-readable size
 fn human_size(bytes: u64) -> String {
     if bytes >= 1_048_576 {
@@ -134,11 +503,14 @@ fn human_size(bytes: u64) -> String {
 /// Parse ls -la output into compact format:
 ///   name/  (dirs)
 ///   name  size  (files)
-fn compact_ls(raw: &str, show_all: bool) -> String {
+///
+/// `classify` applies `.rtkignore`/config path rules to each entry.
+fn compact_ls(raw: &str, show_all: bool, classify: &dyn Fn(&str, bool) -> PathAction) -> String {
     use std::collections::HashMap;
 
     let mut dirs: Vec<String> = Vec::new();
     let mut files: Vec<(String, String)> = Vec::new(); // (name, size)
+    let mut summarized: Vec<String> = Vec::new();
     let mut by_ext: HashMap<String, usize> = HashMap::new();
 
     for line in raw.lines() {
@@ -167,6 +539,15 @@ fn compact_ls(raw: &str, show_all: bool) -> String {
 
         let is_dir = parts[0].starts_with('d');
 
+        match classify(&name, is_dir) {
+            PathAction::Keep => {}
+            PathAction::Exclude => continue,
+            PathAction::Summarize => {
+                summarized.push(if is_dir { format!("{}/", name) } else { name });
+                continue;
+            }
+        }
+
         if is_dir {
             dirs.push(name);
         } else if parts[0].starts_with('-') || parts[0].starts_with('l') {
@@ -181,7 +562,7 @@ fn compact_ls(raw: &str, show_all: bool) -> String {
         }
     }
 
-    if dirs.is_empty() && files.is_empty() {
+    if dirs.is_empty() && files.is_empty() && summarized.is_empty() {
         return "(empty)\n".to_string();
     }
 
@@ -201,6 +582,10 @@ fn compact_ls(raw: &str, show_all: bool) -> String {
         out.push('\n');
     }
 
+    if !summarized.is_empty() {
+        out.push_str(&format!("~ {}\n", summarized.join(" ")));
+    }
+
     // Summary line
     out.push('\n');
     let mut summary = format!("📊 {} files, {} dirs", files.len(), dirs.len());
@@ -229,6 +614,10 @@ fn compact_ls(raw: &str, show_all: bool) -> String {
 mod tests {
     use super::*;
 
+    fn keep(_: &str, _: bool) -> PathAction {
+        PathAction::Keep
+    }
+
     #[test]
     fn test_compact_basic() {
         let input = "total 48\n\
@@ -237,7 +626,7 @@ mod tests {
                      drwxr-xr-x  2 user  staff    64 Jan  1 12:00 src\n\
                      -rw-r--r--  1 user  staff  1234 Jan  1 12:00 Cargo.toml\n\
                      -rw-r--r--  1 user  staff  5678 Jan  1 12:00 README.md\n";
-        let output = compact_ls(input, false);
+        let output = compact_ls(input, false, &keep);
         assert!(output.contains("src/"));
         assert!(output.contains("Cargo.toml"));
         assert!(output.contains("README.md"));
@@ -258,7 +647,7 @@ mod tests {
                      drwxr-xr-x  2 user  staff  64 Jan  1 12:00 target\n\
                      drwxr-xr-x  2 user  staff  64 Jan  1 12:00 src\n\
                      -rw-r--r--  1 user  staff  100 Jan  1 12:00 main.rs\n";
-        let output = compact_ls(input, false);
+        let output = compact_ls(input, false, &keep);
         assert!(!output.contains("node_modules"));
         assert!(!output.contains(".git"));
         assert!(!output.contains("target"));
@@ -271,7 +660,7 @@ mod tests {
         let input = "total 8\n\
                      drwxr-xr-x  2 user  staff  64 Jan  1 12:00 .git\n\
                      drwxr-xr-x  2 user  staff  64 Jan  1 12:00 src\n";
-        let output = compact_ls(input, true);
+        let output = compact_ls(input, true, &keep);
         assert!(output.contains(".git/"));
         assert!(output.contains("src/"));
     }
@@ -279,7 +668,7 @@ mod tests {
     #[test]
     fn test_compact_empty() {
         let input = "total 0\n";
-        let output = compact_ls(input, false);
+        let output = compact_ls(input, false, &keep);
         assert_eq!(output, "(empty)\n");
     }
 
@@ -290,12 +679,43 @@ mod tests {
                      -rw-r--r--  1 user  staff  1234 Jan  1 12:00 main.rs\n\
                      -rw-r--r--  1 user  staff  5678 Jan  1 12:00 lib.rs\n\
                      -rw-r--r--  1 user  staff   100 Jan  1 12:00 Cargo.toml\n";
-        let output = compact_ls(input, false);
+        let output = compact_ls(input, false, &keep);
         assert!(output.contains("📊 3 files, 1 dirs"));
         assert!(output.contains(".rs"));
         assert!(output.contains(".toml"));
     }
 
+    #[test]
+    fn test_compact_applies_path_rules() {
+        let input = "total 8\n\
+                     drwxr-xr-x  2 user  staff  64 Jan  1 12:00 generated\n\
+                     drwxr-xr-x  2 user  staff  64 Jan  1 12:00 fixtures\n\
+                     drwxr-xr-x  2 user  staff  64 Jan  1 12:00 src\n\
+                     -rw-r--r--  1 user  staff  100 Jan  1 12:00 main.rs\n";
+        let rules = |name: &str, _: bool| match name {
+            "generated" => PathAction::Exclude,
+            "fixtures" => PathAction::Summarize,
+            _ => PathAction::Keep,
+        };
+        let output = compact_ls(input, false, &rules);
+        assert!(!output.contains("generated"));
+        assert!(output.contains("~ fixtures/\n"));
+        assert!(output.contains("src/\n"));
+        assert!(output.contains("📊 1 files, 1 dirs"));
+    }
+
+    #[test]
+    fn test_native_listing_matches_ls_format() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("my notes.md"), "hello").unwrap();
+        let raw = native_listing(&[dir.path().to_str().unwrap()]).unwrap();
+        let out = compact_ls(&raw, false, &keep);
+        assert!(out.contains("src/\n"));
+        assert!(out.contains("my notes.md  5B\n"));
+        assert!(out.contains("1 files, 1 dirs"));
+    }
+
     #[test]
     fn test_human_size() {
         assert_eq!(human_size(0), "0B");
@@ -310,15 +730,102 @@ mod tests {
     fn test_compact_handles_filenames_with_spaces() {
         let input = "total 8\n\
                      -rw-r--r--  1 user  staff  1234 Jan  1 12:00 my file.txt\n";
-        let output = compact_ls(input, false);
+        let output = compact_ls(input, false, &keep);
         assert!(output.contains("my file.txt"));
     }
 
+    fn sample_tree() -> TreeNode {
+        let mut tree = TreeNode::dir(".");
+        tree.insert(&["Cargo.toml"], false, 1200, false);
+        tree.insert(&["src"], true, 0, false);
+        tree.insert(&["src", "main.rs"], false, 4300, false);
+        tree.insert(&["src", "cmd", "run.rs"], false, 900, false);
+        tree.insert(&["src", "cmd", "build.rs"], false, 800, false);
+        tree.insert(&["fixtures"], true, 0, true);
+        tree.insert(&["fixtures", "a.json"], false, 2048, false);
+        tree.finish();
+        tree
+    }
+
+    #[test]
+    fn test_take_tree() {
+        let args: Vec<String> = ["--tree", "--budget", "300", "src"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (budget, rest) = take_tree(&args).unwrap().unwrap();
+        assert_eq!(budget, 300);
+        assert_eq!(rest, vec!["src".to_string()]);
+        assert!(take_tree(&["-la".to_string()]).unwrap().is_none());
+        assert!(take_tree(&["--tree".to_string(), "--budget=x".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_render_tree_full_depth() {
+        let tree = sample_tree();
+        assert_eq!(tree.files, 5);
+        assert_eq!(tree.count_dirs(), 3);
+        assert_eq!(
+            render_tree(&tree, 3),
+            "fixtures/ (1 files, 2.0K)\n\
+             src/\n\
+             \x20 cmd/\n\
+             \x20   build.rs  800B\n\
+             \x20   run.rs  900B\n\
+             \x20 main.rs  4.2K  [entry]\n\
+             Cargo.toml  1.2K  [config]\n"
+        );
+    }
+
+    #[test]
+    fn test_render_tree_rolls_up_at_depth() {
+        let out = render_tree(&sample_tree(), 1);
+        assert!(out.contains("src/ (3 files, 5.9K)\n"));
+        assert!(!out.contains("main.rs"));
+        assert!(out.contains("Cargo.toml  1.2K  [config]"));
+    }
+
+    #[test]
+    fn test_fit_tree_respects_budget() {
+        let tree = sample_tree();
+        let (_, depth) = fit_tree(&tree, 10_000);
+        assert_eq!(depth, 3);
+        let (out, depth) = fit_tree(&tree, 1);
+        assert_eq!(depth, 1);
+        assert!(out.contains("src/ ("));
+    }
+
+    #[test]
+    fn test_render_tree_rolls_up_large_dirs() {
+        let mut tree = TreeNode::dir(".");
+        for i in 0..=MAX_DIR_ENTRIES {
+            tree.insert(&["gen", &format!("f{}.rs", i)], false, 10, false);
+        }
+        tree.finish();
+        let out = render_tree(&tree, 5);
+        assert_eq!(out, format!("gen/ ({} files, 410B)\n", MAX_DIR_ENTRIES + 1));
+    }
+
+    #[test]
+    fn test_build_tree_honors_gitignore() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "out/\n").unwrap();
+        fs::create_dir_all(dir.path().join("out")).unwrap();
+        fs::write(dir.path().join("out/big.bin"), "x").unwrap();
+        fs::create_dir_all(dir.path().join("node_modules/pkg")).unwrap();
+        fs::write(dir.path().join("node_modules/pkg/index.js"), "x").unwrap();
+        fs::write(dir.path().join("main.py"), "print()").unwrap();
+        let tree = build_tree(dir.path(), false);
+        let out = render_tree(&tree, 3);
+        assert_eq!(out, "main.py  7B  [entry]\n");
+    }
+
     #[test]
     fn test_compact_symlinks() {
         let input = "total 8\n\
                      lrwxr-xr-x  1 user  staff  10 Jan  1 12:00 link -> target\n";
-        let output = compact_ls(input, false);
+        let output = compact_ls(input, false, &keep);
         assert!(output.contains("link -> target"));
     }
 }
@@ -1,51 +1,35 @@
-mod cargo_cmd;
-mod cc_economics;
-mod ccusage;
-mod config;
-mod container;
-mod curl_cmd;
-mod deps;
-mod diff_cmd;
-mod discover;
-mod display_helpers;
-mod env_cmd;
-mod filter;
-mod find_cmd;
-mod gain;
-mod gh_cmd;
-mod git;
-mod go_cmd;
-mod golangci_cmd;
-mod grep_cmd;
-mod init;
-mod json_cmd;
-mod learn;
-mod lint_cmd;
-mod local_llm;
-mod log_cmd;
-mod ls;
-mod next_cmd;
-mod npm_cmd;
-mod parser;
-mod pip_cmd;
-mod playwright_cmd;
-mod pnpm_cmd;
-mod prettier_cmd;
-mod prisma_cmd;
-mod pytest_cmd;
-mod read;
-mod ruff_cmd;
-mod runner;
-mod summary;
-mod tracking;
-mod tree;
-mod tsc_cmd;
-mod utils;
-mod vitest_cmd;
-mod wget_cmd;
+// As in lib.rs: `print!`/`println!` go through the active output profile.
+macro_rules! print {
+    ($($arg:tt)*) => {
+        ::rtk::profile::print(&::std::format!($($arg)*))
+    };
+}
+
+macro_rules! println {
+    () => {
+        ::rtk::profile::print_line("")
+    };
+    ($($arg:tt)*) => {
+        ::rtk::profile::print_line(&::std::format!($($arg)*))
+    };
+}
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
+use rtk::{
+    advertise, alias, android_cmd, bench_self, bloat_cmd, budget, bundle_cmd, cargo_cmd,
+    cc_economics, config, conflicts_cmd, container, corpus, crash, curl_cmd, daemon, debug_log,
+    delta, deps, diff_cmd, discover, doc_cmd, doctor, elided, env_cmd, err_extract, exit_code,
+    explain, fetch_cmd, filter, find_cmd, fmt_cmd, gain, gain_digest, gain_reconcile, gain_team,
+    generic_cmd, gh_cmd, git, go_cmd, golangci_cmd, grep_cmd, html_cmd, init, install_cmd,
+    integrate, json_cmd, learn, lighthouse_cmd, lint_cmd, local_llm, log_cmd, ls, markdown, mcp,
+    next_cmd, nm_cmd, npm_cmd, owners_cmd, pager, parallel, pip_cmd, pipeline, plan,
+    playwright_cmd, plugin, pnpm_cmd, policy, ports_cmd, pr_cmd, prettier_cmd, prisma_cmd, profile,
+    pytest_cmd, read, relnotes_cmd, rewrite, ruff_cmd, runner, sarif, secrets_cmd, semgrep_cmd,
+    sourcemap_cmd, spill, stream, style, summarize_cmd, summary, syspkg_cmd, systemctl_cmd,
+    test_history, timings, todo_cmd, tracking, tree, tsc_cmd, vitest_cmd, vuln_cmd, watch,
+    wget_cmd, xcode_cmd,
+};
 use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 
@@ -71,6 +55,57 @@ struct Cli {
     /// Set SKIP_ENV_VALIDATION=1 for child processes (Next.js, tsc, lint, prisma)
     #[arg(long = "skip-env", global = true)]
     skip_env: bool,
+
+    /// Output profile: human (default), agent (dense, no emoji), minimal (machine-parsable)
+    #[arg(long, value_enum, global = true)]
+    profile: Option<profile::Profile>,
+
+    /// Colorize output: auto (default), always, never (NO_COLOR is honored)
+    #[arg(long, value_enum, value_name = "WHEN", global = true)]
+    color: Option<style::ColorChoice>,
+
+    /// Plain ASCII output: no emoji, box drawing or Unicode bars and arrows
+    #[arg(long, global = true)]
+    ascii: bool,
+
+    /// Print the unfiltered command output (savings are still measured and tracked)
+    #[arg(long, global = true)]
+    raw: bool,
+
+    /// Page the output interactively, with elided sections expandable (terminal only)
+    #[arg(long, global = true)]
+    pager: bool,
+
+    /// Append a report of what was removed and why (lines dropped, sections elided, savings)
+    #[arg(long, global = true)]
+    explain: bool,
+
+    /// Show which hook rewrite, wrapper and filters apply to the command, without running it
+    #[arg(long, global = true)]
+    plan: bool,
+
+    /// Threads for multi-file work (0 = one per CPU, 1 = serial; default: execution.jobs)
+    #[arg(long, value_name = "N", global = true)]
+    jobs: Option<usize>,
+
+    /// Print where rtk's own time went (parsing, setup, command, tracking) to stderr
+    #[arg(long, global = true)]
+    timings: bool,
+
+    /// Override a config setting for this run (KEY=VALUE, repeatable)
+    #[arg(long = "config", value_name = "KEY=VALUE", global = true)]
+    config_overrides: Vec<String>,
+
+    /// Append a trace of rtk's own steps to FILE (default: debug.log in rtk's data directory)
+    #[arg(
+        long,
+        value_name = "FILE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "",
+        global = true
+    )]
+    debug_log: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -92,8 +127,9 @@ enum Commands {
     /// Read file with intelligent filtering
     #[command(alias = "cat")]
     Read {
-        /// File to read
-        file: PathBuf,
+        /// Files to read (- for stdin); several are filtered in parallel (see --jobs)
+        #[arg(required = true, num_args = 1..)]
+        files: Vec<PathBuf>,
         /// Filter: none, minimal, aggressive
         #[arg(short, long, default_value = "minimal")]
         level: filter::FilterLevel,
@@ -103,6 +139,58 @@ enum Commands {
         /// Show line numbers
         #[arg(short = 'n', long)]
         line_numbers: bool,
+        /// Only show what changed since rtk last served this file (unified diff)
+        #[arg(long)]
+        delta: bool,
+        /// Markdown: only the heading outline, with line numbers
+        #[arg(long, conflicts_with_all = ["section", "delta"])]
+        headings: bool,
+        /// Markdown: only this section, nested ones included (e.g., "## Install" or install)
+        #[arg(long, conflicts_with = "delta")]
+        section: Option<String>,
+    },
+
+    /// URL as compact text (HTML extracted, JSON summarized), cached by URL + ETag
+    Fetch {
+        /// http(s) URL
+        url: String,
+        /// Skip the cache and fetch again
+        #[arg(long)]
+        refresh: bool,
+        /// Fetch even when robots.txt disallows it
+        #[arg(long)]
+        ignore_robots: bool,
+    },
+
+    /// HTML page (file or piped curl output) as readable text: main content + links list
+    Html {
+        /// HTML file (- or omitted for stdin)
+        #[arg(default_value = "-")]
+        file: PathBuf,
+        /// Page URL, to make relative links absolute
+        #[arg(long)]
+        base: Option<String>,
+        /// Omit the links list
+        #[arg(long)]
+        no_links: bool,
+    },
+
+    /// PDF/docx: extracted text summarized (headings by page), or pages/sections in full
+    Doc {
+        /// Document (.pdf or .docx)
+        file: PathBuf,
+        /// Print these pages in full (e.g., 3 or 3-5)
+        #[arg(short, long, conflicts_with_all = ["section", "full"])]
+        page: Option<String>,
+        /// Print one section (e.g., "## Scope" or scope)
+        #[arg(long, conflicts_with = "full")]
+        section: Option<String>,
+        /// Print all extracted text
+        #[arg(long)]
+        full: bool,
+        /// Token budget for the summary
+        #[arg(short, long, default_value = "500")]
+        max_tokens: usize,
     },
 
     /// Generate 2-line technical summary (heuristic-based)
@@ -132,21 +220,98 @@ enum Commands {
         args: Vec<String>,
     },
 
+    /// PR review packet: description, checks, files, substantive diff, unresolved threads inline
+    Pr {
+        /// PR number, URL or branch
+        number: String,
+        /// Repository (OWNER/REPO), defaults to the current one
+        #[arg(short = 'R', long)]
+        repo: Option<String>,
+        /// Maximum diff lines
+        #[arg(short = 'n', long, default_value = "200")]
+        max_lines: usize,
+    },
+
     /// pnpm commands with ultra-compact output
     Pnpm {
         #[command(subcommand)]
         command: PnpmCommands,
     },
 
-    /// Run command and show only errors/warnings
+    /// yarn: installs condensed to package counts, peer conflicts and build failures
+    Yarn {
+        /// yarn arguments (e.g., install, add react)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// poetry: installs condensed to package counts, conflicts and build failures
+    Poetry {
+        /// poetry arguments (e.g., install, add requests)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// brew: installed/upgraded/removed packages with sizes, full output on errors
+    Brew {
+        /// brew arguments (e.g., install wget)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// apt: packages to install/upgrade/remove with sizes, full output on errors
+    Apt {
+        /// apt arguments (e.g., install -y curl)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// apt-get: same as `rtk apt`
+    #[command(name = "apt-get")]
+    AptGet {
+        /// apt-get arguments (e.g., install -y curl)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// dnf: packages to install/upgrade/remove with sizes, full output on errors
+    Dnf {
+        /// dnf arguments (e.g., install -y curl)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// yum: same as `rtk dnf`
+    Yum {
+        /// yum arguments (e.g., install -y curl)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Print a section rtk elided, by the ID listed after the output (e.g., rtk show af3c01)
+    Show {
+        /// Section ID (af3c01 or [elided:af3c01])
+        id: String,
+    },
+
+    /// Run command (or read stdin with `-`) and show only errors/warnings, stack traces compressed
     Err {
-        /// Command to run
+        /// Drop warnings and notes (same as --min-severity error)
+        #[arg(long)]
+        only_errors: bool,
+        /// Lowest severity shown: error, warning, note
+        #[arg(long, default_value = "note")]
+        min_severity: err_extract::Severity,
+        /// Command to run, or `-` to read piped output
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
         command: Vec<String>,
     },
 
-    /// Run tests and show only failures
+    /// Run tests and show only failures, compared with the previous run
     Test {
+        /// List tests that failed and passed on the same code in recent runs
+        #[arg(long)]
+        flaky: bool,
         /// Test command (e.g. cargo test)
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
         command: Vec<String>,
@@ -191,6 +356,146 @@ enum Commands {
         /// Filter by type: f (file), d (directory)
         #[arg(short = 't', long, default_value = "f")]
         file_type: String,
+        /// Names shown per directory before the rest collapse to +N
+        #[arg(long, default_value_t = find_cmd::DEFAULT_PER_DIR)]
+        per_dir: usize,
+        /// Only entries modified within this age (e.g. 30m, 2h, 3d, 1w), newest first
+        #[arg(long, value_name = "AGE")]
+        changed_within: Option<String>,
+    },
+
+    /// fd with results grouped by directory (supports -e, -t, -g, --changed-within)
+    Fd {
+        /// Arguments passed to fd
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// TODO/FIXME/HACK/XXX markers grouped by directory, most urgent first, with blame authors
+    Todo {
+        /// Path to scan
+        #[arg(default_value = ".")]
+        path: String,
+        /// Maximum markers to list
+        #[arg(short, long, default_value = "50")]
+        max: usize,
+        /// Skip git blame (no per-author counts)
+        #[arg(long)]
+        no_blame: bool,
+    },
+
+    /// Code ownership from git blame: author shares, last touched, per-directory leaders, CODEOWNERS
+    Owners {
+        /// Path to summarize
+        #[arg(default_value = ".")]
+        path: String,
+        /// Authors to list
+        #[arg(short = 'n', long, default_value = "5")]
+        top: usize,
+    },
+
+    /// Markdown changelog from conventional commits, dependency bumps and chores folded
+    Relnotes {
+        /// Revision range (default: latest tag..HEAD)
+        range: Option<String>,
+    },
+
+    /// Listening ports and the processes owning them (ss/lsof)
+    Ports {
+        /// Only this port (listeners and connections to it)
+        port: Option<u16>,
+        /// Also list established connections per process
+        #[arg(short, long)]
+        all: bool,
+    },
+
+    /// Secret scan (gitleaks, trufflehog or built-in) grouped by rule and file, values redacted
+    Secrets {
+        /// Path to scan
+        #[arg(default_value = ".")]
+        path: String,
+        /// Scanner: auto (first installed), gitleaks, trufflehog or builtin
+        #[arg(long, default_value = "auto")]
+        scanner: String,
+        /// Maximum files to list
+        #[arg(short, long, default_value = "50")]
+        max: usize,
+        /// Extra scanner arguments (after --)
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+
+    /// SARIF findings (CodeQL, etc.) grouped by severity and rule
+    Sarif {
+        /// SARIF file (omit for stdin)
+        file: Option<PathBuf>,
+        /// Maximum rules to list
+        #[arg(short, long, default_value = "30")]
+        max: usize,
+    },
+
+    /// Symbolicate a minified JS stack trace with source maps, library frames folded
+    Sourcemap {
+        /// Stack trace file (omit for stdin)
+        file: Option<PathBuf>,
+        /// Directories searched for .map files (default: current directory)
+        #[arg(short, long)]
+        maps: Vec<PathBuf>,
+        /// Keep node_modules and bundler runtime frames
+        #[arg(short, long)]
+        all: bool,
+    },
+
+    /// Binary size: top symbols/crates from cargo-bloat, twiggy or bloaty, with deltas vs baseline
+    Bloat {
+        /// cargo-bloat, twiggy or bloaty, or a file with their JSON/CSV output
+        source: String,
+        /// Entries to list
+        #[arg(short = 'n', long, default_value = "15")]
+        top: usize,
+        /// Replace the stored baseline with this run
+        #[arg(long)]
+        save_baseline: bool,
+        /// Arguments for the tool
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Binary symbols: exported/local/imported counts by section, largest symbols, linked libraries
+    Nm {
+        /// Binary or library to inspect
+        binary: PathBuf,
+        /// Largest symbols to list
+        #[arg(short = 'n', long, default_value = "10")]
+        top: usize,
+    },
+
+    /// Lighthouse: category scores, web vitals, opportunities and failing audits
+    Lighthouse {
+        /// URL and lighthouse arguments, or a saved JSON report
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// JS bundle sizes from webpack stats, a Vite manifest or visualizer data, with deltas vs baseline
+    Bundle {
+        /// stats.json / manifest.json (default: stats.json, dist/stats.json, dist/.vite/manifest.json, ...)
+        file: Option<PathBuf>,
+        /// Modules and packages to list
+        #[arg(short = 'n', long, default_value = "10")]
+        top: usize,
+        /// Replace the stored baseline with this run
+        #[arg(long)]
+        save_baseline: bool,
+    },
+
+    /// Merge conflicts: unmerged files, hunks as minimal ours/base/theirs
+    Conflicts {
+        /// Only these paths
+        paths: Vec<String>,
+        /// Maximum lines shown per side of a hunk
+        #[arg(short = 'n', long, default_value = "12")]
+        max_lines: usize,
     },
 
     /// Ultra-condensed diff (only changed lines)
@@ -219,6 +524,22 @@ enum Commands {
         command: KubectlCommands,
     },
 
+    /// Run any command with generic compression (ANSI, progress bars, dedup, truncation)
+    X {
+        /// Command and arguments to execute
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+        command: Vec<String>,
+    },
+
+    /// Extractive summary of a prose document (README, RFC, design doc): outline + key sentences
+    Summarize {
+        /// File to summarize (- for stdin)
+        file: PathBuf,
+        /// Token budget for the summary
+        #[arg(short, long, default_value = "500")]
+        max_tokens: usize,
+    },
+
     /// Run command and show heuristic summary
     Summary {
         /// Command to run and summarize
@@ -280,6 +601,10 @@ enum Commands {
         /// Remove all RTK artifacts (hook, RTK.md, CLAUDE.md reference, settings.json entry)
         #[arg(long)]
         uninstall: bool,
+
+        /// Guided setup: detect agents, install the hook, create config, verify
+        #[arg(short, long, conflicts_with_all = ["show", "uninstall", "claude_md", "hook_only"])]
+        interactive: bool,
     },
 
     /// Download with compact output (strips progress bars)
@@ -296,6 +621,8 @@ enum Commands {
 
     /// Show token savings summary and history
     Gain {
+        #[command(subcommand)]
+        command: Option<GainCommands>,
         /// Show ASCII graph of daily savings
         #[arg(short, long)]
         graph: bool,
@@ -320,9 +647,24 @@ enum Commands {
         /// Show all time breakdowns (daily + weekly + monthly)
         #[arg(short, long)]
         all: bool,
-        /// Output format: text, json, csv
+        /// Output format: text, json, csv, markdown
         #[arg(short, long, default_value = "text")]
         format: String,
+        /// Live dashboard that refreshes as new commands are tracked
+        #[arg(long)]
+        watch: bool,
+        /// Show failure rate per command and the most frequently failing commands
+        #[arg(long)]
+        failures: bool,
+        /// Show savings per agent (claude-code, codex, shell, ...)
+        #[arg(long)]
+        by_agent: bool,
+        /// Show savings per model (recorded from RTK_MODEL / ANTHROPIC_MODEL / ...)
+        #[arg(long)]
+        by_model: bool,
+        /// Anonymize paths, URLs, repo names and username in output
+        #[arg(long, global = true)]
+        redact: bool,
     },
 
     /// Claude Code economics: spending (ccusage) vs savings (rtk) analysis
@@ -344,13 +686,106 @@ enum Commands {
         format: String,
     },
 
+    /// Diagnose hook setup, config, tracking database and PATH, with fixes
+    Doctor,
+
+    /// Measure compression and latency of rtk's filters on bundled fixtures
+    BenchSelf {
+        /// Timed runs per fixture; the median is reported
+        #[arg(long, default_value = "5")]
+        runs: usize,
+        /// Save this run as the baseline later runs are compared with
+        #[arg(long)]
+        save: bool,
+        /// Exit 1 if savings fall below a fixture's floor or the baseline
+        #[arg(long)]
+        check: bool,
+        /// Baseline file (default: bench-self.json in rtk's data directory)
+        #[arg(long)]
+        baseline: Option<PathBuf>,
+    },
+
+    /// Check every summarizer still keeps the annotated lines of the corpus fixtures
+    VerifyCorpus {
+        /// Corpus directory (default: ./corpus)
+        dir: Option<PathBuf>,
+    },
+
+    /// Print a CLAUDE.md / AGENTS.md section listing the rtk commands for this project
+    Advertise {
+        /// Update the section in place (CLAUDE.md and/or AGENTS.md, or --file)
+        #[arg(short, long)]
+        write: bool,
+        /// File to update instead of CLAUDE.md / AGENTS.md
+        #[arg(long, requires = "write")]
+        file: Option<PathBuf>,
+    },
+
+    /// Show how much of the context window the agent session has used, with advice
+    Budget {
+        /// Session transcript (default: the latest Claude Code session of this project)
+        #[arg(long)]
+        transcript: Option<PathBuf>,
+        /// Hook mode: read the hook payload on stdin, print a systemMessage past the warn threshold
+        #[arg(long, conflicts_with = "transcript")]
+        hook: bool,
+    },
+
+    /// Set up rtk for another coding agent in the current project (aider, cursor, codex, gemini)
+    Integrate {
+        /// Agent to integrate with
+        agent: integrate::Agent,
+        /// Remove the integration instead
+        #[arg(long)]
+        uninstall: bool,
+        /// Print the changes without writing them
+        #[arg(long)]
+        show: bool,
+    },
+
+    /// Serve a local HTTP/JSON API (summarize, run, gain) with caches kept warm
+    Daemon {
+        /// Port to listen on (127.0.0.1 only)
+        #[arg(short, long, default_value = "7171")]
+        port: u16,
+    },
+
+    /// Run a Model Context Protocol server on stdio exposing rtk's filters as tools
+    Mcp,
+
+    /// List the safety policy, or check whether a command is protected from rewriting
+    Policy {
+        /// Command to check
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+
+    /// Show the rtk rewrite of a command, as the agent hook applies it
+    Rewrite {
+        /// Command to rewrite
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+        /// Hook mode: read a PreToolUse tool call on stdin, print the hook response
+        #[arg(long, conflicts_with_all = ["command", "log"])]
+        hook: bool,
+        /// Show the last N rewrites made by the hook
+        #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "20", conflicts_with = "command")]
+        log: Option<usize>,
+    },
+
     /// Show or create configuration file
     Config {
+        #[command(subcommand)]
+        command: Option<ConfigCommands>,
+
         /// Create default config file
         #[arg(long)]
         create: bool,
     },
 
+    /// List command aliases (`alias.<name>` in config)
+    Alias,
+
     /// Vitest commands with compact output
     Vitest {
         #[command(subcommand)]
@@ -392,6 +827,13 @@ enum Commands {
         args: Vec<String>,
     },
 
+    /// Formatter check (cargo fmt/rustfmt/prettier/black/ruff format/gofmt): files + line counts, one hunk
+    FmtCheck {
+        /// Formatter command (e.g., cargo fmt --check)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
     /// Playwright E2E tests with compact output
     Playwright {
         /// Playwright arguments
@@ -511,6 +953,205 @@ enum Commands {
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
     },
+
+    /// Semgrep findings grouped by severity and rule, one snippet per rule
+    Semgrep {
+        /// semgrep arguments
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// adb logcat: app-scoped, deduplicated, crashes/ANRs first (--app <package>); other adb commands unchanged
+    Adb {
+        /// adb arguments, plus rtk's --app <package> for logcat
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Gradle (./gradlew when present): errors, warnings by file, failed tests, build result
+    Gradle {
+        /// Gradle tasks and arguments
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// xcodebuild: errors, warnings by file, failing tests (from xcresult), final status
+    Xcodebuild {
+        /// xcodebuild arguments
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Re-run a command on file changes, printing only what changed between runs
+    Watch {
+        /// Paths to watch (default: current directory)
+        #[arg(short, long = "path")]
+        paths: Vec<PathBuf>,
+        /// Milliseconds to wait for a burst of changes to settle
+        #[arg(long, default_value = "300")]
+        debounce: u64,
+        /// Command to re-run, as given to rtk (e.g. -- cargo test)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+        command: Vec<String>,
+    },
+
+    /// swift build/test: errors, warnings by file, failing tests; other subcommands unchanged
+    Swift {
+        /// swift arguments
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// systemctl status/list-units: unit states, journal lines for failed units only
+    Systemctl {
+        /// systemctl arguments
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Trivy vulnerabilities grouped by severity and package (--fail-on <severity> gates CI)
+    Trivy {
+        /// trivy arguments, plus rtk's --fail-on critical|high|medium|low
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Grype vulnerabilities grouped by severity and package (--fail-on <severity> gates CI)
+    Grype {
+        /// grype arguments, plus rtk's --fail-on critical|high|medium|low
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Any other command: summarized by a `rtk-<name>` plugin or [plugins.<name>]
+    #[command(external_subcommand)]
+    Plugin(Vec<OsString>),
+}
+
+#[derive(Subcommand)]
+enum GainCommands {
+    /// Export raw tracking data for downstream analysis
+    Export {
+        /// Stream every raw tracking row
+        #[arg(long)]
+        records: bool,
+        /// Output format: jsonl
+        #[arg(short, long, default_value = "jsonl")]
+        format: String,
+        /// Only export rows with id greater than this cursor
+        #[arg(long, default_value = "0")]
+        after: i64,
+        /// Maximum number of rows to export (prints next cursor on stderr)
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Apply pending tracking database schema migrations
+    Migrate {
+        /// Only report schema version and pending migrations (exit 1 if any)
+        #[arg(long)]
+        check: bool,
+    },
+    /// Import spooled tracking records into the database now
+    Compact,
+    /// Summarize the last week of savings, optionally posting it to a webhook or file
+    Digest {
+        /// Last 7 days (default)
+        #[arg(long, conflicts_with = "daily")]
+        weekly: bool,
+        /// Last 24 hours instead of the last week
+        #[arg(long)]
+        daily: bool,
+        /// Slack/Discord incoming webhook URL (default: digest.webhook)
+        #[arg(long)]
+        webhook: Option<String>,
+        /// Also write the digest to this file (default: digest.output)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Output format: text, json
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+    /// Upload anonymized per-day totals to the team server (see `rtk gain serve`)
+    Push {
+        /// Team server URL (default: team.server)
+        #[arg(long)]
+        server: Option<String>,
+        /// Bearer token (default: team.token)
+        #[arg(long)]
+        token: Option<String>,
+        /// Days of history to upload
+        #[arg(short, long, default_value = "30")]
+        days: u32,
+    },
+    /// Show the team's merged savings from the team server
+    Team {
+        /// Team server URL (default: team.server)
+        #[arg(long)]
+        server: Option<String>,
+        /// Bearer token (default: team.token)
+        #[arg(long)]
+        token: Option<String>,
+        /// Limit to the last N days
+        #[arg(short, long, default_value = "30")]
+        days: u32,
+        /// Output format: text, json
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+    /// Run the team aggregation server that `rtk gain push` uploads to
+    Serve {
+        /// Address to bind (use 0.0.0.0 behind a TLS proxy to serve the team)
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: String,
+        /// Port to listen on
+        #[arg(short, long, default_value = "7272")]
+        port: u16,
+        /// Bearer token clients must send (default: team.token)
+        #[arg(long)]
+        token: Option<String>,
+        /// JSON file storing the aggregates (default: <data dir>/rtk/team.json)
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Show the raw vs filtered output sample of a record (needs tracking.samples)
+    Inspect {
+        /// Record id (default: list recent sampled records)
+        id: Option<i64>,
+        /// Output format: text, json
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+    /// Compare tracked savings with real usage from Claude Code transcripts
+    Reconcile {
+        /// Transcripts directory (default: ~/.claude/projects)
+        #[arg(long)]
+        transcripts: Option<PathBuf>,
+        /// Filter by project directory name (substring match)
+        #[arg(short, long)]
+        project: Option<String>,
+        /// Limit to the last N days (transcripts and tracking data)
+        #[arg(short, long, default_value = "30")]
+        since: u64,
+        /// Output format: text, json
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Print the effective value of a setting (e.g. tracking.history_days)
+    Get { key: String },
+    /// Write a setting to the global config (or .rtk.toml with --project)
+    Set {
+        key: String,
+        value: String,
+        /// Write to the project's .rtk.toml instead of the global file
+        #[arg(long)]
+        project: bool,
+    },
+    /// List every effective setting and the layer it comes from
+    List,
 }
 
 #[derive(Subcommand)]
@@ -583,6 +1224,10 @@ enum GitCommands {
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
     },
+    /// Rebase/merge/cherry-pick/revert/bisect in progress: step, stopped commit, next commands
+    State,
+    /// Session-start overview: branch vs upstream, changes, stashes, worktrees, recent branches
+    Overview,
     /// Compact worktree listing
     Worktree {
         /// Git worktree arguments (add, remove, prune, or empty for list)
@@ -619,7 +1264,7 @@ enum PnpmCommands {
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
     },
-    /// Build (delegates to next build filter)
+    /// Build (detects Next.js, Nuxt or Angular; Next.js by default)
     Build {
         /// Additional build arguments
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
@@ -796,8 +1441,99 @@ enum GoCommands {
     Other(Vec<OsString>),
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+fn main() {
+    timings::start();
+    crash::install(
+        std::env::args_os()
+            .skip(1)
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect(),
+    );
+    let code = match run() {
+        Ok(()) => exit_code::pending(),
+        Err(e) => {
+            tracing::error!(error = format!("{:#}", e), "command failed");
+            eprintln!("Error: {:?}", e);
+            exit_code::for_error(&e)
+        }
+    };
+    timings::report();
+    tracing::debug!(code, pid = std::process::id(), "exit");
+    std::process::exit(code);
+}
+
+/// A builtin subcommand, which an alias can't shadow.
+fn is_builtin(name: &str) -> bool {
+    Cli::command().find_subcommand(name).is_some()
+}
+
+/// Parse again with the command expanded when it names an alias.
+fn expand_alias(matches: ArgMatches, cli: Cli) -> Result<(ArgMatches, Cli)> {
+    let Commands::Plugin(argv) = &cli.command else {
+        return Ok((matches, cli));
+    };
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let at = args.len() - argv.len();
+    let Some(expanded) = alias::expand(&args, at, is_builtin)? else {
+        return Ok((matches, cli));
+    };
+    let matches = Cli::command().get_matches_from(expanded);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    Ok((matches, cli))
+}
+
+fn run() -> Result<()> {
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    timings::enable(cli.timings);
+    debug_log::init(cli.debug_log.as_deref().map(Path::new));
+    timings::mark("cli");
+    config::set_cli_overrides(&cli.config_overrides)?;
+    let (matches, cli) = expand_alias(matches, cli)?;
+    crash::set_subcommand(matches.subcommand_name().unwrap_or_default());
+    pipeline::init(matches.subcommand_name().unwrap_or_default());
+    stream::init(matches.subcommand_name().unwrap_or_default());
+    parallel::init(cli.jobs);
+    profile::init(cli.profile);
+    style::init(cli.color, cli.ascii, profile::active());
+    if cli.plan {
+        return plan::run(
+            &Cli::command(),
+            &matches,
+            profile::raw_enabled(cli.raw),
+            cli.explain,
+        );
+    }
+    let raw = profile::raw_enabled(cli.raw);
+    let _raw_guard = raw.then(profile::enable_raw);
+    // Interactive paging is for people: not with --raw or agent profiles
+    let pager_guard =
+        (!raw && profile::active() == profile::Profile::Human && pager::enabled(cli.pager))
+            .then(pager::enable)
+            .flatten();
+    // --raw asks for everything, and the pager shows everything
+    let _spill_guard = (!raw && pager_guard.is_none())
+        .then(spill::enable)
+        .flatten();
+    if cli.explain {
+        explain::enable();
+    }
+    let _span = tracing::debug_span!(
+        "rtk",
+        command = matches.subcommand_name().unwrap_or_default(),
+        pid = std::process::id()
+    )
+    .entered();
+    tracing::debug!(
+        argv = ?std::env::args_os()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect::<Vec<_>>(),
+        cwd = ?std::env::current_dir().ok(),
+        profile = ?profile::active(),
+        raw,
+        "dispatch"
+    );
+    timings::mark("init");
 
     match cli.command {
         Commands::Ls { args } => {
@@ -809,18 +1545,67 @@ fn main() -> Result<()> {
         }
 
         Commands::Read {
-            file,
+            files,
             level,
             max_lines,
             line_numbers,
+            delta,
+            headings,
+            section,
         } => {
-            if file == Path::new("-") {
+            let file = match files.as_slice() {
+                [file] => file.clone(),
+                _ if headings || section.is_some() || delta => {
+                    anyhow::bail!("--headings, --section and --delta read one file at a time")
+                }
+                _ if files.iter().any(|f| f == Path::new("-")) => {
+                    anyhow::bail!("- (stdin) can only be read on its own")
+                }
+                _ => return read::run_many(&files, level, max_lines, line_numbers, cli.verbose),
+            };
+            if headings || section.is_some() {
+                markdown::run(&file, section.as_deref(), cli.verbose)?;
+            } else if file == Path::new("-") {
                 read::run_stdin(level, max_lines, line_numbers, cli.verbose)?;
+            } else if delta {
+                delta::run(&file, level, max_lines, line_numbers, cli.verbose)?;
             } else {
                 read::run(&file, level, max_lines, line_numbers, cli.verbose)?;
             }
         }
 
+        Commands::Fetch {
+            url,
+            refresh,
+            ignore_robots,
+        } => {
+            fetch_cmd::run(&url, refresh, ignore_robots, cli.verbose)?;
+        }
+
+        Commands::Html {
+            file,
+            base,
+            no_links,
+        } => {
+            html_cmd::run(&file, base.as_deref(), !no_links, cli.verbose)?;
+        }
+
+        Commands::Doc {
+            file,
+            page,
+            section,
+            full,
+            max_tokens,
+        } => {
+            let mode = match (page, section) {
+                (Some(range), _) => doc_cmd::Mode::Pages(range),
+                (_, Some(query)) => doc_cmd::Mode::Section(query),
+                _ if full => doc_cmd::Mode::Full,
+                _ => doc_cmd::Mode::Summary { max_tokens },
+            };
+            doc_cmd::run(&file, mode, cli.verbose)?;
+        }
+
         Commands::Smart {
             file,
             model,
@@ -868,6 +1653,12 @@ fn main() -> Result<()> {
                     cli.verbose,
                 )?;
             }
+            GitCommands::State => {
+                git::run(git::GitCommand::State, &[], None, cli.verbose)?;
+            }
+            GitCommands::Overview => {
+                git::run(git::GitCommand::Overview, &[], None, cli.verbose)?;
+            }
             GitCommands::Worktree { args } => {
                 git::run(git::GitCommand::Worktree, &args, None, cli.verbose)?;
             }
@@ -880,6 +1671,14 @@ fn main() -> Result<()> {
             gh_cmd::run(&subcommand, &args, cli.verbose, cli.ultra_compact)?;
         }
 
+        Commands::Pr {
+            number,
+            repo,
+            max_lines,
+        } => {
+            pr_cmd::run(&number, repo.as_deref(), max_lines, cli.verbose)?;
+        }
+
         Commands::Pnpm { command } => match command {
             PnpmCommands::List { depth, args } => {
                 pnpm_cmd::run(pnpm_cmd::PnpmCommand::List { depth }, &args, cli.verbose)?;
@@ -895,24 +1694,65 @@ fn main() -> Result<()> {
                 )?;
             }
             PnpmCommands::Build { args } => {
-                next_cmd::run(&args, cli.verbose)?;
+                next_cmd::run_build(&args, cli.verbose)?;
             }
             PnpmCommands::Typecheck { args } => {
                 tsc_cmd::run(&args, cli.verbose)?;
             }
             PnpmCommands::Other(args) => {
-                pnpm_cmd::run_passthrough(&args, cli.verbose)?;
+                if install_cmd::is_install("pnpm", args.first().and_then(|a| a.to_str())) {
+                    let args: Vec<String> = args
+                        .iter()
+                        .map(|a| a.to_string_lossy().into_owned())
+                        .collect();
+                    install_cmd::run("pnpm", &args, cli.verbose)?;
+                } else {
+                    pnpm_cmd::run_passthrough(&args, cli.verbose)?;
+                }
             }
         },
 
-        Commands::Err { command } => {
-            let cmd = command.join(" ");
-            runner::run_err(&cmd, cli.verbose)?;
+        Commands::Yarn { args } => install_cmd::run_tool("yarn", &args, cli.verbose)?,
+
+        Commands::Poetry { args } => install_cmd::run_tool("poetry", &args, cli.verbose)?,
+
+        Commands::Brew { args } => syspkg_cmd::run("brew", &args, cli.verbose)?,
+
+        Commands::Apt { args } => syspkg_cmd::run("apt", &args, cli.verbose)?,
+
+        Commands::AptGet { args } => syspkg_cmd::run("apt-get", &args, cli.verbose)?,
+
+        Commands::Dnf { args } => syspkg_cmd::run("dnf", &args, cli.verbose)?,
+
+        Commands::Yum { args } => syspkg_cmd::run("yum", &args, cli.verbose)?,
+
+        Commands::Show { id } => elided::show(&id)?,
+
+        Commands::Err {
+            only_errors,
+            min_severity,
+            command,
+        } => {
+            let min = if only_errors {
+                err_extract::Severity::Error
+            } else {
+                min_severity
+            };
+            if command == ["-"] {
+                runner::run_err_stdin(min, cli.verbose)?;
+            } else {
+                let cmd = command.join(" ");
+                runner::run_err(&cmd, min, cli.verbose)?;
+            }
         }
 
-        Commands::Test { command } => {
+        Commands::Test { flaky, command } => {
             let cmd = command.join(" ");
-            runner::run_test(&cmd, cli.verbose)?;
+            if flaky {
+                test_history::run_flaky(Some(cmd.as_str()).filter(|c| !c.is_empty()))?;
+            } else {
+                runner::run_test(&cmd, cli.verbose)?;
+            }
         }
 
         Commands::Json { file, depth } => {
@@ -936,8 +1776,88 @@ fn main() -> Result<()> {
             path,
             max,
             file_type,
+            per_dir,
+            changed_within,
         } => {
-            find_cmd::run(&pattern, &path, max, &file_type, cli.verbose)?;
+            find_cmd::run(
+                &pattern,
+                &path,
+                max,
+                per_dir,
+                &file_type,
+                changed_within.as_deref(),
+                cli.verbose,
+            )?;
+        }
+
+        Commands::Fd { args } => {
+            find_cmd::run_fd(&args, cli.verbose)?;
+        }
+
+        Commands::Todo {
+            path,
+            max,
+            no_blame,
+        } => {
+            todo_cmd::run(&path, max, no_blame, cli.verbose)?;
+        }
+
+        Commands::Owners { path, top } => {
+            owners_cmd::run(&path, top, cli.verbose)?;
+        }
+
+        Commands::Relnotes { range } => {
+            relnotes_cmd::run(range.as_deref(), cli.verbose)?;
+        }
+
+        Commands::Ports { port, all } => {
+            ports_cmd::run(port, all, cli.verbose)?;
+        }
+
+        Commands::Secrets {
+            path,
+            scanner,
+            max,
+            args,
+        } => {
+            secrets_cmd::run(&path, &scanner, max, &args, cli.verbose)?;
+        }
+
+        Commands::Sarif { file, max } => {
+            sarif::run(file.as_deref(), max, cli.verbose)?;
+        }
+
+        Commands::Sourcemap { file, maps, all } => {
+            sourcemap_cmd::run(file.as_deref(), &maps, all, cli.verbose)?;
+        }
+
+        Commands::Bloat {
+            source,
+            top,
+            save_baseline,
+            args,
+        } => {
+            bloat_cmd::run(&source, &args, top, save_baseline, cli.verbose)?;
+        }
+
+        Commands::Nm { binary, top } => {
+            nm_cmd::run(&binary, top, cli.verbose)?;
+        }
+
+        Commands::Lighthouse { args } => {
+            lighthouse_cmd::run(&args, cli.verbose)?;
+        }
+
+        Commands::Bundle {
+            file,
+            top,
+            save_baseline,
+        } => {
+            bundle_cmd::run(file.as_deref(), top, save_baseline, cli.verbose)?;
+        }
+
+        Commands::Conflicts { paths, max_lines } => {
+            conflicts_cmd::run(&paths, max_lines, cli.verbose)?;
         }
 
         Commands::Diff { file1, file2 } => {
@@ -1005,6 +1925,14 @@ fn main() -> Result<()> {
             }
         },
 
+        Commands::X { command } => {
+            generic_cmd::run(&command, cli.verbose)?;
+        }
+
+        Commands::Summarize { file, max_tokens } => {
+            summarize_cmd::run(&file, max_tokens, cli.verbose)?;
+        }
+
         Commands::Summary { command } => {
             let cmd = command.join(" ");
             summary::run(&cmd, cli.verbose)?;
@@ -1039,8 +1967,11 @@ fn main() -> Result<()> {
             auto_patch,
             no_patch,
             uninstall,
+            interactive,
         } => {
-            if show {
+            if interactive {
+                init::run_interactive(cli.verbose)?;
+            } else if show {
                 init::show_config()?;
             } else if uninstall {
                 init::uninstall(global, cli.verbose)?;
@@ -1065,6 +1996,115 @@ fn main() -> Result<()> {
         }
 
         Commands::Gain {
+            command:
+                Some(GainCommands::Export {
+                    records,
+                    format,
+                    after,
+                    limit,
+                }),
+            redact,
+            ..
+        } => {
+            gain::run_export(records, &format, after, limit, redact, cli.verbose)?;
+        }
+
+        Commands::Gain {
+            command: Some(GainCommands::Migrate { check }),
+            ..
+        } => {
+            gain::run_migrate(check, cli.verbose)?;
+        }
+
+        Commands::Gain {
+            command: Some(GainCommands::Compact),
+            ..
+        } => {
+            gain::run_compact()?;
+        }
+
+        Commands::Gain {
+            command:
+                Some(GainCommands::Digest {
+                    weekly: _,
+                    daily,
+                    webhook,
+                    output,
+                    format,
+                }),
+            redact,
+            ..
+        } => {
+            gain_digest::run(daily, webhook, output, &format, redact, cli.verbose)?;
+        }
+
+        Commands::Gain {
+            command:
+                Some(GainCommands::Push {
+                    server,
+                    token,
+                    days,
+                }),
+            ..
+        } => {
+            gain_team::run_push(server, token, days, cli.verbose)?;
+        }
+
+        Commands::Gain {
+            command:
+                Some(GainCommands::Team {
+                    server,
+                    token,
+                    days,
+                    format,
+                }),
+            ..
+        } => {
+            gain_team::run_team(server, token, days, &format, cli.verbose)?;
+        }
+
+        Commands::Gain {
+            command:
+                Some(GainCommands::Serve {
+                    bind,
+                    port,
+                    token,
+                    data,
+                }),
+            ..
+        } => {
+            gain_team::run_serve(&bind, port, token, data, cli.verbose)?;
+        }
+
+        Commands::Gain {
+            command: Some(GainCommands::Inspect { id, format }),
+            redact,
+            ..
+        } => {
+            gain::run_inspect(id, &format, redact)?;
+        }
+
+        Commands::Gain {
+            command:
+                Some(GainCommands::Reconcile {
+                    transcripts,
+                    project,
+                    since,
+                    format,
+                }),
+            ..
+        } => {
+            gain_reconcile::run(
+                transcripts.as_deref(),
+                project.as_deref(),
+                since,
+                &format,
+                cli.verbose,
+            )?;
+        }
+
+        Commands::Gain {
+            command: None,
             graph,
             history,
             quota,
@@ -1074,17 +2114,29 @@ fn main() -> Result<()> {
             monthly,
             all,
             format,
+            watch,
+            failures,
+            by_agent,
+            by_model,
+            redact,
         } => {
             gain::run(
-                graph,
-                history,
-                quota,
-                &tier,
-                daily,
-                weekly,
-                monthly,
-                all,
-                &format,
+                gain::GainOptions {
+                    graph,
+                    history,
+                    quota,
+                    tier: &tier,
+                    daily,
+                    weekly,
+                    monthly,
+                    all,
+                    format: &format,
+                    watch,
+                    failures,
+                    by_agent,
+                    by_model,
+                    redact,
+                },
                 cli.verbose,
             )?;
         }
@@ -1099,15 +2151,62 @@ fn main() -> Result<()> {
             cc_economics::run(daily, weekly, monthly, all, &format, cli.verbose)?;
         }
 
-        Commands::Config { create } => {
-            if create {
-                let path = config::Config::create_default()?;
-                println!("Created: {}", path.display());
+        Commands::Doctor => doctor::run(cli.verbose)?,
+
+        Commands::BenchSelf {
+            runs,
+            save,
+            check,
+            baseline,
+        } => bench_self::run(runs, save, check, baseline)?,
+
+        Commands::VerifyCorpus { dir } => corpus::run(dir, cli.verbose)?,
+
+        Commands::Advertise { write, file } => advertise::run(write, file, cli.verbose)?,
+
+        Commands::Budget { transcript, hook } => budget::run(transcript, hook, cli.verbose)?,
+
+        Commands::Integrate {
+            agent,
+            uninstall,
+            show,
+        } => integrate::run(agent, uninstall, show, cli.verbose)?,
+
+        Commands::Daemon { port } => daemon::run(port, cli.verbose)?,
+
+        Commands::Mcp => mcp::run(cli.verbose)?,
+
+        Commands::Policy { command } => policy::run(&command)?,
+
+        Commands::Rewrite { command, hook, log } => {
+            if hook {
+                rewrite::run_hook()?;
+            } else if let Some(limit) = log {
+                rewrite::show_log(limit)?;
+            } else if command.is_empty() {
+                anyhow::bail!("Usage: rtk rewrite <command> | --hook | --log [N]");
             } else {
-                config::show_config()?;
+                rewrite::run(&command)?;
             }
         }
 
+        Commands::Config { command, create } => match command {
+            Some(ConfigCommands::Get { key }) => config::get_value(&key)?,
+            Some(ConfigCommands::Set {
+                key,
+                value,
+                project,
+            }) => config::set_value(&key, &value, project)?,
+            Some(ConfigCommands::List) => config::list_values()?,
+            None if create => {
+                let path = config::Config::create_default()?;
+                println!("Created: {}", path.display());
+            }
+            None => config::show_config()?,
+        },
+
+        Commands::Alias => alias::list(is_builtin)?,
+
         Commands::Vitest { command } => match command {
             VitestCommands::Run { args } => {
                 vitest_cmd::run(vitest_cmd::VitestCommand::Run, &args, cli.verbose)?;
@@ -1168,6 +2267,10 @@ fn main() -> Result<()> {
             prettier_cmd::run(&args, cli.verbose)?;
         }
 
+        Commands::FmtCheck { args } => {
+            fmt_cmd::run(&args, cli.verbose)?;
+        }
+
         Commands::Playwright { args } => {
             playwright_cmd::run(&args, cli.verbose)?;
         }
@@ -1272,12 +2375,12 @@ fn main() -> Result<()> {
                                 }
                                 let status = cmd.status().context("Failed to run npx prisma")?;
                                 let args_str = args.join(" ");
-                                timer.track_passthrough(
+                                timer.with_exit_code(status).track_passthrough(
                                     &format!("npx {}", args_str),
                                     &format!("rtk npx {} (passthrough)", args_str),
                                 );
                                 if !status.success() {
-                                    std::process::exit(status.code().unwrap_or(1));
+                                    exit_code::exit_with(status);
                                 }
                             }
                         }
@@ -1287,15 +2390,23 @@ fn main() -> Result<()> {
                             .arg("prisma")
                             .status()
                             .context("Failed to run npx prisma")?;
-                        timer.track_passthrough("npx prisma", "rtk npx prisma (passthrough)");
+                        timer
+                            .with_exit_code(status)
+                            .track_passthrough("npx prisma", "rtk npx prisma (passthrough)");
                         if !status.success() {
-                            std::process::exit(status.code().unwrap_or(1));
+                            exit_code::exit_with(status);
                         }
                     }
                 }
                 "next" => {
                     next_cmd::run(&args[1..], cli.verbose)?;
                 }
+                "nuxi" | "nuxt" if args.get(1).map(String::as_str) == Some("build") => {
+                    next_cmd::run_framework(next_cmd::Framework::Nuxt, &args[2..], cli.verbose)?;
+                }
+                "ng" if args.get(1).map(String::as_str) == Some("build") => {
+                    next_cmd::run_framework(next_cmd::Framework::Angular, &args[2..], cli.verbose)?;
+                }
                 "prettier" => {
                     prettier_cmd::run(&args[1..], cli.verbose)?;
                 }
@@ -1340,6 +2451,46 @@ fn main() -> Result<()> {
             golangci_cmd::run(&args, cli.verbose)?;
         }
 
+        Commands::Semgrep { args } => {
+            semgrep_cmd::run(&args, cli.verbose)?;
+        }
+
+        Commands::Adb { args } => {
+            android_cmd::run_adb(&args, cli.verbose)?;
+        }
+
+        Commands::Gradle { args } => {
+            android_cmd::run_gradle(&args, cli.verbose)?;
+        }
+
+        Commands::Xcodebuild { args } => {
+            xcode_cmd::run_xcodebuild(&args, cli.verbose)?;
+        }
+
+        Commands::Swift { args } => {
+            xcode_cmd::run_swift(&args, cli.verbose)?;
+        }
+
+        Commands::Watch {
+            paths,
+            debounce,
+            command,
+        } => {
+            watch::run(&paths, debounce, &command, cli.verbose)?;
+        }
+
+        Commands::Systemctl { args } => {
+            systemctl_cmd::run(&args, cli.verbose)?;
+        }
+
+        Commands::Trivy { args } => {
+            vuln_cmd::run(vuln_cmd::Scanner::Trivy, &args, cli.verbose)?;
+        }
+
+        Commands::Grype { args } => {
+            vuln_cmd::run(vuln_cmd::Scanner::Grype, &args, cli.verbose)?;
+        }
+
         Commands::Proxy { args } => {
             use std::process::Command;
 
@@ -1375,7 +2526,7 @@ fn main() -> Result<()> {
             eprint!("{}", stderr);
 
             // Track usage (input = output since no filtering)
-            timer.track(
+            timer.with_exit_code(output.status).track(
                 &format!("{} {}", cmd_name, cmd_args.join(" ")),
                 &format!("rtk proxy {} {}", cmd_name, cmd_args.join(" ")),
                 &full_output,
@@ -1384,9 +2535,13 @@ fn main() -> Result<()> {
 
             // Exit with same code as child process
             if !output.status.success() {
-                std::process::exit(output.status.code().unwrap_or(1));
+                exit_code::exit_with(output.status);
             }
         }
+
+        Commands::Plugin(argv) => {
+            plugin::run(&argv, cli.verbose)?;
+        }
     }
 
     Ok(())
@@ -0,0 +1,132 @@
+//! Large file reads: memory-mapped, with memchr newline scanning.
+//!
+//! `rtk cat` on a log of a few hundred megabytes should cost about what
+//! `cat` does. Regular files of [`MMAP_MIN`] bytes or more are mapped rather
+//! than copied into a buffer first, and line splitting goes through memchr's
+//! SIMD search. Inputs of [`LARGE`] bytes or more also skip work that scales
+//! with their size but not with what is shown: hashing the whole content for
+//! cache keys and storing elided sections.
+
+use anyhow::{Context, Result};
+use memmap2::Mmap;
+use std::fs::File;
+use std::ops::Deref;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+/// Below this, a plain read is cheaper than setting up a mapping.
+pub const MMAP_MIN: u64 = 1 << 20;
+/// Inputs from this size on are keyed by file metadata, not content.
+pub const LARGE: usize = 16 << 20;
+
+/// Bytes of a file, mapped or read.
+pub enum Contents {
+    Mapped(Mmap),
+    Read(Vec<u8>),
+}
+
+impl Deref for Contents {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Contents::Mapped(map) => map,
+            Contents::Read(bytes) => bytes,
+        }
+    }
+}
+
+/// Contents of `path`, mapped when it is a large regular file.
+pub fn read(path: &Path) -> Result<Contents> {
+    let context = || format!("Failed to read file: {}", path.display());
+    let file = File::open(path).with_context(context)?;
+    let meta = file.metadata().with_context(context)?;
+    if meta.is_file() && meta.len() >= MMAP_MIN {
+        // SAFETY: the mapping is read-only and only lives while the caller
+        // decodes it. A file truncated meanwhile by another process can fault,
+        // as it can for ripgrep; rtk reads, it never maps its own output.
+        if let Ok(map) = unsafe { Mmap::map(&file) } {
+            return Ok(Contents::Mapped(map));
+        }
+    }
+    let mut bytes = Vec::with_capacity(meta.len() as usize);
+    std::io::Read::read_to_end(&mut &file, &mut bytes).with_context(context)?;
+    Ok(Contents::Read(bytes))
+}
+
+/// Size and modification time of `path`, standing in for its content in
+/// cache keys of [`LARGE`] files.
+pub fn fingerprint(path: &Path) -> Option<String> {
+    let meta = std::fs::metadata(path).ok()?;
+    let modified = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some(format!("{}:{}", meta.len(), modified.as_nanos()))
+}
+
+/// `text.lines()`, found with memchr.
+pub fn lines(text: &str) -> Lines<'_> {
+    Lines { rest: text }
+}
+
+/// `text.lines().count()`, without splitting.
+pub fn line_count(text: &str) -> usize {
+    let newlines = memchr::memchr_iter(b'\n', text.as_bytes()).count();
+    newlines + usize::from(!text.is_empty() && !text.ends_with('\n'))
+}
+
+pub struct Lines<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        // `\n` is ASCII, so both halves stay on char boundaries
+        match memchr::memchr(b'\n', self.rest.as_bytes()) {
+            Some(i) => {
+                let line = &self.rest[..i];
+                self.rest = &self.rest[i + 1..];
+                Some(line.strip_suffix('\r').unwrap_or(line))
+            }
+            // Like `str::lines`, a final `\r` without `\n` is kept
+            None => Some(std::mem::take(&mut self.rest)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_match_std() {
+        for text in ["", "a", "a\n", "a\n\nb", "a\r\nb\r\n", "\n\n", "é\nñ\r", "x\r\r\n"] {
+            assert_eq!(
+                lines(text).collect::<Vec<_>>(),
+                text.lines().collect::<Vec<_>>(),
+                "{:?}",
+                text
+            );
+            assert_eq!(line_count(text), text.lines().count(), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_read_maps_large_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let small = dir.path().join("small.txt");
+        std::fs::write(&small, "one\ntwo\n").unwrap();
+        assert!(matches!(read(&small).unwrap(), Contents::Read(_)));
+
+        let large = dir.path().join("large.txt");
+        let text = "line\n".repeat(MMAP_MIN as usize / 5 + 1);
+        std::fs::write(&large, &text).unwrap();
+        let contents = read(&large).unwrap();
+        assert!(matches!(contents, Contents::Mapped(_)));
+        assert_eq!(&contents[..], text.as_bytes());
+        assert!(read(&dir.path().join("missing")).is_err());
+    }
+}
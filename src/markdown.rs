@@ -0,0 +1,338 @@
+//! Markdown-aware `rtk cat` for `.md` files.
+//!
+//! A plain read collapses the bulk that docs carry without adding much:
+//! fenced code blocks over `CODE_BLOCK_MAX` lines keep their head, and
+//! `<table>` blocks over `TABLE_MAX` lines become a one-line note with the
+//! row count and column names. `--headings` prints only the outline and
+//! `--section "## Install"` prints a single section, nested ones included.
+
+use crate::tracking;
+use anyhow::{bail, Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::path::Path;
+
+/// Fenced code blocks longer than this are collapsed.
+const CODE_BLOCK_MAX: usize = 40;
+/// Lines kept from the top of a collapsed code block.
+const CODE_BLOCK_HEAD: usize = 15;
+/// HTML tables longer than this are collapsed.
+const TABLE_MAX: usize = 20;
+
+lazy_static! {
+    static ref ATX: Regex = Regex::new(r"^(#{1,6})\s+(.+?)\s*#*\s*$").unwrap();
+    static ref SETEXT: Regex = Regex::new(r"^(=+|-+)\s*$").unwrap();
+    static ref FENCE: Regex = Regex::new(r"^\s*(```+|~~~+)\s*([\w+-]*)").unwrap();
+    static ref TABLE_ROW: Regex = Regex::new(r"(?i)<tr[\s>]").unwrap();
+    static ref TABLE_HEADER: Regex = Regex::new(r"(?is)<th[^>]*>(.*?)</th>").unwrap();
+    static ref TAG: Regex = Regex::new(r"<[^>]+>").unwrap();
+}
+
+pub fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| matches!(e.to_lowercase().as_str(), "md" | "markdown" | "mdx"))
+}
+
+#[derive(Debug, PartialEq)]
+struct Heading {
+    /// 0-based line index
+    line: usize,
+    level: usize,
+    text: String,
+}
+
+/// `rtk cat --section <query>`, or `--headings` when `section` is `None`.
+pub fn run(file: &Path, section: Option<&str>, verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let content = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read file: {}", file.display()))?;
+    if verbose > 0 {
+        eprintln!("Reading markdown: {}", file.display());
+    }
+
+    let (output, flag) = match section {
+        Some(query) => (
+            extract_section(&content, query)?,
+            format!("--section {:?}", query),
+        ),
+        None => (outline(&content), "--headings".to_string()),
+    };
+    println!("{}", output.trim_end());
+    timer.track(
+        &format!("cat {}", file.display()),
+        &format!("rtk cat {} {}", flag, file.display()),
+        &content,
+        &output,
+    );
+    Ok(())
+}
+
+/// Headings outside code blocks, ATX (`## x`) and underlined alike.
+fn headings(lines: &[&str]) -> Vec<Heading> {
+    let mut out = Vec::new();
+    let mut fence: Option<&str> = None;
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(caps) = FENCE.captures(line) {
+            let marker = caps.get(1).unwrap().as_str();
+            match fence {
+                Some(open) if marker.starts_with(open) => fence = None,
+                None => fence = Some(marker),
+                _ => {}
+            }
+            continue;
+        }
+        if fence.is_some() {
+            continue;
+        }
+        let trimmed = line.trim();
+        if let Some(caps) = ATX.captures(trimmed) {
+            out.push(Heading {
+                line: i,
+                level: caps[1].len(),
+                text: caps[2].to_string(),
+            });
+        } else if !trimmed.is_empty()
+            && !line.starts_with(' ')
+            && i + 1 < lines.len()
+            && SETEXT.is_match(lines[i + 1].trim())
+            && (i == 0 || lines[i - 1].trim().is_empty())
+        {
+            let level = if lines[i + 1].trim().starts_with('=') {
+                1
+            } else {
+                2
+            };
+            out.push(Heading {
+                line: i,
+                level,
+                text: trimmed.to_string(),
+            });
+        }
+    }
+    out
+}
+
+/// Outline with line numbers, indented by level.
+fn outline(content: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let found = headings(&lines);
+    if found.is_empty() {
+        return format!("(no headings, {} lines)", lines.len());
+    }
+    let width = (found.last().unwrap().line + 1).to_string().len();
+    found
+        .iter()
+        .map(|h| {
+            format!(
+                "{:>width$}  {}{} {}",
+                h.line + 1,
+                "  ".repeat(h.level - 1),
+                "#".repeat(h.level),
+                h.text,
+                width = width
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The section whose heading matches `query` (`"## Install"` or `"install"`):
+/// exact text first, then the first heading containing it.
+pub(crate) fn extract_section(content: &str, query: &str) -> Result<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let found = headings(&lines);
+    let query = query.trim();
+    let level = query.chars().take_while(|&c| c == '#').count();
+    let wanted = query.trim_start_matches('#').trim().to_lowercase();
+    let candidates = || {
+        found
+            .iter()
+            .enumerate()
+            .filter(move |(_, h)| level == 0 || h.level == level)
+    };
+    let index = candidates()
+        .find(|(_, h)| h.text.to_lowercase() == wanted)
+        .or_else(|| candidates().find(|(_, h)| h.text.to_lowercase().contains(&wanted)))
+        .map(|(i, _)| i);
+
+    let Some(index) = index else {
+        let available: Vec<&str> = found
+            .iter()
+            .filter(|h| h.level <= 2)
+            .take(15)
+            .map(|h| h.text.as_str())
+            .collect();
+        bail!(
+            "No section matching '{}'. Top headings: {}",
+            query,
+            if available.is_empty() {
+                "(none)".to_string()
+            } else {
+                available.join(", ")
+            }
+        );
+    };
+    let heading = &found[index];
+    let end = found[index + 1..]
+        .iter()
+        .find(|h| h.level <= heading.level)
+        .map_or(lines.len(), |h| h.line);
+    Ok(condense(&lines[heading.line..end].join("\n")))
+}
+
+/// The document with giant code blocks and HTML tables collapsed.
+pub fn condense(content: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut out: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        if let Some(caps) = FENCE.captures(line) {
+            let marker = caps.get(1).unwrap().as_str();
+            let lang = caps.get(2).map_or("", |m| m.as_str());
+            let close = lines[i + 1..]
+                .iter()
+                .position(|l| l.trim_start().starts_with(marker))
+                .map(|p| i + 1 + p);
+            let body_end = close.unwrap_or(lines.len());
+            let body = body_end - i - 1;
+            out.push(line.to_string());
+            if body > CODE_BLOCK_MAX {
+                out.extend(
+                    lines[i + 1..i + 1 + CODE_BLOCK_HEAD]
+                        .iter()
+                        .map(|l| l.to_string()),
+                );
+                out.push(format!(
+                    "... {} more lines{} (rtk cat --level none for all)",
+                    body - CODE_BLOCK_HEAD,
+                    if lang.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" of {}", lang)
+                    }
+                ));
+            } else {
+                out.extend(lines[i + 1..body_end].iter().map(|l| l.to_string()));
+            }
+            if let Some(close) = close {
+                out.push(lines[close].to_string());
+            }
+            i = body_end + 1;
+            continue;
+        }
+        if line.trim_start().to_lowercase().starts_with("<table") {
+            let end = lines[i..]
+                .iter()
+                .position(|l| l.to_lowercase().contains("</table>"))
+                .map_or(lines.len(), |p| i + p + 1);
+            if end - i > TABLE_MAX {
+                out.push(describe_table(&lines[i..end].join("\n")));
+            } else {
+                out.extend(lines[i..end].iter().map(|l| l.to_string()));
+            }
+            i = end;
+            continue;
+        }
+        out.push(line.to_string());
+        i += 1;
+    }
+    let mut text = out.join("\n");
+    if content.ends_with('\n') {
+        text.push('\n');
+    }
+    text
+}
+
+fn describe_table(html: &str) -> String {
+    let rows = TABLE_ROW.find_iter(html).count();
+    let columns: Vec<String> = TABLE_HEADER
+        .captures_iter(html)
+        .map(|c| TAG.replace_all(&c[1], "").trim().to_string())
+        .filter(|c| !c.is_empty())
+        .collect();
+    format!(
+        "<table> {} rows, {} lines collapsed{} (rtk cat --level none for all)",
+        rows,
+        html.lines().count(),
+        if columns.is_empty() {
+            String::new()
+        } else {
+            format!(", columns: {}", columns.join(", "))
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "\
+# Tool
+
+Intro text.
+
+## Install
+
+```bash
+# not a heading
+cargo install tool
+```
+
+### From source
+
+Build it.
+
+Usage
+-----
+
+Run it.
+";
+
+    #[test]
+    fn test_outline_skips_code_and_reads_setext() {
+        assert_eq!(
+            outline(DOC),
+            " 1  # Tool\n 5    ## Install\n12      ### From source\n16    ## Usage"
+        );
+    }
+
+    #[test]
+    fn test_extract_section_includes_nested() {
+        let section = extract_section(DOC, "## install").unwrap();
+        assert!(section.starts_with("## Install\n"));
+        assert!(section.contains("### From source\n\nBuild it."));
+        assert!(!section.contains("Usage"));
+
+        assert_eq!(
+            extract_section(DOC, "usage").unwrap(),
+            "Usage\n-----\n\nRun it."
+        );
+        let err = extract_section(DOC, "### Usage").unwrap_err().to_string();
+        assert!(err.contains("Top headings: Tool, Install, Usage"));
+    }
+
+    #[test]
+    fn test_condense_collapses_giant_blocks() {
+        let code: Vec<String> = (0..60).map(|i| format!("let x{} = {};", i, i)).collect();
+        let rows: Vec<String> = (0..30)
+            .map(|i| format!("<tr><td>{}</td><td>v</td></tr>", i))
+            .collect();
+        let doc = format!(
+            "# A\n\n```rust\n{}\n```\n\n<table>\n<tr><th>Name</th><th><b>Value</b></th></tr>\n{}\n</table>\nafter\n",
+            code.join("\n"),
+            rows.join("\n")
+        );
+        let condensed = condense(&doc);
+        assert!(condensed.contains("let x14 = 14;\n... 45 more lines of rust"));
+        assert!(!condensed.contains("let x15 ="));
+        assert!(
+            condensed.contains("```\n\n<table> 31 rows, 33 lines collapsed, columns: Name, Value")
+        );
+        assert!(condensed.ends_with("after\n"));
+
+        let small = "```\nshort\n```\n";
+        assert_eq!(condense(small), small);
+    }
+}
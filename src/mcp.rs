@@ -0,0 +1,364 @@
+//! `rtk mcp` — Model Context Protocol server over stdio.
+//!
+//! Speaks newline-delimited JSON-RPC 2.0 on stdin/stdout and exposes rtk's
+//! filters as MCP tools, so MCP clients (Claude Desktop, IDE agents) get
+//! compact output without a shell hook. Each tool call runs the matching
+//! rtk subcommand as a child process, so filtering, policy and tracking are
+//! exactly those of the CLI. Diagnostics go to stderr; stdout carries only
+//! protocol messages.
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use std::process::{Command, Stdio};
+
+/// Protocol revision answered when the client does not ask for one.
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+struct Tool {
+    name: &'static str,
+    description: &'static str,
+    schema: fn() -> Value,
+    /// rtk arguments for a call, or an error for the client
+    args: fn(&Value) -> std::result::Result<Vec<String>, String>,
+}
+
+const TOOLS: &[Tool] = &[
+    Tool {
+        name: "read_file_summary",
+        description: "Read a file with comments and boilerplate stripped (rtk read).",
+        schema: || {
+            json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "File to read" },
+                    "level": { "type": "string", "enum": ["none", "minimal", "aggressive"], "description": "Filter level (default minimal)" },
+                    "max_lines": { "type": "integer", "minimum": 1, "description": "Truncate to this many lines" }
+                },
+                "required": ["path"]
+            })
+        },
+        args: |input| {
+            let mut args = vec!["read".to_string(), required_str(input, "path")?];
+            if let Some(level) = optional_str(input, "level")? {
+                args.extend(["--level".to_string(), level]);
+            }
+            if let Some(max) = optional_u64(input, "max_lines")? {
+                args.extend(["--max-lines".to_string(), max.to_string()]);
+            }
+            Ok(args)
+        },
+    },
+    Tool {
+        name: "search",
+        description: "Search file contents, grouped by file with long lines trimmed (rtk grep).",
+        schema: || {
+            json!({
+                "type": "object",
+                "properties": {
+                    "pattern": { "type": "string", "description": "Regex to search for" },
+                    "path": { "type": "string", "description": "Directory or file (default .)" },
+                    "max_results": { "type": "integer", "minimum": 1, "description": "Max matches shown (default 50)" },
+                    "file_type": { "type": "string", "description": "Restrict to a file type, e.g. rust, ts, py" }
+                },
+                "required": ["pattern"]
+            })
+        },
+        args: |input| {
+            let mut args = vec!["grep".to_string(), required_str(input, "pattern")?];
+            args.push(optional_str(input, "path")?.unwrap_or_else(|| ".".to_string()));
+            if let Some(max) = optional_u64(input, "max_results")? {
+                args.extend(["--max".to_string(), max.to_string()]);
+            }
+            if let Some(file_type) = optional_str(input, "file_type")? {
+                args.extend(["--file-type".to_string(), file_type]);
+            }
+            Ok(args)
+        },
+    },
+    Tool {
+        name: "git_status",
+        description: "Compact git status of the working directory (rtk git status).",
+        schema: || json!({ "type": "object", "properties": {} }),
+        args: |_| Ok(vec!["git".to_string(), "status".to_string()]),
+    },
+    Tool {
+        name: "git_diff_summary",
+        description:
+            "Condensed git diff: changed files with stats and trimmed hunks (rtk git diff).",
+        schema: || {
+            json!({
+                "type": "object",
+                "properties": {
+                    "args": { "type": "array", "items": { "type": "string" }, "description": "git diff arguments, e.g. [\"HEAD~3\"] or [\"--cached\"]" }
+                }
+            })
+        },
+        args: |input| {
+            let mut args = vec!["git".to_string(), "diff".to_string()];
+            args.extend(optional_str_list(input, "args")?);
+            Ok(args)
+        },
+    },
+    Tool {
+        name: "run_tests_summary",
+        description: "Run a test command and return only failures and the summary (rtk test).",
+        schema: || {
+            json!({
+                "type": "object",
+                "properties": {
+                    "command": { "type": "string", "description": "Test command, e.g. \"cargo test\" or \"npm test\"" }
+                },
+                "required": ["command"]
+            })
+        },
+        args: |input| {
+            let command = required_str(input, "command")?;
+            Ok(vec!["test".to_string(), command])
+        },
+    },
+];
+
+fn required_str(input: &Value, key: &str) -> std::result::Result<String, String> {
+    optional_str(input, key)?.ok_or_else(|| format!("missing required argument '{}'", key))
+}
+
+fn optional_str(input: &Value, key: &str) -> std::result::Result<Option<String>, String> {
+    match input.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.clone())),
+        Some(_) => Err(format!("argument '{}' must be a string", key)),
+    }
+}
+
+fn optional_u64(input: &Value, key: &str) -> std::result::Result<Option<u64>, String> {
+    match input.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => v
+            .as_u64()
+            .map(Some)
+            .ok_or_else(|| format!("argument '{}' must be a positive integer", key)),
+    }
+}
+
+fn optional_str_list(input: &Value, key: &str) -> std::result::Result<Vec<String>, String> {
+    match input.get(key) {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|item| {
+                item.as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| format!("argument '{}' must be a list of strings", key))
+            })
+            .collect(),
+        Some(_) => Err(format!("argument '{}' must be a list of strings", key)),
+    }
+}
+
+pub fn run(verbose: u8) -> Result<()> {
+    let exe = std::env::current_exe().context("Failed to locate the rtk binary")?;
+    crate::crash::serving();
+    if verbose > 0 {
+        eprintln!("rtk mcp: serving {} tools on stdio", TOOLS.len());
+    }
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    for line in stdin.lock().lines() {
+        let line = line.context("Failed to read from stdin")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = crate::crash::catch(|| handle_message(&line, &|args| run_rtk(&exe, args)))
+            .unwrap_or_else(|panic| {
+                let id = serde_json::from_str::<Value>(&line)
+                    .ok()
+                    .and_then(|message| message.get("id").cloned())
+                    .unwrap_or(Value::Null);
+                Some(error(
+                    id,
+                    INTERNAL_ERROR,
+                    &format!("internal error: {}", panic),
+                ))
+            });
+        if let Some(response) = response {
+            // Bypass the output profile and style: the client parses this verbatim
+            writeln!(stdout, "{}", response)?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Response to one JSON-RPC message (`None` for notifications).
+fn handle_message(line: &str, exec: &dyn Fn(&[String]) -> (String, bool)) -> Option<Value> {
+    let Ok(message) = serde_json::from_str::<Value>(line) else {
+        return Some(error(Value::Null, PARSE_ERROR, "parse error"));
+    };
+    // Requests carry an id; notifications (e.g. notifications/initialized) get no reply
+    let id = message.get("id").cloned()?;
+    let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match method {
+        "initialize" => {
+            let version = params
+                .get("protocolVersion")
+                .and_then(Value::as_str)
+                .unwrap_or(PROTOCOL_VERSION);
+            json!({
+                "protocolVersion": version,
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "rtk", "version": env!("CARGO_PKG_VERSION") }
+            })
+        }
+        "ping" => json!({}),
+        "tools/list" => json!({
+            "tools": TOOLS
+                .iter()
+                .map(|tool| json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "inputSchema": (tool.schema)(),
+                }))
+                .collect::<Vec<_>>()
+        }),
+        "tools/call" => {
+            let name = params.get("name").and_then(Value::as_str).unwrap_or("");
+            let Some(tool) = TOOLS.iter().find(|tool| tool.name == name) else {
+                return Some(error(
+                    id,
+                    INVALID_PARAMS,
+                    &format!("unknown tool '{}'", name),
+                ));
+            };
+            let input = params.get("arguments").cloned().unwrap_or(json!({}));
+            let (text, is_error) = match (tool.args)(&input) {
+                Ok(args) => exec(&args),
+                Err(message) => (message, true),
+            };
+            json!({
+                "content": [{ "type": "text", "text": text }],
+                "isError": is_error
+            })
+        }
+        _ => {
+            return Some(error(
+                id,
+                METHOD_NOT_FOUND,
+                &format!("method not found: {}", method),
+            ))
+        }
+    };
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message }
+    })
+}
+
+/// Run `rtk <args>`; stdout plus stderr, and whether it failed.
+fn run_rtk(exe: &std::path::Path, args: &[String]) -> (String, bool) {
+    let output = Command::new(exe)
+        .args(["--color", "never"])
+        .args(args)
+        // Attribute tracked savings to the MCP client unless it names itself
+        .env(
+            "RTK_AGENT",
+            std::env::var("RTK_AGENT").unwrap_or_else(|_| "mcp".to_string()),
+        )
+        // The child must not read the protocol stream
+        .stdin(Stdio::null())
+        .output();
+    match output {
+        Ok(output) => {
+            let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if !stderr.trim().is_empty() {
+                if !text.is_empty() && !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                text.push_str(&stderr);
+            }
+            (text.trim_end().to_string(), !output.status.success())
+        }
+        Err(e) => (format!("failed to run rtk: {}", e), true),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(line: &str) -> Option<Value> {
+        handle_message(line, &|args| (args.join(" "), false))
+    }
+
+    #[test]
+    fn test_initialize_and_notifications() {
+        let resp = call(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-03-26"}}"#).unwrap();
+        assert_eq!(resp["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(resp["result"]["serverInfo"]["name"], "rtk");
+        assert!(call(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#).is_none());
+        assert_eq!(call("not json").unwrap()["error"]["code"], PARSE_ERROR);
+        assert_eq!(
+            call(r#"{"jsonrpc":"2.0","id":2,"method":"resources/list"}"#).unwrap()["error"]["code"],
+            METHOD_NOT_FOUND
+        );
+    }
+
+    #[test]
+    fn test_tools_list_has_schemas() {
+        let resp = call(r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#).unwrap();
+        let tools = resp["result"]["tools"].as_array().unwrap();
+        assert_eq!(tools.len(), TOOLS.len());
+        for tool in tools {
+            assert_eq!(tool["inputSchema"]["type"], "object");
+        }
+    }
+
+    #[test]
+    fn test_tools_call_builds_rtk_args() {
+        let text = |line: &str| {
+            let resp = call(line).unwrap();
+            (
+                resp["result"]["content"][0]["text"]
+                    .as_str()
+                    .unwrap()
+                    .to_string(),
+                resp["result"]["isError"].as_bool().unwrap(),
+            )
+        };
+        assert_eq!(
+            text(
+                r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"read_file_summary","arguments":{"path":"src/main.rs","max_lines":40}}}"#
+            ),
+            ("read src/main.rs --max-lines 40".to_string(), false)
+        );
+        assert_eq!(
+            text(
+                r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"git_diff_summary","arguments":{"args":["HEAD~3"]}}}"#
+            ),
+            ("git diff HEAD~3".to_string(), false)
+        );
+        assert_eq!(
+            text(
+                r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"search","arguments":{}}}"#
+            ),
+            ("missing required argument 'pattern'".to_string(), true)
+        );
+        let resp =
+            call(r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"nope"}}"#)
+                .unwrap();
+        assert_eq!(resp["error"]["code"], INVALID_PARAMS);
+    }
+}
@@ -1,24 +1,120 @@
+use crate::exit_code;
 use crate::tracking;
-use crate::utils::{strip_ansi, truncate};
+use crate::utils::{strip_ansi, truncate, which};
 use anyhow::{Context, Result};
 use regex::Regex;
+use std::path::Path;
 use std::process::Command;
 
+/// Problem lines kept in the summary; the rest are only counted.
+const MAX_PROBLEMS: usize = 20;
+
+/// Rows kept per size table.
+const MAX_ROWS: usize = 10;
+
+lazy_static::lazy_static! {
+    static ref ERROR_LINE: Regex =
+        Regex::new(r"(?i)\berror\b|\bfailed\b|✖|✘|\[ERROR\]").unwrap();
+    static ref WARNING_LINE: Regex =
+        Regex::new(r"(?i)\bwarn(?:ing)?\b|\(!\)|⚠|\[WARNING\]").unwrap();
+    // `./src/app/page.tsx:5:3` printed above a Next.js type error
+    static ref LOCATION_LINE: Regex = Regex::new(r"^\.?/?[\w@.\-/\[\]()]+:\d+(?::\d+)?$").unwrap();
+    static ref NOISE_LINE: Regex =
+        Regex::new(r"(?i)telemetry|anonymous|usage data|analytics|0 errors?\b").unwrap();
+}
+
+/// Framework whose production build the build wrapper runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Framework {
+    Next,
+    Nuxt,
+    Angular,
+}
+
+impl Framework {
+    fn name(self) -> &'static str {
+        match self {
+            Framework::Next => "Next.js",
+            Framework::Nuxt => "Nuxt",
+            Framework::Angular => "Angular",
+        }
+    }
+
+    /// CLI binary and the env var that silences its telemetry prompt.
+    fn program(self) -> (&'static str, &'static str) {
+        match self {
+            Framework::Next => ("next", "NEXT_TELEMETRY_DISABLED=1"),
+            Framework::Nuxt => ("nuxi", "NUXT_TELEMETRY_DISABLED=1"),
+            Framework::Angular => ("ng", "NG_CLI_ANALYTICS=false"),
+        }
+    }
+
+    fn filter(self, output: &str) -> String {
+        match self {
+            Framework::Next => filter_next_build(output),
+            Framework::Nuxt => filter_nuxt_build(output),
+            Framework::Angular => filter_angular_build(output),
+        }
+    }
+}
+
+/// Framework of the project in `dir`, from `package.json` dependencies or
+/// its config file.
+pub fn detect(dir: &Path) -> Option<Framework> {
+    const MARKERS: &[(&str, &[&str], Framework)] = &[
+        (
+            "next",
+            &["next.config.js", "next.config.mjs", "next.config.ts"],
+            Framework::Next,
+        ),
+        (
+            "nuxt",
+            &["nuxt.config.ts", "nuxt.config.js", "nuxt.config.mjs"],
+            Framework::Nuxt,
+        ),
+        ("@angular/core", &["angular.json"], Framework::Angular),
+    ];
+    let manifest: serde_json::Value = std::fs::read_to_string(dir.join("package.json"))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    MARKERS
+        .iter()
+        .find(|(dependency, configs, _)| {
+            ["dependencies", "devDependencies"]
+                .iter()
+                .any(|key| manifest[key].get(dependency).is_some())
+                || configs.iter().any(|c| dir.join(c).is_file())
+        })
+        .map(|(_, _, framework)| *framework)
+}
+
 pub fn run(args: &[String], verbose: u8) -> Result<()> {
+    run_framework(Framework::Next, args, verbose)
+}
+
+/// Build wrapper (`rtk pnpm build`): runs the detected framework's build,
+/// Next.js when nothing is detected.
+pub fn run_build(args: &[String], verbose: u8) -> Result<()> {
+    let framework = detect(Path::new(".")).unwrap_or(Framework::Next);
+    if verbose > 0 {
+        eprintln!("Detected framework: {}", framework.name());
+    }
+    run_framework(framework, args, verbose)
+}
+
+pub fn run_framework(framework: Framework, args: &[String], verbose: u8) -> Result<()> {
     let timer = tracking::TimedExecution::start();
+    let (program, telemetry) = framework.program();
 
-    // Try next directly first, fallback to npx if not found
-    let next_exists = Command::new("which")
-        .arg("next")
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false);
+    // Try the CLI directly first, fallback to npx if not found
+    let exists = which(program).is_some();
 
-    let mut cmd = if next_exists {
-        Command::new("next")
+    let mut cmd = if exists {
+        Command::new(program)
     } else {
         let mut c = Command::new("npx");
-        c.arg("next");
+        c.arg(program);
         c
     };
 
@@ -28,58 +124,147 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
         cmd.arg(arg);
     }
 
+    if let Some((key, value)) = telemetry.split_once('=') {
+        cmd.env(key, value);
+    }
+
     if verbose > 0 {
-        let tool = if next_exists { "next" } else { "npx next" };
+        let tool = if exists {
+            program.to_string()
+        } else {
+            format!("npx {}", program)
+        };
         eprintln!("Running: {} build", tool);
     }
 
-    let output = cmd
-        .output()
-        .context("Failed to run next build (try: npm install -g next)")?;
+    let output = cmd.output().with_context(|| {
+        format!(
+            "Failed to run {} build (try: npx {} build)",
+            program, program
+        )
+    })?;
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
-    let filtered = filter_next_build(&raw);
+    let filtered = framework.filter(&raw);
 
     println!("{}", filtered);
 
-    timer.track("next build", "rtk next build", &raw, &filtered);
+    timer.with_exit_code(output.status).track(
+        &format!("{} build", program),
+        &format!("rtk {} build", program),
+        &raw,
+        &filtered,
+    );
 
     // Preserve exit code for CI/CD
     if !output.status.success() {
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     Ok(())
 }
 
+/// Error and warning lines (with the `file:line` printed just above them),
+/// deduplicated; telemetry notices are dropped. Returns the kept lines and
+/// the error and warning counts.
+fn problem_lines(output: &str) -> (Vec<String>, usize, usize) {
+    let mut lines: Vec<String> = Vec::new();
+    let mut errors = 0;
+    let mut warnings = 0;
+    let mut location: Option<&str> = None;
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if LOCATION_LINE.is_match(trimmed) {
+            location = Some(trimmed);
+            continue;
+        }
+        if trimmed.is_empty() || NOISE_LINE.is_match(trimmed) {
+            location = None;
+            continue;
+        }
+        if ERROR_LINE.is_match(trimmed) {
+            errors += 1;
+        } else if WARNING_LINE.is_match(trimmed) {
+            warnings += 1;
+        } else {
+            location = None;
+            continue;
+        }
+        for kept in location.take().into_iter().chain([trimmed]) {
+            if !lines.iter().any(|l| l == kept) {
+                lines.push(kept.to_string());
+            }
+        }
+    }
+    (lines, errors, warnings)
+}
+
+fn push_problems(result: &mut String, output: &str, prefix: &str) {
+    let (lines, errors, warnings) = problem_lines(output);
+    for line in lines.iter().take(MAX_PROBLEMS) {
+        result.push_str(&format!("  {}\n", truncate(line, 160)));
+    }
+    if lines.len() > MAX_PROBLEMS {
+        result.push_str(&format!("  ... +{} more\n", lines.len() - MAX_PROBLEMS));
+    }
+    if !lines.is_empty() {
+        result.push('\n');
+    }
+    result.push_str(&format!(
+        "{}Errors: {} | Warnings: {}\n",
+        prefix, errors, warnings
+    ));
+}
+
 /// Filter Next.js build output - extract routes, bundles, warnings
 fn filter_next_build(output: &str) -> String {
     lazy_static::lazy_static! {
         // Route line pattern: ○ /dashboard    1.2 kB  132 kB
         static ref ROUTE_PATTERN: Regex = Regex::new(
-            r"^[○●◐λ✓]\s+(/[^\s]*)\s+(\d+(?:\.\d+)?)\s*(kB|B)"
+            r"^[○●◐λƒ✓]\s+(/[^\s]*)\s+(\d+(?:\.\d+)?)\s*(kB|B)"
         ).unwrap();
 
         // Bundle size pattern
         static ref BUNDLE_PATTERN: Regex = Regex::new(
-            r"^[○●◐λ✓]\s+([\w/\-\.]+)\s+(\d+(?:\.\d+)?)\s*(kB|B)\s+(\d+(?:\.\d+)?)\s*(kB|B)"
+            r"^[○●◐λƒ✓]\s+([\w/\-\.\[\]]+)\s+(\d+(?:\.\d+)?)\s*(kB|B)\s+(\d+(?:\.\d+)?)\s*(kB|B)"
         ).unwrap();
+
+        // Route table rows are drawn with box characters: `├ ○ /about`
+        static ref TREE_PREFIX: Regex = Regex::new(r"^\s*[┌├└│]\s*").unwrap();
+
+        static ref SHARED_JS: Regex =
+            Regex::new(r"First Load JS shared by all\s+(\d+(?:\.\d+)?\s*(?:kB|B|MB))").unwrap();
     }
 
     let mut routes_static = 0;
     let mut routes_dynamic = 0;
     let mut routes_total = 0;
+    let mut seen_routes = std::collections::HashSet::new();
     let mut bundles: Vec<(String, f64, Option<f64>)> = Vec::new();
-    let mut warnings = 0;
-    let mut errors = 0;
+    let mut shared_js = None;
     let mut build_time = String::new();
 
     // Strip ANSI codes
     let clean_output = strip_ansi(output);
 
     for line in clean_output.lines() {
+        if let Some(caps) = SHARED_JS.captures(line) {
+            shared_js = Some(caps[1].to_string());
+        }
+
+        let line = TREE_PREFIX.replace(line, "");
+        let line = line.as_ref();
+
+        // The plain listing and the table can both print a route
+        if let Some(caps) = ROUTE_PATTERN.captures(line) {
+            if !seen_routes.insert(caps[1].to_string()) {
+                continue;
+            }
+        }
+
         // Count route types by symbol
         if line.starts_with("○") {
             routes_static += 1;
@@ -87,7 +272,7 @@ fn filter_next_build(output: &str) -> String {
         } else if line.starts_with("●") || line.starts_with("◐") {
             routes_dynamic += 1;
             routes_total += 1;
-        } else if line.starts_with("λ") {
+        } else if line.starts_with("λ") || line.starts_with("ƒ") {
             routes_total += 1;
         }
 
@@ -107,16 +292,8 @@ fn filter_next_build(output: &str) -> String {
             bundles.push((route, total, pct_change));
         }
 
-        // Count warnings and errors
-        if line.to_lowercase().contains("warning") {
-            warnings += 1;
-        }
-        if line.to_lowercase().contains("error") && !line.contains("0 error") {
-            errors += 1;
-        }
-
         // Extract build time
-        if line.contains("Compiled") || line.contains("in") {
+        if line.contains("Compiled") || line.contains(" in ") {
             if let Some(time_match) = extract_time(line) {
                 build_time = time_match;
             }
@@ -148,7 +325,7 @@ fn filter_next_build(output: &str) -> String {
         // Sort by size (descending) and show top 10
         bundles.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
 
-        for (route, size, pct_change) in bundles.iter().take(10) {
+        for (route, size, pct_change) in bundles.iter().take(MAX_ROWS) {
             let warning_marker = if let Some(pct) = pct_change {
                 if *pct > 10.0 {
                     format!(" ⚠️ (+{:.0}%)", pct)
@@ -167,20 +344,200 @@ fn filter_next_build(output: &str) -> String {
             ));
         }
 
-        if bundles.len() > 10 {
-            result.push_str(&format!("\n  ... +{} more routes\n", bundles.len() - 10));
+        if bundles.len() > MAX_ROWS {
+            result.push_str(&format!(
+                "\n  ... +{} more routes\n",
+                bundles.len() - MAX_ROWS
+            ));
+        }
+
+        if let Some(shared) = &shared_js {
+            result.push_str(&format!("  Shared by all: {}\n", shared));
         }
 
         result.push('\n');
     }
 
     // Show build time and status
-    if !build_time.is_empty() {
-        result.push_str(&format!("Time: {} | ", build_time));
+    let prefix = if build_time.is_empty() {
+        String::new()
+    } else {
+        format!("Time: {} | ", build_time)
+    };
+    push_problems(&mut result, &clean_output, &prefix);
+
+    result.trim().to_string()
+}
+
+/// Filter `nuxi build`: client asset sizes from Vite, Nitro's server total,
+/// build times and problems.
+fn filter_nuxt_build(output: &str) -> String {
+    lazy_static::lazy_static! {
+        // `ℹ .nuxt/dist/client/_nuxt/entry.js   155.81 kB │ gzip: 58.76 kB`
+        static ref ASSET: Regex = Regex::new(
+            r"(\S+\.(?:m?js|css))\s+(\d+(?:\.\d+)?)\s*(kB|B|MB)\s*│\s*gzip:\s*(\d+(?:\.\d+)?\s*(?:kB|B|MB))"
+        ).unwrap();
+        // `✔ Client built in 3001ms`
+        static ref BUILT: Regex =
+            Regex::new(r"(Client|Server) built in (\d+(?:\.\d+)?\s*m?s)").unwrap();
+        static ref TOTAL: Regex = Regex::new(r"Σ Total size:\s*(.+)$").unwrap();
+        static ref VERSION: Regex = Regex::new(r"Nuxt\s+v?(\d+\.\d+\.\d+)").unwrap();
+    }
+
+    let clean_output = strip_ansi(output);
+    let mut assets: Vec<(String, f64, String)> = Vec::new();
+    let mut built = Vec::new();
+    let mut server_total = None;
+    let mut version = None;
+    for line in clean_output.lines() {
+        if let Some(caps) = ASSET.captures(line) {
+            let kb = caps[2].parse::<f64>().unwrap_or(0.0)
+                * match &caps[3] {
+                    "B" => 1.0 / 1024.0,
+                    "MB" => 1024.0,
+                    _ => 1.0,
+                };
+            let file = caps[1].trim_start_matches(".nuxt/dist/").to_string();
+            // Client and server builds both print their chunks
+            if !assets.iter().any(|(f, _, _)| *f == file) {
+                assets.push((file, kb, caps[4].to_string()));
+            }
+        } else if let Some(caps) = BUILT.captures(line) {
+            built.push(format!("{} {}", &caps[1], &caps[2]));
+        } else if let Some(caps) = TOTAL.captures(line) {
+            server_total = Some(caps[1].trim().to_string());
+        } else if version.is_none() {
+            version = VERSION.captures(line).map(|c| c[1].to_string());
+        }
     }
 
-    result.push_str(&format!("Errors: {} | Warnings: {}\n", errors, warnings));
+    let mut result = String::new();
+    match &version {
+        Some(v) => result.push_str(&format!("⚡ Nuxt {} Build\n", v)),
+        None => result.push_str("⚡ Nuxt Build\n"),
+    }
+    result.push_str("═══════════════════════════════════════\n");
+    if !built.is_empty() {
+        result.push_str(&format!("✓ Built: {}\n\n", built.join(", ")));
+    }
 
+    let client: Vec<&(String, f64, String)> = assets
+        .iter()
+        .filter(|(f, _, _)| !f.starts_with("server/"))
+        .collect();
+    if !client.is_empty() {
+        let mut client = client;
+        client.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        let total: f64 = client.iter().map(|a| a.1).sum();
+        result.push_str(&format!(
+            "Client assets ({}, {:.0} kB):\n",
+            client.len(),
+            total
+        ));
+        for (file, kb, gzip) in client.iter().take(MAX_ROWS) {
+            result.push_str(&format!(
+                "  {:<40} {:>8.1} kB  ({} gzip)\n",
+                truncate(file, 40),
+                kb,
+                gzip
+            ));
+        }
+        if client.len() > MAX_ROWS {
+            result.push_str(&format!("  ... +{} more\n", client.len() - MAX_ROWS));
+        }
+        result.push('\n');
+    }
+    if let Some(total) = server_total {
+        result.push_str(&format!("Server output: {}\n\n", total));
+    }
+
+    push_problems(&mut result, &clean_output, "");
+    result.trim().to_string()
+}
+
+/// Filter `ng build`: the initial/lazy chunk tables (largest rows), the
+/// generation result, budget warnings and errors.
+fn filter_angular_build(output: &str) -> String {
+    lazy_static::lazy_static! {
+        static ref COMPLETE: Regex =
+            Regex::new(r"Application bundle generation (complete|failed)\.?\s*(\[[^\]]+\])?").unwrap();
+        static ref OUTPUT_LOCATION: Regex = Regex::new(r"Output location:\s*(\S+)").unwrap();
+    }
+
+    let clean_output = strip_ansi(output);
+    // (section, rows of file, names, raw size, transfer size, total row)
+    let mut tables: Vec<(String, Vec<[String; 4]>, Option<String>)> = Vec::new();
+    let mut status = None;
+    let mut location = None;
+    for line in clean_output.lines() {
+        let cells: Vec<String> = line.split('|').map(|c| c.trim().to_string()).collect();
+        if cells.len() >= 3 {
+            if cells[0].to_lowercase().ends_with("chunk files") {
+                let section = cells[0].split_whitespace().next().unwrap_or("").to_string();
+                tables.push((section, Vec::new(), None));
+            } else if let Some((_, rows, total)) = tables.last_mut() {
+                if cells[0].is_empty() && cells[1].to_lowercase().ends_with("total") {
+                    *total = Some(format!("{} raw", cells[2]));
+                    if let Some(transfer) = cells.get(3).filter(|c| !c.is_empty()) {
+                        *total = Some(format!("{} raw, {} transfer", cells[2], transfer));
+                    }
+                } else if !cells[0].is_empty() {
+                    rows.push([
+                        cells[0].clone(),
+                        cells[1].clone(),
+                        cells[2].clone(),
+                        cells.get(3).cloned().unwrap_or_default(),
+                    ]);
+                }
+            }
+        } else if let Some(caps) = COMPLETE.captures(line) {
+            let time = caps.get(2).map_or("", |m| m.as_str());
+            status = Some(format!("{} {}", &caps[1], time).trim().to_string());
+        } else if let Some(caps) = OUTPUT_LOCATION.captures(line) {
+            location = Some(caps[1].to_string());
+        }
+    }
+
+    let mut result = String::new();
+    result.push_str("⚡ Angular Build\n");
+    result.push_str("═══════════════════════════════════════\n");
+    match status.as_deref() {
+        Some(s) if s.starts_with("failed") => result.push_str(&format!("✗ Build {}\n\n", s)),
+        Some(s) => result.push_str(&format!("✓ Build {}\n\n", s)),
+        None => {}
+    }
+
+    for (section, rows, total) in &tables {
+        let total = total
+            .as_ref()
+            .map(|t| format!(", {}", t))
+            .unwrap_or_default();
+        result.push_str(&format!("{} chunks ({}{}):\n", section, rows.len(), total));
+        let width = rows
+            .iter()
+            .take(MAX_ROWS)
+            .map(|r| r[1].len())
+            .max()
+            .unwrap_or(0);
+        for [file, names, raw, _] in rows.iter().take(MAX_ROWS) {
+            result.push_str(&format!(
+                "  {:<width$} {:>10}  {}\n",
+                names,
+                raw,
+                file,
+                width = width
+            ));
+        }
+        if rows.len() > MAX_ROWS {
+            result.push_str(&format!("  ... +{} more\n", rows.len() - MAX_ROWS));
+        }
+        result.push('\n');
+    }
+    if let Some(location) = location {
+        result.push_str(&format!("Output: {}\n\n", location));
+    }
+
+    push_problems(&mut result, &clean_output, "");
     result.trim().to_string()
 }
 
@@ -238,4 +595,129 @@ Route (app)                    Size     First Load JS
         );
         assert_eq!(extract_time("No time here"), None);
     }
+
+    #[test]
+    fn test_filter_next_build_table_and_errors() {
+        let output = r#"
+Attention: Next.js now collects completely anonymous telemetry regarding usage.
+   ▲ Next.js 15.2.0
+   Creating an optimized production build ...
+ ✓ Compiled successfully in 12.1s
+./src/app/page.tsx:5:3
+Type error: Property 'title' does not exist on type 'Props'.
+
+Route (app)                                 Size  First Load JS
+┌ ○ /                                      5.1 kB         110 kB
+├ ƒ /api/auth/[...nextauth]                  0 B            0 B
+└ ● /blog/[slug]                           1.3 kB         106 kB
++ First Load JS shared by all               102 kB
+"#;
+        let result = filter_next_build(output);
+        assert!(result.contains("✓ 3 routes (1 static, 1 dynamic)"));
+        assert!(result.contains("/blog/[slug]"));
+        assert!(result.contains("Shared by all: 102 kB"));
+        assert!(result.contains(
+            "  ./src/app/page.tsx:5:3\n  Type error: Property 'title' does not exist on type 'Props'."
+        ));
+        assert!(result.contains("Time: 12.1s | Errors: 1 | Warnings: 0"));
+        assert!(!result.contains("telemetry"));
+    }
+
+    #[test]
+    fn test_filter_nuxt_build() {
+        let output = "\
+Nuxi 3.10.0
+Nuxt 3.10.0 with Nitro 2.8.1
+ℹ Building client...
+ℹ vite v5.0.12 building for production...
+ℹ transforming...
+ℹ ✓ 125 modules transformed.
+ℹ .nuxt/dist/client/manifest.json                  3.29 kB │ gzip:  0.52 kB
+ℹ .nuxt/dist/client/_nuxt/entry.Bq1gG7aX.css       1.20 kB │ gzip:  0.50 kB
+ℹ .nuxt/dist/client/_nuxt/entry.Dm2x8WfA.js      155.81 kB │ gzip: 58.76 kB
+ℹ ✓ built in 2.98s
+✔ Client built in 3001ms
+ℹ Building server...
+ℹ .nuxt/dist/server/server.mjs                     45.10 kB │ gzip: 12.00 kB
+✔ Server built in 1234ms
+[nitro] ✔ Generated public .output/public
+[nitro] ℹ Building Nitro Server (preset: node-server)
+  ├─ .output/server/chunks/app/server.mjs (150 kB) (35.8 kB gzip)
+Σ Total size: 1.52 MB (378 kB gzip)
+ WARN  (!) Some chunks are larger than 500 kB after minification.
+ℹ Nuxt collects completely anonymous data about usage.
+";
+        let result = filter_nuxt_build(output);
+        assert_eq!(
+            result,
+            "⚡ Nuxt 3.10.0 Build
+═══════════════════════════════════════
+✓ Built: Client 3001ms, Server 1234ms
+
+Client assets (2, 157 kB):
+  client/_nuxt/entry.Dm2x8WfA.js              155.8 kB  (58.76 kB gzip)
+  client/_nuxt/entry.Bq1gG7aX.css               1.2 kB  (0.50 kB gzip)
+
+Server output: 1.52 MB (378 kB gzip)
+
+  WARN  (!) Some chunks are larger than 500 kB after minification.
+
+Errors: 0 | Warnings: 1"
+        );
+    }
+
+    #[test]
+    fn test_filter_angular_build() {
+        let output = "\
+- Building...
+Initial chunk files   | Names         |  Raw size | Estimated transfer size
+main-ABC.js           | main          | 208.65 kB |                56.65 kB
+polyfills-XYZ.js      | polyfills     |  34.52 kB |                11.28 kB
+
+                      | Initial total | 243.17 kB |                67.93 kB
+
+Lazy chunk files      | Names         |  Raw size
+chunk-DEF.js          | admin-routes  |  12.30 kB
+
+Application bundle generation complete. [3.456 seconds]
+
+▲ [WARNING] bundle initial exceeded maximum budget. Budget 200.00 kB was not met by 43.17 kB with a total of 243.17 kB.
+
+Output location: /work/app/dist/app
+";
+        let result = filter_angular_build(output);
+        assert_eq!(
+            result,
+            "⚡ Angular Build
+═══════════════════════════════════════
+✓ Build complete [3.456 seconds]
+
+Initial chunks (2, 243.17 kB raw, 67.93 kB transfer):
+  main       208.65 kB  main-ABC.js
+  polyfills   34.52 kB  polyfills-XYZ.js
+
+Lazy chunks (1):
+  admin-routes   12.30 kB  chunk-DEF.js
+
+Output: /work/app/dist/app
+
+  ▲ [WARNING] bundle initial exceeded maximum budget. Budget 200.00 kB was not met by 43.17 kB with a total of 243.17 kB.
+
+Errors: 0 | Warnings: 1"
+        );
+    }
+
+    #[test]
+    fn test_detect_framework() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(detect(dir.path()), None);
+        std::fs::write(dir.path().join("angular.json"), "{}").unwrap();
+        assert_eq!(detect(dir.path()), Some(Framework::Angular));
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"dependencies": {"nuxt": "^3.10.0", "vue": "^3.4.0"}}"#,
+        )
+        .unwrap();
+        assert_eq!(detect(dir.path()), Some(Framework::Nuxt));
+    }
 }
@@ -0,0 +1,361 @@
+//! `rtk nm <binary>` — symbol counts, largest symbols and linked libraries
+//! instead of the full `nm`/`objdump`/`ldd` listings.
+
+use crate::bloat_cmd::format_size;
+use crate::tracking;
+use crate::utils::truncate;
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+lazy_static! {
+    // `addr size type name`, `addr type name` or `type name` (undefined);
+    // hex fields are at least 8 digits so one-letter names are not mistaken
+    static ref NM_LINE: Regex =
+        Regex::new(r"^\s*(?:[0-9a-fA-F]{8,}\s+)?(?:([0-9a-fA-F]{8,})\s+)?([A-Za-z?-])\s+(.+)$")
+            .unwrap();
+    // ldd: `libc.so.6 => /lib/libc.so.6 (0x...)`, `libx.so => not found`
+    static ref LDD_LINE: Regex = Regex::new(r"^\s*(\S+)(?:\s+=>\s+(.*?))?\s*(?:\(.*\))?$").unwrap();
+}
+
+#[derive(Debug, PartialEq)]
+struct Symbol {
+    kind: char,
+    size: Option<u64>,
+    name: String,
+}
+
+fn parse_nm(output: &str) -> Vec<Symbol> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let caps = NM_LINE.captures(line)?;
+            Some(Symbol {
+                kind: caps[2].chars().next()?,
+                size: caps
+                    .get(1)
+                    .and_then(|s| u64::from_str_radix(s.as_str(), 16).ok()),
+                name: caps[3].trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Section a symbol type letter stands for.
+fn section(kind: char) -> &'static str {
+    match kind.to_ascii_lowercase() {
+        't' => "text",
+        'd' | 'g' | 'u' => "data",
+        'b' | 's' => "bss",
+        'r' => "rodata",
+        'w' | 'v' => "weak",
+        _ => "other",
+    }
+}
+
+/// Undefined symbols are imports; `w`/`v` are weak undefined references.
+fn is_import(kind: char) -> bool {
+    matches!(kind, 'U' | 'w' | 'v')
+}
+
+/// Library a versioned import comes from: `memcpy@GLIBC_2.14` -> `GLIBC`.
+fn import_origin(name: &str) -> &str {
+    match name.split_once('@') {
+        Some((_, version)) => {
+            let version = version.trim_start_matches('@');
+            version.split_once('_').map_or(version, |(lib, _)| lib)
+        }
+        None => "unversioned",
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Library {
+    name: String,
+    /// Resolved path; `None` when ldd reports "not found"
+    path: Option<String>,
+}
+
+/// `ldd` (Linux) or `otool -L` (macOS) output.
+fn parse_libraries(output: &str) -> Vec<Library> {
+    output
+        .lines()
+        // otool prints the binary itself first, ending with ':'
+        .filter(|l| !l.trim().is_empty() && !l.trim_end().ends_with(':'))
+        .filter_map(|line| {
+            let caps = LDD_LINE.captures(line)?;
+            let name = caps[1].to_string();
+            // statically linked / vdso pseudo-entries
+            if name == "statically" || name.starts_with("linux-vdso") {
+                return None;
+            }
+            let path = match caps.get(2).map(|p| p.as_str().trim()) {
+                Some("not found") => None,
+                Some(p) if !p.is_empty() => Some(p.to_string()),
+                _ => Some(name.clone()),
+            };
+            let name = name.rsplit('/').next().unwrap_or(&name).to_string();
+            Some(Library { name, path })
+        })
+        .collect()
+}
+
+/// `NEEDED` entries from `objdump -p`, for binaries ldd cannot load.
+fn parse_needed(output: &str) -> Vec<Library> {
+    output
+        .lines()
+        .filter_map(|l| l.trim().strip_prefix("NEEDED"))
+        .map(|name| Library {
+            name: name.trim().to_string(),
+            path: Some(String::new()),
+        })
+        .collect()
+}
+
+fn counts_line(counts: &BTreeMap<&str, usize>) -> String {
+    let mut counts: Vec<(&&str, &usize)> = counts.iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    counts
+        .iter()
+        .map(|(name, n)| format!("{} {}", name, n))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_report(
+    binary: &str,
+    symbols: &[Symbol],
+    dynamic_only: bool,
+    libraries: &[Library],
+    top: usize,
+) -> String {
+    let mut exported: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut local: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut imports: BTreeMap<&str, usize> = BTreeMap::new();
+    for symbol in symbols {
+        if is_import(symbol.kind) {
+            *imports.entry(import_origin(&symbol.name)).or_default() += 1;
+        } else if symbol.kind.is_ascii_uppercase() {
+            *exported.entry(section(symbol.kind)).or_default() += 1;
+        } else {
+            *local.entry(section(symbol.kind)).or_default() += 1;
+        }
+    }
+
+    let mut out = Vec::new();
+    let source = if dynamic_only {
+        " (stripped, dynamic table only)"
+    } else {
+        ""
+    };
+    out.push(format!(
+        "🔧 {}: {} symbols{}",
+        binary,
+        symbols.len(),
+        source
+    ));
+    let total = |m: &BTreeMap<&str, usize>| m.values().sum::<usize>();
+    if !exported.is_empty() {
+        out.push(format!(
+            "  exported {}: {}",
+            total(&exported),
+            counts_line(&exported)
+        ));
+    }
+    if !local.is_empty() {
+        out.push(format!(
+            "  local {}: {}",
+            total(&local),
+            counts_line(&local)
+        ));
+    }
+    if !imports.is_empty() {
+        out.push(format!(
+            "  imported {}: {}",
+            total(&imports),
+            counts_line(&imports)
+        ));
+    }
+
+    let mut sized: Vec<&Symbol> = symbols
+        .iter()
+        .filter(|s| s.size.is_some_and(|n| n > 0))
+        .collect();
+    sized.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+    if !sized.is_empty() {
+        let total: u64 = sized.iter().filter_map(|s| s.size).sum();
+        out.push(format!(
+            "Largest symbols (of {} sized, {}):",
+            sized.len(),
+            format_size(total)
+        ));
+        for symbol in sized.iter().take(top) {
+            out.push(format!(
+                "  {:>10}  {}  {}",
+                format_size(symbol.size.unwrap_or(0)),
+                symbol.kind,
+                truncate(&symbol.name, 100)
+            ));
+        }
+    }
+
+    if !libraries.is_empty() {
+        let names: Vec<&str> = libraries.iter().map(|l| l.name.as_str()).collect();
+        out.push(format!(
+            "Dynamic libraries ({}): {}",
+            libraries.len(),
+            names.join(", ")
+        ));
+        for missing in libraries.iter().filter(|l| l.path.is_none()) {
+            out.push(format!("  ✗ {} not found", missing.name));
+        }
+    }
+
+    out.join("\n")
+}
+
+fn stdout_of(cmd: &str, args: &[&str], binary: &Path) -> Option<String> {
+    let output = Command::new(cmd).args(args).arg(binary).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+pub fn run(binary: &Path, top: usize, verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    if !binary.exists() {
+        anyhow::bail!("{}: no such file", binary.display());
+    }
+
+    let output = Command::new("nm")
+        .args(["-C", "-S"])
+        .arg(binary)
+        .output()
+        .context("Failed to run nm. Is binutils installed?")?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let mut raw = String::from_utf8_lossy(&output.stdout).to_string();
+    let mut dynamic_only = false;
+    // Stripped binaries keep only the dynamic symbol table
+    if raw.trim().is_empty() && stderr.contains("no symbols") {
+        if let Some(dynamic) = stdout_of("nm", &["-D", "-C", "-S"], binary) {
+            raw = dynamic;
+            dynamic_only = true;
+        }
+    } else if !output.status.success() {
+        anyhow::bail!("nm {}: {}", binary.display(), stderr.trim());
+    }
+    let symbols = parse_nm(&raw);
+
+    let libraries = if cfg!(target_os = "macos") {
+        stdout_of("otool", &["-L"], binary)
+            .map(|o| parse_libraries(&o))
+            .unwrap_or_default()
+    } else {
+        stdout_of("ldd", &[], binary)
+            .map(|o| parse_libraries(&o))
+            .filter(|libs| !libs.is_empty())
+            .or_else(|| stdout_of("objdump", &["-p"], binary).map(|o| parse_needed(&o)))
+            .unwrap_or_default()
+    };
+    if verbose > 0 {
+        eprintln!(
+            "nm: {} symbols, {} libraries",
+            symbols.len(),
+            libraries.len()
+        );
+    }
+
+    let filtered = format_report(
+        &binary.display().to_string(),
+        &symbols,
+        dynamic_only,
+        &libraries,
+        top,
+    );
+    println!("{}", filtered);
+    timer.track(
+        &format!("nm -C -S {} && ldd {}", binary.display(), binary.display()),
+        &format!("rtk nm {}", binary.display()),
+        &raw,
+        &filtered,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NM: &str = "\
+0000000000004010 0000000000000004 b counter
+                 U memcpy@GLIBC_2.14
+                 U puts@GLIBC_2.2.5
+                 w __gmon_start__
+0000000000001139 0000000000000a0b T main
+0000000000001200 0000000000000040 t helper(int, char const*)
+0000000000002000 0000000000000010 R version_string
+0000000000004000 D __data_start
+0000000000001300 0000000000000200 W std::vector<int, std::allocator<int> >::push_back(int const&)
+";
+
+    #[test]
+    fn test_parse_nm() {
+        let symbols = parse_nm(NM);
+        assert_eq!(symbols.len(), 9);
+        assert_eq!(
+            symbols[5],
+            Symbol {
+                kind: 't',
+                size: Some(0x40),
+                name: "helper(int, char const*)".to_string()
+            }
+        );
+        assert_eq!(symbols[7].size, None);
+        assert_eq!(symbols[1].kind, 'U');
+    }
+
+    #[test]
+    fn test_parse_libraries() {
+        let ldd = "\tlinux-vdso.so.1 (0x00007ffc)\n\tlibfoo.so.1 => not found\n\tlibc.so.6 => /lib/x86_64-linux-gnu/libc.so.6 (0x00007f12)\n\t/lib64/ld-linux-x86-64.so.2 (0x00007f34)\n";
+        let libs = parse_libraries(ldd);
+        let names: Vec<&str> = libs.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["libfoo.so.1", "libc.so.6", "ld-linux-x86-64.so.2"]
+        );
+        assert_eq!(libs[0].path, None);
+
+        let otool = "/usr/local/bin/app:\n\t/usr/lib/libSystem.B.dylib (compatibility version 1.0.0, current version 1319.0.0)\n";
+        let libs = parse_libraries(otool);
+        assert_eq!(libs.len(), 1);
+        assert_eq!(libs[0].name, "libSystem.B.dylib");
+
+        let objdump =
+            "Dynamic Section:\n  NEEDED               libc.so.6\n  SONAME               x\n";
+        assert_eq!(parse_needed(objdump)[0].name, "libc.so.6");
+    }
+
+    #[test]
+    fn test_format_report() {
+        let symbols = parse_nm(NM);
+        let libs =
+            parse_libraries("\tlibc.so.6 => /lib/libc.so.6 (0x1)\n\tlibfoo.so => not found\n");
+        let out = format_report("app", &symbols, false, &libs, 2);
+        assert_eq!(
+            out,
+            "🔧 app: 9 symbols
+  exported 4: data 1, rodata 1, text 1, weak 1
+  local 2: bss 1, text 1
+  imported 3: GLIBC 2, unversioned 1
+Largest symbols (of 5 sized, 3.1 KiB):
+     2.5 KiB  T  main
+       512 B  W  std::vector<int, std::allocator<int> >::push_back(int const&)
+Dynamic libraries (2): libc.so.6, libfoo.so
+  ✗ libfoo.so not found"
+        );
+    }
+}
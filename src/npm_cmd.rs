@@ -1,8 +1,14 @@
+use crate::exit_code;
+use crate::install_cmd;
 use crate::tracking;
 use anyhow::{Context, Result};
 use std::process::Command;
 
 pub fn run(args: &[String], verbose: u8, skip_env: bool) -> Result<()> {
+    if install_cmd::is_install("npm", args.first().map(String::as_str)) {
+        return install_cmd::run("npm", args, verbose);
+    }
+
     let timer = tracking::TimedExecution::start();
 
     let mut cmd = Command::new("npm");
@@ -24,11 +30,12 @@ pub fn run(args: &[String], verbose: u8, skip_env: bool) -> Result<()> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
     let filtered = filter_npm_output(&raw);
     println!("{}", filtered);
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("npm run {}", args.join(" ")),
         &format!("rtk npm run {}", args.join(" ")),
         &raw,
@@ -36,7 +43,7 @@ pub fn run(args: &[String], verbose: u8, skip_env: bool) -> Result<()> {
     );
 
     if !output.status.success() {
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     Ok(())
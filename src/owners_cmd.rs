@@ -0,0 +1,449 @@
+use crate::path_rules::{PathAction, PathRules};
+use crate::tracking;
+use anyhow::{Context, Result};
+use chrono::{TimeZone, Utc};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::process::Command;
+
+/// Blaming is one git process per file; past this many the rest are skipped.
+const MAX_FILES: usize = 400;
+/// Directories listed in the per-directory breakdown.
+const MAX_DIRS: usize = 10;
+/// Where GitHub and GitLab look for CODEOWNERS, in order.
+const CODEOWNERS_PATHS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// Lines attributed to one author.
+#[derive(Debug, Default, Clone)]
+struct Share {
+    lines: usize,
+    files: usize,
+    /// Newest author-time among the lines, unix seconds
+    last: i64,
+}
+
+/// Author -> (line count, newest author-time) for one file, from
+/// `git blame --line-porcelain`.
+fn parse_blame(porcelain: &str) -> HashMap<String, (usize, i64)> {
+    let mut authors: HashMap<String, (usize, i64)> = HashMap::new();
+    let mut author = String::new();
+    for line in porcelain.lines() {
+        if let Some(name) = line.strip_prefix("author ") {
+            author = if name == "Not Committed Yet" {
+                "uncommitted".to_string()
+            } else {
+                name.to_string()
+            };
+        } else if let Some(time) = line.strip_prefix("author-time ") {
+            let entry = authors.entry(author.clone()).or_default();
+            entry.1 = entry.1.max(time.trim().parse().unwrap_or(0));
+        } else if line.starts_with('\t') {
+            // The content line closes each entry
+            authors.entry(author.clone()).or_default().0 += 1;
+        }
+    }
+    authors
+}
+
+/// CODEOWNERS rules in file order; the last matching rule wins.
+struct CodeOwners {
+    source: String,
+    rules: Vec<(Gitignore, String)>,
+}
+
+impl CodeOwners {
+    fn parse(source: &str, content: &str) -> Self {
+        let mut rules = Vec::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('[') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else {
+                continue;
+            };
+            let owners: Vec<&str> = parts.take_while(|p| !p.starts_with('#')).collect();
+            let mut builder = GitignoreBuilder::new("");
+            if builder.add_line(None, pattern).is_err() {
+                continue;
+            }
+            if let Ok(matcher) = builder.build() {
+                rules.push((matcher, owners.join(" ")));
+            }
+        }
+        CodeOwners {
+            source: source.to_string(),
+            rules,
+        }
+    }
+
+    fn load(root: &Path) -> Option<Self> {
+        CODEOWNERS_PATHS.iter().find_map(|rel| {
+            let content = std::fs::read_to_string(root.join(rel)).ok()?;
+            Some(Self::parse(rel, &content))
+        })
+    }
+
+    /// Owners of a repo-relative path; empty when no rule matches or the
+    /// matching rule lists nobody.
+    fn owners(&self, path: &str) -> &str {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(matcher, _)| matcher.matched_path_or_any_parents(path, false).is_ignore())
+            .map_or("", |(_, owners)| owners.as_str())
+    }
+}
+
+/// Longest directory prefix shared by every path ("" for the repo root).
+fn common_dir(files: &[String]) -> String {
+    let Some(first) = files.first() else {
+        return String::new();
+    };
+    let mut prefix: Vec<&str> = first.split('/').collect();
+    prefix.pop();
+    for file in &files[1..] {
+        let dirs: Vec<&str> = file.split('/').collect();
+        let dirs = &dirs[..dirs.len() - 1];
+        let shared = prefix.iter().zip(dirs).take_while(|(a, b)| a == b).count();
+        prefix.truncate(shared);
+    }
+    prefix.join("/")
+}
+
+/// Directory one level below `base` that holds `file`, or `base` itself.
+fn child_dir(base: &str, file: &str) -> String {
+    let rest = if base.is_empty() {
+        file
+    } else {
+        file.strip_prefix(base)
+            .and_then(|r| r.strip_prefix('/'))
+            .unwrap_or(file)
+    };
+    match rest.split_once('/') {
+        Some((dir, _)) if base.is_empty() => format!("{}/", dir),
+        Some((dir, _)) => format!("{}/{}/", base, dir),
+        None if base.is_empty() => "./".to_string(),
+        None => format!("{}/", base),
+    }
+}
+
+fn date(ts: i64) -> String {
+    Utc.timestamp_opt(ts, 0)
+        .single()
+        .map_or_else(|| "?".to_string(), |d| d.format("%Y-%m-%d").to_string())
+}
+
+/// Authors sorted by line count, largest first.
+fn ranked(shares: &HashMap<String, Share>) -> Vec<(&String, &Share)> {
+    let mut ranked: Vec<(&String, &Share)> = shares.iter().collect();
+    ranked.sort_by(|a, b| b.1.lines.cmp(&a.1.lines).then_with(|| a.0.cmp(b.0)));
+    ranked
+}
+
+fn percent(part: usize, total: usize) -> usize {
+    (part * 100 + total / 2).checked_div(total).unwrap_or(0)
+}
+
+struct Report<'a> {
+    label: &'a str,
+    /// Blamed files: repo-relative path -> author -> (lines, newest time)
+    files: &'a BTreeMap<String, HashMap<String, (usize, i64)>>,
+    skipped: usize,
+    codeowners: Option<&'a CodeOwners>,
+    top: usize,
+}
+
+fn format_report(report: &Report) -> String {
+    let mut totals: HashMap<String, Share> = HashMap::new();
+    let mut dirs: BTreeMap<String, HashMap<String, Share>> = BTreeMap::new();
+    let paths: Vec<String> = report.files.keys().cloned().collect();
+    let base = common_dir(&paths);
+    for (file, authors) in report.files {
+        let dir = dirs.entry(child_dir(&base, file)).or_default();
+        for (author, (lines, last)) in authors {
+            for share in [
+                totals.entry(author.clone()).or_default(),
+                dir.entry(author.clone()).or_default(),
+            ] {
+                share.lines += lines;
+                share.files += 1;
+                share.last = share.last.max(*last);
+            }
+        }
+    }
+    let total_lines: usize = totals.values().map(|s| s.lines).sum();
+
+    let mut out = Vec::new();
+    let mut header = format!(
+        "👥 {}: {} lines in {} files, {} authors",
+        report.label,
+        total_lines,
+        report.files.len(),
+        totals.len()
+    );
+    if report.skipped > 0 {
+        header.push_str(&format!(" ({} more files not blamed)", report.skipped));
+    }
+    out.push(header);
+    if total_lines == 0 {
+        return out.join("\n");
+    }
+
+    let authors = ranked(&totals);
+    let width = authors
+        .iter()
+        .take(report.top)
+        .map(|(a, _)| a.chars().count())
+        .max()
+        .unwrap_or(0);
+    for (author, share) in authors.iter().take(report.top) {
+        out.push(format!(
+            "  {:width$}  {:>3}%  {} lines, {} files, last {}",
+            author,
+            percent(share.lines, total_lines),
+            share.lines,
+            share.files,
+            date(share.last),
+        ));
+    }
+    if authors.len() > report.top {
+        let rest: usize = authors[report.top..].iter().map(|(_, s)| s.lines).sum();
+        out.push(format!(
+            "  ... +{} more authors ({}%)",
+            authors.len() - report.top,
+            percent(rest, total_lines)
+        ));
+    }
+
+    if dirs.len() > 1 {
+        out.push(String::new());
+        out.push("By directory:".to_string());
+        let mut ordered: Vec<(&String, &HashMap<String, Share>, usize)> = dirs
+            .iter()
+            .map(|(dir, shares)| (dir, shares, shares.values().map(|s| s.lines).sum()))
+            .collect();
+        ordered.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(b.0)));
+        let width = ordered
+            .iter()
+            .take(MAX_DIRS)
+            .map(|(d, _, _)| d.len())
+            .max()
+            .unwrap_or(0);
+        for (dir, shares, lines) in ordered.iter().take(MAX_DIRS) {
+            let leaders: Vec<String> = ranked(shares)
+                .iter()
+                .take(2)
+                .map(|(a, s)| format!("{} {}%", a, percent(s.lines, *lines)))
+                .collect();
+            let last = shares.values().map(|s| s.last).max().unwrap_or(0);
+            out.push(format!(
+                "  {:width$}  {}  (last {})",
+                dir,
+                leaders.join(", "),
+                date(last)
+            ));
+        }
+        if ordered.len() > MAX_DIRS {
+            out.push(format!("  ... +{} more", ordered.len() - MAX_DIRS));
+        }
+    }
+
+    if let Some(codeowners) = report.codeowners {
+        let mut owned: BTreeMap<&str, usize> = BTreeMap::new();
+        for file in report.files.keys() {
+            *owned.entry(codeowners.owners(file)).or_default() += 1;
+        }
+        let mut owned: Vec<(&str, usize)> = owned.into_iter().collect();
+        owned.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        out.push(String::new());
+        out.push(format!("CODEOWNERS ({}):", codeowners.source));
+        for (owners, count) in owned.iter().take(MAX_DIRS) {
+            let owners = if owners.is_empty() {
+                "(unowned)"
+            } else {
+                owners
+            };
+            out.push(format!("  {}  {} files", owners, count));
+        }
+        if owned.len() > MAX_DIRS {
+            out.push(format!("  ... +{} more owner sets", owned.len() - MAX_DIRS));
+        }
+    }
+
+    out.join("\n")
+}
+
+/// True when the start of the file looks binary (a NUL byte).
+fn is_binary(path: &Path) -> bool {
+    use std::io::Read;
+    let mut buf = [0u8; 8000];
+    match std::fs::File::open(path).and_then(|mut f| f.read(&mut buf)) {
+        Ok(n) => buf[..n].contains(&0),
+        Err(_) => true,
+    }
+}
+
+pub fn run(path: &str, top: usize, verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+
+    let root = Command::new("git")
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .context("Failed to run git")?;
+    if !root.status.success() {
+        anyhow::bail!("Not a git repository: rtk owners needs git blame");
+    }
+    let root = String::from_utf8_lossy(&root.stdout).trim().to_string();
+    let root = Path::new(&root);
+
+    let listed = Command::new("git")
+        .args(["ls-files", "-z", "--full-name", "--"])
+        .arg(path)
+        .output()
+        .context("Failed to run git ls-files")?;
+    let rules = PathRules::load();
+    let tracked: Vec<String> = String::from_utf8_lossy(&listed.stdout)
+        .split('\0')
+        .filter(|f| !f.is_empty())
+        .map(str::to_string)
+        // Lockfiles and generated code would otherwise dominate the counts
+        .filter(|f| matches!(rules.action(Path::new(f), false), PathAction::Keep))
+        .filter(|f| !is_binary(&root.join(f)))
+        .collect();
+    if verbose > 0 {
+        eprintln!("owners: {} tracked files under {}", tracked.len(), path);
+    }
+
+    let mut files = BTreeMap::new();
+    let mut raw = String::new();
+    for file in tracked.iter().take(MAX_FILES) {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(["blame", "--line-porcelain", "--", file])
+            .output();
+        if let Ok(output) = output {
+            if output.status.success() {
+                let porcelain = String::from_utf8_lossy(&output.stdout);
+                files.insert(file.clone(), parse_blame(&porcelain));
+                raw.push_str(&porcelain);
+            }
+        }
+    }
+
+    let codeowners = CodeOwners::load(root);
+    let output = format_report(&Report {
+        label: path,
+        files: &files,
+        skipped: tracked.len().saturating_sub(MAX_FILES),
+        codeowners: codeowners.as_ref(),
+        top,
+    });
+    println!("{}", output);
+    timer.track(
+        &format!("git blame --line-porcelain {}", path),
+        &format!("rtk owners {}", path),
+        &raw,
+        &output,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_blame() {
+        let porcelain = "\
+1111111111111111111111111111111111111111 1 1 2
+author Alice
+author-time 1700000000
+filename src/a.rs
+\tfn a() {}
+1111111111111111111111111111111111111111 2 2
+author Alice
+author-time 1700000000
+filename src/a.rs
+\t}
+2222222222222222222222222222222222222222 3 3 1
+author Bob
+author-time 1750000000
+filename src/a.rs
+\tfn b() {}
+";
+        let authors = parse_blame(porcelain);
+        assert_eq!(authors["Alice"], (2, 1700000000));
+        assert_eq!(authors["Bob"], (1, 1750000000));
+    }
+
+    #[test]
+    fn test_codeowners_last_rule_wins() {
+        let owners = CodeOwners::parse(
+            "CODEOWNERS",
+            "# comment\n* @org/everyone\n/src/ @alice\n*.md @docs # trailing\n/src/vendor/\n",
+        );
+        assert_eq!(owners.owners("Cargo.toml"), "@org/everyone");
+        assert_eq!(owners.owners("src/main.rs"), "@alice");
+        assert_eq!(owners.owners("src/README.md"), "@docs");
+        assert_eq!(owners.owners("src/vendor/x.rs"), "");
+    }
+
+    #[test]
+    fn test_format_report() {
+        let mut files = BTreeMap::new();
+        files.insert(
+            "src/cmd/a.rs".to_string(),
+            HashMap::from([
+                ("Alice".to_string(), (60, 1700000000)),
+                ("Bob".to_string(), (20, 1750000000)),
+            ]),
+        );
+        files.insert(
+            "src/core/b.rs".to_string(),
+            HashMap::from([("Bob".to_string(), (20, 1760000000))]),
+        );
+        let codeowners = CodeOwners::parse("CODEOWNERS", "/src/cmd/ @alice\n");
+        let report = format_report(&Report {
+            label: "src",
+            files: &files,
+            skipped: 0,
+            codeowners: Some(&codeowners),
+            top: 5,
+        });
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "👥 src: 100 lines in 2 files, 2 authors");
+        assert_eq!(
+            lines[1],
+            "  Alice   60%  60 lines, 1 files, last 2023-11-14"
+        );
+        assert_eq!(
+            lines[2],
+            "  Bob     40%  40 lines, 2 files, last 2025-10-09"
+        );
+        assert_eq!(lines[4], "By directory:");
+        assert_eq!(
+            lines[5],
+            "  src/cmd/   Alice 75%, Bob 25%  (last 2025-06-15)"
+        );
+        assert_eq!(lines[6], "  src/core/  Bob 100%  (last 2025-10-09)");
+        assert!(
+            report.ends_with("CODEOWNERS (CODEOWNERS):\n  (unowned)  1 files\n  @alice  1 files")
+        );
+    }
+
+    #[test]
+    fn test_child_dir() {
+        assert_eq!(child_dir("", "src/a.rs"), "src/");
+        assert_eq!(child_dir("", "Cargo.toml"), "./");
+        assert_eq!(child_dir("src", "src/cmd/a.rs"), "src/cmd/");
+        assert_eq!(child_dir("src", "src/lib.rs"), "src/");
+        assert_eq!(
+            common_dir(&["src/cmd/a.rs".to_string(), "src/core/b.rs".to_string()]),
+            "src"
+        );
+    }
+}
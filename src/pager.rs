@@ -0,0 +1,381 @@
+//! `--pager` (or `output.pager = true`): for a human at a terminal, show the
+//! compressed output in an interactive pager where every elided section can
+//! be expanded in place, instead of re-running the command with `--raw`.
+//!
+//! Like `--raw`, output is held back while the wrapper runs. `track` hands
+//! over the unfiltered input; elided blocks are found the way `--explain`
+//! finds them and each becomes a fold under the output line it followed.
+//! Without a terminal, or when nothing was elided, output is printed as-is.
+
+use crate::config::Config;
+use crate::explain;
+use crate::utils::strip_ansi;
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::Paragraph;
+use ratatui::{DefaultTerminal, Frame};
+use std::io::{IsTerminal, Write};
+use std::sync::{Mutex, OnceLock};
+
+/// Output held back for the pager; `None` once shown.
+static HELD: OnceLock<Mutex<Option<Held>>> = OnceLock::new();
+
+#[derive(Default)]
+struct Held {
+    /// Rendered output, as it would have been printed
+    shown: String,
+    /// Unfiltered input from the last `track`
+    input: Option<String>,
+}
+
+pub fn enabled(cli: bool) -> bool {
+    cli || Config::cached().output.pager
+}
+
+/// Hold output for the pager. `None` (pager off) when not on a terminal.
+pub fn enable() -> Option<PagerGuard> {
+    if !std::io::stdout().is_terminal() || !std::io::stdin().is_terminal() {
+        return None;
+    }
+    let _ = HELD.set(Mutex::new(Some(Held::default())));
+    Some(PagerGuard)
+}
+
+pub struct PagerGuard;
+
+impl Drop for PagerGuard {
+    fn drop(&mut self) {
+        flush();
+    }
+}
+
+/// Called from `profile::emit`: true when `text` was held for the pager.
+pub(crate) fn hold(text: &str) -> bool {
+    let Some(held) = HELD.get() else {
+        return false;
+    };
+    let Ok(mut held) = held.lock() else {
+        return false;
+    };
+    match held.as_mut() {
+        Some(held) => {
+            held.shown.push_str(text);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Called from `track`: keep the unfiltered input so folds can be expanded.
+pub fn capture(input: &str) {
+    if let Some(held) = HELD.get() {
+        if let Ok(mut held) = held.lock() {
+            if let Some(held) = held.as_mut() {
+                held.input = Some(input.to_string());
+            }
+        }
+    }
+}
+
+/// Show held output: in the pager when something was elided, then printed
+/// so it stays in the scrollback (also used before an early exit).
+pub fn flush() {
+    let Some(held) = HELD.get().and_then(|m| m.lock().ok()?.take()) else {
+        return;
+    };
+    if let Some(input) = &held.input {
+        let rows = build_rows(&held.shown, input);
+        if rows.iter().any(|r| matches!(r, Row::Fold { .. })) {
+            if let Err(e) = page(rows) {
+                eprintln!("rtk pager: {}", e);
+            }
+        }
+    }
+    let _ = std::io::stdout().lock().write_all(held.shown.as_bytes());
+}
+
+#[derive(Debug, PartialEq)]
+enum Row {
+    Line(String),
+    Fold { lines: Vec<String>, open: bool },
+}
+
+/// Output lines with each elided input block folded in after the output
+/// line that showed the input line right before it.
+fn build_rows(shown: &str, input: &str) -> Vec<Row> {
+    let shown = strip_ansi(shown);
+    let input = strip_ansi(input);
+    let out_lines: Vec<&str> = shown.lines().collect();
+    let in_lines: Vec<&str> = input.lines().collect();
+
+    // (output line the fold follows, hidden lines)
+    let mut folds: Vec<(Option<usize>, Vec<String>)> = Vec::new();
+    let mut next = 0;
+    let mut anchor: Option<usize> = None;
+    for (start, len, _) in explain::elided_sections(&input, &shown) {
+        let begin = start - 1;
+        let kept = in_lines[..begin]
+            .iter()
+            .map(|l| l.trim())
+            .rfind(|l| !l.is_empty());
+        if let Some(kept) = kept {
+            if let Some(pos) = out_lines[next..]
+                .iter()
+                .position(|o| o.trim() == kept || o.contains(kept))
+            {
+                anchor = Some(next + pos);
+                next += pos + 1;
+            }
+        }
+        let hidden = in_lines[begin..begin + len]
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        folds.push((anchor, hidden));
+    }
+
+    let mut rows = Vec::new();
+    let mut folds = folds.into_iter().peekable();
+    while let Some((_, lines)) = folds.next_if(|(a, _)| a.is_none()) {
+        rows.push(Row::Fold { lines, open: false });
+    }
+    for (i, line) in out_lines.iter().enumerate() {
+        rows.push(Row::Line(line.to_string()));
+        while let Some((_, lines)) = folds.next_if(|(a, _)| *a == Some(i)) {
+            rows.push(Row::Fold { lines, open: false });
+        }
+    }
+    rows
+}
+
+/// What the screen shows: one entry per visible line.
+#[derive(Debug, PartialEq)]
+enum Entry {
+    Line(usize),
+    FoldHeader(usize),
+    FoldLine(usize, usize),
+}
+
+struct View {
+    rows: Vec<Row>,
+    entries: Vec<Entry>,
+    cursor: usize,
+    top: usize,
+}
+
+impl View {
+    fn new(rows: Vec<Row>) -> Self {
+        let mut view = Self {
+            rows,
+            entries: Vec::new(),
+            cursor: 0,
+            top: 0,
+        };
+        view.layout();
+        view
+    }
+
+    fn layout(&mut self) {
+        self.entries.clear();
+        for (i, row) in self.rows.iter().enumerate() {
+            match row {
+                Row::Line(_) => self.entries.push(Entry::Line(i)),
+                Row::Fold { lines, open } => {
+                    self.entries.push(Entry::FoldHeader(i));
+                    if *open {
+                        self.entries
+                            .extend((0..lines.len()).map(|j| Entry::FoldLine(i, j)));
+                    }
+                }
+            }
+        }
+        self.cursor = self.cursor.min(self.entries.len().saturating_sub(1));
+    }
+
+    /// Open or close the fold under the cursor.
+    fn toggle(&mut self) {
+        let row = match self.entries.get(self.cursor) {
+            Some(Entry::FoldHeader(i)) | Some(Entry::FoldLine(i, _)) => *i,
+            _ => return,
+        };
+        if let Row::Fold { open, .. } = &mut self.rows[row] {
+            *open = !*open;
+        }
+        self.layout();
+        // Stay on the header of the fold just toggled
+        if let Some(pos) = self
+            .entries
+            .iter()
+            .position(|e| *e == Entry::FoldHeader(row))
+        {
+            self.cursor = pos;
+        }
+    }
+
+    fn set_all(&mut self, value: bool) {
+        for row in &mut self.rows {
+            if let Row::Fold { open, .. } = row {
+                *open = value;
+            }
+        }
+        self.layout();
+    }
+
+    fn next_fold(&mut self) {
+        let rest = self.entries.get(self.cursor + 1..).unwrap_or_default();
+        if let Some(pos) = rest.iter().position(|e| matches!(e, Entry::FoldHeader(_))) {
+            self.cursor += pos + 1;
+        }
+    }
+
+    fn text(&self, entry: &Entry) -> String {
+        match *entry {
+            Entry::Line(i) => match &self.rows[i] {
+                Row::Line(text) => text.clone(),
+                Row::Fold { .. } => String::new(),
+            },
+            Entry::FoldHeader(i) => match &self.rows[i] {
+                Row::Fold { lines, open: true } => format!("▾ {} elided lines", lines.len()),
+                Row::Fold { lines, .. } => {
+                    format!("▸ … {} lines elided (Enter to expand)", lines.len())
+                }
+                Row::Line(_) => String::new(),
+            },
+            Entry::FoldLine(i, j) => match &self.rows[i] {
+                Row::Fold { lines, .. } => format!("│ {}", lines[j]),
+                Row::Line(_) => String::new(),
+            },
+        }
+    }
+
+    /// Keep the cursor on screen.
+    fn scroll(&mut self, height: usize) {
+        if self.cursor < self.top {
+            self.top = self.cursor;
+        } else if height > 0 && self.cursor >= self.top + height {
+            self.top = self.cursor + 1 - height;
+        }
+    }
+
+    /// Returns true to quit.
+    fn handle_key(&mut self, code: KeyCode, page: usize) -> bool {
+        let last = self.entries.len().saturating_sub(1);
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Down | KeyCode::Char('j') => self.cursor = (self.cursor + 1).min(last),
+            KeyCode::Up | KeyCode::Char('k') => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.cursor = (self.cursor + page).min(last),
+            KeyCode::PageUp | KeyCode::Char('b') => self.cursor = self.cursor.saturating_sub(page),
+            KeyCode::Home | KeyCode::Char('g') => self.cursor = 0,
+            KeyCode::End | KeyCode::Char('G') => self.cursor = last,
+            KeyCode::Enter | KeyCode::Tab => self.toggle(),
+            KeyCode::Char('n') => self.next_fold(),
+            KeyCode::Char('e') => self.set_all(true),
+            KeyCode::Char('c') => self.set_all(false),
+            _ => {}
+        }
+        false
+    }
+}
+
+fn page(rows: Vec<Row>) -> Result<()> {
+    let mut view = View::new(rows);
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut view);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, view: &mut View) -> Result<()> {
+    loop {
+        let height = terminal.size()?.height.saturating_sub(1) as usize;
+        view.scroll(height);
+        terminal.draw(|frame| draw(frame, view))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press && view.handle_key(key.code, height.max(1)) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, view: &View) {
+    let [body, footer] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+    let lines: Vec<Line> = view
+        .entries
+        .iter()
+        .enumerate()
+        .skip(view.top)
+        .take(body.height as usize)
+        .map(|(i, entry)| {
+            let mut style = match entry {
+                Entry::Line(_) => Style::default(),
+                Entry::FoldHeader(_) => Style::default().fg(Color::Cyan),
+                Entry::FoldLine(..) => Style::default().fg(Color::DarkGray),
+            };
+            if i == view.cursor {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            Line::styled(view.text(entry), style)
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), body);
+    frame.render_widget(
+        Paragraph::new(
+            "↑↓ move · Enter expand/collapse · n next fold · e/c expand/collapse all · q quit",
+        )
+        .style(Style::default().add_modifier(Modifier::DIM)),
+        footer,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "Compiling a\nCompiling b\nCompiling c\nCompiling d\nerror: boom\nwarning: x\nw1\nw2\nw3\nFinished";
+
+    #[test]
+    fn test_build_rows_folds_after_preceding_line() {
+        let rows = build_rows("error: boom\nFinished\n", INPUT);
+        assert_eq!(
+            rows,
+            vec![
+                Row::Fold {
+                    lines: vec![
+                        "Compiling a".into(),
+                        "Compiling b".into(),
+                        "Compiling c".into(),
+                        "Compiling d".into()
+                    ],
+                    open: false
+                },
+                Row::Line("error: boom".into()),
+                Row::Fold {
+                    lines: vec!["warning: x".into(), "w1".into(), "w2".into(), "w3".into()],
+                    open: false
+                },
+                Row::Line("Finished".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_view_toggle_and_navigation() {
+        let mut view = View::new(build_rows("error: boom\nFinished\n", INPUT));
+        assert_eq!(view.entries.len(), 4);
+        view.handle_key(KeyCode::Char('j'), 10);
+        view.handle_key(KeyCode::Char('n'), 10);
+        assert_eq!(view.entries[view.cursor], Entry::FoldHeader(2));
+        view.handle_key(KeyCode::Enter, 10);
+        assert_eq!(view.entries.len(), 8);
+        assert_eq!(view.text(&view.entries[3]), "│ warning: x");
+        view.handle_key(KeyCode::Char('c'), 10);
+        assert_eq!(view.entries.len(), 4);
+        assert!(view.handle_key(KeyCode::Char('q'), 10));
+    }
+}
@@ -0,0 +1,80 @@
+//! Parallel work over many inputs, merged back in input order.
+//!
+//! `rtk cat a.rs b.rs c.rs` filters files side by side and grep cleans its
+//! match lines the same way; output is always assembled in input order, so
+//! it is identical whatever the number of threads. `--jobs N` (or
+//! `execution.jobs`) sets the thread count, 0 meaning one per CPU; `--jobs 1`
+//! runs everything on the calling thread.
+
+use crate::config::Config;
+use rayon::prelude::*;
+use rayon::ThreadPool;
+use std::sync::OnceLock;
+
+/// Below this many items threads cost more than they save.
+const MIN_PARALLEL: usize = 2;
+
+static JOBS: OnceLock<usize> = OnceLock::new();
+
+/// Use `jobs` threads (`--jobs`), else `execution.jobs`. Call once at startup.
+pub fn init(jobs: Option<usize>) {
+    let _ = JOBS.set(jobs.unwrap_or_else(|| Config::cached().execution.jobs));
+}
+
+/// Configured thread count, with 0 resolved to the number of CPUs.
+pub fn jobs() -> usize {
+    match JOBS.get().copied().unwrap_or(0) {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    }
+}
+
+/// Pool sized by [`jobs`], built on first use; `None` when running serially.
+fn pool() -> Option<&'static ThreadPool> {
+    static POOL: OnceLock<Option<ThreadPool>> = OnceLock::new();
+    POOL.get_or_init(|| {
+        let jobs = jobs();
+        (jobs > 1)
+            .then(|| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(jobs)
+                    .thread_name(|i| format!("rtk-worker-{}", i))
+                    .build()
+                    .ok()
+            })
+            .flatten()
+    })
+    .as_ref()
+}
+
+/// `f` applied to every item, possibly concurrently; results in item order.
+pub fn map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync + Send,
+{
+    match pool() {
+        Some(pool) if items.len() >= MIN_PARALLEL => {
+            pool.install(|| items.par_iter().map(&f).collect())
+        }
+        _ => items.iter().map(f).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_keeps_input_order() {
+        let items: Vec<u64> = (0..200).collect();
+        let out = map(&items, |&n| {
+            // Later items finish first
+            std::thread::sleep(std::time::Duration::from_micros(200 - n));
+            n * 2
+        });
+        assert_eq!(out, items.iter().map(|n| n * 2).collect::<Vec<_>>());
+        assert!(map(&[] as &[u64], |&n| n).is_empty());
+    }
+}
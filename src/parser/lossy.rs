@@ -0,0 +1,107 @@
+//! Lossy parsing: malformed tool output degrades to generic compression.
+//!
+//! Summarizers see whatever a tool printed: truncated JSON, a wrapper's
+//! banner in front of `go test -json` events, binary junk. With
+//! `output.lossy` on (the default), input a summarizer can't parse goes
+//! through [`generic_cmd::compress`] after a `[RTK:DEGRADED]` warning on
+//! stderr, instead of failing the run; with it off, the parse error is
+//! returned as before.
+//!
+//! [`Summarizer`] names the parsers that take arbitrary text; the cargo-fuzz
+//! targets in `fuzz/` feed [`summarize`] arbitrary bytes for each of them.
+
+use crate::config::Config;
+use crate::{cargo_cmd, generic_cmd, git_diff, go_cmd, json_cmd, log_cmd, pytest_cmd};
+use anyhow::Result;
+
+/// Parsers fed raw tool output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Summarizer {
+    Diff,
+    CargoTest,
+    Pytest,
+    GoTest,
+    Json,
+    Log,
+}
+
+impl Summarizer {
+    pub const ALL: [Summarizer; 6] = [
+        Summarizer::Diff,
+        Summarizer::CargoTest,
+        Summarizer::Pytest,
+        Summarizer::GoTest,
+        Summarizer::Json,
+        Summarizer::Log,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Summarizer::Diff => "git diff",
+            Summarizer::CargoTest => "cargo test",
+            Summarizer::Pytest => "pytest",
+            Summarizer::GoTest => "go test",
+            Summarizer::Json => "json",
+            Summarizer::Log => "log",
+        }
+    }
+
+    /// The summary, or the parse error.
+    pub fn parse(self, input: &str) -> Result<String> {
+        match self {
+            Summarizer::Diff => Ok(git_diff::compact_diff(input, 500)),
+            Summarizer::CargoTest => Ok(cargo_cmd::filter_cargo_test(input)),
+            Summarizer::Pytest => Ok(pytest_cmd::filter_pytest_output(input)),
+            Summarizer::GoTest => go_cmd::filter_go_test_json(input),
+            Summarizer::Json => json_cmd::filter_json_string(input, 5),
+            Summarizer::Log => Ok(log_cmd::run_stdin_str(input)),
+        }
+    }
+}
+
+/// `summarizer`'s summary of `input`, degraded per `output.lossy`.
+pub fn summarize(summarizer: Summarizer, input: &str) -> Result<String> {
+    degrade(summarizer.name(), input, summarizer.parse(input))
+}
+
+/// `parsed` as is, or with `output.lossy` on, a parse error replaced by the
+/// generic compression of `input`.
+pub fn degrade(tool: &str, input: &str, parsed: Result<String>) -> Result<String> {
+    match parsed {
+        Err(e) if Config::cached().output.lossy => {
+            tracing::warn!(tool, error = format!("{:#}", e), "parse failed, degrading");
+            super::emit_degradation_warning(tool, &format!("{:#}, using generic compression", e));
+            Ok(generic_cmd::compress(input))
+        }
+        parsed => parsed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_degrade_falls_back_to_generic() {
+        let input = "{\"truncated\": [1, 2,\nnot json";
+        assert!(Summarizer::Json.parse(input).is_err());
+        let summary = summarize(Summarizer::Json, input).unwrap();
+        assert_eq!(summary, generic_cmd::compress(input));
+        assert_eq!(
+            summarize(Summarizer::Json, "{\"a\": 1}").unwrap(),
+            json_cmd::filter_json_string("{\"a\": 1}", 5).unwrap()
+        );
+    }
+
+    proptest::proptest! {
+        #![proptest_config(proptest::prelude::ProptestConfig::with_cases(64))]
+
+        // Same property as the fuzz targets, with a smaller input space
+        #[test]
+        fn prop_summarizers_never_panic(input in "(?s)(diff --git a/x b/x\n|@@ -1 +1 @@\n|[+ -]|\\{|\\}|\\[|\\]|\"|:|,|test |ok|FAILED|ERROR|\\.py:|\n|\r|\u{1b}\\[31m|é|.){0,200}") {
+            for summarizer in Summarizer::ALL {
+                proptest::prop_assert!(summarize(summarizer, &input).is_ok());
+            }
+        }
+    }
+}
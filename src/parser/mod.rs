@@ -9,6 +9,7 @@
 
 pub mod error;
 pub mod formatter;
+pub mod lossy;
 pub mod types;
 
 pub use formatter::{FormatMode, TokenFormatter};
@@ -97,15 +98,19 @@ pub trait OutputParser: Sized {
 
 /// Truncate output to max length with ellipsis
 pub fn truncate_output(output: &str, max_chars: usize) -> String {
-    if output.len() <= max_chars {
+    let chars = output.chars().count();
+    if chars <= max_chars {
         return output.to_string();
     }
 
-    let truncated = &output[..max_chars];
+    let end = output
+        .char_indices()
+        .nth(max_chars)
+        .map_or(output.len(), |(i, _)| i);
     format!(
         "{}\n\n[RTK:PASSTHROUGH] Output truncated ({} chars → {} chars)",
-        truncated,
-        output.len(),
+        &output[..end],
+        chars,
         max_chars
     )
 }
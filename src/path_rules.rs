@@ -0,0 +1,257 @@
+//! Per-project path rules: `.rtkignore` files plus `[filters]` config patterns.
+//!
+//! `.rtkignore` uses gitignore syntax and is looked up in the current
+//! directory and its ancestors (deeper files win, `!pattern` re-includes).
+//! `filters.exclude_paths` behaves like an extra `.rtkignore`, while
+//! `filters.summarize_paths` keeps matching paths but collapses them to counts.
+
+use crate::config::Config;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::path::{Path, PathBuf};
+
+pub const RTKIGNORE_FILE: &str = ".rtkignore";
+
+/// What to do with a path when building compact output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathAction {
+    Keep,
+    Exclude,
+    Summarize,
+}
+
+#[derive(Default)]
+pub struct PathRules {
+    base: PathBuf,
+    /// `.rtkignore` matchers, deepest directory first
+    ignore_files: Vec<Gitignore>,
+    exclude: Option<Gitignore>,
+    summarize: Option<Gitignore>,
+}
+
+impl PathRules {
+    /// Rules for the current directory: ancestor `.rtkignore` files + config.
+    pub fn load() -> Self {
+        let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        let ignore_paths: Vec<PathBuf> = cwd
+            .ancestors()
+            .map(|dir| dir.join(RTKIGNORE_FILE))
+            .filter(|p| p.is_file())
+            .collect();
+        let filters = &Config::cached().filters;
+        Self::from_parts(
+            &cwd,
+            &ignore_paths,
+            &filters.exclude_paths,
+            &filters.summarize_paths,
+        )
+    }
+
+    fn from_parts(
+        base: &Path,
+        ignore_paths: &[PathBuf],
+        exclude: &[String],
+        summarize: &[String],
+    ) -> Self {
+        let ignore_files = ignore_paths
+            .iter()
+            .filter_map(|file| {
+                let root = file.parent().unwrap_or(base);
+                let mut builder = GitignoreBuilder::new(root);
+                if let Some(err) = builder.add(file) {
+                    eprintln!("rtk: warning: {}: {}", file.display(), err);
+                }
+                builder.build().ok()
+            })
+            .collect();
+
+        Self {
+            base: base.to_path_buf(),
+            ignore_files,
+            exclude: build_patterns(base, exclude),
+            summarize: build_patterns(base, summarize),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ignore_files.is_empty() && self.exclude.is_none() && self.summarize.is_none()
+    }
+
+    /// One-line description of the active rules, `None` when there are none.
+    pub fn summary(&self) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+        let count = |m: &Option<Gitignore>| m.as_ref().map_or(0, |g| g.num_ignores());
+        Some(format!(
+            "{} {} file(s), {} exclude pattern(s), {} summarize pattern(s)",
+            self.ignore_files.len(),
+            RTKIGNORE_FILE,
+            count(&self.exclude),
+            count(&self.summarize)
+        ))
+    }
+
+    /// Classify `path` (absolute, or relative to the current directory).
+    pub fn action(&self, path: &Path, is_dir: bool) -> PathAction {
+        if self.is_empty() {
+            return PathAction::Keep;
+        }
+        let abs = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.base.join(path)
+        };
+
+        // Deepest .rtkignore with an opinion decides (so `!keep` can override)
+        for matcher in &self.ignore_files {
+            match matched(matcher, &abs, is_dir) {
+                Match::Ignore(_) => return PathAction::Exclude,
+                Match::Whitelist(_) => break,
+                Match::None => {}
+            }
+        }
+        if let Some(matcher) = &self.exclude {
+            if matched(matcher, &abs, is_dir).is_ignore() {
+                return PathAction::Exclude;
+            }
+        }
+        if let Some(matcher) = &self.summarize {
+            if matched(matcher, &abs, is_dir).is_ignore() {
+                return PathAction::Summarize;
+            }
+        }
+        PathAction::Keep
+    }
+}
+
+fn build_patterns(root: &Path, patterns: &[String]) -> Option<Gitignore> {
+    if patterns.is_empty() {
+        return None;
+    }
+    let mut builder = GitignoreBuilder::new(root);
+    for pattern in patterns {
+        if let Err(err) = builder.add_line(None, pattern) {
+            eprintln!("rtk: warning: invalid path rule '{}': {}", pattern, err);
+        }
+    }
+    builder.build().ok()
+}
+
+/// Match against `path` and its parents; paths outside the matcher root never match.
+fn matched<'a>(
+    matcher: &'a Gitignore,
+    path: &Path,
+    is_dir: bool,
+) -> Match<&'a ignore::gitignore::Glob> {
+    if !path.starts_with(matcher.path()) {
+        return Match::None;
+    }
+    matcher.matched_path_or_any_parents(path, is_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn patterns(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_empty_rules_keep_everything() {
+        let rules = PathRules::default();
+        assert_eq!(
+            rules.action(Path::new("dist/app.js"), false),
+            PathAction::Keep
+        );
+    }
+
+    #[test]
+    fn test_rtkignore_excludes_nested_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(RTKIGNORE_FILE);
+        fs::write(&file, "generated/\n*.snap\n").unwrap();
+        let rules = PathRules::from_parts(dir.path(), &[file], &[], &[]);
+
+        assert_eq!(
+            rules.action(Path::new("generated/api/client.ts"), false),
+            PathAction::Exclude
+        );
+        assert_eq!(
+            rules.action(Path::new("generated"), true),
+            PathAction::Exclude
+        );
+        assert_eq!(
+            rules.action(Path::new("tests/__snapshots__/a.snap"), false),
+            PathAction::Exclude
+        );
+        assert_eq!(
+            rules.action(Path::new("src/main.rs"), false),
+            PathAction::Keep
+        );
+    }
+
+    #[test]
+    fn test_deeper_rtkignore_can_reinclude() {
+        let dir = tempfile::tempdir().unwrap();
+        let sub = dir.path().join("pkg");
+        fs::create_dir_all(&sub).unwrap();
+        let root_file = dir.path().join(RTKIGNORE_FILE);
+        let sub_file = sub.join(RTKIGNORE_FILE);
+        fs::write(&root_file, "*.json\n").unwrap();
+        fs::write(&sub_file, "!package.json\n").unwrap();
+        let rules = PathRules::from_parts(&sub, &[sub_file, root_file], &[], &[]);
+
+        assert_eq!(
+            rules.action(Path::new("package.json"), false),
+            PathAction::Keep
+        );
+        assert_eq!(
+            rules.action(Path::new("data.json"), false),
+            PathAction::Exclude
+        );
+    }
+
+    #[test]
+    fn test_config_exclude_and_summarize() {
+        let base = Path::new("/repo");
+        let rules = PathRules::from_parts(
+            base,
+            &[],
+            &patterns(&["vendor/"]),
+            &patterns(&["fixtures/", "*.min.js"]),
+        );
+
+        assert_eq!(
+            rules.action(Path::new("vendor/lib.go"), false),
+            PathAction::Exclude
+        );
+        assert_eq!(
+            rules.action(Path::new("tests/fixtures/big.json"), false),
+            PathAction::Summarize
+        );
+        assert_eq!(
+            rules.action(Path::new("web/app.min.js"), false),
+            PathAction::Summarize
+        );
+        assert_eq!(
+            rules.action(Path::new("src/lib.rs"), false),
+            PathAction::Keep
+        );
+    }
+
+    #[test]
+    fn test_paths_outside_root_are_kept() {
+        let rules = PathRules::from_parts(Path::new("/repo"), &[], &patterns(&["*.log"]), &[]);
+        assert_eq!(
+            rules.action(Path::new("/var/log/app.log"), false),
+            PathAction::Keep
+        );
+        assert_eq!(
+            rules.action(Path::new("/repo/app.log"), false),
+            PathAction::Exclude
+        );
+    }
+}
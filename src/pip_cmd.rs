@@ -1,4 +1,7 @@
+use crate::exit_code;
+use crate::install_cmd;
 use crate::tracking;
+use crate::utils::which;
 use anyhow::{Context, Result};
 use serde::Deserialize;
 use std::process::Command;
@@ -15,7 +18,7 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
     let timer = tracking::TimedExecution::start();
 
     // Auto-detect uv vs pip
-    let use_uv = which_command("uv").is_some();
+    let use_uv = which("uv").is_some();
     let base_cmd = if use_uv { "uv" } else { "pip" };
 
     if verbose > 0 && use_uv {
@@ -28,10 +31,17 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
     let (cmd_str, filtered) = match subcommand {
         "list" => run_list(base_cmd, &args[1..], verbose)?,
         "outdated" => run_outdated(base_cmd, &args[1..], verbose)?,
-        "install" | "uninstall" | "show" => {
-            // Passthrough for write operations
-            run_passthrough(base_cmd, args, verbose)?
+        "install" | "uninstall" => {
+            let args: Vec<String> = if use_uv {
+                std::iter::once("pip".to_string())
+                    .chain(args.iter().cloned())
+                    .collect()
+            } else {
+                args.to_vec()
+            };
+            return install_cmd::run(base_cmd, &args, verbose);
         }
+        "show" => run_passthrough(base_cmd, args, verbose)?,
         _ => {
             anyhow::bail!(
                 "rtk pip: unsupported subcommand '{}'\nSupported: list, outdated, install, uninstall, show",
@@ -74,12 +84,13 @@ fn run_list(base_cmd: &str, args: &[String], verbose: u8) -> Result<(String, Str
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
     let filtered = filter_pip_list(&stdout);
     println!("{}", filtered);
 
     if !output.status.success() {
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     Ok((raw, filtered))
@@ -109,12 +120,13 @@ fn run_outdated(base_cmd: &str, args: &[String], verbose: u8) -> Result<(String,
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
     let filtered = filter_pip_outdated(&stdout);
     println!("{}", filtered);
 
     if !output.status.success() {
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     Ok((raw, filtered))
@@ -142,29 +154,18 @@ fn run_passthrough(base_cmd: &str, args: &[String], verbose: u8) -> Result<(Stri
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
     print!("{}", stdout);
     eprint!("{}", stderr);
 
     if !output.status.success() {
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     Ok((raw.clone(), raw))
 }
 
-/// Check if a command exists in PATH
-fn which_command(cmd: &str) -> Option<String> {
-    Command::new("which")
-        .arg(cmd)
-        .output()
-        .ok()
-        .filter(|o| o.status.success())
-        .and_then(|o| String::from_utf8(o.stdout).ok())
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-}
-
 /// Filter pip list JSON output
 fn filter_pip_list(output: &str) -> String {
     let packages: Vec<Package> = match serde_json::from_str(output) {
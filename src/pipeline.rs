@@ -0,0 +1,275 @@
+//! User-defined filter pipelines: ordered regex rules from `[[pipelines.<cmd>]]`.
+//!
+//! Rules run on everything rtk prints for the command (before the output
+//! profile) and on the output used for token counting. `<cmd>` is the rtk
+//! subcommand (`git`, `cargo`, ...); rules under `"*"` apply to every command
+//! and run first. The command's rhai script, if any, runs after the rules
+//! (see script.rs).
+//!
+//! ```toml
+//! [[pipelines."*"]]
+//! replace = "[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}"
+//! with = "<uuid>"
+//!
+//! [[pipelines.cargo]]
+//! drop = "^\\s+Compiling "
+//! ```
+
+use crate::config::Config;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+/// One rule as written in config: exactly one of `drop` / `replace`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineRule {
+    /// Drop lines matching this regex
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drop: Option<String>,
+    /// Replace every match of this regex with `with`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replace: Option<String>,
+    /// Replacement text (`$1`, `${name}` capture references allowed)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub with: String,
+}
+
+enum Action {
+    Drop(Regex),
+    Replace(Regex, String),
+}
+
+struct Step {
+    action: Action,
+    /// Lines dropped or rewritten while printing (for `--explain`)
+    hits: AtomicUsize,
+}
+
+impl Step {
+    fn new(action: Action) -> Self {
+        Self {
+            action,
+            hits: AtomicUsize::new(0),
+        }
+    }
+
+    fn label(&self) -> String {
+        match &self.action {
+            Action::Drop(re) => format!("drop /{}/", re.as_str()),
+            Action::Replace(re, with) => format!("replace /{}/ -> \"{}\"", re.as_str(), with),
+        }
+    }
+}
+
+/// rtk subcommand whose rules apply, set by `init`
+static COMMAND: OnceLock<String> = OnceLock::new();
+static ACTIVE: OnceLock<Vec<Step>> = OnceLock::new();
+
+/// Select the rules for `command` (the rtk subcommand). Call once at startup;
+/// they are compiled on first use, so commands that print nothing don't pay
+/// for the regexes.
+pub fn init(command: &str) {
+    let _ = COMMAND.set(command.to_string());
+}
+
+/// The rtk subcommand set by [`init`] (also selects its script, see script.rs).
+pub fn command() -> Option<&'static str> {
+    COMMAND.get().map(String::as_str)
+}
+
+fn active() -> Option<&'static [Step]> {
+    let command = COMMAND.get()?;
+    let steps = ACTIVE.get_or_init(|| {
+        let pipelines = &Config::cached().pipelines;
+        let rules = pipelines
+            .get("*")
+            .into_iter()
+            .chain(pipelines.get(command))
+            .flatten();
+        let steps = compile(rules);
+        tracing::debug!(command, rules = steps.len(), "pipeline rules");
+        steps
+    });
+    Some(steps)
+}
+
+fn compile<'a>(rules: impl Iterator<Item = &'a PipelineRule>) -> Vec<Step> {
+    let mut steps = Vec::new();
+    for rule in rules {
+        let step = match (&rule.drop, &rule.replace) {
+            (Some(p), None) => Regex::new(p).map(Action::Drop),
+            (None, Some(p)) => Regex::new(p).map(|re| Action::Replace(re, rule.with.clone())),
+            _ => {
+                eprintln!("rtk: warning: pipeline rule needs exactly one of drop/replace");
+                continue;
+            }
+        };
+        match step {
+            Ok(action) => steps.push(Step::new(action)),
+            Err(e) => eprintln!("rtk: warning: invalid pipeline regex: {}", e),
+        }
+    }
+    steps
+}
+
+/// Apply the active pipeline, then the command's script, to printed output,
+/// counting rule hits. `None` when every line of non-empty text was dropped.
+pub fn apply(text: &str) -> Option<Cow<'_, str>> {
+    let text = match active() {
+        Some(steps) if !steps.is_empty() => run_steps(steps, text, true),
+        _ => Some(Cow::Borrowed(text)),
+    }?;
+    crate::script::apply(text, true)
+}
+
+/// Same as [`apply`] without counting hits or touching the script's state
+/// (token estimation re-runs it).
+pub fn preview(text: &str) -> Option<Cow<'_, str>> {
+    let text = match active() {
+        Some(steps) if !steps.is_empty() => run_steps(steps, text, false),
+        _ => Some(Cow::Borrowed(text)),
+    }?;
+    crate::script::apply(text, false)
+}
+
+/// `(rule, hits)` for each active rule, in order.
+pub fn stats() -> Vec<(String, usize)> {
+    active()
+        .into_iter()
+        .flatten()
+        .map(|step| (step.label(), step.hits.load(Ordering::Relaxed)))
+        .collect()
+}
+
+fn run_steps<'a>(steps: &[Step], text: &'a str, count: bool) -> Option<Cow<'a, str>> {
+    let hit = |step: &Step| {
+        if count {
+            step.hits.fetch_add(1, Ordering::Relaxed);
+        }
+    };
+    if text.is_empty() {
+        return Some(Cow::Borrowed(text));
+    }
+    let mut kept = Vec::new();
+    'lines: for line in text.lines() {
+        let mut line = Cow::Borrowed(line);
+        for step in steps {
+            match &step.action {
+                Action::Drop(re) if re.is_match(&line) => {
+                    hit(step);
+                    continue 'lines;
+                }
+                Action::Drop(_) => {}
+                Action::Replace(re, with) => {
+                    if let Cow::Owned(s) = re.replace_all(&line, with.as_str()) {
+                        hit(step);
+                        line = Cow::Owned(s);
+                    }
+                }
+            }
+        }
+        kept.push(line);
+    }
+    if kept.is_empty() {
+        None
+    } else {
+        Some(Cow::Owned(kept.join("\n")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drop(p: &str) -> PipelineRule {
+        PipelineRule {
+            drop: Some(p.into()),
+            replace: None,
+            with: String::new(),
+        }
+    }
+
+    fn replace(p: &str, with: &str) -> PipelineRule {
+        PipelineRule {
+            drop: None,
+            replace: Some(p.into()),
+            with: with.into(),
+        }
+    }
+
+    #[test]
+    fn test_drop_and_replace_in_order() {
+        let rules = [
+            drop("^DEBUG"),
+            replace(r"[0-9a-f]{8}-[0-9a-f-]{27}", "<uuid>"),
+        ];
+        let steps = compile(rules.iter());
+        let text = "DEBUG noise\nrequest 123e4567-e89b-12d3-a456-426614174000 ok\ndone";
+        assert_eq!(
+            run_steps(&steps, text, false).as_deref(),
+            Some("request <uuid> ok\ndone")
+        );
+    }
+
+    #[test]
+    fn test_replace_then_drop_sees_replaced_text() {
+        let rules = [replace(r"\d+ms", "<t>"), drop("^took <t>$")];
+        let steps = compile(rules.iter());
+        assert_eq!(
+            run_steps(&steps, "took 42ms\nok", false).as_deref(),
+            Some("ok")
+        );
+    }
+
+    #[test]
+    fn test_all_dropped_is_none_but_blank_is_kept() {
+        let steps = compile([drop(".")].iter());
+        assert_eq!(run_steps(&steps, "abc", false), None);
+        assert_eq!(run_steps(&steps, "", false).as_deref(), Some(""));
+    }
+
+    #[test]
+    fn test_hits_counted_only_when_asked() {
+        let rules = [drop("^DEBUG"), replace("ms", "")];
+        let steps = compile(rules.iter());
+        run_steps(&steps, "DEBUG a\nDEBUG b\n10ms\nok", false);
+        assert_eq!(steps[0].hits.load(Ordering::Relaxed), 0);
+        run_steps(&steps, "DEBUG a\nDEBUG b\n10ms\nok", true);
+        assert_eq!(steps[0].hits.load(Ordering::Relaxed), 2);
+        assert_eq!(steps[1].hits.load(Ordering::Relaxed), 1);
+        assert_eq!(steps[0].label(), "drop /^DEBUG/");
+    }
+
+    #[test]
+    fn test_capture_references() {
+        let steps = compile([replace(r"v(\d+)\.\d+\.\d+", "v$1.x")].iter());
+        assert_eq!(
+            run_steps(&steps, "serde v1.0.210", false).as_deref(),
+            Some("serde v1.x")
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules_are_skipped() {
+        let both = PipelineRule {
+            drop: Some("a".into()),
+            replace: Some("b".into()),
+            with: String::new(),
+        };
+        let steps = compile([both, drop("(unclosed")].iter());
+        assert!(steps.is_empty());
+    }
+
+    #[test]
+    fn test_rules_parse_from_toml() {
+        let config: Config = toml::from_str(
+            "[[pipelines.cargo]]\ndrop = \"^\\\\s+Compiling \"\n\n[[pipelines.\"*\"]]\nreplace = \"x\"\nwith = \"y\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.pipelines["cargo"].len(), 1);
+        assert_eq!(config.pipelines["*"][0].with, "y");
+    }
+}
@@ -0,0 +1,160 @@
+//! `rtk --plan <command>`: show how rtk would handle a command line without
+//! running it — hook rewrite, wrapper, filters and tracking.
+
+use crate::discover::registry::{classify_command, Classification};
+use crate::path_rules::PathRules;
+use crate::{pipeline, plugin, profile, tracking};
+use anyhow::Result;
+use clap::{ArgMatches, Command};
+
+/// Subcommands that apply `.rtkignore` / `filters.*_paths` rules.
+const PATH_RULE_COMMANDS: &[&str] = &["ls", "find", "grep", "read"];
+
+pub fn run(cli: &Command, matches: &ArgMatches, raw: bool, explain: bool) -> Result<()> {
+    let (path, about) = resolve_wrapper(cli, matches);
+    let Some(top) = path.first().cloned() else {
+        anyhow::bail!("--plan needs a command, e.g. rtk --plan git status");
+    };
+    let args = std::env::args_os()
+        .skip(1)
+        .map(|a| a.to_string_lossy().into_owned());
+    let words = command_words(args, &top);
+    let command_line = words.join(" ");
+
+    println!("Command:  {}", command_line);
+    println!("Hook:     {}", describe_hook(&command_line));
+
+    let is_plugin = about.is_none() && !cli.get_subcommands().any(|s| s.get_name() == top);
+    if is_plugin {
+        match plugin::resolve_summarizer(&top) {
+            Some(path) => println!("Wrapper:  plugin {} ({})", top, path.display()),
+            None => println!(
+                "Wrapper:  none (no builtin, no [plugins.{}], no rtk-{} on PATH)",
+                top, top
+            ),
+        }
+    } else {
+        println!(
+            "Wrapper:  rtk {}{}",
+            path.join(" "),
+            about.map(|a| format!(" - {}", a)).unwrap_or_default()
+        );
+    }
+
+    println!("Filters:");
+    let rules = pipeline::stats();
+    if rules.is_empty() {
+        println!("  pipeline:   (none for '{}')", top);
+    }
+    for (rule, _) in &rules {
+        println!("  pipeline:   {}", rule);
+    }
+    if PATH_RULE_COMMANDS.contains(&top.as_str()) {
+        let rules = PathRules::load();
+        println!(
+            "  path rules: {}",
+            rules.summary().unwrap_or_else(|| "(none)".to_string())
+        );
+    }
+    println!("  profile:    {:?}", profile::active());
+    if raw {
+        println!("  --raw:      filtered output replaced by the unfiltered input");
+    }
+    if explain {
+        println!("  --explain:  compression report appended");
+    }
+
+    let rtk_cmd = format!("rtk {}", command_line);
+    let tracked = tracking::should_track(&command_line, &rtk_cmd);
+    println!(
+        "Tracking: {}",
+        if tracked {
+            "recorded"
+        } else {
+            "skipped (RTK_NO_TRACK, tracking.enabled or exclude_commands)"
+        }
+    );
+    Ok(())
+}
+
+/// Walk the matched subcommand chain; returns the path and the leaf's help text.
+fn resolve_wrapper(cli: &Command, matches: &ArgMatches) -> (Vec<String>, Option<String>) {
+    let mut path = Vec::new();
+    let mut about = None;
+    let mut cmd = Some(cli);
+    let mut m = matches;
+    while let Some((name, sub)) = m.subcommand() {
+        path.push(name.to_string());
+        cmd = cmd.and_then(|c| c.find_subcommand(name));
+        about = cmd.and_then(|c| c.get_about()).map(|a| a.to_string());
+        m = sub;
+    }
+    (path, about)
+}
+
+/// Words of the planned command: everything from the first subcommand word on
+/// (global flags like `--plan`/`--profile agent` come before it).
+fn command_words(args: impl Iterator<Item = String>, first: &str) -> Vec<String> {
+    args.skip_while(|a| a != first).collect()
+}
+
+fn describe_hook(command_line: &str) -> String {
+    match classify_command(command_line) {
+        Classification::Supported {
+            rtk_equivalent,
+            category,
+            estimated_savings_pct,
+            ..
+        } => format!(
+            "rewritten to `{}` ({}, ~{:.0}% savings)",
+            rtk_equivalent, category, estimated_savings_pct
+        ),
+        Classification::Unsupported { base_command } => {
+            format!("not rewritten ('{}' has no rtk rule)", base_command)
+        }
+        Classification::Ignored => "ignored (shell builtin or no-op)".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Arg, ArgAction};
+
+    fn words<'a>(args: &'a [&'a str]) -> impl Iterator<Item = String> + 'a {
+        args.iter().map(|s| s.to_string())
+    }
+
+    #[test]
+    fn test_command_words_skip_global_flags() {
+        let got = command_words(
+            words(&["--profile", "agent", "--plan", "git", "status", "-s"]),
+            "git",
+        );
+        assert_eq!(got, vec!["git", "status", "-s"]);
+    }
+
+    #[test]
+    fn test_resolve_wrapper_nested() {
+        let cli = Command::new("rtk")
+            .arg(Arg::new("plan").long("plan").action(ArgAction::SetTrue))
+            .subcommand(
+                Command::new("git")
+                    .about("Git commands")
+                    .subcommand(Command::new("status").about("Compact status")),
+            );
+        let matches = cli
+            .clone()
+            .get_matches_from(["rtk", "--plan", "git", "status"]);
+        let (path, about) = resolve_wrapper(&cli, &matches);
+        assert_eq!(path, vec!["git", "status"]);
+        assert_eq!(about.as_deref(), Some("Compact status"));
+    }
+
+    #[test]
+    fn test_describe_hook() {
+        assert!(describe_hook("git status").starts_with("rewritten to `rtk git`"));
+        assert!(describe_hook("terraform plan").starts_with("not rewritten"));
+        assert!(describe_hook("cd").starts_with("ignored"));
+    }
+}
@@ -0,0 +1,248 @@
+//! External summarizer plugins for commands rtk has no builtin handler for.
+//!
+//! `rtk foo args...` runs `foo args...`, then pipes the captured output to a
+//! summarizer: `[plugins.foo] summarizer = "..."` in config, else `rtk-foo`
+//! on PATH. Protocol (one JSON document each way):
+//!
+//! ```text
+//! stdin:  {"version":1,"command":"foo","args":[..],"exit_code":0,"stdout":"..","stderr":".."}
+//! stdout: {"output":"compact text","exit_code":0}   // exit_code optional
+//! ```
+//!
+//! Savings are tracked as `rtk foo ...` like any builtin.
+
+use crate::tracking;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// `[plugins.<name>]` config entry.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginConfig {
+    /// Summarizer executable (default: `rtk-<name>` on PATH)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summarizer: Option<PathBuf>,
+    /// Wrapped program (default: `<name>`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+#[derive(Serialize)]
+struct PluginRequest<'a> {
+    version: u32,
+    command: &'a str,
+    args: &'a [String],
+    exit_code: Option<i32>,
+    stdout: &'a str,
+    stderr: &'a str,
+}
+
+#[derive(Deserialize)]
+struct PluginResponse {
+    output: String,
+    #[serde(default)]
+    exit_code: Option<i32>,
+}
+
+/// Handle `rtk <name> args...` for a name clap did not recognise.
+pub fn run(argv: &[OsString], verbose: u8) -> Result<()> {
+    let name = argv
+        .first()
+        .map(|n| n.to_string_lossy().into_owned())
+        .context("missing command")?;
+    let args: Vec<String> = argv[1..]
+        .iter()
+        .map(|a| a.to_string_lossy().into_owned())
+        .collect();
+
+    let entry = config_entry(&name);
+    let summarizer = resolve_summarizer(&name).with_context(|| {
+        format!(
+            "unknown command '{}': no builtin, no [plugins.{}] entry and no rtk-{} on PATH",
+            name, name, name
+        )
+    })?;
+    let program = entry.command.unwrap_or_else(|| name.clone());
+
+    if verbose > 0 {
+        eprintln!("Plugin: {} | {}", program, summarizer.display());
+    }
+
+    let timer = tracking::TimedExecution::start();
+    let output = Command::new(&program)
+        .args(&args)
+        .output()
+        .with_context(|| format!("Failed to run {}", program))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let raw = format!("{}{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
+
+    let request = PluginRequest {
+        version: PROTOCOL_VERSION,
+        command: &program,
+        args: &args,
+        exit_code: output.status.code(),
+        stdout: &stdout,
+        stderr: &stderr,
+    };
+
+    let original_cmd = format!("{} {}", program, args.join(" "));
+    let rtk_cmd = format!("rtk {} {}", name, args.join(" "));
+    let mut exit_code = output.status.code().unwrap_or(1);
+
+    match summarize(&summarizer, &request) {
+        Ok(response) => {
+            print!("{}", response.output);
+            if !response.output.ends_with('\n') {
+                println!();
+            }
+            exit_code = response.exit_code.unwrap_or(exit_code);
+            timer.with_exit_code(output.status).track(
+                &original_cmd,
+                &rtk_cmd,
+                &raw,
+                &response.output,
+            );
+        }
+        Err(e) => {
+            // Never lose the command's output because a plugin misbehaved
+            eprintln!("rtk: plugin {} failed: {:#}", summarizer.display(), e);
+            print!("{}", stdout);
+            eprint!("{}", stderr);
+            timer
+                .with_exit_code(output.status)
+                .track(&original_cmd, &rtk_cmd, &raw, &raw);
+        }
+    }
+
+    // The summarizer may override the command's exit code
+    crate::exit_code::set(exit_code);
+    Ok(())
+}
+
+fn config_entry(name: &str) -> PluginConfig {
+    crate::config::Config::cached()
+        .plugins
+        .get(name)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Summarizer for `rtk <name>`: the config entry, else `rtk-<name>` on PATH.
+pub fn resolve_summarizer(name: &str) -> Option<PathBuf> {
+    config_entry(name)
+        .summarizer
+        .or_else(|| find_in_path(&format!("rtk-{}", name)))
+}
+
+fn summarize(summarizer: &Path, request: &PluginRequest) -> Result<PluginResponse> {
+    let mut child = Command::new(summarizer)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .context("spawn failed")?;
+
+    let payload = serde_json::to_vec(request)?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(&payload).context("writing request")?;
+    }
+    let result = child.wait_with_output().context("waiting for plugin")?;
+    if !result.status.success() {
+        anyhow::bail!("exited with {}", result.status);
+    }
+    parse_response(&result.stdout)
+}
+
+fn parse_response(bytes: &[u8]) -> Result<PluginResponse> {
+    serde_json::from_slice(bytes).context("invalid JSON response (expected {\"output\": ...})")
+}
+
+/// First executable named `name` on PATH.
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_shape() {
+        let args = vec!["--fast".to_string()];
+        let request = PluginRequest {
+            version: PROTOCOL_VERSION,
+            command: "terraform",
+            args: &args,
+            exit_code: Some(2),
+            stdout: "plan",
+            stderr: "",
+        };
+        let json: serde_json::Value = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["version"], 1);
+        assert_eq!(json["command"], "terraform");
+        assert_eq!(json["args"][0], "--fast");
+        assert_eq!(json["exit_code"], 2);
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let r = parse_response(br#"{"output":"3 changes"}"#).unwrap();
+        assert_eq!(r.output, "3 changes");
+        assert_eq!(r.exit_code, None);
+
+        let r = parse_response(br#"{"output":"","exit_code":1}"#).unwrap();
+        assert_eq!(r.exit_code, Some(1));
+
+        assert!(parse_response(b"not json").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_summarize_round_trip() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("rtk-echo");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\ncat >/dev/null\necho '{\"output\":\"summarized\",\"exit_code\":0}'\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let request = PluginRequest {
+            version: PROTOCOL_VERSION,
+            command: "echo",
+            args: &[],
+            exit_code: Some(0),
+            stdout: "hello",
+            stderr: "",
+        };
+        let response = summarize(&script, &request).unwrap();
+        assert_eq!(response.output, "summarized");
+        assert!(is_executable(&script));
+    }
+}
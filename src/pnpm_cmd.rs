@@ -1,3 +1,5 @@
+use crate::exit_code;
+use crate::install_cmd;
 use crate::tracking;
 use anyhow::{Context, Result};
 use serde::Deserialize;
@@ -337,7 +339,7 @@ fn run_list(depth: usize, args: &[String], verbose: u8) -> Result<()> {
 
     println!("{}", filtered);
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("pnpm list --depth={}", depth),
         &format!("rtk pnpm list --depth={}", depth),
         &stdout,
@@ -393,14 +395,17 @@ fn run_outdated(args: &[String], verbose: u8) -> Result<()> {
         println!("{}", filtered);
     }
 
-    timer.track("pnpm outdated", "rtk pnpm outdated", &combined, &filtered);
+    timer.with_exit_code(output.status).track(
+        "pnpm outdated",
+        "rtk pnpm outdated",
+        &combined,
+        &filtered,
+    );
 
     Ok(())
 }
 
 fn run_install(packages: &[String], args: &[String], verbose: u8) -> Result<()> {
-    let timer = tracking::TimedExecution::start();
-
     // Validate package names to prevent command injection
     for pkg in packages {
         if !is_valid_package_name(pkg) {
@@ -411,81 +416,11 @@ fn run_install(packages: &[String], args: &[String], verbose: u8) -> Result<()>
         }
     }
 
-    let mut cmd = Command::new("pnpm");
-    cmd.arg("install");
-
-    for pkg in packages {
-        cmd.arg(pkg);
-    }
-
-    for arg in args {
-        cmd.arg(arg);
-    }
-
-    if verbose > 0 {
-        eprintln!("pnpm install running...");
-    }
-
-    let output = cmd.output().context("Failed to run pnpm install")?;
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-
-    if !output.status.success() {
-        anyhow::bail!("pnpm install failed: {}", stderr);
-    }
-
-    let combined = format!("{}{}", stdout, stderr);
-    let filtered = filter_pnpm_install(&combined);
-
-    println!("{}", filtered);
-
-    timer.track(
-        &format!("pnpm install {}", packages.join(" ")),
-        &format!("rtk pnpm install {}", packages.join(" ")),
-        &combined,
-        &filtered,
-    );
-
-    Ok(())
-}
-
-/// Filter pnpm install output - remove progress bars, keep summary
-fn filter_pnpm_install(output: &str) -> String {
-    let mut result = Vec::new();
-    let mut saw_progress = false;
-
-    for line in output.lines() {
-        // Skip progress bars
-        if line.contains("Progress") || line.contains('│') || line.contains('%') {
-            saw_progress = true;
-            continue;
-        }
-
-        if saw_progress && line.trim().is_empty() {
-            continue;
-        }
-
-        // Keep error lines
-        if line.contains("ERR") || line.contains("error") || line.contains("ERROR") {
-            result.push(line.to_string());
-            continue;
-        }
-
-        // Keep summary lines
-        if line.contains("packages in")
-            || line.contains("dependencies")
-            || line.starts_with('+')
-            || line.starts_with('-')
-        {
-            result.push(line.trim().to_string());
-        }
-    }
-
-    if result.is_empty() {
-        "ok ✓".to_string()
-    } else {
-        result.join("\n")
-    }
+    let args: Vec<String> = std::iter::once("install".to_string())
+        .chain(packages.iter().cloned())
+        .chain(args.iter().cloned())
+        .collect();
+    install_cmd::run("pnpm", &args, verbose)
 }
 
 /// Runs an unsupported pnpm subcommand by passing it through directly
@@ -501,13 +436,13 @@ pub fn run_passthrough(args: &[OsString], verbose: u8) -> Result<()> {
         .context("Failed to run pnpm")?;
 
     let args_str = tracking::args_display(args);
-    timer.track_passthrough(
+    timer.with_exit_code(status).track_passthrough(
         &format!("pnpm {}", args_str),
         &format!("rtk pnpm {} (passthrough)", args_str),
     );
 
     if !status.success() {
-        std::process::exit(status.code().unwrap_or(1));
+        exit_code::exit_with(status);
     }
     Ok(())
 }
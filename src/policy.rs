@@ -0,0 +1,340 @@
+//! Safety policy: commands rtk must never intercept or summarize.
+//!
+//! Built-in rules (`policy.builtin`, on by default) protect commands that
+//! delete or write files, need a terminal, or escalate privileges. Users add
+//! their own in `policy.never`: globs (`*` wildcard) or `re:` regexes, each
+//! matched against every command of a line (split on `&&`, `||`, `;`, `|`
+//! and `&`) and against the whole line.
+//!
+//! A protected command is never rewritten by the agent hook (the whole line
+//! is left as written), and `rtk x` / `rtk err` / `rtk test` run it with the
+//! terminal attached and output untouched.
+
+use crate::config::{Config, PolicyConfig};
+use crate::exit_code;
+use crate::tracking::{self, glob_match};
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::process::Command;
+
+/// Built-in protected programs, by reason.
+const BUILTIN: &[(&str, &[&str])] = &[
+    (
+        "deletes or moves files",
+        &[
+            "rm", "rmdir", "unlink", "mv", "shred", "dd", "truncate", "mkfs",
+        ],
+    ),
+    (
+        "writes files",
+        &[
+            "tee", "cp", "install", "touch", "mkdir", "ln", "chmod", "chown", "rsync",
+        ],
+    ),
+    (
+        "needs a terminal",
+        &[
+            "vi",
+            "vim",
+            "nvim",
+            "nano",
+            "emacs",
+            "less",
+            "more",
+            "man",
+            "top",
+            "htop",
+            "watch",
+            "ssh",
+            "sftp",
+            "ftp",
+            "telnet",
+            "tmux",
+            "screen",
+            "psql",
+            "mysql",
+            "sqlite3",
+            "redis-cli",
+            "fzf",
+        ],
+    ),
+    ("changes privileges", &["sudo", "su", "doas"]),
+];
+
+/// Built-in rules that depend on arguments.
+const BUILTIN_PATTERNS: &[(&str, &str)] = &[
+    (
+        "edits files in place",
+        r"^(sed|perl)(\s.*)?\s(-[a-zA-Z]*i|--in-place)",
+    ),
+    (
+        "deletes or runs commands",
+        r"^find\s.*\s-(delete|exec|execdir|ok)\b",
+    ),
+    (
+        "deletes or runs commands",
+        r"^fd\s(.*\s)?(-x|-X|--exec|--exec-batch)(\s|=|$)",
+    ),
+    (
+        "needs a terminal",
+        r"^git\s+(rebase|add|checkout|reset|stash)\b.*\s(-i|--interactive|-p|--patch)\b",
+    ),
+    ("needs a terminal", r"^(python3?|node|irb|ghci|R)$"),
+];
+
+/// Programs that run the command given in their arguments.
+const LAUNCHERS: &[&str] = &["env", "xargs", "nohup", "time", "nice", "command", "exec"];
+
+lazy_static! {
+    static ref SEPARATOR: Regex = Regex::new(r"&&|\|\||[;|&\n]").unwrap();
+    static ref ENV_ASSIGN: Regex = Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*=").unwrap();
+    static ref PATTERNS: Vec<(&'static str, Regex)> = BUILTIN_PATTERNS
+        .iter()
+        .map(|(reason, re)| (*reason, Regex::new(re).unwrap()))
+        .collect();
+}
+
+/// Why a command is protected.
+#[derive(Debug, PartialEq)]
+pub struct Protected {
+    /// The command of the line that matched
+    pub segment: String,
+    pub rule: String,
+}
+
+enum Rule {
+    Glob(String),
+    Regex(String, Regex),
+}
+
+pub struct Policy {
+    builtin: bool,
+    rules: Vec<Rule>,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self::from_config(&PolicyConfig::default())
+    }
+}
+
+impl Policy {
+    /// Policy from config; invalid `re:` rules are reported and skipped.
+    pub fn from_config(config: &PolicyConfig) -> Self {
+        let rules = config
+            .never
+            .iter()
+            .filter_map(|rule| match rule.strip_prefix("re:") {
+                Some(re) => match Regex::new(re) {
+                    Ok(regex) => Some(Rule::Regex(rule.clone(), regex)),
+                    Err(e) => {
+                        eprintln!("rtk: ignoring invalid policy rule '{}': {}", rule, e);
+                        None
+                    }
+                },
+                None => Some(Rule::Glob(rule.clone())),
+            })
+            .collect();
+        Self {
+            builtin: config.builtin,
+            rules,
+        }
+    }
+
+    pub fn load() -> Self {
+        Self::from_config(&Config::cached().policy)
+    }
+
+    /// The first protected command in `line`, if any.
+    pub fn check(&self, line: &str) -> Option<Protected> {
+        let line = line.trim();
+        if let Some(rule) = self.user_rule(line) {
+            return Some(Protected {
+                segment: line.to_string(),
+                rule,
+            });
+        }
+        SEPARATOR
+            .split(line)
+            .map(str::trim)
+            .filter(|segment| !segment.is_empty())
+            .find_map(|segment| {
+                let rule = self
+                    .user_rule(segment)
+                    .or_else(|| self.builtin_rule(segment))?;
+                Some(Protected {
+                    segment: segment.to_string(),
+                    rule,
+                })
+            })
+    }
+
+    fn user_rule(&self, command: &str) -> Option<String> {
+        self.rules.iter().find_map(|rule| match rule {
+            Rule::Glob(glob) => glob_match(glob, command).then(|| glob.clone()),
+            Rule::Regex(source, regex) => regex.is_match(command).then(|| source.clone()),
+        })
+    }
+
+    fn builtin_rule(&self, segment: &str) -> Option<String> {
+        if !self.builtin {
+            return None;
+        }
+        let words: Vec<&str> = segment
+            .split_whitespace()
+            .skip_while(|w| ENV_ASSIGN.is_match(w))
+            .collect();
+        // `sudo`, `xargs rm`, `env X=1 rm`: look through launchers
+        let mut start = 0;
+        while let Some(word) = words.get(start) {
+            let program = word.rsplit('/').next().unwrap_or(word);
+            if let Some((reason, _)) = BUILTIN.iter().find(|(_, p)| p.contains(&program)) {
+                return Some(format!("{} (built-in: {})", program, reason));
+            }
+            let command = words[start..].join(" ");
+            if let Some((reason, _)) = PATTERNS.iter().find(|(_, re)| re.is_match(&command)) {
+                return Some(format!("{} (built-in: {})", program, reason));
+            }
+            if !LAUNCHERS.contains(&program) {
+                return None;
+            }
+            start += 1;
+            while words
+                .get(start)
+                .is_some_and(|w| w.starts_with('-') || ENV_ASSIGN.is_match(w))
+            {
+                start += 1;
+            }
+        }
+        None
+    }
+
+    fn describe(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if self.builtin {
+            for (reason, programs) in BUILTIN {
+                lines.push(format!("built-in  {}: {}", reason, programs.join(", ")));
+            }
+            lines.push(
+                "built-in  sed -i / perl -i, find -delete / -exec, fd -x, git -i / -p, bare REPLs"
+                    .into(),
+            );
+        }
+        for rule in &self.rules {
+            match rule {
+                Rule::Glob(glob) => lines.push(format!("never     {}", glob)),
+                Rule::Regex(source, _) => lines.push(format!("never     {}", source)),
+            }
+        }
+        lines
+    }
+}
+
+/// Run a protected command as-is: terminal attached, nothing filtered.
+pub fn run_unfiltered(
+    cmd: &mut Command,
+    original: &str,
+    rtk_cmd: &str,
+    protected: &Protected,
+    verbose: u8,
+) -> Result<()> {
+    if verbose > 0 {
+        eprintln!(
+            "Protected by policy ({}), running without filtering",
+            protected.rule
+        );
+    }
+    let timer = tracking::TimedExecution::start();
+    let status = cmd
+        .status()
+        .with_context(|| format!("Failed to run {}", original))?;
+    timer
+        .with_exit_code(status)
+        .track_passthrough(original, &format!("{} (protected)", rtk_cmd));
+    exit_code::record(status);
+    Ok(())
+}
+
+/// `rtk policy [command]`: list the rules, or check a command (exit 1 when
+/// protected).
+pub fn run(command: &[String]) -> Result<()> {
+    let policy = Policy::load();
+    if command.is_empty() {
+        for line in policy.describe() {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+    match policy.check(&command.join(" ")) {
+        Some(protected) => {
+            println!("protected: {}", protected.segment);
+            println!("rule: {}", protected.rule);
+            exit_code::set(1);
+        }
+        None => println!("not protected"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(line: &str) -> Option<String> {
+        Policy::default().check(line).map(|p| p.rule)
+    }
+
+    #[test]
+    fn test_builtin_programs_and_launchers() {
+        assert_eq!(
+            rule("rm -rf build").as_deref(),
+            Some("rm (built-in: deletes or moves files)")
+        );
+        assert!(rule("git status && /bin/rm x").is_some());
+        assert!(rule("find . -name '*.o' | xargs -0 rm").is_some());
+        assert!(rule("sudo apt install jq").is_some());
+        assert!(rule("cargo build | tee build.log").is_some());
+        assert!(rule("vim src/main.rs").is_some());
+        assert_eq!(rule("git status"), None);
+        assert_eq!(rule("cargo test 2>&1"), None);
+        assert_eq!(rule("grep -r rmdir src"), None);
+    }
+
+    #[test]
+    fn test_builtin_patterns() {
+        assert!(rule("sed -i 's/a/b/' f.txt").is_some());
+        assert!(rule("find . -name '*.tmp' -delete").is_some());
+        assert!(rule("fd -e tmp -x rm").is_some());
+        assert!(rule("git rebase -i HEAD~3").is_some());
+        assert!(rule("git add -p").is_some());
+        assert!(rule("python3").is_some());
+        assert_eq!(rule("sed 's/a/b/' f.txt"), None);
+        assert_eq!(rule("python3 -m pytest"), None);
+        assert_eq!(rule("find . -name '*.rs'"), None);
+        assert_eq!(rule("fd -e rs main src"), None);
+    }
+
+    #[test]
+    fn test_user_rules() {
+        let policy = Policy::from_config(&PolicyConfig {
+            builtin: false,
+            never: vec![
+                "terraform apply*".into(),
+                "re:^kubectl (delete|apply)\\b".into(),
+                "re:(".into(),
+            ],
+        });
+        let check = |line: &str| policy.check(line).map(|p| (p.segment, p.rule));
+        assert_eq!(
+            check("cd infra && terraform apply -auto-approve"),
+            Some((
+                "terraform apply -auto-approve".into(),
+                "terraform apply*".into()
+            ))
+        );
+        assert!(check("kubectl delete pod x").is_some());
+        assert_eq!(check("kubectl get pods"), None);
+        assert_eq!(check("rm -rf build"), None);
+    }
+}
@@ -0,0 +1,352 @@
+//! `rtk ports`: what's listening on which port, and which process owns it.
+//!
+//! Reads `ss` on Linux, else `lsof`, and prints one row per port and
+//! process instead of a socket dump. `rtk ports 3000` answers a port
+//! conflict directly; `--all` adds established connections per process.
+
+use crate::tracking;
+use crate::utils::which;
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::process::Command;
+
+/// Remote endpoints listed per process with `--all`.
+const MAX_REMOTES: usize = 5;
+
+lazy_static! {
+    static ref SS_USER: Regex = Regex::new(r#"\("([^"]+)",pid=(\d+)"#).unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Socket {
+    proto: String,
+    /// `LISTEN`, `ESTAB`, `UNCONN`, ...
+    state: String,
+    local_addr: String,
+    local_port: u16,
+    /// `host:port` of the peer, `None` for listening sockets
+    remote: Option<String>,
+    /// `name (pid)`, `None` when the owner isn't visible (another user's)
+    process: Option<String>,
+}
+
+impl Socket {
+    fn listening(&self) -> bool {
+        self.remote.is_none()
+    }
+}
+
+/// Host and port of `addr:port`, `[v6]:port` or `addr%iface:port`.
+fn split_endpoint(endpoint: &str) -> Option<(String, Option<u16>)> {
+    let (host, port) = endpoint.rsplit_once(':')?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let host = host.split('%').next().unwrap_or(host);
+    Some((host.to_string(), port.parse().ok()))
+}
+
+/// Wildcard addresses shown as `*`.
+fn display_addr(addr: &str) -> &str {
+    match addr {
+        "0.0.0.0" | "::" | "*" | "" => "*",
+        addr => addr,
+    }
+}
+
+/// `ss -Htunlp` (or `-Htunap`) rows.
+fn parse_ss(output: &str) -> Vec<Socket> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 6 {
+                return None;
+            }
+            let (local_addr, local_port) = split_endpoint(fields[4])?;
+            let (peer_addr, peer_port) = split_endpoint(fields[5])?;
+            let process = SS_USER
+                .captures(line)
+                .map(|caps| format!("{} ({})", &caps[1], &caps[2]));
+            let state = fields[1].to_string();
+            let listening = state == "LISTEN" || state == "UNCONN";
+            Some(Socket {
+                proto: fields[0].to_string(),
+                state,
+                local_addr,
+                local_port: local_port?,
+                remote: match peer_port {
+                    Some(port) if !listening => Some(format!("{}:{}", peer_addr, port)),
+                    _ => None,
+                },
+                process,
+            })
+        })
+        .collect()
+}
+
+/// `lsof -nP -i...` rows.
+fn parse_lsof(output: &str) -> Vec<Socket> {
+    output
+        .lines()
+        .skip_while(|l| l.starts_with("COMMAND"))
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 9 {
+                return None;
+            }
+            let name = fields[8..].join(" ");
+            let (endpoints, state) = match name.split_once(" (") {
+                Some((endpoints, state)) => (endpoints, state.trim_end_matches(')')),
+                None => (name.as_str(), ""),
+            };
+            let (local, remote) = match endpoints.split_once("->") {
+                Some((local, remote)) => (local, Some(remote)),
+                None => (endpoints, None),
+            };
+            let (local_addr, local_port) = split_endpoint(local)?;
+            let remote = remote
+                .and_then(split_endpoint)
+                .and_then(|(addr, port)| port.map(|p| format!("{}:{}", addr, p)));
+            Some(Socket {
+                proto: fields[7].to_lowercase(),
+                state: match (state, &remote) {
+                    ("", None) => "UNCONN".to_string(),
+                    ("ESTABLISHED", _) => "ESTAB".to_string(),
+                    (state, _) => state.to_string(),
+                },
+                local_addr,
+                local_port: local_port?,
+                remote,
+                process: Some(format!("{} ({})", fields[0], fields[1])),
+            })
+        })
+        .collect()
+}
+
+fn format_report(sockets: &[Socket], port: Option<u16>, all: bool) -> String {
+    let matches_port = |s: &&Socket| {
+        port.is_none_or(|p| {
+            s.local_port == p
+                || s.remote
+                    .as_deref()
+                    .is_some_and(|r| r.ends_with(&format!(":{}", p)))
+        })
+    };
+
+    // (port, proto, process) -> addresses
+    let mut listeners: BTreeMap<(u16, &str, &str), BTreeSet<&str>> = BTreeMap::new();
+    for socket in sockets
+        .iter()
+        .filter(|s| s.listening())
+        .filter(matches_port)
+    {
+        listeners
+            .entry((
+                socket.local_port,
+                &socket.proto,
+                socket.process.as_deref().unwrap_or("?"),
+            ))
+            .or_default()
+            .insert(display_addr(&socket.local_addr));
+    }
+
+    let mut out = Vec::new();
+    if listeners.is_empty() {
+        out.push(match port {
+            Some(p) => format!("✓ nothing listening on port {}", p),
+            None => "✓ nothing listening".to_string(),
+        });
+    } else {
+        let protos: BTreeSet<&str> = listeners.keys().map(|(_, proto, _)| *proto).collect();
+        let counts: Vec<String> = protos
+            .iter()
+            .map(|proto| {
+                let n = listeners.keys().filter(|(_, p, _)| p == proto).count();
+                format!("{} {}", n, proto)
+            })
+            .collect();
+        out.push(format!(
+            "🔌 {} listening ({})",
+            listeners.len(),
+            counts.join(", ")
+        ));
+
+        let rows: Vec<(String, &str, String, &str)> = listeners
+            .iter()
+            .map(|((port, proto, process), addrs)| {
+                // `*` covers the specific addresses on the same port
+                let addrs = if addrs.contains("*") {
+                    "*".to_string()
+                } else {
+                    addrs.iter().copied().collect::<Vec<_>>().join(",")
+                };
+                (port.to_string(), *proto, addrs, *process)
+            })
+            .collect();
+        let port_w = rows.iter().map(|r| r.0.len()).max().unwrap_or(4).max(4);
+        let addr_w = rows.iter().map(|r| r.2.len()).max().unwrap_or(4).max(4);
+        out.push(format!(
+            "{:<port_w$}  PROTO  {:<addr_w$}  PROCESS",
+            "PORT", "ADDR"
+        ));
+        for (port, proto, addrs, process) in &rows {
+            out.push(format!(
+                "{:<port_w$}  {:<5}  {:<addr_w$}  {}",
+                port, proto, addrs, process
+            ));
+        }
+    }
+
+    if all {
+        // process -> remote endpoint -> count
+        let mut connections: BTreeMap<&str, BTreeMap<&str, usize>> = BTreeMap::new();
+        for socket in sockets
+            .iter()
+            .filter(|s| !s.listening())
+            .filter(matches_port)
+        {
+            *connections
+                .entry(socket.process.as_deref().unwrap_or("?"))
+                .or_default()
+                .entry(socket.remote.as_deref().unwrap_or("?"))
+                .or_default() += 1;
+        }
+        let total: usize = connections.values().flat_map(|r| r.values()).sum();
+        out.push(String::new());
+        out.push(format!("🔗 {} connections", total));
+        for (process, remotes) in &connections {
+            let count: usize = remotes.values().sum();
+            let mut remotes: Vec<_> = remotes.iter().collect();
+            remotes.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+            let mut list: Vec<String> = remotes
+                .iter()
+                .take(MAX_REMOTES)
+                .map(|(remote, n)| match n {
+                    1 => remote.to_string(),
+                    n => format!("{} ×{}", remote, n),
+                })
+                .collect();
+            if remotes.len() > MAX_REMOTES {
+                list.push(format!("+{} more", remotes.len() - MAX_REMOTES));
+            }
+            out.push(format!("  {}: {} → {}", process, count, list.join(", ")));
+        }
+    }
+    out.join("\n")
+}
+
+pub fn run(port: Option<u16>, all: bool, verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+
+    let use_ss = cfg!(target_os = "linux") && which("ss").is_some();
+    let (program, args): (&str, Vec<&str>) = match (use_ss, all) {
+        (true, false) => ("ss", vec!["-Htunlp"]),
+        (true, true) => ("ss", vec!["-Htunap"]),
+        (false, false) => ("lsof", vec!["-nP", "-iTCP", "-sTCP:LISTEN", "-iUDP"]),
+        (false, true) => ("lsof", vec!["-nP", "-i"]),
+    };
+    if verbose > 0 {
+        eprintln!("Running: {} {}", program, args.join(" "));
+    }
+    let output = Command::new(program)
+        .args(&args)
+        .output()
+        .with_context(|| format!("Failed to run {}. Is it installed?", program))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // lsof exits 1 when nothing matches
+    if !output.status.success() && !stdout.trim().is_empty() {
+        anyhow::bail!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let sockets = if use_ss {
+        parse_ss(&stdout)
+    } else {
+        parse_lsof(&stdout)
+    };
+    if verbose > 0 && sockets.iter().any(|s| s.process.is_none()) {
+        eprintln!("ports: some owners are hidden; run as root to see every process");
+    }
+    let filtered = format_report(&sockets, port, all);
+    println!("{}", filtered);
+
+    let mut rtk_cmd = "rtk ports".to_string();
+    if let Some(p) = port {
+        rtk_cmd.push_str(&format!(" {}", p));
+    }
+    if all {
+        rtk_cmd.push_str(" --all");
+    }
+    timer.track(
+        &format!("{} {}", program, args.join(" ")),
+        &rtk_cmd,
+        &stdout,
+        &filtered,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SS: &str = r#"tcp   LISTEN 0      511          0.0.0.0:80        0.0.0.0:*    users:(("nginx",pid=1234,fd=6),("nginx",pid=1235,fd=6))
+tcp   LISTEN 0      511             [::]:80           [::]:*    users:(("nginx",pid=1234,fd=7))
+tcp   LISTEN 0      128        127.0.0.1:5432      0.0.0.0:*
+udp   UNCONN 0      0      127.0.0.53%lo:53        0.0.0.0:*    users:(("systemd-resolve",pid=600,fd=13))
+tcp   ESTAB  0      0          10.0.0.2:51622     10.0.0.5:5432 users:(("node",pid=4321,fd=21))
+tcp   ESTAB  0      0          10.0.0.2:51624     10.0.0.5:5432 users:(("node",pid=4321,fd=22))
+"#;
+
+    #[test]
+    fn test_ss_listening_table() {
+        let sockets = parse_ss(SS);
+        assert_eq!(sockets.len(), 6);
+        assert_eq!(sockets[3].local_addr, "127.0.0.53");
+        assert_eq!(sockets[4].remote.as_deref(), Some("10.0.0.5:5432"));
+        let report = format_report(&sockets, None, false);
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "🔌 3 listening (2 tcp, 1 udp)");
+        assert_eq!(lines[1], "PORT  PROTO  ADDR        PROCESS");
+        assert_eq!(lines[2], "53    udp    127.0.0.53  systemd-resolve (600)");
+        assert_eq!(lines[3], "80    tcp    *           nginx (1234)");
+        assert_eq!(lines[4], "5432  tcp    127.0.0.1   ?");
+        assert_eq!(lines.len(), 5);
+    }
+
+    #[test]
+    fn test_port_filter_and_connections() {
+        let sockets = parse_ss(SS);
+        assert_eq!(
+            format_report(&sockets, Some(3000), false),
+            "✓ nothing listening on port 3000"
+        );
+        let report = format_report(&sockets, Some(5432), true);
+        assert!(report.starts_with("🔌 1 listening (1 tcp)"));
+        assert!(report.ends_with("🔗 2 connections\n  node (4321): 2 → 10.0.0.5:5432 ×2"));
+    }
+
+    #[test]
+    fn test_parse_lsof() {
+        let output = "\
+COMMAND   PID USER   FD   TYPE             DEVICE SIZE/OFF NODE NAME
+node     4321 dev   23u  IPv6 0x1234567890abcdef      0t0  TCP *:3000 (LISTEN)
+postgres  812 dev    7u  IPv4 0x1234567890abcdf0      0t0  TCP 127.0.0.1:5432 (LISTEN)
+node     4321 dev   30u  IPv4 0x1234567890abcdf1      0t0  TCP 10.0.0.2:51622->10.0.0.5:443 (ESTABLISHED)
+mDNSRespo 300 dev   10u  IPv4 0x1234567890abcdf2      0t0  UDP *:5353
+";
+        let sockets = parse_lsof(output);
+        assert_eq!(sockets.len(), 4);
+        assert_eq!(sockets[0].local_port, 3000);
+        assert_eq!(sockets[0].process.as_deref(), Some("node (4321)"));
+        assert_eq!(sockets[2].state, "ESTAB");
+        assert_eq!(sockets[2].remote.as_deref(), Some("10.0.0.5:443"));
+        assert!(sockets[3].listening());
+        let report = format_report(&sockets, None, false);
+        assert!(report.contains("3000  tcp    *          node (4321)"));
+    }
+}
@@ -0,0 +1,664 @@
+//! Review packet for one pull request: description, checks, changed files,
+//! substantive diff hunks and unresolved review threads, from `gh`.
+
+use crate::exit_code;
+use crate::git;
+use crate::tracking;
+use crate::utils::truncate;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::process::Command;
+
+/// Description lines kept after comments and blank lines are dropped.
+const MAX_BODY_LINES: usize = 8;
+/// Files listed before the rest are counted.
+const MAX_FILES: usize = 40;
+/// Replies shown per review thread.
+const MAX_REPLIES: usize = 3;
+
+/// Lockfiles and snapshots churn a lot and are rarely what a review is about.
+const SKIPPED_DIFFS: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "pnpm-lock.yaml",
+    "yarn.lock",
+    "poetry.lock",
+    "uv.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+    "Pipfile.lock",
+];
+
+fn skip_diff(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    SKIPPED_DIFFS.contains(&name)
+        || name.ends_with(".snap")
+        || name.ends_with(".min.js")
+        || name.ends_with(".min.css")
+}
+
+const REVIEW_THREADS_QUERY: &str = "query($owner: String!, $name: String!, $number: Int!) {
+  repository(owner: $owner, name: $name) {
+    pullRequest(number: $number) {
+      reviewThreads(first: 100) {
+        nodes {
+          isResolved
+          isOutdated
+          path
+          line
+          originalLine
+          comments(first: 20) { nodes { author { login } body } }
+        }
+      }
+    }
+  }
+}";
+
+#[derive(Debug, PartialEq)]
+struct Thread {
+    path: String,
+    line: Option<u64>,
+    outdated: bool,
+    /// (author, body), first comment first
+    comments: Vec<(String, String)>,
+}
+
+/// Unresolved threads from the `reviewThreads` GraphQL response.
+fn parse_threads(json: &Value) -> Vec<Thread> {
+    let nodes = &json["data"]["repository"]["pullRequest"]["reviewThreads"]["nodes"];
+    nodes
+        .as_array()
+        .map(|nodes| {
+            nodes
+                .iter()
+                .filter(|t| !t["isResolved"].as_bool().unwrap_or(false))
+                .map(|t| Thread {
+                    path: t["path"].as_str().unwrap_or("?").to_string(),
+                    line: t["line"].as_u64().or_else(|| t["originalLine"].as_u64()),
+                    outdated: t["isOutdated"].as_bool().unwrap_or(false),
+                    comments: t["comments"]["nodes"]
+                        .as_array()
+                        .map(|cs| {
+                            cs.iter()
+                                .map(|c| {
+                                    (
+                                        c["author"]["login"]
+                                            .as_str()
+                                            .unwrap_or("ghost")
+                                            .to_string(),
+                                        c["body"].as_str().unwrap_or("").to_string(),
+                                    )
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// First line of a comment body that says something (skips quotes and
+/// suggestion fences).
+fn comment_gist(body: &str) -> String {
+    let line = body
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !l.starts_with('>') && !l.starts_with("```"))
+        .unwrap_or("");
+    let mut gist = truncate(line, 120);
+    if body.contains("```suggestion") {
+        gist.push_str(" [suggestion]");
+    }
+    gist
+}
+
+fn format_thread(thread: &Thread) -> Vec<String> {
+    let mut out = Vec::new();
+    let location = match thread.line {
+        Some(line) => format!("L{}", line),
+        None => "file".to_string(),
+    };
+    let outdated = if thread.outdated { " (outdated)" } else { "" };
+    let mut comments = thread.comments.iter();
+    if let Some((author, body)) = comments.next() {
+        out.push(format!(
+            "  💬 {}{} @{}: {}",
+            location,
+            outdated,
+            author,
+            comment_gist(body)
+        ));
+    }
+    let replies: Vec<_> = comments.collect();
+    for (author, body) in replies.iter().take(MAX_REPLIES) {
+        out.push(format!("     ↳ @{}: {}", author, comment_gist(body)));
+    }
+    if replies.len() > MAX_REPLIES {
+        out.push(format!(
+            "     ↳ ... +{} more replies",
+            replies.len() - MAX_REPLIES
+        ));
+    }
+    out
+}
+
+/// Description without HTML comments, images and blank lines.
+fn summarize_body(body: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut in_comment = false;
+    let mut total = 0;
+    for line in body.lines() {
+        let line = line.trim();
+        if in_comment {
+            in_comment = !line.contains("-->");
+            continue;
+        }
+        if line.starts_with("<!--") {
+            in_comment = !line.contains("-->");
+            continue;
+        }
+        if line.is_empty() || line.starts_with("![") || line.starts_with("<img") {
+            continue;
+        }
+        total += 1;
+        if out.len() < MAX_BODY_LINES {
+            out.push(format!("  {}", truncate(line, 100)));
+        }
+    }
+    if total > MAX_BODY_LINES {
+        out.push(format!("  ... +{} more lines", total - MAX_BODY_LINES));
+    }
+    out
+}
+
+/// Check summary line and the names of failing checks.
+fn summarize_checks(rollup: &Value) -> Option<(String, Vec<String>)> {
+    let checks = rollup.as_array().filter(|c| !c.is_empty())?;
+    let (mut passed, mut pending) = (0, 0);
+    let mut failed = Vec::new();
+    for check in checks {
+        // CheckRun has status/conclusion, StatusContext has state
+        let result = check["conclusion"]
+            .as_str()
+            .filter(|c| !c.is_empty())
+            .or_else(|| check["state"].as_str())
+            .unwrap_or("PENDING");
+        match result {
+            "SUCCESS" | "NEUTRAL" | "SKIPPED" => passed += 1,
+            "FAILURE" | "ERROR" | "TIMED_OUT" | "CANCELLED" | "ACTION_REQUIRED"
+            | "STARTUP_FAILURE" => failed.push(
+                check["name"]
+                    .as_str()
+                    .or_else(|| check["context"].as_str())
+                    .unwrap_or("?")
+                    .to_string(),
+            ),
+            _ => pending += 1,
+        }
+    }
+    let mut line = format!("Checks: {}/{} passed", passed, checks.len());
+    if !failed.is_empty() {
+        line.push_str(&format!(", {} failed", failed.len()));
+    }
+    if pending > 0 {
+        line.push_str(&format!(", {} pending", pending));
+    }
+    Some((line, failed))
+}
+
+/// Splits a unified diff into per-file sections keyed by the new path.
+fn split_diff(diff: &str) -> Vec<(String, String)> {
+    let mut sections: Vec<(String, String)> = Vec::new();
+    for line in diff.lines() {
+        if line.starts_with("diff --git") {
+            let path = line.split(" b/").nth(1).unwrap_or("unknown").to_string();
+            sections.push((path, String::new()));
+        }
+        if let Some((_, section)) = sections.last_mut() {
+            section.push_str(line);
+            section.push('\n');
+        }
+    }
+    sections
+}
+
+/// A hunk that only re-indents or reorders lines changes nothing a
+/// reviewer needs to read.
+fn is_substantive(hunk: &[&str]) -> bool {
+    let normalize = |prefix: char| {
+        let mut lines: Vec<String> = hunk
+            .iter()
+            .filter_map(|l| l.strip_prefix(prefix))
+            .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|l| !l.is_empty())
+            .collect();
+        lines.sort();
+        lines
+    };
+    normalize('+') != normalize('-')
+}
+
+/// The section with whitespace-only hunks removed; `None` when every hunk
+/// was dropped.
+fn substantive_hunks(section: &str) -> Option<String> {
+    let mut header = Vec::new();
+    let mut hunks: Vec<Vec<&str>> = Vec::new();
+    for line in section.lines() {
+        if line.starts_with("@@") {
+            hunks.push(vec![line]);
+        } else if let Some(hunk) = hunks.last_mut() {
+            hunk.push(line);
+        } else {
+            header.push(line);
+        }
+    }
+    if hunks.is_empty() {
+        // Binary files, renames and mode changes
+        return Some(section.to_string());
+    }
+    let kept: Vec<&Vec<&str>> = hunks.iter().filter(|h| is_substantive(&h[1..])).collect();
+    if kept.is_empty() {
+        return None;
+    }
+    let mut out = header.join("\n");
+    for hunk in kept {
+        out.push('\n');
+        out.push_str(&hunk.join("\n"));
+    }
+    Some(out)
+}
+
+struct Packet<'a> {
+    pr: &'a Value,
+    diff: &'a str,
+    threads: &'a [Thread],
+    max_lines: usize,
+}
+
+fn format_packet(packet: &Packet) -> String {
+    let pr = packet.pr;
+    let mut out = Vec::new();
+
+    let state = pr["state"].as_str().unwrap_or("?");
+    let icon = match state {
+        "OPEN" => "🟢",
+        "MERGED" => "🟣",
+        "CLOSED" => "🔴",
+        _ => "⚪",
+    };
+    let draft = if pr["isDraft"].as_bool().unwrap_or(false) {
+        " (draft)"
+    } else {
+        ""
+    };
+    out.push(format!(
+        "{} PR #{}: {}{}",
+        icon,
+        pr["number"].as_i64().unwrap_or(0),
+        pr["title"].as_str().unwrap_or("?"),
+        draft
+    ));
+
+    let files = pr["files"].as_array().cloned().unwrap_or_default();
+    let mut meta = format!(
+        "  @{}  {} → {}  +{} -{} in {} files",
+        pr["author"]["login"].as_str().unwrap_or("?"),
+        pr["headRefName"].as_str().unwrap_or("?"),
+        pr["baseRefName"].as_str().unwrap_or("?"),
+        pr["additions"].as_i64().unwrap_or(0),
+        pr["deletions"].as_i64().unwrap_or(0),
+        files.len()
+    );
+    if let Some(decision) = pr["reviewDecision"].as_str().filter(|d| !d.is_empty()) {
+        meta.push_str(&format!(
+            "  review: {}",
+            decision.to_lowercase().replace('_', " ")
+        ));
+    }
+    match pr["mergeable"].as_str() {
+        Some("CONFLICTING") => meta.push_str("  ✗ conflicts"),
+        Some("MERGEABLE") => meta.push_str("  mergeable ✓"),
+        _ => {}
+    }
+    out.push(meta);
+    if let Some(url) = pr["url"].as_str() {
+        out.push(format!("  {}", url));
+    }
+
+    if let Some(body) = pr["body"].as_str() {
+        let body = summarize_body(body);
+        if !body.is_empty() {
+            out.push(String::new());
+            out.extend(body);
+        }
+    }
+
+    if let Some((line, failed)) = summarize_checks(&pr["statusCheckRollup"]) {
+        out.push(String::new());
+        out.push(line);
+        for name in &failed {
+            out.push(format!("  ✗ {}", name));
+        }
+    }
+
+    let mut threads_by_file: BTreeMap<&str, Vec<&Thread>> = BTreeMap::new();
+    for thread in packet.threads {
+        threads_by_file
+            .entry(thread.path.as_str())
+            .or_default()
+            .push(thread);
+    }
+
+    out.push(String::new());
+    out.push(format!("Files ({}):", files.len()));
+    let width = files
+        .iter()
+        .take(MAX_FILES)
+        .filter_map(|f| f["path"].as_str())
+        .map(|p| p.len())
+        .max()
+        .unwrap_or(0);
+    for file in files.iter().take(MAX_FILES) {
+        let path = file["path"].as_str().unwrap_or("?");
+        let mut line = format!(
+            "  {:width$}  +{} -{}",
+            path,
+            file["additions"].as_i64().unwrap_or(0),
+            file["deletions"].as_i64().unwrap_or(0)
+        );
+        if let Some(threads) = threads_by_file.get(path) {
+            line.push_str(&format!("  💬{}", threads.len()));
+        }
+        if skip_diff(path) {
+            line.push_str("  (diff skipped)");
+        }
+        out.push(line);
+    }
+    if files.len() > MAX_FILES {
+        out.push(format!("  ... +{} more files", files.len() - MAX_FILES));
+    }
+
+    // Diff, with each file's unresolved threads right under it
+    let mut budget = packet.max_lines;
+    let mut whitespace_only = 0;
+    let mut truncated = 0;
+    let mut shown = Vec::new();
+    for (path, section) in split_diff(packet.diff) {
+        if skip_diff(&path) {
+            continue;
+        }
+        let Some(section) = substantive_hunks(&section) else {
+            whitespace_only += 1;
+            continue;
+        };
+        if budget == 0 {
+            truncated += 1;
+            continue;
+        }
+        let compact = git::compact_diff(&section, budget);
+        budget = budget.saturating_sub(compact.lines().count());
+        out.push(compact.trim_end().to_string());
+        if let Some(threads) = threads_by_file.remove(path.as_str()) {
+            for thread in threads {
+                out.extend(format_thread(thread));
+            }
+        }
+        shown.push(path);
+    }
+    if whitespace_only > 0 {
+        out.push(format!(
+            "\n({} files with whitespace-only changes omitted)",
+            whitespace_only
+        ));
+    }
+    if truncated > 0 {
+        out.push(format!(
+            "\n... {} more files not shown (rtk gh pr diff {})",
+            truncated,
+            pr["number"].as_i64().unwrap_or(0)
+        ));
+    }
+
+    if !threads_by_file.is_empty() {
+        out.push(String::new());
+        out.push("Unresolved threads:".to_string());
+        for (path, threads) in threads_by_file {
+            out.push(format!("📄 {}", path));
+            for thread in threads {
+                out.extend(format_thread(thread));
+            }
+        }
+    }
+
+    out.join("\n")
+}
+
+/// `owner` and `name` from a PR URL like `https://github.com/o/r/pull/1`.
+fn repo_from_url(url: &str) -> Option<(String, String)> {
+    let path = url.split("://").nth(1)?;
+    let mut parts = path.split('/').skip(1);
+    let owner = parts.next()?.to_string();
+    let name = parts.next()?.to_string();
+    Some((owner, name))
+}
+
+fn gh(args: &[&str], repo: Option<&str>) -> Result<std::process::Output> {
+    let mut cmd = Command::new("gh");
+    cmd.args(args);
+    if let Some(repo) = repo {
+        cmd.args(["--repo", repo]);
+    }
+    cmd.output()
+        .context("Failed to run gh. Is it installed? Try: https://cli.github.com")
+}
+
+pub fn run(number: &str, repo: Option<&str>, max_lines: usize, verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+
+    if verbose > 0 {
+        eprintln!(
+            "gh pr view {} / gh pr diff {} / review threads",
+            number, number
+        );
+    }
+
+    let view = gh(
+        &[
+            "pr",
+            "view",
+            number,
+            "--json",
+            "number,title,state,isDraft,author,body,url,baseRefName,headRefName,\
+             additions,deletions,files,mergeable,reviewDecision,statusCheckRollup",
+        ],
+        repo,
+    )?;
+    if !view.status.success() {
+        let stderr = String::from_utf8_lossy(&view.stderr).to_string();
+        timer.with_exit_code(view.status).track(
+            &format!("gh pr view {}", number),
+            &format!("rtk pr {}", number),
+            &stderr,
+            &stderr,
+        );
+        eprintln!("{}", stderr.trim());
+        exit_code::exit_with(view.status);
+    }
+    let pr: Value =
+        serde_json::from_slice(&view.stdout).context("Failed to parse gh pr view output")?;
+
+    let diff = gh(&["pr", "diff", number], repo)?;
+    let diff = String::from_utf8_lossy(&diff.stdout).to_string();
+
+    // Threads need GraphQL; a failure here just leaves them out
+    let threads = pr["url"]
+        .as_str()
+        .and_then(repo_from_url)
+        .and_then(|(owner, name)| {
+            let output = Command::new("gh")
+                .args(["api", "graphql"])
+                .arg("-f")
+                .arg(format!("query={}", REVIEW_THREADS_QUERY))
+                .args(["-F", &format!("owner={}", owner)])
+                .args(["-F", &format!("name={}", name)])
+                .args(["-F", &format!("number={}", pr["number"].as_i64()?)])
+                .output()
+                .ok()?;
+            if !output.status.success() {
+                if verbose > 0 {
+                    eprintln!(
+                        "review threads unavailable: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    );
+                }
+                return None;
+            }
+            serde_json::from_slice::<Value>(&output.stdout).ok()
+        })
+        .map(|json| parse_threads(&json))
+        .unwrap_or_default();
+
+    let filtered = format_packet(&Packet {
+        pr: &pr,
+        diff: &diff,
+        threads: &threads,
+        max_lines,
+    });
+    crate::profile::println_quoting(&filtered, git::is_hunk_line);
+
+    let raw = format!("{}\n{}", String::from_utf8_lossy(&view.stdout), diff);
+    timer.track(
+        &format!("gh pr view {} && gh pr diff {}", number, number),
+        &format!("rtk pr {}", number),
+        &raw,
+        &filtered,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 111..222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,3 @@
+ fn a() {
+-    old();
++    new();
+ }
+@@ -10,2 +10,2 @@
+-fn  spaced() {}
++fn spaced() {}
+diff --git a/src/fmt.rs b/src/fmt.rs
+--- a/src/fmt.rs
++++ b/src/fmt.rs
+@@ -1,2 +1,2 @@
+-  let x = 1;
++    let x = 1;
+diff --git a/Cargo.lock b/Cargo.lock
+--- a/Cargo.lock
++++ b/Cargo.lock
+@@ -1 +1 @@
+-version = 1
++version = 2
+";
+
+    #[test]
+    fn test_substantive_hunks() {
+        let sections = split_diff(DIFF);
+        assert_eq!(sections.len(), 3);
+        let lib = substantive_hunks(&sections[0].1).unwrap();
+        assert!(lib.contains("+    new();"));
+        assert!(!lib.contains("spaced"));
+        assert_eq!(substantive_hunks(&sections[1].1), None);
+    }
+
+    #[test]
+    fn test_parse_threads_skips_resolved() {
+        let json = json!({"data": {"repository": {"pullRequest": {"reviewThreads": {"nodes": [
+            {"isResolved": false, "isOutdated": false, "path": "src/lib.rs", "line": 2,
+             "comments": {"nodes": [
+                {"author": {"login": "bob"}, "body": "> quoted\nWhy not keep old()?"},
+                {"author": {"login": "alice"}, "body": "```suggestion\nnew();\n```\nSee above"}]}},
+            {"isResolved": true, "path": "src/lib.rs", "line": 9,
+             "comments": {"nodes": [{"author": {"login": "bob"}, "body": "nit"}]}}
+        ]}}}}});
+        let threads = parse_threads(&json);
+        assert_eq!(threads.len(), 1);
+        assert_eq!(
+            format_thread(&threads[0]),
+            vec![
+                "  💬 L2 @bob: Why not keep old()?",
+                "     ↳ @alice: new(); [suggestion]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_packet() {
+        let pr = json!({
+            "number": 42, "title": "Swap old for new", "state": "OPEN", "isDraft": false,
+            "author": {"login": "alice"}, "headRefName": "swap", "baseRefName": "main",
+            "additions": 4, "deletions": 4, "mergeable": "MERGEABLE",
+            "reviewDecision": "CHANGES_REQUESTED", "url": "https://github.com/o/r/pull/42",
+            "body": "<!-- template\nhint -->\nReplaces old() with new().\n\n![shot](x.png)\n",
+            "files": [
+                {"path": "src/lib.rs", "additions": 2, "deletions": 2},
+                {"path": "src/fmt.rs", "additions": 1, "deletions": 1},
+                {"path": "Cargo.lock", "additions": 1, "deletions": 1}
+            ],
+            "statusCheckRollup": [
+                {"name": "test", "status": "COMPLETED", "conclusion": "SUCCESS"},
+                {"name": "lint", "status": "COMPLETED", "conclusion": "FAILURE"},
+                {"context": "ci/legacy", "state": "PENDING"}
+            ]
+        });
+        let threads = vec![
+            Thread {
+                path: "src/lib.rs".to_string(),
+                line: Some(2),
+                outdated: false,
+                comments: vec![("bob".to_string(), "Why?".to_string())],
+            },
+            Thread {
+                path: "README.md".to_string(),
+                line: None,
+                outdated: true,
+                comments: vec![("bob".to_string(), "Document this".to_string())],
+            },
+        ];
+        let out = format_packet(&Packet {
+            pr: &pr,
+            diff: DIFF,
+            threads: &threads,
+            max_lines: 100,
+        });
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "🟢 PR #42: Swap old for new");
+        assert_eq!(
+            lines[1],
+            "  @alice  swap → main  +4 -4 in 3 files  review: changes requested  mergeable ✓"
+        );
+        assert_eq!(lines[4], "  Replaces old() with new().");
+        assert_eq!(lines[6], "Checks: 1/3 passed, 1 failed, 1 pending");
+        assert_eq!(lines[7], "  ✗ lint");
+        assert_eq!(lines[10], "  src/lib.rs  +2 -2  💬1");
+        assert_eq!(lines[12], "  Cargo.lock  +1 -1  (diff skipped)");
+        assert!(out.contains("📄 src/lib.rs\n  @@ -1,3 +1,3 @@\n  -    old();\n  +    new();"));
+        assert!(out.contains("+1 -1\n  💬 L2 @bob: Why?"));
+        assert!(!out.contains("📄 src/fmt.rs"));
+        assert!(!out.contains("version = 2"));
+        assert!(out.contains("(1 files with whitespace-only changes omitted)"));
+        assert!(out.ends_with(
+            "Unresolved threads:\n📄 README.md\n  💬 file (outdated) @bob: Document this"
+        ));
+    }
+}
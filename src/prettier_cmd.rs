@@ -1,3 +1,4 @@
+use crate::exit_code;
 use crate::tracking;
 use crate::utils::package_manager_exec;
 use anyhow::{Context, Result};
@@ -23,12 +24,13 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
     let filtered = filter_prettier_output(&raw);
 
     println!("{}", filtered);
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("prettier {}", args.join(" ")),
         &format!("rtk prettier {}", args.join(" ")),
         &raw,
@@ -37,7 +39,7 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
 
     // Preserve exit code for CI/CD
     if !output.status.success() {
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     Ok(())
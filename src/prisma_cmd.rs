@@ -1,4 +1,5 @@
 use crate::tracking;
+use crate::utils::which;
 use anyhow::{Context, Result};
 use std::process::Command;
 
@@ -26,11 +27,7 @@ pub fn run(cmd: PrismaCommand, args: &[String], verbose: u8) -> Result<()> {
 
 /// Create a Command that will run prisma (tries global first, then npx)
 fn create_prisma_command() -> Command {
-    let prisma_exists = Command::new("which")
-        .arg("prisma")
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false);
+    let prisma_exists = which("prisma").is_some();
 
     if prisma_exists {
         Command::new("prisma")
@@ -67,11 +64,17 @@ fn run_generate(args: &[String], verbose: u8) -> Result<()> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
     let filtered = filter_prisma_generate(&raw);
 
     println!("{}", filtered);
 
-    timer.track("prisma generate", "rtk prisma generate", &raw, &filtered);
+    timer.with_exit_code(output.status).track(
+        "prisma generate",
+        "rtk prisma generate",
+        &raw,
+        &filtered,
+    );
 
     Ok(())
 }
@@ -118,6 +121,7 @@ fn run_migrate(subcommand: MigrateSubcommand, args: &[String], verbose: u8) -> R
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
     let filtered = match subcommand {
         MigrateSubcommand::Dev { .. } => filter_migrate_dev(&raw),
@@ -127,7 +131,12 @@ fn run_migrate(subcommand: MigrateSubcommand, args: &[String], verbose: u8) -> R
 
     println!("{}", filtered);
 
-    timer.track(cmd_name, &format!("rtk {}", cmd_name), &raw, &filtered);
+    timer.with_exit_code(output.status).track(
+        cmd_name,
+        &format!("rtk {}", cmd_name),
+        &raw,
+        &filtered,
+    );
 
     Ok(())
 }
@@ -156,11 +165,17 @@ fn run_db_push(args: &[String], verbose: u8) -> Result<()> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
     let filtered = filter_db_push(&raw);
 
     println!("{}", filtered);
 
-    timer.track("prisma db push", "rtk prisma db push", &raw, &filtered);
+    timer.with_exit_code(output.status).track(
+        "prisma db push",
+        "rtk prisma db push",
+        &raw,
+        &filtered,
+    );
 
     Ok(())
 }
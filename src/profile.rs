@@ -0,0 +1,246 @@
+//! Output profiles: how rtk renders text on stdout.
+//!
+//! `print!`/`println!` are shadowed crate-wide (see lib.rs) so every
+//! formatter goes through [`print`] / [`print_line`], after any user
+//! pipeline rules (see pipeline.rs):
+//! - `human`: output as written (emoji, box drawing, colors)
+//! - `agent`: no emoji, colors or box drawing, no blank spacer lines
+//! - `minimal`: `agent` plus tab-separated columns, for machine parsing
+//!
+//! Structured exports (`--format json/csv/markdown`) and file content use
+//! `print_verbatim!`/`println_verbatim!` instead, which skip the profile: a
+//! JSON string or a source line must come out exactly as it went in. Output
+//! that quotes file lines among its own (grep matches, diff hunks) goes
+//! through [`println_quoting`], which renders only rtk's lines.
+//!
+//! Selected by `--profile`, then `RTK_PROFILE`, then `output.profile` in config.
+//! Color and `--ascii` handling apply on top of every profile (see style.rs).
+//!
+//! `--raw` (or `RTK_RAW=1`) bypasses all of it: wrappers still filter so the
+//! savings they *would* have made are tracked, but their output is held back
+//! and the unfiltered input is printed when they call `track`.
+
+use crate::config::Config;
+use crate::pipeline;
+use crate::style::{self, is_rule, strip_ansi, strip_glyphs};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::sync::{Mutex, OnceLock};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    Agent,
+    #[default]
+    Human,
+    Minimal,
+}
+
+static ACTIVE: OnceLock<Profile> = OnceLock::new();
+
+/// Filtered output held back in `--raw` mode; `None` once raw input was shown.
+static RAW_HELD: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+
+lazy_static! {
+    static ref COLUMN_GAP: Regex = Regex::new(r"(\S) {2,}").unwrap();
+}
+
+/// Resolve and activate the profile. Call once, before any output.
+pub fn init(cli: Option<Profile>) {
+    let profile = cli
+        .or_else(|| {
+            std::env::var("RTK_PROFILE")
+                .ok()
+                .and_then(|v| <Profile as clap::ValueEnum>::from_str(v.trim(), true).ok())
+        })
+        .unwrap_or_else(|| Config::cached().output.profile);
+    let _ = ACTIVE.set(profile);
+}
+
+pub fn active() -> Profile {
+    ACTIVE.get().copied().unwrap_or_default()
+}
+
+/// Enable `--raw` mode. The returned guard prints held output on drop if no
+/// wrapper ever tracked (commands like `rtk gain` have no raw form).
+pub fn enable_raw() -> RawGuard {
+    let _ = RAW_HELD.set(Mutex::new(Some(String::new())));
+    RawGuard
+}
+
+pub fn raw_enabled(cli: bool) -> bool {
+    cli || std::env::var("RTK_RAW").is_ok_and(|v| v == "1" || v == "true")
+}
+
+pub struct RawGuard;
+
+impl Drop for RawGuard {
+    fn drop(&mut self) {
+        flush_raw();
+    }
+}
+
+/// Print output still held back in `--raw` mode (also used before an early exit).
+pub fn flush_raw() {
+    let held = RAW_HELD.get().and_then(|m| m.lock().ok()?.take());
+    if let Some(held) = held {
+        let _ = std::io::stdout().lock().write_all(held.as_bytes());
+    }
+}
+
+/// Called from `track`: in `--raw` mode, drop the filtered output and print
+/// the unfiltered `input` instead.
+pub fn show_raw(input: &str) {
+    let Some(held) = RAW_HELD.get() else {
+        return;
+    };
+    if let Ok(mut held) = held.lock() {
+        *held = None;
+    }
+    let mut out = std::io::stdout().lock();
+    let _ = out.write_all(input.as_bytes());
+    if !input.is_empty() && !input.ends_with('\n') {
+        let _ = out.write_all(b"\n");
+    }
+}
+
+/// Write to stdout, or hold back while `--raw` waits for the unfiltered input
+/// (or for the pager, see pager.rs, or past the token budget, see spill.rs).
+fn emit(text: &str) {
+    if crate::pager::hold(text) {
+        return;
+    }
+    if let Some(held) = RAW_HELD.get() {
+        if let Ok(mut held) = held.lock() {
+            // After raw input was shown, later filtered output is redundant
+            if let Some(buf) = held.as_mut() {
+                buf.push_str(text);
+            }
+            return;
+        }
+    }
+    let text = crate::spill::admit(text);
+    if text.is_empty() {
+        return;
+    }
+    let _ = std::io::stdout().lock().write_all(text.as_bytes());
+}
+
+/// `print!` target. Fragments ending in a newline are rendered like
+/// `println!`; partial lines only get glyph substitution.
+pub fn print(fragment: &str) {
+    if let Some(lines) = fragment.strip_suffix('\n') {
+        print_line(lines);
+    } else if active() == Profile::Human {
+        emit(&style::apply(fragment));
+    } else {
+        emit(&style::apply(&strip_glyphs(&strip_ansi(fragment))));
+    }
+}
+
+/// `println!` target: user pipeline, then full per-line rendering.
+pub fn print_line(text: &str) {
+    let Some(text) = pipeline::apply(text) else {
+        return;
+    };
+    let profile = active();
+    if profile == Profile::Human {
+        emit(&format!("{}\n", style::apply(&text)));
+    } else if let Some(rendered) = render(&text, profile) {
+        emit(&format!("{}\n", style::apply(&rendered)));
+    }
+}
+
+/// `print_verbatim!` target: no profile rendering, glyph or color handling.
+/// `--raw`, the pager and the token budget still apply.
+pub fn print_verbatim(text: &str) {
+    emit(text);
+}
+
+/// `println!` for output that quotes file content: lines `quoted` accepts
+/// are printed verbatim, runs of the others are rendered as usual.
+pub fn println_quoting(text: &str, quoted: impl Fn(&str) -> bool) {
+    let lines: Vec<&str> = text.split('\n').collect();
+    for run in lines.chunk_by(|a, b| quoted(a) == quoted(b)) {
+        if quoted(run[0]) {
+            print_verbatim(&format!("{}\n", run.join("\n")));
+        } else {
+            print_line(&run.join("\n"));
+        }
+    }
+}
+
+/// Render `text` for `profile`; `None` when nothing is left to print.
+fn render(text: &str, profile: Profile) -> Option<String> {
+    if profile == Profile::Human {
+        return Some(text.to_string());
+    }
+    let lines: Vec<String> = text
+        .lines()
+        .filter_map(|line| render_line(line, profile))
+        .collect();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+fn render_line(line: &str, profile: Profile) -> Option<String> {
+    let line = strip_ansi(line);
+    if is_rule(&line) {
+        return None;
+    }
+    let mut line = strip_glyphs(&line).trim_end().to_string();
+    if line.trim().is_empty() {
+        return None;
+    }
+    if profile == Profile::Minimal {
+        line = COLUMN_GAP.replace_all(&line, "$1\t").into_owned();
+    }
+    Some(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_human_is_untouched() {
+        let text = "📊 Summary\n═════\n\n✓ ok";
+        assert_eq!(render(text, Profile::Human).as_deref(), Some(text));
+    }
+
+    #[test]
+    fn test_agent_strips_decoration() {
+        let text = "📊 RTK Token Savings\n════════════════\n\n✅ 3 passed │ ❌ 1 failed";
+        assert_eq!(
+            render(text, Profile::Agent).as_deref(),
+            Some("RTK Token Savings\nok 3 passed | FAIL 1 failed")
+        );
+    }
+
+    #[test]
+    fn test_agent_drops_blank_spacers() {
+        assert_eq!(render("", Profile::Agent), None);
+        assert_eq!(render("───────", Profile::Agent), None);
+    }
+
+    #[test]
+    fn test_agent_strips_ansi() {
+        assert_eq!(
+            render("\x1b[32mgreen\x1b[0m text", Profile::Agent).as_deref(),
+            Some("green text")
+        );
+    }
+
+    #[test]
+    fn test_minimal_tab_separates_columns() {
+        assert_eq!(
+            render("  main.rs   1.2K\nREADME.md  4.0K", Profile::Minimal).as_deref(),
+            Some("  main.rs\t1.2K\nREADME.md\t4.0K")
+        );
+    }
+}
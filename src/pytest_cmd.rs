@@ -1,5 +1,6 @@
+use crate::exit_code;
 use crate::tracking;
-use crate::utils::truncate;
+use crate::utils::{truncate, which};
 use anyhow::{Context, Result};
 use std::process::Command;
 
@@ -15,7 +16,7 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
     let timer = tracking::TimedExecution::start();
 
     // Try to detect pytest command (could be "pytest", "python -m pytest", etc.)
-    let mut cmd = if which_command("pytest").is_some() {
+    let mut cmd = if which("pytest").is_some() {
         Command::new("pytest")
     } else {
         // Fallback to python -m pytest
@@ -50,6 +51,7 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
     let filtered = filter_pytest_output(&stdout);
 
@@ -60,7 +62,7 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
         eprintln!("{}", stderr.trim());
     }
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("pytest {}", args.join(" ")),
         &format!("rtk pytest {}", args.join(" ")),
         &raw,
@@ -69,26 +71,14 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
 
     // Preserve exit code for CI/CD
     if !output.status.success() {
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     Ok(())
 }
 
-/// Check if a command exists in PATH
-fn which_command(cmd: &str) -> Option<String> {
-    Command::new("which")
-        .arg(cmd)
-        .output()
-        .ok()
-        .filter(|o| o.status.success())
-        .and_then(|o| String::from_utf8(o.stdout).ok())
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-}
-
 /// Parse pytest output using state machine
-fn filter_pytest_output(output: &str) -> String {
+pub(crate) fn filter_pytest_output(output: &str) -> String {
     let mut state = ParseState::Header;
     let mut test_files: Vec<String> = Vec::new();
     let mut failures: Vec<String> = Vec::new();
@@ -218,17 +208,20 @@ fn build_pytest_summary(summary: &str, _test_files: &[String], failures: &[Strin
             }
         }
 
-        // Show relevant error lines (assertions, errors, file locations)
+        // Show relevant error lines (assertions, errors), and always the
+        // file location
         let mut relevant_lines = 0;
         for line in &lines[1..] {
             let line_lower = line.to_lowercase();
+            let is_location = line.contains(".py:");
             let is_relevant = line.trim().starts_with('>')
                 || line.trim().starts_with('E')
                 || line_lower.contains("assert")
-                || line_lower.contains("error")
-                || line.contains(".py:");
+                || line_lower.contains("error");
 
-            if is_relevant && relevant_lines < 3 {
+            if is_location {
+                result.push_str(&format!("     {}\n", truncate(line, 100)));
+            } else if is_relevant && relevant_lines < 3 {
                 result.push_str(&format!("     {}\n", truncate(line, 100)));
                 relevant_lines += 1;
             }
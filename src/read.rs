@@ -1,8 +1,16 @@
+use crate::cache;
+use crate::encoding;
 use crate::filter::{self, FilterLevel, Language};
+use crate::mapped;
+use crate::markdown;
+use crate::parallel;
+use crate::path_rules::{PathAction, PathRules};
 use crate::tracking;
 use anyhow::{Context, Result};
-use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Line cap for files matched by `.rtkignore` or `filters.*_paths` rules.
+const RULED_MAX_LINES: usize = 40;
 
 pub fn run(
     file: &Path,
@@ -12,14 +20,102 @@ pub fn run(
     verbose: u8,
 ) -> Result<()> {
     let timer = tracking::TimedExecution::start();
+    let (content, rtk_output) = render(file, level, max_lines, line_numbers, verbose)?;
+    println_verbatim!("{}", rtk_output);
+    timer.track(
+        &format!("cat {}", file.display()),
+        "rtk cat",
+        &content,
+        &rtk_output,
+    );
+    Ok(())
+}
+
+/// `rtk cat a b c`: files are filtered concurrently and printed in argument
+/// order, each under a `==> file <==` header as `head` does. Like `cat`, an
+/// unreadable file is reported and skipped, and rtk exits 1.
+pub fn run_many(
+    files: &[PathBuf],
+    level: FilterLevel,
+    max_lines: Option<usize>,
+    line_numbers: bool,
+    verbose: u8,
+) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let (raw, rtk_output, errors) = render_many(files, level, max_lines, line_numbers, verbose);
+    print_verbatim!("{}", rtk_output);
+    for e in &errors {
+        eprintln!("rtk cat: {:#}", e);
+    }
+    let names: Vec<String> = files.iter().map(|f| f.display().to_string()).collect();
+    let code = if errors.is_empty() { 0 } else { 1 };
+    timer.with_code(code).track(
+        &format!("cat {}", names.join(" ")),
+        "rtk cat",
+        &raw,
+        &rtk_output,
+    );
+    Ok(())
+}
+
+/// (original contents, sectioned output, read errors) for `files`.
+fn render_many(
+    files: &[PathBuf],
+    level: FilterLevel,
+    max_lines: Option<usize>,
+    line_numbers: bool,
+    verbose: u8,
+) -> (String, String, Vec<anyhow::Error>) {
+    let rendered = parallel::map(files, |file| {
+        render(file, level, max_lines, line_numbers, verbose)
+    });
+    let mut raw = String::new();
+    let mut sections = Vec::new();
+    let mut errors = Vec::new();
+    for (file, result) in files.iter().zip(rendered) {
+        match result {
+            Ok((content, output)) => {
+                raw.push_str(&content);
+                sections.push(format!(
+                    "==> {} <==\n{}\n",
+                    file.display(),
+                    output.trim_end_matches('\n')
+                ));
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+    (raw, sections.join("\n"), errors)
+}
+
+/// Read and filter `file`: (original content, filtered output).
+pub fn render(
+    file: &Path,
+    level: FilterLevel,
+    max_lines: Option<usize>,
+    line_numbers: bool,
+    verbose: u8,
+) -> Result<(String, String)> {
+    // Explicitly requested files are still read, but aggressively
+    let (level, max_lines) = match PathRules::load().action(file, false) {
+        PathAction::Keep => (level, max_lines),
+        PathAction::Exclude | PathAction::Summarize => {
+            if verbose > 0 {
+                eprintln!("{} matches path rules, summarizing", file.display());
+            }
+            (
+                FilterLevel::Aggressive,
+                Some(max_lines.unwrap_or(RULED_MAX_LINES)),
+            )
+        }
+    };
 
     if verbose > 0 {
         eprintln!("Reading: {} (filter: {})", file.display(), level);
     }
 
-    // Read file content
-    let content = fs::read_to_string(file)
-        .with_context(|| format!("Failed to read file: {}", file.display()))?;
+    // Read file content, whatever its encoding
+    let content = encoding::read_file(file)?;
 
     // Detect language from extension
     let lang = file
@@ -32,13 +128,69 @@ pub fn run(
         eprintln!("Detected language: {:?}", lang);
     }
 
-    // Apply filter
-    let filter = filter::get_filter(level);
-    let mut filtered = filter.filter(&content, &lang);
+    // Hashing hundreds of megabytes would cost more than filtering them
+    let fingerprint = (content.len() >= mapped::LARGE)
+        .then(|| mapped::fingerprint(file))
+        .flatten();
+    let cache_key = cache::key(&[
+        "cat",
+        &level.to_string(),
+        &format!("{:?}", max_lines),
+        &line_numbers.to_string(),
+        &file.display().to_string(),
+        fingerprint.as_deref().unwrap_or(&content),
+    ]);
+    let rtk_output = match cache::get(&cache_key) {
+        Some(entry) => {
+            if verbose > 0 {
+                eprintln!("Cache hit: {}", file.display());
+            }
+            entry.output
+        }
+        None => {
+            let output = filter_content(
+                &content,
+                &lang,
+                markdown::is_markdown(file),
+                level,
+                max_lines,
+                line_numbers,
+                verbose,
+            );
+            cache::put(
+                &cache_key,
+                &cache::Entry {
+                    // The file content is read on every run anyway
+                    input: String::new(),
+                    output: output.clone(),
+                },
+            );
+            output
+        }
+    };
+    Ok((content, rtk_output))
+}
+
+/// Filter, truncate and optionally number `content`.
+fn filter_content(
+    content: &str,
+    lang: &Language,
+    markdown: bool,
+    level: FilterLevel,
+    max_lines: Option<usize>,
+    line_numbers: bool,
+    verbose: u8,
+) -> String {
+    // Apply filter; code filters would mangle prose, so docs get their own
+    let mut filtered = if markdown && level != FilterLevel::None {
+        markdown::condense(content)
+    } else {
+        filter::get_filter(level).filter(content, lang)
+    };
 
     if verbose > 0 {
-        let original_lines = content.lines().count();
-        let filtered_lines = filtered.lines().count();
+        let original_lines = mapped::line_count(content);
+        let filtered_lines = mapped::line_count(&filtered);
         let reduction = if original_lines > 0 {
             ((original_lines - filtered_lines) as f64 / original_lines as f64) * 100.0
         } else {
@@ -52,22 +204,14 @@ pub fn run(
 
     // Apply smart truncation if max_lines is set
     if let Some(max) = max_lines {
-        filtered = filter::smart_truncate(&filtered, max, &lang);
+        filtered = filter::smart_truncate(&filtered, max, lang);
     }
 
-    let rtk_output = if line_numbers {
+    if line_numbers {
         format_with_line_numbers(&filtered)
     } else {
-        filtered.clone()
-    };
-    println!("{}", rtk_output);
-    timer.track(
-        &format!("cat {}", file.display()),
-        "rtk cat",
-        &content,
-        &rtk_output,
-    );
-    Ok(())
+        filtered
+    }
 }
 
 pub fn run_stdin(
@@ -85,11 +229,13 @@ pub fn run_stdin(
     }
 
     // Read from stdin
-    let mut content = String::new();
+    let mut bytes = Vec::new();
     io::stdin()
         .lock()
-        .read_to_string(&mut content)
+        .read_to_end(&mut bytes)
         .context("Failed to read from stdin")?;
+    let (content, detected) = encoding::decode(&bytes);
+    encoding::notice("stdin", detected);
 
     // No file extension, so use Unknown language
     let lang = Language::Unknown;
@@ -103,8 +249,8 @@ pub fn run_stdin(
     let mut filtered = filter.filter(&content, &lang);
 
     if verbose > 0 {
-        let original_lines = content.lines().count();
-        let filtered_lines = filtered.lines().count();
+        let original_lines = mapped::line_count(&content);
+        let filtered_lines = mapped::line_count(&filtered);
         let reduction = if original_lines > 0 {
             ((original_lines - filtered_lines) as f64 / original_lines as f64) * 100.0
         } else {
@@ -126,7 +272,7 @@ pub fn run_stdin(
     } else {
         filtered.clone()
     };
-    println!("{}", rtk_output);
+    println_verbatim!("{}", rtk_output);
 
     timer.track("cat - (stdin)", "rtk cat -", &content, &rtk_output);
     Ok(())
@@ -164,6 +310,31 @@ fn main() {{
         Ok(())
     }
 
+    #[test]
+    fn test_render_many_keeps_argument_order() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let files: Vec<PathBuf> = ["b.txt", "missing.txt", "a.txt"]
+            .iter()
+            .map(|name| dir.path().join(name))
+            .collect();
+        std::fs::write(&files[0], "second\n")?;
+        std::fs::write(&files[2], "first\n")?;
+
+        let (raw, output, errors) = render_many(&files, FilterLevel::None, None, false, 0);
+        assert_eq!(raw, "second\nfirst\n");
+        assert_eq!(
+            output,
+            format!(
+                "==> {} <==\nsecond\n\n==> {} <==\nfirst\n",
+                files[0].display(),
+                files[2].display()
+            )
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(format!("{:#}", errors[0]).contains("missing.txt"));
+        Ok(())
+    }
+
     #[test]
     fn test_stdin_support_signature() {
         // Test that run_stdin has correct signature and compiles
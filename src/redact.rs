@@ -0,0 +1,172 @@
+//! Anonymization of command strings for shareable tracking data.
+//!
+//! Replaces absolute paths, URLs, git remotes, `--repo owner/name` arguments
+//! and the current username with short stable hashes, so exports can be
+//! published while identical values still group together.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::io::{BufRead, BufReader, Read};
+
+lazy_static! {
+    static ref URL_RE: Regex = Regex::new(r#"\b[a-zA-Z][a-zA-Z0-9+.-]*://[^\s'"]+"#).unwrap();
+    static ref SCP_REMOTE_RE: Regex = Regex::new(r"\b[\w.-]+@[\w.-]+:[\w./~-]+").unwrap();
+    static ref REPO_FLAG_RE: Regex = Regex::new(r"(--repo[= ]|-R )([\w.-]+/[\w.-]+)").unwrap();
+    static ref ABS_PATH_RE: Regex =
+        Regex::new(r#"(^|[\s='"])(/[^\s'"]+|~/[^\s'"]*|[A-Za-z]:\\[^\s'"]*)"#).unwrap();
+}
+
+/// Redact identifying details from a command string.
+///
+/// # Examples
+/// ```
+/// use rtk::redact::redact_command;
+/// let r = redact_command("rtk read /home/alice/project/src/main.rs");
+/// assert!(r.starts_with("rtk read <path:"));
+/// ```
+pub fn redact_command(cmd: &str) -> String {
+    let redacted = URL_RE.replace_all(cmd, |caps: &regex::Captures| {
+        format!("<url:{}>", short_hash(&caps[0]))
+    });
+    let redacted = SCP_REMOTE_RE.replace_all(&redacted, |caps: &regex::Captures| {
+        format!("<repo:{}>", short_hash(&caps[0]))
+    });
+    let redacted = REPO_FLAG_RE.replace_all(&redacted, |caps: &regex::Captures| {
+        format!("{}<repo:{}>", &caps[1], short_hash(&caps[2]))
+    });
+    let redacted = ABS_PATH_RE.replace_all(&redacted, |caps: &regex::Captures| {
+        format!("{}<path:{}>", &caps[1], short_hash(&caps[2]))
+    });
+
+    match current_username() {
+        Some(user) => redact_username(&redacted, &user),
+        None => redacted.into_owned(),
+    }
+}
+
+fn current_username() -> Option<String> {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
+        .filter(|u| u.len() >= 3)
+}
+
+fn redact_username(text: &str, user: &str) -> String {
+    let pattern = format!(r"\b{}\b", regex::escape(user));
+    match Regex::new(&pattern) {
+        Ok(re) => re.replace_all(text, "<user>").into_owned(),
+        Err(_) => text.to_string(),
+    }
+}
+
+/// Stable 8-hex-digit FNV-1a hash (independent of Rust version, unlike `DefaultHasher`).
+pub(crate) fn short_hash(value: &str) -> String {
+    format!("{:08x}", fnv1a(0xcbf29ce484222325, value.as_bytes()) as u32)
+}
+
+/// [`short_hash`] of everything `reader` yields, read in chunks rather than
+/// held in memory; `None` when it yields nothing.
+pub(crate) fn short_hash_reader(reader: impl Read) -> std::io::Result<Option<String>> {
+    let mut reader = BufReader::new(reader);
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut empty = true;
+    loop {
+        let chunk = reader.fill_buf()?;
+        if chunk.is_empty() {
+            break;
+        }
+        hash = fnv1a(hash, chunk);
+        empty = false;
+        let len = chunk.len();
+        reader.consume(len);
+    }
+    Ok((!empty).then(|| format!("{:08x}", hash as u32)))
+}
+
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_hash_reader_matches_short_hash() {
+        let text = "diff --git a/x b/x\n".repeat(2000);
+        assert_eq!(
+            short_hash_reader(text.as_bytes()).unwrap(),
+            Some(short_hash(&text))
+        );
+        assert_eq!(short_hash_reader(&b""[..]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_redact_absolute_path() {
+        let r = redact_command("rtk read /home/alice/work/secret-repo/src/main.rs");
+        assert!(!r.contains("alice"));
+        assert!(!r.contains("secret-repo"));
+        assert!(r.starts_with("rtk read <path:"));
+    }
+
+    #[test]
+    fn test_redact_keeps_relative_paths() {
+        assert_eq!(redact_command("rtk ls src/utils"), "rtk ls src/utils");
+        assert_eq!(redact_command("rtk git status"), "rtk git status");
+    }
+
+    #[test]
+    fn test_redact_home_and_windows_paths() {
+        assert!(redact_command("rtk ls ~/code/app").contains("<path:"));
+        assert!(redact_command(r"rtk ls C:\Users\bob\app").contains("<path:"));
+    }
+
+    #[test]
+    fn test_redact_path_after_equals() {
+        let r = redact_command("rtk cargo test --manifest-path=/srv/acme/Cargo.toml");
+        assert!(r.contains("--manifest-path=<path:"));
+        assert!(!r.contains("acme"));
+    }
+
+    #[test]
+    fn test_redact_urls_and_remotes() {
+        let r = redact_command("rtk curl https://api.acme.internal/v1/users");
+        assert!(r.starts_with("rtk curl <url:"));
+        let r = redact_command("git clone git@github.com:acme/private.git");
+        assert_eq!(r.matches("<repo:").count(), 1);
+        assert!(!r.contains("acme"));
+    }
+
+    #[test]
+    fn test_redact_repo_flag() {
+        let r = redact_command("rtk gh pr list --repo acme/private");
+        assert!(r.starts_with("rtk gh pr list --repo <repo:"));
+        let r = redact_command("rtk gh pr list -R acme/private");
+        assert!(!r.contains("acme"));
+    }
+
+    #[test]
+    fn test_redact_is_stable() {
+        let a = redact_command("rtk read /etc/hosts");
+        let b = redact_command("rtk read /etc/hosts");
+        assert_eq!(a, b);
+        assert_ne!(a, redact_command("rtk read /etc/passwd"));
+    }
+
+    #[test]
+    fn test_redact_username() {
+        assert_eq!(
+            redact_username("rtk grep alice src", "alice"),
+            "rtk grep <user> src"
+        );
+        // Only whole words
+        assert_eq!(
+            redact_username("rtk grep malice", "alice"),
+            "rtk grep malice"
+        );
+    }
+}
@@ -0,0 +1,318 @@
+use crate::tracking;
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::process::Command;
+
+/// Sections in output order: conventional type, heading.
+const SECTIONS: &[(&str, &str)] = &[
+    ("feat", "Features"),
+    ("fix", "Fixes"),
+    ("perf", "Performance"),
+    ("refactor", "Refactoring"),
+    ("docs", "Documentation"),
+];
+
+/// Types folded into one maintenance line instead of listed.
+const NOISE_TYPES: &[&str] = &["chore", "ci", "build", "style", "test", "tests", "release"];
+
+/// Bumped packages named before the rest are counted.
+const MAX_BUMPS: usize = 8;
+
+lazy_static! {
+    static ref CONVENTIONAL: Regex = Regex::new(r"^(\w+)(?:\(([^)]*)\))?(!)?:\s*(.+)$").unwrap();
+    static ref BUMP: Regex = Regex::new(r"(?i)\bbump (\S+) from (\S+) to (\S+)").unwrap();
+}
+
+#[derive(Debug, PartialEq)]
+struct Commit {
+    sha: String,
+    subject: String,
+    author: String,
+    body: String,
+}
+
+/// Parses `git log --format=%h%x1f%s%x1f%an%x1f%b%x1e` output.
+fn parse_log(log: &str) -> Vec<Commit> {
+    log.split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').split('\x1f');
+            let sha = fields.next()?.trim();
+            if sha.is_empty() {
+                return None;
+            }
+            Some(Commit {
+                sha: sha.to_string(),
+                subject: fields.next()?.trim().to_string(),
+                author: fields.next().unwrap_or("").trim().to_string(),
+                body: fields.next().unwrap_or("").trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Default)]
+struct Notes {
+    breaking: Vec<String>,
+    /// Section type -> entries
+    sections: BTreeMap<&'static str, Vec<String>>,
+    other: Vec<String>,
+    /// Folded type -> count
+    noise: BTreeMap<String, usize>,
+    /// package -> (from, to), widest range kept
+    bumps: BTreeMap<String, (String, String)>,
+    reverts: usize,
+    authors: BTreeSet<String>,
+}
+
+fn entry(scope: Option<&str>, description: &str, sha: &str) -> String {
+    match scope.filter(|s| !s.is_empty()) {
+        Some(scope) => format!("- **{}:** {} ({})", scope, description, sha),
+        None => format!("- {} ({})", description, sha),
+    }
+}
+
+fn is_bot(author: &str) -> bool {
+    let author = author.to_lowercase();
+    author.contains("[bot]") || author.contains("dependabot") || author.contains("renovate")
+}
+
+fn classify(commits: &[Commit]) -> Notes {
+    let mut notes = Notes::default();
+    let mut seen = BTreeSet::new();
+    for commit in commits {
+        if !is_bot(&commit.author) {
+            notes.authors.insert(commit.author.clone());
+        }
+        // Cherry-picks and re-lands repeat a subject; list it once
+        if !seen.insert(commit.subject.clone()) {
+            continue;
+        }
+        if commit.subject.starts_with("Revert \"") {
+            notes.reverts += 1;
+            continue;
+        }
+        if let Some(caps) = BUMP.captures(&commit.subject) {
+            let range = notes
+                .bumps
+                .entry(caps[1].to_string())
+                .or_insert_with(|| (caps[2].to_string(), caps[3].to_string()));
+            // Log is newest first, so later entries hold older "from" versions
+            range.0 = caps[2].to_string();
+            continue;
+        }
+
+        let Some(caps) = CONVENTIONAL.captures(&commit.subject) else {
+            notes.other.push(entry(None, &commit.subject, &commit.sha));
+            continue;
+        };
+        let kind = caps[1].to_lowercase();
+        let scope = caps.get(2).map(|m| m.as_str());
+        let description = &caps[4];
+        let line = entry(scope, description, &commit.sha);
+
+        if caps.get(3).is_some() || commit.body.contains("BREAKING CHANGE") {
+            notes.breaking.push(line);
+        } else if let Some((key, _)) = SECTIONS.iter().find(|(key, _)| *key == kind) {
+            notes.sections.entry(key).or_default().push(line);
+        } else if NOISE_TYPES.contains(&kind.as_str()) || scope == Some("deps") {
+            *notes.noise.entry(kind).or_default() += 1;
+        } else {
+            notes.other.push(line);
+        }
+    }
+    notes
+}
+
+fn format_notes(range: &str, commits: usize, notes: &Notes) -> String {
+    let mut out = vec![format!(
+        "## Changes in {} ({} commits, {} contributors)",
+        range,
+        commits,
+        notes.authors.len()
+    )];
+
+    let mut section = |title: &str, entries: &[String]| {
+        if entries.is_empty() {
+            return;
+        }
+        out.push(String::new());
+        out.push(format!("### {}", title));
+        let mut sorted = entries.to_vec();
+        // Scoped entries group together; unscoped ones keep log order
+        sorted.sort_by_key(|e| !e.starts_with("- **"));
+        out.extend(sorted);
+    };
+    section("⚠️ Breaking changes", &notes.breaking);
+    for (key, title) in SECTIONS {
+        if let Some(entries) = notes.sections.get(key) {
+            section(title, entries);
+        }
+    }
+    section("Other", &notes.other);
+
+    let mut maintenance = Vec::new();
+    if !notes.bumps.is_empty() {
+        let mut bumps: Vec<String> = notes
+            .bumps
+            .iter()
+            .take(MAX_BUMPS)
+            .map(|(pkg, (from, to))| format!("{} {}→{}", pkg, from, to))
+            .collect();
+        if notes.bumps.len() > MAX_BUMPS {
+            bumps.push(format!("+{} more", notes.bumps.len() - MAX_BUMPS));
+        }
+        maintenance.push(format!(
+            "- {} dependency updates: {}",
+            notes.bumps.len(),
+            bumps.join(", ")
+        ));
+    }
+    if !notes.noise.is_empty() {
+        let counts: Vec<String> = notes
+            .noise
+            .iter()
+            .map(|(kind, n)| format!("{} {}", kind, n))
+            .collect();
+        maintenance.push(format!("- {}", counts.join(", ")));
+    }
+    if notes.reverts > 0 {
+        maintenance.push(format!("- {} reverts", notes.reverts));
+    }
+    if !maintenance.is_empty() {
+        out.push(String::new());
+        out.push("### Maintenance".to_string());
+        out.extend(maintenance);
+    }
+
+    out.join("\n")
+}
+
+/// `<latest tag>..HEAD`, or all of history when there are no tags.
+fn default_range() -> String {
+    Command::new("git")
+        .args(["describe", "--tags", "--abbrev=0"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .map_or_else(|| "HEAD".to_string(), |tag| format!("{}..HEAD", tag))
+}
+
+pub fn run(range: Option<&str>, verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let range = range.map_or_else(default_range, str::to_string);
+    if verbose > 0 {
+        eprintln!("git log --no-merges {}", range);
+    }
+
+    let output = Command::new("git")
+        .args([
+            "log",
+            "--no-merges",
+            "--format=%h%x1f%s%x1f%an%x1f%b%x1e",
+            &range,
+            "--",
+        ])
+        .output()
+        .context("Failed to run git log")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git log {} failed: {}",
+            range,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let log = String::from_utf8_lossy(&output.stdout);
+    let commits = parse_log(&log);
+    let notes = classify(&commits);
+    let filtered = format_notes(&range, commits.len(), &notes);
+    println!("{}", filtered);
+
+    timer.track(
+        &format!("git log {}", range),
+        &format!("rtk relnotes {}", range),
+        &log.replace(['\x1e', '\x1f'], "\n"),
+        &filtered,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(sha: &str, subject: &str, author: &str, body: &str) -> String {
+        format!("{}\x1f{}\x1f{}\x1f{}\x1e\n", sha, subject, author, body)
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let log = commit("a1", "feat: x", "Alice", "line one\nline two");
+        let commits = parse_log(&log);
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].body, "line one\nline two");
+    }
+
+    #[test]
+    fn test_release_notes() {
+        let log = [
+            commit("a1", "feat(api): add /v2/search (#12)", "Alice", ""),
+            commit("b2", "fix: handle empty query", "Bob", ""),
+            commit("c3", "feat!: drop Python 3.8", "Alice", ""),
+            commit(
+                "d4",
+                "refactor(db): split pool module",
+                "Bob",
+                "BREAKING CHANGE: pool moved",
+            ),
+            commit(
+                "e5",
+                "chore(deps): bump serde from 1.0.2 to 1.0.3",
+                "dependabot[bot]",
+                "",
+            ),
+            commit(
+                "f6",
+                "chore(deps): bump serde from 1.0.1 to 1.0.2",
+                "dependabot[bot]",
+                "",
+            ),
+            commit("g7", "Bump tokio from 1.37 to 1.38", "renovate[bot]", ""),
+            commit("h8", "ci: cache cargo", "Bob", ""),
+            commit("i9", "chore: release prep", "Alice", ""),
+            commit("j0", "Revert \"feat: flaky thing\"", "Bob", ""),
+            commit("k1", "Update README wording", "Carol", ""),
+            commit("l2", "fix: handle empty query", "Bob", ""),
+            commit("m3", "feat: add --json flag", "Carol", ""),
+        ]
+        .concat();
+        let commits = parse_log(&log);
+        let out = format_notes("v1.2..HEAD", commits.len(), &classify(&commits));
+        assert_eq!(
+            out,
+            "## Changes in v1.2..HEAD (13 commits, 3 contributors)
+
+### ⚠️ Breaking changes
+- **db:** split pool module (d4)
+- drop Python 3.8 (c3)
+
+### Features
+- **api:** add /v2/search (#12) (a1)
+- add --json flag (m3)
+
+### Fixes
+- handle empty query (b2)
+
+### Other
+- Update README wording (k1)
+
+### Maintenance
+- 2 dependency updates: serde 1.0.1→1.0.3, tokio 1.37→1.38
+- chore 1, ci 1
+- 1 reverts"
+        );
+    }
+}
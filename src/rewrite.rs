@@ -0,0 +1,721 @@
+//! Command rewriting behind the agent hook (`rtk rewrite`).
+//!
+//! Turns the shell command an agent is about to run into its rtk equivalent
+//! when that is safe:
+//! - `&&`, `||` and `;` chains are rewritten segment by segment; `cd` and
+//!   segments rtk does not handle are kept verbatim.
+//! - Pipelines are left alone, except `cat F | head -N` which becomes
+//!   `rtk cat F --max-lines N`.
+//! - Heredocs, command substitution, background jobs and redirections to or
+//!   from files are never rewritten.
+//! - Lines with a command protected by the safety policy (`rm`, editors,
+//!   file writers, `[policy] never`, see `policy.rs`) are left as written.
+//! - `[rewrite] allow` restricts the programs rewritten, `[rewrite] deny`
+//!   globs exclude commands entirely.
+//!
+//! A rewrite is read-only when every segment is `cd` or a read-only command;
+//! the hook auto-approves those (they were auto-allowed before the rewrite).
+
+use crate::config::{Config, RewriteConfig};
+use crate::exit_code;
+use crate::policy::Policy;
+use crate::tracking::glob_match;
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+/// Log rotated to `rewrites.jsonl.1` past this size.
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+
+/// Programs prefixed with `rtk` as-is; unfiltered subcommands hit rtk's
+/// passthrough handlers and run unmodified.
+const DIRECT: &[&str] = &[
+    "git",
+    "gh",
+    "cargo",
+    "cat",
+    "grep",
+    "rg",
+    "ls",
+    "find",
+    "fd",
+    "tree",
+    "diff",
+    "docker",
+    "kubectl",
+    "curl",
+    "wget",
+    "vitest",
+    "tsc",
+    "eslint",
+    "prettier",
+    "playwright",
+    "prisma",
+    "npm",
+    "pnpm",
+    "yarn",
+    "pytest",
+    "ruff",
+    "pip",
+    "poetry",
+    "go",
+    "golangci-lint",
+    "semgrep",
+    "trivy",
+    "grype",
+    "systemctl",
+    "brew",
+    "apt",
+    "apt-get",
+    "dnf",
+    "yum",
+    "lighthouse",
+    "adb",
+    "gradle",
+    "xcodebuild",
+    "swift",
+];
+
+/// Commands Claude Code already auto-allows (matched on whole words).
+const READ_ONLY: &[&str] = &[
+    "git status",
+    "git log",
+    "git diff",
+    "git show",
+    "git branch",
+    "git stash list",
+    "git remote",
+    "cat",
+    "grep",
+    "rg",
+    "ls",
+    "find",
+    "fd",
+    "tree",
+    "diff",
+    "head",
+    "pnpm list",
+    "pnpm ls",
+    "pnpm outdated",
+    "pip list",
+    "pip show",
+    "pip outdated",
+    "gh pr view",
+    "gh pr list",
+    "gh pr diff",
+    "gh issue view",
+    "gh issue list",
+    "gh run view",
+    "gh run list",
+    "docker ps",
+    "docker images",
+    "docker logs",
+    "kubectl get",
+    "kubectl logs",
+    "go vet",
+    "golangci-lint",
+    "eslint",
+    "ruff check",
+    "prettier --check",
+];
+
+lazy_static! {
+    static ref ENV_PREFIX: Regex =
+        Regex::new(r#"^(?:[A-Za-z_][A-Za-z0-9_]*=(?:'[^']*'|"[^"]*"|\S*)\s+)*"#).unwrap();
+    static ref GIT_C: Regex = Regex::new(r"^git\s+-C\s+(\S+)\s+(.+)$").unwrap();
+    static ref PNPM_LAUNCHER: Regex =
+        Regex::new(r"^pnpm\s+(tsc|lint|test|vitest|playwright)(\s|$)").unwrap();
+    static ref PYTHON_PYTEST: Regex = Regex::new(r"^python3?\s+-m\s+(pytest(?:\s.*)?)$").unwrap();
+    static ref HEAD_FILE: Regex =
+        Regex::new(r"^head\s+(?:-n\s*|--lines=|-)(\d+)\s+(\S+)$").unwrap();
+    static ref CAT_FILE: Regex = Regex::new(r"^cat\s+(\S+)$").unwrap();
+    static ref HEAD_STDIN: Regex = Regex::new(r"^head\s+(?:-n\s*|--lines=|-)(\d+)$").unwrap();
+    static ref LSOF_PORT: Regex = Regex::new(
+        r"^lsof\s+(?:-[nP]+\s+)*-i\s*(?:(?i:tcp|udp))?:(\d+)(?:\s+-[nP]+|\s+-sTCP:LISTEN)*$"
+    )
+    .unwrap();
+    static ref LISTENING_SOCKETS: Regex = Regex::new(r"^(?:ss|netstat)\s+-([tulnpH]+)$").unwrap();
+    /// Formatter check modes print whole diffs
+    static ref FMT_CHECK: Regex = Regex::new(
+        r"^(?:(?:cargo\s+fmt|rustfmt|black|ruff\s+format|prettier)\s(?:.*\s)?--check|gofmt\s+(?:.*\s)?-[ld])(?:\s|$)"
+    )
+    .unwrap();
+    static ref FD_DUP: Regex = Regex::new(r"^&\d|^\s*/dev/null(\s|$)").unwrap();
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Rewrite {
+    pub command: String,
+    /// Every segment is read-only: safe to auto-approve
+    pub read_only: bool,
+}
+
+enum Segment {
+    Cd,
+    Kept,
+    Rewritten { command: String, read_only: bool },
+}
+
+/// Rewrite `cmd` through rtk, or `None` when nothing would change.
+pub fn rewrite(cmd: &str, config: &RewriteConfig, policy: &Policy) -> Option<Rewrite> {
+    if !config.enabled || cmd.contains("<<") || cmd.contains("$(") || cmd.contains('`') {
+        return None;
+    }
+    if denied(cmd.trim(), config) || policy.check(cmd).is_some() {
+        return None;
+    }
+    let mask = unquoted_mask(cmd);
+    if has_background_job(cmd, &mask) {
+        return None;
+    }
+
+    let parts = split_chain(cmd, &mask);
+    let mut out = String::with_capacity(cmd.len() + 16);
+    let mut read_only = true;
+    let mut changed = false;
+    for (segment, separator) in parts {
+        let body = segment.trim();
+        let lead = &segment[..segment.len() - segment.trim_start().len()];
+        let trail = &segment[segment.trim_end().len()..];
+        match rewrite_segment(body, config) {
+            Segment::Cd => out.push_str(segment),
+            Segment::Kept => {
+                read_only = false;
+                out.push_str(segment);
+            }
+            Segment::Rewritten {
+                command,
+                read_only: segment_read_only,
+            } => {
+                changed = true;
+                read_only &= segment_read_only;
+                out.push_str(lead);
+                out.push_str(&command);
+                out.push_str(trail);
+            }
+        }
+        out.push_str(separator);
+    }
+
+    changed.then_some(Rewrite {
+        command: out,
+        read_only,
+    })
+}
+
+fn rewrite_segment(segment: &str, config: &RewriteConfig) -> Segment {
+    if segment == "cd" || segment.starts_with("cd ") {
+        return Segment::Cd;
+    }
+    if segment.is_empty() || denied(segment, config) {
+        return Segment::Kept;
+    }
+    let mask = unquoted_mask(segment);
+    if has_file_redirect(segment, &mask) {
+        return Segment::Kept;
+    }
+
+    let pipeline = split_unquoted(segment, &mask, b'|');
+    if pipeline.len() > 1 {
+        return match cat_head(&pipeline) {
+            Some(command) if allowed("cat", "cat", config) => Segment::Rewritten {
+                command,
+                read_only: true,
+            },
+            _ => Segment::Kept,
+        };
+    }
+
+    let env_len = ENV_PREFIX.find(segment).map_or(0, |m| m.end());
+    let (env, body) = segment.split_at(env_len);
+    if body.starts_with("rtk ") {
+        return Segment::Kept;
+    }
+
+    // `git -C p` → `(cd p && …)`: the subshell keeps the agent's cwd unchanged
+    let (cd, body) = match GIT_C.captures(body) {
+        Some(caps) => (Some(caps[1].to_string()), format!("git {}", &caps[2])),
+        None => (None, body.to_string()),
+    };
+    let Some((command, head_transform)) = rewrite_command(&body) else {
+        return Segment::Kept;
+    };
+    if !allowed(program(&body), program(&command[4..]), config) {
+        return Segment::Kept;
+    }
+    let command = format!("{}{}", env, command);
+    Segment::Rewritten {
+        command: match cd {
+            Some(dir) => format!("(cd {} && {})", dir, command),
+            None => command,
+        },
+        read_only: head_transform || is_read_only(&body),
+    }
+}
+
+/// The rtk form of a single command and whether it came from a `head` or
+/// port-lookup transform (always read-only).
+fn rewrite_command(body: &str) -> Option<(String, bool)> {
+    let first = program(body);
+    let rest = body[first.len()..].trim_start();
+    if first == "npx" && !rest.is_empty() {
+        return Some((format!("rtk {}", rest), false));
+    }
+    if PNPM_LAUNCHER.is_match(body) {
+        return Some((format!("rtk {}", rest), false));
+    }
+    if let Some(caps) = PYTHON_PYTEST.captures(body) {
+        return Some((format!("rtk {}", &caps[1]), false));
+    }
+    if FMT_CHECK.is_match(body) && !body.contains("--write") {
+        return Some((format!("rtk fmt-check {}", body), true));
+    }
+    if first == "uv" && rest.starts_with("pip ") {
+        return Some((format!("rtk {}", rest), false));
+    }
+    if DIRECT.contains(&first) {
+        return Some((format!("rtk {}", body), false));
+    }
+    if let Some(caps) = HEAD_FILE.captures(body) {
+        return Some((
+            format!("rtk cat {} --max-lines {}", &caps[2], &caps[1]),
+            true,
+        ));
+    }
+    if let Some(caps) = LSOF_PORT.captures(body) {
+        return Some((format!("rtk ports {}", &caps[1]), true));
+    }
+    if LISTENING_SOCKETS
+        .captures(body)
+        .is_some_and(|caps| caps[1].contains('l'))
+    {
+        return Some(("rtk ports".to_string(), true));
+    }
+    None
+}
+
+/// `cat F | head -N` → `rtk cat F --max-lines N`.
+fn cat_head(pipeline: &[&str]) -> Option<String> {
+    let [cat, head] = pipeline else {
+        return None;
+    };
+    let file = CAT_FILE.captures(cat.trim())?;
+    let lines = HEAD_STDIN.captures(head.trim())?;
+    Some(format!("rtk cat {} --max-lines {}", &file[1], &lines[1]))
+}
+
+fn program(command: &str) -> &str {
+    command.split_whitespace().next().unwrap_or("")
+}
+
+fn is_read_only(body: &str) -> bool {
+    // `find` can delete or run arbitrary commands
+    if program(body) == "find"
+        && body
+            .split_whitespace()
+            .any(|arg| matches!(arg, "-delete" | "-exec" | "-execdir" | "-ok"))
+    {
+        return false;
+    }
+    if program(body) == "fd"
+        && body.split_whitespace().any(|arg| {
+            matches!(arg, "-x" | "-X" | "--exec" | "--exec-batch")
+                || arg.starts_with("--exec=")
+                || arg.starts_with("--exec-batch=")
+        })
+    {
+        return false;
+    }
+    READ_ONLY
+        .iter()
+        .any(|prefix| body == *prefix || body.starts_with(&format!("{} ", prefix)))
+}
+
+/// Allowed when the list is empty or names the original program or the
+/// rtk subcommand it becomes (`npx tsc` matches "npx" or "tsc").
+fn allowed(original: &str, target: &str, config: &RewriteConfig) -> bool {
+    config.allow.is_empty()
+        || config
+            .allow
+            .iter()
+            .any(|name| name == original || name == target)
+}
+
+fn denied(command: &str, config: &RewriteConfig) -> bool {
+    config
+        .deny
+        .iter()
+        .any(|pattern| glob_match(pattern, command))
+}
+
+/// Per byte: true when outside quotes and not backslash-escaped.
+fn unquoted_mask(cmd: &str) -> Vec<bool> {
+    let mut mask = Vec::with_capacity(cmd.len());
+    let (mut single, mut double, mut escaped) = (false, false, false);
+    for &b in cmd.as_bytes() {
+        let quoted = single || double || escaped;
+        mask.push(!quoted && b != b'\'' && b != b'"' && b != b'\\');
+        if escaped {
+            escaped = false;
+        } else if b == b'\\' && !single {
+            escaped = true;
+        } else if b == b'\'' && !double {
+            single = !single;
+        } else if b == b'"' && !single {
+            double = !double;
+        }
+    }
+    mask
+}
+
+/// Split on unquoted `&&`, `||` and `;`, keeping each separator (with its
+/// surrounding text untouched) after its segment.
+fn split_chain<'a>(cmd: &'a str, mask: &[bool]) -> Vec<(&'a str, &'a str)> {
+    let bytes = cmd.as_bytes();
+    let mut parts = Vec::new();
+    let (mut start, mut i) = (0, 0);
+    while i < bytes.len() {
+        let len = match bytes[i] {
+            b';' if mask[i] => 1,
+            b'&' | b'|' if mask[i] && bytes.get(i + 1) == Some(&bytes[i]) => 2,
+            _ => 0,
+        };
+        if len > 0 {
+            parts.push((&cmd[start..i], &cmd[i..i + len]));
+            start = i + len;
+            i += len;
+        } else {
+            i += 1;
+        }
+    }
+    parts.push((&cmd[start..], ""));
+    parts
+}
+
+fn split_unquoted<'a>(segment: &'a str, mask: &[bool], sep: u8) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (i, &b) in segment.as_bytes().iter().enumerate() {
+        if b == sep && mask[i] {
+            parts.push(&segment[start..i]);
+            start = i + 1;
+        }
+    }
+    parts.push(&segment[start..]);
+    parts
+}
+
+/// A lone unquoted `&` (not `&&`, `>&`, `&>`).
+fn has_background_job(cmd: &str, mask: &[bool]) -> bool {
+    let bytes = cmd.as_bytes();
+    (0..bytes.len()).any(|i| {
+        bytes[i] == b'&'
+            && mask[i]
+            && !matches!(bytes.get(i + 1), Some(b'&' | b'>'))
+            && !matches!(i.checked_sub(1).map(|p| bytes[p]), Some(b'&' | b'>' | b'<'))
+    })
+}
+
+/// Unquoted `<` or `>` other than fd duplication (`2>&1`) or `/dev/null`.
+fn has_file_redirect(segment: &str, mask: &[bool]) -> bool {
+    let bytes = segment.as_bytes();
+    (0..bytes.len()).any(|i| match bytes[i] {
+        b'<' if mask[i] => true,
+        b'>' if mask[i] => {
+            let target = segment[i + 1..].trim_start_matches('>');
+            !FD_DUP.is_match(target)
+        }
+        _ => false,
+    })
+}
+
+#[derive(Serialize, Deserialize)]
+struct LogEntry {
+    ts: String,
+    original: String,
+    rewritten: String,
+    read_only: bool,
+}
+
+fn log_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rtk")
+        .join("rewrites.jsonl")
+}
+
+fn append_log(original: &str, rewrite: &Rewrite) -> Result<()> {
+    let path = log_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::metadata(&path).is_ok_and(|m| m.len() > MAX_LOG_BYTES) {
+        fs::rename(&path, path.with_extension("jsonl.1"))?;
+    }
+    let entry = LogEntry {
+        ts: chrono::Local::now().to_rfc3339(),
+        original: original.to_string(),
+        rewritten: rewrite.command.clone(),
+        read_only: rewrite.read_only,
+    };
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    file.write_all(format!("{}\n", serde_json::to_string(&entry)?).as_bytes())?;
+    Ok(())
+}
+
+/// `rtk rewrite <command>`: print the rewrite, exit 1 when there is none.
+pub fn run(command: &[String]) -> Result<()> {
+    let cmd = command.join(" ");
+    match rewrite(&cmd, &Config::cached().rewrite, &Policy::load()) {
+        Some(rewrite) => {
+            println!("{}", rewrite.command);
+            if rewrite.read_only {
+                println!("(read-only: auto-approved by the hook)");
+            }
+        }
+        None => exit_code::set(1),
+    }
+    Ok(())
+}
+
+/// `rtk rewrite --hook`: PreToolUse JSON on stdin, hook response on stdout
+/// (nothing when the command is left alone).
+pub fn run_hook() -> Result<()> {
+    let mut input = String::new();
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .context("Failed to read hook input")?;
+    let Ok(input) = serde_json::from_str::<serde_json::Value>(&input) else {
+        return Ok(());
+    };
+    let Some(cmd) = input
+        .pointer("/tool_input/command")
+        .and_then(|c| c.as_str())
+    else {
+        return Ok(());
+    };
+    let config = &Config::cached().rewrite;
+    let Some(rewrite) = rewrite(cmd, config, &Policy::load()) else {
+        tracing::debug!(command = cmd, "hook: left alone");
+        return Ok(());
+    };
+    tracing::debug!(
+        command = cmd,
+        rewritten = %rewrite.command,
+        read_only = rewrite.read_only,
+        "hook: rewritten"
+    );
+    if config.log {
+        let _ = append_log(cmd, &rewrite);
+    }
+
+    // Keep every original tool_input field, only the command changes
+    let mut updated = input["tool_input"].clone();
+    updated["command"] = rewrite.command.clone().into();
+    let mut output = serde_json::json!({ "hookEventName": "PreToolUse" });
+    if rewrite.read_only {
+        output["permissionDecision"] = "allow".into();
+        output["permissionDecisionReason"] = "RTK rewrite of read-only command".into();
+    }
+    output["updatedInput"] = updated;
+    // Bypass the output profile and style: the agent parses this verbatim
+    ::std::println!("{}", serde_json::json!({ "hookSpecificOutput": output }));
+    Ok(())
+}
+
+/// `rtk rewrite --log [N]`: the last N logged rewrites.
+pub fn show_log(limit: usize) -> Result<()> {
+    let path = log_path();
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(_) => {
+            println!("No rewrites logged yet ({})", path.display());
+            return Ok(());
+        }
+    };
+    let entries: Vec<LogEntry> = content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    for entry in &entries[entries.len().saturating_sub(limit)..] {
+        let ts = chrono::DateTime::parse_from_rfc3339(&entry.ts)
+            .map(|t| t.format("%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let mark = if entry.read_only {
+            " (auto-allowed)"
+        } else {
+            ""
+        };
+        println!("{}  {}", ts, entry.original);
+        println!("         → {}{}", entry.rewritten, mark);
+    }
+    println!("{} rewrites logged in {}", entries.len(), path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rw(cmd: &str) -> Option<(String, bool)> {
+        rewrite(cmd, &RewriteConfig::default(), &Policy::default())
+            .map(|r| (r.command, r.read_only))
+    }
+
+    fn cmd(cmd: &str) -> Option<String> {
+        rw(cmd).map(|(c, _)| c)
+    }
+
+    #[test]
+    fn test_rewrite_simple_and_launchers() {
+        assert_eq!(rw("git status"), Some(("rtk git status".into(), true)));
+        assert_eq!(rw("git push"), Some(("rtk git push".into(), false)));
+        assert_eq!(cmd("npx tsc --noEmit").as_deref(), Some("rtk tsc --noEmit"));
+        assert_eq!(cmd("pnpm vitest run").as_deref(), Some("rtk vitest run"));
+        assert_eq!(cmd("python -m pytest -x").as_deref(), Some("rtk pytest -x"));
+        assert_eq!(cmd("uv pip list").as_deref(), Some("rtk pip list"));
+        assert_eq!(
+            cmd("RUST_LOG=debug cargo test").as_deref(),
+            Some("RUST_LOG=debug rtk cargo test")
+        );
+        assert_eq!(cmd("rtk git status"), None);
+        assert_eq!(cmd("make build"), None);
+    }
+
+    #[test]
+    fn test_rewrite_port_lookups() {
+        assert_eq!(rw("lsof -i :3000"), Some(("rtk ports 3000".into(), true)));
+        assert_eq!(
+            cmd("lsof -nP -iTCP:8080 -sTCP:LISTEN").as_deref(),
+            Some("rtk ports 8080")
+        );
+        assert_eq!(cmd("ss -tulpn").as_deref(), Some("rtk ports"));
+        assert_eq!(cmd("netstat -tlnp").as_deref(), Some("rtk ports"));
+        // Not a listening-socket listing
+        assert_eq!(cmd("ss -tn"), None);
+        assert_eq!(cmd("lsof -p 1234"), None);
+    }
+
+    #[test]
+    fn test_rewrite_formatter_checks() {
+        assert_eq!(
+            rw("cargo fmt --all -- --check"),
+            Some(("rtk fmt-check cargo fmt --all -- --check".into(), true))
+        );
+        assert_eq!(
+            cmd("black --check .").as_deref(),
+            Some("rtk fmt-check black --check .")
+        );
+        assert_eq!(
+            cmd("gofmt -l .").as_deref(),
+            Some("rtk fmt-check gofmt -l .")
+        );
+        assert_eq!(cmd("cargo fmt").as_deref(), Some("rtk cargo fmt"));
+        assert_eq!(cmd("black ."), None);
+    }
+
+    #[test]
+    fn test_rewrite_head_and_cat_pipeline() {
+        assert_eq!(
+            rw("head -50 src/main.rs"),
+            Some(("rtk cat src/main.rs --max-lines 50".into(), true))
+        );
+        assert_eq!(
+            cmd("head -n 20 README.md").as_deref(),
+            Some("rtk cat README.md --max-lines 20")
+        );
+        assert_eq!(
+            rw("cat src/main.rs | head -50"),
+            Some(("rtk cat src/main.rs --max-lines 50".into(), true))
+        );
+        assert_eq!(cmd("cat a.log | grep error"), None);
+        assert_eq!(cmd("git log | wc -l"), None);
+    }
+
+    #[test]
+    fn test_rewrite_chains() {
+        assert_eq!(
+            rw("cd /repo && git diff HEAD~3"),
+            Some(("cd /repo && rtk git diff HEAD~3".into(), true))
+        );
+        // A mutating segment makes the whole rewrite need approval
+        assert_eq!(
+            rw("make && git status"),
+            Some(("make && rtk git status".into(), false))
+        );
+        // Protected commands leave the whole line alone
+        assert_eq!(rw("rm -rf build && git status"), None);
+        assert_eq!(
+            cmd("cargo fmt; cargo clippy || echo 'a && b'").as_deref(),
+            Some("rtk cargo fmt; rtk cargo clippy || echo 'a && b'")
+        );
+        assert_eq!(
+            cmd("git -C ../lib status").as_deref(),
+            Some("(cd ../lib && rtk git status)")
+        );
+        assert_eq!(
+            rw("git -C ../lib status && ls"),
+            Some(("(cd ../lib && rtk git status) && rtk ls".into(), true))
+        );
+    }
+
+    #[test]
+    fn test_rewrite_skips_unsafe_shapes() {
+        assert_eq!(cmd("cat <<EOF\nx\nEOF"), None);
+        assert_eq!(cmd("git log $(git merge-base a b)"), None);
+        assert_eq!(cmd("cargo build &"), None);
+        assert_eq!(cmd("git diff > out.patch"), None);
+        assert_eq!(cmd("grep x < input.txt"), None);
+        assert_eq!(
+            cmd("cargo test 2>&1").as_deref(),
+            Some("rtk cargo test 2>&1")
+        );
+        assert_eq!(cmd("ls 2>/dev/null").as_deref(), Some("rtk ls 2>/dev/null"));
+        assert_eq!(
+            cmd("grep '>' file.txt").as_deref(),
+            Some("rtk grep '>' file.txt")
+        );
+        assert_eq!(rw("find . -name '*.tmp' -delete"), None);
+        assert_eq!(rw("fd -e tmp -x rm"), None);
+        assert_eq!(cmd("git log | less"), None);
+    }
+
+    #[test]
+    fn test_rewrite_allow_and_deny() {
+        let config = RewriteConfig {
+            allow: vec!["git".into(), "tsc".into()],
+            deny: vec!["git push*".into()],
+            ..RewriteConfig::default()
+        };
+        let apply = |c: &str| rewrite(c, &config, &Policy::default()).map(|r| r.command);
+        assert_eq!(apply("git status").as_deref(), Some("rtk git status"));
+        assert_eq!(apply("npx tsc").as_deref(), Some("rtk tsc"));
+        assert_eq!(apply("cargo test"), None);
+        assert_eq!(apply("git push origin main"), None);
+        assert_eq!(
+            apply("git add . && git push").as_deref(),
+            Some("rtk git add . && git push")
+        );
+
+        let disabled = RewriteConfig {
+            enabled: false,
+            ..RewriteConfig::default()
+        };
+        assert_eq!(rewrite("git status", &disabled, &Policy::default()), None);
+    }
+
+    #[test]
+    fn test_unquoted_mask() {
+        let mask = unquoted_mask(r#"a "b|c" 'd' \;"#);
+        let free: String =
+            r#"a "b|c" 'd' \;"#.chars().zip(&mask).filter(|(_, m)| **m).map(|(c, _)| c).collect();
+        assert_eq!(free, "a   ");
+    }
+}
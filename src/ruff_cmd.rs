@@ -1,3 +1,4 @@
+use crate::exit_code;
 use crate::tracking;
 use crate::utils::truncate;
 use anyhow::{Context, Result};
@@ -84,6 +85,7 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
     let filtered = if is_check && !stdout.trim().is_empty() {
         filter_ruff_check_json(&stdout)
@@ -96,7 +98,7 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
 
     println!("{}", filtered);
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("ruff {}", args.join(" ")),
         &format!("rtk ruff {}", args.join(" ")),
         &raw,
@@ -105,7 +107,7 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
 
     // Preserve exit code for CI/CD
     if !output.status.success() {
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     Ok(())
@@ -1,244 +1,271 @@
+use crate::err_extract::{self, ErrorExtractor, Severity};
+use crate::policy::{self, Policy};
+use crate::stream::{self, RawInput};
+use crate::test_history;
 use crate::tracking;
 use anyhow::{Context, Result};
-use regex::Regex;
-use std::process::{Command, Stdio};
+use std::collections::{BTreeSet, VecDeque};
 
 /// Run a command and filter output to show only errors/warnings
-pub fn run_err(command: &str, verbose: u8) -> Result<()> {
+///
+/// The first error of each file is printed as soon as the command emits it;
+/// repeat errors, warnings and notes below `min` follow once it exits.
+pub fn run_err(command: &str, min: Severity, verbose: u8) -> Result<()> {
+    if let Some(protected) = Policy::load().check(command) {
+        let mut cmd = stream::shell(command);
+        return policy::run_unfiltered(&mut cmd, command, "rtk err", &protected, verbose);
+    }
+
     let timer = tracking::TimedExecution::start();
 
     if verbose > 0 {
         eprintln!("Running: {}", command);
     }
 
-    let output = if cfg!(target_os = "windows") {
-        Command::new("cmd")
-            .args(["/C", command])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-    } else {
-        Command::new("sh")
-            .args(["-c", command])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-    }
-    .context("Failed to execute command")?;
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let raw = format!("{}\n{}", stdout, stderr);
-    let filtered = filter_errors(&raw);
-    let mut rtk = String::new();
-
-    if filtered.is_empty() {
-        if output.status.success() {
+    let mut extractor = ErrorExtractor::prioritized(min);
+    let mut shown = Vec::new();
+    let streamed = stream::run(&mut stream::shell(command), |line| {
+        let out = extractor.feed(&line.text);
+        if out.is_empty() {
+            return None;
+        }
+        shown.extend(out.iter().cloned());
+        Some(out.join("\n"))
+    })?;
+    // A trace still open when the command exited
+    let rest = extractor.finish();
+    if !rest.is_empty() {
+        println!("{}", rest.join("\n"));
+        shown.extend(rest);
+    }
+
+    let mut rtk = shown.join("\n");
+    if let Some(notice) = streamed.timeout_notice() {
+        // Whatever was found so far is already on screen
+        let notice = format!("{}, output above is partial", notice);
+        println!("{}", notice);
+        rtk.push_str(&notice);
+    } else if shown.is_empty() {
+        if streamed.status.success() {
             rtk.push_str("✅ Command completed successfully (no errors)");
         } else {
             rtk.push_str(&format!(
                 "❌ Command failed (exit code: {:?})\n",
-                output.status.code()
+                streamed.status.code()
             ));
-            let lines: Vec<&str> = raw.lines().collect();
-            for line in lines.iter().rev().take(10).rev() {
-                rtk.push_str(&format!("  {}\n", line));
+            let skip = streamed.tail.len().saturating_sub(10);
+            for line in streamed.tail.iter().skip(skip) {
+                rtk.push_str(&format!("  {}\n", line.text));
             }
         }
-    } else {
-        rtk.push_str(&filtered);
+        println!("{}", rtk);
+    }
+
+    streamed.track(timer, command, "rtk err", &rtk);
+    Ok(())
+}
+
+/// Extract errors from piped input (`rtk err -`), printing each as it arrives.
+pub fn run_err_stdin(min: Severity, verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    if verbose > 0 {
+        eprintln!("Extracting errors from stdin");
+    }
+
+    let mut extractor = ErrorExtractor::prioritized(min);
+    let mut input = RawInput::default();
+    let mut shown = Vec::new();
+    stream::each_line(std::io::stdin().lock(), "stdin", |line| {
+        let out = extractor.feed(&line);
+        if !out.is_empty() {
+            println!("{}", out.join("\n"));
+            shown.extend(out);
+        }
+        input.push_line(&line);
+        true
+    })
+    .context("Failed to read from stdin")?;
+    let rest = extractor.finish();
+    if !rest.is_empty() {
+        println!("{}", rest.join("\n"));
+        shown.extend(rest);
     }
 
-    println!("{}", rtk);
-    timer.track(command, "rtk err", &raw, &rtk);
+    let mut rtk = shown.join("\n");
+    if shown.is_empty() {
+        rtk.push_str("✅ No errors found");
+        println!("{}", rtk);
+    }
+    input.track(timer, "cat - (stdin)", "rtk err -", &rtk);
     Ok(())
 }
 
 /// Run tests and show only failures
 pub fn run_test(command: &str, verbose: u8) -> Result<()> {
+    if let Some(protected) = Policy::load().check(command) {
+        let mut cmd = stream::shell(command);
+        return policy::run_unfiltered(&mut cmd, command, "rtk test", &protected, verbose);
+    }
+
     let timer = tracking::TimedExecution::start();
 
     if verbose > 0 {
         eprintln!("Running tests: {}", command);
     }
 
-    let output = if cfg!(target_os = "windows") {
-        Command::new("cmd")
-            .args(["/C", command])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-    } else {
-        Command::new("sh")
-            .args(["-c", command])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-    }
-    .context("Failed to execute test command")?;
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let raw = format!("{}\n{}", stdout, stderr);
+    let mut summary = TestSummary::new(command);
+    let streamed = stream::run(&mut stream::shell(command), |line| {
+        summary.feed(&line.text);
+        None
+    })?;
 
-    let summary = extract_test_summary(&raw, command);
+    let failed = std::mem::take(&mut summary.failed);
+    let mut summary = summary.finish();
+    if let Some(notice) = streamed.timeout_notice() {
+        summary = format!("{}, partial output follows\n{}", notice, summary);
+    } else if tracking::should_track(command, "rtk test") {
+        // A partial run would report every unreached failure as fixed
+        let delta = test_history::record_and_compare(command, &failed);
+        if !delta.is_empty() {
+            summary = format!("{}\n{}", summary, delta);
+        }
+    }
     println!("{}", summary);
-    timer.track(command, "rtk test", &raw, &summary);
+    streamed.track(timer, command, "rtk test", &summary);
     Ok(())
 }
 
-fn filter_errors(output: &str) -> String {
-    lazy_static::lazy_static! {
-        static ref ERROR_PATTERNS: Vec<Regex> = vec![
-            // Generic errors
-            Regex::new(r"(?i)^.*error[\s:\[].*$").unwrap(),
-            Regex::new(r"(?i)^.*\berr\b.*$").unwrap(),
-            Regex::new(r"(?i)^.*warning[\s:\[].*$").unwrap(),
-            Regex::new(r"(?i)^.*\bwarn\b.*$").unwrap(),
-            Regex::new(r"(?i)^.*failed.*$").unwrap(),
-            Regex::new(r"(?i)^.*failure.*$").unwrap(),
-            Regex::new(r"(?i)^.*exception.*$").unwrap(),
-            Regex::new(r"(?i)^.*panic.*$").unwrap(),
-            // Rust specific
-            Regex::new(r"^error\[E\d+\]:.*$").unwrap(),
-            Regex::new(r"^\s*--> .*:\d+:\d+$").unwrap(),
-            // Python
-            Regex::new(r"^Traceback.*$").unwrap(),
-            Regex::new(r#"^\s*File ".*", line \d+.*$"#).unwrap(),
-            // JavaScript/TypeScript
-            Regex::new(r"^\s*at .*:\d+:\d+.*$").unwrap(),
-            // Go
-            Regex::new(r"^.*\.go:\d+:.*$").unwrap(),
-        ];
-    }
-
-    let mut result = Vec::new();
-    let mut in_error_block = false;
-    let mut blank_count = 0;
+/// Errors and warnings of a captured output, as `rtk err` shows them.
+pub fn filter_errors(output: &str) -> String {
+    err_extract::extract(output)
+}
 
+/// Failures and summary of a captured test run, as `rtk test` shows them;
+/// `command` selects the framework-specific patterns.
+pub fn condense_test_output(command: &str, output: &str) -> String {
+    let mut summary = TestSummary::new(command);
     for line in output.lines() {
-        let is_error_line = ERROR_PATTERNS.iter().any(|p| p.is_match(line));
-
-        if is_error_line {
-            in_error_block = true;
-            blank_count = 0;
-            result.push(line.to_string());
-        } else if in_error_block {
-            if line.trim().is_empty() {
-                blank_count += 1;
-                if blank_count >= 2 {
-                    in_error_block = false;
-                } else {
-                    result.push(line.to_string());
-                }
-            } else if line.starts_with(' ') || line.starts_with('\t') {
-                // Continuation of error
-                result.push(line.to_string());
-                blank_count = 0;
-            } else {
-                in_error_block = false;
-            }
-        }
+        summary.feed(line);
     }
-
-    result.join("\n")
+    summary.finish()
 }
 
-fn extract_test_summary(output: &str, command: &str) -> String {
-    let mut result = Vec::new();
-    let lines: Vec<&str> = output.lines().collect();
+/// Streaming test summary: collects result lines and failures per framework,
+/// keeping only the last lines for the fallback.
+struct TestSummary {
+    is_cargo: bool,
+    is_pytest: bool,
+    is_jest: bool,
+    is_go: bool,
+    result: Vec<String>,
+    failures: Vec<String>,
+    failure_count: usize,
+    /// Identities of every failing test (see [`test_history::test_id`])
+    failed: BTreeSet<String>,
+    tail: VecDeque<String>,
+}
 
-    // Detect test framework
-    let is_cargo = command.contains("cargo test");
-    let is_pytest = command.contains("pytest");
-    let is_jest =
-        command.contains("jest") || command.contains("npm test") || command.contains("yarn test");
-    let is_go = command.contains("go test");
+impl TestSummary {
+    fn new(command: &str) -> Self {
+        // Detect test framework
+        Self {
+            is_cargo: command.contains("cargo test"),
+            is_pytest: command.contains("pytest"),
+            is_jest: command.contains("jest")
+                || command.contains("npm test")
+                || command.contains("yarn test"),
+            is_go: command.contains("go test"),
+            result: Vec::new(),
+            failures: Vec::new(),
+            failure_count: 0,
+            failed: BTreeSet::new(),
+            tail: VecDeque::new(),
+        }
+    }
 
-    // Collect failures
-    let mut failures = Vec::new();
-    let mut in_failure = false;
-    let mut failure_lines = Vec::new();
+    fn feed(&mut self, line: &str) {
+        let mut failure = false;
 
-    for line in lines.iter() {
         // Cargo test
-        if is_cargo {
+        if self.is_cargo {
             if line.contains("test result:") {
-                result.push(line.to_string());
-            }
-            if line.contains("FAILED") && !line.contains("test result") {
-                failures.push(line.to_string());
-            }
-            if line.starts_with("failures:") {
-                in_failure = true;
-            }
-            if in_failure && line.starts_with("    ") {
-                failure_lines.push(line.to_string());
+                self.result.push(line.to_string());
             }
+            failure |= line.contains("FAILED") && !line.contains("test result");
         }
 
         // Pytest
-        if is_pytest {
+        if self.is_pytest {
             if line.contains(" passed") || line.contains(" failed") || line.contains(" error") {
-                result.push(line.to_string());
-            }
-            if line.contains("FAILED") {
-                failures.push(line.to_string());
+                self.result.push(line.to_string());
             }
+            failure |= line.contains("FAILED");
         }
 
         // Jest
-        if is_jest {
+        if self.is_jest {
             if line.contains("Tests:") || line.contains("Test Suites:") {
-                result.push(line.to_string());
-            }
-            if line.contains("✕") || line.contains("FAIL") {
-                failures.push(line.to_string());
+                self.result.push(line.to_string());
             }
+            failure |= line.contains("✕") || line.contains("FAIL");
         }
 
         // Go test
-        if is_go {
+        if self.is_go {
             if line.starts_with("ok") || line.starts_with("FAIL") || line.starts_with("---") {
-                result.push(line.to_string());
-            }
-            if line.contains("FAIL") {
-                failures.push(line.to_string());
+                self.result.push(line.to_string());
             }
+            failure |= line.contains("FAIL");
         }
-    }
 
-    // Build output
-    let mut output = String::new();
-
-    if !failures.is_empty() {
-        output.push_str("❌ FAILURES:\n");
-        for f in failures.iter().take(10) {
-            output.push_str(&format!("  {}\n", f));
+        if failure {
+            self.failure_count += 1;
+            self.failed.extend(test_history::test_id(line));
+            // Only the first 10 are shown
+            if self.failures.len() < 10 {
+                self.failures.push(line.to_string());
+            }
         }
-        if failures.len() > 10 {
-            output.push_str(&format!("  ... +{} more failures\n", failures.len() - 10));
+        if self.tail.len() == 5 {
+            self.tail.pop_front();
         }
-        output.push('\n');
+        self.tail.push_back(line.to_string());
     }
 
-    if !result.is_empty() {
-        output.push_str("📊 SUMMARY:\n");
-        for r in &result {
-            output.push_str(&format!("  {}\n", r));
+    fn finish(self) -> String {
+        let mut output = String::new();
+
+        if !self.failures.is_empty() {
+            output.push_str("❌ FAILURES:\n");
+            for f in &self.failures {
+                output.push_str(&format!("  {}\n", f));
+            }
+            if self.failure_count > 10 {
+                output.push_str(&format!(
+                    "  ... +{} more failures\n",
+                    self.failure_count - 10
+                ));
+            }
+            output.push('\n');
         }
-    } else {
-        // Fallback: show last few lines
-        output.push_str("📊 OUTPUT (last 5 lines):\n");
-        let start = lines.len().saturating_sub(5);
-        for line in &lines[start..] {
-            if !line.trim().is_empty() {
-                output.push_str(&format!("  {}\n", line));
+
+        if !self.result.is_empty() {
+            output.push_str("📊 SUMMARY:\n");
+            for r in &self.result {
+                output.push_str(&format!("  {}\n", r));
+            }
+        } else {
+            // Fallback: show last few lines
+            output.push_str("📊 OUTPUT (last 5 lines):\n");
+            for line in &self.tail {
+                if !line.trim().is_empty() {
+                    output.push_str(&format!("  {}\n", line));
+                }
             }
         }
-    }
 
-    output
+        output
+    }
 }
 
 #[cfg(test)]
@@ -252,4 +279,32 @@ mod tests {
         assert!(filtered.contains("error"));
         assert!(!filtered.contains("info"));
     }
+
+    #[test]
+    fn test_summary_cargo_failures() {
+        let mut summary = TestSummary::new("cargo test");
+        for line in [
+            "running 3 tests",
+            "test a ... ok",
+            "test b ... FAILED",
+            "test result: FAILED. 2 passed; 1 failed",
+        ] {
+            summary.feed(line);
+        }
+        assert_eq!(summary.failed, BTreeSet::from(["b".to_string()]));
+        let out = summary.finish();
+        assert!(out.contains("❌ FAILURES:\n  test b ... FAILED\n"));
+        assert!(out.contains("📊 SUMMARY:\n  test result: FAILED. 2 passed; 1 failed\n"));
+    }
+
+    #[test]
+    fn test_summary_fallback_tail() {
+        let mut summary = TestSummary::new("make check");
+        for i in 0..8 {
+            summary.feed(&format!("line {}", i));
+        }
+        let out = summary.finish();
+        assert!(out.starts_with("📊 OUTPUT (last 5 lines):\n  line 3\n"));
+        assert!(out.ends_with("  line 7\n"));
+    }
 }
@@ -0,0 +1,307 @@
+//! Static-analysis findings: SARIF parsing and the grouped report shared by
+//! `rtk sarif` (CodeQL and any other SARIF producer) and `rtk semgrep`.
+//!
+//! Findings are grouped by severity, then rule; each rule shows its message
+//! and one code snippet once, followed by the `file:line` list.
+
+use crate::tracking;
+use crate::utils::truncate;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    /// SARIF `level` or semgrep `severity`, case-insensitive.
+    pub fn parse(level: &str) -> Self {
+        match level.to_ascii_lowercase().as_str() {
+            "error" | "critical" | "high" => Self::Error,
+            "note" | "info" | "none" | "low" | "recommendation" => Self::Note,
+            _ => Self::Warning,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Note => "note",
+        }
+    }
+
+    fn icon(self) -> &'static str {
+        match self {
+            Self::Error => "❌",
+            Self::Warning => "⚠️",
+            Self::Note => "ℹ️",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub rule: String,
+    pub severity: Severity,
+    pub file: String,
+    pub line: usize,
+    pub message: String,
+    /// Source of the flagged region, when the tool reports it
+    pub snippet: Option<String>,
+}
+
+/// Findings of every run in a SARIF log, with the first run's tool name.
+pub fn parse_sarif(json: &str) -> Result<(String, Vec<Finding>)> {
+    let log: Value = serde_json::from_str(json).context("Failed to parse SARIF JSON")?;
+    let runs = log["runs"]
+        .as_array()
+        .context("Not a SARIF log: missing \"runs\"")?;
+
+    let mut tool = None;
+    let mut findings = Vec::new();
+    for run in runs {
+        let driver = &run["tool"]["driver"];
+        tool.get_or_insert_with(|| driver["name"].as_str().unwrap_or("sarif").to_string());
+
+        // Rule defaults, for results that only carry a ruleIndex or no level
+        let rules: Vec<&Value> = driver["rules"]
+            .as_array()
+            .map(|r| r.iter().collect())
+            .unwrap_or_default();
+        let rule_levels: HashMap<&str, &str> = rules
+            .iter()
+            .filter_map(|r| {
+                Some((
+                    r["id"].as_str()?,
+                    r["defaultConfiguration"]["level"].as_str()?,
+                ))
+            })
+            .collect();
+
+        for result in run["results"].as_array().into_iter().flatten() {
+            let rule = result["ruleId"]
+                .as_str()
+                .or_else(|| {
+                    let index = result["ruleIndex"].as_u64()? as usize;
+                    rules.get(index)?["id"].as_str()
+                })
+                .unwrap_or("unknown")
+                .to_string();
+            let level = result["level"]
+                .as_str()
+                .or_else(|| rule_levels.get(rule.as_str()).copied())
+                .unwrap_or("warning");
+            let location = &result["locations"][0]["physicalLocation"];
+            let snippet = location["region"]["snippet"]["text"]
+                .as_str()
+                .map(str::to_string);
+            findings.push(Finding {
+                severity: Severity::parse(level),
+                file: location["artifactLocation"]["uri"]
+                    .as_str()
+                    .map(|uri| uri.trim_start_matches("file://").to_string())
+                    .unwrap_or_else(|| "?".to_string()),
+                line: location["region"]["startLine"].as_u64().unwrap_or(0) as usize,
+                message: result["message"]["text"].as_str().unwrap_or("").to_string(),
+                snippet,
+                rule,
+            });
+        }
+    }
+    Ok((tool.unwrap_or_else(|| "sarif".to_string()), findings))
+}
+
+/// Line `line` of `file`, for tools that don't embed snippets.
+fn snippet_from_disk(file: &str, line: usize) -> Option<String> {
+    let content = std::fs::read_to_string(file).ok()?;
+    content
+        .lines()
+        .nth(line.checked_sub(1)?)
+        .map(|l| l.trim().to_string())
+}
+
+/// Grouped report: header with counts, then per severity and rule the
+/// message, one snippet and the `file:line` list. At most `max` rules.
+pub fn format_report(tool: &str, findings: &[Finding], max: usize) -> String {
+    if findings.is_empty() {
+        return format!("✓ {}: no findings", tool);
+    }
+
+    let mut by_severity: BTreeMap<Severity, usize> = BTreeMap::new();
+    for finding in findings {
+        *by_severity.entry(finding.severity).or_default() += 1;
+    }
+    let counts: Vec<String> = by_severity
+        .iter()
+        .map(|(severity, n)| format!("{} {}", n, severity.label()))
+        .collect();
+
+    // (severity, rule) -> findings, most severe first
+    let mut by_rule: BTreeMap<(Severity, &str), Vec<&Finding>> = BTreeMap::new();
+    for finding in findings {
+        by_rule
+            .entry((finding.severity, &finding.rule))
+            .or_default()
+            .push(finding);
+    }
+    let files: std::collections::HashSet<&str> = findings.iter().map(|f| f.file.as_str()).collect();
+
+    let mut out = vec![format!(
+        "🔍 {}: {} findings ({}) in {} files, {} rules",
+        tool,
+        findings.len(),
+        counts.join(", "),
+        files.len(),
+        by_rule.len()
+    )];
+
+    let mut rules: Vec<_> = by_rule.into_iter().collect();
+    rules.sort_by_key(|((severity, rule), list)| (*severity, usize::MAX - list.len(), *rule));
+
+    for ((severity, rule), list) in rules.iter().take(max) {
+        out.push(String::new());
+        out.push(format!("{} {} ({})", severity.icon(), rule, list.len()));
+        let first = list[0];
+        let message = first.message.lines().next().unwrap_or("").trim();
+        if !message.is_empty() {
+            out.push(format!("  {}", truncate(message, 120)));
+        }
+        let snippet = first
+            .snippet
+            .as_deref()
+            .and_then(|s| s.lines().map(str::trim).find(|l| !l.is_empty()))
+            .map(str::to_string)
+            .or_else(|| snippet_from_disk(&first.file, first.line));
+        if let Some(snippet) = snippet {
+            out.push(format!("  > {}", truncate(&snippet, 120)));
+        }
+
+        // file -> lines, files in order of first appearance
+        let mut lines_by_file: Vec<(&str, Vec<usize>)> = Vec::new();
+        for finding in list {
+            match lines_by_file.iter_mut().find(|(f, _)| *f == finding.file) {
+                Some((_, lines)) => lines.push(finding.line),
+                None => lines_by_file.push((&finding.file, vec![finding.line])),
+            }
+        }
+        let locations: Vec<String> = lines_by_file
+            .iter()
+            .map(|(file, lines)| {
+                let lines: Vec<String> = lines.iter().map(usize::to_string).collect();
+                format!("{}:{}", file, lines.join(","))
+            })
+            .collect();
+        out.push(format!("  {}", locations.join("  ")));
+    }
+    if rules.len() > max {
+        let hidden: usize = rules[max..].iter().map(|(_, list)| list.len()).sum();
+        out.push(format!(
+            "+{} more rules ({} findings)",
+            rules.len() - max,
+            hidden
+        ));
+    }
+    out.join("\n")
+}
+
+/// `rtk sarif [file]`: summarize a SARIF log from a file or stdin.
+pub fn run(file: Option<&Path>, max: usize, verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let (json, source) = match file {
+        Some(path) => (
+            std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?,
+            path.display().to_string(),
+        ),
+        None => {
+            let mut json = String::new();
+            std::io::stdin()
+                .read_to_string(&mut json)
+                .context("Failed to read SARIF from stdin")?;
+            (json, "(stdin)".to_string())
+        }
+    };
+
+    let (tool, findings) = parse_sarif(&json)?;
+    if verbose > 0 {
+        eprintln!(
+            "sarif: {} findings from {} in {}",
+            findings.len(),
+            tool,
+            source
+        );
+    }
+    let output = format_report(&tool, &findings, max);
+    println!("{}", output);
+    timer.track(
+        &format!("cat {}", source),
+        &format!("rtk sarif {}", source),
+        &json,
+        &output,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODEQL: &str = r#"{
+      "version": "2.1.0",
+      "runs": [{
+        "tool": {"driver": {"name": "CodeQL", "rules": [
+          {"id": "js/sql-injection", "defaultConfiguration": {"level": "error"}},
+          {"id": "js/unused-local-variable", "defaultConfiguration": {"level": "note"}}
+        ]}},
+        "results": [
+          {"ruleId": "js/sql-injection", "message": {"text": "Query built from user input."},
+           "locations": [{"physicalLocation": {"artifactLocation": {"uri": "src/db.js"},
+             "region": {"startLine": 12, "snippet": {"text": "  db.query(sql + req.query.id)\n"}}}}]},
+          {"ruleId": "js/sql-injection", "message": {"text": "Query built from user input."},
+           "locations": [{"physicalLocation": {"artifactLocation": {"uri": "src/db.js"},
+             "region": {"startLine": 30}}}]},
+          {"ruleIndex": 1, "message": {"text": "Unused variable x."},
+           "locations": [{"physicalLocation": {"artifactLocation": {"uri": "src/app.js"},
+             "region": {"startLine": 4}}}]}
+        ]
+      }]
+    }"#;
+
+    #[test]
+    fn test_parse_sarif_rule_defaults() {
+        let (tool, findings) = parse_sarif(CODEQL).unwrap();
+        assert_eq!(tool, "CodeQL");
+        assert_eq!(findings.len(), 3);
+        assert_eq!(findings[0].severity, Severity::Error);
+        assert_eq!(findings[2].rule, "js/unused-local-variable");
+        assert_eq!(findings[2].severity, Severity::Note);
+        assert!(parse_sarif("{}").is_err());
+    }
+
+    #[test]
+    fn test_report_one_snippet_per_rule() {
+        let (tool, findings) = parse_sarif(CODEQL).unwrap();
+        let report = format_report(&tool, &findings, 10);
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(
+            lines[0],
+            "🔍 CodeQL: 3 findings (2 error, 1 note) in 2 files, 2 rules"
+        );
+        assert_eq!(lines[2], "❌ js/sql-injection (2)");
+        assert_eq!(lines[3], "  Query built from user input.");
+        assert_eq!(lines[4], "  > db.query(sql + req.query.id)");
+        assert_eq!(lines[5], "  src/db.js:12,30");
+        assert_eq!(report.matches("> ").count(), 1);
+
+        let capped = format_report(&tool, &findings, 1);
+        assert!(capped.ends_with("+1 more rules (1 findings)"));
+        assert_eq!(format_report("semgrep", &[], 10), "✓ semgrep: no findings");
+    }
+}
@@ -0,0 +1,238 @@
+//! Per-command rhai scripts: `<config dir>/rtk/scripts/<cmd>.rhai`.
+//!
+//! For logic `[[pipelines]]` regexes can't express (conditional keeps,
+//! counting, stateful dedup). The script for the rtk subcommand defines
+//! `filter(lines)`: every block rtk prints for the command is passed as an
+//! array of lines, after the pipeline rules and before the output profile,
+//! and the script returns an array of lines, a string, or `()` to print
+//! nothing. `this` is a map kept for the whole run, for state across blocks:
+//!
+//! ```text
+//! // scripts/cargo.rhai: print each distinct warning once
+//! fn filter(lines) {
+//!     let out = [];
+//!     for line in lines {
+//!         if line.starts_with("warning:") {
+//!             if line in this { continue; }
+//!             this[line] = true;
+//!         }
+//!         out.push(line);
+//!     }
+//!     out
+//! }
+//! ```
+//!
+//! Scripts are bounded (operations, string and array sizes). One that fails
+//! to compile or errors prints a warning, and the output is printed
+//! unscripted. Needs the `scripting` feature (on by default).
+
+use std::borrow::Cow;
+
+#[cfg(feature = "scripting")]
+pub use enabled::apply;
+
+/// Without the `scripting` feature output is never scripted.
+#[cfg(not(feature = "scripting"))]
+pub fn apply(text: Cow<'_, str>, _keep_state: bool) -> Option<Cow<'_, str>> {
+    Some(text)
+}
+
+#[cfg(feature = "scripting")]
+mod enabled {
+    use super::*;
+    use anyhow::{bail, Context, Result};
+    use rhai::{Array, CallFnOptions, Dynamic, Engine, Map, Scope, AST};
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Mutex, OnceLock};
+
+    const MAX_OPERATIONS: u64 = 1_000_000;
+    const MAX_STRING_SIZE: usize = 16 * 1024 * 1024;
+    const MAX_ARRAY_SIZE: usize = 1_000_000;
+
+    static SCRIPT: OnceLock<Option<Script>> = OnceLock::new();
+    /// Runtime errors are reported once per run, not once per block
+    static WARNED: AtomicBool = AtomicBool::new(false);
+
+    pub(super) struct Script {
+        path: PathBuf,
+        engine: Engine,
+        ast: AST,
+        /// `this` across the run's blocks
+        state: Mutex<Dynamic>,
+    }
+
+    impl Script {
+        pub(super) fn compile(path: &Path, source: &str) -> Result<Self> {
+            let mut engine = Engine::new();
+            engine
+                .set_max_operations(MAX_OPERATIONS)
+                .set_max_string_size(MAX_STRING_SIZE)
+                .set_max_array_size(MAX_ARRAY_SIZE);
+            let ast = engine
+                .compile(source)
+                .map_err(|e| anyhow::anyhow!("{}", e))?;
+            if !ast.iter_functions().any(|f| f.name == "filter") {
+                bail!("no filter(lines) function");
+            }
+            Ok(Self {
+                path: path.to_path_buf(),
+                engine,
+                ast,
+                state: Mutex::new(Dynamic::from_map(Map::new())),
+            })
+        }
+
+        /// `filter(lines)` on `text` with `state` as `this`; `None` when the
+        /// script printed nothing.
+        pub(super) fn run(&self, text: &str, state: &mut Dynamic) -> Result<Option<String>> {
+            let lines: Array = text.lines().map(|l| Dynamic::from(l.to_string())).collect();
+            let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(state);
+            let result: Dynamic = self
+                .engine
+                .call_fn_with_options(options, &mut Scope::new(), &self.ast, "filter", (lines,))
+                .map_err(|e| anyhow::anyhow!("{}", e))?;
+            if result.is_unit() {
+                return Ok(None);
+            }
+            if result.is_string() {
+                return Ok(Some(result.to_string()));
+            }
+            let Some(lines) = result.clone().try_cast::<Array>() else {
+                bail!(
+                    "filter returned {}, not an array or string",
+                    result.type_name()
+                );
+            };
+            if lines.is_empty() {
+                return Ok(None);
+            }
+            let lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+            Ok(Some(lines.join("\n")))
+        }
+    }
+
+    fn script_path(command: &str) -> Option<PathBuf> {
+        let config = crate::config::get_config_path().ok()?;
+        Some(
+            config
+                .with_file_name("scripts")
+                .join(format!("{}.rhai", command)),
+        )
+    }
+
+    fn script() -> Option<&'static Script> {
+        SCRIPT
+            .get_or_init(|| {
+                let path = script_path(crate::pipeline::command()?)?;
+                let source = std::fs::read_to_string(&path).ok()?;
+                match Script::compile(&path, &source)
+                    .with_context(|| format!("script {}", path.display()))
+                {
+                    Ok(script) => {
+                        tracing::debug!(path = %path.display(), "script loaded");
+                        Some(script)
+                    }
+                    Err(e) => {
+                        eprintln!("rtk: warning: {:#}, output not scripted", e);
+                        None
+                    }
+                }
+            })
+            .as_ref()
+    }
+
+    /// Run the command's script on printed output. `keep_state` carries `this`
+    /// across calls (printing); token estimation runs with a fresh map.
+    pub fn apply(text: Cow<'_, str>, keep_state: bool) -> Option<Cow<'_, str>> {
+        let Some(script) = script().filter(|_| !text.is_empty()) else {
+            return Some(text);
+        };
+        let result = if keep_state {
+            match script.state.lock() {
+                Ok(mut state) => script.run(&text, &mut state),
+                Err(_) => return Some(text),
+            }
+        } else {
+            script.run(&text, &mut Dynamic::from_map(Map::new()))
+        };
+        match result {
+            Ok(scripted) => scripted.map(Cow::Owned),
+            Err(e) => {
+                if !WARNED.swap(true, Ordering::Relaxed) {
+                    eprintln!(
+                        "rtk: warning: script {}: {:#}, output not scripted",
+                        script.path.display(),
+                        e
+                    );
+                }
+                Some(text)
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "scripting"))]
+mod tests {
+    use super::enabled::Script;
+    use rhai::{Dynamic, Map};
+    use std::path::Path;
+
+    fn compile(source: &str) -> Script {
+        Script::compile(Path::new("test.rhai"), source).unwrap()
+    }
+
+    fn state() -> Dynamic {
+        Dynamic::from_map(Map::new())
+    }
+
+    #[test]
+    fn test_state_is_kept_across_blocks() {
+        let script = compile(
+            r#"
+            fn filter(lines) {
+                let out = [];
+                for line in lines {
+                    if line.starts_with("warning:") {
+                        if line in this { continue; }
+                        this[line] = true;
+                    }
+                    out.push(line);
+                }
+                out
+            }
+            "#,
+        );
+        let mut state = state();
+        let block = "warning: unused x\nbuilt a";
+        assert_eq!(
+            script.run(block, &mut state).unwrap().as_deref(),
+            Some(block)
+        );
+        assert_eq!(
+            script.run(block, &mut state).unwrap().as_deref(),
+            Some("built a")
+        );
+    }
+
+    #[test]
+    fn test_return_types() {
+        let counted = compile(r#"fn filter(lines) { `${lines.len()} lines` }"#);
+        assert_eq!(
+            counted.run("a\nb\nc", &mut state()).unwrap().as_deref(),
+            Some("3 lines")
+        );
+        let silent = compile("fn filter(lines) { }");
+        assert_eq!(silent.run("a", &mut state()).unwrap(), None);
+        let number = compile("fn filter(lines) { 42 }");
+        assert!(number.run("a", &mut state()).is_err());
+    }
+
+    #[test]
+    fn test_bad_scripts_are_rejected() {
+        assert!(Script::compile(Path::new("x.rhai"), "fn filter(lines) {").is_err());
+        assert!(Script::compile(Path::new("x.rhai"), "fn other(lines) { lines }").is_err());
+        let runaway = compile("fn filter(lines) { loop {} }");
+        assert!(runaway.run("a", &mut state()).is_err());
+    }
+}
@@ -0,0 +1,175 @@
+use crate::sarif::{self, Finding, Severity};
+use crate::tracking;
+use crate::utils::truncate;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::process::Command;
+
+/// Rules listed before the rest are counted in a `+N more rules` line.
+const MAX_RULES: usize = 30;
+
+#[derive(Debug, Deserialize)]
+struct Position {
+    line: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct Extra {
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    severity: String,
+    /// Matched source; "requires login" in recent OSS releases
+    #[serde(default)]
+    lines: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SemgrepResult {
+    check_id: String,
+    path: String,
+    start: Position,
+    extra: Extra,
+}
+
+#[derive(Debug, Deserialize)]
+struct SemgrepError {
+    #[serde(default)]
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SemgrepOutput {
+    #[serde(default)]
+    results: Vec<SemgrepResult>,
+    #[serde(default)]
+    errors: Vec<SemgrepError>,
+}
+
+/// Findings and scan errors from `semgrep --json`.
+fn parse_semgrep_json(json: &str) -> Result<(Vec<Finding>, Vec<String>)> {
+    let output: SemgrepOutput =
+        serde_json::from_str(json).context("Failed to parse semgrep JSON output")?;
+    let findings = output
+        .results
+        .into_iter()
+        .map(|r| Finding {
+            rule: r.check_id,
+            severity: Severity::parse(&r.extra.severity),
+            file: r.path,
+            line: r.start.line,
+            message: r.extra.message,
+            snippet: Some(r.extra.lines).filter(|l| !l.trim().is_empty() && l != "requires login"),
+        })
+        .collect();
+    let errors = output.errors.into_iter().map(|e| e.message).collect();
+    Ok((findings, errors))
+}
+
+fn filter_semgrep(stdout: &str, sarif_output: bool, max: usize) -> String {
+    let parsed = if sarif_output {
+        sarif::parse_sarif(stdout).map(|(_, findings)| (findings, Vec::new()))
+    } else {
+        parse_semgrep_json(stdout)
+    };
+    let (findings, errors) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            // Fallback if JSON parsing fails
+            return format!(
+                "semgrep (JSON parse failed: {})\n{}",
+                e,
+                truncate(stdout, 500)
+            );
+        }
+    };
+
+    let mut report = sarif::format_report("semgrep", &findings, max);
+    if let Some(first) = errors.first() {
+        report.push_str(&format!(
+            "\n\n⚠️ {} scan errors, first: {}",
+            errors.len(),
+            truncate(first.lines().next().unwrap_or("").trim(), 120)
+        ));
+    }
+    report
+}
+
+pub fn run(args: &[String], verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+
+    let sarif_output = args.iter().any(|a| a == "--sarif");
+    let mut cmd = Command::new("semgrep");
+    // Force JSON output unless SARIF was asked for
+    if !sarif_output && !args.iter().any(|a| a == "--json") {
+        cmd.arg("--json");
+    }
+    cmd.args(args);
+
+    if verbose > 0 {
+        eprintln!("Running: semgrep {}", args.join(" "));
+    }
+
+    let output = cmd
+        .output()
+        .context("Failed to run semgrep. Is it installed? Try: pip install semgrep")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
+
+    let filtered = if stdout.trim().is_empty() {
+        // Usage or config errors only go to stderr
+        truncate(stderr.trim(), 2000)
+    } else {
+        filter_semgrep(&stdout, sarif_output, MAX_RULES)
+    };
+    println!("{}", filtered);
+
+    // semgrep exits 1 with --error when there are findings, 2+ on failure
+    timer.with_exit_code(output.status).track(
+        &format!("semgrep {}", args.join(" ")),
+        &format!("rtk semgrep {}", args.join(" ")),
+        &raw,
+        &filtered,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_semgrep_json() {
+        let json = r#"{
+          "results": [
+            {"check_id": "python.lang.security.audit.eval-detected", "path": "app/views.py",
+             "start": {"line": 12, "col": 5}, "end": {"line": 12, "col": 30},
+             "extra": {"message": "Detected eval() on dynamic content.\nMore text.",
+                       "severity": "ERROR", "lines": "    eval(request.args['q'])"}},
+            {"check_id": "python.lang.security.audit.eval-detected", "path": "app/util.py",
+             "start": {"line": 7}, "extra": {"message": "Detected eval()", "severity": "ERROR",
+             "lines": "requires login"}},
+            {"check_id": "python.lang.best-practice.open-never-closed", "path": "app/io.py",
+             "start": {"line": 3}, "extra": {"message": "file not closed", "severity": "WARNING",
+             "lines": "f = open(p)"}}
+          ],
+          "errors": [{"message": "Syntax error at line broken.py:1"}]
+        }"#;
+        let report = filter_semgrep(json, false, 10);
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(
+            lines[0],
+            "🔍 semgrep: 3 findings (2 error, 1 warning) in 3 files, 2 rules"
+        );
+        assert_eq!(lines[2], "❌ python.lang.security.audit.eval-detected (2)");
+        assert_eq!(lines[3], "  Detected eval() on dynamic content.");
+        assert_eq!(lines[4], "  > eval(request.args['q'])");
+        assert_eq!(lines[5], "  app/views.py:12  app/util.py:7");
+        assert!(report.ends_with("1 scan errors, first: Syntax error at line broken.py:1"));
+        assert!(!report.contains("requires login"));
+        assert!(filter_semgrep("not json", false, 10).starts_with("semgrep (JSON parse failed"));
+    }
+}
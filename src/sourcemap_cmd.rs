@@ -0,0 +1,534 @@
+//! Maps minified JS stack frames back to original sources via `.map` files.
+
+use crate::tracking;
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+lazy_static! {
+    // V8: `    at fn (https://x/main.js:1:234)` or `    at https://x/main.js:1:234`
+    static ref V8_FRAME: Regex =
+        Regex::new(r"^\s*at (?:(.+?) \()?(\S+?):(\d+):(\d+)\)?\s*$").unwrap();
+    // Firefox/Safari: `fn@https://x/main.js:1:234`
+    static ref GECKO_FRAME: Regex = Regex::new(r"^\s*([^@\s]*)@(\S+?):(\d+):(\d+)\s*$").unwrap();
+}
+
+/// Directories never searched for maps.
+const SKIP_DIRS: &[&str] = &["node_modules", ".git", "target"];
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawMap {
+    #[serde(default)]
+    sources: Vec<Option<String>>,
+    #[serde(default)]
+    source_root: Option<String>,
+    #[serde(default)]
+    names: Vec<String>,
+    #[serde(default)]
+    mappings: String,
+    #[serde(default)]
+    sources_content: Vec<Option<String>>,
+    /// Index maps: each section is a full map at an offset
+    #[serde(default)]
+    sections: Vec<RawSection>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawSection {
+    offset: Offset,
+    map: RawMap,
+}
+
+#[derive(Debug, Deserialize)]
+struct Offset {
+    line: u32,
+    column: u32,
+}
+
+/// One mapping segment: generated column -> source position (all 0-based).
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    column: u32,
+    source: u32,
+    line: u32,
+    source_column: u32,
+    name: Option<u32>,
+}
+
+struct SourceMap {
+    sources: Vec<String>,
+    names: Vec<String>,
+    contents: Vec<Option<String>>,
+    /// Segments per generated line, sorted by column
+    lines: Vec<Vec<Segment>>,
+    /// Index-map sections, by (line, column) offset
+    sections: Vec<((u32, u32), SourceMap)>,
+}
+
+/// Original position of a generated one.
+#[derive(Debug, PartialEq)]
+struct Original<'a> {
+    source: &'a str,
+    line: u32,
+    column: u32,
+    name: Option<&'a str>,
+    content: Option<&'a str>,
+}
+
+fn vlq_digit(c: u8) -> Option<i64> {
+    let v = match c {
+        b'A'..=b'Z' => c - b'A',
+        b'a'..=b'z' => c - b'a' + 26,
+        b'0'..=b'9' => c - b'0' + 52,
+        b'+' => 62,
+        b'/' => 63,
+        _ => return None,
+    };
+    Some(v as i64)
+}
+
+/// Base64 VLQ values of one segment.
+fn decode_vlq(segment: &str) -> Option<Vec<i64>> {
+    let mut values = Vec::new();
+    let (mut value, mut shift) = (0i64, 0);
+    for c in segment.bytes() {
+        let digit = vlq_digit(c)?;
+        value += (digit & 31) << shift;
+        if digit & 32 != 0 {
+            shift += 5;
+            continue;
+        }
+        let negative = value & 1 == 1;
+        value >>= 1;
+        values.push(if negative { -value } else { value });
+        value = 0;
+        shift = 0;
+    }
+    Some(values)
+}
+
+fn decode_mappings(mappings: &str) -> Vec<Vec<Segment>> {
+    let mut lines = Vec::new();
+    // Source fields are deltas across the whole string; column resets per line
+    let (mut source, mut line, mut column, mut name) = (0i64, 0i64, 0i64, 0i64);
+    for generated in mappings.split(';') {
+        let mut segments = Vec::new();
+        let mut generated_column = 0i64;
+        for raw in generated.split(',').filter(|s| !s.is_empty()) {
+            let Some(v) = decode_vlq(raw) else {
+                continue;
+            };
+            generated_column += v[0];
+            if v.len() < 4 {
+                continue;
+            }
+            source += v[1];
+            line += v[2];
+            column += v[3];
+            let segment_name = if v.len() >= 5 {
+                name += v[4];
+                Some(name as u32)
+            } else {
+                None
+            };
+            segments.push(Segment {
+                column: generated_column as u32,
+                source: source as u32,
+                line: line as u32,
+                source_column: column as u32,
+                name: segment_name,
+            });
+        }
+        segments.sort_by_key(|s| s.column);
+        lines.push(segments);
+    }
+    lines
+}
+
+/// `webpack://app/./src/a.ts` -> `src/a.ts`
+fn clean_source(source: &str, root: Option<&str>) -> String {
+    let mut s = source;
+    for prefix in ["webpack://", "vite://", "file://"] {
+        if let Some(rest) = s.strip_prefix(prefix) {
+            // webpack://<namespace>/<path>
+            s = rest.split_once('/').map_or(rest, |(_, path)| path);
+        }
+    }
+    let mut s = s.trim_start_matches('/');
+    while let Some(rest) = s.strip_prefix("./").or_else(|| s.strip_prefix("../")) {
+        s = rest;
+    }
+    match root.filter(|r| !r.is_empty() && !source.contains("://")) {
+        Some(root) => format!("{}/{}", root.trim_end_matches('/'), s),
+        None => s.to_string(),
+    }
+}
+
+impl SourceMap {
+    fn parse(json: &str) -> Result<Self> {
+        let raw: RawMap = serde_json::from_str(json).context("Invalid source map JSON")?;
+        Ok(Self::from_raw(raw))
+    }
+
+    fn from_raw(raw: RawMap) -> Self {
+        let root = raw.source_root.as_deref();
+        SourceMap {
+            sources: raw
+                .sources
+                .iter()
+                .map(|s| clean_source(s.as_deref().unwrap_or("?"), root))
+                .collect(),
+            names: raw.names,
+            contents: raw.sources_content,
+            lines: decode_mappings(&raw.mappings),
+            sections: raw
+                .sections
+                .into_iter()
+                .map(|s| ((s.offset.line, s.offset.column), Self::from_raw(s.map)))
+                .collect(),
+        }
+    }
+
+    /// Looks up a 1-based line and column, as printed in stack traces.
+    fn lookup(&self, line: u32, column: u32) -> Option<Original<'_>> {
+        let (line, column) = (line.checked_sub(1)?, column.saturating_sub(1));
+        if !self.sections.is_empty() {
+            let (offset, map) = self
+                .sections
+                .iter()
+                .rev()
+                .find(|(offset, _)| *offset <= (line, column))?;
+            let column = if line == offset.0 {
+                column - offset.1
+            } else {
+                column
+            };
+            return map.lookup(line - offset.0 + 1, column + 1);
+        }
+        let segments = self.lines.get(line as usize)?;
+        let segment = segments.iter().rev().find(|s| s.column <= column)?;
+        Some(Original {
+            source: self.sources.get(segment.source as usize)?,
+            line: segment.line + 1,
+            column: segment.source_column + 1,
+            name: segment
+                .name
+                .and_then(|n| self.names.get(n as usize))
+                .map(String::as_str),
+            content: self
+                .contents
+                .get(segment.source as usize)
+                .and_then(|c| c.as_deref()),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Frame<'a> {
+    function: Option<&'a str>,
+    url: &'a str,
+    line: u32,
+    column: u32,
+}
+
+fn parse_frame(line: &str) -> Option<Frame<'_>> {
+    let caps = V8_FRAME
+        .captures(line)
+        .or_else(|| GECKO_FRAME.captures(line))?;
+    Some(Frame {
+        function: caps
+            .get(1)
+            .map(|m| m.as_str())
+            .filter(|f| !f.is_empty() && *f != "async"),
+        url: caps.get(2)?.as_str(),
+        line: caps[3].parse().ok()?,
+        column: caps[4].parse().ok()?,
+    })
+}
+
+/// Bundle file name of a frame URL, without query or hash.
+fn bundle_name(url: &str) -> &str {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// Package name when `source` lives under node_modules.
+fn library(source: &str) -> Option<String> {
+    let (_, rest) = source.rsplit_once("node_modules/")?;
+    let mut parts = rest.split('/');
+    let first = parts.next()?;
+    Some(if first.starts_with('@') {
+        format!("{}/{}", first, parts.next().unwrap_or(""))
+    } else {
+        first.to_string()
+    })
+}
+
+fn is_runtime(source: &str) -> bool {
+    source.starts_with("webpack/") || source.contains("(webpack)") || source.starts_with("vite/")
+}
+
+/// Finds and caches source maps by bundle name.
+struct Maps {
+    index: HashMap<String, PathBuf>,
+    loaded: HashMap<String, Option<SourceMap>>,
+}
+
+impl Maps {
+    fn scan(dirs: &[PathBuf]) -> Self {
+        let mut index = HashMap::new();
+        for dir in dirs {
+            let walker = WalkDir::new(dir).into_iter().filter_entry(|e| {
+                !(e.file_type().is_dir()
+                    && SKIP_DIRS.contains(&e.file_name().to_string_lossy().as_ref()))
+            });
+            for entry in walker.flatten() {
+                let name = entry.file_name().to_string_lossy();
+                if let Some(bundle) = name.strip_suffix(".map") {
+                    index
+                        .entry(bundle.to_string())
+                        .or_insert_with(|| entry.path().to_path_buf());
+                }
+            }
+        }
+        Maps {
+            index,
+            loaded: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, bundle: &str) -> Option<&SourceMap> {
+        if !self.loaded.contains_key(bundle) {
+            let map = self.index.get(bundle).and_then(|path| {
+                std::fs::read_to_string(path)
+                    .ok()
+                    .and_then(|json| SourceMap::parse(&json).ok())
+            });
+            self.loaded.insert(bundle.to_string(), map);
+        }
+        self.loaded.get(bundle)?.as_ref()
+    }
+}
+
+fn flush_library(out: &mut Vec<String>, run: &mut Vec<String>) {
+    if run.is_empty() {
+        return;
+    }
+    let mut libs: Vec<&String> = run.iter().collect();
+    libs.dedup();
+    let names: Vec<&str> = libs.iter().take(3).map(|s| s.as_str()).collect();
+    out.push(format!(
+        "    ... {} library {} ({})",
+        run.len(),
+        if run.len() == 1 { "frame" } else { "frames" },
+        names.join(", ")
+    ));
+    run.clear();
+}
+
+fn symbolicate(trace: &str, maps: &mut Maps, all: bool) -> String {
+    let mut out = Vec::new();
+    let mut library_run: Vec<String> = Vec::new();
+    let (mut frames, mut mapped, mut app) = (0, 0, 0);
+    let mut missing: Vec<String> = Vec::new();
+    let mut snippet_shown = false;
+
+    for line in trace.lines() {
+        let Some(frame) = parse_frame(line) else {
+            flush_library(&mut out, &mut library_run);
+            if !line.trim().is_empty() {
+                out.push(line.trim_end().to_string());
+            }
+            continue;
+        };
+        frames += 1;
+        let bundle = bundle_name(frame.url);
+        let Some(original) = maps
+            .get(bundle)
+            .and_then(|m| m.lookup(frame.line, frame.column))
+        else {
+            if !missing.iter().any(|m| m == bundle) {
+                missing.push(bundle.to_string());
+            }
+            flush_library(&mut out, &mut library_run);
+            out.push(format!(
+                "    at {} ({}:{}:{}) [unmapped]",
+                frame.function.unwrap_or("<anonymous>"),
+                bundle,
+                frame.line,
+                frame.column
+            ));
+            continue;
+        };
+        mapped += 1;
+
+        let lib = library(original.source);
+        if !all && (lib.is_some() || is_runtime(original.source)) {
+            library_run.push(lib.unwrap_or_else(|| "bundler runtime".to_string()));
+            continue;
+        }
+        flush_library(&mut out, &mut library_run);
+        app += 1;
+        let function = original.name.or(frame.function).unwrap_or("<anonymous>");
+        out.push(format!(
+            "    at {} ({}:{}:{})",
+            function, original.source, original.line, original.column
+        ));
+        // The top app frame is usually the one to read
+        if !snippet_shown {
+            if let Some(code) = original
+                .content
+                .and_then(|c| c.lines().nth(original.line as usize - 1))
+            {
+                out.push(format!("      > {}", code.trim()));
+                snippet_shown = true;
+            }
+        }
+    }
+    flush_library(&mut out, &mut library_run);
+
+    if frames > 0 {
+        let mut summary = format!("({}/{} frames mapped, {} in app code", mapped, frames, app);
+        if !missing.is_empty() {
+            summary.push_str(&format!("; no source map for {}", missing.join(", ")));
+        }
+        summary.push(')');
+        out.push(summary);
+    }
+    out.join("\n")
+}
+
+pub fn run(file: Option<&Path>, map_dirs: &[PathBuf], all: bool, verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let (trace, source) = match file {
+        Some(path) => (
+            std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?,
+            path.display().to_string(),
+        ),
+        None => {
+            let mut trace = String::new();
+            std::io::stdin()
+                .read_to_string(&mut trace)
+                .context("Failed to read stack trace from stdin")?;
+            (trace, "(stdin)".to_string())
+        }
+    };
+
+    let dirs = if map_dirs.is_empty() {
+        vec![PathBuf::from(".")]
+    } else {
+        map_dirs.to_vec()
+    };
+    let mut maps = Maps::scan(&dirs);
+    if verbose > 0 {
+        eprintln!("sourcemap: {} .map files indexed", maps.index.len());
+    }
+
+    let output = symbolicate(&trace, &mut maps, all);
+    println!("{}", output);
+    timer.track(
+        &format!("cat {}", source),
+        &format!("rtk sourcemap {}", source),
+        &trace,
+        &output,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Generated line 1: col 0 -> src/app.ts 2:2 `render`, col 10 ->
+    // node_modules/react-dom/index.js 1:0, col 20 -> src/app.ts 5:4
+    const MAP: &str = r#"{
+        "version": 3,
+        "sources": ["webpack://demo/./src/app.ts", "webpack://demo/./node_modules/react-dom/index.js"],
+        "names": ["render"],
+        "mappings": "AACEA,UCDF,UDII",
+        "sourcesContent": ["// app\n  render(items.map(f));\n\n\n    boot();\n", null]
+    }"#;
+
+    #[test]
+    fn test_decode_vlq() {
+        assert_eq!(decode_vlq("AACEA"), Some(vec![0, 0, 1, 2, 0]));
+        assert_eq!(decode_vlq("D"), Some(vec![-1]));
+        assert_eq!(decode_vlq("2H"), Some(vec![123]));
+    }
+
+    #[test]
+    fn test_lookup() {
+        let map = SourceMap::parse(MAP).unwrap();
+        let original = map.lookup(1, 15).unwrap();
+        assert_eq!(original.source, "node_modules/react-dom/index.js");
+        assert_eq!((original.line, original.column), (1, 1));
+        let original = map.lookup(1, 1).unwrap();
+        assert_eq!(original.source, "src/app.ts");
+        assert_eq!(
+            (original.line, original.column, original.name),
+            (2, 3, Some("render"))
+        );
+        assert!(map.lookup(2, 1).is_none());
+    }
+
+    #[test]
+    fn test_parse_frame() {
+        assert_eq!(
+            parse_frame("    at n.render (https://x.io/static/main.3f2a.js?v=1:1:2345)"),
+            Some(Frame {
+                function: Some("n.render"),
+                url: "https://x.io/static/main.3f2a.js?v=1",
+                line: 1,
+                column: 2345
+            })
+        );
+        assert_eq!(
+            parse_frame("render@https://x.io/main.js:1:5").map(|f| f.function),
+            Some(Some("render"))
+        );
+        assert_eq!(
+            bundle_name("https://x.io/static/main.3f2a.js?v=1"),
+            "main.3f2a.js"
+        );
+        assert_eq!(
+            library("node_modules/@tanstack/query-core/build/x.js"),
+            Some("@tanstack/query-core".to_string())
+        );
+    }
+
+    #[test]
+    fn test_symbolicate_trims_library_frames() {
+        let mut maps = Maps {
+            index: HashMap::new(),
+            loaded: HashMap::from([
+                ("main.js".to_string(), Some(SourceMap::parse(MAP).unwrap())),
+                ("vendor.js".to_string(), None),
+            ]),
+        };
+        let trace = "TypeError: Cannot read properties of undefined (reading 'map')
+    at r (https://x.io/main.js:1:3)
+    at a (https://x.io/main.js:1:12)
+    at b (https://x.io/main.js:1:14)
+    at https://x.io/main.js:1:25
+    at Object.c (https://x.io/vendor.js:2:100)";
+        let out = symbolicate(trace, &mut maps, false);
+        assert_eq!(
+            out,
+            "TypeError: Cannot read properties of undefined (reading 'map')
+    at render (src/app.ts:2:3)
+      > render(items.map(f));
+    ... 2 library frames (react-dom)
+    at <anonymous> (src/app.ts:5:5)
+    at Object.c (vendor.js:2:100) [unmapped]
+(4/5 frames mapped, 2 in app code; no source map for vendor.js)"
+        );
+        assert!(symbolicate(trace, &mut maps, true).contains("node_modules/react-dom/index.js:1:1"));
+    }
+}
@@ -0,0 +1,241 @@
+//! Spill oversized output to a file instead of truncating it.
+//!
+//! When stdout is not a terminal (an agent is reading) and the filtered
+//! output passes `output.max_tokens`, the rest is held back instead of
+//! printed. At exit the whole output is written to
+//! `<data dir>/rtk/spill/<hash>.txt` (keyed by content, so rerunning an
+//! unchanged command reuses the file) and a short digest follows what was
+//! shown: hidden line count, error lines and the last lines from the hidden
+//! part, and commands to query the file. Files expire after a day.
+
+use crate::cache;
+use crate::config::Config;
+use crate::generic_cmd::IMPORTANT;
+use crate::tracking::estimate_tokens;
+use crate::utils::{format_tokens, strip_ansi, truncate};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+const TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Error/warning lines from the hidden part quoted in the digest.
+const MAX_IMPORTANT: usize = 5;
+/// Last lines of the hidden part quoted in the digest (summaries live there).
+const TAIL_LINES: usize = 3;
+
+/// Output of this run; `None` once flushed.
+static STATE: OnceLock<Mutex<Option<Spill>>> = OnceLock::new();
+/// Set while printing notes that must stay visible (see [`unbudgeted`]).
+static EXEMPT: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Default)]
+struct Spill {
+    budget: usize,
+    /// Everything emitted, shown or not
+    text: String,
+    /// Bytes of `text` that went to stdout
+    shown_len: usize,
+    shown_tokens: usize,
+    /// Set once the budget was hit; later output is held too
+    spilled: bool,
+}
+
+/// Start counting output against `output.max_tokens` (0 disables), unless a
+/// person is reading on a terminal.
+pub fn enable() -> Option<SpillGuard> {
+    let budget = Config::cached().output.max_tokens;
+    if budget == 0 || std::io::stdout().is_terminal() {
+        return None;
+    }
+    let _ = STATE.set(Mutex::new(Some(Spill {
+        budget,
+        ..Spill::default()
+    })));
+    Some(SpillGuard)
+}
+
+pub struct SpillGuard;
+
+impl Drop for SpillGuard {
+    fn drop(&mut self) {
+        flush();
+    }
+}
+
+/// Run `print` with its output neither counted nor held: notes about the
+/// output itself, like the elided-section IDs, are useless inside the file.
+pub fn unbudgeted(print: impl FnOnce()) {
+    EXEMPT.store(true, Ordering::Relaxed);
+    print();
+    EXEMPT.store(false, Ordering::Relaxed);
+}
+
+/// Called from `profile::emit`: the part of `text` to print now. Once the
+/// output is over budget that is only the whole lines that still fit.
+pub(crate) fn admit(text: &str) -> &str {
+    if EXEMPT.load(Ordering::Relaxed) {
+        return text;
+    }
+    let Some(state) = STATE.get() else {
+        return text;
+    };
+    let Ok(mut state) = state.lock() else {
+        return text;
+    };
+    let Some(spill) = state.as_mut() else {
+        return text;
+    };
+    let start = spill.text.len();
+    spill.text.push_str(text);
+    if spill.spilled {
+        return "";
+    }
+    let tokens = estimate_tokens(text);
+    if spill.shown_tokens + tokens <= spill.budget {
+        spill.shown_tokens += tokens;
+        spill.shown_len = spill.text.len();
+        return text;
+    }
+    spill.spilled = true;
+    let mut fits = 0;
+    for line in text.split_inclusive('\n') {
+        let tokens = estimate_tokens(line);
+        if !line.ends_with('\n') || spill.shown_tokens + tokens > spill.budget {
+            break;
+        }
+        spill.shown_tokens += tokens;
+        fits += line.len();
+    }
+    spill.shown_len = start + fits;
+    &text[..fits]
+}
+
+/// Write held output to the spill file and print the digest (also used
+/// before an early exit).
+pub fn flush() {
+    let Some(spill) = STATE.get().and_then(|m| m.lock().ok()?.take()) else {
+        return;
+    };
+    if !spill.spilled {
+        return;
+    }
+    let mut out = std::io::stdout().lock();
+    let shown = &spill.text[..spill.shown_len];
+    let hidden = &spill.text[spill.shown_len..];
+    let Some(path) = spill_dir().and_then(|dir| write_spill(&dir, &spill.text)) else {
+        // Nowhere to put it: printing everything beats losing it
+        let _ = out.write_all(hidden.as_bytes());
+        return;
+    };
+    if !shown.is_empty() && !shown.ends_with('\n') {
+        let _ = out.write_all(b"\n");
+    }
+    let digest = digest(&spill.text, shown, hidden, spill.budget, &path);
+    let _ = writeln!(out, "{}", digest);
+}
+
+fn spill_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("RTK_SPILL_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    Some(dirs::data_local_dir()?.join("rtk").join("spill"))
+}
+
+/// Store `text` under its content hash; the path, or `None` on I/O failure.
+fn write_spill(dir: &Path, text: &str) -> Option<PathBuf> {
+    std::fs::create_dir_all(dir).ok()?;
+    cache::prune(dir, TTL);
+    let path = dir.join(format!("{}.txt", cache::key(&[text])));
+    if !path.exists() {
+        std::fs::write(&path, strip_ansi(text)).ok()?;
+    }
+    Some(path)
+}
+
+fn digest(full: &str, shown: &str, hidden: &str, budget: usize, path: &Path) -> String {
+    let total_lines = full.lines().count();
+    let first_hidden = shown.lines().count() + 1;
+    let hidden = strip_ansi(hidden);
+    let hidden_lines: Vec<&str> = hidden.lines().collect();
+    let last_hidden = first_hidden + hidden_lines.len().saturating_sub(1);
+
+    let mut out = vec![format!(
+        "--- output over budget: {} lines, ~{} tokens (max {}); lines {}-{} not shown ---",
+        total_lines,
+        format_tokens(estimate_tokens(full)),
+        format_tokens(budget),
+        first_hidden,
+        last_hidden
+    )];
+
+    let important: Vec<(usize, &str)> = hidden_lines
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| IMPORTANT.is_match(l))
+        .map(|(i, l)| (first_hidden + i, l.trim()))
+        .collect();
+    if !important.is_empty() {
+        out.push(format!(
+            "errors/warnings in hidden part ({}):",
+            important.len()
+        ));
+        for (line, text) in important.iter().take(MAX_IMPORTANT) {
+            out.push(format!("  {}: {}", line, truncate(text, 100)));
+        }
+    }
+    let tail_start = hidden_lines.len().saturating_sub(TAIL_LINES);
+    out.push("last lines:".to_string());
+    for (i, text) in hidden_lines.iter().enumerate().skip(tail_start) {
+        out.push(format!(
+            "  {}: {}",
+            first_hidden + i,
+            truncate(text.trim_end(), 100)
+        ));
+    }
+
+    let path = path.display();
+    out.push(format!("full output: {}", path));
+    out.push(format!("  rtk grep <pattern> {}", path));
+    out.push(format!(
+        "  sed -n '{},{}p' {}",
+        first_hidden,
+        (first_hidden + 199).min(last_hidden),
+        path
+    ));
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digest_points_at_hidden_part() {
+        let shown = "line 1\nline 2\n";
+        let hidden = "line 3\nerror: disk full\nline 5\nline 6\nDone in 3s\n";
+        let full = format!("{}{}", shown, hidden);
+        let digest = digest(&full, shown, hidden, 2, Path::new("/tmp/x.txt"));
+        let lines: Vec<&str> = digest.lines().collect();
+        assert!(lines[0].contains("7 lines"));
+        assert!(lines[0].contains("lines 3-7 not shown"));
+        assert_eq!(lines[2], "  4: error: disk full");
+        assert_eq!(
+            lines[4..7],
+            ["  5: line 5", "  6: line 6", "  7: Done in 3s"]
+        );
+        assert_eq!(lines[9], "  sed -n '3,7p' /tmp/x.txt");
+    }
+
+    #[test]
+    fn test_write_spill_is_content_addressed() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = write_spill(dir.path(), "\x1b[31mred\x1b[0m\n").unwrap();
+        let b = write_spill(dir.path(), "\x1b[31mred\x1b[0m\n").unwrap();
+        let c = write_spill(dir.path(), "other\n").unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(std::fs::read_to_string(a).unwrap(), "red\n");
+    }
+}
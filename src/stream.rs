@@ -0,0 +1,619 @@
+//! Streaming execution: run a command and filter its output line by line.
+//!
+//! stdout and stderr are read on two threads into a bounded channel, so a
+//! wrapper sees lines as the command produces them and can print diagnostics
+//! immediately instead of after the command exits. Each [`Line`] is tagged
+//! with its stream and the time it was read, so formatters can label or
+//! separate stderr, and collected lines can be put back in true order. Memory stays bounded for
+//! arbitrarily large outputs: the raw text (needed by `--raw`/`--explain` and
+//! for exact token counts) is kept only up to `RAW_KEEP_BYTES`; past that,
+//! input tokens are counted incrementally and only the last lines are kept.
+//! Lines longer than `MAX_LINE_BYTES` are cut. [`each_line`] and
+//! [`RawInput`] give readers of files and stdin (`rtk log`, `rtk err -`) the
+//! same bounds.
+//!
+//! With `execution.timeout_secs` (or `[commands.<name>] timeout_secs`) set, a
+//! command still running at the deadline gets SIGTERM, then SIGKILL after
+//! `execution.kill_grace_secs`; the whole process group is signalled so
+//! `sh -c` children die too. Timed-out runs exit with `exit_code::TIMEOUT`.
+//! That group is not the terminal's foreground group, so while it runs rtk
+//! forwards SIGINT, SIGTERM and SIGHUP to it: Ctrl-C still stops the command.
+
+use crate::config::Config;
+use crate::encoding;
+use crate::exit_code;
+use crate::tracking::{self, TimedExecution};
+use anyhow::{Context, Result};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Raw output beyond this is not retained (tokens are still counted).
+const RAW_KEEP_BYTES: usize = 4 * 1024 * 1024;
+/// Longer lines are cut here (the rest of the line is skipped unread).
+const MAX_LINE_BYTES: usize = 64 * 1024;
+/// Lines kept from the end of the output for failure context.
+const TAIL_LINES: usize = 20;
+/// Lines buffered between the reader threads and the filter.
+const CHANNEL_LINES: usize = 1024;
+
+/// Timeout for the current subcommand, resolved once by `init`.
+static TIMEOUT: OnceLock<Option<Timeout>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Timeout {
+    secs: u64,
+    grace_secs: u64,
+}
+
+/// Resolve the timeout for rtk subcommand `command`.
+pub fn init(command: &str) {
+    let _ = TIMEOUT.set(timeout_for(Config::cached(), command));
+}
+
+fn timeout_for(config: &Config, command: &str) -> Option<Timeout> {
+    let secs = config
+        .commands
+        .get(command)
+        .and_then(|table| table.get("timeout_secs"))
+        .and_then(|v| v.as_integer())
+        .map(|v| v.max(0) as u64)
+        .unwrap_or(config.execution.timeout_secs);
+    (secs > 0).then_some(Timeout {
+        secs,
+        grace_secs: config.execution.kill_grace_secs,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    Stdout,
+    Stderr,
+}
+
+/// One output line, tagged with its stream and when it was read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub source: Source,
+    pub text: String,
+    /// Time since the command was started
+    pub at: Duration,
+}
+
+/// Restore emission order of collected lines. The channel delivers lines
+/// roughly in order, but the two reader threads can race.
+pub fn sort_by_time(lines: &mut [Line]) {
+    lines.sort_by_key(|line| line.at);
+}
+
+/// What is left of a streamed run once the command exits.
+pub struct Streamed {
+    pub status: ExitStatus,
+    /// Full raw output, unless it exceeded `RAW_KEEP_BYTES`
+    pub raw: Option<String>,
+    /// Estimated tokens of the whole raw output
+    pub input_tokens: usize,
+    /// Last `TAIL_LINES` lines
+    pub tail: VecDeque<Line>,
+    /// Seconds after which the command was stopped, if it timed out
+    pub timed_out: Option<u64>,
+}
+
+impl Streamed {
+    /// Record savings: exact when the raw output was retained, from the
+    /// incremental count otherwise.
+    pub fn track(&self, timer: TimedExecution, original_cmd: &str, rtk_cmd: &str, output: &str) {
+        let timer = timer.with_code(self.exit_code());
+        match &self.raw {
+            Some(raw) => timer.track(original_cmd, rtk_cmd, raw, output),
+            None => timer.track_tokens(original_cmd, rtk_cmd, self.input_tokens, output),
+        }
+    }
+
+    /// `exit_code::TIMEOUT` for timed-out runs, else the code a shell would report.
+    pub fn exit_code(&self) -> i32 {
+        match self.timed_out {
+            Some(_) => exit_code::TIMEOUT,
+            None => exit_code::from_status(self.status),
+        }
+    }
+
+    /// Headline for a timed-out run, e.g. `⏱ timed out after 30s`.
+    pub fn timeout_notice(&self) -> Option<String> {
+        self.timed_out
+            .map(|secs| format!("⏱ timed out after {}s", secs))
+    }
+}
+
+/// Raw input of a streamed reader, for tracking: the text up to
+/// `RAW_KEEP_BYTES` and a token count of all of it.
+pub struct RawInput {
+    raw: Option<String>,
+    tokens: usize,
+}
+
+impl Default for RawInput {
+    fn default() -> Self {
+        Self {
+            raw: Some(String::new()),
+            tokens: 0,
+        }
+    }
+}
+
+impl RawInput {
+    pub fn push_line(&mut self, line: &str) {
+        self.tokens += tracking::estimate_tokens(line);
+        crate::crash::raw_line(line);
+        if let Some(buf) = self.raw.as_mut() {
+            if buf.len() + line.len() < RAW_KEEP_BYTES {
+                buf.push_str(line);
+                buf.push('\n');
+            } else {
+                self.raw = None;
+            }
+        }
+    }
+
+    /// Record savings, exactly while the raw text was kept.
+    pub fn track(self, timer: TimedExecution, original_cmd: &str, rtk_cmd: &str, output: &str) {
+        match &self.raw {
+            Some(raw) => timer.track(original_cmd, rtk_cmd, raw, output),
+            None => timer.track_tokens(original_cmd, rtk_cmd, self.tokens, output),
+        }
+    }
+
+    fn into_parts(self) -> (Option<String>, usize) {
+        (self.raw, self.tokens)
+    }
+}
+
+/// Call `f` with every line of `reader` (decoded, without the line ending,
+/// cut at `MAX_LINE_BYTES`) until it returns `false`. Reads in buffer-sized
+/// chunks, so memory doesn't grow with the input or its line lengths.
+pub fn each_line(
+    mut reader: impl BufRead,
+    source: &str,
+    mut f: impl FnMut(String) -> bool,
+) -> std::io::Result<()> {
+    let mut line = Vec::new();
+    let mut cut = false;
+    loop {
+        let chunk = match reader.fill_buf() {
+            Ok(chunk) => chunk,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        if chunk.is_empty() {
+            break;
+        }
+        let (part, complete) = match chunk.iter().position(|&b| b == b'\n') {
+            Some(i) => (&chunk[..=i], true),
+            None => (chunk, false),
+        };
+        let room = MAX_LINE_BYTES.saturating_sub(line.len());
+        cut |= part.len() > room;
+        line.extend_from_slice(&part[..part.len().min(room)]);
+        let used = part.len();
+        reader.consume(used);
+        if complete && !emit_line(&mut line, &mut cut, source, &mut f) {
+            return Ok(());
+        }
+    }
+    if !line.is_empty() {
+        emit_line(&mut line, &mut cut, source, &mut f);
+    }
+    Ok(())
+}
+
+fn emit_line(
+    line: &mut Vec<u8>,
+    cut: &mut bool,
+    source: &str,
+    f: &mut impl FnMut(String) -> bool,
+) -> bool {
+    if *cut {
+        // Don't leave half a UTF-8 character at the cut
+        if let Err(e) = std::str::from_utf8(line) {
+            if e.error_len().is_none() {
+                line.truncate(e.valid_up_to());
+            }
+        }
+    }
+    let (text, detected) = encoding::decode_line(line);
+    encoding::notice(source, detected);
+    line.clear();
+    *cut = false;
+    f(text.trim_end_matches(['\n', '\r']).to_string())
+}
+
+/// Run `cmd`, passing every output line to `on_line` as it arrives; whatever
+/// it returns is printed right away.
+pub fn run(cmd: &mut Command, on_line: impl FnMut(&Line) -> Option<String>) -> Result<Streamed> {
+    run_with(cmd, TIMEOUT.get().copied().flatten(), on_line)
+}
+
+fn run_with(
+    cmd: &mut Command,
+    timeout: Option<Timeout>,
+    mut on_line: impl FnMut(&Line) -> Option<String>,
+) -> Result<Streamed> {
+    #[cfg(unix)]
+    if timeout.is_some() {
+        // Own process group, so a timeout reaches the shell's children as well
+        std::os::unix::process::CommandExt::process_group(cmd, 0);
+    }
+    let mut child = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to execute command")?;
+    tracing::debug!(command = ?cmd, timeout = ?timeout.map(|t| t.secs), "spawn");
+    #[cfg(unix)]
+    let _forward = timeout.map(|_| forward::Forward::start(child.id()));
+
+    let started = Instant::now();
+    let (tx, rx) = mpsc::sync_channel::<Line>(CHANNEL_LINES);
+    let readers = [
+        (child.stdout.take()).map(|s| spawn_reader(s, Source::Stdout, started, tx.clone())),
+        (child.stderr.take()).map(|s| spawn_reader(s, Source::Stderr, started, tx)),
+    ];
+
+    let mut input = RawInput::default();
+    let mut tail = VecDeque::with_capacity(TAIL_LINES);
+    let mut deadline = timeout.map(|t| Instant::now() + Duration::from_secs(t.secs));
+    let mut signals_sent = 0;
+    let mut drained = true;
+    loop {
+        let received = match deadline {
+            Some(at) => rx.recv_timeout(at.saturating_duration_since(Instant::now())),
+            None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        let line = match received {
+            Ok(line) => line,
+            Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => {
+                let grace = Duration::from_secs(timeout.map_or(0, |t| t.grace_secs));
+                tracing::warn!(signals_sent, pid = child.id(), "timed out, stopping");
+                match signals_sent {
+                    0 => signal_group(child.id(), Stop::Term),
+                    1 => {
+                        signal_group(child.id(), Stop::Kill);
+                        let _ = child.kill();
+                    }
+                    _ => {
+                        // Something outside the group still holds the pipes open
+                        drained = false;
+                        break;
+                    }
+                }
+                signals_sent += 1;
+                deadline = Some(Instant::now() + grace);
+                continue;
+            }
+        };
+
+        input.push_line(&line.text);
+        if let Some(out) = on_line(&line) {
+            println!("{}", out);
+        }
+        if tail.len() == TAIL_LINES {
+            tail.pop_front();
+        }
+        tail.push_back(line);
+    }
+
+    if drained {
+        for reader in readers.into_iter().flatten() {
+            let _ = reader.join();
+        }
+    }
+    let status = child.wait().context("Failed to wait for command")?;
+    let (raw, input_tokens) = input.into_parts();
+    tracing::debug!(
+        code = crate::exit_code::from_status(status),
+        input_tokens,
+        drained,
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "finished"
+    );
+    Ok(Streamed {
+        status,
+        raw,
+        input_tokens,
+        tail,
+        timed_out: timeout.filter(|_| signals_sent > 0).map(|t| t.secs),
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Stop {
+    Term,
+    Kill,
+}
+
+/// Send SIGTERM or SIGKILL to the process group led by `pid`.
+#[cfg(unix)]
+fn signal_group(pid: u32, stop: Stop) {
+    let signal = match stop {
+        Stop::Term => libc::SIGTERM,
+        Stop::Kill => libc::SIGKILL,
+    };
+    // SAFETY: kill(2) has no memory effects; a stale group only gets ESRCH
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), signal);
+    }
+}
+
+/// No graceful stop on Windows: terminate the process tree.
+#[cfg(not(unix))]
+fn signal_group(pid: u32, _stop: Stop) {
+    let _ = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+/// Forwarding of terminal signals to a child moved into its own group.
+#[cfg(unix)]
+mod forward {
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    const SIGNALS: [libc::c_int; 3] = [libc::SIGINT, libc::SIGTERM, libc::SIGHUP];
+
+    /// Group signals go to; 0 when no timed command is running.
+    static GROUP: AtomicI32 = AtomicI32::new(0);
+
+    pub(super) extern "C" fn handler(signal: libc::c_int) {
+        let group = GROUP.load(Ordering::SeqCst);
+        if group > 0 {
+            // SAFETY: kill(2) is async-signal-safe
+            unsafe {
+                libc::kill(-group, signal);
+            }
+        }
+    }
+
+    /// Installed while the command runs; the previous handlers come back on
+    /// drop. rtk then exits with the child's 128 + signal status.
+    pub(super) struct Forward {
+        previous: [libc::sighandler_t; SIGNALS.len()],
+    }
+
+    impl Forward {
+        pub(super) fn start(pid: u32) -> Self {
+            GROUP.store(pid as i32, Ordering::SeqCst);
+            let handler = handler as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            // SAFETY: the handler only reads an atomic and calls kill(2)
+            let previous = SIGNALS.map(|signal| unsafe { libc::signal(signal, handler) });
+            Self { previous }
+        }
+    }
+
+    impl Drop for Forward {
+        fn drop(&mut self) {
+            for (signal, previous) in SIGNALS.iter().zip(self.previous) {
+                // SAFETY: restores what was installed before `start`
+                unsafe {
+                    libc::signal(*signal, previous);
+                }
+            }
+            GROUP.store(0, Ordering::SeqCst);
+        }
+    }
+}
+
+fn spawn_reader(
+    pipe: impl Read + Send + 'static,
+    source: Source,
+    started: Instant,
+    tx: mpsc::SyncSender<Line>,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        // Decoded per line: invalid UTF-8 never aborts the stream
+        let _ = each_line(BufReader::new(pipe), "command output", |text| {
+            let at = started.elapsed();
+            tx.send(Line { source, text, at }).is_ok()
+        });
+    })
+}
+
+/// `sh -c` (or `cmd /C` on Windows) for a shell command string.
+pub fn shell(command: &str) -> Command {
+    if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_each_line_bounds_long_lines() {
+        let mut input = "first\r\n".to_string();
+        input.push_str(&"é".repeat(MAX_LINE_BYTES));
+        input.push_str("\nlast");
+        // A tiny buffer makes lines span many reads
+        let reader = BufReader::with_capacity(7, input.as_bytes());
+        let mut lines = Vec::new();
+        each_line(reader, "test", |line| {
+            lines.push(line);
+            true
+        })
+        .unwrap();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "first");
+        assert_eq!(lines[1], "é".repeat(MAX_LINE_BYTES / 2));
+
+        // Cut in the middle of a character
+        let odd = format!("x{}\n", "é".repeat(MAX_LINE_BYTES));
+        let mut cut = Vec::new();
+        each_line(odd.as_bytes(), "test", |line| {
+            cut.push(line);
+            true
+        })
+        .unwrap();
+        assert_eq!(cut, vec![format!("x{}", "é".repeat(MAX_LINE_BYTES / 2 - 1))]);
+        assert_eq!(lines[2], "last");
+
+        let mut count = 0;
+        each_line("a\nb\nc\n".as_bytes(), "test", |_| {
+            count += 1;
+            count < 2
+        })
+        .unwrap();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_lines_arrive_in_order_with_tail() {
+        let mut seen = Vec::new();
+        let streamed = run(&mut shell("seq 1 30; echo oops >&2; exit 3"), |line| {
+            seen.push(line.clone());
+            None
+        })
+        .unwrap();
+
+        // stdout and stderr interleave freely; each stays in order
+        assert_eq!(seen.len(), 31);
+        let (stderr, stdout): (Vec<Line>, Vec<Line>) =
+            seen.into_iter().partition(|l| l.source == Source::Stderr);
+        let stdout: Vec<String> = stdout.into_iter().map(|l| l.text).collect();
+        assert_eq!(stdout, (1..=30).map(|i| i.to_string()).collect::<Vec<_>>());
+        assert_eq!(stderr.len(), 1);
+        assert_eq!(stderr[0].text, "oops");
+        assert_eq!(streamed.status.code(), Some(3));
+        assert_eq!(streamed.tail.len(), TAIL_LINES);
+        // Raw input is in arrival order, so "oops" may come anywhere
+        let raw = streamed.raw.unwrap();
+        let raw_stdout: Vec<&str> = raw.lines().filter(|l| *l != "oops").collect();
+        assert_eq!(raw_stdout[..2], ["1", "2"]);
+        assert!(streamed.input_tokens > 0);
+    }
+
+    #[test]
+    fn test_sort_by_time_restores_interleaving() {
+        let mut lines = Vec::new();
+        run(
+            &mut shell("echo a; sleep 0.2; echo b >&2; sleep 0.2; echo c"),
+            |line| {
+                lines.push(line.clone());
+                None
+            },
+        )
+        .unwrap();
+        sort_by_time(&mut lines);
+        let tagged: Vec<(Source, &str)> =
+            lines.iter().map(|l| (l.source, l.text.as_str())).collect();
+        assert_eq!(
+            tagged,
+            vec![
+                (Source::Stdout, "a"),
+                (Source::Stderr, "b"),
+                (Source::Stdout, "c")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_raw_dropped_past_limit() {
+        let streamed = run(
+            &mut shell("head -c 6000000 /dev/zero | tr '\\0' 'x' | fold -w 1000"),
+            |_| None,
+        )
+        .unwrap();
+        assert!(streamed.raw.is_none());
+        assert!(streamed.input_tokens >= 1_500_000);
+        assert_eq!(streamed.tail.back().map(|l| l.text.len()), Some(1000));
+    }
+
+    #[test]
+    fn test_timeout_terminates_then_kills() {
+        let timeout = Some(Timeout {
+            secs: 1,
+            grace_secs: 1,
+        });
+        let started = Instant::now();
+        // Ignored TERM is inherited by sleep, so only KILL stops it
+        let mut lines = Vec::new();
+        let streamed = run_with(
+            &mut shell("trap '' TERM; echo started; sleep 30; echo never"),
+            timeout,
+            |line| {
+                lines.push(line.text.clone());
+                None
+            },
+        )
+        .unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(lines, vec!["started"]);
+        assert_eq!(streamed.timed_out, Some(1));
+        assert_eq!(streamed.exit_code(), exit_code::TIMEOUT);
+        assert_eq!(
+            streamed.timeout_notice().as_deref(),
+            Some("⏱ timed out after 1s")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_interrupt_is_forwarded_to_the_group() {
+        use std::os::unix::process::{CommandExt, ExitStatusExt};
+        // The group's shell and its sleep both get the signal
+        let mut child = shell("sleep 30; sleep 30")
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let started = Instant::now();
+        {
+            let _forward = forward::Forward::start(child.id());
+            forward::handler(libc::SIGINT);
+        }
+        let status = child.wait().unwrap();
+        assert_eq!(status.signal(), Some(libc::SIGINT));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_fast_command_not_timed_out() {
+        let timeout = Some(Timeout {
+            secs: 5,
+            grace_secs: 1,
+        });
+        let streamed = run_with(&mut shell("echo ok"), timeout, |_| None).unwrap();
+        assert_eq!(streamed.timed_out, None);
+        assert_eq!(streamed.exit_code(), 0);
+    }
+
+    #[test]
+    fn test_timeout_for_prefers_command_setting() {
+        let mut config = Config::default();
+        assert_eq!(timeout_for(&config, "test"), None);
+
+        config.execution.timeout_secs = 60;
+        assert_eq!(timeout_for(&config, "test").map(|t| t.secs), Some(60));
+
+        let table: toml::Table = toml::from_str("timeout_secs = 600").unwrap();
+        config.commands.insert("test".into(), table);
+        assert_eq!(
+            timeout_for(&config, "test"),
+            Some(Timeout {
+                secs: 600,
+                grace_secs: 5
+            })
+        );
+        assert_eq!(timeout_for(&config, "err").map(|t| t.secs), Some(60));
+    }
+}
@@ -0,0 +1,193 @@
+//! Color and glyph policy shared by every printer.
+//!
+//! - Color: `--color always|never`, else `NO_COLOR` (any non-empty value),
+//!   else `display.colors` in config. `auto` leaves the terminal check to
+//!   `colored`; non-human profiles never get color.
+//! - ASCII: `--ascii`, `RTK_ASCII=1` or `display.emoji = false`. Emoji are
+//!   dropped and status glyphs, box drawing, bars and arrows become plain
+//!   ASCII, while the human layout (tables, spacing) is kept.
+//!
+//! `profile::print`/`print_line` pass all stdout text through [`apply`], so
+//! gain tables, graphs and summaries need no per-printer handling.
+
+use crate::config::Config;
+use crate::profile::Profile;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, clap::ValueEnum)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Style {
+    /// Remove ANSI sequences, including those passed through from wrapped tools
+    strip_ansi: bool,
+    ascii: bool,
+}
+
+static ACTIVE: OnceLock<Style> = OnceLock::new();
+
+lazy_static! {
+    static ref ANSI: Regex = Regex::new(r"\x1b\[[0-9;]*[A-Za-z]").unwrap();
+}
+
+/// Resolve and activate the style. Call once, after `profile::init`.
+pub fn init(color: Option<ColorChoice>, ascii: bool, profile: Profile) {
+    let config = Config::cached();
+    let no_color = std::env::var("NO_COLOR").is_ok_and(|v| !v.is_empty());
+    let choice = match color.unwrap_or_default() {
+        ColorChoice::Auto if no_color || !config.display.colors => ColorChoice::Never,
+        choice => choice,
+    };
+    let choice = if profile == Profile::Human {
+        choice
+    } else {
+        ColorChoice::Never
+    };
+    match choice {
+        ColorChoice::Always => colored::control::set_override(true),
+        ColorChoice::Never => colored::control::set_override(false),
+        ColorChoice::Auto => {}
+    }
+
+    let ascii = ascii
+        || std::env::var("RTK_ASCII").is_ok_and(|v| v == "1" || v == "true")
+        || !config.display.emoji;
+    let _ = ACTIVE.set(Style {
+        strip_ansi: choice == ColorChoice::Never,
+        ascii,
+    });
+}
+
+/// Apply the active style to text about to be printed.
+pub fn apply(text: &str) -> Cow<'_, str> {
+    let style = ACTIVE.get().copied().unwrap_or_default();
+    let mut text = Cow::Borrowed(text);
+    if style.strip_ansi {
+        text = strip_ansi(&text).into_owned().into();
+    }
+    if style.ascii && !text.is_ascii() {
+        text = to_ascii(&text).into();
+    }
+    text
+}
+
+pub fn strip_ansi(text: &str) -> Cow<'_, str> {
+    ANSI.replace_all(text, "")
+}
+
+fn is_box_drawing(c: char) -> bool {
+    ('\u{2500}'..='\u{257F}').contains(&c)
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(c,
+        '\u{1F000}'..='\u{1FAFF}'
+        | '\u{2600}'..='\u{27BF}'
+        | '\u{2B00}'..='\u{2BFF}'
+        | '\u{23E9}'..='\u{23FA}'
+        | '\u{2139}')
+}
+
+/// Separator lines made only of box-drawing characters (═══, ───).
+pub fn is_rule(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty() && trimmed.chars().all(|c| is_box_drawing(c) || c == ' ')
+}
+
+/// Replace status glyphs with words, box drawing with ASCII, and drop emoji.
+pub fn strip_glyphs(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let replacement = match c {
+            '✓' | '✔' | '✅' => Some("ok"),
+            '✗' | '✘' | '✕' | '❌' => Some("FAIL"),
+            '⚠' => Some("WARN"),
+            '│' | '┃' | '║' => Some("|"),
+            '─' | '━' => Some("-"),
+            '═' => Some("="),
+            c if is_box_drawing(c) => Some("+"),
+            // Variation selector / zero-width joiner of a multi-codepoint emoji
+            '\u{FE0F}' | '\u{200D}' => continue,
+            c if is_emoji(c) => None,
+            _ => {
+                out.push(c);
+                continue;
+            }
+        };
+        match replacement {
+            Some(word) => out.push_str(word),
+            // Dropped icon: also drop the space that separated it from the text
+            None => {
+                if chars.peek() == Some(&' ') {
+                    chars.next();
+                }
+            }
+        }
+    }
+    out
+}
+
+/// [`strip_glyphs`], then ASCII stand-ins for arrows, bullets, bars and
+/// typographic punctuation. Letters (accented file names) are kept.
+fn to_ascii(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in strip_glyphs(text).chars() {
+        match c {
+            '→' => out.push_str("->"),
+            '←' => out.push_str("<-"),
+            '⬆' | '▲' => out.push('^'),
+            '⬇' | '▼' => out.push('v'),
+            '—' | '–' => out.push('-'),
+            '…' => out.push_str("..."),
+            '•' | '●' | '◐' => out.push('*'),
+            '○' => out.push('o'),
+            '≈' => out.push('~'),
+            '×' => out.push('x'),
+            '«' => out.push_str("<<"),
+            '»' => out.push_str(">>"),
+            '›' => out.push('>'),
+            '░' | '▒' => out.push('.'),
+            // Bars and sparklines
+            '\u{2580}'..='\u{259F}' => out.push('#'),
+            // Braille spinner frames
+            '\u{2800}'..='\u{28FF}' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_glyphs_keeps_arrows_and_text() {
+        assert_eq!(strip_glyphs("a → b ⚠️ slow"), "a → b WARN slow");
+        assert_eq!(strip_glyphs("├── src"), "+-- src");
+    }
+
+    #[test]
+    fn test_to_ascii_graphs_and_tables() {
+        assert_eq!(
+            to_ascii("📊 Savings\n═══════\n████░░ 80% → done ✅"),
+            "Savings\n=======\n####.. 80% -> done ok"
+        );
+        assert_eq!(to_ascii("│ café.rs │ 1.2K │"), "| café.rs | 1.2K |");
+        assert!(to_ascii("⠋ • ● ○ … — ▁▃▅▇").is_ascii());
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi("\x1b[1;31merror\x1b[0m: x"), "error: x");
+    }
+}
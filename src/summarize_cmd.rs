@@ -0,0 +1,494 @@
+//! `rtk summarize <file>` — extractive summary of prose (READMEs, RFCs,
+//! design docs, issue text) to triage a document before reading it.
+//!
+//! The document is split into sections by its headings (markdown `#` and
+//! underlined headings, numbered RFC-style headings). The outline always
+//! comes first; the first sentence of each section and then the sentences
+//! with the highest TF-IDF weight fill the rest of the `--max-tokens` budget.
+//! Chosen sentences are printed under their heading in document order, with
+//! the heading's line number so the section can be read in full.
+
+use crate::tracking::{self, estimate_tokens};
+use crate::utils::{format_tokens, truncate};
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::Path;
+
+/// Longest sentence quoted, in characters.
+const MAX_SENTENCE: usize = 200;
+
+lazy_static! {
+    static ref ATX: Regex = Regex::new(r"^(#{1,6})\s+(.+?)\s*#*\s*$").unwrap();
+    static ref SETEXT: Regex = Regex::new(r"^(=+|-+)\s*$").unwrap();
+    static ref NUMBERED: Regex =
+        Regex::new(r"^((?:\d+\.)*\d+)\.?\s{1,4}([A-Z][^.]{0,70})$").unwrap();
+    static ref LIST_ITEM: Regex = Regex::new(r"^\s*(?:[-*+]|\d+[.)])\s+").unwrap();
+    static ref IMAGE: Regex = Regex::new(r"!\[[^\]]*\]\([^)]*\)").unwrap();
+    static ref LINK: Regex = Regex::new(r"\[([^\]]*)\]\([^)]*\)").unwrap();
+    static ref EMPHASIS: Regex = Regex::new(r"\*\*|__|`").unwrap();
+    static ref HTML_TAG: Regex = Regex::new(r"</?[a-zA-Z][^>]*>").unwrap();
+    static ref WORD: Regex = Regex::new(r"[A-Za-z][A-Za-z0-9_'-]+").unwrap();
+}
+
+const STOPWORDS: &[&str] = &[
+    "a", "about", "after", "all", "also", "an", "and", "any", "are", "as", "at", "be", "been",
+    "but", "by", "can", "could", "do", "does", "each", "for", "from", "has", "have", "how", "if",
+    "in", "into", "is", "it", "its", "may", "more", "most", "must", "no", "not", "of", "on", "one",
+    "only", "or", "other", "our", "should", "so", "some", "such", "than", "that", "the", "their",
+    "them", "then", "there", "these", "they", "this", "those", "to", "use", "used", "using", "was",
+    "we", "were", "what", "when", "which", "while", "will", "with", "would", "you", "your",
+];
+
+#[derive(Debug, Default, PartialEq)]
+struct Section {
+    /// 0 for text before the first heading
+    level: usize,
+    title: String,
+    /// 1-based line of the heading
+    line: usize,
+    sentences: Vec<String>,
+}
+
+pub fn run(file: &Path, max_tokens: usize, verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let (name, content) = if file == Path::new("-") {
+        let mut content = String::new();
+        std::io::stdin()
+            .read_to_string(&mut content)
+            .context("Failed to read from stdin")?;
+        ("stdin".to_string(), content)
+    } else {
+        let content = std::fs::read_to_string(file)
+            .with_context(|| format!("Failed to read file: {}", file.display()))?;
+        (file.display().to_string(), content)
+    };
+
+    let sections = parse(&content);
+    if verbose > 0 {
+        eprintln!(
+            "{} sections, {} sentences",
+            sections.len(),
+            sections.iter().map(|s| s.sentences.len()).sum::<usize>()
+        );
+    }
+    let summary = format!(
+        "{}\n{}",
+        header(&name, &content, &sections),
+        summarize(&sections, max_tokens, &line_label)
+    );
+    println!("{}", summary.trim_end());
+    timer.track(
+        &format!("cat {}", name),
+        &format!("rtk summarize {}", name),
+        &content,
+        &summary,
+    );
+    Ok(())
+}
+
+fn header(name: &str, content: &str, sections: &[Section]) -> String {
+    let headings = sections.iter().filter(|s| s.level > 0).count();
+    format!(
+        "{}: {} words, {} sections, ~{} tokens",
+        name,
+        WORD.find_iter(content).count(),
+        headings,
+        format_tokens(estimate_tokens(content))
+    )
+}
+
+/// Split `content` into sections of plain-text sentences.
+fn parse(content: &str) -> Vec<Section> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut sections = vec![Section::default()];
+    let mut paragraph: Vec<String> = Vec::new();
+    let mut in_code = false;
+    // In markdown, "1. Foo" is a list item, not an RFC-style heading
+    let markdown = lines.iter().any(|l| ATX.is_match(l.trim()));
+
+    let flush = |paragraph: &mut Vec<String>, sections: &mut Vec<Section>| {
+        if !paragraph.is_empty() {
+            let text = paragraph.join(" ");
+            let section = sections.last_mut().unwrap();
+            section.sentences.extend(split_sentences(&text));
+            paragraph.clear();
+        }
+    };
+
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            flush(&mut paragraph, &mut sections);
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        let underline = lines.get(i + 1).map(|l| l.trim()).unwrap_or("");
+        let heading = if let Some(caps) = ATX.captures(trimmed) {
+            Some((caps[1].len(), caps[2].to_string()))
+        } else if !trimmed.is_empty()
+            && paragraph.is_empty()
+            && !LIST_ITEM.is_match(line)
+            && SETEXT.is_match(underline)
+        {
+            Some((
+                if underline.starts_with('=') { 1 } else { 2 },
+                trimmed.to_string(),
+            ))
+        } else if markdown {
+            None
+        } else {
+            NUMBERED
+                .captures(line)
+                .map(|caps| (caps[1].split('.').count(), line.trim().to_string()))
+        };
+
+        if let Some((level, title)) = heading {
+            flush(&mut paragraph, &mut sections);
+            sections.push(Section {
+                level,
+                title: plain(&title),
+                line: i + 1,
+                sentences: Vec::new(),
+            });
+            continue;
+        }
+        let is_underline =
+            i > 0 && SETEXT.is_match(trimmed) && sections.last().is_some_and(|s| s.line == i);
+        if trimmed.is_empty()
+            || is_underline
+            || trimmed.starts_with('|')
+            || trimmed.starts_with("<!--")
+            || line.starts_with("    ")
+            || line.starts_with('\t')
+        {
+            flush(&mut paragraph, &mut sections);
+            continue;
+        }
+        // List items and quotes are sentences of their own
+        if LIST_ITEM.is_match(line) || trimmed.starts_with('>') {
+            flush(&mut paragraph, &mut sections);
+        }
+        let text = plain(
+            LIST_ITEM
+                .replace(trimmed.trim_start_matches('>'), "")
+                .trim(),
+        );
+        if !text.is_empty() {
+            paragraph.push(text);
+        }
+    }
+    flush(&mut paragraph, &mut sections);
+    if sections[0].sentences.is_empty() {
+        sections.remove(0);
+    }
+    sections
+}
+
+/// Inline markdown and HTML reduced to its text.
+fn plain(text: &str) -> String {
+    let text = IMAGE.replace_all(text, "");
+    let text = LINK.replace_all(&text, "$1");
+    let text = HTML_TAG.replace_all(&text, "");
+    EMPHASIS.replace_all(&text, "").trim().to_string()
+}
+
+/// Sentence ends: `.`, `!` or `?` followed by whitespace and an uppercase
+/// letter, digit or quote (so `e.g. foo` and `v1.2` stay whole).
+fn split_sentences(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        current.push(c);
+        let ends = matches!(c, '.' | '!' | '?')
+            && chars.get(i + 1).is_some_and(|n| n.is_whitespace())
+            && chars.get(i + 2).is_some_and(|n| {
+                n.is_uppercase() || n.is_ascii_digit() || matches!(n, '"' | '\'' | '`' | '(')
+            });
+        if ends {
+            push_sentence(&mut sentences, &current);
+            current.clear();
+        }
+    }
+    push_sentence(&mut sentences, &current);
+    sentences
+}
+
+fn push_sentence(sentences: &mut Vec<String>, text: &str) {
+    let text = text.trim();
+    // Badges, separators and stray symbols carry no words
+    if WORD.find_iter(text).count() >= 2 {
+        sentences.push(text.to_string());
+    }
+}
+
+fn terms(sentence: &str) -> Vec<String> {
+    WORD.find_iter(sentence)
+        .map(|m| m.as_str().to_lowercase())
+        .filter(|w| w.len() > 2 && !STOPWORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// TF-IDF weight of every sentence, by (section, sentence) index. Sentences
+/// are the documents; the score is averaged over terms so long sentences
+/// don't win on length alone.
+fn scores(sections: &[Section]) -> HashMap<(usize, usize), f64> {
+    let all: Vec<((usize, usize), Vec<String>)> = sections
+        .iter()
+        .enumerate()
+        .flat_map(|(s, section)| {
+            section
+                .sentences
+                .iter()
+                .enumerate()
+                .map(move |(i, text)| ((s, i), terms(text)))
+        })
+        .collect();
+    let mut df: HashMap<&str, usize> = HashMap::new();
+    for (_, terms) in &all {
+        let unique: HashSet<&str> = terms.iter().map(String::as_str).collect();
+        for term in unique {
+            *df.entry(term).or_default() += 1;
+        }
+    }
+    // Terms in the headings mark what the document is about
+    let topical: HashSet<String> = sections.iter().flat_map(|s| terms(&s.title)).collect();
+    let n = all.len() as f64;
+
+    all.iter()
+        .map(|(key, terms)| {
+            if terms.is_empty() {
+                return (*key, 0.0);
+            }
+            let mut tf: HashMap<&str, usize> = HashMap::new();
+            for term in terms {
+                *tf.entry(term).or_default() += 1;
+            }
+            let total: f64 = tf
+                .iter()
+                .map(|(term, count)| {
+                    let idf = (n / df[term] as f64).ln() + 1.0;
+                    let boost = if topical.contains(*term) { 1.5 } else { 1.0 };
+                    *count as f64 * idf * boost
+                })
+                .sum();
+            (*key, total / (terms.len() as f64).sqrt())
+        })
+        .collect()
+}
+
+fn line_label(line: usize) -> String {
+    format!("L{}", line)
+}
+
+/// Summary of `content` within `max_tokens`, headings labelled by
+/// `locate(line)` (e.g. a page number for documents, see doc_cmd.rs).
+pub(crate) fn summarize_text(
+    content: &str,
+    max_tokens: usize,
+    locate: &dyn Fn(usize) -> String,
+) -> String {
+    summarize(&parse(content), max_tokens, locate)
+}
+
+/// Outline plus the sentences that fit in `max_tokens`.
+fn summarize(sections: &[Section], max_tokens: usize, locate: &dyn Fn(usize) -> String) -> String {
+    let heading_line = |s: &Section| {
+        format!(
+            "{}{} ({})",
+            "  ".repeat(s.level.saturating_sub(1)),
+            s.title,
+            locate(s.line)
+        )
+    };
+    let sentence_line = |s: &Section, text: &str| {
+        format!(
+            "{}  {}",
+            "  ".repeat(s.level.saturating_sub(1)),
+            truncate(text, MAX_SENTENCE)
+        )
+    };
+
+    // Outline first, shallowest levels kept when even that is over budget
+    let mut max_level = sections.iter().map(|s| s.level).max().unwrap_or(0);
+    let outline_cost = |max_level: usize| -> usize {
+        sections
+            .iter()
+            .filter(|s| s.level > 0 && s.level <= max_level)
+            .map(|s| estimate_tokens(&heading_line(s)) + 1)
+            .sum()
+    };
+    while max_level > 1 && outline_cost(max_level) > max_tokens {
+        max_level -= 1;
+    }
+    let mut used = outline_cost(max_level);
+
+    // Then each section's first sentence, then the best of the rest
+    let scores = scores(sections);
+    let mut ranked: Vec<(usize, usize)> = sections
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.level <= max_level)
+        .filter(|(_, s)| !s.sentences.is_empty())
+        .map(|(i, _)| (i, 0))
+        .collect();
+    let mut rest: Vec<(usize, usize)> = scores
+        .keys()
+        .filter(|(s, i)| *i > 0 && sections[*s].level <= max_level)
+        .copied()
+        .collect();
+    rest.sort_by(|a, b| scores[b].total_cmp(&scores[a]).then(a.cmp(b)));
+    ranked.extend(rest);
+
+    let mut chosen: HashSet<(usize, usize)> = HashSet::new();
+    for (s, i) in ranked {
+        let cost = estimate_tokens(&sentence_line(&sections[s], &sections[s].sentences[i])) + 1;
+        if used + cost > max_tokens {
+            continue;
+        }
+        used += cost;
+        chosen.insert((s, i));
+    }
+
+    let mut out = Vec::new();
+    let mut omitted = 0;
+    for (s, section) in sections.iter().enumerate() {
+        if section.level > max_level {
+            omitted += 1;
+            continue;
+        }
+        if section.level > 0 {
+            out.push(heading_line(section));
+        }
+        for (i, text) in section.sentences.iter().enumerate() {
+            if chosen.contains(&(s, i)) {
+                out.push(sentence_line(section, text));
+            }
+        }
+    }
+    if omitted > 0 {
+        out.push(format!("... +{} deeper headings", omitted));
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "\
+# Widget
+
+[![build](https://ci/badge.svg)](https://ci) [![docs](https://docs/badge.svg)](https://docs)
+
+Widget is a **fast** cache for [HTTP](https://x) responses. It stores bodies on disk.
+
+## Install
+
+Run `cargo install widget` to get the binary. Nothing else is needed.
+
+```bash
+cargo install widget
+```
+
+Configuration
+-------------
+
+- Set the cache size with `--max-size`.
+- Eviction uses LRU ordering of cache entries.
+
+Security Considerations
+=======================
+
+Cached responses with credentials are never stored. The cache directory is private.
+";
+
+    #[test]
+    fn test_parse_sections_and_sentences() {
+        let sections = parse(DOC);
+        let titles: Vec<(usize, &str, usize)> = sections
+            .iter()
+            .map(|s| (s.level, s.title.as_str(), s.line))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![
+                (1, "Widget", 1),
+                (2, "Install", 7),
+                (2, "Configuration", 15),
+                (1, "Security Considerations", 21),
+            ]
+        );
+        assert_eq!(
+            sections[0].sentences,
+            vec![
+                "Widget is a fast cache for HTTP responses.",
+                "It stores bodies on disk."
+            ]
+        );
+        // Code blocks skipped, list items kept apart
+        assert_eq!(sections[1].sentences.len(), 2);
+        assert_eq!(
+            sections[2].sentences,
+            vec![
+                "Set the cache size with --max-size.",
+                "Eviction uses LRU ordering of cache entries."
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_sentences_keeps_abbreviations() {
+        assert_eq!(
+            split_sentences("Use a tool, e.g. rtk v1.2 here. Then stop! 3 more follow."),
+            vec![
+                "Use a tool, e.g. rtk v1.2 here.",
+                "Then stop!",
+                "3 more follow."
+            ]
+        );
+    }
+
+    #[test]
+    fn test_summary_fits_budget_with_outline_and_first_sentences() {
+        let sections = parse(DOC);
+        let summary = summarize(&sections, 60, &line_label);
+        assert!(estimate_tokens(&summary) <= 60 + sections.len());
+        assert!(summary.starts_with("Widget (L1)\n  Widget is a fast cache"));
+        assert!(summary.contains("  Install (L7)\n    Run cargo install widget"));
+        assert!(!summary.contains("Nothing else is needed"));
+
+        let full = summarize(&sections, 10_000, &line_label);
+        assert!(full.contains("Nothing else is needed"));
+    }
+
+    #[test]
+    fn test_tiny_budget_drops_deeper_headings() {
+        let sections = parse(DOC);
+        let summary = summarize(&sections, 5, &line_label);
+        assert_eq!(
+            summary,
+            "Widget (L1)\nSecurity Considerations (L21)\n... +2 deeper headings"
+        );
+    }
+
+    #[test]
+    fn test_numbered_headings_outside_markdown() {
+        let rfc = "1.  Introduction\n\n   This memo defines a cache.\n\n1.1.  Terminology\n\n   Words are defined here.\n";
+        let sections = parse(rfc);
+        let titles: Vec<(usize, &str)> = sections
+            .iter()
+            .map(|s| (s.level, s.title.as_str()))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![(1, "1.  Introduction"), (2, "1.1.  Terminology")]
+        );
+    }
+}
@@ -30,10 +30,13 @@ pub fn run(command: &str, verbose: u8) -> Result<()> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
     let summary = summarize_output(&raw, command, output.status.success());
     println!("{}", summary);
-    timer.track(command, "rtk summary", &raw, &summary);
+    timer
+        .with_exit_code(output.status)
+        .track(command, "rtk summary", &raw, &summary);
     Ok(())
 }
 
@@ -0,0 +1,489 @@
+//! System package managers (brew, apt/apt-get, dnf/yum): installs list every
+//! dependency, mirror hit and unpack step. This reports what will be or was
+//! installed, upgraded and removed, with sizes. The full output is shown only
+//! when the command failed, and dpkg conffile prompts are shown verbatim.
+//!
+//! stdin is closed, so a confirmation prompt (`Do you want to continue?`)
+//! aborts instead of hanging: the planned transaction is still reported,
+//! with a hint to re-run with `-y`.
+
+use crate::install_cmd;
+use crate::tracking;
+use crate::utils::strip_ansi;
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::ffi::OsString;
+use std::process::{Command, Stdio};
+
+/// Package names listed per action before collapsing to a count.
+const MAX_NAMES: usize = 15;
+
+lazy_static! {
+    // apt: "The following NEW packages will be installed:"
+    static ref APT_SECTION: Regex = Regex::new(
+        r"^The following (?:(NEW) packages will be installed|packages will be (upgraded|REMOVED|DOWNGRADED)|packages will be (reinstalled)):"
+    )
+    .unwrap();
+    static ref APT_OTHER_SECTION: Regex = Regex::new(r"^(The following|Suggested packages|Recommended packages)").unwrap();
+    static ref APT_DOWNLOAD: Regex = Regex::new(r"^Need to get ([\d.,]+ [kMG]?B)").unwrap();
+    static ref APT_DISK: Regex =
+        Regex::new(r"^After this operation, ([\d.,]+ [kMG]?B) (?:of additional disk space will be used|disk space will be (freed))").unwrap();
+    // dnf: "Installing:", "Upgrading:", "Removing dependent packages:"
+    static ref DNF_SECTION: Regex = Regex::new(
+        r"^(Installing|Upgrading|Removing|Downgrading|Reinstalling)(?: (?:weak )?dependencies| dependent packages| unused dependencies)?:$"
+    )
+    .unwrap();
+    // " curl   x86_64   7.76.1-26.el9   baseos   297 k"
+    static ref DNF_ROW: Regex = Regex::new(r"^ (\S+)\s+\S+\s+\S+\s+\S+\s+([\d.]+ [kMG])$").unwrap();
+    static ref DNF_DOWNLOAD: Regex = Regex::new(r"^Total download size: (.+)$").unwrap();
+    static ref DNF_DISK: Regex = Regex::new(r"^(?:Installed size|Freed space): (.+)$").unwrap();
+    // brew: "🍺  /opt/homebrew/Cellar/wget/1.21.4: 91 files, 4.5MB"
+    static ref BREW_POURED: Regex =
+        Regex::new(r"^🍺\s+\S*/Cellar/([^/]+)/([^:/]+): [\d,]+ files?, (\S+)$").unwrap();
+    static ref BREW_UPGRADING: Regex = Regex::new(r"^==> Upgrading (\S+)$").unwrap();
+    static ref BREW_UNINSTALLED: Regex =
+        Regex::new(r"^Uninstalling \S*/Cellar/([^/]+)/\S+\.\.\. \([\d,]+ files?, (\S+)\)$").unwrap();
+    static ref ERROR: Regex = Regex::new(r"^(E: |Error: |Error:|error: |dpkg: error)").unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Action {
+    Install,
+    Upgrade,
+    Downgrade,
+    Reinstall,
+    Remove,
+}
+
+impl Action {
+    fn label(self) -> &'static str {
+        match self {
+            Action::Install => "installed",
+            Action::Upgrade => "upgraded",
+            Action::Downgrade => "downgraded",
+            Action::Reinstall => "reinstalled",
+            Action::Remove => "removed",
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Transaction {
+    packages: Vec<(Action, String, Option<String>)>,
+    download: Option<String>,
+    /// Disk change, negative when space is freed
+    disk: Option<String>,
+    /// The transaction ran (as opposed to only being planned)
+    applied: bool,
+    /// Aborted at a confirmation prompt
+    needs_confirmation: bool,
+    errors: Vec<String>,
+    /// dpkg conffile prompts, verbatim
+    conffiles: Vec<String>,
+}
+
+/// Subcommands that change installed packages.
+pub fn is_transaction(program: &str, subcommand: Option<&str>) -> bool {
+    let Some(sub) = subcommand else {
+        return false;
+    };
+    match program {
+        "apt" | "apt-get" => matches!(
+            sub,
+            "install"
+                | "remove"
+                | "purge"
+                | "upgrade"
+                | "full-upgrade"
+                | "dist-upgrade"
+                | "autoremove"
+                | "reinstall"
+        ),
+        "dnf" | "yum" => matches!(
+            sub,
+            "install"
+                | "remove"
+                | "erase"
+                | "upgrade"
+                | "update"
+                | "downgrade"
+                | "reinstall"
+                | "autoremove"
+        ),
+        "brew" => matches!(
+            sub,
+            "install" | "upgrade" | "uninstall" | "remove" | "rm" | "reinstall"
+        ),
+        _ => false,
+    }
+}
+
+pub fn run(program: &str, args: &[String], verbose: u8) -> Result<()> {
+    if !is_transaction(program, args.first().map(String::as_str)) {
+        let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+        return install_cmd::run_passthrough(program, &args, verbose);
+    }
+
+    let timer = tracking::TimedExecution::start();
+    let label = format!("{} {}", program, args.join(" "));
+    if verbose > 0 {
+        eprintln!("Running: {}", label);
+    }
+
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .env("HOMEBREW_NO_COLOR", "1")
+        .env("HOMEBREW_NO_ENV_HINTS", "1")
+        .output()
+        .with_context(|| format!("Failed to run {}", program))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let raw = strip_ansi(&format!("{}\n{}", stdout, stderr));
+
+    let transaction = parse(&raw);
+    let mut filtered = format_transaction(&label, &transaction, output.status.success());
+    if !output.status.success() && !transaction.needs_confirmation {
+        filtered.push_str("\n--- full output ---\n");
+        filtered.push_str(raw.trim());
+    }
+    println!("{}", filtered);
+
+    timer
+        .with_exit_code(output.status)
+        .track(&label, &format!("rtk {}", label), &raw, &filtered);
+    Ok(())
+}
+
+fn parse(output: &str) -> Transaction {
+    let mut t = Transaction::default();
+    let mut apt_section: Option<Action> = None;
+    let mut dnf_section: Option<Action> = None;
+    let mut brew_upgrading: Vec<String> = Vec::new();
+    let mut in_conffile = false;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+
+        // dpkg conffile prompt: kept verbatim up to its question
+        if trimmed.starts_with("Configuration file '") {
+            in_conffile = true;
+        }
+        if in_conffile {
+            t.conffiles.push(line.to_string());
+            if trimmed.contains("[default=") || trimmed.is_empty() {
+                in_conffile = false;
+            }
+            continue;
+        }
+
+        if let Some(caps) = APT_SECTION.captures(line) {
+            apt_section = Some(if caps.get(1).is_some() {
+                Action::Install
+            } else if let Some(m) = caps.get(2) {
+                match m.as_str() {
+                    "upgraded" => Action::Upgrade,
+                    "REMOVED" => Action::Remove,
+                    _ => Action::Downgrade,
+                }
+            } else {
+                Action::Reinstall
+            });
+            continue;
+        }
+        if let Some(action) = apt_section {
+            if line.starts_with("  ") {
+                for name in trimmed.split_whitespace() {
+                    // `foo*` marks a purge
+                    t.packages
+                        .push((action, name.trim_end_matches('*').to_string(), None));
+                }
+                continue;
+            }
+            apt_section = None;
+        }
+        if APT_OTHER_SECTION.is_match(line) {
+            continue;
+        }
+        if let Some(caps) = APT_DOWNLOAD.captures(line) {
+            t.download = Some(caps[1].to_string());
+        }
+        if let Some(caps) = APT_DISK.captures(line) {
+            let sign = if caps.get(2).is_some() { "-" } else { "+" };
+            t.disk = Some(format!("{}{}", sign, &caps[1]));
+        }
+        if line.starts_with("Setting up ") || line.starts_with("Removing ") {
+            t.applied = true;
+        }
+
+        if let Some(caps) = DNF_SECTION.captures(line) {
+            dnf_section = Some(match &caps[1] {
+                "Installing" => Action::Install,
+                "Upgrading" => Action::Upgrade,
+                "Downgrading" => Action::Downgrade,
+                "Reinstalling" => Action::Reinstall,
+                _ => Action::Remove,
+            });
+            continue;
+        }
+        if let Some(action) = dnf_section {
+            if let Some(caps) = DNF_ROW.captures(line) {
+                let size = caps[2].replace(' ', "");
+                t.packages.push((action, caps[1].to_string(), Some(size)));
+                continue;
+            }
+            // Wrapped rows (" replacing ...") stay in the section
+            if !line.starts_with("  ") {
+                dnf_section = None;
+            }
+        }
+        if let Some(caps) = DNF_DOWNLOAD.captures(line) {
+            t.download = Some(caps[1].trim().to_string());
+        }
+        if let Some(caps) = DNF_DISK.captures(line) {
+            let sign = if line.starts_with("Freed") { "-" } else { "+" };
+            t.disk = Some(format!("{}{}", sign, caps[1].trim()));
+        }
+        if trimmed == "Complete!" {
+            t.applied = true;
+        }
+
+        if let Some(caps) = BREW_UPGRADING.captures(line) {
+            brew_upgrading.push(caps[1].to_string());
+        }
+        if let Some(caps) = BREW_POURED.captures(line) {
+            let name = caps[1].to_string();
+            let action = if brew_upgrading.contains(&name) {
+                Action::Upgrade
+            } else {
+                Action::Install
+            };
+            t.packages.push((
+                action,
+                format!("{} {}", name, &caps[2]),
+                Some(caps[3].to_string()),
+            ));
+            t.applied = true;
+        }
+        if let Some(caps) = BREW_UNINSTALLED.captures(line) {
+            t.packages.push((
+                Action::Remove,
+                caps[1].to_string(),
+                Some(caps[2].to_string()),
+            ));
+            t.applied = true;
+        }
+
+        if trimmed.contains("Do you want to continue? [Y/n] Abort")
+            || trimmed.starts_with("Operation aborted")
+            || trimmed.contains("Is this ok [y/N]: Operation aborted")
+        {
+            t.needs_confirmation = true;
+        } else if ERROR.is_match(trimmed) {
+            t.errors.push(trimmed.to_string());
+        }
+    }
+    t
+}
+
+fn format_transaction(label: &str, t: &Transaction, success: bool) -> String {
+    let actions = [
+        Action::Install,
+        Action::Upgrade,
+        Action::Downgrade,
+        Action::Reinstall,
+        Action::Remove,
+    ];
+    let mut counts = Vec::new();
+    let mut details = Vec::new();
+    for action in actions {
+        let names: Vec<String> = t
+            .packages
+            .iter()
+            .filter(|(a, _, _)| *a == action)
+            .map(|(_, name, size)| match size {
+                Some(size) => format!("{} ({})", name, size),
+                None => name.clone(),
+            })
+            .collect();
+        if names.is_empty() {
+            continue;
+        }
+        counts.push(format!("{} {}", names.len(), action.label()));
+        let mut shown = names[..names.len().min(MAX_NAMES)].join(", ");
+        if names.len() > MAX_NAMES {
+            shown.push_str(&format!(", +{} more", names.len() - MAX_NAMES));
+        }
+        details.push(format!("  {}: {}", action.label(), shown));
+    }
+
+    let mut sizes = Vec::new();
+    if let Some(download) = &t.download {
+        sizes.push(format!("download {}", download));
+    }
+    if let Some(disk) = &t.disk {
+        sizes.push(format!("disk {}", disk));
+    }
+    let sizes = if sizes.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", sizes.join(", "))
+    };
+
+    let (mark, summary) = if t.needs_confirmation {
+        ("⏸", format!("would be {}", join_or(&counts, "no changes")))
+    } else if !success {
+        ("✗", join_or(&counts, "nothing changed"))
+    } else if counts.is_empty() {
+        ("✓", "nothing to do".to_string())
+    } else if t.applied {
+        ("✓", counts.join(", "))
+    } else {
+        ("✓", format!("planned: {}", counts.join(", ")))
+    };
+    let mut out = vec![format!("{} {}: {}{}", mark, label, summary, sizes)];
+    out.extend(details);
+    if t.needs_confirmation {
+        out.push("aborted at the confirmation prompt: re-run with -y to apply".to_string());
+    }
+    for error in &t.errors {
+        out.push(error.clone());
+    }
+    if !t.conffiles.is_empty() {
+        out.push("conffile prompt:".to_string());
+        out.extend(t.conffiles.iter().cloned());
+    }
+    out.join("\n")
+}
+
+fn join_or(parts: &[String], empty: &str) -> String {
+    if parts.is_empty() {
+        empty.to_string()
+    } else {
+        parts.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apt_install() {
+        let output = "Reading package lists...\n\
+                      Building dependency tree...\n\
+                      The following additional packages will be installed:\n\
+                      \x20 libcurl4\n\
+                      Suggested packages:\n\
+                      \x20 curl-doc\n\
+                      The following NEW packages will be installed:\n\
+                      \x20 curl libcurl4\n\
+                      The following packages will be upgraded:\n\
+                      \x20 openssl\n\
+                      1 upgraded, 2 newly installed, 0 to remove and 12 not upgraded.\n\
+                      Need to get 1,734 kB of archives.\n\
+                      After this operation, 3,456 kB of additional disk space will be used.\n\
+                      Get:1 http://archive.ubuntu.com/ubuntu jammy/main amd64 curl amd64 7.81.0 [194 kB]\n\
+                      Unpacking curl (7.81.0) ...\n\
+                      Setting up curl (7.81.0) ...\n";
+        let t = parse(output);
+        assert!(t.applied);
+        assert_eq!(
+            format_transaction("apt install -y curl", &t, true),
+            "✓ apt install -y curl: 2 installed, 1 upgraded (download 1,734 kB, disk +3,456 kB)\n\
+             \x20 installed: curl, libcurl4\n\
+             \x20 upgraded: openssl"
+        );
+    }
+
+    #[test]
+    fn test_apt_needs_confirmation() {
+        let output = "The following packages will be REMOVED:\n\
+                      \x20 nginx* nginx-common*\n\
+                      After this operation, 2,048 kB disk space will be freed.\n\
+                      Do you want to continue? [Y/n] Abort.\n";
+        let t = parse(output);
+        assert!(t.needs_confirmation);
+        let out = format_transaction("apt purge nginx", &t, false);
+        assert!(out.starts_with("⏸ apt purge nginx: would be 2 removed (disk -2,048 kB)"));
+        assert!(out.contains("re-run with -y"));
+    }
+
+    #[test]
+    fn test_apt_conffile_prompt() {
+        let output = "Setting up nginx-common (1.24.0) ...\n\
+                      \n\
+                      Configuration file '/etc/nginx/nginx.conf'\n\
+                      \x20==> Modified (by you or by a script) since installation.\n\
+                      \x20==> Package distributor has shipped an updated version.\n\
+                      \x20  What would you like to do about it ?  Your options are:\n\
+                      \x20*** nginx.conf (Y/I/N/O/D/Z) [default=N] ? dpkg: error processing package nginx-common (--configure):\n\
+                      E: Sub-process /usr/bin/dpkg returned an error code (1)\n";
+        let t = parse(output);
+        assert_eq!(t.conffiles.len(), 5);
+        assert_eq!(
+            t.errors,
+            vec!["E: Sub-process /usr/bin/dpkg returned an error code (1)"]
+        );
+    }
+
+    #[test]
+    fn test_dnf_install() {
+        let output = "Dependencies resolved.\n\
+                      ================================================================================\n\
+                      \x20Package          Architecture   Version             Repository           Size\n\
+                      ================================================================================\n\
+                      Installing:\n\
+                      \x20curl             x86_64         7.76.1-26.el9       baseos              297 k\n\
+                      Installing dependencies:\n\
+                      \x20libcurl          x86_64         7.76.1-26.el9       baseos              1.2 M\n\
+                      \n\
+                      Transaction Summary\n\
+                      ================================================================================\n\
+                      Install  2 Packages\n\
+                      \n\
+                      Total download size: 1.5 M\n\
+                      Installed size: 3.4 M\n\
+                      Downloading Packages:\n\
+                      Installed:\n\
+                      \x20 curl-7.76.1-26.el9.x86_64   libcurl-7.76.1-26.el9.x86_64\n\
+                      \n\
+                      Complete!\n";
+        let t = parse(output);
+        assert_eq!(
+            format_transaction("dnf install -y curl", &t, true),
+            "✓ dnf install -y curl: 2 installed (download 1.5 M, disk +3.4 M)\n\
+             \x20 installed: curl (297k), libcurl (1.2M)"
+        );
+    }
+
+    #[test]
+    fn test_brew_install_and_upgrade() {
+        let output = "==> Downloading https://ghcr.io/v2/homebrew/core/wget/manifests/1.21.4\n\
+                      ==> Fetching dependencies for wget: libunistring\n\
+                      ==> Installing wget dependency: libunistring\n\
+                      ==> Pouring libunistring--1.1.arm64_sonoma.bottle.tar.gz\n\
+                      🍺  /opt/homebrew/Cellar/libunistring/1.1: 56 files, 5.0MB\n\
+                      ==> Upgrading openssl@3\n\
+                      \x20 3.1.0 -> 3.2.0\n\
+                      🍺  /opt/homebrew/Cellar/openssl@3/3.2.0: 6,800 files, 30MB\n\
+                      ==> Running `brew cleanup wget`...\n";
+        let t = parse(output);
+        let out = format_transaction("brew install wget", &t, true);
+        assert_eq!(
+            out,
+            "✓ brew install wget: 1 installed, 1 upgraded\n\
+             \x20 installed: libunistring 1.1 (5.0MB)\n\
+             \x20 upgraded: openssl@3 3.2.0 (30MB)"
+        );
+    }
+
+    #[test]
+    fn test_is_transaction() {
+        assert!(is_transaction("apt-get", Some("dist-upgrade")));
+        assert!(is_transaction("brew", Some("uninstall")));
+        assert!(!is_transaction("apt", Some("list")));
+        assert!(!is_transaction("dnf", None));
+    }
+}
@@ -0,0 +1,442 @@
+//! `rtk systemctl`: unit states instead of status trees.
+//!
+//! - `status <units>`: one line per unit (state, enablement, PID, memory);
+//!   journal lines only for failed units.
+//! - `list-units` (or no subcommand): counts by state and type, failed
+//!   units with their last journal lines.
+//! - Anything else (start, restart, enable, ...) runs unchanged.
+
+use crate::exit_code;
+use crate::tracking;
+use crate::utils::truncate;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::process::{Command, ExitStatus};
+
+/// Journal lines shown per failed unit.
+const JOURNAL_LINES: usize = 5;
+/// Failed units listed by `list-units`.
+const MAX_FAILED: usize = 10;
+
+/// Unit status glyphs in `systemctl status` headers, across systemd versions.
+const STATUS_GLYPHS: &[char] = &['●', '○', '×', '↻', '*'];
+
+#[derive(Debug, Default, PartialEq)]
+struct UnitStatus {
+    unit: String,
+    description: String,
+    /// `active (running)`, `failed (Result: exit-code)`, ...
+    state: String,
+    /// `4 days ago`
+    since: Option<String>,
+    /// `enabled`, `disabled`, `static`, ...
+    enablement: Option<String>,
+    main_pid: Option<String>,
+    memory: Option<String>,
+    journal: Vec<String>,
+}
+
+impl UnitStatus {
+    fn failed(&self) -> bool {
+        self.state.starts_with("failed")
+    }
+}
+
+/// Units in `systemctl status` output.
+fn parse_status(output: &str) -> Vec<UnitStatus> {
+    let mut units: Vec<UnitStatus> = Vec::new();
+    let mut in_journal = false;
+    for line in output.lines() {
+        if let Some(header) = line
+            .strip_prefix(STATUS_GLYPHS)
+            .map(str::trim_start)
+            .filter(|_| !line.starts_with(' '))
+        {
+            let (unit, description) = header.split_once(" - ").unwrap_or((header, ""));
+            units.push(UnitStatus {
+                unit: unit.trim().to_string(),
+                description: description.trim().to_string(),
+                ..Default::default()
+            });
+            in_journal = false;
+            continue;
+        }
+        let Some(current) = units.last_mut() else {
+            continue;
+        };
+        if line.trim().is_empty() {
+            // Properties end at the first blank line; the journal follows
+            in_journal = true;
+            continue;
+        }
+        if in_journal {
+            current.journal.push(line.trim().to_string());
+            continue;
+        }
+        let Some((key, value)) = line.trim().split_once(": ") else {
+            continue;
+        };
+        match key {
+            "Active" => {
+                let (state, rest) = value.split_once(" since ").unwrap_or((value, ""));
+                current.state = state.trim().to_string();
+                current.since = rest
+                    .rsplit_once("; ")
+                    .map(|(_, ago)| ago.trim().to_string());
+            }
+            "Loaded" => {
+                // loaded (/lib/systemd/system/x.service; enabled; preset: enabled)
+                current.enablement = value
+                    .split(';')
+                    .nth(1)
+                    .map(|s| s.trim().trim_end_matches(')').to_string())
+                    .filter(|s| !s.is_empty());
+            }
+            "Main PID" => current.main_pid = Some(value.trim().to_string()),
+            "Memory" => {
+                current.memory = value.split_whitespace().next().map(str::to_string);
+            }
+            _ => {}
+        }
+    }
+    units
+}
+
+fn format_status(units: &[UnitStatus]) -> String {
+    let mut out = Vec::new();
+    for unit in units {
+        let mut parts = vec![match &unit.since {
+            Some(since) => format!("{}, {}", unit.state, since),
+            None => unit.state.clone(),
+        }];
+        parts.extend(unit.enablement.clone());
+        if let Some(pid) = &unit.main_pid {
+            parts.push(format!("pid {}", pid));
+        }
+        if let Some(memory) = &unit.memory {
+            parts.push(format!("mem {}", memory));
+        }
+        let icon = if unit.failed() {
+            "✗"
+        } else if unit.state.starts_with("active") {
+            "✓"
+        } else {
+            "○"
+        };
+        out.push(format!("{} {}: {}", icon, unit.unit, parts.join(" | ")));
+        if unit.failed() {
+            let skip = unit.journal.len().saturating_sub(JOURNAL_LINES);
+            for line in &unit.journal[skip..] {
+                out.push(format!("    {}", truncate(line, 160)));
+            }
+        }
+    }
+    out.join("\n")
+}
+
+#[derive(Debug, PartialEq)]
+struct UnitRow {
+    unit: String,
+    active: String,
+    sub: String,
+    description: String,
+}
+
+/// Rows of `systemctl list-units --plain --no-legend`.
+fn parse_list_units(output: &str) -> Vec<UnitRow> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start_matches(STATUS_GLYPHS).trim();
+            let mut fields = line.split_whitespace();
+            let unit = fields.next()?.to_string();
+            let _load = fields.next()?;
+            let active = fields.next()?.to_string();
+            let sub = fields.next()?.to_string();
+            Some(UnitRow {
+                unit,
+                active,
+                sub,
+                description: fields.collect::<Vec<_>>().join(" "),
+            })
+        })
+        .collect()
+}
+
+/// Counts by state and type, then failed units with their journals.
+fn format_list_units(rows: &[UnitRow], journals: &BTreeMap<String, Vec<String>>) -> String {
+    if rows.is_empty() {
+        return "✓ systemctl: 0 units".to_string();
+    }
+    let mut by_state: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut by_type: BTreeMap<&str, usize> = BTreeMap::new();
+    for row in rows {
+        *by_state.entry(&row.active).or_default() += 1;
+        let kind = row.unit.rsplit_once('.').map_or("other", |(_, t)| t);
+        *by_type.entry(kind).or_default() += 1;
+    }
+    let sorted = |counts: BTreeMap<&str, usize>| {
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        counts
+            .iter()
+            .map(|(name, n)| format!("{} {}", n, name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut out = vec![
+        format!("⚙️ {} units: {}", rows.len(), sorted(by_state)),
+        format!("  by type: {}", sorted(by_type)),
+    ];
+    let failed: Vec<&UnitRow> = rows.iter().filter(|r| r.active == "failed").collect();
+    if failed.is_empty() {
+        out.push("✓ no failed units".to_string());
+        return out.join("\n");
+    }
+    out.push(String::new());
+    out.push(format!("✗ failed ({})", failed.len()));
+    for row in failed.iter().take(MAX_FAILED) {
+        out.push(format!("  {} ({}) {}", row.unit, row.sub, row.description));
+        for line in journals.get(&row.unit).into_iter().flatten() {
+            out.push(format!("    {}", truncate(line, 160)));
+        }
+    }
+    if failed.len() > MAX_FAILED {
+        out.push(format!("  +{} more failed", failed.len() - MAX_FAILED));
+    }
+    out.join("\n")
+}
+
+/// Last journal messages of `unit`; `user` for `systemctl --user` units.
+fn journal_tail(unit: &str, user: bool) -> Vec<String> {
+    let mut cmd = Command::new("journalctl");
+    if user {
+        cmd.arg("--user");
+    }
+    cmd.args(["-u", unit, "-n"])
+        .arg(JOURNAL_LINES.to_string())
+        .args(["--no-pager", "-o", "cat"]);
+    match cmd.output() {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn run_systemctl(args: &[String]) -> Result<(String, String, ExitStatus)> {
+    let output = Command::new("systemctl")
+        .args(args)
+        .output()
+        .context("Failed to run systemctl")?;
+    Ok((
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+        output.status,
+    ))
+}
+
+fn run_status(args: &[String], verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let mut cmd_args = args.to_vec();
+    cmd_args.push("--no-pager".to_string());
+    if !args
+        .iter()
+        .any(|a| a == "-n" || a.starts_with("--lines") || a.starts_with("-n"))
+    {
+        cmd_args.push(format!("--lines={}", JOURNAL_LINES));
+    }
+    if verbose > 0 {
+        eprintln!("Running: systemctl {}", cmd_args.join(" "));
+    }
+    let (stdout, stderr, status) = run_systemctl(&cmd_args)?;
+
+    let units = parse_status(&stdout);
+    let mut filtered = format_status(&units);
+    // Missing units are only reported on stderr
+    if !stderr.trim().is_empty() {
+        if !filtered.is_empty() {
+            filtered.push('\n');
+        }
+        filtered.push_str(stderr.trim());
+    }
+    println!("{}", filtered);
+
+    // status exits 3 for inactive or failed units, 4 for unknown ones
+    timer.with_exit_code(status).track(
+        &format!("systemctl {}", args.join(" ")),
+        &format!("rtk systemctl {}", args.join(" ")),
+        &format!("{}\n{}", stdout, stderr),
+        &filtered,
+    );
+    Ok(())
+}
+
+fn run_list_units(args: &[String], verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let mut cmd_args = args.to_vec();
+    if !args.iter().any(|a| a == "list-units") {
+        cmd_args.insert(0, "list-units".to_string());
+    }
+    cmd_args.extend(["--plain", "--no-legend", "--no-pager"].map(String::from));
+    if verbose > 0 {
+        eprintln!("Running: systemctl {}", cmd_args.join(" "));
+    }
+    let (stdout, stderr, status) = run_systemctl(&cmd_args)?;
+    if !status.success() {
+        eprint!("{}", stderr);
+        timer.with_exit_code(status).track(
+            &format!("systemctl {}", args.join(" ")),
+            &format!("rtk systemctl {}", args.join(" ")),
+            &stderr,
+            &stderr,
+        );
+        return Ok(());
+    }
+
+    let rows = parse_list_units(&stdout);
+    let user = args.iter().any(|a| a == "--user");
+    let journals: BTreeMap<String, Vec<String>> = rows
+        .iter()
+        .filter(|r| r.active == "failed")
+        .take(MAX_FAILED)
+        .map(|r| (r.unit.clone(), journal_tail(&r.unit, user)))
+        .collect();
+    let filtered = format_list_units(&rows, &journals);
+    println!("{}", filtered);
+
+    // What `systemctl list-units` plus `journalctl -u` per failure would print
+    let raw = std::iter::once(stdout.clone())
+        .chain(journals.values().map(|j| j.join("\n")))
+        .collect::<Vec<_>>()
+        .join("\n");
+    timer.with_exit_code(status).track(
+        &format!("systemctl {}", args.join(" ")),
+        &format!("rtk systemctl {}", args.join(" ")),
+        &raw,
+        &filtered,
+    );
+    Ok(())
+}
+
+fn run_passthrough(args: &[String], verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    if verbose > 0 {
+        eprintln!("systemctl passthrough: {:?}", args);
+    }
+    let status = Command::new("systemctl")
+        .args(args)
+        .status()
+        .context("Failed to run systemctl")?;
+
+    timer.with_exit_code(status).track_passthrough(
+        &format!("systemctl {}", args.join(" ")),
+        &format!("rtk systemctl {} (passthrough)", args.join(" ")),
+    );
+    if !status.success() {
+        exit_code::exit_with(status);
+    }
+    Ok(())
+}
+
+pub fn run(args: &[String], verbose: u8) -> Result<()> {
+    let subcommand = args
+        .iter()
+        .find(|a| !a.starts_with('-'))
+        .map(String::as_str);
+    match subcommand {
+        Some("status") => {
+            // Without units, status prints the whole cgroup tree
+            if args.iter().filter(|a| !a.starts_with('-')).count() > 1 {
+                run_status(args, verbose)
+            } else {
+                let rest: Vec<String> = args.iter().filter(|a| *a != "status").cloned().collect();
+                run_list_units(&rest, verbose)
+            }
+        }
+        None | Some("list-units") => run_list_units(args, verbose),
+        _ => run_passthrough(args, verbose),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATUS: &str = "\
+● nginx.service - A high performance web server and a reverse proxy server
+     Loaded: loaded (/lib/systemd/system/nginx.service; enabled; vendor preset: enabled)
+     Active: active (running) since Mon 2026-10-12 10:00:00 UTC; 4 days ago
+       Docs: man:nginx(8)
+   Main PID: 1234 (nginx)
+      Tasks: 3 (limit: 4915)
+     Memory: 12.3M
+        CPU: 1.2s
+     CGroup: /system.slice/nginx.service
+             ├─1234 \"nginx: master process\"
+             └─1235 \"nginx: worker process\"
+
+Oct 12 10:00:00 host systemd[1]: Started nginx.
+
+× backup.service - Nightly backup
+     Loaded: loaded (/etc/systemd/system/backup.service; static)
+     Active: failed (Result: exit-code) since Fri 2026-10-16 02:00:03 UTC; 3h ago
+    Process: 999 ExecStart=/usr/local/bin/backup.sh (code=exited, status=1/FAILURE)
+   Main PID: 999 (code=exited, status=1/FAILURE)
+
+Oct 16 02:00:01 host backup.sh[999]: rsync: connection refused
+Oct 16 02:00:03 host systemd[1]: backup.service: Failed with result 'exit-code'.
+";
+
+    #[test]
+    fn test_status_journal_only_for_failed() {
+        let units = parse_status(STATUS);
+        assert_eq!(units.len(), 2);
+        assert_eq!(units[0].state, "active (running)");
+        assert_eq!(units[0].enablement.as_deref(), Some("enabled"));
+        assert_eq!(units[1].enablement.as_deref(), Some("static"));
+        let report = format_status(&units);
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(
+            lines[0],
+            "✓ nginx.service: active (running), 4 days ago | enabled | pid 1234 (nginx) | mem 12.3M"
+        );
+        assert_eq!(
+            lines[1],
+            "✗ backup.service: failed (Result: exit-code), 3h ago | static | pid 999 (code=exited, status=1/FAILURE)"
+        );
+        assert_eq!(
+            lines[2],
+            "    Oct 16 02:00:01 host backup.sh[999]: rsync: connection refused"
+        );
+        assert_eq!(lines.len(), 4);
+        assert!(!report.contains("Started nginx"));
+    }
+
+    #[test]
+    fn test_list_units_summary() {
+        let output = "\
+nginx.service loaded active running A high performance web server
+backup.service loaded failed failed Nightly backup
+ssh.socket loaded active listening OpenBSD Secure Shell server socket
+apt-daily.timer loaded active waiting Daily apt download activities
+";
+        let rows = parse_list_units(output);
+        assert_eq!(rows[1].description, "Nightly backup");
+        let mut journals = BTreeMap::new();
+        journals.insert(
+            "backup.service".to_string(),
+            vec!["rsync: connection refused".to_string()],
+        );
+        let report = format_list_units(&rows, &journals);
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "⚙️ 4 units: 3 active, 1 failed");
+        assert_eq!(lines[1], "  by type: 2 service, 1 socket, 1 timer");
+        assert_eq!(lines[3], "✗ failed (1)");
+        assert_eq!(lines[4], "  backup.service (failed) Nightly backup");
+        assert_eq!(lines[5], "    rsync: connection refused");
+    }
+}
@@ -0,0 +1,431 @@
+//! Failing-test history for `rtk test`.
+//!
+//! Each run stores the fingerprints of its failing tests in the tracking
+//! database, keyed by project directory and test command. The next run of the
+//! same suite reports which failures are new, which persist and which tests
+//! started passing again. Tests that both failed and passed on the same code
+//! (same HEAD and uncommitted changes) are reported as flaky.
+
+use crate::config::Config;
+use crate::redact::{short_hash, short_hash_reader};
+use crate::tracking::{TestRun, Tracker};
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::process::{Command, Stdio};
+
+/// Test names listed per section before collapsing to `+N more`.
+const MAX_LISTED: usize = 10;
+
+/// Recent runs searched for flaky tests.
+const FLAKY_WINDOW: usize = 50;
+
+lazy_static! {
+    static ref TEST_ID: Vec<Regex> = vec![
+        // cargo: test parser::tests::empty ... FAILED
+        Regex::new(r"^test (\S+) \.\.\. FAILED").unwrap(),
+        // pytest: FAILED tests/test_io.py::test_read - AssertionError
+        Regex::new(r"^FAILED (\S+)").unwrap(),
+        // go: --- FAIL: TestParse (0.00s)
+        Regex::new(r"^\s*--- FAIL: (\S+)").unwrap(),
+        // go package / jest suite: FAIL  src/a.test.ts, FAIL\tgithub.com/x/y\t0.01s
+        Regex::new(r"^FAIL\s+(\S+)").unwrap(),
+        // jest: ✕ renders the header (12 ms)
+        Regex::new(r"✕\s+(.+?)(?:\s+\(\d+(?:\.\d+)?\s*m?s\))?$").unwrap(),
+    ];
+    /// Durations, addresses and counters that change between identical failures
+    static ref VOLATILE: Regex =
+        Regex::new(r"0x[0-9a-fA-F]+|\b\d+(\.\d+)?\s?(ms|s|µs)\b").unwrap();
+}
+
+/// Stable identity of a failure line: the test name when a known framework
+/// format matches, otherwise the line without timings and addresses.
+/// `None` for summary lines that name no test.
+pub fn test_id(line: &str) -> Option<String> {
+    let line = line.trim_end();
+    for re in TEST_ID.iter() {
+        if let Some(caps) = re.captures(line) {
+            return Some(caps[1].to_string());
+        }
+    }
+    let trimmed = line.trim();
+    // "FAIL", "test result: FAILED. ..." and similar carry no test name
+    if trimmed == "FAIL" || trimmed.starts_with("test result:") {
+        return None;
+    }
+    Some(VOLATILE.replace_all(trimmed, "_").into_owned())
+}
+
+fn cwd() -> String {
+    std::env::current_dir()
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Lookup key of the current project directory.
+pub fn project_key() -> String {
+    short_hash(&cwd())
+}
+
+/// Lookup key of a test suite: project directory + command.
+pub fn suite_key(command: &str) -> String {
+    short_hash(&format!("{}\0{}", cwd(), command.trim()))
+}
+
+/// `(HEAD, HEAD + hash of uncommitted changes)`, both `None` outside git.
+pub fn code_state() -> (Option<String>, Option<String>) {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).into_owned())
+    };
+    let Some(head) = git(&["rev-parse", "HEAD"]).map(|h| h.trim().to_string()) else {
+        return (None, None);
+    };
+    let state = match diff_hash() {
+        Some(hash) => format!("{}+{}", head, hash),
+        None => head.clone(),
+    };
+    (Some(head), Some(state))
+}
+
+/// Hash of `git diff HEAD`, streamed from git (a large uncommitted change
+/// is never held in memory); `None` when there are no changes.
+fn diff_hash() -> Option<String> {
+    let mut child = Command::new("git")
+        .args(["diff", "HEAD"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let hash = child.stdout.take().map(short_hash_reader);
+    let status = child.wait().ok()?;
+    hash.filter(|_| status.success())?.ok().flatten()
+}
+
+/// Fingerprints as stored: test names are hashed when `tracking.redact` is on.
+fn fingerprints(failures: &BTreeSet<String>) -> Vec<String> {
+    if Config::cached().tracking.redact {
+        failures.iter().map(|f| short_hash(f)).collect()
+    } else {
+        failures.iter().cloned().collect()
+    }
+}
+
+/// Compare with the previous run of this suite, record this one and return
+/// the delta section (empty when there is nothing to compare).
+pub fn record_and_compare(command: &str, failures: &BTreeSet<String>) -> String {
+    let Ok(tracker) = Tracker::new() else {
+        return String::new();
+    };
+    let suite = suite_key(command);
+    let current = fingerprints(failures);
+    let (head, state) = code_state();
+    let _ = tracker.record_test_run(
+        &project_key(),
+        &suite,
+        head.as_deref(),
+        state.as_deref(),
+        &current,
+    );
+    // Newest first: this run, then the one to compare against
+    let runs = tracker
+        .get_test_runs(&suite, FLAKY_WINDOW)
+        .unwrap_or_default();
+    let Some(previous) = runs.get(1) else {
+        return String::new();
+    };
+    let mut delta = Delta::between(previous, &current);
+    let flaky: BTreeSet<String> = find_flaky(&runs).into_iter().map(|f| f.name).collect();
+    delta.flaky = current.into_iter().filter(|f| flaky.contains(f)).collect();
+    format_delta(&delta)
+}
+
+/// A test that failed in some runs and passed in others on the same code.
+#[derive(Debug, PartialEq)]
+pub struct Flaky {
+    pub name: String,
+    /// Failing runs among `runs`
+    pub failed: usize,
+    /// Runs of its suite that shared a code state with another run
+    pub runs: usize,
+}
+
+/// Tests whose outcome differed between runs of the same suite on the same
+/// code state, most often failing first. Runs outside git are ignored.
+pub fn find_flaky(runs: &[TestRun]) -> Vec<Flaky> {
+    let mut groups: BTreeMap<(&str, &str), Vec<&TestRun>> = BTreeMap::new();
+    for run in runs {
+        if let Some(state) = &run.code_state {
+            groups
+                .entry((run.suite.as_str(), state.as_str()))
+                .or_default()
+                .push(run);
+        }
+    }
+    let mut by_name: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for group in groups.values().filter(|g| g.len() > 1) {
+        let names: BTreeSet<&str> = group
+            .iter()
+            .flat_map(|r| r.failures.iter().map(String::as_str))
+            .collect();
+        for name in names {
+            let failed = group
+                .iter()
+                .filter(|r| r.failures.iter().any(|f| f == name))
+                .count();
+            if failed < group.len() {
+                let entry = by_name.entry(name).or_default();
+                entry.0 += failed;
+                entry.1 += group.len();
+            }
+        }
+    }
+    let mut flaky: Vec<Flaky> = by_name
+        .into_iter()
+        .map(|(name, (failed, runs))| Flaky {
+            name: name.to_string(),
+            failed,
+            runs,
+        })
+        .collect();
+    flaky.sort_by_key(|f| std::cmp::Reverse(f.failed));
+    flaky
+}
+
+/// `rtk test --flaky [command]`: flaky tests of one suite, or of every
+/// suite run in this directory.
+pub fn run_flaky(command: Option<&str>) -> Result<()> {
+    let tracker = Tracker::new().context("Failed to initialize tracking database")?;
+    let runs = match command {
+        Some(command) => tracker.get_test_runs(&suite_key(command), FLAKY_WINDOW)?,
+        None => tracker.get_project_test_runs(&project_key(), FLAKY_WINDOW)?,
+    };
+    println!("{}", format_flaky(&find_flaky(&runs), runs.len()));
+    Ok(())
+}
+
+fn format_flaky(flaky: &[Flaky], runs: usize) -> String {
+    if runs == 0 {
+        return "No test runs recorded here yet (run tests with `rtk test <command>`)".to_string();
+    }
+    if flaky.is_empty() {
+        return format!(
+            "No flaky tests in the last {} runs (flaky = failed and passed on the same code)",
+            runs
+        );
+    }
+    let mut out = vec![format!(
+        "🎲 Flaky tests ({} of the last {} runs):",
+        flaky.len(),
+        runs
+    )];
+    let width = flaky
+        .iter()
+        .map(|f| f.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(60);
+    for f in flaky {
+        out.push(format!(
+            "  {:<width$}  failed {}/{} runs on unchanged code",
+            f.name,
+            f.failed,
+            f.runs,
+            width = width
+        ));
+    }
+    out.join("\n")
+}
+
+/// Failures of this run relative to the previous one.
+#[derive(Debug, Default, PartialEq)]
+pub struct Delta {
+    pub newly_failing: Vec<String>,
+    pub still_failing: Vec<String>,
+    pub newly_passing: Vec<String>,
+    /// Failing now and known to be flaky (see [`find_flaky`])
+    pub flaky: Vec<String>,
+}
+
+impl Delta {
+    pub fn between(previous: &TestRun, current: &[String]) -> Self {
+        let before: BTreeSet<&String> = previous.failures.iter().collect();
+        let after: BTreeSet<&String> = current.iter().collect();
+        Delta {
+            newly_failing: after.difference(&before).map(|s| s.to_string()).collect(),
+            still_failing: after.intersection(&before).map(|s| s.to_string()).collect(),
+            newly_passing: before.difference(&after).map(|s| s.to_string()).collect(),
+            flaky: Vec::new(),
+        }
+    }
+}
+
+/// `🔁 vs previous run` section; empty when neither run had failures.
+pub fn format_delta(delta: &Delta) -> String {
+    if delta.newly_failing.is_empty()
+        && delta.still_failing.is_empty()
+        && delta.newly_passing.is_empty()
+    {
+        return String::new();
+    }
+    let mut out = String::from("🔁 vs previous run:\n");
+    for (label, names) in [
+        ("🆕 newly failing", &delta.newly_failing),
+        ("⏳ still failing", &delta.still_failing),
+        ("✅ newly passing", &delta.newly_passing),
+        ("🎲 flaky, failed and passed on the same code", &delta.flaky),
+    ] {
+        if names.is_empty() {
+            continue;
+        }
+        let mut listed: Vec<&str> = names.iter().take(MAX_LISTED).map(String::as_str).collect();
+        let more = format!("+{} more", names.len().saturating_sub(MAX_LISTED));
+        if names.len() > MAX_LISTED {
+            listed.push(&more);
+        }
+        out.push_str(&format!(
+            "  {} ({}): {}\n",
+            label,
+            names.len(),
+            listed.join(", ")
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(failures: &[&str]) -> TestRun {
+        state_run("s", None, failures)
+    }
+
+    fn state_run(suite: &str, state: Option<&str>, failures: &[&str]) -> TestRun {
+        TestRun {
+            suite: suite.to_string(),
+            timestamp: chrono::Utc::now(),
+            git_head: state.map(str::to_string),
+            code_state: state.map(str::to_string),
+            failures: failures.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_id_frameworks() {
+        assert_eq!(
+            test_id("test parser::tests::empty ... FAILED").as_deref(),
+            Some("parser::tests::empty")
+        );
+        assert_eq!(
+            test_id("FAILED tests/test_io.py::test_read - AssertionError: x").as_deref(),
+            Some("tests/test_io.py::test_read")
+        );
+        assert_eq!(
+            test_id("    --- FAIL: TestParse (0.00s)").as_deref(),
+            Some("TestParse")
+        );
+        assert_eq!(
+            test_id("FAIL\tgithub.com/acme/api\t0.012s").as_deref(),
+            Some("github.com/acme/api")
+        );
+        assert_eq!(
+            test_id("  ✕ renders the header (12 ms)").as_deref(),
+            Some("renders the header")
+        );
+        assert_eq!(test_id("FAIL"), None);
+        assert_eq!(test_id("test result: FAILED. 2 passed; 1 failed"), None);
+        assert_eq!(
+            test_id("boom at 0x7ffd1234 after 1.5s").as_deref(),
+            Some("boom at _ after _")
+        );
+    }
+
+    #[test]
+    fn test_delta_sections() {
+        let current = vec!["b".to_string(), "c".to_string()];
+        let delta = Delta::between(&run(&["a", "b"]), &current);
+        assert_eq!(delta.newly_failing, vec!["c"]);
+        assert_eq!(delta.still_failing, vec!["b"]);
+        assert_eq!(delta.newly_passing, vec!["a"]);
+        assert_eq!(
+            format_delta(&delta),
+            "🔁 vs previous run:\n\
+             \x20 🆕 newly failing (1): c\n\
+             \x20 ⏳ still failing (1): b\n\
+             \x20 ✅ newly passing (1): a\n"
+        );
+    }
+
+    #[test]
+    fn test_delta_all_green_is_silent() {
+        assert_eq!(format_delta(&Delta::between(&run(&[]), &[])), "");
+    }
+
+    #[test]
+    fn test_find_flaky_same_code_only() {
+        let runs = vec![
+            state_run("s", Some("abc"), &["io", "build"]),
+            state_run("s", Some("abc"), &["build"]),
+            state_run("s", Some("abc"), &["io", "parse", "build"]),
+            state_run("s", Some("abc"), &["build"]),
+            // changed code: the outcome may legitimately differ
+            state_run("s", Some("def"), &["net"]),
+            state_run("s", Some("ghi"), &[]),
+            // no git: never grouped
+            state_run("s", None, &["tmp"]),
+            state_run("s", None, &[]),
+        ];
+        // "build" failed on every run of the same code: broken, not flaky
+        assert_eq!(
+            find_flaky(&runs),
+            vec![
+                Flaky {
+                    name: "io".to_string(),
+                    failed: 2,
+                    runs: 4
+                },
+                Flaky {
+                    name: "parse".to_string(),
+                    failed: 1,
+                    runs: 4
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_flaky() {
+        let flaky = vec![Flaky {
+            name: "net::retry".to_string(),
+            failed: 2,
+            runs: 5,
+        }];
+        assert_eq!(
+            format_flaky(&flaky, 12),
+            "🎲 Flaky tests (1 of the last 12 runs):\n  net::retry  failed 2/5 runs on unchanged code"
+        );
+        assert!(format_flaky(&[], 3).starts_with("No flaky tests in the last 3 runs"));
+    }
+
+    #[test]
+    fn test_delta_lists_flaky() {
+        let mut delta = Delta::between(&run(&["io"]), &["io".to_string()]);
+        delta.flaky = vec!["io".to_string()];
+        assert!(format_delta(&delta)
+            .ends_with("🎲 flaky, failed and passed on the same code (1): io\n"));
+    }
+
+    #[test]
+    fn test_delta_caps_names() {
+        let names: Vec<String> = (0..12).map(|i| format!("t{:02}", i)).collect();
+        let out = format_delta(&Delta::between(&run(&[]), &names));
+        assert!(out.contains("🆕 newly failing (12): t00, t01,"));
+        assert!(out.ends_with("t09, +2 more\n"));
+    }
+}
@@ -0,0 +1,106 @@
+//! `--timings`: where rtk's own time goes.
+//!
+//! rtk wraps commands that take a few milliseconds, so its overhead has to
+//! stay in the noise. Phases are marked as the run goes (argument parsing,
+//! setup, the wrapped command, tracking) and printed to stderr at exit:
+//!
+//! ```text
+//! rtk timings: cli 0.6ms, init 0.2ms, command 2.9ms, track 0.3ms, total 4.1ms
+//! ```
+//!
+//! `RTK_TIMINGS=1` does the same for invocations whose arguments can't be
+//! changed, e.g. hook rewrites.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+static START: OnceLock<Instant> = OnceLock::new();
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// (phase, time since start) in the order marked
+static MARKS: Mutex<Vec<(&'static str, Duration)>> = Mutex::new(Vec::new());
+
+/// Start the clock; first thing in `main`.
+pub fn start() {
+    START.get_or_init(Instant::now);
+}
+
+/// Record marks from now on (`--timings` or `RTK_TIMINGS`).
+pub fn enable(flag: bool) {
+    let env = std::env::var("RTK_TIMINGS").is_ok_and(|v| !v.is_empty() && v != "0");
+    if flag || env {
+        ENABLED.store(true, Ordering::Relaxed);
+    }
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// End of `phase`. Marks before [`enable`] are kept too, since enabling
+/// needs the parsed arguments.
+pub fn mark(phase: &'static str) {
+    let Some(start) = START.get() else {
+        return;
+    };
+    if let Ok(mut marks) = MARKS.lock() {
+        marks.push((phase, start.elapsed()));
+    }
+}
+
+/// Print the phases (once) if enabled.
+pub fn report() {
+    if !enabled() {
+        return;
+    }
+    let Some(start) = START.get() else {
+        return;
+    };
+    let total = start.elapsed();
+    let marks = MARKS.lock().map(|m| m.clone()).unwrap_or_default();
+    ENABLED.store(false, Ordering::Relaxed);
+    eprintln!("rtk timings: {}", render(&marks, total));
+}
+
+/// Phase durations from cumulative marks; repeated phases add up.
+fn render(marks: &[(&'static str, Duration)], total: Duration) -> String {
+    let mut phases: Vec<(&str, Duration)> = Vec::new();
+    let mut previous = Duration::ZERO;
+    for &(phase, at) in marks {
+        let spent = at.saturating_sub(previous);
+        previous = at;
+        match phases.iter_mut().find(|(p, _)| *p == phase) {
+            Some((_, sum)) => *sum += spent,
+            None => phases.push((phase, spent)),
+        }
+    }
+    if total > previous {
+        phases.push(("exit", total - previous));
+    }
+    phases.push(("total", total));
+    phases
+        .iter()
+        .map(|(phase, spent)| format!("{} {:.1}ms", phase, spent.as_secs_f64() * 1000.0))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_phases() {
+        let ms = Duration::from_millis;
+        let marks = [("cli", ms(1)), ("command", ms(4)), ("track", ms(5))];
+        assert_eq!(
+            render(&marks, ms(6)),
+            "cli 1.0ms, command 3.0ms, track 1.0ms, exit 1.0ms, total 6.0ms"
+        );
+        let repeated = [("command", ms(2)), ("track", ms(3)), ("command", ms(5))];
+        assert_eq!(
+            render(&repeated, ms(5)),
+            "command 4.0ms, track 1.0ms, total 5.0ms"
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use crate::path_rules::{PathAction, PathRules};
+use crate::tracking;
+use crate::utils::{slash_path, truncate};
+use crate::walk::{self, Options};
+use anyhow::Result;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::process::Command;
+
+/// Markers, most urgent first.
+const MARKERS: &[&str] = &["FIXME", "HACK", "XXX", "TODO"];
+
+/// Files larger than this are generated or vendored, not worth scanning.
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+lazy_static! {
+    // `// TODO: text`, `# FIXME(alice): text`, `/* HACK text */`; a comment
+    // leader is required so string literals and identifiers don't count
+    static ref MARKER_RE: Regex = Regex::new(
+        r"(?://+|#+|/\*+|^\s*\*|--|<!--|;+)\s*(FIXME|HACK|XXX|TODO)\b(?:\(([^)]*)\))?:?\s*(.*)"
+    )
+    .unwrap();
+    static ref BLAME_HEADER: Regex = Regex::new(r"^[0-9a-f]{40} \d+ (\d+)").unwrap();
+}
+
+#[derive(Debug, PartialEq)]
+struct Marker {
+    file: String,
+    line: usize,
+    kind: &'static str,
+    text: String,
+    /// `TODO(name)`, else the blame author
+    author: Option<String>,
+}
+
+impl Marker {
+    fn priority(&self) -> usize {
+        MARKERS.iter().position(|m| *m == self.kind).unwrap_or(0)
+    }
+}
+
+/// Markers in `content`, with the raw `file:line:text` lines grep would print.
+fn scan(file: &str, content: &str) -> (Vec<Marker>, Vec<String>) {
+    let mut markers = Vec::new();
+    let mut raw = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let Some(caps) = MARKER_RE.captures(line) else {
+            continue;
+        };
+        let kind = MARKERS
+            .iter()
+            .copied()
+            .find(|m| *m == &caps[1])
+            .unwrap_or("TODO");
+        let text = caps[3]
+            .trim()
+            .trim_end_matches("*/")
+            .trim_end_matches("-->")
+            .trim_end();
+        markers.push(Marker {
+            file: file.to_string(),
+            line: i + 1,
+            kind,
+            text: truncate(text, 80),
+            author: caps
+                .get(2)
+                .map(|a| a.as_str().trim().to_string())
+                .filter(|a| !a.is_empty()),
+        });
+        raw.push(format!("{}:{}:{}", file, i + 1, line));
+    }
+    (markers, raw)
+}
+
+/// Author of each line of `file`, from `git blame --line-porcelain`.
+fn blame(file: &Path) -> HashMap<usize, String> {
+    let Ok(file) = file.canonicalize() else {
+        return HashMap::new();
+    };
+    let dir = file.parent().unwrap_or(Path::new("."));
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["blame", "--line-porcelain", "--"])
+        .arg(&file)
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            parse_blame(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => HashMap::new(),
+    }
+}
+
+fn parse_blame(porcelain: &str) -> HashMap<usize, String> {
+    let mut authors = HashMap::new();
+    let mut current = None;
+    for line in porcelain.lines() {
+        if let Some(caps) = BLAME_HEADER.captures(line) {
+            current = caps[1].parse::<usize>().ok();
+        } else if let (Some(author), Some(n)) = (line.strip_prefix("author "), current) {
+            let author = if author == "Not Committed Yet" {
+                "uncommitted"
+            } else {
+                author
+            };
+            authors.insert(n, author.to_string());
+        }
+    }
+    authors
+}
+
+/// Directory part of a relative path, "." for top-level files.
+fn parent_dir(path: &str) -> String {
+    match path.rsplit_once('/') {
+        Some((dir, _)) => dir.to_string(),
+        None => ".".to_string(),
+    }
+}
+
+fn format_report(markers: &[Marker], max: usize) -> String {
+    let mut out = Vec::new();
+    let mut counts: Vec<String> = MARKERS
+        .iter()
+        .filter_map(|kind| {
+            let n = markers.iter().filter(|m| m.kind == *kind).count();
+            (n > 0).then(|| format!("{} {}", n, kind))
+        })
+        .collect();
+    if counts.is_empty() {
+        counts.push("none".to_string());
+    }
+    out.push(format!(
+        "📝 {} markers: {}",
+        markers.len(),
+        counts.join(", ")
+    ));
+
+    let mut by_author: HashMap<&str, usize> = HashMap::new();
+    for author in markers.iter().filter_map(|m| m.author.as_deref()) {
+        *by_author.entry(author).or_default() += 1;
+    }
+    if !by_author.is_empty() {
+        let mut authors: Vec<_> = by_author.into_iter().collect();
+        authors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        let list: Vec<String> = authors
+            .iter()
+            .take(8)
+            .map(|(author, n)| format!("{} {}", author, n))
+            .collect();
+        let more = if authors.len() > 8 {
+            format!(", +{} more", authors.len() - 8)
+        } else {
+            String::new()
+        };
+        out.push(format!("👤 {}{}", list.join(", "), more));
+    }
+
+    // Directories with the most urgent markers first
+    let mut by_dir: BTreeMap<String, Vec<&Marker>> = BTreeMap::new();
+    for marker in markers {
+        by_dir
+            .entry(parent_dir(&marker.file))
+            .or_default()
+            .push(marker);
+    }
+    let mut dirs: Vec<(String, Vec<&Marker>)> = by_dir.into_iter().collect();
+    for (_, list) in dirs.iter_mut() {
+        list.sort_by_key(|m| (m.priority(), m.file.clone(), m.line));
+    }
+    dirs.sort_by_key(|(dir, list)| (list[0].priority(), usize::MAX - list.len(), dir.clone()));
+
+    let mut shown = 0;
+    for (dir, list) in &dirs {
+        if shown >= max {
+            break;
+        }
+        out.push(String::new());
+        out.push(format!("{}/ ({})", dir, list.len()));
+        for marker in list.iter().take(max - shown) {
+            let name = marker.file.rsplit('/').next().unwrap_or(&marker.file);
+            let author = marker
+                .author
+                .as_deref()
+                .map(|a| format!(" ({})", a))
+                .unwrap_or_default();
+            out.push(format!(
+                "  {} {}:{} {}{}",
+                marker.kind, name, marker.line, marker.text, author
+            ));
+            shown += 1;
+        }
+    }
+    if shown < markers.len() {
+        out.push(format!("+{} more", markers.len() - shown));
+    }
+    out.join("\n")
+}
+
+pub fn run(path: &str, max: usize, no_blame: bool, verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let rules = PathRules::load();
+
+    let mut markers = Vec::new();
+    let mut raw = Vec::new();
+    let mut files = 0usize;
+    for entry in walk::walk(Path::new(path), Options::default()) {
+        if !entry.is_file() {
+            continue;
+        }
+        let entry_path = entry.path.as_path();
+        if !matches!(rules.action(entry_path, false), PathAction::Keep)
+            || entry.metadata().map_or(true, |m| m.len() > MAX_FILE_SIZE)
+        {
+            continue;
+        }
+        // Binary and non-UTF-8 files are skipped
+        let Ok(content) = std::fs::read_to_string(entry_path) else {
+            continue;
+        };
+        files += 1;
+        let display = slash_path(
+            &entry_path
+                .strip_prefix(path)
+                .unwrap_or(entry_path)
+                .to_string_lossy(),
+        );
+        let (mut found, lines) = scan(&display, &content);
+        if found.is_empty() {
+            continue;
+        }
+        if !no_blame && found.iter().any(|m| m.author.is_none()) {
+            let authors = blame(entry_path);
+            for marker in found.iter_mut().filter(|m| m.author.is_none()) {
+                marker.author = authors.get(&marker.line).cloned();
+            }
+        }
+        markers.append(&mut found);
+        raw.extend(lines);
+    }
+
+    if verbose > 0 {
+        eprintln!("todo: scanned {} files under {}", files, path);
+    }
+
+    let output = format_report(&markers, max);
+    println!("{}", output);
+    timer.track(
+        &format!("grep -rnE 'TODO|FIXME|HACK|XXX' {}", path),
+        "rtk todo",
+        &raw.join("\n"),
+        &output,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_markers() {
+        let content = "fn a() {}\n// TODO: wire up cache\n/* FIXME(alice) off by one */\nlet todo = 1;\nlet s = \"TODO\";\n# HACK skip on windows\n";
+        let (markers, raw) = scan("src/a.rs", content);
+        assert_eq!(markers.len(), 3);
+        assert_eq!(markers[0].kind, "TODO");
+        assert_eq!(markers[0].text, "wire up cache");
+        assert_eq!(markers[1].line, 3);
+        assert_eq!(markers[1].text, "off by one");
+        assert_eq!(markers[1].author.as_deref(), Some("alice"));
+        assert_eq!(raw[0], "src/a.rs:2:// TODO: wire up cache");
+    }
+
+    #[test]
+    fn test_parse_blame() {
+        let porcelain = "\
+1234567890abcdef1234567890abcdef12345678 1 1 1
+author Alice
+author-mail <a@x>
+\tfn a() {}
+0000000000000000000000000000000000000000 2 2 1
+author Not Committed Yet
+\t// TODO
+";
+        let authors = parse_blame(porcelain);
+        assert_eq!(authors[&1], "Alice");
+        assert_eq!(authors[&2], "uncommitted");
+    }
+
+    #[test]
+    fn test_report_orders_by_priority() {
+        let marker = |file: &str, line, kind, author: &str| Marker {
+            file: file.into(),
+            line,
+            kind,
+            text: "x".into(),
+            author: Some(author.into()),
+        };
+        let markers = vec![
+            marker("src/a.rs", 1, "TODO", "bob"),
+            marker("src/a.rs", 9, "TODO", "bob"),
+            marker("tests/t.rs", 4, "FIXME", "alice"),
+            marker("README.md", 2, "TODO", "bob"),
+        ];
+        let report = format_report(&markers, 3);
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "📝 4 markers: 1 FIXME, 3 TODO");
+        assert_eq!(lines[1], "👤 bob 3, alice 1");
+        assert_eq!(lines[3], "tests/ (1)");
+        assert_eq!(lines[4], "  FIXME t.rs:4 x (alice)");
+        assert_eq!(lines[6], "src/ (2)");
+        assert_eq!(report.lines().last(), Some("+1 more"));
+    }
+}
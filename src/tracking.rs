@@ -7,7 +7,7 @@
 //! # Architecture
 //!
 //! - Storage: SQLite database (~/.local/share/rtk/tracking.db)
-//! - Retention: 90-day automatic cleanup
+//! - Retention: `tracking.history_days` (90 by default), cleaned up on write
 //! - Metrics: Input/output tokens, savings %, execution time
 //!
 //! # Quick Start
@@ -29,16 +29,25 @@
 //!
 //! See [docs/tracking.md](../docs/tracking.md) for full documentation.
 
+use crate::redact::redact_command;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use rusqlite::{params, Connection};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::ffi::OsString;
-use std::path::PathBuf;
-use std::time::Instant;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::time::{Duration, Instant};
 
-/// Number of days to retain tracking history before automatic cleanup.
-const HISTORY_DAYS: i64 = 90;
+/// How long a write waits on a locked database before giving up and
+/// queueing the record instead. Kept short so tracking never delays a command.
+const BUSY_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Spool size at which the invocation that grows past it imports the spool
+/// into the database (a few hundred records).
+const SPOOL_FLUSH_BYTES: u64 = 64 * 1024;
 
 /// Main tracking interface for recording and querying command history.
 ///
@@ -51,7 +60,7 @@ const HISTORY_DAYS: i64 = 90;
 ///
 /// - Linux: `~/.local/share/rtk/tracking.db`
 /// - macOS: `~/Library/Application Support/rtk/tracking.db`
-/// - Windows: `%APPDATA%\rtk\tracking.db`
+/// - Windows: `%LOCALAPPDATA%\rtk\tracking.db`
 ///
 /// # Examples
 ///
@@ -59,7 +68,7 @@ const HISTORY_DAYS: i64 = 90;
 /// use rtk::tracking::Tracker;
 ///
 /// let tracker = Tracker::new()?;
-/// tracker.record("ls -la", "rtk ls", 1000, 200, 50)?;
+/// tracker.record("ls -la", "rtk ls", 1000, 200, 50, Some(0))?;
 ///
 /// let summary = tracker.get_summary()?;
 /// println!("Total saved: {} tokens", summary.total_saved);
@@ -67,6 +76,125 @@ const HISTORY_DAYS: i64 = 90;
 /// ```
 pub struct Tracker {
     conn: Connection,
+    redact: bool,
+    /// Records older than this are deleted on write (`tracking.history_days`)
+    history_days: i64,
+    pending_path: PathBuf,
+}
+
+/// Tracked command as appended to the spool (`history.pending.jsonl` next to
+/// the database) and later imported by [`Tracker::drain_pending`].
+#[derive(Debug, Serialize, Deserialize)]
+struct PendingRecord {
+    timestamp: String,
+    original_cmd: String,
+    rtk_cmd: String,
+    input_tokens: usize,
+    output_tokens: usize,
+    exec_time_ms: u64,
+    #[serde(default)]
+    exit_code: Option<i32>,
+    #[serde(default)]
+    cache_hit: bool,
+    #[serde(default)]
+    invoker: Option<String>,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    sample: Option<OutputSample>,
+}
+
+impl PendingRecord {
+    fn now(
+        original_cmd: &str,
+        rtk_cmd: &str,
+        input_tokens: usize,
+        output_tokens: usize,
+        exec_time_ms: u64,
+        exit_code: Option<i32>,
+        sample: Option<OutputSample>,
+    ) -> Self {
+        Self {
+            timestamp: Utc::now().to_rfc3339(),
+            original_cmd: original_cmd.to_string(),
+            rtk_cmd: rtk_cmd.to_string(),
+            input_tokens,
+            output_tokens,
+            exec_time_ms,
+            exit_code,
+            cache_hit: crate::cache::was_hit(),
+            invoker: Some(invoker()),
+            model: model(),
+            sample,
+        }
+    }
+
+    /// The record as `tracking.redact` stores it: commands anonymized and
+    /// sample text dropped, sizes and hashes kept.
+    fn redacted(mut self) -> Self {
+        self.original_cmd = redact_command(&self.original_cmd);
+        self.rtk_cmd = redact_command(&self.rtk_cmd);
+        if let Some(sample) = &mut self.sample {
+            sample.raw_head.clear();
+            sample.output_head.clear();
+        }
+        self
+    }
+}
+
+/// Raw vs filtered output kept with a record when `tracking.samples` is on,
+/// so `rtk gain inspect <id>` can show what a compression actually did.
+///
+/// Sizes and hashes cover the full outputs; the text is cut to
+/// `tracking.sample_chars` characters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputSample {
+    /// Size of the standard command output (bytes)
+    pub raw_bytes: usize,
+    /// FNV-1a hash of the standard command output
+    pub raw_hash: String,
+    /// Beginning of the standard command output
+    pub raw_head: String,
+    /// Size of the RTK output (bytes)
+    pub output_bytes: usize,
+    /// FNV-1a hash of the RTK output
+    pub output_hash: String,
+    /// Beginning of the RTK output
+    pub output_head: String,
+}
+
+impl OutputSample {
+    /// Sample `raw` and `output`, keeping at most `chars` characters of each.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtk::tracking::OutputSample;
+    ///
+    /// let sample = OutputSample::new("line 1\nline 2\n", "2 lines", 4);
+    /// assert_eq!(sample.raw_bytes, 14);
+    /// assert_eq!(sample.raw_head, "line");
+    /// assert_eq!(sample.output_head, "2 li");
+    /// ```
+    pub fn new(raw: &str, output: &str, chars: usize) -> Self {
+        let head = |text: &str| text.chars().take(chars).collect::<String>();
+        Self {
+            raw_bytes: raw.len(),
+            raw_hash: crate::redact::short_hash(raw),
+            raw_head: head(raw),
+            output_bytes: output.len(),
+            output_hash: crate::redact::short_hash(output),
+            output_head: head(output),
+        }
+    }
+}
+
+/// A tracked record together with its output sample (`rtk gain inspect`).
+#[derive(Debug, Serialize)]
+pub struct SampledRecord {
+    #[serde(flatten)]
+    pub record: RawRecord,
+    pub sample: OutputSample,
 }
 
 /// Individual command record from tracking history.
@@ -84,6 +212,116 @@ pub struct CommandRecord {
     pub savings_pct: f64,
 }
 
+/// Raw tracking row, as stored in the database.
+///
+/// Streamed by `rtk gain export --records --format jsonl` for downstream
+/// analysis (pandas, duckdb, ...). `id` doubles as the pagination cursor.
+#[derive(Debug, Clone, Serialize)]
+pub struct RawRecord {
+    /// Row id (monotonic, usable as `--after` cursor)
+    pub id: i64,
+    /// RFC 3339 timestamp when the command was executed
+    pub timestamp: String,
+    /// Standard command (e.g., "ls -la")
+    pub original_cmd: String,
+    /// RTK command used (e.g., "rtk ls")
+    pub rtk_cmd: String,
+    /// Estimated tokens from the standard command output
+    pub input_tokens: usize,
+    /// Estimated tokens from the RTK output
+    pub output_tokens: usize,
+    /// Tokens saved (input - output)
+    pub saved_tokens: usize,
+    /// Savings percentage
+    pub savings_pct: f64,
+    /// Execution time (milliseconds)
+    pub exec_time_ms: u64,
+    /// Exit code of the wrapped command (`null` when unknown or killed by a signal)
+    pub exit_code: Option<i32>,
+    /// Agent that ran the command ("claude-code", "codex", ...; "shell" for a person)
+    pub invoker: Option<String>,
+    /// Model the agent was running, when it exposes one (`RTK_MODEL`, ...)
+    pub model: Option<String>,
+}
+
+/// Failure statistics for one command, from `rtk gain --failures`.
+///
+/// Only runs with a recorded exit code are counted.
+#[derive(Debug, Serialize)]
+pub struct FailureStats {
+    /// RTK command (e.g., "rtk cargo test")
+    pub command: String,
+    /// Runs with a known exit code
+    pub runs: usize,
+    /// Runs that exited non-zero
+    pub failures: usize,
+    /// Failure rate (failures / runs * 100)
+    pub failure_pct: f64,
+}
+
+/// Dimension of a `rtk gain --by-agent` / `--by-model` breakdown.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Breakdown {
+    /// Agent that ran rtk (`invoker` column)
+    Agent,
+    /// Model the agent was running (`model` column)
+    Model,
+}
+
+impl Breakdown {
+    fn column(self) -> &'static str {
+        match self {
+            Breakdown::Agent => "invoker",
+            Breakdown::Model => "model",
+        }
+    }
+}
+
+/// Savings for one agent or model (see [`Tracker::get_breakdown`]).
+#[derive(Debug, Serialize)]
+pub struct GroupStats {
+    /// Agent or model name (`None` when it was not recorded)
+    pub name: Option<String>,
+    /// Commands recorded
+    pub commands: usize,
+    /// Estimated tokens of the standard command output
+    pub input_tokens: usize,
+    /// Estimated tokens of the RTK output
+    pub output_tokens: usize,
+    /// Tokens saved (input - output)
+    pub saved_tokens: usize,
+    /// Savings percentage (saved / input * 100)
+    pub savings_pct: f64,
+}
+
+/// Failing tests of one `rtk test` run (see [`Tracker::get_test_runs`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestRun {
+    /// Test command in its project (see [`crate::test_history::suite_key`])
+    pub suite: String,
+    /// UTC timestamp of the run
+    pub timestamp: DateTime<Utc>,
+    /// `git rev-parse HEAD` when the run was recorded
+    pub git_head: Option<String>,
+    /// HEAD plus a hash of uncommitted changes: equal states ran the same code
+    pub code_state: Option<String>,
+    /// Fingerprints of the failing tests
+    pub failures: Vec<String>,
+}
+
+/// Token totals for records newer than a cutoff (see [`Tracker::get_totals_since`]).
+#[derive(Debug, Default, Serialize)]
+pub struct WindowTotals {
+    /// Commands recorded in the window
+    pub commands: usize,
+    /// Estimated tokens of the standard command output
+    pub input_tokens: usize,
+    /// Estimated tokens of the RTK output
+    pub output_tokens: usize,
+    /// Tokens saved (input - output)
+    pub saved_tokens: usize,
+}
+
 /// Aggregated statistics across all recorded commands.
 ///
 /// Provides overall metrics and breakdowns by command and by day.
@@ -104,12 +342,55 @@ pub struct GainSummary {
     pub total_time_ms: u64,
     /// Average execution time per command (milliseconds)
     pub avg_time_ms: u64,
-    /// Top 10 commands by tokens saved: (cmd, count, saved, avg_pct, avg_time_ms)
-    pub by_command: Vec<(String, usize, usize, f64, u64)>,
+    /// Commands served from the result cache
+    pub cache_hits: usize,
+    /// Top 10 commands by tokens saved: (cmd, count, saved, avg_pct, avg_time_ms, percentiles)
+    pub by_command: Vec<(String, usize, usize, f64, u64, TimePercentiles)>,
     /// Last 30 days of activity: (date, saved_tokens)
     pub by_day: Vec<(String, usize)>,
 }
 
+/// Execution time distribution (nearest-rank percentiles, milliseconds).
+///
+/// Averages hide slow outliers; p95 and max surface them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct TimePercentiles {
+    /// Median execution time
+    pub p50_ms: u64,
+    /// 95th percentile execution time
+    pub p95_ms: u64,
+    /// Slowest execution
+    pub max_ms: u64,
+}
+
+impl TimePercentiles {
+    /// Compute percentiles from unsorted samples (all zero when empty).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtk::tracking::TimePercentiles;
+    ///
+    /// let p = TimePercentiles::from_samples(vec![10, 20, 30, 40, 1000]);
+    /// assert_eq!((p.p50_ms, p.p95_ms, p.max_ms), (30, 1000, 1000));
+    /// ```
+    pub fn from_samples(mut samples: Vec<u64>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_unstable();
+        let rank = |pct: f64| {
+            let idx = (pct / 100.0 * samples.len() as f64).ceil() as usize;
+            samples[idx.clamp(1, samples.len()) - 1]
+        };
+        Self {
+            p50_ms: rank(50.0),
+            p95_ms: rank(95.0),
+            max_ms: samples[samples.len() - 1],
+        }
+    }
+}
+
 /// Daily statistics for token savings and execution metrics.
 ///
 /// Serializable to JSON for export via `rtk gain --daily --format json`.
@@ -125,7 +406,10 @@ pub struct GainSummary {
 ///   "saved_tokens": 11578,
 ///   "savings_pct": 75.08,
 ///   "total_time_ms": 8450,
-///   "avg_time_ms": 201
+///   "avg_time_ms": 201,
+///   "p50_time_ms": 120,
+///   "p95_time_ms": 640,
+///   "max_time_ms": 2310
 /// }
 /// ```
 #[derive(Debug, Serialize)]
@@ -146,12 +430,18 @@ pub struct DayStats {
     pub total_time_ms: u64,
     /// Average execution time per command (milliseconds)
     pub avg_time_ms: u64,
+    /// Median execution time this day (milliseconds)
+    pub p50_time_ms: u64,
+    /// 95th percentile execution time this day (milliseconds)
+    pub p95_time_ms: u64,
+    /// Slowest execution this day (milliseconds)
+    pub max_time_ms: u64,
 }
 
 /// Weekly statistics for token savings and execution metrics.
 ///
 /// Serializable to JSON for export via `rtk gain --weekly --format json`.
-/// Weeks start on Sunday (SQLite default).
+/// Weeks start on `display.week_start` (Monday by default), in `display.timezone`.
 #[derive(Debug, Serialize)]
 pub struct WeekStats {
     /// Week start date (YYYY-MM-DD)
@@ -172,6 +462,12 @@ pub struct WeekStats {
     pub total_time_ms: u64,
     /// Average execution time per command (milliseconds)
     pub avg_time_ms: u64,
+    /// Median execution time this week (milliseconds)
+    pub p50_time_ms: u64,
+    /// 95th percentile execution time this week (milliseconds)
+    pub p95_time_ms: u64,
+    /// Slowest execution this week (milliseconds)
+    pub max_time_ms: u64,
 }
 
 /// Monthly statistics for token savings and execution metrics.
@@ -195,6 +491,12 @@ pub struct MonthStats {
     pub total_time_ms: u64,
     /// Average execution time per command (milliseconds)
     pub avg_time_ms: u64,
+    /// Median execution time this month (milliseconds)
+    pub p50_time_ms: u64,
+    /// 95th percentile execution time this month (milliseconds)
+    pub p95_time_ms: u64,
+    /// Slowest execution this month (milliseconds)
+    pub max_time_ms: u64,
 }
 
 impl Tracker {
@@ -221,44 +523,53 @@ impl Tracker {
     /// # Ok::<(), anyhow::Error>(())
     /// ```
     pub fn new() -> Result<Self> {
+        let tracker = Self::open()?;
+        // Records spooled by earlier runs, so readers see them too
+        let _ = tracker.recover_drains();
+        let _ = tracker.drain_pending();
+        Ok(tracker)
+    }
+
+    /// Import records spooled since the tracker was opened.
+    ///
+    /// [`new`](Self::new) drains the spool once; long-lived readers
+    /// (`rtk gain --watch`, the daemon's `/gain`) call this before each read
+    /// so runs from other processes show up. Returns the number imported.
+    pub fn refresh(&self) -> Result<usize> {
+        self.drain_pending()
+    }
+
+    fn open() -> Result<Self> {
         let db_path = get_db_path()?;
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
 
         let conn = Connection::open(&db_path)?;
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS commands (
-                id INTEGER PRIMARY KEY,
-                timestamp TEXT NOT NULL,
-                original_cmd TEXT NOT NULL,
-                rtk_cmd TEXT NOT NULL,
-                input_tokens INTEGER NOT NULL,
-                output_tokens INTEGER NOT NULL,
-                saved_tokens INTEGER NOT NULL,
-                savings_pct REAL NOT NULL
-            )",
-            [],
-        )?;
-
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_timestamp ON commands(timestamp)",
-            [],
-        )?;
-
-        // Migration: add exec_time_ms column if it doesn't exist
-        let _ = conn.execute(
-            "ALTER TABLE commands ADD COLUMN exec_time_ms INTEGER DEFAULT 0",
-            [],
-        );
-
-        Ok(Self { conn })
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        // WAL lets readers and a writer proceed concurrently (parallel agent tool calls)
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        run_migrations(&conn)?;
+        crate::local_time::register(&conn)?;
+
+        let tracking = &crate::config::Config::cached().tracking;
+
+        Ok(Self {
+            conn,
+            redact: tracking.redact,
+            history_days: tracking.history_days.into(),
+            pending_path: pending_path_for(&db_path),
+        })
     }
 
     /// Record a command execution with token counts and timing.
     ///
     /// Calculates savings metrics and stores the record in the database.
-    /// Automatically cleans up records older than 90 days after insertion.
+    /// Automatically cleans up records older than `tracking.history_days`
+    /// after insertion.
+    /// When `tracking.redact` is enabled in config, command strings are
+    /// anonymized (see [`crate::redact`]) before being written.
     ///
     /// # Arguments
     ///
@@ -267,6 +578,7 @@ impl Tracker {
     /// - `input_tokens`: Estimated tokens from standard command output
     /// - `output_tokens`: Actual tokens from RTK output
     /// - `exec_time_ms`: Execution time in milliseconds
+    /// - `exit_code`: Exit code of the wrapped command (`None` if unknown)
     ///
     /// # Examples
     ///
@@ -274,7 +586,7 @@ impl Tracker {
     /// use rtk::tracking::Tracker;
     ///
     /// let tracker = Tracker::new()?;
-    /// tracker.record("ls -la", "rtk ls", 1000, 200, 50)?;
+    /// tracker.record("ls -la", "rtk ls", 1000, 200, 50, Some(0))?;
     /// # Ok::<(), anyhow::Error>(())
     /// ```
     pub fn record(
@@ -284,39 +596,214 @@ impl Tracker {
         input_tokens: usize,
         output_tokens: usize,
         exec_time_ms: u64,
+        exit_code: Option<i32>,
     ) -> Result<()> {
-        let saved = input_tokens.saturating_sub(output_tokens);
-        let pct = if input_tokens > 0 {
-            (saved as f64 / input_tokens as f64) * 100.0
+        self.write(&PendingRecord::now(
+            original_cmd,
+            rtk_cmd,
+            input_tokens,
+            output_tokens,
+            exec_time_ms,
+            exit_code,
+            None,
+        ))
+    }
+
+    fn write(&self, rec: &PendingRecord) -> Result<()> {
+        self.insert(rec)?;
+        self.cleanup_old()?;
+        Ok(())
+    }
+
+    fn insert(&self, rec: &PendingRecord) -> Result<()> {
+        let saved = rec.input_tokens.saturating_sub(rec.output_tokens);
+        let pct = if rec.input_tokens > 0 {
+            (saved as f64 / rec.input_tokens as f64) * 100.0
         } else {
             0.0
         };
 
+        let (original_cmd, rtk_cmd) = if self.redact {
+            (
+                redact_command(&rec.original_cmd),
+                redact_command(&rec.rtk_cmd),
+            )
+        } else {
+            (rec.original_cmd.clone(), rec.rtk_cmd.clone())
+        };
+
         self.conn.execute(
-            "INSERT INTO commands (timestamp, original_cmd, rtk_cmd, input_tokens, output_tokens, saved_tokens, savings_pct, exec_time_ms)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT INTO commands (timestamp, original_cmd, rtk_cmd, input_tokens, output_tokens, saved_tokens, savings_pct, exec_time_ms, exit_code, cache_hit, invoker, model)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
             params![
-                Utc::now().to_rfc3339(),
+                rec.timestamp,
                 original_cmd,
                 rtk_cmd,
-                input_tokens as i64,
-                output_tokens as i64,
+                rec.input_tokens as i64,
+                rec.output_tokens as i64,
                 saved as i64,
                 pct,
-                exec_time_ms as i64
+                rec.exec_time_ms as i64,
+                rec.exit_code,
+                rec.cache_hit,
+                rec.invoker,
+                rec.model
             ],
         )?;
 
-        self.cleanup_old()?;
+        if let Some(sample) = &rec.sample {
+            // Output text is as identifying as the command: keep sizes and hashes only
+            let (raw_head, output_head) = if self.redact {
+                ("", "")
+            } else {
+                (sample.raw_head.as_str(), sample.output_head.as_str())
+            };
+            self.conn.execute(
+                "INSERT INTO samples (command_id, raw_bytes, raw_hash, raw_head, output_bytes, output_hash, output_head)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    self.conn.last_insert_rowid(),
+                    sample.raw_bytes as i64,
+                    sample.raw_hash,
+                    raw_head,
+                    sample.output_bytes as i64,
+                    sample.output_hash,
+                    output_head
+                ],
+            )?;
+        }
         Ok(())
     }
 
+    /// Import the spool into the database in one transaction.
+    ///
+    /// The spool is renamed before reading so concurrent drains never import
+    /// the same record twice, then locked exclusively to wait for appends
+    /// that opened it before the rename. If the import fails the records go
+    /// back to the spool; if the process dies first, the next tracker opened
+    /// imports them (see `recover_drains`). Returns the number of records
+    /// imported.
+    fn drain_pending(&self) -> Result<usize> {
+        if !self.pending_path.exists() {
+            return Ok(0);
+        }
+
+        let claimed = self.claimed_path(std::process::id());
+        if std::fs::rename(&self.pending_path, &claimed).is_err() {
+            // Another process claimed it first
+            return Ok(0);
+        }
+        self.import_claimed(&claimed)
+    }
+
+    /// Import spools claimed by drains that never finished: their process
+    /// died (killed, crashed) between the rename and the import.
+    fn recover_drains(&self) -> Result<usize> {
+        let (Some(dir), Some(stem)) = (self.pending_path.parent(), self.pending_path.file_stem())
+        else {
+            return Ok(0);
+        };
+        let prefix = format!("{}.draining.", stem.to_string_lossy());
+        let own = std::process::id();
+        let mut imported = 0;
+        for entry in std::fs::read_dir(dir)?.flatten() {
+            let name = entry.file_name();
+            let Some(pid) = name
+                .to_string_lossy()
+                .strip_prefix(&prefix)
+                .and_then(|pid| pid.parse::<u32>().ok())
+            else {
+                continue;
+            };
+            if pid == own || process_alive(pid) {
+                continue;
+            }
+            let claimed = self.claimed_path(own);
+            // Our own drain is under way; the leftover waits for next time
+            if claimed.exists() {
+                break;
+            }
+            if std::fs::rename(entry.path(), &claimed).is_err() {
+                // Another process is recovering it
+                continue;
+            }
+            tracing::debug!(pid, "recovering an interrupted spool drain");
+            imported += self.import_claimed(&claimed)?;
+        }
+        Ok(imported)
+    }
+
+    /// Where process `pid` moves the spool while draining it.
+    fn claimed_path(&self, pid: u32) -> PathBuf {
+        self.pending_path
+            .with_extension(format!("draining.{}", pid))
+    }
+
+    /// Import a claimed spool, then remove it.
+    fn import_claimed(&self, claimed: &Path) -> Result<usize> {
+        let file = std::fs::File::open(claimed)?;
+        file.lock()?;
+        let content = std::fs::read_to_string(claimed)?;
+        match self.import(&content) {
+            Ok(imported) => {
+                std::fs::remove_file(claimed)?;
+                if imported > 0 {
+                    self.cleanup_old()?;
+                }
+                Ok(imported)
+            }
+            Err(e) => {
+                // Keep the claimed file if even that fails: nothing is lost
+                if append_lines(&self.pending_path, &content).is_ok() {
+                    let _ = std::fs::remove_file(claimed);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    fn import(&self, content: &str) -> Result<usize> {
+        self.conn.execute_batch("BEGIN IMMEDIATE")?;
+        let result = (|| -> Result<usize> {
+            let mut imported = 0;
+            for line in content.lines() {
+                let Ok(rec) = serde_json::from_str::<PendingRecord>(line) else {
+                    continue;
+                };
+                self.insert(&rec)?;
+                imported += 1;
+            }
+            Ok(imported)
+        })();
+        match result {
+            Ok(imported) => {
+                self.conn.execute_batch("COMMIT")?;
+                Ok(imported)
+            }
+            Err(e) => {
+                let _ = self.conn.execute_batch("ROLLBACK");
+                Err(e)
+            }
+        }
+    }
+
     fn cleanup_old(&self) -> Result<()> {
-        let cutoff = Utc::now() - chrono::Duration::days(HISTORY_DAYS);
-        self.conn.execute(
+        let cutoff = Utc::now() - chrono::Duration::days(self.history_days);
+        let removed = self.conn.execute(
             "DELETE FROM commands WHERE timestamp < ?1",
             params![cutoff.to_rfc3339()],
         )?;
+        // Scans every sample: only worth it when commands went away
+        if removed > 0 {
+            self.conn.execute(
+                "DELETE FROM samples WHERE command_id NOT IN (SELECT id FROM commands)",
+                [],
+            )?;
+        }
+        self.conn.execute(
+            "DELETE FROM test_runs WHERE timestamp < ?1",
+            params![cutoff.to_rfc3339()],
+        )?;
         Ok(())
     }
 
@@ -380,6 +867,11 @@ impl Tracker {
             0
         };
 
+        let cache_hits = self.conn.query_row(
+            "SELECT COUNT(*) FROM commands WHERE cache_hit = 1",
+            [],
+            |row| row.get::<_, i64>(0),
+        )? as usize;
         let by_command = self.get_by_command()?;
         let by_day = self.get_by_day()?;
 
@@ -391,12 +883,14 @@ impl Tracker {
             avg_savings_pct,
             total_time_ms,
             avg_time_ms,
+            cache_hits,
             by_command,
             by_day,
         })
     }
 
-    fn get_by_command(&self) -> Result<Vec<(String, usize, usize, f64, u64)>> {
+    fn get_by_command(&self) -> Result<Vec<(String, usize, usize, f64, u64, TimePercentiles)>> {
+        let timings = self.time_percentiles_by("rtk_cmd")?;
         let mut stmt = self.conn.prepare(
             "SELECT rtk_cmd, COUNT(*), SUM(saved_tokens), AVG(savings_pct), AVG(exec_time_ms)
              FROM commands
@@ -406,24 +900,51 @@ impl Tracker {
         )?;
 
         let rows = stmt.query_map([], |row| {
+            let cmd = row.get::<_, String>(0)?;
+            let timing = timings.get(&cmd).copied().unwrap_or_default();
             Ok((
-                row.get::<_, String>(0)?,
+                cmd,
                 row.get::<_, i64>(1)? as usize,
                 row.get::<_, i64>(2)? as usize,
                 row.get::<_, f64>(3)?,
                 row.get::<_, f64>(4)? as u64,
+                timing,
             ))
         })?;
 
         Ok(rows.collect::<Result<Vec<_>, _>>()?)
     }
 
+    /// Execution time percentiles grouped by an SQL expression over `commands`.
+    ///
+    /// SQLite has no percentile aggregate, so samples are collected per group
+    /// and ranked in Rust. Bounded by the `tracking.history_days` retention window.
+    fn time_percentiles_by(&self, key_expr: &str) -> Result<HashMap<String, TimePercentiles>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}, COALESCE(exec_time_ms, 0) FROM commands",
+            key_expr
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64))
+        })?;
+
+        let mut samples: HashMap<String, Vec<u64>> = HashMap::new();
+        for row in rows {
+            let (key, time_ms) = row?;
+            samples.entry(key).or_default().push(time_ms);
+        }
+        Ok(samples
+            .into_iter()
+            .map(|(key, times)| (key, TimePercentiles::from_samples(times)))
+            .collect())
+    }
+
     fn get_by_day(&self) -> Result<Vec<(String, usize)>> {
         let mut stmt = self.conn.prepare(
-            "SELECT DATE(timestamp), SUM(saved_tokens)
+            "SELECT rtk_date(timestamp) as date, SUM(saved_tokens)
              FROM commands
-             GROUP BY DATE(timestamp)
-             ORDER BY DATE(timestamp) DESC
+             GROUP BY date
+             ORDER BY date DESC
              LIMIT 30",
         )?;
 
@@ -455,17 +976,18 @@ impl Tracker {
     /// # Ok::<(), anyhow::Error>(())
     /// ```
     pub fn get_all_days(&self) -> Result<Vec<DayStats>> {
+        let timings = self.time_percentiles_by("rtk_date(timestamp)")?;
         let mut stmt = self.conn.prepare(
             "SELECT
-                DATE(timestamp) as date,
+                rtk_date(timestamp) as date,
                 COUNT(*) as commands,
                 SUM(input_tokens) as input,
                 SUM(output_tokens) as output,
                 SUM(saved_tokens) as saved,
                 SUM(exec_time_ms) as total_time
              FROM commands
-             GROUP BY DATE(timestamp)
-             ORDER BY DATE(timestamp) DESC",
+             GROUP BY date
+             ORDER BY date DESC",
         )?;
 
         let rows = stmt.query_map([], |row| {
@@ -484,8 +1006,11 @@ impl Tracker {
                 0
             };
 
+            let date: String = row.get(0)?;
+            let timing = timings.get(&date).copied().unwrap_or_default();
+
             Ok(DayStats {
-                date: row.get(0)?,
+                date,
                 commands,
                 input_tokens: input,
                 output_tokens: row.get::<_, i64>(3)? as usize,
@@ -493,6 +1018,9 @@ impl Tracker {
                 savings_pct,
                 total_time_ms: total_time,
                 avg_time_ms,
+                p50_time_ms: timing.p50_ms,
+                p95_time_ms: timing.p95_ms,
+                max_time_ms: timing.max_ms,
             })
         })?;
 
@@ -504,7 +1032,7 @@ impl Tracker {
     /// Get weekly statistics grouped by week.
     ///
     /// Returns one [`WeekStats`] per week with aggregated metrics.
-    /// Weeks start on Sunday (SQLite default). Results ordered chronologically.
+    /// Weeks start on `display.week_start`. Results ordered chronologically.
     ///
     /// # Examples
     ///
@@ -520,10 +1048,11 @@ impl Tracker {
     /// # Ok::<(), anyhow::Error>(())
     /// ```
     pub fn get_by_week(&self) -> Result<Vec<WeekStats>> {
+        let timings = self.time_percentiles_by("rtk_week_start(timestamp)")?;
         let mut stmt = self.conn.prepare(
             "SELECT
-                DATE(timestamp, 'weekday 0', '-6 days') as week_start,
-                DATE(timestamp, 'weekday 0') as week_end,
+                rtk_week_start(timestamp) as week_start,
+                DATE(rtk_week_start(timestamp), '+6 days') as week_end,
                 COUNT(*) as commands,
                 SUM(input_tokens) as input,
                 SUM(output_tokens) as output,
@@ -550,8 +1079,11 @@ impl Tracker {
                 0
             };
 
+            let week_start: String = row.get(0)?;
+            let timing = timings.get(&week_start).copied().unwrap_or_default();
+
             Ok(WeekStats {
-                week_start: row.get(0)?,
+                week_start,
                 week_end: row.get(1)?,
                 commands,
                 input_tokens: input,
@@ -560,6 +1092,9 @@ impl Tracker {
                 savings_pct,
                 total_time_ms: total_time,
                 avg_time_ms,
+                p50_time_ms: timing.p50_ms,
+                p95_time_ms: timing.p95_ms,
+                max_time_ms: timing.max_ms,
             })
         })?;
 
@@ -587,9 +1122,10 @@ impl Tracker {
     /// # Ok::<(), anyhow::Error>(())
     /// ```
     pub fn get_by_month(&self) -> Result<Vec<MonthStats>> {
+        let timings = self.time_percentiles_by("substr(rtk_date(timestamp), 1, 7)")?;
         let mut stmt = self.conn.prepare(
             "SELECT
-                strftime('%Y-%m', timestamp) as month,
+                substr(rtk_date(timestamp), 1, 7) as month,
                 COUNT(*) as commands,
                 SUM(input_tokens) as input,
                 SUM(output_tokens) as output,
@@ -616,8 +1152,11 @@ impl Tracker {
                 0
             };
 
+            let month: String = row.get(0)?;
+            let timing = timings.get(&month).copied().unwrap_or_default();
+
             Ok(MonthStats {
-                month: row.get(0)?,
+                month,
                 commands,
                 input_tokens: input,
                 output_tokens: row.get::<_, i64>(3)? as usize,
@@ -625,6 +1164,9 @@ impl Tracker {
                 savings_pct,
                 total_time_ms: total_time,
                 avg_time_ms,
+                p50_time_ms: timing.p50_ms,
+                p95_time_ms: timing.p95_ms,
+                max_time_ms: timing.max_ms,
             })
         })?;
 
@@ -675,111 +1217,1037 @@ impl Tracker {
 
         Ok(rows.collect::<Result<Vec<_>, _>>()?)
     }
-}
-
-fn get_db_path() -> Result<PathBuf> {
-    // Priority 1: Environment variable RTK_DB_PATH
-    if let Ok(custom_path) = std::env::var("RTK_DB_PATH") {
-        return Ok(PathBuf::from(custom_path));
-    }
-
-    // Priority 2: Configuration file
-    if let Ok(config) = crate::config::Config::load() {
-        if let Some(db_path) = config.tracking.database_path {
-            return Ok(db_path);
-        }
-    }
-
-    // Priority 3: Default platform-specific location
-    let data_dir = dirs::data_local_dir().unwrap_or_else(|| PathBuf::from("."));
-    Ok(data_dir.join("rtk").join("history.db"))
-}
-
-/// Estimate token count from text using ~4 chars = 1 token heuristic.
-///
-/// This is a fast approximation suitable for tracking purposes.
-/// For precise counts, integrate with your LLM's tokenizer API.
-///
-/// # Formula
-///
-/// `tokens = ceil(chars / 4)`
-///
-/// # Examples
-///
-/// ```
-/// use rtk::tracking::estimate_tokens;
-///
-/// assert_eq!(estimate_tokens(""), 0);
-/// assert_eq!(estimate_tokens("abcd"), 1);  // 4 chars = 1 token
-/// assert_eq!(estimate_tokens("abcde"), 2); // 5 chars = ceil(1.25) = 2
-/// assert_eq!(estimate_tokens("hello world"), 3); // 11 chars = ceil(2.75) = 3
-/// ```
-pub fn estimate_tokens(text: &str) -> usize {
-    // ~4 chars per token on average
-    (text.len() as f64 / 4.0).ceil() as usize
-}
-
-/// Helper struct for timing command execution
-/// Helper for timing command execution and tracking results.
-///
-/// Preferred API for tracking commands. Automatically measures execution time
-/// and records token savings. Use instead of the deprecated [`track`] function.
-///
-/// # Examples
-///
-/// ```no_run
-/// use rtk::tracking::TimedExecution;
-///
-/// let timer = TimedExecution::start();
-/// let input = execute_standard_command()?;
-/// let output = execute_rtk_command()?;
-/// timer.track("ls -la", "rtk ls", &input, &output);
-/// # Ok::<(), anyhow::Error>(())
-/// ```
-pub struct TimedExecution {
-    start: Instant,
-}
 
-impl TimedExecution {
-    /// Start timing a command execution.
+    /// Get a page of raw records with `id > after_id`, oldest first.
     ///
-    /// Creates a new timer that starts measuring elapsed time immediately.
-    /// Call [`track`](Self::track) or [`track_passthrough`](Self::track_passthrough)
-    /// when the command completes.
+    /// Keyset pagination: pass the last returned `id` as `after_id` to fetch
+    /// the next page. Never loads more than `limit` rows at once.
     ///
     /// # Examples
     ///
     /// ```no_run
-    /// use rtk::tracking::TimedExecution;
+    /// use rtk::tracking::Tracker;
     ///
-    /// let timer = TimedExecution::start();
-    /// // ... execute command ...
-    /// timer.track("cmd", "rtk cmd", "input", "output");
+    /// let tracker = Tracker::new()?;
+    /// let mut cursor = 0;
+    /// loop {
+    ///     let page = tracker.get_records_after(cursor, 1000)?;
+    ///     let Some(last) = page.last() else { break };
+    ///     cursor = last.id;
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
     /// ```
-    pub fn start() -> Self {
-        Self {
-            start: Instant::now(),
-        }
+    pub fn get_records_after(&self, after_id: i64, limit: usize) -> Result<Vec<RawRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, timestamp, original_cmd, rtk_cmd, input_tokens, output_tokens,
+                    saved_tokens, savings_pct, exec_time_ms, exit_code, invoker, model
+             FROM commands
+             WHERE id > ?1
+             ORDER BY id ASC
+             LIMIT ?2",
+        )?;
+
+        let rows = stmt.query_map(params![after_id, limit as i64], |row| {
+            Ok(RawRecord {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                original_cmd: row.get(2)?,
+                rtk_cmd: row.get(3)?,
+                input_tokens: row.get::<_, i64>(4)? as usize,
+                output_tokens: row.get::<_, i64>(5)? as usize,
+                saved_tokens: row.get::<_, i64>(6)? as usize,
+                savings_pct: row.get(7)?,
+                exec_time_ms: row.get::<_, Option<i64>>(8)?.unwrap_or(0) as u64,
+                exit_code: row.get(9)?,
+                invoker: row.get(10)?,
+                model: row.get(11)?,
+            })
+        })?;
+
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
     }
 
-    /// Track the command with elapsed time and token counts.
-    ///
-    /// Records the command execution with:
-    /// - Elapsed time since [`start`](Self::start)
-    /// - Token counts estimated from input/output strings
-    /// - Calculated savings metrics
-    ///
-    /// # Arguments
-    ///
-    /// - `original_cmd`: Standard command (e.g., "ls -la")
-    /// - `rtk_cmd`: RTK command used (e.g., "rtk ls")
-    /// - `input`: Standard command output (for token estimation)
-    /// - `output`: RTK command output (for token estimation)
+    /// Get a record and its output sample (`None` if the record does not
+    /// exist or was tracked without `tracking.samples`).
     ///
     /// # Examples
     ///
     /// ```no_run
-    /// use rtk::tracking::TimedExecution;
+    /// use rtk::tracking::Tracker;
+    ///
+    /// let tracker = Tracker::new()?;
+    /// if let Some(s) = tracker.get_sample(42)? {
+    ///     println!("{} → {} bytes", s.sample.raw_bytes, s.sample.output_bytes);
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn get_sample(&self, id: i64) -> Result<Option<SampledRecord>> {
+        Ok(self
+            .query_sampled("WHERE c.id = ?1", params![id])?
+            .into_iter()
+            .next())
+    }
+
+    /// Get the most recent records that carry an output sample, newest first.
+    pub fn get_recent_samples(&self, limit: usize) -> Result<Vec<SampledRecord>> {
+        self.query_sampled("ORDER BY c.id DESC LIMIT ?1", params![limit as i64])
+    }
+
+    fn query_sampled(&self, tail: &str, args: impl rusqlite::Params) -> Result<Vec<SampledRecord>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT c.id, c.timestamp, c.original_cmd, c.rtk_cmd, c.input_tokens, c.output_tokens,
+                    c.saved_tokens, c.savings_pct, c.exec_time_ms, c.exit_code, c.invoker, c.model,
+                    s.raw_bytes, s.raw_hash, s.raw_head, s.output_bytes, s.output_hash, s.output_head
+             FROM commands c
+             JOIN samples s ON s.command_id = c.id
+             {}",
+            tail
+        ))?;
+
+        let rows = stmt.query_map(args, |row| {
+            Ok(SampledRecord {
+                record: RawRecord {
+                    id: row.get(0)?,
+                    timestamp: row.get(1)?,
+                    original_cmd: row.get(2)?,
+                    rtk_cmd: row.get(3)?,
+                    input_tokens: row.get::<_, i64>(4)? as usize,
+                    output_tokens: row.get::<_, i64>(5)? as usize,
+                    saved_tokens: row.get::<_, i64>(6)? as usize,
+                    savings_pct: row.get(7)?,
+                    exec_time_ms: row.get::<_, Option<i64>>(8)?.unwrap_or(0) as u64,
+                    exit_code: row.get(9)?,
+                    invoker: row.get(10)?,
+                    model: row.get(11)?,
+                },
+                sample: OutputSample {
+                    raw_bytes: row.get::<_, i64>(12)? as usize,
+                    raw_hash: row.get(13)?,
+                    raw_head: row.get(14)?,
+                    output_bytes: row.get::<_, i64>(15)? as usize,
+                    output_hash: row.get(16)?,
+                    output_head: row.get(17)?,
+                },
+            })
+        })?;
+
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Get the id of the most recently inserted record (0 if empty).
+    ///
+    /// Cheap change-detection probe used by `rtk gain --watch` to decide
+    /// when to reload statistics.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rtk::tracking::Tracker;
+    ///
+    /// let tracker = Tracker::new()?;
+    /// let before = tracker.latest_id()?;
+    /// // ... another rtk process records a command ...
+    /// let changed = tracker.latest_id()? != before;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn latest_id(&self) -> Result<i64> {
+        let id = self
+            .conn
+            .query_row("SELECT COALESCE(MAX(id), 0) FROM commands", [], |row| {
+                row.get::<_, i64>(0)
+            })?;
+        Ok(id)
+    }
+
+    /// Get token totals for records at or after `since`.
+    ///
+    /// Used by `rtk gain reconcile` to compare tracked estimates against the
+    /// same time window of agent transcripts.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use chrono::{Duration, Utc};
+    /// use rtk::tracking::Tracker;
+    ///
+    /// let tracker = Tracker::new()?;
+    /// let week = tracker.get_totals_since(Utc::now() - Duration::days(7))?;
+    /// println!("{} tokens saved this week", week.saved_tokens);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn get_totals_since(&self, since: DateTime<Utc>) -> Result<WindowTotals> {
+        let totals = self.conn.query_row(
+            "SELECT COUNT(*),
+                    COALESCE(SUM(input_tokens), 0),
+                    COALESCE(SUM(output_tokens), 0),
+                    COALESCE(SUM(saved_tokens), 0)
+             FROM commands
+             WHERE timestamp >= ?1",
+            params![since.to_rfc3339()],
+            |row| {
+                Ok(WindowTotals {
+                    commands: row.get::<_, i64>(0)? as usize,
+                    input_tokens: row.get::<_, i64>(1)? as usize,
+                    output_tokens: row.get::<_, i64>(2)? as usize,
+                    saved_tokens: row.get::<_, i64>(3)? as usize,
+                })
+            },
+        )?;
+        Ok(totals)
+    }
+
+    /// Get the commands that saved the most tokens at or after `since`.
+    ///
+    /// Used by `rtk gain digest`; `name` is the RTK command.
+    pub fn get_top_commands_since(
+        &self,
+        since: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<GroupStats>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT rtk_cmd, COUNT(*), SUM(input_tokens), SUM(output_tokens), SUM(saved_tokens)
+             FROM commands
+             WHERE timestamp >= ?1
+             GROUP BY rtk_cmd
+             ORDER BY SUM(saved_tokens) DESC
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![since.to_rfc3339(), limit as i64], group_stats)?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Record the failing tests of one `rtk test` run.
+    ///
+    /// `project` identifies the directory and `suite` the test command in
+    /// it (see [`crate::test_history`]); `failures` are test fingerprints.
+    pub fn record_test_run(
+        &self,
+        project: &str,
+        suite: &str,
+        git_head: Option<&str>,
+        code_state: Option<&str>,
+        failures: &[String],
+    ) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO test_runs (timestamp, project, suite, git_head, code_state, failures)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                Utc::now().to_rfc3339(),
+                project,
+                suite,
+                git_head,
+                code_state,
+                serde_json::to_string(failures)?
+            ],
+        )?;
+        self.cleanup_old()
+    }
+
+    /// Get the most recent runs of a test suite, newest first.
+    pub fn get_test_runs(&self, suite: &str, limit: usize) -> Result<Vec<TestRun>> {
+        self.query_test_runs("suite", suite, limit)
+    }
+
+    /// Get the most recent test runs of every suite in a project, newest first.
+    pub fn get_project_test_runs(&self, project: &str, limit: usize) -> Result<Vec<TestRun>> {
+        self.query_test_runs("project", project, limit)
+    }
+
+    fn query_test_runs(&self, column: &str, key: &str, limit: usize) -> Result<Vec<TestRun>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT suite, timestamp, git_head, code_state, failures FROM test_runs
+             WHERE {} = ?1
+             ORDER BY timestamp DESC, id DESC
+             LIMIT ?2",
+            column
+        ))?;
+        let rows = stmt.query_map(params![key, limit as i64], |row| {
+            let timestamp: String = row.get(1)?;
+            let failures: String = row.get(4)?;
+            Ok(TestRun {
+                suite: row.get(0)?,
+                timestamp: DateTime::parse_from_rfc3339(&timestamp)
+                    .map(|t| t.with_timezone(&Utc))
+                    .unwrap_or_default(),
+                git_head: row.get(2)?,
+                code_state: row.get(3)?,
+                failures: serde_json::from_str(&failures).unwrap_or_default(),
+            })
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Get per-command failure counts, most frequently failing first.
+    ///
+    /// Records without an exit code (older rows, passthrough of killed
+    /// processes) are ignored. Commands that never failed are included so
+    /// their run count is visible.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rtk::tracking::Tracker;
+    ///
+    /// let tracker = Tracker::new()?;
+    /// for f in tracker.get_failure_stats()? {
+    ///     println!("{}: {}/{} failed", f.command, f.failures, f.runs);
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn get_failure_stats(&self) -> Result<Vec<FailureStats>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT rtk_cmd,
+                    COUNT(*) as runs,
+                    SUM(CASE WHEN exit_code != 0 THEN 1 ELSE 0 END) as failures
+             FROM commands
+             WHERE exit_code IS NOT NULL
+             GROUP BY rtk_cmd
+             ORDER BY failures DESC, runs DESC",
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            let runs = row.get::<_, i64>(1)? as usize;
+            let failures = row.get::<_, i64>(2)? as usize;
+            Ok(FailureStats {
+                command: row.get(0)?,
+                runs,
+                failures,
+                failure_pct: failure_pct(failures, runs),
+            })
+        })?;
+
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Get savings per agent or per model, most tokens saved first.
+    ///
+    /// Passthrough records (no token counts) are included in `commands`
+    /// but do not affect the savings figures.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rtk::tracking::{Breakdown, Tracker};
+    ///
+    /// let tracker = Tracker::new()?;
+    /// for agent in tracker.get_breakdown(Breakdown::Agent)? {
+    ///     println!("{:?}: {} tokens saved", agent.name, agent.saved_tokens);
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn get_breakdown(&self, by: Breakdown) -> Result<Vec<GroupStats>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {col}, COUNT(*), SUM(input_tokens), SUM(output_tokens), SUM(saved_tokens)
+             FROM commands
+             GROUP BY {col}
+             ORDER BY SUM(saved_tokens) DESC",
+            col = by.column()
+        ))?;
+
+        let rows = stmt.query_map([], group_stats)?;
+
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+}
+
+/// `name, COUNT(*), SUM(input), SUM(output), SUM(saved)` row → [`GroupStats`].
+fn group_stats(row: &rusqlite::Row) -> rusqlite::Result<GroupStats> {
+    let input_tokens = row.get::<_, i64>(2)? as usize;
+    let saved_tokens = row.get::<_, i64>(4)? as usize;
+    Ok(GroupStats {
+        name: row.get(0)?,
+        commands: row.get::<_, i64>(1)? as usize,
+        input_tokens,
+        output_tokens: row.get::<_, i64>(3)? as usize,
+        saved_tokens,
+        savings_pct: if input_tokens > 0 {
+            saved_tokens as f64 / input_tokens as f64 * 100.0
+        } else {
+            0.0
+        },
+    })
+}
+
+/// Failure rate in percent (0 when there were no runs).
+pub fn failure_pct(failures: usize, runs: usize) -> f64 {
+    if runs > 0 {
+        failures as f64 / runs as f64 * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// A forward-only schema change, applied once and recorded in `schema_version`.
+pub struct Migration {
+    /// Schema version after this migration is applied (1-based, contiguous)
+    pub version: u32,
+    /// Short human-readable description shown by `rtk gain migrate`
+    pub description: &'static str,
+    sql: &'static str,
+}
+
+/// Ordered list of schema migrations. Append new entries; never edit applied ones.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create commands table",
+        sql: "CREATE TABLE IF NOT EXISTS commands (
+                id INTEGER PRIMARY KEY,
+                timestamp TEXT NOT NULL,
+                original_cmd TEXT NOT NULL,
+                rtk_cmd TEXT NOT NULL,
+                input_tokens INTEGER NOT NULL,
+                output_tokens INTEGER NOT NULL,
+                saved_tokens INTEGER NOT NULL,
+                savings_pct REAL NOT NULL
+              );
+              CREATE INDEX IF NOT EXISTS idx_timestamp ON commands(timestamp);",
+    },
+    Migration {
+        version: 2,
+        description: "add exec_time_ms column",
+        sql: "ALTER TABLE commands ADD COLUMN exec_time_ms INTEGER DEFAULT 0;",
+    },
+    Migration {
+        version: 3,
+        description: "add exit_code column",
+        sql: "ALTER TABLE commands ADD COLUMN exit_code INTEGER;",
+    },
+    Migration {
+        version: 4,
+        description: "add cache_hit column",
+        sql: "ALTER TABLE commands ADD COLUMN cache_hit INTEGER DEFAULT 0;",
+    },
+    Migration {
+        version: 5,
+        description: "add invoker column",
+        sql: "ALTER TABLE commands ADD COLUMN invoker TEXT;",
+    },
+    Migration {
+        version: 6,
+        description: "create samples table",
+        sql: "CREATE TABLE IF NOT EXISTS samples (
+                command_id INTEGER PRIMARY KEY,
+                raw_bytes INTEGER NOT NULL,
+                raw_hash TEXT NOT NULL,
+                raw_head TEXT NOT NULL,
+                output_bytes INTEGER NOT NULL,
+                output_hash TEXT NOT NULL,
+                output_head TEXT NOT NULL
+              );",
+    },
+    Migration {
+        version: 7,
+        description: "add model column",
+        sql: "ALTER TABLE commands ADD COLUMN model TEXT;",
+    },
+    Migration {
+        version: 8,
+        description: "create test_runs table",
+        sql: "CREATE TABLE IF NOT EXISTS test_runs (
+                id INTEGER PRIMARY KEY,
+                timestamp TEXT NOT NULL,
+                suite TEXT NOT NULL,
+                git_head TEXT,
+                code_state TEXT,
+                failures TEXT NOT NULL
+              );
+              CREATE INDEX IF NOT EXISTS idx_test_runs_suite ON test_runs(suite, timestamp);",
+    },
+    Migration {
+        version: 9,
+        description: "add project column to test_runs",
+        sql: "ALTER TABLE test_runs ADD COLUMN project TEXT;
+              CREATE INDEX IF NOT EXISTS idx_test_runs_project ON test_runs(project, timestamp);",
+    },
+];
+
+/// Latest schema version known to this build.
+pub fn latest_schema_version() -> u32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+/// Current schema state of the tracking database, as reported by `rtk gain migrate --check`.
+pub struct MigrationStatus {
+    /// Version currently recorded in the database
+    pub current: u32,
+    /// Latest version known to this build
+    pub latest: u32,
+    /// Migrations that would be applied on next open
+    pub pending: Vec<&'static Migration>,
+}
+
+/// Inspect the database schema version without applying anything.
+pub fn migration_status() -> Result<MigrationStatus> {
+    let db_path = get_db_path()?;
+    let current = if db_path.exists() {
+        let conn = Connection::open(&db_path)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        schema_version(&conn)?
+    } else {
+        0
+    };
+
+    Ok(MigrationStatus {
+        current,
+        latest: latest_schema_version(),
+        pending: MIGRATIONS.iter().filter(|m| m.version > current).collect(),
+    })
+}
+
+/// Tracking database health, as reported by `rtk doctor`.
+pub struct DbHealth {
+    pub path: PathBuf,
+    /// `None` when the database has not been created yet
+    pub integrity: Option<String>,
+    pub records: i64,
+    pub schema: u32,
+}
+
+/// Open the database read-only and run SQLite's integrity check.
+pub fn db_health() -> Result<DbHealth> {
+    let path = get_db_path()?;
+    if !path.exists() {
+        return Ok(DbHealth {
+            path,
+            integrity: None,
+            records: 0,
+            schema: 0,
+        });
+    }
+    let conn = Connection::open_with_flags(&path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    let integrity: String = conn.query_row("PRAGMA quick_check", [], |row| row.get(0))?;
+    let records = if table_exists(&conn, "commands")? {
+        conn.query_row("SELECT COUNT(*) FROM commands", [], |row| row.get(0))?
+    } else {
+        0
+    };
+    let schema = schema_version(&conn)?;
+    Ok(DbHealth {
+        path,
+        integrity: Some(integrity),
+        records,
+        schema,
+    })
+}
+
+/// Read the schema version, inferring it for databases created before
+/// `schema_version` existed (they were migrated ad hoc on open).
+fn schema_version(conn: &Connection) -> Result<u32> {
+    let has_version_table = table_exists(conn, "schema_version")?;
+    if has_version_table {
+        let version: Option<u32> =
+            conn.query_row("SELECT MAX(version) FROM schema_version", [], |row| {
+                row.get(0)
+            })?;
+        return Ok(version.unwrap_or(0));
+    }
+    infer_legacy_version(conn)
+}
+
+/// Map a pre-versioning schema onto the migration it corresponds to.
+fn infer_legacy_version(conn: &Connection) -> Result<u32> {
+    if !table_exists(conn, "commands")? {
+        Ok(0)
+    } else if column_exists(conn, "commands", "exec_time_ms")? {
+        Ok(2)
+    } else {
+        Ok(1)
+    }
+}
+
+fn table_exists(conn: &Connection, table: &str) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        params![table],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+fn column_exists(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    for name in names {
+        if name? == column {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Bring the schema up to [`latest_schema_version`]. Returns the versions applied.
+///
+/// Each migration runs in its own `BEGIN IMMEDIATE` transaction and re-checks
+/// the version inside it, so concurrent rtk processes never apply one twice.
+fn run_migrations(conn: &Connection) -> Result<Vec<u32>> {
+    let mut applied = Vec::new();
+    if schema_version(conn)? >= latest_schema_version() {
+        return Ok(applied);
+    }
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            applied_at TEXT NOT NULL
+        )",
+    )?;
+
+    // Record the inferred baseline for pre-versioning databases
+    let baseline: Option<u32> =
+        conn.query_row("SELECT MAX(version) FROM schema_version", [], |row| {
+            row.get(0)
+        })?;
+    if baseline.is_none() {
+        for version in 1..=infer_legacy_version(conn)? {
+            conn.execute(
+                "INSERT OR IGNORE INTO schema_version (version, applied_at) VALUES (?1, ?2)",
+                params![version, Utc::now().to_rfc3339()],
+            )?;
+        }
+    }
+
+    for migration in MIGRATIONS {
+        conn.execute_batch("BEGIN IMMEDIATE")?;
+        let result = (|| -> Result<bool> {
+            let current: Option<u32> =
+                conn.query_row("SELECT MAX(version) FROM schema_version", [], |row| {
+                    row.get(0)
+                })?;
+            if current.unwrap_or(0) >= migration.version {
+                return Ok(false);
+            }
+            conn.execute_batch(migration.sql)?;
+            conn.execute(
+                "INSERT INTO schema_version (version, applied_at) VALUES (?1, ?2)",
+                params![migration.version, Utc::now().to_rfc3339()],
+            )?;
+            Ok(true)
+        })();
+        match result {
+            Ok(did_apply) => {
+                conn.execute_batch("COMMIT")?;
+                if did_apply {
+                    applied.push(migration.version);
+                }
+            }
+            Err(e) => {
+                let _ = conn.execute_batch("ROLLBACK");
+                return Err(e.context(format!(
+                    "Tracking DB migration v{} ({}) failed",
+                    migration.version, migration.description
+                )));
+            }
+        }
+    }
+
+    Ok(applied)
+}
+
+/// Apply pending migrations now (`rtk gain migrate`). Returns the versions applied.
+pub fn migrate() -> Result<Vec<u32>> {
+    let db_path = get_db_path()?;
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let conn = Connection::open(&db_path)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    run_migrations(&conn)
+}
+
+/// Whether process `pid` is still running, i.e. may still finish its drain.
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    // SAFETY: signal 0 only checks that the process exists
+    let exists = unsafe { libc::kill(pid as libc::pid_t, 0) } == 0;
+    // EPERM: it exists, under another user
+    exists || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn process_alive(pid: u32) -> bool {
+    std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).contains(&pid.to_string()))
+        .unwrap_or(true)
+}
+
+/// Pending-queue file living next to the database (`history.db` → `history.pending.jsonl`).
+fn pending_path_for(db_path: &Path) -> PathBuf {
+    db_path.with_extension("pending.jsonl")
+}
+
+/// Import the spool now and checkpoint the WAL (`rtk gain compact`).
+/// Returns the number of records imported.
+pub fn compact() -> Result<usize> {
+    let tracker = Tracker::open()?;
+    let imported = tracker.recover_drains()? + tracker.drain_pending()?;
+    tracker
+        .conn
+        .execute_batch("PRAGMA wal_checkpoint(TRUNCATE)")?;
+    Ok(imported)
+}
+
+/// Record a tracked command without ever failing or delaying the
+/// user-facing command.
+///
+/// The record is appended to the spool, a single small write that concurrent
+/// invocations can make at the same time. The database is only opened by the
+/// run that grows the spool past [`SPOOL_FLUSH_BYTES`]; every
+/// [`Tracker::new`] (e.g. `rtk gain`) imports the spool first, and
+/// `rtk gain compact` imports it on demand. If the spool can't be written
+/// the record goes straight to the database.
+fn record_or_queue(
+    original_cmd: &str,
+    rtk_cmd: &str,
+    input_tokens: usize,
+    output_tokens: usize,
+    exec_time_ms: u64,
+    exit_code: Option<i32>,
+    sample: Option<OutputSample>,
+) {
+    crate::timings::mark("command");
+    let rtk_cmd = crate::alias::label().unwrap_or(rtk_cmd);
+    let pending = PendingRecord::now(
+        original_cmd,
+        rtk_cmd,
+        input_tokens,
+        output_tokens,
+        exec_time_ms,
+        exit_code,
+        sample,
+    );
+    // The spool is as readable as the database: redact before it's written
+    let pending = if crate::config::Config::cached().tracking.redact {
+        pending.redacted()
+    } else {
+        pending
+    };
+    let db_path = match get_db_path() {
+        Ok(path) => path,
+        Err(e) => {
+            tracing::warn!(
+                error = format!("{:#}", e),
+                "no tracking database, not recorded"
+            );
+            return;
+        }
+    };
+    if let Some(parent) = db_path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let spool = pending_path_for(&db_path);
+    match append_pending(&spool, &pending) {
+        Ok(size) if size < SPOOL_FLUSH_BYTES => {
+            tracing::debug!(spool = %spool.display(), size, "tracked")
+        }
+        // Imports the spool, this record included
+        Ok(size) => {
+            tracing::debug!(spool = %spool.display(), size, "tracked, importing spool");
+            drop(Tracker::new())
+        }
+        Err(e) => {
+            tracing::warn!(
+                error = format!("{:#}", e),
+                "spool append failed, writing to database"
+            );
+            match Tracker::new().and_then(|tracker| tracker.write(&pending)) {
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = format!("{:#}", e), "not tracked"),
+            }
+        }
+    }
+    crate::timings::mark("track");
+}
+
+/// Append one record to the spool. Returns the spool's size afterwards.
+fn append_pending(path: &Path, record: &PendingRecord) -> Result<u64> {
+    append_lines(path, &format!("{}\n", serde_json::to_string(record)?))
+}
+
+fn append_lines(path: &Path, lines: &str) -> Result<u64> {
+    for attempt in 0.. {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        // Shared: appends run side by side, a drain waits for them to finish
+        file.lock_shared()?;
+        if !is_current(&file, path) && attempt < 3 {
+            // A drain claimed the spool between open and lock
+            continue;
+        }
+        // Single write of full lines: O_APPEND keeps concurrent appends intact
+        file.write_all(lines.as_bytes())?;
+        return Ok(file.metadata()?.len());
+    }
+    unreachable!()
+}
+
+/// Whether `file` is still the file at `path` (not renamed away).
+#[cfg(unix)]
+fn is_current(file: &std::fs::File, path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (file.metadata(), std::fs::metadata(path)) {
+        (Ok(open), Ok(at_path)) => open.dev() == at_path.dev() && open.ino() == at_path.ino(),
+        _ => false,
+    }
+}
+
+/// Open files can't be renamed on Windows, so a drain never moves it.
+#[cfg(not(unix))]
+fn is_current(_file: &std::fs::File, _path: &Path) -> bool {
+    true
+}
+
+fn get_db_path() -> Result<PathBuf> {
+    // Priority 1: Environment variable RTK_DB_PATH
+    if let Ok(custom_path) = std::env::var("RTK_DB_PATH") {
+        return Ok(PathBuf::from(custom_path));
+    }
+
+    // Priority 2: Configuration file
+    if let Some(db_path) = &crate::config::Config::cached().tracking.database_path {
+        return Ok(db_path.clone());
+    }
+
+    // Priority 3: Default platform-specific location
+    let data_dir = dirs::data_local_dir().unwrap_or_else(|| PathBuf::from("."));
+    Ok(data_dir.join("rtk").join("history.db"))
+}
+
+/// Who ran rtk: `RTK_AGENT` when set (the agent integrations set it), else
+/// the marker variable an agent puts in its shell environment, else "shell".
+pub fn invoker() -> String {
+    invoker_from(|name| std::env::var(name).ok())
+}
+
+/// Model the agent is running: `RTK_MODEL` when set, else the model
+/// variable the agent itself reads. `None` when unknown.
+pub fn model() -> Option<String> {
+    model_from(|name| std::env::var(name).ok())
+}
+
+fn model_from(var: impl Fn(&str) -> Option<String>) -> Option<String> {
+    [
+        "RTK_MODEL",
+        "ANTHROPIC_MODEL",
+        "GEMINI_MODEL",
+        "CODEX_MODEL",
+    ]
+    .iter()
+    .filter_map(|name| var(name))
+    .map(|value| value.trim().to_string())
+    .find(|value| !value.is_empty())
+}
+
+fn invoker_from(var: impl Fn(&str) -> Option<String>) -> String {
+    if let Some(agent) = var("RTK_AGENT").filter(|v| !v.trim().is_empty()) {
+        return agent.trim().to_string();
+    }
+    const MARKERS: &[(&str, &str)] = &[
+        ("CLAUDECODE", "claude-code"),
+        ("GEMINI_CLI", "gemini-cli"),
+        ("CODEX_SANDBOX", "codex"),
+        ("CODEX_SANDBOX_NETWORK_DISABLED", "codex"),
+    ];
+    MARKERS
+        .iter()
+        .find(|(name, _)| var(name).is_some())
+        .map_or("shell", |(_, agent)| agent)
+        .to_string()
+}
+
+/// Decide whether a command execution should be written to the database.
+///
+/// Tracking is skipped entirely (no DB file is opened) when:
+/// - `RTK_NO_TRACK` is set to anything but `0`/`false`/empty
+/// - `tracking.enabled = false` in config.toml
+/// - the original or rtk command matches a `tracking.exclude_commands` glob
+pub fn should_track(original_cmd: &str, rtk_cmd: &str) -> bool {
+    if let Ok(v) = std::env::var("RTK_NO_TRACK") {
+        if !matches!(v.trim(), "" | "0" | "false") {
+            return false;
+        }
+    }
+
+    let config = crate::config::Config::cached();
+    if !config.tracking.enabled {
+        return false;
+    }
+
+    !config
+        .tracking
+        .exclude_commands
+        .iter()
+        .any(|pattern| glob_match(pattern, original_cmd) || glob_match(pattern, rtk_cmd))
+}
+
+/// Minimal glob matcher: `*` matches any run of characters, everything else is literal.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let first = parts[0];
+    let last = parts[parts.len() - 1];
+    if text.len() < first.len() + last.len() || !text.starts_with(first) || !text.ends_with(last) {
+        return false;
+    }
+
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Estimate token count from text using ~4 chars = 1 token heuristic.
+///
+/// This is a fast approximation suitable for tracking purposes.
+/// For precise counts, integrate with your LLM's tokenizer API.
+///
+/// # Formula
+///
+/// `tokens = ceil(chars / 4)`
+///
+/// # Examples
+///
+/// ```
+/// use rtk::tracking::estimate_tokens;
+///
+/// assert_eq!(estimate_tokens(""), 0);
+/// assert_eq!(estimate_tokens("abcd"), 1);  // 4 chars = 1 token
+/// assert_eq!(estimate_tokens("abcde"), 2); // 5 chars = ceil(1.25) = 2
+/// assert_eq!(estimate_tokens("hello world"), 3); // 11 chars = ceil(2.75) = 3
+/// ```
+pub fn estimate_tokens(text: &str) -> usize {
+    estimate_tokens_with(text, crate::config::Config::cached().output.tokenizer)
+}
+
+/// [`estimate_tokens`] with an explicit tokenizer instead of `output.tokenizer`.
+pub fn estimate_tokens_with(text: &str, tokenizer: crate::config::Tokenizer) -> usize {
+    use crate::config::Tokenizer;
+
+    match tokenizer {
+        // ~4 chars per token on average
+        Tokenizer::Chars => (text.len() as f64 / 4.0).ceil() as usize,
+        // ~0.75 words per token for prose
+        Tokenizer::Words => (text.split_whitespace().count() as f64 / 0.75).ceil() as usize,
+    }
+}
+
+/// Helper struct for timing command execution
+/// Helper for timing command execution and tracking results.
+///
+/// Preferred API for tracking commands. Automatically measures execution time
+/// and records token savings. Use instead of the deprecated [`track`] function.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rtk::tracking::TimedExecution;
+/// # fn execute_standard_command() -> anyhow::Result<String> { Ok(String::new()) }
+/// # fn execute_rtk_command() -> anyhow::Result<String> { Ok(String::new()) }
+///
+/// let timer = TimedExecution::start();
+/// let input = execute_standard_command()?;
+/// let output = execute_rtk_command()?;
+/// timer.track("ls -la", "rtk ls", &input, &output);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct TimedExecution {
+    start: Instant,
+    exit_code: Option<i32>,
+    sensitive: bool,
+}
+
+impl TimedExecution {
+    /// Start timing a command execution.
+    ///
+    /// Creates a new timer that starts measuring elapsed time immediately.
+    /// Call [`track`](Self::track) or [`track_passthrough`](Self::track_passthrough)
+    /// when the command completes.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rtk::tracking::TimedExecution;
+    ///
+    /// let timer = TimedExecution::start();
+    /// // ... execute command ...
+    /// timer.track("cmd", "rtk cmd", "input", "output");
+    /// ```
+    pub fn start() -> Self {
+        Self {
+            start: Instant::now(),
+            exit_code: None,
+            sensitive: false,
+        }
+    }
+
+    /// Attach the wrapped command's exit status to the record.
+    ///
+    /// Mapped like a shell would (a signal is `128 + signal`, see
+    /// exit_code.rs). Feeds `rtk gain --failures`, and becomes rtk's own exit
+    /// code.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rtk::tracking::TimedExecution;
+    ///
+    /// let timer = TimedExecution::start();
+    /// let status = std::process::Command::new("cargo").arg("test").status()?;
+    /// timer
+    ///     .with_exit_code(status)
+    ///     .track_passthrough("cargo test", "rtk cargo test");
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn with_exit_code(self, status: ExitStatus) -> Self {
+        self.with_code(crate::exit_code::from_status(status))
+    }
+
+    /// [`with_exit_code`](Self::with_exit_code) for a code rtk decided itself
+    /// (a timeout, `--fail-on`, a report read from a file).
+    pub fn with_code(mut self, code: i32) -> Self {
+        self.exit_code = Some(code);
+        crate::exit_code::set(code);
+        self
+    }
+
+    /// Mark the wrapped command's output as secret (`rtk secrets`, `rtk env`):
+    /// counts are still tracked, but its text is never written to disk, as
+    /// elided sections or samples.
+    pub fn sensitive(mut self) -> Self {
+        self.sensitive = true;
+        self
+    }
+
+    /// Track the command with elapsed time and token counts.
+    ///
+    /// Records the command execution with:
+    /// - Elapsed time since [`start`](Self::start)
+    /// - Token counts estimated from input/output strings
+    /// - Calculated savings metrics
+    ///
+    /// # Arguments
+    ///
+    /// - `original_cmd`: Standard command (e.g., "ls -la")
+    /// - `rtk_cmd`: RTK command used (e.g., "rtk ls")
+    /// - `input`: Standard command output (for token estimation)
+    /// - `output`: RTK command output (for token estimation)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rtk::tracking::TimedExecution;
     ///
     /// let timer = TimedExecution::start();
     /// let input = "long output...";
@@ -787,19 +2255,88 @@ impl TimedExecution {
     /// timer.track("ls -la", "rtk ls", input, output);
     /// ```
     pub fn track(&self, original_cmd: &str, rtk_cmd: &str, input: &str, output: &str) {
+        crate::profile::show_raw(input);
+        crate::pager::capture(input);
+        let tracked = should_track(original_cmd, rtk_cmd);
+        let listing = (tracked && !self.sensitive)
+            .then(|| crate::elided::record(input, output))
+            .flatten();
+        crate::explain::report_if_enabled(input, output);
         let elapsed_ms = self.start.elapsed().as_millis() as u64;
         let input_tokens = estimate_tokens(input);
-        let output_tokens = estimate_tokens(output);
+        // Count what was actually printed, i.e. after user pipeline rules,
+        // plus the elided-section listing
+        let printed = crate::pipeline::preview(output);
+        let output_tokens = printed.as_deref().map_or(0, estimate_tokens)
+            + listing.as_deref().map_or(0, estimate_tokens);
+        tracing::debug!(
+            original_cmd,
+            rtk_cmd,
+            input_tokens,
+            output_tokens,
+            elapsed_ms,
+            exit_code = ?self.exit_code,
+            pipeline = ?crate::pipeline::stats(),
+            "filtered"
+        );
 
-        if let Ok(tracker) = Tracker::new() {
-            let _ = tracker.record(
-                original_cmd,
-                rtk_cmd,
-                input_tokens,
-                output_tokens,
-                elapsed_ms,
-            );
+        if !tracked {
+            return;
         }
+        let tracking = &crate::config::Config::cached().tracking;
+        let sample = (tracking.samples && !self.sensitive).then(|| {
+            OutputSample::new(
+                input,
+                printed.as_deref().unwrap_or(""),
+                tracking.sample_chars,
+            )
+        });
+        record_or_queue(
+            original_cmd,
+            rtk_cmd,
+            input_tokens,
+            output_tokens,
+            elapsed_ms,
+            self.exit_code,
+            sample,
+        );
+    }
+
+    /// Track a command whose raw output was too large to keep in memory.
+    ///
+    /// Same as [`track`](Self::track) with `input_tokens` counted by the
+    /// caller as the output streamed past (see [`crate::stream`]).
+    pub fn track_tokens(
+        &self,
+        original_cmd: &str,
+        rtk_cmd: &str,
+        input_tokens: usize,
+        output: &str,
+    ) {
+        let elapsed_ms = self.start.elapsed().as_millis() as u64;
+        let output_tokens = crate::pipeline::preview(output).map_or(0, |o| estimate_tokens(&o));
+        tracing::debug!(
+            original_cmd,
+            rtk_cmd,
+            input_tokens,
+            output_tokens,
+            elapsed_ms,
+            exit_code = ?self.exit_code,
+            "filtered (streamed)"
+        );
+
+        if !should_track(original_cmd, rtk_cmd) {
+            return;
+        }
+        record_or_queue(
+            original_cmd,
+            rtk_cmd,
+            input_tokens,
+            output_tokens,
+            elapsed_ms,
+            self.exit_code,
+            None,
+        );
     }
 
     /// Track passthrough commands (timing-only, no token counting).
@@ -824,10 +2361,20 @@ impl TimedExecution {
     /// ```
     pub fn track_passthrough(&self, original_cmd: &str, rtk_cmd: &str) {
         let elapsed_ms = self.start.elapsed().as_millis() as u64;
+        tracing::debug!(original_cmd, elapsed_ms, exit_code = ?self.exit_code, "passthrough");
         // input_tokens=0, output_tokens=0 won't dilute savings statistics
-        if let Ok(tracker) = Tracker::new() {
-            let _ = tracker.record(original_cmd, rtk_cmd, 0, 0, elapsed_ms);
+        if !should_track(original_cmd, rtk_cmd) {
+            return;
         }
+        record_or_queue(
+            original_cmd,
+            rtk_cmd,
+            0,
+            0,
+            elapsed_ms,
+            self.exit_code,
+            None,
+        );
     }
 }
 
@@ -852,6 +2399,28 @@ pub fn args_display(args: &[OsString]) -> String {
         .join(" ")
 }
 
+/// Record a run that panicked (see [`crate::crash`]).
+///
+/// Exit code [`INTERNAL_ERROR`](crate::exit_code::INTERNAL_ERROR), the panic
+/// message as the output sample, and the raw output shown in its place as the
+/// sample's input when `tracking.samples` is on.
+pub fn record_crash(original_cmd: &str, rtk_cmd: &str, raw: &str, message: &str) {
+    if !should_track(original_cmd, rtk_cmd) {
+        return;
+    }
+    let tracking = &crate::config::Config::cached().tracking;
+    let input = if tracking.samples { raw } else { "" };
+    record_or_queue(
+        original_cmd,
+        rtk_cmd,
+        estimate_tokens(raw),
+        estimate_tokens(message),
+        0,
+        Some(crate::exit_code::INTERNAL_ERROR),
+        Some(OutputSample::new(input, message, tracking.sample_chars)),
+    );
+}
+
 /// Track a command execution (legacy function, use [`TimedExecution`] for new code).
 ///
 /// # Deprecation Notice
@@ -882,15 +2451,51 @@ pub fn track(original_cmd: &str, rtk_cmd: &str, input: &str, output: &str) {
     let input_tokens = estimate_tokens(input);
     let output_tokens = estimate_tokens(output);
 
-    if let Ok(tracker) = Tracker::new() {
-        let _ = tracker.record(original_cmd, rtk_cmd, input_tokens, output_tokens, 0);
+    if !should_track(original_cmd, rtk_cmd) {
+        return;
     }
+    record_or_queue(
+        original_cmd,
+        rtk_cmd,
+        input_tokens,
+        output_tokens,
+        0,
+        None,
+        None,
+    );
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// In-memory tracker with default settings; the spool is never used.
+    fn test_tracker() -> Tracker {
+        tracker_on(
+            Connection::open_in_memory().unwrap(),
+            PathBuf::from("unused.pending.jsonl"),
+        )
+    }
+
+    /// Tracker on a database file, with its spool next to it.
+    fn test_tracker_at(db_path: &Path) -> Tracker {
+        let conn = Connection::open(db_path).unwrap();
+        // Generous: tests race drains across threads
+        conn.busy_timeout(Duration::from_secs(5)).unwrap();
+        tracker_on(conn, pending_path_for(db_path))
+    }
+
+    fn tracker_on(conn: Connection, pending_path: PathBuf) -> Tracker {
+        run_migrations(&conn).unwrap();
+        crate::local_time::register(&conn).unwrap();
+        Tracker {
+            conn,
+            redact: false,
+            history_days: 90,
+            pending_path,
+        }
+    }
+
     // 1. estimate_tokens — verify ~4 chars/token ratio
     #[test]
     fn test_estimate_tokens() {
@@ -921,7 +2526,7 @@ mod tests {
         let test_cmd = format!("rtk git status test_{}", std::process::id());
 
         tracker
-            .record("git status", &test_cmd, 100, 20, 50)
+            .record("git status", &test_cmd, 100, 20, 50, None)
             .expect("Failed to record");
 
         let recent = tracker.get_recent(10).expect("Failed to get recent");
@@ -948,12 +2553,12 @@ mod tests {
 
         // Record one real command with 80% savings
         tracker
-            .record("cmd1", &cmd1, 1000, 200, 10)
+            .record("cmd1", &cmd1, 1000, 200, 10, None)
             .expect("Failed to record cmd1");
 
         // Record passthrough (0, 0)
         tracker
-            .record("cmd2", &cmd2, 0, 0, 5)
+            .record("cmd2", &cmd2, 0, 0, 5, None)
             .expect("Failed to record passthrough");
 
         // Verify both records exist in recent history
@@ -1012,7 +2617,380 @@ mod tests {
         assert_eq!(pt.saved_tokens, 0);
     }
 
-    // 7. get_db_path respects environment variable RTK_DB_PATH
+    #[cfg(unix)]
+    #[test]
+    fn test_exit_status_killed_by_signal() {
+        use std::os::unix::process::ExitStatusExt;
+        // SIGINT (2), as a shell reports it; not a generic failure
+        let timer = TimedExecution::start().with_exit_code(ExitStatus::from_raw(2));
+        assert_eq!(timer.exit_code, Some(130));
+        let timer = TimedExecution::start().with_exit_code(ExitStatus::from_raw(3 << 8));
+        assert_eq!(timer.exit_code, Some(3));
+    }
+
+    // 7. latest_id advances after a new record
+    #[test]
+    fn test_latest_id_advances() {
+        let tracker = Tracker::new().expect("Failed to create tracker");
+        let before = tracker.latest_id().expect("Failed to get latest id");
+
+        let test_cmd = format!("rtk latest_id_test_{}", std::process::id());
+        tracker
+            .record("ls", &test_cmd, 10, 5, 1, None)
+            .expect("Failed to record");
+
+        let after = tracker.latest_id().expect("Failed to get latest id");
+        assert!(after > before);
+    }
+
+    // 8. get_records_after pages by id cursor
+    #[test]
+    fn test_get_records_after_cursor() {
+        let tracker = Tracker::new().expect("Failed to create tracker");
+        let start = tracker.latest_id().expect("Failed to get latest id");
+
+        let pid = std::process::id();
+        let cmd_a = format!("rtk records_a_{}", pid);
+        let cmd_b = format!("rtk records_b_{}", pid);
+        tracker.record("a", &cmd_a, 100, 40, 7, Some(0)).unwrap();
+        tracker.record("b", &cmd_b, 50, 50, 3, None).unwrap();
+
+        let page = tracker
+            .get_records_after(start, 10_000)
+            .expect("Failed to page records");
+        let a = page.iter().find(|r| r.rtk_cmd == cmd_a).expect("a missing");
+        let b = page.iter().find(|r| r.rtk_cmd == cmd_b).expect("b missing");
+        assert!(a.id < b.id);
+        assert_eq!(a.saved_tokens, 60);
+        assert_eq!(a.exec_time_ms, 7);
+
+        let next = tracker.get_records_after(a.id, 10_000).unwrap();
+        assert!(next.iter().all(|r| r.id > a.id));
+        assert!(next.iter().any(|r| r.rtk_cmd == cmd_b));
+    }
+
+    // 9. glob_match — exclusion patterns
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("rtk curl*", "rtk curl https://x"));
+        assert!(glob_match("*secret*", "cat secret.txt"));
+        assert!(glob_match("rtk git status", "rtk git status"));
+        assert!(glob_match("rtk * push", "rtk git push"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("rtk curl*", "rtk git status"));
+        assert!(!glob_match("rtk git status", "rtk git status -s"));
+        assert!(!glob_match("a*a", "a"));
+    }
+
+    // 10. pending queue — records appended while locked are drained once
+    #[test]
+    fn test_drain_pending_imports_queued_records() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let db_path = dir.path().join("history.db");
+        let tracker = test_tracker_at(&db_path);
+
+        let timestamp = Utc::now().to_rfc3339();
+        let queued = PendingRecord {
+            timestamp: timestamp.clone(),
+            original_cmd: "ls -la".to_string(),
+            rtk_cmd: "rtk ls queued".to_string(),
+            input_tokens: 100,
+            output_tokens: 25,
+            exec_time_ms: 4,
+            exit_code: Some(1),
+            cache_hit: false,
+            invoker: Some("codex".to_string()),
+            model: None,
+            sample: None,
+        };
+        append_pending(&tracker.pending_path, &queued).unwrap();
+        append_pending(&tracker.pending_path, &queued).unwrap();
+
+        assert_eq!(tracker.refresh().unwrap(), 2);
+        assert!(!tracker.pending_path.exists());
+        assert_eq!(tracker.refresh().unwrap(), 0);
+
+        let rows = tracker.get_records_after(0, 10).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].timestamp, timestamp);
+        assert_eq!(rows[0].saved_tokens, 75);
+        assert_eq!(rows[0].exit_code, Some(1));
+    }
+
+    // spool — a drain whose process died is imported by the next tracker
+    #[test]
+    fn test_interrupted_drain_is_recovered() {
+        use std::process::{Command, Stdio};
+        let dir = tempfile::tempdir().expect("tempdir");
+        let db_path = dir.path().join("history.db");
+        let tracker = test_tracker_at(&db_path);
+        let rec = PendingRecord::now("ls", "rtk ls", 10, 5, 1, Some(0), None);
+
+        let mut exited = crate::stream::shell("exit 0").spawn().unwrap();
+        exited.wait().unwrap();
+        let dead = tracker.claimed_path(exited.id());
+        append_pending(&dead, &rec).unwrap();
+        append_pending(&dead, &rec).unwrap();
+        // Still being drained by a live process: left alone
+        let mut running = Command::new("sort")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+        let live = tracker.claimed_path(running.id());
+        append_pending(&live, &rec).unwrap();
+
+        assert_eq!(tracker.recover_drains().unwrap(), 2);
+        assert!(!dead.exists());
+        assert!(live.exists());
+        assert_eq!(tracker.recover_drains().unwrap(), 0);
+        assert_eq!(tracker.get_records_after(0, 10).unwrap().len(), 2);
+        drop(running.stdin.take());
+        running.wait().unwrap();
+    }
+
+    // spool — appends racing with drains are each imported exactly once
+    #[test]
+    fn test_spool_survives_concurrent_appends_and_drains() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let db_path = dir.path().join("history.db");
+        let spool = pending_path_for(&db_path);
+        let drainer = test_tracker_at(&db_path);
+
+        let writers: Vec<_> = (0..4)
+            .map(|w| {
+                let spool = spool.clone();
+                std::thread::spawn(move || {
+                    for i in 0..50 {
+                        let rec = PendingRecord::now(
+                            &format!("cmd {} {}", w, i),
+                            "rtk cmd",
+                            10,
+                            5,
+                            1,
+                            Some(0),
+                            None,
+                        );
+                        append_pending(&spool, &rec).unwrap();
+                    }
+                })
+            })
+            .collect();
+        let mut imported = 0;
+        while writers.iter().any(|w| !w.is_finished()) {
+            imported += drainer.drain_pending().unwrap();
+        }
+        for w in writers {
+            w.join().unwrap();
+        }
+        imported += drainer.drain_pending().unwrap();
+
+        assert_eq!(imported, 200);
+        let rows = drainer.get_records_after(0, 1000).unwrap();
+        assert_eq!(rows.len(), 200);
+    }
+
+    // output samples — stored with the record, text dropped when redacting
+    #[test]
+    fn test_output_samples_round_trip() {
+        let mut tracker = test_tracker();
+        let raw = "Compiling foo v0.1.0\nCompiling bar v0.2.0\ntest result: ok\n";
+        let sample = OutputSample::new(raw, "✓ cargo test: ok", 20);
+        assert_eq!(sample.raw_head, "Compiling foo v0.1.0");
+        assert_eq!(sample.raw_bytes, raw.len());
+
+        let rec = PendingRecord::now(
+            "cargo test",
+            "rtk cargo test",
+            20,
+            5,
+            1,
+            Some(0),
+            Some(sample.clone()),
+        );
+        tracker.write(&rec).unwrap();
+        tracker.record("ls", "rtk ls", 10, 2, 1, None).unwrap();
+
+        let sampled = tracker.get_sample(1).unwrap().expect("sample stored");
+        assert_eq!(sampled.record.rtk_cmd, "rtk cargo test");
+        assert_eq!(sampled.sample, sample);
+        assert!(tracker.get_sample(2).unwrap().is_none());
+        assert_eq!(tracker.get_recent_samples(10).unwrap().len(), 1);
+
+        tracker.redact = true;
+        tracker.write(&rec).unwrap();
+        let redacted = tracker.get_sample(3).unwrap().unwrap().sample;
+        assert_eq!(redacted.raw_hash, sample.raw_hash);
+        assert!(redacted.raw_head.is_empty() && redacted.output_head.is_empty());
+
+        // Spooled records are redacted before they reach the disk
+        let spooled = PendingRecord::now(
+            "cat /srv/app/secrets.env",
+            "rtk read /srv/app/secrets.env",
+            20,
+            5,
+            1,
+            Some(0),
+            Some(sample.clone()),
+        )
+        .redacted();
+        assert!(!spooled.original_cmd.contains("/srv/app"));
+        assert!(spooled.rtk_cmd.starts_with("rtk read <path:"));
+        let spooled_sample = spooled.sample.unwrap();
+        assert_eq!(spooled_sample.raw_hash, sample.raw_hash);
+        assert!(spooled_sample.raw_head.is_empty() && spooled_sample.output_head.is_empty());
+    }
+
+    // retention — cleanup follows tracking.history_days
+    #[test]
+    fn test_cleanup_follows_history_days() {
+        let tracker = Tracker {
+            history_days: 7,
+            ..test_tracker()
+        };
+        for days in [10, 3] {
+            let mut rec = PendingRecord::now("ls", "rtk ls", 10, 2, 1, None, None);
+            rec.timestamp = (Utc::now() - chrono::Duration::days(days)).to_rfc3339();
+            tracker.insert(&rec).unwrap();
+        }
+        assert_eq!(tracker.get_records_after(0, 10).unwrap().len(), 2);
+        tracker.cleanup_old().unwrap();
+        let rows = tracker.get_records_after(0, 10).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].id, 2);
+    }
+
+    // 11. pending file sits next to the database
+    #[test]
+    fn test_pending_path_for() {
+        assert_eq!(
+            pending_path_for(Path::new("/data/rtk/history.db")),
+            PathBuf::from("/data/rtk/history.pending.jsonl")
+        );
+    }
+
+    // 12. migrations — fresh DB goes straight to latest, reruns are no-ops
+    #[test]
+    fn test_run_migrations_fresh_db() {
+        let conn = Connection::open_in_memory().unwrap();
+        let applied = run_migrations(&conn).unwrap();
+        assert_eq!(applied, (1..=latest_schema_version()).collect::<Vec<_>>());
+        assert_eq!(schema_version(&conn).unwrap(), latest_schema_version());
+        assert!(column_exists(&conn, "commands", "exec_time_ms").unwrap());
+
+        assert!(run_migrations(&conn).unwrap().is_empty());
+    }
+
+    // 13. migrations — legacy (pre-versioning) DB is adopted without re-running ALTERs
+    #[test]
+    fn test_run_migrations_legacy_db() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0].sql).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), 1);
+
+        let applied = run_migrations(&conn).unwrap();
+        assert_eq!(applied.first(), Some(&2));
+        assert_eq!(schema_version(&conn).unwrap(), latest_schema_version());
+
+        // Already has exec_time_ms: adopted as v2, nothing re-applied
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0].sql).unwrap();
+        conn.execute_batch(MIGRATIONS[1].sql).unwrap();
+        let applied = run_migrations(&conn).unwrap();
+        assert!(!applied.contains(&2));
+        assert_eq!(schema_version(&conn).unwrap(), latest_schema_version());
+    }
+
+    // 14. migration list is contiguous and 1-based
+    #[test]
+    fn test_migrations_are_contiguous() {
+        for (i, m) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(m.version as usize, i + 1);
+        }
+    }
+
+    // 15. failure stats — only runs with a known exit code count
+    #[test]
+    fn test_get_failure_stats() {
+        let tracker = test_tracker();
+
+        for code in [Some(0), Some(101), Some(101), None] {
+            tracker
+                .record("cargo test", "rtk cargo test", 10, 5, 1, code)
+                .unwrap();
+        }
+        tracker
+            .record("git status", "rtk git status", 10, 5, 1, Some(0))
+            .unwrap();
+
+        let stats = tracker.get_failure_stats().unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].command, "rtk cargo test");
+        assert_eq!(stats[0].runs, 3);
+        assert_eq!(stats[0].failures, 2);
+        assert!((stats[0].failure_pct - 66.666).abs() < 0.01);
+        assert_eq!(stats[1].failures, 0);
+        assert_eq!(failure_pct(0, 0), 0.0);
+    }
+
+    // 16. TimePercentiles — nearest-rank p50/p95/max
+    #[test]
+    fn test_time_percentiles_from_samples() {
+        assert_eq!(
+            TimePercentiles::from_samples(vec![]),
+            TimePercentiles::default()
+        );
+
+        let single = TimePercentiles::from_samples(vec![7]);
+        assert_eq!((single.p50_ms, single.p95_ms, single.max_ms), (7, 7, 7));
+
+        // 1..=100 unsorted: p50 = 50, p95 = 95
+        let samples: Vec<u64> = (1..=100).rev().collect();
+        let p = TimePercentiles::from_samples(samples);
+        assert_eq!((p.p50_ms, p.p95_ms, p.max_ms), (50, 95, 100));
+    }
+
+    // 17. period stats carry percentiles of their own rows
+    #[test]
+    fn test_period_stats_percentiles() {
+        let tracker = test_tracker();
+        for ms in [10, 20, 30, 40, 5000] {
+            tracker.record("ls", "rtk ls", 10, 5, ms, Some(0)).unwrap();
+        }
+
+        let days = tracker.get_all_days().unwrap();
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].p50_time_ms, 30);
+        assert_eq!(days[0].p95_time_ms, 5000);
+        assert_eq!(days[0].max_time_ms, 5000);
+
+        let summary = tracker.get_summary().unwrap();
+        assert_eq!(summary.by_command[0].5.p50_ms, 30);
+        assert_eq!(summary.by_command[0].5.max_ms, 5000);
+    }
+
+    // 18. get_totals_since — only rows inside the window are summed
+    #[test]
+    fn test_get_totals_since() {
+        let tracker = test_tracker();
+        tracker.record("ls", "rtk ls", 100, 20, 1, Some(0)).unwrap();
+        tracker.record("ls", "rtk ls", 50, 10, 1, Some(0)).unwrap();
+
+        let all = tracker
+            .get_totals_since(Utc::now() - chrono::Duration::days(1))
+            .unwrap();
+        assert_eq!(all.commands, 2);
+        assert_eq!(all.output_tokens, 30);
+        assert_eq!(all.saved_tokens, 120);
+
+        let none = tracker
+            .get_totals_since(Utc::now() + chrono::Duration::days(1))
+            .unwrap();
+        assert_eq!(none.commands, 0);
+        assert_eq!(none.saved_tokens, 0);
+    }
+
+    // 19. get_db_path respects environment variable RTK_DB_PATH
     #[test]
     fn test_custom_db_path_env() {
         use std::env;
@@ -1026,7 +3004,7 @@ mod tests {
         env::remove_var("RTK_DB_PATH");
     }
 
-    // 8. get_db_path falls back to default when no custom config
+    // 20. get_db_path falls back to default when no custom config
     #[test]
     fn test_default_db_path() {
         use std::env;
@@ -1037,4 +3015,106 @@ mod tests {
         let db_path = get_db_path().expect("Failed to get db path");
         assert!(db_path.ends_with("rtk/history.db"));
     }
+
+    #[test]
+    fn test_invoker_detection() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        assert_eq!(invoker_from(env(&[])), "shell");
+        assert_eq!(invoker_from(env(&[("CLAUDECODE", "1")])), "claude-code");
+        assert_eq!(invoker_from(env(&[("CODEX_SANDBOX", "seatbelt")])), "codex");
+        assert_eq!(
+            invoker_from(env(&[("RTK_AGENT", "aider"), ("CLAUDECODE", "1")])),
+            "aider"
+        );
+    }
+
+    #[test]
+    fn test_model_detection() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        assert_eq!(model_from(env(&[])), None);
+        assert_eq!(
+            model_from(env(&[("ANTHROPIC_MODEL", "claude-sonnet-4-5")])).as_deref(),
+            Some("claude-sonnet-4-5")
+        );
+        assert_eq!(
+            model_from(env(&[
+                ("RTK_MODEL", " "),
+                ("GEMINI_MODEL", "gemini-2.5-pro")
+            ]))
+            .as_deref(),
+            Some("gemini-2.5-pro")
+        );
+    }
+
+    #[test]
+    fn test_get_breakdown_groups_by_agent_and_model() {
+        let tracker = test_tracker();
+        for (invoker, model, input) in [
+            ("claude-code", Some("opus"), 100),
+            ("claude-code", Some("sonnet"), 300),
+            ("shell", None, 50),
+        ] {
+            let mut rec = PendingRecord::now("ls", "rtk ls", input, 10, 1, Some(0), None);
+            rec.invoker = Some(invoker.to_string());
+            rec.model = model.map(str::to_string);
+            tracker.write(&rec).unwrap();
+        }
+
+        let agents = tracker.get_breakdown(Breakdown::Agent).unwrap();
+        assert_eq!(agents.len(), 2);
+        assert_eq!(agents[0].name.as_deref(), Some("claude-code"));
+        assert_eq!((agents[0].commands, agents[0].saved_tokens), (2, 380));
+        assert_eq!(agents[0].savings_pct, 95.0);
+
+        let models = tracker.get_breakdown(Breakdown::Model).unwrap();
+        let names: Vec<Option<&str>> = models.iter().map(|m| m.name.as_deref()).collect();
+        assert_eq!(names, vec![Some("sonnet"), Some("opus"), None]);
+    }
+
+    #[test]
+    fn test_test_runs_newest_first_per_suite() {
+        let tracker = test_tracker();
+        let fails = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        tracker
+            .record_test_run(
+                "p",
+                "suite-a",
+                Some("abc"),
+                Some("abc"),
+                &fails(&["t1", "t2"]),
+            )
+            .unwrap();
+        tracker
+            .record_test_run("p", "suite-a", Some("abc"), Some("abc+1f"), &fails(&["t2"]))
+            .unwrap();
+        tracker
+            .record_test_run("p", "suite-b", None, None, &fails(&["x"]))
+            .unwrap();
+        tracker
+            .record_test_run("q", "suite-c", None, None, &fails(&["y"]))
+            .unwrap();
+
+        let runs = tracker.get_test_runs("suite-a", 10).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].failures, vec!["t2"]);
+        assert_eq!(runs[0].code_state.as_deref(), Some("abc+1f"));
+        assert_eq!(runs[1].failures, vec!["t1", "t2"]);
+        assert_eq!(tracker.get_test_runs("suite-a", 1).unwrap().len(), 1);
+
+        let project = tracker.get_project_test_runs("p", 10).unwrap();
+        let suites: Vec<&str> = project.iter().map(|r| r.suite.as_str()).collect();
+        assert_eq!(suites, vec!["suite-b", "suite-a", "suite-a"]);
+    }
 }
@@ -6,7 +6,9 @@
 //! Token optimization: automatically excludes noise directories via -I pattern
 //! unless -a flag is present (respecting user intent).
 
+use crate::exit_code;
 use crate::tracking;
+use crate::utils::which;
 use anyhow::{Context, Result};
 use std::process::Command;
 
@@ -44,8 +46,7 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
     let timer = tracking::TimedExecution::start();
 
     // Check if tree is installed
-    let tree_check = Command::new("which").arg("tree").output();
-    if tree_check.is_err() || !tree_check.unwrap().status.success() {
+    if which("tree").is_none() {
         anyhow::bail!(
             "tree command not found. Install it first:\n\
              - macOS: brew install tree\n\
@@ -77,10 +78,11 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         eprint!("{}", stderr);
-        std::process::exit(output.status.code().unwrap_or(1));
+        exit_code::exit_with(output.status);
     }
 
     let raw = String::from_utf8_lossy(&output.stdout).to_string();
+    crate::crash::raw_output(&raw);
     let filtered = filter_tree_output(&raw);
 
     if verbose > 0 {
@@ -97,7 +99,9 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
     }
 
     print!("{}", filtered);
-    timer.track("tree", "rtk tree", &raw, &filtered);
+    timer
+        .with_exit_code(output.status)
+        .track("tree", "rtk tree", &raw, &filtered);
 
     Ok(())
 }
@@ -1,5 +1,6 @@
+use crate::exit_code;
 use crate::tracking;
-use crate::utils::truncate;
+use crate::utils::{truncate, which};
 use anyhow::{Context, Result};
 use regex::Regex;
 use std::collections::HashMap;
@@ -9,11 +10,7 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
     let timer = tracking::TimedExecution::start();
 
     // Try tsc directly first, fallback to npx if not found
-    let tsc_exists = Command::new("which")
-        .arg("tsc")
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false);
+    let tsc_exists = which("tsc").is_some();
 
     let mut cmd = if tsc_exists {
         Command::new("tsc")
@@ -38,12 +35,13 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
     let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
 
     let filtered = filter_tsc_output(&raw);
 
     println!("{}", filtered);
 
-    timer.track(
+    timer.with_exit_code(output.status).track(
         &format!("tsc {}", args.join(" ")),
         &format!("rtk tsc {}", args.join(" ")),
         &raw,
@@ -51,11 +49,11 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
     );
 
     // Preserve tsc exit code for CI/CD compatibility
-    std::process::exit(output.status.code().unwrap_or(1));
+    exit_code::exit_with(output.status);
 }
 
 /// Filter TypeScript compiler output - group errors by file, show every error
-fn filter_tsc_output(output: &str) -> String {
+pub(crate) fn filter_tsc_output(output: &str) -> String {
     lazy_static::lazy_static! {
         // Pattern: src/file.ts(12,5): error TS2322: Type 'string' is not assignable to type 'number'.
         static ref TSC_ERROR: Regex = Regex::new(
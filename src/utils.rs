@@ -2,35 +2,107 @@
 //!
 //! Provides common helpers used across rtk commands:
 //! - ANSI color code stripping
-//! - Text truncation
+//! - Text truncation and padding by display width (CJK, emoji, combining marks)
 //! - Command execution with error context
+//! - Portable PATH lookup, path separators and line endings (Windows)
 
 use anyhow::{Context, Result};
 use regex::Regex;
+use std::path::PathBuf;
 use std::process::Command;
 
-/// Tronque une chaîne à `max_len` caractères avec "..." si nécessaire.
+/// Tronque une chaîne à `max_len` colonnes avec "..." si nécessaire.
+///
+/// La largeur est celle affichée dans un terminal (CJK et emoji comptent
+/// double) et la coupe tombe entre deux graphèmes, jamais au milieu d'un
+/// caractère ou d'un accent combiné.
 ///
 /// # Arguments
 /// * `s` - La chaîne à tronquer
-/// * `max_len` - Longueur maximale avant troncature (minimum 3 pour inclure "...")
+/// * `max_len` - Largeur maximale avant troncature (minimum 3 pour inclure "...")
 ///
 /// # Examples
 /// ```
 /// use rtk::utils::truncate;
 /// assert_eq!(truncate("hello world", 8), "hello...");
 /// assert_eq!(truncate("hi", 10), "hi");
+/// assert_eq!(truncate("日本語のコマンド", 9), "日本語...");
 /// ```
 pub fn truncate(s: &str, max_len: usize) -> String {
-    let char_count = s.chars().count();
-    if char_count <= max_len {
+    if display_width(s) <= max_len {
         s.to_string()
     } else if max_len < 3 {
         // If max_len is too small, just return "..."
         "...".to_string()
     } else {
-        format!("{}...", s.chars().take(max_len - 3).collect::<String>())
+        format!("{}...", take_width(s, max_len - 3))
+    }
+}
+
+/// Largeur d'une chaîne en colonnes de terminal.
+///
+/// # Examples
+/// ```
+/// use rtk::utils::display_width;
+/// assert_eq!(display_width("git"), 3);
+/// assert_eq!(display_width("構築"), 4);
+/// ```
+pub fn display_width(s: &str) -> usize {
+    use unicode_width::UnicodeWidthStr;
+    s.width()
+}
+
+/// Plus long préfixe de graphèmes entiers tenant dans `width` colonnes.
+///
+/// # Examples
+/// ```
+/// use rtk::utils::take_width;
+/// assert_eq!(take_width("café", 3), "caf");
+/// assert_eq!(take_width("日本", 3), "日");
+/// ```
+pub fn take_width(s: &str, width: usize) -> &str {
+    use unicode_segmentation::UnicodeSegmentation;
+    let mut used = 0;
+    for (i, grapheme) in s.grapheme_indices(true) {
+        used += display_width(grapheme);
+        if used > width {
+            return &s[..i];
+        }
+    }
+    s
+}
+
+/// Plus long suffixe de graphèmes entiers tenant dans `width` colonnes.
+///
+/// # Examples
+/// ```
+/// use rtk::utils::take_last_width;
+/// assert_eq!(take_last_width("src/main.rs", 7), "main.rs");
+/// ```
+pub fn take_last_width(s: &str, width: usize) -> &str {
+    use unicode_segmentation::UnicodeSegmentation;
+    let mut used = 0;
+    for (i, grapheme) in s.grapheme_indices(true).rev() {
+        used += display_width(grapheme);
+        if used > width {
+            return &s[i + grapheme.len()..];
+        }
     }
+    s
+}
+
+/// Complète `s` avec des espaces jusqu'à `width` colonnes, pour aligner les
+/// tableaux (`{:<20}` compte les caractères, pas les colonnes).
+///
+/// # Examples
+/// ```
+/// use rtk::utils::pad;
+/// assert_eq!(pad("ls", 4), "ls  ");
+/// assert_eq!(pad("日本", 6), "日本  ");
+/// ```
+pub fn pad(s: &str, width: usize) -> String {
+    let fill = width.saturating_sub(display_width(s));
+    format!("{}{}", s, " ".repeat(fill))
 }
 
 /// Supprime les codes ANSI d'une chaîne (couleurs, styles).
@@ -46,7 +118,8 @@ pub fn truncate(s: &str, max_len: usize) -> String {
 /// ```
 pub fn strip_ansi(text: &str) -> String {
     lazy_static::lazy_static! {
-        static ref ANSI_RE: Regex = Regex::new(r"\x1b\[[0-9;]*[a-zA-Z]").unwrap();
+        // CSI sequences, plus charset selection (`ESC ( B`) emitted by terminfo resets
+        static ref ANSI_RE: Regex = Regex::new(r"\x1b\[[0-9;]*[a-zA-Z]|\x1b[()][0-9A-Za-z]").unwrap();
     }
     ANSI_RE.replace_all(text, "").to_string()
 }
@@ -192,16 +265,74 @@ pub fn detect_package_manager() -> &'static str {
     }
 }
 
+/// Cherche un exécutable dans le PATH, sans dépendre de `which`.
+///
+/// Sous Windows, les extensions de `PATHEXT` (`.exe`, `.cmd`, ...) sont essayées.
+///
+/// # Examples
+/// ```no_run
+/// use rtk::utils::which;
+/// if which("rg").is_some() { /* ripgrep installé */ }
+/// ```
+pub fn which(tool: &str) -> Option<PathBuf> {
+    which_all(tool).into_iter().next()
+}
+
+/// Toutes les occurrences d'un exécutable dans le PATH, dans l'ordre de résolution.
+pub fn which_all(tool: &str) -> Vec<PathBuf> {
+    let Some(path) = std::env::var_os("PATH") else {
+        return Vec::new();
+    };
+    let exts: Vec<String> = if cfg!(windows) {
+        let pathext = std::env::var("PATHEXT").unwrap_or_else(|_| ".EXE;.CMD;.BAT".into());
+        std::iter::once(String::new())
+            .chain(pathext.split(';').map(str::to_lowercase))
+            .collect()
+    } else {
+        vec![String::new()]
+    };
+    std::env::split_paths(&path)
+        .flat_map(|dir| {
+            exts.iter()
+                .map(move |ext| dir.join(format!("{}{}", tool, ext)))
+        })
+        .filter(|candidate| candidate.is_file())
+        .collect()
+}
+
+/// Normalise les séparateurs de chemin en `/` pour un affichage identique
+/// sur toutes les plateformes.
+///
+/// # Examples
+/// ```
+/// use rtk::utils::slash_path;
+/// assert_eq!(slash_path(r"src\cmds\git.rs"), "src/cmds/git.rs");
+/// ```
+pub fn slash_path(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+/// Fins de ligne utilisées par un texte : `"LF"`, `"CRLF"` ou `"mixed"`.
+///
+/// # Examples
+/// ```
+/// use rtk::utils::line_endings;
+/// assert_eq!(line_endings("a\r\nb\r\n"), "CRLF");
+/// ```
+pub fn line_endings(text: &str) -> &'static str {
+    let crlf = text.matches("\r\n").count();
+    let lf = text.matches('\n').count();
+    match (crlf, lf) {
+        (0, _) => "LF",
+        (c, l) if c == l => "CRLF",
+        _ => "mixed",
+    }
+}
+
 /// Build a Command using the detected package manager's exec mechanism.
 /// Returns a Command ready to have tool-specific args appended.
 pub fn package_manager_exec(tool: &str) -> Command {
-    let tool_exists = Command::new("which")
-        .arg(tool)
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false);
-
-    if tool_exists {
+    if which(tool).is_some() {
         Command::new(tool)
     } else {
         let pm = detect_package_manager();
@@ -229,6 +360,26 @@ pub fn package_manager_exec(tool: &str) -> Command {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_slash_path_and_line_endings() {
+        assert_eq!(
+            slash_path(r"C:\Users\dev\src\main.rs"),
+            "C:/Users/dev/src/main.rs"
+        );
+        assert_eq!(slash_path("src/main.rs"), "src/main.rs");
+        assert_eq!(line_endings("a\nb\n"), "LF");
+        assert_eq!(line_endings("a\r\nb"), "CRLF");
+        assert_eq!(line_endings("a\r\nb\n"), "mixed");
+        assert_eq!(line_endings(""), "LF");
+    }
+
+    #[test]
+    fn test_which_finds_shell() {
+        let shell = if cfg!(windows) { "cmd" } else { "sh" };
+        assert!(which(shell).is_some());
+        assert!(which("rtk-definitely-missing-binary").is_none());
+    }
+
     #[test]
     fn test_truncate_short_string() {
         assert_eq!(truncate("hello", 10), "hello");
@@ -255,6 +406,55 @@ mod tests {
         assert_eq!(truncate("hello world", 3), "...");
     }
 
+    #[test]
+    fn test_truncate_multibyte() {
+        // Byte slicing here used to panic
+        assert_eq!(truncate("rtk grep «ñandú»", 12), "rtk grep ...");
+        assert_eq!(
+            truncate("e\u{301}e\u{301}e\u{301}e\u{301}", 4),
+            "e\u{301}e\u{301}e\u{301}e\u{301}"
+        );
+        assert_eq!(
+            truncate("e\u{301}e\u{301}e\u{301}e\u{301}x", 4),
+            "e\u{301}..."
+        );
+        assert_eq!(truncate("🦀🦀🦀🦀", 7), "🦀🦀...");
+        assert_eq!(pad(&truncate("構築コマンド実行", 10), 12), "構築コ...   ");
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_truncate_fits_width(s in "\\PC{0,40}", max in 3usize..30) {
+            let out = truncate(&s, max);
+            proptest::prop_assert!(display_width(&out) <= max);
+            if display_width(&s) <= max {
+                proptest::prop_assert_eq!(&out, &s);
+            } else {
+                let kept = out.strip_suffix("...").unwrap();
+                proptest::prop_assert!(s.starts_with(kept));
+            }
+        }
+
+        #[test]
+        fn prop_take_width_splits_on_graphemes(s in "\\PC{0,40}", width in 0usize..30) {
+            use unicode_segmentation::UnicodeSegmentation;
+            let head = take_width(&s, width);
+            let tail = take_last_width(&s, width);
+            proptest::prop_assert!(display_width(head) <= width);
+            proptest::prop_assert!(display_width(tail) <= width);
+            let graphemes: Vec<&str> = s.graphemes(true).collect();
+            let head_graphemes = head.graphemes(true).count();
+            proptest::prop_assert_eq!(graphemes[..head_graphemes].concat(), head);
+            proptest::prop_assert!(s.ends_with(tail));
+        }
+
+        #[test]
+        fn prop_pad_reaches_width(s in "\\PC{0,20}", width in 0usize..40) {
+            let padded = pad(&s, width);
+            proptest::prop_assert_eq!(display_width(&padded), display_width(&s).max(width));
+        }
+    }
+
     #[test]
     fn test_strip_ansi_simple() {
         let input = "\x1b[31mError\x1b[0m";
@@ -278,6 +478,12 @@ mod tests {
         assert_eq!(strip_ansi(input), "Green normal Red");
     }
 
+    #[test]
+    fn test_strip_ansi_charset_reset() {
+        let input = "\x1b[31m-old\n\x1b(B\x1b[m\x1b[32m+new";
+        assert_eq!(strip_ansi(input), "-old\n+new");
+    }
+
     #[test]
     fn test_execute_command_success() {
         let result = execute_command("echo", &["test"]);
@@ -2,6 +2,7 @@ use anyhow::{Context, Result};
 use regex::Regex;
 use serde::Deserialize;
 
+use crate::exit_code;
 use crate::parser::{
     emit_degradation_warning, emit_passthrough_warning, extract_json_object, truncate_output,
     FormatMode, OutputParser, ParseResult, TestFailure, TestResult, TokenFormatter,
@@ -262,10 +263,12 @@ fn run_vitest(args: &[String], verbose: u8) -> Result<()> {
 
     println!("{}", filtered);
 
-    timer.track("vitest run", "rtk vitest run", &combined, &filtered);
+    timer
+        .with_exit_code(output.status)
+        .track("vitest run", "rtk vitest run", &combined, &filtered);
 
     // Propagate original exit code
-    std::process::exit(output.status.code().unwrap_or(1))
+    exit_code::exit_with(output.status)
 }
 
 #[cfg(test)]
@@ -0,0 +1,439 @@
+//! `rtk trivy` / `rtk grype`: vulnerability scans grouped by severity and
+//! package, with fix availability and capped descriptions.
+//!
+//! `--fail-on <severity>` (anywhere in the arguments) turns the summary
+//! into a CI gate: exit 1 when any vulnerability is at or above it.
+
+use crate::exit_code;
+use crate::tracking;
+use crate::utils::truncate;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::process::Command;
+
+/// Packages listed per severity before the rest are counted.
+const MAX_PACKAGES: usize = 10;
+/// CVE ids listed per package.
+const MAX_IDS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scanner {
+    Trivy,
+    Grype,
+}
+
+impl Scanner {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Trivy => "trivy",
+            Self::Grype => "grype",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Severity {
+    Critical,
+    High,
+    Medium,
+    Low,
+    Unknown,
+}
+
+impl Severity {
+    fn parse(s: &str) -> Self {
+        match s.to_ascii_lowercase().as_str() {
+            "critical" => Self::Critical,
+            "high" => Self::High,
+            "medium" | "moderate" => Self::Medium,
+            "low" | "negligible" => Self::Low,
+            _ => Self::Unknown,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Critical => "CRITICAL",
+            Self::High => "HIGH",
+            Self::Medium => "MEDIUM",
+            Self::Low => "LOW",
+            Self::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+impl std::str::FromStr for Severity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match Self::parse(s) {
+            Self::Unknown if !s.eq_ignore_ascii_case("unknown") => anyhow::bail!(
+                "Unknown severity '{}': expected critical, high, medium, low or unknown",
+                s
+            ),
+            severity => Ok(severity),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Vuln {
+    id: String,
+    package: String,
+    installed: String,
+    /// Fixed version(s), empty when no fix is released
+    fixed: String,
+    severity: Severity,
+    title: String,
+}
+
+fn str_field(v: &Value, key: &str) -> String {
+    v[key].as_str().unwrap_or("").trim().to_string()
+}
+
+/// Vulnerabilities and scanned target count from `trivy --format json`.
+fn parse_trivy(json: &str) -> Result<(Vec<Vuln>, usize)> {
+    let report: Value = serde_json::from_str(json).context("Failed to parse trivy JSON")?;
+    let results = report["Results"].as_array().cloned().unwrap_or_default();
+    let vulns = results
+        .iter()
+        .flat_map(|r| r["Vulnerabilities"].as_array().cloned().unwrap_or_default())
+        .map(|v| {
+            let title = match str_field(&v, "Title") {
+                t if t.is_empty() => str_field(&v, "Description"),
+                t => t,
+            };
+            Vuln {
+                id: str_field(&v, "VulnerabilityID"),
+                package: str_field(&v, "PkgName"),
+                installed: str_field(&v, "InstalledVersion"),
+                fixed: str_field(&v, "FixedVersion"),
+                severity: Severity::parse(v["Severity"].as_str().unwrap_or("")),
+                title,
+            }
+        })
+        .collect();
+    Ok((vulns, results.len()))
+}
+
+/// Vulnerabilities from `grype -o json`.
+fn parse_grype(json: &str) -> Result<(Vec<Vuln>, usize)> {
+    let report: Value = serde_json::from_str(json).context("Failed to parse grype JSON")?;
+    let vulns = report["matches"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default()
+        .iter()
+        .map(|m| {
+            let vuln = &m["vulnerability"];
+            let fixed: Vec<&str> = vuln["fix"]["versions"]
+                .as_array()
+                .map(|v| v.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            Vuln {
+                id: str_field(vuln, "id"),
+                package: str_field(&m["artifact"], "name"),
+                installed: str_field(&m["artifact"], "version"),
+                fixed: fixed.join(", "),
+                severity: Severity::parse(vuln["severity"].as_str().unwrap_or("")),
+                title: str_field(vuln, "description"),
+            }
+        })
+        .collect();
+    Ok((vulns, 1))
+}
+
+fn format_report(scanner: Scanner, vulns: &[Vuln], targets: usize) -> String {
+    if vulns.is_empty() {
+        return format!("✓ {}: no vulnerabilities", scanner.name());
+    }
+
+    // severity -> (package, installed) -> vulnerabilities
+    let mut grouped: BTreeMap<Severity, BTreeMap<(&str, &str), Vec<&Vuln>>> = BTreeMap::new();
+    for vuln in vulns {
+        let by_package = grouped.entry(vuln.severity).or_default();
+        let list = by_package
+            .entry((&vuln.package, &vuln.installed))
+            .or_default();
+        // The same CVE is reported once per target (layer, lockfile)
+        if !list.iter().any(|v| v.id == vuln.id) {
+            list.push(vuln);
+        }
+    }
+
+    let unique: usize = grouped
+        .values()
+        .flat_map(|p| p.values())
+        .map(Vec::len)
+        .sum();
+    let packages: BTreeSet<(&str, &str)> =
+        grouped.values().flat_map(|p| p.keys()).copied().collect();
+    let fixable = grouped
+        .values()
+        .flat_map(|p| p.values().flatten())
+        .filter(|v| !v.fixed.is_empty())
+        .count();
+    let counts: Vec<String> = grouped
+        .iter()
+        .map(|(severity, by_package)| {
+            let n: usize = by_package.values().map(Vec::len).sum();
+            format!("{} {}", n, severity.label().to_lowercase())
+        })
+        .collect();
+
+    let mut out = vec![format!(
+        "🛡️ {}: {} vulnerabilities ({}) in {} packages{}, {} fixable",
+        scanner.name(),
+        unique,
+        counts.join(", "),
+        packages.len(),
+        if targets > 1 {
+            format!(" across {} targets", targets)
+        } else {
+            String::new()
+        },
+        fixable
+    )];
+
+    for (severity, by_package) in &grouped {
+        let n: usize = by_package.values().map(Vec::len).sum();
+        out.push(String::new());
+        out.push(format!("{} ({})", severity.label(), n));
+
+        // Most vulnerable packages first
+        let mut list: Vec<_> = by_package.iter().collect();
+        list.sort_by_key(|((name, _), vulns)| (usize::MAX - vulns.len(), *name));
+        for ((name, installed), vulns) in list.iter().take(MAX_PACKAGES) {
+            let fix = match vulns.iter().find(|v| !v.fixed.is_empty()) {
+                Some(v) => format!(" → {}", truncate(&v.fixed, 30)),
+                None => " (no fix)".to_string(),
+            };
+            let ids: Vec<&str> = vulns.iter().take(MAX_IDS).map(|v| v.id.as_str()).collect();
+            let more = vulns.len().saturating_sub(MAX_IDS);
+            let more = if more > 0 {
+                format!(" +{}", more)
+            } else {
+                String::new()
+            };
+            let title = vulns[0].title.lines().next().unwrap_or("");
+            out.push(format!(
+                "  {} {}{}  {}{}  {}",
+                name,
+                installed,
+                fix,
+                ids.join(", "),
+                more,
+                truncate(title, 70)
+            ));
+        }
+        if list.len() > MAX_PACKAGES {
+            out.push(format!("  +{} more packages", list.len() - MAX_PACKAGES));
+        }
+    }
+    out.join("\n")
+}
+
+/// Split rtk's `--fail-on <severity>` out of the scanner arguments.
+fn take_fail_on(args: &[String]) -> Result<(Option<Severity>, Vec<String>)> {
+    let mut fail_on = None;
+    let mut rest = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = arg.strip_prefix("--fail-on=") {
+            fail_on = Some(value.parse()?);
+        } else if arg == "--fail-on" {
+            let value = iter.next().context("--fail-on requires a severity")?;
+            fail_on = Some(value.parse()?);
+        } else {
+            rest.push(arg.clone());
+        }
+    }
+    Ok((fail_on, rest))
+}
+
+/// Vulnerabilities at or above `threshold`, counted once per package and id.
+fn count_at_or_above(vulns: &[Vuln], threshold: Severity) -> usize {
+    let unique: BTreeSet<(&str, &str, &str)> = vulns
+        .iter()
+        .filter(|v| v.severity <= threshold)
+        .map(|v| (v.package.as_str(), v.installed.as_str(), v.id.as_str()))
+        .collect();
+    unique.len()
+}
+
+pub fn run(scanner: Scanner, args: &[String], verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let (fail_on, args) = take_fail_on(args)?;
+
+    let mut cmd = Command::new(scanner.name());
+    // Force JSON output
+    match scanner {
+        Scanner::Trivy => {
+            cmd.args(&args);
+            if !args
+                .iter()
+                .any(|a| a == "-f" || a == "--format" || a.starts_with("--format="))
+            {
+                cmd.args(["--format", "json"]);
+            }
+        }
+        Scanner::Grype => {
+            cmd.args(&args);
+            if !args
+                .iter()
+                .any(|a| a == "-o" || a == "--output" || a.starts_with("--output="))
+            {
+                cmd.args(["-o", "json"]);
+            }
+        }
+    }
+
+    if verbose > 0 {
+        eprintln!(
+            "Running: {} {} (JSON output)",
+            scanner.name(),
+            args.join(" ")
+        );
+    }
+
+    let output = cmd.output().with_context(|| {
+        format!(
+            "Failed to run {}. Is it installed? Try: brew install {}",
+            scanner.name(),
+            scanner.name()
+        )
+    })?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let raw = format!("{}\n{}", stdout, stderr);
+    crate::crash::raw_output(&raw);
+
+    let parsed = match scanner {
+        Scanner::Trivy => parse_trivy(&stdout),
+        Scanner::Grype => parse_grype(&stdout),
+    };
+    let mut exit_code = exit_code::from_status(output.status);
+    let filtered = match parsed {
+        Ok((vulns, targets)) if output.status.success() || !vulns.is_empty() => {
+            let mut report = format_report(scanner, &vulns, targets);
+            if let Some(threshold) = fail_on {
+                let failing = count_at_or_above(&vulns, threshold);
+                if failing > 0 {
+                    report.push_str(&format!(
+                        "\n\n✗ --fail-on {}: {} vulnerabilities at or above {}",
+                        threshold.label().to_lowercase(),
+                        failing,
+                        threshold.label()
+                    ));
+                    exit_code = 1;
+                } else {
+                    report.push_str(&format!(
+                        "\n\n✓ --fail-on {}: passed",
+                        threshold.label().to_lowercase()
+                    ));
+                }
+            }
+            report
+        }
+        // Scanner errors (bad image name, DB download) only go to stderr
+        _ => truncate(
+            if stderr.trim().is_empty() {
+                stdout.trim()
+            } else {
+                stderr.trim()
+            },
+            2000,
+        ),
+    };
+    println!("{}", filtered);
+
+    timer.with_code(exit_code).track(
+        &format!("{} {}", scanner.name(), args.join(" ")),
+        &format!("rtk {} {}", scanner.name(), args.join(" ")),
+        &raw,
+        &filtered,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRIVY: &str = r#"{
+      "Results": [
+        {"Target": "alpine:3.15 (alpine 3.15.4)", "Vulnerabilities": [
+          {"VulnerabilityID": "CVE-2022-3602", "PkgName": "libssl3", "InstalledVersion": "3.0.2-r0",
+           "FixedVersion": "3.0.7-r0", "Severity": "CRITICAL", "Title": "X.400 address type confusion in X.509 GeneralName"},
+          {"VulnerabilityID": "CVE-2022-3786", "PkgName": "libssl3", "InstalledVersion": "3.0.2-r0",
+           "FixedVersion": "3.0.7-r0", "Severity": "HIGH", "Title": "Buffer overflow"},
+          {"VulnerabilityID": "CVE-2023-0001", "PkgName": "busybox", "InstalledVersion": "1.35.0-r13",
+           "Severity": "LOW", "Description": "A very long description\nspanning lines"}
+        ]},
+        {"Target": "app/package-lock.json", "Vulnerabilities": [
+          {"VulnerabilityID": "CVE-2022-3602", "PkgName": "libssl3", "InstalledVersion": "3.0.2-r0",
+           "FixedVersion": "3.0.7-r0", "Severity": "CRITICAL", "Title": "dup"}
+        ]},
+        {"Target": "clean layer"}
+      ]
+    }"#;
+
+    #[test]
+    fn test_trivy_report_groups_by_severity_and_package() {
+        let (vulns, targets) = parse_trivy(TRIVY).unwrap();
+        assert_eq!((vulns.len(), targets), (4, 3));
+        let report = format_report(Scanner::Trivy, &vulns, targets);
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(
+            lines[0],
+            "🛡️ trivy: 3 vulnerabilities (1 critical, 1 high, 1 low) in 2 packages across 3 targets, 2 fixable"
+        );
+        assert_eq!(lines[2], "CRITICAL (1)");
+        assert_eq!(
+            lines[3],
+            "  libssl3 3.0.2-r0 → 3.0.7-r0  CVE-2022-3602  X.400 address type confusion in X.509 GeneralName"
+        );
+        assert_eq!(
+            lines.last(),
+            Some(&"  busybox 1.35.0-r13 (no fix)  CVE-2023-0001  A very long description")
+        );
+        assert_eq!(
+            format_report(Scanner::Grype, &[], 1),
+            "✓ grype: no vulnerabilities"
+        );
+    }
+
+    #[test]
+    fn test_parse_grype() {
+        let json = r#"{"matches": [
+          {"vulnerability": {"id": "GHSA-xxxx", "severity": "Medium", "description": "Prototype pollution",
+                             "fix": {"versions": ["4.17.21"], "state": "fixed"}},
+           "artifact": {"name": "lodash", "version": "4.17.15", "type": "npm"}},
+          {"vulnerability": {"id": "CVE-2021-1", "severity": "Negligible", "fix": {"versions": [], "state": "not-fixed"}},
+           "artifact": {"name": "zlib", "version": "1.2.11"}}
+        ]}"#;
+        let (vulns, _) = parse_grype(json).unwrap();
+        assert_eq!(vulns[0].severity, Severity::Medium);
+        assert_eq!(vulns[0].fixed, "4.17.21");
+        assert_eq!(vulns[1].severity, Severity::Low);
+        assert!(vulns[1].fixed.is_empty());
+    }
+
+    #[test]
+    fn test_fail_on_gate() {
+        let args: Vec<String> = ["image", "--fail-on", "high", "alpine:3.15"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (fail_on, rest) = take_fail_on(&args).unwrap();
+        assert_eq!(fail_on, Some(Severity::High));
+        assert_eq!(rest, vec!["image", "alpine:3.15"]);
+        assert!(take_fail_on(&["--fail-on=severe".to_string()]).is_err());
+
+        let (vulns, _) = parse_trivy(TRIVY).unwrap();
+        assert_eq!(count_at_or_above(&vulns, Severity::High), 2);
+        assert_eq!(count_at_or_above(&vulns, Severity::Critical), 1);
+        assert_eq!(count_at_or_above(&vulns, Severity::Unknown), 3);
+    }
+}
@@ -0,0 +1,266 @@
+//! Shared gitignore-aware tree walk, cached across runs.
+//!
+//! `rtk ls --tree`, `find`, `todo` and `secrets` list a tree the same way:
+//! `.gitignore` (local, global and `.git/info/exclude`), `.ignore` and
+//! `.rtkignore` apply and `.git` is never entered. Agents run several of them
+//! on one repository in a row, so the listing is cached (see cache.rs) along
+//! with the mtime of every directory visited and of every ignore file that
+//! applies. Creating, removing or renaming a file changes its directory's
+//! mtime and editing an ignore file changes its own, so a cached listing is
+//! reused only while the tree's shape is unchanged; checking that costs one
+//! `stat` per directory instead of reading each one. Path rules and file
+//! metadata are left to callers.
+
+use crate::cache;
+use crate::path_rules::RTKIGNORE_FILE;
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Files that change what is ignored in the directory holding them.
+const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore", RTKIGNORE_FILE];
+/// Listings depending on anything modified this recently are not cached: a
+/// change in the same filesystem clock tick would leave the mtime as it was.
+const RACY_NS: u128 = 1_000_000_000;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    /// Include hidden files and directories
+    pub hidden: bool,
+    /// Directory names not descended into, besides `.git`
+    pub prune: &'static [&'static str],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Kind {
+    Dir,
+    File,
+    /// Symlinks and special files
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// `root` joined with the path below it, as `ignore` yields it
+    pub path: PathBuf,
+    pub kind: Kind,
+}
+
+impl Entry {
+    pub fn is_dir(&self) -> bool {
+        self.kind == Kind::Dir
+    }
+
+    pub fn is_file(&self) -> bool {
+        self.kind == Kind::File
+    }
+
+    /// Metadata of the entry itself; symlinks are not followed.
+    pub fn metadata(&self) -> std::io::Result<std::fs::Metadata> {
+        std::fs::symlink_metadata(&self.path)
+    }
+}
+
+/// A walk as cached: what it found and what it depended on.
+#[derive(Debug, Serialize, Deserialize)]
+struct Listing {
+    /// (path, mtime in ns) of every directory and ignore file consulted
+    stamps: Vec<(PathBuf, u128)>,
+    /// Paths below the root in walk order; the root itself is ""
+    entries: Vec<(PathBuf, Kind)>,
+}
+
+/// Everything under `root`, `root` itself first, reusing the last walk with
+/// the same options while the tree is unchanged.
+pub fn walk(root: &Path, opts: Options) -> Vec<Entry> {
+    let key = cache_key(root, opts);
+    if let Some(listing) = key.as_deref().and_then(cache::load::<Listing>) {
+        if is_fresh(&listing.stamps) {
+            return into_entries(root, listing.entries);
+        }
+    }
+    let started = mtime_ns(SystemTime::now()).unwrap_or(0);
+    let listing = walk_uncached(root, opts);
+    let settled = listing
+        .stamps
+        .iter()
+        .all(|(_, mtime)| mtime + RACY_NS < started);
+    if let Some(key) = key.filter(|_| settled) {
+        cache::store(&key, &listing);
+    }
+    into_entries(root, listing.entries)
+}
+
+fn cache_key(root: &Path, opts: Options) -> Option<String> {
+    let canonical = root.canonicalize().ok()?;
+    Some(cache::key(&[
+        "walk",
+        &canonical.to_string_lossy(),
+        &root.to_string_lossy(),
+        &opts.hidden.to_string(),
+        &opts.prune.join("/"),
+    ]))
+}
+
+fn walk_uncached(root: &Path, opts: Options) -> Listing {
+    let canonical = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let mut stamps = ancestor_stamps(&canonical);
+    let prune = opts.prune;
+    let walker = WalkBuilder::new(root)
+        .hidden(!opts.hidden)
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true)
+        .add_custom_ignore_filename(RTKIGNORE_FILE)
+        .filter_entry(move |e| {
+            e.depth() == 0
+                || (e.file_name() != ".git" && !prune.iter().any(|n| e.file_name() == *n))
+        })
+        .build();
+
+    let mut entries = Vec::new();
+    for entry in walker.flatten() {
+        let Ok(rel) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        let kind = match entry.file_type() {
+            Some(t) if t.is_dir() => Kind::Dir,
+            Some(t) if t.is_file() => Kind::File,
+            _ => Kind::Other,
+        };
+        // Stamped before `ignore` reads it, so a change during the walk shows
+        if kind == Kind::Dir {
+            stamp_dir(&canonical.join(rel), &mut stamps);
+        }
+        entries.push((rel.to_path_buf(), kind));
+    }
+    Listing { stamps, entries }
+}
+
+/// Ignore files above `root` apply too, up to the repository root.
+fn ancestor_stamps(root: &Path) -> Vec<(PathBuf, u128)> {
+    let mut stamps = Vec::new();
+    for dir in root.ancestors() {
+        if dir != root {
+            stamp_dir(dir, &mut stamps);
+        }
+        let git = dir.join(".git");
+        if git.exists() {
+            stamp(&git.join("info"), &mut stamps);
+            stamp(&git.join("info").join("exclude"), &mut stamps);
+            break;
+        }
+    }
+    stamps
+}
+
+fn stamp_dir(dir: &Path, stamps: &mut Vec<(PathBuf, u128)>) {
+    stamp(dir, stamps);
+    for name in IGNORE_FILES {
+        stamp(&dir.join(name), stamps);
+    }
+}
+
+/// Record `path`'s mtime if it exists; creating it changes its parent's.
+fn stamp(path: &Path, stamps: &mut Vec<(PathBuf, u128)>) {
+    if let Some(mtime) = modified(path) {
+        stamps.push((path.to_path_buf(), mtime));
+    }
+}
+
+fn is_fresh(stamps: &[(PathBuf, u128)]) -> bool {
+    stamps
+        .iter()
+        .all(|(path, mtime)| modified(path) == Some(*mtime))
+}
+
+fn modified(path: &Path) -> Option<u128> {
+    mtime_ns(std::fs::metadata(path).ok()?.modified().ok()?)
+}
+
+fn mtime_ns(time: SystemTime) -> Option<u128> {
+    Some(time.duration_since(UNIX_EPOCH).ok()?.as_nanos())
+}
+
+fn into_entries(root: &Path, entries: Vec<(PathBuf, Kind)>) -> Vec<Entry> {
+    entries
+        .into_iter()
+        .map(|(rel, kind)| Entry {
+            // `join("")` would add a trailing separator
+            path: if rel.as_os_str().is_empty() {
+                root.to_path_buf()
+            } else {
+                root.join(rel)
+            },
+            kind,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn names(listing: &Listing) -> Vec<String> {
+        let mut names: Vec<String> = listing
+            .entries
+            .iter()
+            .map(|(p, _)| p.to_string_lossy().replace('\\', "/"))
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_walk_honors_ignores_and_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for sub in [".git", "src", "node_modules/pkg"] {
+            std::fs::create_dir_all(root.join(sub)).unwrap();
+        }
+        std::fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        std::fs::write(root.join("src/main.rs"), "").unwrap();
+        std::fs::write(root.join("debug.log"), "").unwrap();
+        std::fs::write(root.join("node_modules/pkg/index.js"), "").unwrap();
+
+        let opts = Options {
+            hidden: false,
+            prune: &["node_modules"],
+        };
+        let listing = walk_uncached(root, opts);
+        assert_eq!(names(&listing), ["", "src", "src/main.rs"]);
+        assert_eq!(listing.entries[0].1, Kind::Dir);
+
+        let all = walk_uncached(root, Options::default());
+        assert!(names(&all).contains(&"node_modules/pkg/index.js".to_string()));
+        assert!(!names(&all).iter().any(|n| n.contains(".git/")));
+
+        let entries = into_entries(root, listing.entries);
+        assert_eq!(entries[0].path, root);
+        assert!(entries.iter().any(|e| e.is_file() && e.path.ends_with("main.rs")));
+    }
+
+    #[test]
+    fn test_stamps_notice_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+
+        let listing = walk_uncached(root, Options::default());
+        assert!(is_fresh(&listing.stamps));
+
+        // Past the filesystem clock's granularity
+        std::thread::sleep(Duration::from_millis(50));
+        std::fs::write(root.join("src/new.rs"), "").unwrap();
+        assert!(!is_fresh(&listing.stamps));
+
+        let listing = walk_uncached(root, Options::default());
+        std::thread::sleep(Duration::from_millis(50));
+        std::fs::write(root.join(".gitignore"), "*.tmp\n").unwrap();
+        assert!(!is_fresh(&listing.stamps));
+    }
+}
@@ -0,0 +1,338 @@
+//! `rtk watch -- <command>`: re-run a wrapped command whenever files change
+//! and print only what changed between consecutive summarized outputs.
+//!
+//! The first run prints the full rtk summary; every later run prints a short
+//! delta ("2 new failures, 1 fixed") instead of the whole output again, which
+//! is what a fix-and-verify loop needs.
+
+use crate::tracking;
+use anyhow::{Context, Result};
+use ignore::gitignore::Gitignore;
+use lazy_static::lazy_static;
+use notify::{EventKind, RecursiveMode, Watcher};
+use regex::Regex;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Failure lines listed per direction before collapsing to a count.
+const MAX_LISTED: usize = 10;
+
+/// Directories whose changes never trigger a re-run.
+const IGNORED_DIRS: &[&str] = &[".git", "target", "node_modules", "__pycache__", ".venv"];
+
+lazy_static! {
+    static ref FAILURE_LINE: Regex =
+        Regex::new(r"(?i)\b(fail(ed|ure|ures|ing)?|errors?|panicked)\b|✗|❌").unwrap();
+    /// Summary counts ("3 failed, 9 passed") change with every fix
+    static ref COUNT_LINE: Regex =
+        Regex::new(r"(?i)\b\d+\s+(failed|failures?|failing|errors?)\b").unwrap();
+    /// Timings differ on every run and would show up as changed lines
+    static ref TIMING: Regex =
+        Regex::new(r"\b\d+(\.\d+)?\s?(ms|s|sec|seconds|µs|us|m)\b").unwrap();
+}
+
+pub fn run(paths: &[PathBuf], debounce_ms: u64, command: &[String], verbose: u8) -> Result<()> {
+    // `rtk watch -- rtk cargo test` and `rtk watch -- cargo test` are the same
+    let command: Vec<String> = match command.first().map(String::as_str) {
+        Some("rtk") => command[1..].to_vec(),
+        _ => command.to_vec(),
+    };
+    if command.is_empty() {
+        anyhow::bail!("Usage: rtk watch [-p PATH]... -- <command>");
+    }
+    let exe = std::env::current_exe().context("Failed to locate the rtk binary")?;
+    let roots: Vec<PathBuf> = if paths.is_empty() {
+        vec![PathBuf::from(".")]
+    } else {
+        paths.to_vec()
+    };
+    let gitignore = Gitignore::new(".gitignore").0;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).context("Failed to start file watcher")?;
+    for root in &roots {
+        watcher
+            .watch(root, RecursiveMode::Recursive)
+            .with_context(|| format!("Failed to watch {}", root.display()))?;
+    }
+
+    let label = format!("rtk {}", command.join(" "));
+    eprintln!(
+        "👀 watching {} — re-running `{}` on change (Ctrl-C to stop)",
+        roots
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        label
+    );
+
+    let (mut previous, mut previous_exit) = run_once(&exe, &command);
+    println!("{}", previous);
+    let mut run = 1;
+
+    while let Ok(event) = rx.recv() {
+        let mut changed: Vec<PathBuf> = Vec::new();
+        collect_changes(event, &gitignore, &mut changed);
+        // Editors write several events per save: wait for the burst to settle
+        while let Ok(event) = rx.recv_timeout(Duration::from_millis(debounce_ms)) {
+            collect_changes(event, &gitignore, &mut changed);
+        }
+        if changed.is_empty() {
+            continue;
+        }
+        changed.sort();
+        changed.dedup();
+        if verbose > 0 {
+            eprintln!("changed: {:?}", changed);
+        }
+
+        let timer = tracking::TimedExecution::start();
+        let (output, exit) = run_once(&exe, &command);
+        run += 1;
+        let delta = Delta::between(&previous, &output);
+        let report = format_report(run, &changed, previous_exit, exit, &delta);
+        println!("{}", report);
+        timer.with_code(exit).track(
+            &label,
+            &format!("rtk watch {}", command.join(" ")),
+            &output,
+            &report,
+        );
+        previous = output;
+        previous_exit = exit;
+    }
+    Ok(())
+}
+
+/// Keep content changes outside ignored directories.
+fn collect_changes(
+    event: notify::Result<notify::Event>,
+    gitignore: &Gitignore,
+    out: &mut Vec<PathBuf>,
+) {
+    let Ok(event) = event else {
+        return;
+    };
+    if matches!(event.kind, EventKind::Access(_) | EventKind::Other) {
+        return;
+    }
+    for path in event.paths {
+        if is_relevant(&path, gitignore) {
+            out.push(display_path(&path));
+        }
+    }
+}
+
+fn is_relevant(path: &Path, gitignore: &Gitignore) -> bool {
+    if path
+        .components()
+        .any(|c| IGNORED_DIRS.iter().any(|d| c.as_os_str() == *d))
+    {
+        return false;
+    }
+    let rel = display_path(path);
+    !gitignore
+        .matched_path_or_any_parents(&rel, path.is_dir())
+        .is_ignore()
+}
+
+/// Path relative to the current directory when possible.
+fn display_path(path: &Path) -> PathBuf {
+    std::env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok().map(Path::to_path_buf))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Run `rtk <command>` and capture its summarized output (stdout + stderr).
+fn run_once(exe: &Path, command: &[String]) -> (String, i32) {
+    let output = Command::new(exe)
+        .args(["--color", "never"])
+        .args(command)
+        .stdin(Stdio::null())
+        .output();
+    match output {
+        Ok(output) => {
+            let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if !stderr.trim().is_empty() {
+                if !text.is_empty() && !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                text.push_str(&stderr);
+            }
+            (
+                text.trim_end().to_string(),
+                output.status.code().unwrap_or(1),
+            )
+        }
+        Err(e) => (format!("failed to run rtk: {}", e), 1),
+    }
+}
+
+/// Line-level difference between two summarized outputs.
+#[derive(Debug, Default, PartialEq)]
+struct Delta {
+    new_failures: Vec<String>,
+    fixed: Vec<String>,
+    /// Other lines that appeared / disappeared
+    added: Vec<String>,
+    removed: Vec<String>,
+}
+
+impl Delta {
+    fn between(previous: &str, current: &str) -> Self {
+        let before: HashSet<String> = previous.lines().map(normalize).collect();
+        let after: HashSet<String> = current.lines().map(normalize).collect();
+        let mut delta = Delta::default();
+        for line in current.lines() {
+            let key = normalize(line);
+            if key.is_empty() || before.contains(&key) {
+                continue;
+            }
+            if is_failure(line) {
+                delta.new_failures.push(line.trim().to_string());
+            } else {
+                delta.added.push(line.trim().to_string());
+            }
+        }
+        for line in previous.lines() {
+            let key = normalize(line);
+            if key.is_empty() || after.contains(&key) {
+                continue;
+            }
+            if is_failure(line) {
+                delta.fixed.push(line.trim().to_string());
+            } else {
+                delta.removed.push(line.trim().to_string());
+            }
+        }
+        delta
+    }
+
+    fn is_empty(&self) -> bool {
+        self.new_failures.is_empty()
+            && self.fixed.is_empty()
+            && self.added.is_empty()
+            && self.removed.is_empty()
+    }
+}
+
+fn is_failure(line: &str) -> bool {
+    FAILURE_LINE.is_match(line) && !COUNT_LINE.is_match(line)
+}
+
+fn normalize(line: &str) -> String {
+    TIMING.replace_all(line.trim(), "<t>").into_owned()
+}
+
+fn format_report(
+    run: usize,
+    changed: &[PathBuf],
+    previous_exit: i32,
+    exit: i32,
+    delta: &Delta,
+) -> String {
+    let mut files: Vec<String> = changed
+        .iter()
+        .take(3)
+        .map(|p| p.display().to_string())
+        .collect();
+    if changed.len() > 3 {
+        files.push(format!("+{} more", changed.len() - 3));
+    }
+    let status = if exit == previous_exit {
+        format!("exit {}", exit)
+    } else {
+        format!("exit {} (was {})", exit, previous_exit)
+    };
+    let mut out = vec![format!("🔁 #{} {} → {}", run, files.join(", "), status)];
+
+    if delta.is_empty() {
+        out.push("no change".to_string());
+        return out.join("\n");
+    }
+    let mut parts = Vec::new();
+    if !delta.new_failures.is_empty() {
+        parts.push(format!("{} new failures", delta.new_failures.len()));
+    }
+    if !delta.fixed.is_empty() {
+        parts.push(format!("{} fixed", delta.fixed.len()));
+    }
+    let other = delta.added.len() + delta.removed.len();
+    if other > 0 {
+        parts.push(format!("{} other lines changed", other));
+    }
+    out.push(parts.join(", "));
+    list(&mut out, "+", &delta.new_failures);
+    list(&mut out, "-", &delta.fixed);
+    // Other changes only matter when no failure moved (e.g. a summary count)
+    if delta.new_failures.is_empty() && delta.fixed.is_empty() {
+        list(&mut out, "+", &delta.added);
+        list(&mut out, "-", &delta.removed);
+    }
+    out.join("\n")
+}
+
+fn list(out: &mut Vec<String>, sign: &str, lines: &[String]) {
+    for line in lines.iter().take(MAX_LISTED) {
+        out.push(format!("  {} {}", sign, line));
+    }
+    if lines.len() > MAX_LISTED {
+        out.push(format!("  {} … {} more", sign, lines.len() - MAX_LISTED));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delta_failures() {
+        let before = "FAILED tests::a\nFAILED tests::b\n2 failed, 10 passed in 1.2s";
+        let after = "FAILED tests::b\nFAILED tests::c\nFAILED tests::d\n3 failed, 9 passed in 0.9s";
+        let delta = Delta::between(before, after);
+        assert_eq!(
+            delta.new_failures,
+            vec!["FAILED tests::c", "FAILED tests::d"]
+        );
+        assert_eq!(delta.fixed, vec!["FAILED tests::a"]);
+        assert_eq!(delta.added, vec!["3 failed, 9 passed in 0.9s"]);
+    }
+
+    #[test]
+    fn test_delta_ignores_timings() {
+        let delta = Delta::between("ok: 12 passed in 1.52s", "ok: 12 passed in 0.98s");
+        assert!(delta.is_empty());
+    }
+
+    #[test]
+    fn test_format_report() {
+        let delta = Delta::between("✗ parser::empty_input\n✓ 41 passed", "✓ 42 passed");
+        let report = format_report(2, &[PathBuf::from("src/parser.rs")], 1, 0, &delta);
+        assert_eq!(
+            report,
+            "🔁 #2 src/parser.rs → exit 0 (was 1)\n\
+             1 fixed, 2 other lines changed\n\
+             \x20 - ✗ parser::empty_input"
+        );
+    }
+
+    #[test]
+    fn test_format_report_no_change() {
+        let report = format_report(3, &[PathBuf::from("a.rs")], 0, 0, &Delta::default());
+        assert_eq!(report, "🔁 #3 a.rs → exit 0\nno change");
+    }
+
+    #[test]
+    fn test_ignored_paths() {
+        let gitignore = Gitignore::empty();
+        assert!(!is_relevant(Path::new("target/debug/rtk"), &gitignore));
+        assert!(!is_relevant(Path::new(".git/index"), &gitignore));
+        assert!(is_relevant(Path::new("src/main.rs"), &gitignore));
+    }
+}
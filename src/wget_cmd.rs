@@ -1,4 +1,5 @@
 use crate::tracking;
+use crate::utils::{display_width, take_last_width, take_width, truncate};
 use anyhow::{Context, Result};
 use std::process::Command;
 
@@ -39,12 +40,22 @@ pub fn run(url: &str, args: &[String], verbose: u8) -> Result<()> {
             format_size(size)
         );
         println!("{}", msg);
-        timer.track(&format!("wget {}", url), "rtk wget", &raw_output, &msg);
+        timer.with_exit_code(output.status).track(
+            &format!("wget {}", url),
+            "rtk wget",
+            &raw_output,
+            &msg,
+        );
     } else {
         let error = parse_error(&stderr, &stdout);
         let msg = format!("⬇️ {} FAILED: {}", compact_url(url), error);
         println!("{}", msg);
-        timer.track(&format!("wget {}", url), "rtk wget", &raw_output, &msg);
+        timer.with_exit_code(output.status).track(
+            &format!("wget {}", url),
+            "rtk wget",
+            &raw_output,
+            &msg,
+        );
     }
 
     Ok(())
@@ -95,7 +106,7 @@ pub fn run_stdout(url: &str, args: &[String], verbose: u8) -> Result<()> {
             }
         }
         print!("{}", rtk_output);
-        timer.track(
+        timer.with_exit_code(output.status).track(
             &format!("wget -O - {}", url),
             "rtk wget -o",
             &raw_output,
@@ -106,7 +117,12 @@ pub fn run_stdout(url: &str, args: &[String], verbose: u8) -> Result<()> {
         let error = parse_error(&stderr, "");
         let msg = format!("⬇️ {} FAILED: {}", compact_url(url), error);
         println!("{}", msg);
-        timer.track(&format!("wget -O - {}", url), "rtk wget -o", &stderr, &msg);
+        timer.with_exit_code(output.status).track(
+            &format!("wget -O - {}", url),
+            "rtk wget -o",
+            &stderr,
+            &msg,
+        );
     }
 
     Ok(())
@@ -196,13 +212,13 @@ fn compact_url(url: &str) -> String {
         .unwrap_or(url);
 
     // Truncate if too long
-    if without_proto.len() <= 50 {
+    if display_width(without_proto) <= 50 {
         without_proto.to_string()
     } else {
         format!(
             "{}...{}",
-            &without_proto[..25],
-            &without_proto[without_proto.len() - 20..]
+            take_width(without_proto, 25),
+            take_last_width(without_proto, 20)
         )
     }
 }
@@ -240,10 +256,7 @@ fn parse_error(stderr: &str, stdout: &str) -> String {
     for line in stderr.lines() {
         let trimmed = line.trim();
         if !trimmed.is_empty() && !trimmed.starts_with("--") {
-            if trimmed.len() > 60 {
-                return format!("{}...", &trimmed[..60]);
-            }
-            return trimmed.to_string();
+            return truncate(trimmed, 63);
         }
     }
 
@@ -251,9 +264,5 @@ fn parse_error(stderr: &str, stdout: &str) -> String {
 }
 
 fn truncate_line(line: &str, max: usize) -> String {
-    if line.len() <= max {
-        line.to_string()
-    } else {
-        format!("{}...", &line[..max - 3])
-    }
+    truncate(line, max)
 }
@@ -0,0 +1,579 @@
+//! `rtk xcodebuild` and `rtk swift build|test`: diagnostics and results
+//! instead of per-file compile logs.
+//!
+//! Keeps errors, warnings deduplicated and grouped by file, failing tests
+//! (from the `.xcresult` bundle when xcodebuild wrote one, else from the
+//! log) and the final status. Compiler invocations, `cd`/`export` lines,
+//! source excerpts under diagnostics and notes are dropped.
+
+use crate::exit_code;
+use crate::tracking;
+use crate::utils::{strip_ansi, truncate};
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+/// Warnings listed per file.
+const MAX_WARNINGS_PER_FILE: usize = 3;
+/// Failed tests listed.
+const MAX_TESTS: usize = 20;
+
+lazy_static! {
+    // `/path/File.swift:12:5: error: message`
+    static ref DIAGNOSTIC: Regex =
+        Regex::new(r"^(\S.*?):(\d+)(?::(\d+))?: (error|warning|fatal error): (.*)$").unwrap();
+    // `ld: warning: ...`, `clang: error: ...`, `error: ...`
+    static ref TOOL_DIAGNOSTIC: Regex =
+        Regex::new(r"^(?:(ld|clang|swift-\w+|xcodebuild): )?(error|warning): (.*)$").unwrap();
+    // XCTest: `Test Case '-[Mod.FooTests testBar]' failed (0.003 seconds).`
+    static ref XCTEST_FAILED: Regex =
+        Regex::new(r"^Test Case '-?\[?([^'\]]+?)\]?' failed").unwrap();
+    // XCTest assertion: `/path/FooTests.swift:20: error: -[Mod.FooTests testBar] : message`
+    static ref XCTEST_ASSERT: Regex =
+        Regex::new(r"^(\S+?):(\d+): error: -?\[?([^\]]+?)\]? : (.*)$").unwrap();
+    // Swift Testing: `✘ Test testFoo() failed after ...`, `✘ Test "name" recorded an issue at F.swift:3:5: msg`
+    static ref SWIFT_TESTING_ISSUE: Regex =
+        Regex::new(r#"^✘ Test (.+?) recorded an issue at (\S+?): (.*)$"#).unwrap();
+    static ref SWIFT_TESTING_FAILED: Regex =
+        Regex::new(r#"^✘ Test (.+?) failed after"#).unwrap();
+    static ref STATUS: Regex = Regex::new(r"^\*\* (.+) \*\*$").unwrap();
+    static ref COMPILE_STEP: Regex =
+        Regex::new(r"^(?:CompileSwift|SwiftCompile|CompileC|CompileSwiftSources|\[\d+/\d+\] Compiling)\b").unwrap();
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Report {
+    errors: Vec<String>,
+    /// file -> `line:col: message`
+    warnings: BTreeMap<String, Vec<String>>,
+    /// test -> first failure message
+    failed_tests: Vec<(String, Option<String>)>,
+    /// `The following build commands failed:` entries
+    failed_commands: Vec<String>,
+    /// `** BUILD SUCCEEDED **`, `Build complete! (3.2s)`, ...
+    status: Option<String>,
+    /// `Executed 42 tests, with 1 failure ...`
+    test_summary: Option<String>,
+    compile_steps: usize,
+    /// `.xcresult` bundle announced in the log
+    result_bundle: Option<String>,
+}
+
+impl Report {
+    fn failed(&self) -> bool {
+        !self.errors.is_empty()
+            || !self.failed_tests.is_empty()
+            || self
+                .status
+                .as_deref()
+                .is_some_and(|s| s.contains("FAILED") || s.contains("failed"))
+    }
+
+    fn add_test_failure(&mut self, test: String, message: Option<String>) {
+        match self.failed_tests.iter_mut().find(|(t, _)| *t == test) {
+            Some((_, existing)) => {
+                if existing.is_none() {
+                    *existing = message;
+                }
+            }
+            None => self.failed_tests.push((test, message)),
+        }
+    }
+}
+
+/// `-[Mod.FooTests testBar]` / `Mod.FooTests testBar` -> `FooTests.testBar`
+fn test_name(raw: &str) -> String {
+    let raw = raw.trim_start_matches("-[").trim_end_matches(']');
+    let (class, method) = match raw.split_once(' ') {
+        Some((class, method)) => (class, method),
+        None => raw.rsplit_once('.').unwrap_or(("", raw)),
+    };
+    let class = class.rsplit('.').next().unwrap_or(class);
+    if class.is_empty() {
+        method.to_string()
+    } else {
+        format!("{}.{}", class, method)
+    }
+}
+
+fn relative(path: &str, root: &str) -> String {
+    let path = path.strip_prefix("file://").unwrap_or(path);
+    if root.is_empty() {
+        return path.to_string();
+    }
+    path.strip_prefix(root)
+        .map(|p| p.trim_start_matches('/').to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+fn parse(output: &str, root: &str) -> Report {
+    let mut report = Report::default();
+    let mut in_failed_commands = false;
+    let mut expect_bundle = false;
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if expect_bundle {
+            if trimmed.ends_with(".xcresult") {
+                report.result_bundle = Some(trimmed.to_string());
+            }
+            expect_bundle = false;
+        }
+        if in_failed_commands {
+            if trimmed.is_empty() || trimmed.starts_with('(') {
+                in_failed_commands = false;
+            } else {
+                let command = relative(trimmed, root);
+                if !report.failed_commands.contains(&command) {
+                    report.failed_commands.push(command);
+                }
+            }
+            continue;
+        }
+
+        if COMPILE_STEP.is_match(trimmed) {
+            report.compile_steps += 1;
+        } else if let Some(c) = XCTEST_ASSERT.captures(trimmed) {
+            report.add_test_failure(test_name(&c[3]), Some(c[4].trim().to_string()));
+        } else if let Some(c) = XCTEST_FAILED.captures(trimmed) {
+            report.add_test_failure(test_name(&c[1]), None);
+        } else if let Some(c) = SWIFT_TESTING_ISSUE.captures(trimmed) {
+            let message = format!("{}: {}", &c[2], c[3].trim());
+            report.add_test_failure(c[1].trim_matches('"').to_string(), Some(message));
+        } else if let Some(c) = SWIFT_TESTING_FAILED.captures(trimmed) {
+            report.add_test_failure(c[1].trim_matches('"').to_string(), None);
+        } else if let Some(c) = DIAGNOSTIC.captures(trimmed) {
+            let file = relative(&c[1], root);
+            let position = match c.get(3) {
+                Some(col) => format!("{}:{}", &c[2], col.as_str()),
+                None => c[2].to_string(),
+            };
+            let message = c[5].trim();
+            if c[4].ends_with("error") {
+                let entry = format!("{}:{}: {}", file, position, message);
+                if !report.errors.contains(&entry) {
+                    report.errors.push(entry);
+                }
+            } else {
+                let entry = format!("{}: {}", position, message);
+                let messages = report.warnings.entry(file).or_default();
+                // Each architecture reports the same warning again
+                if !messages.contains(&entry) {
+                    messages.push(entry);
+                }
+            }
+        } else if let Some(c) = TOOL_DIAGNOSTIC.captures(trimmed) {
+            let tool = c.get(1).map_or("", |t| t.as_str());
+            let message = if tool.is_empty() {
+                c[3].trim().to_string()
+            } else {
+                format!("{}: {}", tool, c[3].trim())
+            };
+            if &c[2] == "error" {
+                if !report.errors.contains(&message) {
+                    report.errors.push(message);
+                }
+            } else {
+                let messages = report.warnings.entry(String::from("(build)")).or_default();
+                if !messages.contains(&message) {
+                    messages.push(message);
+                }
+            }
+        } else if trimmed == "The following build commands failed:" {
+            in_failed_commands = true;
+        } else if let Some(c) = STATUS.captures(trimmed) {
+            report.status = Some(c[1].to_string());
+        } else if trimmed.starts_with("Build complete!")
+            || trimmed.starts_with("Compiling failed")
+            || (trimmed.starts_with("Build of ") && trimmed.contains("complete"))
+        {
+            report.status = Some(trimmed.to_string());
+        } else if trimmed.starts_with("Executed ") && trimmed.contains(" test") {
+            // XCTest prints one per suite; the last is the total
+            report.test_summary = Some(trimmed.to_string());
+        } else if trimmed.starts_with("Test session results, code coverage, and logs:") {
+            expect_bundle = true;
+        }
+    }
+    report
+}
+
+/// Failed test cases in `xcresulttool get test-results tests` JSON
+/// (Xcode 16+).
+fn parse_xcresult_tests(json: &Value, suite: &str, out: &mut Vec<(String, Option<String>)>) {
+    for node in json["testNodes"]
+        .as_array()
+        .or_else(|| json["children"].as_array())
+        .into_iter()
+        .flatten()
+    {
+        let name = node["name"].as_str().unwrap_or("");
+        match node["nodeType"].as_str() {
+            Some("Test Case") if node["result"] == "Failed" => {
+                let message = node["children"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .find(|c| c["nodeType"] == "Failure Message")
+                    .and_then(|c| c["name"].as_str())
+                    .map(str::to_string);
+                let test = if suite.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{}.{}", suite, name)
+                };
+                out.push((test, message));
+            }
+            Some("Test Suite") => parse_xcresult_tests(node, name, out),
+            _ => parse_xcresult_tests(node, suite, out),
+        }
+    }
+}
+
+fn xcresult_failures(bundle: &str) -> Option<Vec<(String, Option<String>)>> {
+    let output = Command::new("xcrun")
+        .args([
+            "xcresulttool",
+            "get",
+            "test-results",
+            "tests",
+            "--path",
+            bundle,
+        ])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let json: Value = serde_json::from_slice(&output.stdout).ok()?;
+    let mut failures = Vec::new();
+    parse_xcresult_tests(&json, "", &mut failures);
+    Some(failures)
+}
+
+fn format_report(tool: &str, report: &Report) -> String {
+    let mut out = Vec::new();
+    let status = report.status.clone().unwrap_or_else(|| {
+        if report.failed() {
+            "failed".to_string()
+        } else {
+            "done".to_string()
+        }
+    });
+    let mark = if report.failed() { "✗" } else { "✓" };
+    let steps = if report.compile_steps > 0 {
+        format!(" ({} compile steps)", report.compile_steps)
+    } else {
+        String::new()
+    };
+    out.push(format!("{} {}: {}{}", mark, tool, status, steps));
+
+    if !report.errors.is_empty() {
+        out.push(format!("Errors ({}):", report.errors.len()));
+        for error in &report.errors {
+            out.push(format!("  {}", truncate(error, 200)));
+        }
+    }
+    if !report.failed_tests.is_empty() {
+        out.push(format!("Failed tests ({}):", report.failed_tests.len()));
+        for (test, message) in report.failed_tests.iter().take(MAX_TESTS) {
+            match message {
+                Some(message) => out.push(format!("  {}: {}", test, truncate(message, 160))),
+                None => out.push(format!("  {}", test)),
+            }
+        }
+        if report.failed_tests.len() > MAX_TESTS {
+            out.push(format!(
+                "  ... +{} more",
+                report.failed_tests.len() - MAX_TESTS
+            ));
+        }
+    }
+    if let Some(summary) = &report.test_summary {
+        out.push(format!("Tests: {}", summary));
+    }
+    // Compile failures already show as errors; other failed steps (link,
+    // script phases, code signing) are the only clue otherwise
+    if !report.failed_commands.is_empty() && report.errors.is_empty() {
+        out.push("Failed commands:".to_string());
+        for command in &report.failed_commands {
+            out.push(format!("  {}", truncate(command, 160)));
+        }
+    }
+    if !report.warnings.is_empty() {
+        let total: usize = report.warnings.values().map(Vec::len).sum();
+        out.push(format!(
+            "Warnings ({} in {} files):",
+            total,
+            report.warnings.len()
+        ));
+        for (file, messages) in &report.warnings {
+            out.push(format!("  {} ({})", file, messages.len()));
+            for message in messages.iter().take(MAX_WARNINGS_PER_FILE) {
+                out.push(format!("    {}", truncate(message, 160)));
+            }
+            if messages.len() > MAX_WARNINGS_PER_FILE {
+                out.push(format!(
+                    "    ... +{} more",
+                    messages.len() - MAX_WARNINGS_PER_FILE
+                ));
+            }
+        }
+    }
+    out.join("\n")
+}
+
+fn run_filtered(program: &str, args: &[String], verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    let is_xcodebuild = program == "xcodebuild";
+    let mut args = args.to_vec();
+    // Write a result bundle for test runs so failures come from xcresult
+    let bundle_dir = tempfile::tempdir().ok();
+    let testing = args
+        .iter()
+        .any(|a| a == "test" || a == "test-without-building");
+    if is_xcodebuild && testing && !args.iter().any(|a| a == "-resultBundlePath") {
+        if let Some(dir) = &bundle_dir {
+            args.push("-resultBundlePath".to_string());
+            args.push(dir.path().join("rtk.xcresult").display().to_string());
+        }
+    }
+    if verbose > 0 {
+        eprintln!("Running: {} {}", program, args.join(" "));
+    }
+
+    let output = Command::new(program)
+        .args(&args)
+        .output()
+        .with_context(|| format!("Failed to run {}", program))?;
+    let raw = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    crate::crash::raw_output(&raw);
+    let root = std::env::current_dir()
+        .map(|d| d.display().to_string())
+        .unwrap_or_default();
+    let mut report = parse(&strip_ansi(&raw), &root);
+
+    let bundle = args
+        .iter()
+        .position(|a| a == "-resultBundlePath")
+        .and_then(|i| args.get(i + 1).cloned())
+        .or_else(|| report.result_bundle.clone());
+    if let Some(bundle) = bundle.filter(|b| Path::new(b).exists()) {
+        if let Some(failures) = xcresult_failures(&bundle) {
+            if verbose > 0 {
+                eprintln!("xcresult: {} failed tests in {}", failures.len(), bundle);
+            }
+            if !failures.is_empty() || report.failed_tests.is_empty() {
+                report.failed_tests = failures;
+            }
+        }
+    }
+
+    let label = if is_xcodebuild {
+        "xcodebuild".to_string()
+    } else {
+        format!("{} {}", program, args.first().map_or("", String::as_str))
+    };
+    let filtered = format_report(&label, &report);
+    println!("{}", filtered);
+
+    timer.with_exit_code(output.status).track(
+        &format!("{} {}", program, args.join(" ")),
+        &format!("rtk {} {}", program, args.join(" ")),
+        &raw,
+        &filtered,
+    );
+    if !output.status.success() {
+        exit_code::exit_with(output.status);
+    }
+    Ok(())
+}
+
+fn run_passthrough(program: &str, args: &[String], verbose: u8) -> Result<()> {
+    let timer = tracking::TimedExecution::start();
+    if verbose > 0 {
+        eprintln!("{} passthrough: {:?}", program, args);
+    }
+    let status = Command::new(program)
+        .args(args)
+        .status()
+        .with_context(|| format!("Failed to run {}", program))?;
+
+    timer.with_exit_code(status).track_passthrough(
+        &format!("{} {}", program, args.join(" ")),
+        &format!("rtk {} {} (passthrough)", program, args.join(" ")),
+    );
+    if !status.success() {
+        exit_code::exit_with(status);
+    }
+    Ok(())
+}
+
+pub fn run_xcodebuild(args: &[String], verbose: u8) -> Result<()> {
+    // Informational modes print small listings worth reading in full
+    const INFO: &[&str] = &[
+        "-list",
+        "-showBuildSettings",
+        "-showsdks",
+        "-showdestinations",
+        "-version",
+        "-help",
+    ];
+    if args.iter().any(|a| INFO.contains(&a.as_str())) {
+        run_passthrough("xcodebuild", args, verbose)
+    } else {
+        run_filtered("xcodebuild", args, verbose)
+    }
+}
+
+pub fn run_swift(args: &[String], verbose: u8) -> Result<()> {
+    match args.first().map(String::as_str) {
+        Some("build") | Some("test") => run_filtered("swift", args, verbose),
+        _ => run_passthrough("swift", args, verbose),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XCODEBUILD: &str = "\
+Command line invocation:
+    /Applications/Xcode.app/Contents/Developer/usr/bin/xcodebuild -scheme App build
+note: Using codesigning identity override: -
+CompileSwift normal arm64 /Users/me/App/Sources/Home.swift (in target 'App' from project 'App')
+    cd /Users/me/App
+    /Applications/Xcode.app/Contents/Developer/Toolchains/XcodeDefault.xctoolchain/usr/bin/swift-frontend -frontend -c ...
+/Users/me/App/Sources/Home.swift:12:5: error: cannot find 'titel' in scope
+        titel = \"x\"
+        ^~~~~
+CompileSwift normal x86_64 /Users/me/App/Sources/Home.swift (in target 'App' from project 'App')
+/Users/me/App/Sources/Home.swift:12:5: error: cannot find 'titel' in scope
+CompileSwift normal arm64 /Users/me/App/Sources/User.swift (in target 'App' from project 'App')
+/Users/me/App/Sources/User.swift:3:10: warning: 'name' is deprecated: use fullName
+/Users/me/App/Sources/User.swift:3:10: warning: 'name' is deprecated: use fullName
+/Users/me/App/Sources/User.swift:9:1: warning: variable 'x' was never used
+ld: warning: ignoring duplicate libraries: '-lc++'
+
+The following build commands failed:
+\tCompileSwift normal arm64 /Users/me/App/Sources/Home.swift (in target 'App' from project 'App')
+(1 failure)
+** BUILD FAILED **
+";
+
+    #[test]
+    fn test_xcodebuild_build_failure() {
+        let report = parse(XCODEBUILD, "/Users/me/App");
+        assert_eq!(
+            format_report("xcodebuild", &report),
+            "✗ xcodebuild: BUILD FAILED (3 compile steps)
+Errors (1):
+  Sources/Home.swift:12:5: cannot find 'titel' in scope
+Warnings (3 in 2 files):
+  (build) (1)
+    ld: ignoring duplicate libraries: '-lc++'
+  Sources/User.swift (2)
+    3:10: 'name' is deprecated: use fullName
+    9:1: variable 'x' was never used"
+        );
+        assert_eq!(report.failed_commands.len(), 1);
+    }
+
+    #[test]
+    fn test_xctest_failures() {
+        let log = "\
+Test Suite 'All tests' started at 2026-01-15 10:00:00.000.
+Test Case '-[AppTests.ParserTests testEmpty]' started.
+/Users/me/App/Tests/ParserTests.swift:20: error: -[AppTests.ParserTests testEmpty] : XCTAssertEqual failed: (\"1\") is not equal to (\"2\")
+Test Case '-[AppTests.ParserTests testEmpty]' failed (0.003 seconds).
+Test Case '-[AppTests.ParserTests testFull]' passed (0.001 seconds).
+Test Case 'NetTests.testTimeout' failed (1.002 seconds).
+\t Executed 3 tests, with 2 failures (0 unexpected) in 1.006 (1.010) seconds
+Test session results, code coverage, and logs:
+\t/tmp/Test-App.xcresult
+** TEST FAILED **
+";
+        let report = parse(log, "/Users/me/App");
+        assert_eq!(
+            report.failed_tests,
+            vec![
+                (
+                    "ParserTests.testEmpty".to_string(),
+                    Some("XCTAssertEqual failed: (\"1\") is not equal to (\"2\")".to_string())
+                ),
+                ("NetTests.testTimeout".to_string(), None),
+            ]
+        );
+        assert_eq!(
+            report.result_bundle.as_deref(),
+            Some("/tmp/Test-App.xcresult")
+        );
+        assert!(report.errors.is_empty());
+        assert!(format_report("xcodebuild", &report).starts_with(
+            "✗ xcodebuild: TEST FAILED\nFailed tests (2):\n  ParserTests.testEmpty: XCTAssertEqual"
+        ));
+    }
+
+    #[test]
+    fn test_swift_build_and_testing() {
+        let log = "\
+Building for debugging...
+[1/4] Compiling Core Parser.swift
+[2/4] Compiling Core Lexer.swift
+✘ Test \"parses empty input\" recorded an issue at ParserTests.swift:8:5: Expectation failed: (tokens.count → 1) == 0
+✘ Test \"parses empty input\" failed after 0.002 seconds with 1 issue.
+Build complete! (3.21s)
+";
+        let report = parse(log, "");
+        assert_eq!(report.compile_steps, 2);
+        assert_eq!(
+            report.failed_tests,
+            vec![(
+                "parses empty input".to_string(),
+                Some(
+                    "ParserTests.swift:8:5: Expectation failed: (tokens.count → 1) == 0"
+                        .to_string()
+                )
+            )]
+        );
+        assert!(format_report("swift test", &report)
+            .starts_with("✗ swift test: Build complete! (3.21s) (2 compile steps)"));
+    }
+
+    #[test]
+    fn test_parse_xcresult_tests() {
+        let json: Value = serde_json::from_str(
+            r#"{"testNodes": [{"nodeType": "Test Plan", "name": "App", "children": [
+                {"nodeType": "Unit test bundle", "name": "AppTests", "children": [
+                    {"nodeType": "Test Suite", "name": "ParserTests", "children": [
+                        {"nodeType": "Test Case", "name": "testEmpty()", "result": "Failed", "children": [
+                            {"nodeType": "Failure Message", "name": "ParserTests.swift:20: XCTAssertEqual failed"}
+                        ]},
+                        {"nodeType": "Test Case", "name": "testFull()", "result": "Passed"}
+                    ]}
+                ]}
+            ]}]}"#,
+        )
+        .unwrap();
+        let mut failures = Vec::new();
+        parse_xcresult_tests(&json, "", &mut failures);
+        assert_eq!(
+            failures,
+            vec![(
+                "ParserTests.testEmpty()".to_string(),
+                Some("ParserTests.swift:20: XCTAssertEqual failed".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn test_test_name() {
+        assert_eq!(test_name("-[Mod.FooTests testBar]"), "FooTests.testBar");
+        assert_eq!(test_name("Mod.FooTests.testBar"), "FooTests.testBar");
+    }
+}